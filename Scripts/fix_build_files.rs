@@ -1,3 +1,4 @@
+use std::env;
 use std::fs;
 use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
@@ -5,11 +6,15 @@ use std::process::Command;
 use regex::{Regex, Captures};
 
 fn main() -> io::Result<()> {
-    // Get the root directory of the UmbraCore project
-    let project_root = Path::new("/Users/mpy/CascadeProjects/UmbraCore");
+    // Get the root directory of the UmbraCore project: the first argument, or
+    // the current directory
+    let project_root = match env::args_os().nth(1) {
+        Some(root) => PathBuf::from(root),
+        None => env::current_dir()?,
+    };
     
     // Find all BUILD.bazel files
-    let build_files = find_build_files(project_root)?;
+    let build_files = find_build_files(&project_root)?;
     println!("Found {} BUILD.bazel files", build_files.len());
     
     // Process each BUILD.bazel file
@@ -0,0 +1,92 @@
+//! Throughput of analyzing and fixing a repository-sized batch of BUILD files.
+//!
+//! Run with `cargo bench --bench fix_files`.
+
+use std::fs;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use umbra_build_fixer::checks::apply_fixes;
+use umbra_build_fixer::parallel_io::{read_files, DEFAULT_MAX_OPEN_FILES};
+use umbra_build_fixer::{analyze_build_file, find_build_files, fix_build_file, Config, RunMode};
+
+const FILE_COUNT: usize = 1000;
+
+const DIRTY: &str = include_str!("../tests/fixtures/dirty.BUILD");
+const CLEAN: &str = include_str!("../tests/fixtures/clean.BUILD");
+
+// Half the files need fixing, half are already clean
+fn sample_content(index: usize) -> &'static str {
+    if index.is_multiple_of(2) {
+        DIRTY
+    } else {
+        CLEAN
+    }
+}
+
+fn analyze_and_fix_in_memory(c: &mut Criterion) {
+    let config = Config::default();
+    c.bench_function("analyze_and_fix_1000_files", |b| {
+        b.iter(|| {
+            for index in 0..FILE_COUNT {
+                let content = sample_content(index);
+                let findings = analyze_build_file(content, &config);
+                std::hint::black_box(apply_fixes(content, &findings));
+            }
+        })
+    });
+}
+
+fn sample_workspace() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for index in 0..FILE_COUNT {
+        let package_dir = dir.path().join(format!("Sources/Module{}", index));
+        fs::create_dir_all(&package_dir).unwrap();
+        fs::write(package_dir.join("BUILD.bazel"), sample_content(index)).unwrap();
+    }
+    dir
+}
+
+fn fix_workspace_dry_run(c: &mut Criterion) {
+    let dir = sample_workspace();
+    let mut config = Config::new(dir.path());
+    config.mode = RunMode::DryRun;
+
+    c.bench_function("dry_run_1000_files", |b| {
+        b.iter_batched(
+            || find_build_files(&config).unwrap(),
+            |files| {
+                for file in files {
+                    std::hint::black_box(fix_build_file(&file, &config).unwrap());
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+// Sequential reads against --parallel-io reads of the same files
+fn read_workspace(c: &mut Criterion) {
+    let dir = sample_workspace();
+    let files = find_build_files(&Config::new(dir.path())).unwrap();
+
+    let mut group = c.benchmark_group("read_1000_files");
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            for file in &files {
+                std::hint::black_box(fs::read_to_string(file).unwrap());
+            }
+        })
+    });
+    group.bench_function("parallel_io", |b| {
+        b.iter(|| std::hint::black_box(read_files(&files, DEFAULT_MAX_OPEN_FILES).unwrap()))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    analyze_and_fix_in_memory,
+    fix_workspace_dry_run,
+    read_workspace
+);
+criterion_main!(benches);
@@ -1,201 +1,1405 @@
-use std::fs;
-use std::io::{self, Read, Write};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, IsTerminal, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use regex::{Regex, Captures};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
 
-fn main() -> io::Result<()> {
-    // Get the root directory of the UmbraCore project
-    let project_root = Path::new("/Users/mpy/CascadeProjects/UmbraCore");
-    
+use clap::{Parser, Subcommand};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use termcolor::{ColorChoice, StandardStream};
+use umbra_build_fixer::atomic_write::atomic_write;
+use umbra_build_fixer::baseline::Baseline;
+use umbra_build_fixer::bazel_query::{run_bazel_query, DEFAULT_TIMEOUT_SECS};
+use umbra_build_fixer::build_cleaner::{clean_backups, format_bytes};
+use umbra_build_fixer::cache::{Cache, CACHE_FILE_NAME};
+use umbra_build_fixer::checks::analyze_target_graph;
+use umbra_build_fixer::checks::comments::remove_comment_block;
+use umbra_build_fixer::checks::loads::default_rule_migrations;
+use umbra_build_fixer::checks::spm::PACKAGE_MANIFEST;
+use umbra_build_fixer::checks::version_catalog::VersionCatalog;
+use umbra_build_fixer::config::schema::config_schema;
+use umbra_build_fixer::config::IMPORT_MAP_FILE_NAME;
+use umbra_build_fixer::dependency_updater::{read_manifest, update_deps, DEPS_UPDATE_FILE_NAME};
+use umbra_build_fixer::generate::generate_build_file;
+use umbra_build_fixer::hook::{install_hook, uninstall_hook};
+use umbra_build_fixer::label::Label;
+use umbra_build_fixer::lockfile_checker::{
+    check_lockfile, update_lockfile, LockStatus, LOCK_FILE_NAME, MODULE_FILE_NAME,
+};
+use umbra_build_fixer::lsp;
+use umbra_build_fixer::metrics::{self, PhaseTimer};
+use umbra_build_fixer::migrations::rules_swift::VersionUpgrade;
+use umbra_build_fixer::module_mapper::{
+    format_import_map, generate_import_map, read_import_map, ImportMap,
+};
+use umbra_build_fixer::parallel_io::read_files;
+use umbra_build_fixer::patch::{apply_patch, write_colored_diff};
+#[cfg(feature = "profile")]
+use umbra_build_fixer::profiler::Profiler;
+use umbra_build_fixer::progress_state::{
+    remove_state, ProgressLog, ProgressState, PROGRESS_FILE_NAME,
+};
+use umbra_build_fixer::report::github_actions::{is_github_actions, write_annotations};
+use umbra_build_fixer::report::html::write_html;
+use umbra_build_fixer::report::junit::write_junit_report;
+use umbra_build_fixer::starlark::ast::ParseError;
+use umbra_build_fixer::swift_package_converter::convert_manifest;
+use umbra_build_fixer::target_graph::build_target_graph;
+use umbra_build_fixer::undo::{find_backups, parse_duration, restore_backup};
+use umbra_build_fixer::workspace::find_workspace_root;
+use umbra_build_fixer::{
+    find_build_files, find_workspace_build_files, fix_build_file, fix_build_file_content,
+    fix_build_file_with_cache, BuildIssue, ColorMode, Config, IssueReport, OutputFormat,
+    ProgressMode, RemainingIssues, RunMode, RunReport,
+};
+
+/// Detects and fixes common problems in UmbraCore BUILD.bazel files.
+#[derive(Parser)]
+#[command(name = "umbra-fix")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Directory to scan for BUILD.bazel files (defaults to the workspace containing the current directory, or else the current directory)
+    #[arg(long)]
+    root: Option<PathBuf>,
+
+    /// Report what would be fixed without modifying any files
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Exit with a non-zero code if any fixable issues are found; never modifies files
+    #[arg(long, conflicts_with = "dry_run")]
+    check: bool,
+
+    /// Print a unified diff of the fixes without modifying any files; exit 1 if there is anything to fix
+    #[arg(long, conflicts_with_all = ["dry_run", "check", "resume", "save_baseline"])]
+    diff_only: bool,
+
+    /// Fix what can be fixed (only report with --dry-run), then exit 1 if any issues remain, listing them as JSON on stderr
+    #[arg(long, conflicts_with_all = ["check", "diff_only"])]
+    assert_no_issues: bool,
+
+    /// Unchanged lines shown around each change in --diff-only and patch output (defaults to 3)
+    #[arg(long, value_name = "N")]
+    diff_context: Option<usize>,
+
+    /// Print the diff of each fix as it is applied, with 2 lines of context, to debug unexpected results
+    #[arg(long)]
+    verbose_diffs: bool,
+
+    /// Color diffs printed to the console: auto (when stdout is a terminal), always or never
+    #[arg(long, value_enum, value_name = "WHEN")]
+    color: Option<ColorMode>,
+
+    /// Show a progress bar of the files processed: auto (when stdout is a terminal), always or never
+    #[arg(long, value_enum, value_name = "WHEN")]
+    progress: Option<ProgressMode>,
+
+    /// Fix files in place (text), or write the fixes as a unified diff (patch), HTML report (html) or JUnit XML (junit)
+    #[arg(long, value_enum)]
+    output: Option<OutputFormat>,
+
+    /// File that --output patch appends to
+    #[arg(long, value_name = "FILE", required_if_eq("output", "patch"))]
+    patch_file: Option<PathBuf>,
+
+    /// File that --output html writes to
+    #[arg(long, value_name = "FILE", required_if_eq("output", "html"))]
+    html_file: Option<PathBuf>,
+
+    /// File that --output junit writes to
+    #[arg(long, value_name = "FILE", required_if_eq("output", "junit"))]
+    junit_file: Option<PathBuf>,
+
+    /// Copy each file to <file>.bak before fixing it, so `umbra-fix undo` can restore it
+    #[arg(long)]
+    backup: bool,
+
+    /// Analyze every file even if it is unchanged since the last run, and don't update the cache
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Discard the cached analysis results and rebuild the cache
+    #[arg(long, conflicts_with = "no_cache")]
+    invalidate_cache: bool,
+
+    /// Continue the interrupted run recorded in .umbra-fix-progress.json (fails if there is none)
+    #[arg(long, conflicts_with_all = ["dry_run", "check"])]
+    resume: bool,
+
+    /// Delete the progress of an interrupted run and process every file again
+    #[arg(long, conflicts_with = "resume")]
+    reset: bool,
+
+    /// Neither report nor fix the issues recorded in this baseline file; --check fails only for new issues
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// Record every issue found in the --baseline file instead of fixing anything
+    #[arg(long, requires = "baseline", conflicts_with = "check")]
+    save_baseline: bool,
+
+    /// Re-analyze each fixed file and fail if a fix introduced new issues
+    #[arg(long)]
+    verify_idempotent: bool,
+
+    /// Read all BUILD files concurrently with async I/O before processing them (helps on network filesystems)
+    #[arg(long)]
+    parallel_io: bool,
+
+    /// With --parallel-io, how many files may be open at once (defaults to 64)
+    #[arg(long, value_name = "N", requires = "parallel_io")]
+    max_open_files: Option<usize>,
+
+    /// Migrate BUILD files to a newer rules_swift major version API, e.g. 1->2 (implies --backup, so `umbra-fix undo` can roll it back)
+    #[arg(long, value_name = "VERSION")]
+    upgrade_rules_swift_version: Option<VersionUpgrade>,
+
+    /// Only run the checks of these rule types, comma-separated (e.g. swift_library,swift_test), or `all` [default: swift_*]
+    #[arg(long, value_name = "RULES", value_delimiter = ',')]
+    rules: Vec<String>,
+
+    /// Report globs that set `allow_empty = True` although they match files, and remove it
+    #[arg(long)]
+    warn_redundant_allow_empty: bool,
+
+    /// Report lines longer than N characters; single-line deps and srcs lists are split one element per line
+    #[arg(long, value_name = "N")]
+    max_line_length: Option<usize>,
+
+    /// Reorder rule attributes into canonical order: name, module_name, srcs, hdrs, deps, data, ..., visibility
+    #[arg(long)]
+    sort_attributes: bool,
+
+    /// Reformat files into canonical layout after applying the other fixes
+    #[arg(long)]
+    format: bool,
+
+    /// Only reformat files; skip every other check
+    #[arg(long, conflicts_with = "format")]
+    format_only: bool,
+
+    /// Remove deps whose module none of the target's Swift sources import (uses import_map.toml)
+    #[arg(long)]
+    prune_deps: bool,
+
+    /// With --prune-deps, also remove unused //third_party/apple system framework deps
+    #[arg(long, requires = "prune_deps")]
+    prune_system_deps: bool,
+
+    /// Migrate load() labels of moved rules_swift/rules_apple files (implied by rule_migrations.toml)
+    #[arg(long)]
+    migrate_rule_loads: bool,
+
+    /// Download http_archive URLs in WORKSPACE files to fill in missing sha256 values (only when fixing)
+    #[arg(long, conflicts_with_all = ["dry_run", "check", "diff_only"])]
+    network: bool,
+
+    /// Download http_archive URLs in WORKSPACE files and report sha256 values that don't match
+    #[arg(long)]
+    check_sha256: bool,
+
+    /// Replace the sha256 values --check-sha256 finds wrong (implies --check-sha256)
+    #[arg(long)]
+    fix_sha256: bool,
+
+    /// Don't report sha256 values --check-sha256 can't verify because the archive can't be downloaded
+    #[arg(long)]
+    allow_unverifiable_sha256: bool,
+
+    /// Version catalog that http_archive versions in WORKSPACE must match (defaults to third_party/versions.bzl)
+    #[arg(long, value_name = "PATH")]
+    versions_file: Option<PathBuf>,
+
+    /// Seconds each download may take (defaults to 60)
+    #[arg(long, value_name = "SECS")]
+    network_timeout: Option<u64>,
+
+    /// After fixing, run `bazel query` (or $UMBRA_FIX_BAZEL), report the errors Bazel finds and check MODULE.bazel.lock
+    #[arg(long)]
+    bazel_validate: bool,
+
+    /// Query expression --bazel-validate runs (defaults to //...)
+    #[arg(long, value_name = "EXPR", requires = "bazel_validate")]
+    bazel_query: Option<String>,
+
+    /// Seconds --bazel-validate waits for bazel before giving up (defaults to 60)
+    #[arg(long, value_name = "SECS", requires = "bazel_validate")]
+    bazel_timeout: Option<u64>,
+
+    /// Serve diagnostics and quick fixes to editors over the Language Server Protocol on stdin/stdout
+    #[arg(long)]
+    lsp: bool,
+
+    /// Print every target of the discovered BUILD files (label, rule, file, name, visibility) as JSON and exit
+    #[arg(long)]
+    emit_targets: bool,
+
+    /// With --emit-targets, only print targets of these rule types, comma-separated
+    #[arg(
+        long,
+        value_name = "RULE_TYPE",
+        value_delimiter = ',',
+        requires = "emit_targets"
+    )]
+    emit_targets_filter: Vec<String>,
+
+    /// Delete the block of comments starting on LINE of FILE (as reported by CommentedOutRule) and exit
+    #[arg(long, num_args = 2, value_names = ["LINE", "FILE"])]
+    remove_comment_block: Vec<String>,
+
+    /// Apply FILE, a patch written by --output patch, to the files under --root and exit
+    #[arg(long, value_name = "FILE")]
+    apply_patch: Option<PathBuf>,
+
+    /// Print the JSON Schema of umbra-fix.toml and exit
+    #[arg(long)]
+    print_schema: bool,
+
+    /// Print how long each phase took (also enabled by UMBRA_FIX_METRICS=1)
+    #[arg(long)]
+    metrics: bool,
+
+    /// Write a CPU profile of the run to this file as a pprof protobuf (view it with go tool pprof)
+    #[cfg(feature = "profile")]
+    #[arg(long, value_name = "PATH")]
+    profile: Option<PathBuf>,
+
+    /// Also write the full analysis as JSON to this file
+    #[arg(long, value_name = "PATH")]
+    report_file: Option<PathBuf>,
+
+    /// Only process the BUILD file defining this label (//pkg:target, //pkg or :target)
+    #[arg(long, value_name = "LABEL")]
+    target: Option<String>,
+
+    /// Only process BUILD files that differ from REF (committed, staged or unstaged)
+    #[arg(long, value_name = "REF")]
+    git_changed_only: Option<String>,
+
+    /// Only process BUILD files changed in the commits since TAG (git diff TAG...HEAD)
+    #[arg(long, value_name = "TAG", conflicts_with = "git_changed_only")]
+    since_tag: Option<String>,
+
+    /// Limit how many directory levels below the root are searched (1 = root only)
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Only process BUILD files matching this glob, relative to the root (repeatable)
+    #[arg(long = "include", alias = "include-pattern", value_name = "PATTERN")]
+    include_patterns: Vec<String>,
+
+    /// Skip BUILD files matching this glob, relative to the root (repeatable)
+    #[arg(long = "exclude", alias = "exclude-pattern", value_name = "PATTERN")]
+    exclude_patterns: Vec<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Write a skeleton BUILD.bazel for a directory that doesn't have one
+    Generate {
+        /// Module directory to generate the BUILD.bazel for
+        #[arg(long)]
+        dir: PathBuf,
+
+        /// Starlark template with {{name}} and {{glob}} placeholders
+        #[arg(long)]
+        template: Option<PathBuf>,
+    },
+
+    /// Convert a Package.swift to a BUILD.bazel with a rule for each target
+    ConvertSpm {
+        /// SPM manifest to convert
+        #[arg(long, value_name = "FILE", default_value = PACKAGE_MANIFEST)]
+        manifest: PathBuf,
+
+        /// BUILD file to write (defaults to BUILD.bazel next to the manifest)
+        #[arg(long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Add a git pre-commit hook that runs --check on changed BUILD files
+    InstallHook {
+        /// Repository to install the hook in (defaults to the current directory)
+        #[arg(long)]
+        repo: Option<PathBuf>,
+    },
+
+    /// Remove the pre-commit hook block added by install-hook
+    UninstallHook {
+        /// Repository to remove the hook from (defaults to the current directory)
+        #[arg(long)]
+        repo: Option<PathBuf>,
+    },
+
+    /// Rename labels in the deps, data and load() statements of every BUILD file, as listed in a manifest
+    UpdateDeps {
+        /// TOML file of `renames = [{ from = "//old:label", to = "//new:label" }]` (defaults to deps_update.toml in the root)
+        #[arg(long, value_name = "FILE")]
+        manifest: Option<PathBuf>,
+
+        /// Directory to search for BUILD files (defaults to the workspace containing the current directory)
+        #[arg(long)]
+        root: Option<PathBuf>,
+
+        /// List the labels that would be replaced without touching the files
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check that MODULE.bazel.lock is up to date, using `bazel mod deps` (or $UMBRA_FIX_BAZEL)
+    CheckLock {
+        /// Workspace to check (defaults to the workspace containing the current directory)
+        #[arg(long)]
+        root: Option<PathBuf>,
+
+        /// Rewrite the lock file if it is out of date
+        #[arg(long)]
+        update_lock: bool,
+
+        /// Seconds bazel may run before giving up (defaults to 60)
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+    },
+
+    /// Write import_map.toml from the module names of the workspace's swift_library targets
+    GenImportMap {
+        /// File to write (defaults to import_map.toml in the root)
+        #[arg(long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Import map whose entries, such as third-party modules, are kept in the generated one
+        #[arg(long, value_name = "FILE")]
+        seed_map: Option<PathBuf>,
+
+        /// Workspace to map (defaults to the workspace containing the current directory)
+        #[arg(long)]
+        root: Option<PathBuf>,
+    },
+
+    /// Delete the .bak copies kept by --backup once they are old enough
+    Clean {
+        /// Directory to search for backups (defaults to the workspace containing the current directory)
+        #[arg(long)]
+        root: Option<PathBuf>,
+
+        /// Only delete backups at least this old (e.g. 30m, 24h, 7d)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        older_than: Duration,
+
+        /// List the backups that would be deleted without touching them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Restore the files backed up by --backup from their .bak copies
+    Undo {
+        /// Directory to search for backups (defaults to the current directory)
+        #[arg(long)]
+        root: Option<PathBuf>,
+
+        /// List the files that would be restored without touching them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Only restore backups at least this old (e.g. 30m, 2h, 7d)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        older_than: Option<Duration>,
+    },
+}
+
+impl Cli {
+    fn into_config(self) -> io::Result<Config> {
+        let root_dir = match self.root {
+            Some(root) => root,
+            None => {
+                let cwd = env::current_dir()?;
+                find_workspace_root(&cwd).unwrap_or(cwd)
+            }
+        };
+
+        let mut config = Config::load(root_dir)?;
+        config.mode = if self.diff_only {
+            RunMode::Diff
+        } else if self.check {
+            RunMode::Check
+        } else if self.dry_run {
+            RunMode::DryRun
+        } else {
+            RunMode::Fix
+        };
+        if let Some(output) = self.output {
+            config.output = output;
+        }
+        config.patch_file = self.patch_file;
+        config.html_file = self.html_file;
+        config.junit_file = self.junit_file;
+        if let Some(context) = self.diff_context {
+            config.diff_context = context;
+        }
+        config.verbose_diffs = self.verbose_diffs;
+        if let Some(color) = self.color {
+            config.color = color;
+        }
+        if let Some(progress) = self.progress {
+            config.progress = progress;
+        }
+        // The bar would be hidden anyway; say so instead of ignoring the flag
+        if config.progress == ProgressMode::Always
+            && (matches!(config.mode, RunMode::Check | RunMode::Diff)
+                || config.output != OutputFormat::Text)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--progress always can't be combined with --check, --diff-only or --output reports",
+            ));
+        }
+        config.rules_swift_upgrade = self.upgrade_rules_swift_version;
+        config.backup |= self.backup || config.rules_swift_upgrade.is_some();
+        config.cache_file = (!self.no_cache).then(|| config.root_dir.join(CACHE_FILE_NAME));
+        config.invalidate_cache = self.invalidate_cache;
+        config.progress_file = Some(config.root_dir.join(PROGRESS_FILE_NAME));
+        config.resume = self.resume;
+        config.reset_progress = self.reset;
+        if self.save_baseline {
+            config.mode = RunMode::DryRun;
+            config.save_baseline = self.baseline;
+        } else if let Some(baseline) = &self.baseline {
+            config.baseline = Some(Baseline::load(baseline)?);
+        }
+        config.verify_idempotent = self.verify_idempotent;
+        config.parallel_io = self.parallel_io;
+        if let Some(max_open_files) = self.max_open_files {
+            config.max_open_files = max_open_files;
+        }
+        if let Some(target) = &self.target {
+            // `:target` refers to the package of the current directory
+            let cwd = env::current_dir()?;
+            let root = fs::canonicalize(&config.root_dir)?;
+            let current_package = cwd
+                .strip_prefix(&root)
+                .unwrap_or(Path::new(""))
+                .to_string_lossy()
+                .replace('\\', "/");
+            config.target = Some(Label::parse(target, &current_package)?);
+        }
+        config.git_changed_only = self.git_changed_only;
+        config.since_tag = self.since_tag;
+        config.report_file = self.report_file;
+        config.github_annotations = is_github_actions();
+        if self.rules.iter().any(|rule| rule == "all") {
+            config.rule_filter.clear();
+        } else if !self.rules.is_empty() {
+            config.rule_filter = self.rules;
+        }
+        config.warn_redundant_allow_empty |= self.warn_redundant_allow_empty;
+        if self.max_line_length.is_some() {
+            config.max_line_length = self.max_line_length;
+        }
+        config.sort_attributes |= self.sort_attributes;
+        config.format |= self.format;
+        config.format_only = self.format_only;
+        config.prune_deps = self.prune_deps;
+        config.prune_system_deps = self.prune_system_deps;
+        config.network = self.network;
+        config.check_sha256 = self.check_sha256 || self.fix_sha256;
+        config.fix_sha256 = self.fix_sha256;
+        config.allow_unverifiable_sha256 = self.allow_unverifiable_sha256;
+        if let Some(versions_file) = &self.versions_file {
+            config.version_catalog = VersionCatalog::load(versions_file)?;
+        }
+        if let Some(timeout) = self.network_timeout {
+            config.network_timeout_secs = timeout;
+        }
+        config.bazel_validate = self.bazel_validate;
+        if let Some(bazel) = env::var_os("UMBRA_FIX_BAZEL") {
+            config.bazel = PathBuf::from(bazel);
+        }
+        if let Some(query) = self.bazel_query {
+            config.bazel_query = query;
+        }
+        if let Some(timeout) = self.bazel_timeout {
+            config.bazel_timeout_secs = timeout;
+        }
+        if self.migrate_rule_loads && config.rule_migrations.is_empty() {
+            config.rule_migrations = default_rule_migrations();
+        }
+        if self.max_depth.is_some() {
+            config.max_depth = self.max_depth;
+        }
+        config.include_patterns.extend(self.include_patterns);
+        config.exclude_patterns.extend(self.exclude_patterns);
+
+        Ok(config)
+    }
+}
+
+fn main() -> ExitCode {
+    let started = Instant::now();
+    let mut cli = Cli::parse();
+    let show_metrics = cli.metrics || env::var("UMBRA_FIX_METRICS").is_ok_and(|value| value == "1");
+    let assert_clean = cli.assert_no_issues;
+    #[cfg(feature = "profile")]
+    let profile = cli.profile.take();
+
+    if cli.print_schema {
+        println!("{:#}", config_schema());
+        return ExitCode::SUCCESS;
+    }
+    if let Some(patch_file) = &cli.apply_patch {
+        return match apply_patch_file(patch_file, cli.root.as_deref()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                ExitCode::from(2)
+            }
+        };
+    }
+    if let [line, file] = cli.remove_comment_block.as_slice() {
+        return match remove_comment_block_at(line, Path::new(file)) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                ExitCode::from(2)
+            }
+        };
+    }
+
+    let result = match cli.command.take() {
+        Some(command) => run_command(command).map(|()| ExitCode::SUCCESS),
+        None if cli.lsp => cli
+            .into_config()
+            .and_then(|config| lsp::serve(&config, io::stdin().lock(), io::stdout().lock()))
+            .map(|()| ExitCode::SUCCESS),
+        None if cli.emit_targets => {
+            let rule_types = std::mem::take(&mut cli.emit_targets_filter);
+            cli.into_config()
+                .and_then(|config| emit_targets(&config, &rule_types))
+                .map(|()| ExitCode::SUCCESS)
+        }
+        None => cli.into_config().and_then(|config| {
+            #[cfg(feature = "profile")]
+            let reports = run_with_profile(&config, profile.as_deref())?;
+            #[cfg(not(feature = "profile"))]
+            let reports = run(&config)?;
+            if assert_clean {
+                return assert_no_issues(&config, &reports);
+            }
+            Ok(exit_code(&config, &reports))
+        }),
+    };
+
+    if show_metrics {
+        print!("{}", metrics::snapshot().summary(started.elapsed()));
+    }
+
+    result.unwrap_or_else(|err| {
+        eprintln!("error: {}", err);
+        ExitCode::from(2)
+    })
+}
+
+fn run_command(command: Command) -> io::Result<()> {
+    match command {
+        Command::Generate { dir, template } => {
+            let build_file = dir.join("BUILD.bazel");
+            if build_file.exists() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", build_file.display()),
+                ));
+            }
+
+            let content = generate_build_file(&dir, template.as_deref())?;
+            atomic_write(&build_file, content.as_bytes())?;
+            println!("Generated {}", build_file.display());
+            Ok(())
+        }
+        Command::ConvertSpm { manifest, output } => {
+            let package_dir = match manifest.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+                _ => env::current_dir()?,
+            };
+            let build_file = output.unwrap_or_else(|| package_dir.join("BUILD.bazel"));
+            if build_file.exists() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", build_file.display()),
+                ));
+            }
+
+            // The label map is read from the workspace the package is in
+            let root = find_workspace_root(&package_dir).unwrap_or(package_dir);
+            let config = Config::load(root)?;
+            let content = convert_manifest(&manifest, &config.spm_labels)?;
+            atomic_write(&build_file, content.as_bytes())?;
+            println!("Generated {}", build_file.display());
+            Ok(())
+        }
+        Command::InstallHook { repo } => {
+            let repo = match repo {
+                Some(repo) => repo,
+                None => env::current_dir()?,
+            };
+            let hook = install_hook(&repo, &env::current_exe()?)?;
+            println!("Installed pre-commit hook: {}", hook.display());
+            Ok(())
+        }
+        Command::UninstallHook { repo } => {
+            let repo = match repo {
+                Some(repo) => repo,
+                None => env::current_dir()?,
+            };
+            if uninstall_hook(&repo)? {
+                println!("Removed umbra-fix from the pre-commit hook");
+            } else {
+                println!("No umbra-fix pre-commit hook found");
+            }
+            Ok(())
+        }
+        Command::UpdateDeps {
+            manifest,
+            root,
+            dry_run,
+        } => {
+            let root = match root {
+                Some(root) => root,
+                None => {
+                    let cwd = env::current_dir()?;
+                    find_workspace_root(&cwd).unwrap_or(cwd)
+                }
+            };
+            let manifest =
+                read_manifest(&manifest.unwrap_or_else(|| root.join(DEPS_UPDATE_FILE_NAME)))?;
+            let replacements = update_deps(&Config::load(&root)?, &manifest.renames, dry_run)?;
+            for replacement in &replacements {
+                println!(
+                    "{}:{}: {} -> {}",
+                    replacement
+                        .path
+                        .strip_prefix(&root)
+                        .unwrap_or(&replacement.path)
+                        .display(),
+                    replacement.line,
+                    replacement.from,
+                    replacement.to
+                );
+            }
+
+            let verb = if dry_run { "Would replace" } else { "Replaced" };
+            println!("{} {} labels", verb, replacements.len());
+            Ok(())
+        }
+        Command::CheckLock {
+            root,
+            update_lock,
+            timeout,
+        } => {
+            let root = match root {
+                Some(root) => root,
+                None => {
+                    let cwd = env::current_dir()?;
+                    find_workspace_root(&cwd).unwrap_or(cwd)
+                }
+            };
+            let bazel = env::var_os("UMBRA_FIX_BAZEL")
+                .map_or_else(|| PathBuf::from("bazel"), PathBuf::from);
+            let timeout = Duration::from_secs(timeout.unwrap_or(DEFAULT_TIMEOUT_SECS));
+
+            match check_lockfile(&bazel, &root, timeout)? {
+                LockStatus::NoModule => {
+                    println!(
+                        "No {} in {}, nothing to check",
+                        MODULE_FILE_NAME,
+                        root.display()
+                    );
+                    Ok(())
+                }
+                LockStatus::UpToDate => {
+                    println!("{} is up to date", LOCK_FILE_NAME);
+                    Ok(())
+                }
+                LockStatus::Stale { errors } => {
+                    for error in &errors {
+                        println!("bazel: {}", error);
+                    }
+                    if update_lock {
+                        update_lockfile(&bazel, &root, timeout)?;
+                        println!("Updated {}", LOCK_FILE_NAME);
+                        Ok(())
+                    } else {
+                        Err(io::Error::other(format!(
+                            "{} is out of date (run `umbra-fix check-lock --update-lock`)",
+                            LOCK_FILE_NAME
+                        )))
+                    }
+                }
+            }
+        }
+        Command::GenImportMap {
+            output,
+            seed_map,
+            root,
+        } => {
+            let root = match root {
+                Some(root) => root,
+                None => {
+                    let cwd = env::current_dir()?;
+                    find_workspace_root(&cwd).unwrap_or(cwd)
+                }
+            };
+            let seed = match seed_map {
+                Some(seed_map) => read_import_map(&seed_map)?,
+                None => ImportMap::new(),
+            };
+            let build_files = find_workspace_build_files(&Config::load(&root)?)?;
+            let (graph, errors) = build_target_graph(&root, &build_files);
+            if !errors.is_empty() {
+                return Err(parse_errors(&errors));
+            }
+            let import_map = generate_import_map(&graph, &seed);
+            let output = output.unwrap_or_else(|| root.join(IMPORT_MAP_FILE_NAME));
+            atomic_write(&output, format_import_map(&import_map).as_bytes())?;
+            println!("Wrote {} modules to {}", import_map.len(), output.display());
+            Ok(())
+        }
+        Command::Clean {
+            root,
+            older_than,
+            dry_run,
+        } => {
+            let root = match root {
+                Some(root) => root,
+                None => {
+                    let cwd = env::current_dir()?;
+                    find_workspace_root(&cwd).unwrap_or(cwd)
+                }
+            };
+            let report = clean_backups(&root, older_than, dry_run)?;
+            let verb = if dry_run { "Would delete" } else { "Deleted" };
+            for path in &report.removed {
+                println!("{}: {}", verb, path.display());
+            }
+
+            println!(
+                "{} {} backups, freeing {}",
+                verb,
+                report.removed.len(),
+                format_bytes(report.bytes_freed)
+            );
+            Ok(())
+        }
+        Command::Undo {
+            root,
+            dry_run,
+            older_than,
+        } => {
+            let root = match root {
+                Some(root) => root,
+                None => env::current_dir()?,
+            };
+            let backups = find_backups(&root, older_than)?;
+            for backup in &backups {
+                if dry_run {
+                    println!("Would restore: {}", backup.original.display());
+                } else {
+                    restore_backup(backup)?;
+                    println!("Restored: {}", backup.original.display());
+                }
+            }
+
+            let verb = if dry_run { "Would restore" } else { "Restored" };
+            println!("{} {} files from backups", verb, backups.len());
+            Ok(())
+        }
+    }
+}
+
+// Print the targets of every BUILD file the run would process as a JSON array
+fn emit_targets(config: &Config, rule_types: &[String]) -> io::Result<()> {
+    let graph = {
+        let _timer = PhaseTimer::start(metrics::DISCOVER);
+        let build_files = find_build_files(config)?;
+        let (graph, errors) = build_target_graph(&config.root_dir, &build_files);
+        if !errors.is_empty() {
+            return Err(parse_errors(&errors));
+        }
+        graph
+    };
+    let entries = graph.entries(&config.root_dir, rule_types);
+    println!("{}", serde_json::to_string_pretty(&entries)?);
+    Ok(())
+}
+
+// Apply the patch at `patch_file` to the files under `root`, which defaults to
+// the workspace containing the current directory like the fix run's root
+fn apply_patch_file(patch_file: &Path, root: Option<&Path>) -> io::Result<()> {
+    let root = match root {
+        Some(root) => root.to_path_buf(),
+        None => {
+            let cwd = env::current_dir()?;
+            find_workspace_root(&cwd).unwrap_or(cwd)
+        }
+    };
+    let patch = fs::read_to_string(patch_file)?;
+    for path in apply_patch(&root, &patch)? {
+        println!("Patched: {}", path.display());
+    }
+    Ok(())
+}
+
+// Delete the comment block starting on `line` of `file`
+fn remove_comment_block_at(line: &str, file: &Path) -> io::Result<()> {
+    let line: usize = line.parse().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{:?} isn't a line number", line),
+        )
+    })?;
+    let content = fs::read_to_string(file)?;
+    let fixed = remove_comment_block(&content, line).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "no block of comments starts on line {} of {}",
+                line,
+                file.display()
+            ),
+        )
+    })?;
+    atomic_write(file, fixed.as_bytes())?;
+    println!(
+        "Removed the comment block on line {} of {}",
+        line,
+        file.display()
+    );
+    Ok(())
+}
+
+// Run, sampling the CPU throughout and writing the profile to `profile`
+// afterwards if it is set
+#[cfg(feature = "profile")]
+fn run_with_profile(config: &Config, profile: Option<&Path>) -> io::Result<Vec<IssueReport>> {
+    let Some(profile) = profile else {
+        return run(config);
+    };
+    let profiler = Profiler::start()?;
+    let reports = run(config);
+    profiler.write_report(profile)?;
+    if !matches!(config.mode, RunMode::Check | RunMode::Diff) {
+        println!("Wrote CPU profile to {}", profile.display());
+    }
+    reports
+}
+
+// Run the checks that look across BUILD files over the whole workspace, not
+// only the files this run processes, so each file's analysis reports them.
+// Files that can't be parsed are left out of them, with a warning.
+fn with_cross_file_findings(config: &Config) -> io::Result<Config> {
+    let _timer = PhaseTimer::start(metrics::DISCOVER);
+    let mut config = config.clone();
+    let build_files = find_workspace_build_files(&config)?;
+    let (graph, errors) = build_target_graph(&config.root_dir, &build_files);
+    if !errors.is_empty() {
+        eprintln!(
+            "warning: cross-file checks skip {} BUILD files that can't be parsed:",
+            errors.len()
+        );
+        for (path, err) in &errors {
+            let path = path.strip_prefix(&config.root_dir).unwrap_or(path);
+            eprintln!("  {}: {}", path.display(), err);
+        }
+    }
+    config.cross_file_findings = analyze_target_graph(&graph);
+    Ok(config)
+}
+
+// One error listing each BUILD file that can't be parsed
+fn parse_errors(errors: &[(PathBuf, ParseError)]) -> io::Error {
+    let messages: Vec<String> = errors
+        .iter()
+        .map(|(path, err)| format!("{}: {}", path.display(), err))
+        .collect();
+    io::Error::new(io::ErrorKind::InvalidData, messages.join("\n"))
+}
+
+fn run(config: &Config) -> io::Result<Vec<IssueReport>> {
     // Find all BUILD.bazel files
-    let build_files = find_build_files(project_root)?;
-    println!("Found {} BUILD.bazel files", build_files.len());
-    
+    let build_files = {
+        let _timer = PhaseTimer::start(metrics::DISCOVER);
+        find_build_files(config)?
+    };
+    // Check and diff output stays free of progress messages
+    let verbose = !matches!(config.mode, RunMode::Check | RunMode::Diff);
+    if verbose {
+        println!("Found {} BUILD.bazel files", build_files.len());
+    }
+    let config = &with_cross_file_findings(config)?;
+
+    let mut cache = config.cache_file.as_deref().map(|cache_file| {
+        if config.invalidate_cache {
+            Cache::new(config)
+        } else {
+            Cache::load(cache_file, config)
+        }
+    });
+
+    // Only runs that write files leave anything worth resuming
+    if let (true, Some(progress_file)) = (config.reset_progress, &config.progress_file) {
+        remove_state(progress_file)?;
+    }
+    let progress_file = config
+        .progress_file
+        .as_deref()
+        .filter(|_| config.writes_files());
+    let progress = progress_file
+        .map(|progress_file| load_progress(config, progress_file))
+        .transpose()?;
+    let mut progress_log = progress_file.map(ProgressLog::open).transpose()?;
+
+    // With --parallel-io every file is read up front, overlapping the reads;
+    // otherwise each file is read when it is fixed
+    let contents: Vec<Option<io::Result<String>>> = if config.parallel_io {
+        let _timer = PhaseTimer::start(metrics::DISCOVER);
+        read_files(&build_files, config.max_open_files)?
+            .into_iter()
+            .map(Some)
+            .collect()
+    } else {
+        build_files.iter().map(|_| None).collect()
+    };
+
     // Process each BUILD.bazel file
-    let mut modified_files = 0;
-    for file_path in build_files {
-        if fix_build_file(&file_path)? {
-            modified_files += 1;
+    let bar = progress_bar(config, build_files.len());
+    let mut reports = Vec::with_capacity(build_files.len());
+    for (file_path, content) in build_files.into_iter().zip(contents) {
+        let relative = file_path
+            .strip_prefix(&config.root_dir)
+            .unwrap_or(&file_path)
+            .to_path_buf();
+        if progress
+            .as_ref()
+            .is_some_and(|progress| progress.is_completed(&relative))
+        {
+            bar.inc(1);
+            continue;
+        }
+
+        let result = match (content, &mut cache) {
+            (Some(content), cache) => content.and_then(|content| {
+                fix_build_file_content(&file_path, content, config, cache.as_mut())
+            }),
+            (None, Some(cache)) => fix_build_file_with_cache(&file_path, config, cache),
+            (None, None) => fix_build_file(&file_path, config),
+        };
+        if let Some(progress_log) = &mut progress_log {
+            match result {
+                Ok(_) => progress_log.mark_completed(&relative)?,
+                Err(_) => progress_log.mark_failed(&relative)?,
+            }
         }
+        let report = result?;
+        bar.suspend(|| print_report(config, &report));
+        bar.inc(1);
+        reports.push(report);
+    }
+    bar.finish_and_clear();
+
+    if let (Some(cache), Some(cache_file)) = (&cache, &config.cache_file) {
+        if cache.hits() > 0 && verbose {
+            println!(
+                "Skipped analysis of {} unchanged files (cached)",
+                cache.hits()
+            );
+        }
+        // Runs that only report leave the working tree untouched
+        if config.writes_files() {
+            cache.save(cache_file)?;
+        }
+    }
+
+    match config.output {
+        OutputFormat::Text => {}
+        OutputFormat::Patch => write_patch(config, &reports)?,
+        OutputFormat::Html => write_html_report(config, &reports)?,
+        OutputFormat::Junit => write_junit(config, &reports)?,
+    }
+
+    if let Some(report_file) = &config.report_file {
+        RunReport::new(config, &reports).write_to(report_file)?;
+    }
+
+    if let Some(baseline_file) = &config.save_baseline {
+        let baseline = Baseline::from_reports(&config.root_dir, &reports);
+        baseline.save(baseline_file)?;
+        println!(
+            "Saved {} issues to baseline {}",
+            baseline.len(),
+            baseline_file.display()
+        );
+    }
+
+    drop(progress_log);
+    if let Some(progress_file) = progress_file {
+        remove_state(progress_file)?;
+    }
+
+    print_summary(config, &reports);
+
+    if config.bazel_validate {
+        validate_with_bazel(config)?;
+    }
+    Ok(reports)
+}
+
+// A bar of the files processed, drawn on stderr, or a hidden one when it
+// would get in the way: output that isn't to a terminal (unless --progress
+// always), check and diff output, and reports written instead of fixes
+fn progress_bar(config: &Config, files: usize) -> ProgressBar {
+    let target = match config.progress {
+        _ if matches!(config.mode, RunMode::Check | RunMode::Diff) => ProgressDrawTarget::hidden(),
+        _ if config.output != OutputFormat::Text => ProgressDrawTarget::hidden(),
+        ProgressMode::Never => ProgressDrawTarget::hidden(),
+        ProgressMode::Always => ProgressDrawTarget::term_like(Box::new(console::Term::stderr())),
+        ProgressMode::Auto if io::stdout().is_terminal() => ProgressDrawTarget::stderr(),
+        ProgressMode::Auto => ProgressDrawTarget::hidden(),
+    };
+    let style = ProgressStyle::with_template("[{bar:40}] {pos}/{len} files ({percent}%)")
+        .expect("progress bar template is valid")
+        .progress_chars("=> ");
+    ProgressBar::with_draw_target(Some(files as u64), target).with_style(style)
+}
+
+// The progress of an interrupted run, or a fresh state if there is none to
+// resume (an error with --resume)
+fn load_progress(config: &Config, progress_file: &Path) -> io::Result<ProgressState> {
+    match ProgressState::load(progress_file)? {
+        Some(progress) => {
+            println!(
+                "Resuming an interrupted run: skipping {} completed files",
+                progress.completed.len()
+            );
+            Ok(progress)
+        }
+        None if config.resume => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("--resume: no progress file at {}", progress_file.display()),
+        )),
+        None => Ok(ProgressState::default()),
+    }
+}
+
+// Run bazel query over the (fixed) workspace and check its lock file; query
+// errors or a stale lock file fail the run
+fn validate_with_bazel(config: &Config) -> io::Result<()> {
+    let timeout = Duration::from_secs(config.bazel_timeout_secs);
+    let (errors, lock) = {
+        let _timer = PhaseTimer::start(metrics::VALIDATE);
+        let errors = run_bazel_query(
+            &config.bazel,
+            &config.root_dir,
+            &config.bazel_query,
+            timeout,
+        )?;
+        (
+            errors,
+            check_lockfile(&config.bazel, &config.root_dir, timeout)?,
+        )
+    };
+
+    if errors.is_empty() {
+        println!("bazel query {} succeeded", config.bazel_query);
+    }
+    for error in &errors {
+        println!("bazel: {}", error);
+    }
+    match &lock {
+        LockStatus::NoModule => {}
+        LockStatus::UpToDate => println!("{} is up to date", LOCK_FILE_NAME),
+        LockStatus::Stale { errors } => {
+            for error in errors {
+                println!("bazel: {}", error);
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(io::Error::other(format!(
+            "bazel query {} reported {} errors",
+            config.bazel_query,
+            errors.len()
+        )));
+    }
+    if matches!(lock, LockStatus::Stale { .. }) {
+        return Err(io::Error::other(format!(
+            "{} is out of date (run `umbra-fix check-lock --update-lock`)",
+            LOCK_FILE_NAME
+        )));
     }
-    
-    println!("Successfully modified {} BUILD.bazel files", modified_files);
     Ok(())
 }
 
-// Find all BUILD.bazel files in the project
-fn find_build_files(project_root: &Path) -> io::Result<Vec<PathBuf>> {
-    let output = Command::new("find")
-        .arg(project_root)
-        .arg("-name")
-        .arg("BUILD.bazel")
-        .output()?;
-    
-    if !output.status.success() {
-        let error = String::from_utf8_lossy(&output.stderr);
-        return Err(io::Error::new(io::ErrorKind::Other, format!("Failed to find BUILD.bazel files: {}", error)));
-    }
-    
-    let files = String::from_utf8_lossy(&output.stdout)
-        .lines()
-        .map(|line| PathBuf::from(line))
+fn write_patch(config: &Config, reports: &[IssueReport]) -> io::Result<()> {
+    let Some(patch_file) = &config.patch_file else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--output patch requires --patch-file",
+        ));
+    };
+
+    let patch: String = reports
+        .iter()
+        .filter_map(|report| report.diff.as_deref())
         .collect();
-    
-    Ok(files)
-}
-
-// Fix a single BUILD.bazel file
-fn fix_build_file(file_path: &Path) -> io::Result<bool> {
-    // Read the file content
-    let mut file = fs::File::open(file_path)?;
-    let mut content = String::new();
-    file.read_to_string(&mut content)?;
-    
-    // Apply fixes
-    let mut modified = false;
-    let new_content = apply_fixes(&content, &mut modified);
-    
-    // Write back if modified
-    if modified {
-        println!("Modifying: {}", file_path.display());
-        let mut file = fs::File::create(file_path)?;
-        file.write_all(new_content.as_bytes())?;
-    }
-    
-    Ok(modified)
-}
-
-// Apply all fixes to the content
-fn apply_fixes(content: &str, modified: &mut bool) -> String {
-    // First ensure swift_library is loaded if it's used in the file
-    let content = ensure_swift_library_load(content, modified);
-    
-    // Convert umbra_swift_library to swift_library
-    let content = convert_custom_library(&content, modified);
-    
-    // Remove exports attribute
-    let content = remove_exports_attribute(&content, modified);
-    
-    // Fix glob patterns to set allow_empty=True
-    let content = fix_glob_patterns(&content, modified);
-    
-    // Ensure swift_library has valid srcs
-    let content = ensure_valid_srcs(&content, modified);
-    
-    content
-}
-
-// Ensure swift_library is properly loaded at the top of the file
-fn ensure_swift_library_load(content: &str, modified: &mut bool) -> String {
-    // Create a regex to detect swift_library in any format
-    let swift_lib_re = Regex::new(r"\bswift_library\s*\(").unwrap();
-
-    // Check if the file contains swift_library
-    if swift_lib_re.is_match(content) {
-        // Check if the swift library load statement is already present
-        let swift_load = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")"#;
-        if !content.contains(swift_load) {
-            // Add the load statement at the top of the file
-            let new_content = format!("{}\n\n{}", swift_load, content);
-            *modified = true;
-            return new_content;
-        }
-    }
-    
-    content.to_string()
-}
-
-// Convert umbra_swift_library to swift_library
-fn convert_custom_library(content: &str, modified: &mut bool) -> String {
-    let load_re = Regex::new(r#"load\(\s*"//:swift_rules\.bzl"\s*,\s*"umbra_swift_library"\s*\)"#).unwrap();
-    let library_re = Regex::new(r#"umbra_swift_library\s*\("#).unwrap();
-    
-    let new_content = load_re.replace_all(content, 
-        r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")"#);
-    
-    if new_content != content {
-        *modified = true;
-    }
-    
-    let new_content = library_re.replace_all(&new_content, "swift_library(");
-    
-    if new_content != content {
-        *modified = true;
-    }
-    
-    new_content.to_string()
-}
-
-// Remove unsupported exports attribute
-fn remove_exports_attribute(content: &str, modified: &mut bool) -> String {
-    // This regex matches the exports attribute and its array of values
-    let re = Regex::new(r#"(?s)exports\s*=\s*\[(.*?),?\s*\],"#).unwrap();
-    
-    let new_content = re.replace_all(content, |_: &Captures| {
-        *modified = true;
-        ""
-    });
-    
-    new_content.to_string()
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(patch_file)?
+        .write_all(patch.as_bytes())
 }
 
-// Fix glob patterns to set allow_empty=True
-fn fix_glob_patterns(content: &str, modified: &mut bool) -> String {
-    // First fix patterns with allow_empty=False
-    let false_re = Regex::new(r"allow_empty\s*=\s*False").unwrap();
-    let new_content = false_re.replace_all(content, |_: &Captures| {
-        *modified = true;
-        "allow_empty = True"
-    });
-    
-    // Then add allow_empty=True to patterns that don't have it
-    let glob_re = Regex::new(r"glob\s*\(\s*\[(.*?)\]\s*\)").unwrap();
-    
-    let new_content = glob_re.replace_all(&new_content, |caps: &Captures| {
-        // Only replace if it doesn't already have allow_empty
-        if !caps[0].contains("allow_empty") {
-            *modified = true;
-            format!("glob(\n        [{}],\n        allow_empty = True\n    )", &caps[1])
+fn write_html_report(config: &Config, reports: &[IssueReport]) -> io::Result<()> {
+    let Some(html_file) = &config.html_file else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--output html requires --html-file",
+        ));
+    };
+    write_html(html_file, config, reports)
+}
+
+fn write_junit(config: &Config, reports: &[IssueReport]) -> io::Result<()> {
+    let Some(junit_file) = &config.junit_file else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--output junit requires --junit-file",
+        ));
+    };
+    write_junit_report(reports, junit_file)
+}
+
+// The file a non-text --output writes to
+fn output_file(config: &Config) -> &Path {
+    let file = match config.output {
+        OutputFormat::Html => config.html_file.as_deref(),
+        OutputFormat::Junit => config.junit_file.as_deref(),
+        _ => config.patch_file.as_deref(),
+    };
+    file.unwrap_or(Path::new(""))
+}
+
+fn print_report(config: &Config, report: &IssueReport) {
+    let fixable = report.findings.iter().filter(|f| f.issue.is_fixable());
+
+    if report.modified {
+        match config.mode {
+            RunMode::Fix if !config.writes_files() => {
+                let target = match config.output {
+                    OutputFormat::Html | OutputFormat::Junit => "report",
+                    _ => "patch",
+                };
+                println!("Adding to {}: {}", target, report.path.display())
+            }
+            RunMode::Fix => println!("Modifying: {}", report.path.display()),
+            RunMode::DryRun => {
+                println!("Would modify: {}", report.path.display());
+                for finding in fixable {
+                    println!("  {}", finding);
+                }
+            }
+            RunMode::Check => {
+                for finding in fixable {
+                    println!("{}: {}", report.path.display(), finding);
+                }
+            }
+            RunMode::Diff => print!("{}", report.diff.as_deref().unwrap_or_default()),
+        }
+    }
+
+    if config.verbose_diffs && !report.fix_diffs.is_empty() {
+        print_fix_diffs(config, report);
+    }
+
+    // Issues the fixer can't resolve are shown in every mode, on stderr when
+    // stdout is a diff
+    for finding in report.findings.iter().filter(|f| !f.issue.is_fixable()) {
+        let message = format!("{}: needs manual fix: {}", report.path.display(), finding);
+        if config.mode == RunMode::Diff {
+            eprintln!("{}", message);
         } else {
-            // Return the original match
-            caps[0].to_string()
+            println!("{}", message);
         }
+    }
+
+    if config.github_annotations && !report.findings.is_empty() {
+        print_annotations(config, report);
+    }
+}
+
+// Annotate the findings for GitHub Actions, with positions in the file as it
+// is now (fixed, unless the run doesn't write files)
+fn print_annotations(config: &Config, report: &IssueReport) {
+    let path = report
+        .path
+        .strip_prefix(&config.root_dir)
+        .unwrap_or(&report.path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let content = fs::read_to_string(&report.path).unwrap_or_default();
+    let result = if config.mode == RunMode::Diff {
+        write_annotations(&mut io::stderr(), &path, &content, &report.findings)
+    } else {
+        write_annotations(&mut io::stdout(), &path, &content, &report.findings)
+    };
+    if let Err(err) = result {
+        eprintln!("error: writing annotations: {}", err);
+    }
+}
+
+// Print the diff of each fix, on stderr when stdout is a diff
+fn print_fix_diffs(config: &Config, report: &IssueReport) {
+    let to_stderr = config.mode == RunMode::Diff;
+    let color = match config.color {
+        ColorMode::Always => ColorChoice::Always,
+        ColorMode::Never => ColorChoice::Never,
+        ColorMode::Auto if to_stderr && io::stderr().is_terminal() => ColorChoice::Auto,
+        ColorMode::Auto if !to_stderr && io::stdout().is_terminal() => ColorChoice::Auto,
+        ColorMode::Auto => ColorChoice::Never,
+    };
+    let mut out = if to_stderr {
+        StandardStream::stderr(color)
+    } else {
+        StandardStream::stdout(color)
+    };
+
+    for (finding, diff) in &report.fix_diffs {
+        let result =
+            writeln!(out, "  fixed {}", finding).and_then(|()| write_colored_diff(&mut out, diff));
+        if let Err(err) = result {
+            eprintln!("error: writing diff: {}", err);
+            return;
+        }
+    }
+}
+
+fn print_summary(config: &Config, reports: &[IssueReport]) {
+    let modified_files = reports.iter().filter(|report| report.modified).count();
+    let manual = manual_issue_count(reports);
+
+    match config.mode {
+        RunMode::Fix if !config.writes_files() => println!(
+            "Wrote fixes for {} BUILD.bazel files to {}",
+            modified_files,
+            output_file(config).display()
+        ),
+        RunMode::Fix => println!("Successfully modified {} BUILD.bazel files", modified_files),
+        RunMode::DryRun => println!("{} BUILD.bazel files would be modified", modified_files),
+        RunMode::Diff => return,
+        RunMode::Check => {
+            let issues: usize = reports
+                .iter()
+                .filter(|report| report.modified)
+                .flat_map(|report| &report.findings)
+                .filter(|finding| finding.issue.is_fixable())
+                .count();
+            println!(
+                "{} fixable issues in {} BUILD.bazel files",
+                issues, modified_files
+            );
+        }
+    }
+
+    if manual > 0 {
+        println!("{} issues need a manual fix", manual);
+    }
+}
+
+fn manual_issue_count(reports: &[IssueReport]) -> usize {
+    reports
+        .iter()
+        .flat_map(|report| &report.findings)
+        .filter(|finding| !finding.issue.is_fixable())
+        .count()
+}
+
+// The CI gate of --assert-no-issues: list the issues the run left behind
+// and write them to stderr as JSON, failing if there are any. Files the run
+// fixed are analyzed again, so a fix that didn't work still fails.
+fn assert_no_issues(config: &Config, reports: &[IssueReport]) -> io::Result<ExitCode> {
+    let remaining = if config.writes_files() {
+        RemainingIssues::new(&config.root_dir, &reanalyze(config, reports)?)
+    } else {
+        RemainingIssues::new(&config.root_dir, reports)
+    };
+    if !remaining.is_empty() {
+        println!("{} issues remain:", remaining.remaining);
+        for issue in &remaining.issues {
+            println!(
+                "  {}: [{}] {}",
+                issue.file.display(),
+                issue.issue,
+                issue.message
+            );
+        }
+    }
+    eprintln!("{}", serde_json::to_string(&remaining)?);
+    Ok(if remaining.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+// Analyze the files of `reports` again as they are on disk, without fixing
+// them, along with the cross-file checks of the workspace
+fn reanalyze(config: &Config, reports: &[IssueReport]) -> io::Result<Vec<IssueReport>> {
+    let mut config = config.clone();
+    config.mode = RunMode::DryRun;
+    let build_files = find_workspace_build_files(&config)?;
+    // Files that can't be parsed were already warned about
+    let (graph, _) = build_target_graph(&config.root_dir, &build_files);
+    config.cross_file_findings = analyze_target_graph(&graph);
+    reports
+        .iter()
+        .map(|report| fix_build_file(&report.path, &config))
+        .collect()
+}
+
+fn exit_code(config: &Config, reports: &[IssueReport]) -> ExitCode {
+    // Malformed files are rejected in every mode
+    let rejected: Vec<&IssueReport> = reports
+        .iter()
+        .filter(|report| {
+            report
+                .findings
+                .iter()
+                .any(|finding| matches!(finding.issue, BuildIssue::InvalidSyntax { .. }))
+        })
+        .collect();
+    if !rejected.is_empty() {
+        eprintln!(
+            "error: {} BUILD files are malformed and were left unchanged:",
+            rejected.len()
+        );
+        for report in rejected {
+            eprintln!("  {}", report.path.display());
+        }
+        return ExitCode::FAILURE;
+    }
+
+    // Warnings are fixed like other issues, but don't fail the run
+    let has_diffs = reports.iter().any(|report| {
+        report.modified
+            && report
+                .findings
+                .iter()
+                .any(|finding| finding.issue.is_fixable() && !finding.issue.is_warning())
     });
-    
-    new_content.to_string()
-}
-
-// Ensure swift_library has valid srcs
-fn ensure_valid_srcs(content: &str, modified: &mut bool) -> String {
-    // Find swift_library blocks
-    let lib_re = Regex::new(r#"swift_library\s*\(\s*name\s*=\s*"[^"]+"#).unwrap();
-    
-    // Process the content for each swift_library
-    let mut new_content = content.to_string();
-    for lib_match in lib_re.find_iter(content) {
-        let lib_start = lib_match.start();
-        
-        // Check if there's a srcs attribute in the following text
-        let has_srcs = content[lib_start..].contains("srcs");
-        
-        if !has_srcs {
-            // Find the position after name =
-            if let Some(pos) = content[lib_start..].find(',') {
-                let insert_pos = lib_start + pos + 1;
-                
-                // Insert srcs attribute with proper string termination
-                let srcs_attr = r#"
-    srcs = glob(
-        ["*.swift"],
-        allow_empty = True,
-    ),"#;
-                
-                new_content.insert_str(insert_pos, srcs_attr);
-                *modified = true;
-            }
-        }
-    }
-    
-    new_content
+    if has_diffs && matches!(config.mode, RunMode::Check | RunMode::Diff) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
 }
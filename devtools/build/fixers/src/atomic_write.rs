@@ -0,0 +1,53 @@
+//! Crash-safe file replacement.
+//!
+//! Content is written to a temporary file next to the destination and then
+//! renamed over it, so readers see either the old or the new file, never a
+//! truncated one. `fs::rename` is atomic on POSIX within one filesystem and
+//! uses `MoveFileExW` with `MOVEFILE_REPLACE_EXISTING` on Windows.
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Suffix of temporary files; the full name is `<file>.umbra-fix.tmp.<pid>`.
+pub const TEMP_SUFFIX: &str = ".umbra-fix.tmp";
+
+pub fn atomic_write(path: &Path, content: &[u8]) -> io::Result<()> {
+    let temp_path = write_temp_file(path, content)?;
+    fs::rename(&temp_path, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp_path);
+    })
+}
+
+/// Temporary file used while replacing `path` from this process.
+pub fn temp_path_for(path: &Path) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(format!("{}.{}", TEMP_SUFFIX, process::id()));
+    path.with_file_name(name)
+}
+
+// Write and sync the temporary file for `path`, keeping the permissions of
+// the file it will replace
+pub fn write_temp_file(path: &Path, content: &[u8]) -> io::Result<PathBuf> {
+    let temp_path = temp_path_for(path);
+
+    let result = (|| {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&temp_path, metadata.permissions())?;
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => Ok(temp_path),
+        Err(err) => {
+            let _ = fs::remove_file(&temp_path);
+            Err(err)
+        }
+    }
+}
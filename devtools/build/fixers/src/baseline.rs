@@ -0,0 +1,146 @@
+//! Baselines of known issues, so a repository can adopt the fixer without
+//! fixing everything at once: issues recorded in the baseline are neither
+//! reported nor fixed, and only new ones fail `--check`.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::atomic_write::atomic_write;
+use crate::checks::fix_issue;
+use crate::issue::{Finding, IssueReport};
+
+/// Issues recorded by `--save-baseline`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    issues: BTreeSet<BaselineEntry>,
+}
+
+/// One recorded issue, identified by what it is about rather than where it
+/// is, so that editing the lines around it doesn't make it new again.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    /// File the issue was found in, relative to the root.
+    pub path: String,
+    /// Issue type, as in reports (`EmptySrcs`, `MissingLoad`, ...).
+    pub issue: String,
+    /// SHA-256 of the issue's fields (its target, attribute, labels, ...)
+    /// with its line number replaced by the text of the line, or for issues
+    /// without any fields, of the lines their fix changes. Hashed so the file
+    /// stays small and free of the paths and labels they quote.
+    pub fingerprint: String,
+}
+
+// Fields of an issue that only locate it
+const POSITION_FIELDS: &[&str] = &["line", "column"];
+
+impl Baseline {
+    /// A baseline of every finding in `reports`, whose files must still hold
+    /// the content the findings were made on.
+    pub fn from_reports(root_dir: &Path, reports: &[IssueReport]) -> Self {
+        let issues = reports
+            .iter()
+            .flat_map(|report| {
+                let path = relative_path(root_dir, &report.path);
+                let content = fs::read_to_string(&report.path).unwrap_or_default();
+                report
+                    .findings
+                    .iter()
+                    .map(move |finding| BaselineEntry::new(&path, &content, finding))
+            })
+            .collect();
+        Baseline { issues }
+    }
+
+    /// Read the baseline at `path`.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let json = fs::read_to_string(path).map_err(|err| {
+            io::Error::new(err.kind(), format!("baseline {}: {}", path.display(), err))
+        })?;
+        serde_json::from_str(&json).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), err),
+            )
+        })
+    }
+
+    /// Write the baseline to `path`.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        atomic_write(path, json.as_bytes())
+    }
+
+    /// Whether `finding` in `content`, the root-relative file `path`, is
+    /// recorded.
+    pub fn contains(&self, path: &str, content: &str, finding: &Finding) -> bool {
+        self.issues
+            .contains(&BaselineEntry::new(path, content, finding))
+    }
+
+    pub fn len(&self) -> usize {
+        self.issues.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl BaselineEntry {
+    fn new(path: &str, content: &str, finding: &Finding) -> Self {
+        BaselineEntry {
+            path: path.to_string(),
+            issue: finding.issue.name().to_string(),
+            fingerprint: fingerprint(content, finding),
+        }
+    }
+}
+
+// Issues serialize as an object of their name and fields. Positions are
+// replaced by the text they point at, so the fingerprint survives lines
+// moving but still tells two issues of a kind apart.
+fn fingerprint(content: &str, finding: &Finding) -> String {
+    let mut fields = match serde_json::to_value(&finding.issue).unwrap_or_default() {
+        serde_json::Value::Object(fields) => fields,
+        _ => serde_json::Map::new(),
+    };
+    let line = fields
+        .get("line")
+        .and_then(serde_json::Value::as_u64)
+        .and_then(|line| content.lines().nth((line as usize).checked_sub(1)?));
+    fields.retain(|field, _| field != "name" && !POSITION_FIELDS.contains(&field.as_str()));
+
+    let anchor: Vec<&str> = match line {
+        Some(line) => vec![line.trim()],
+        // Nothing else says which part of the file the issue is about
+        None if fields.is_empty() => changed_lines(content, &fix_issue(&finding.issue, content)),
+        None => Vec::new(),
+    };
+    let key = serde_json::json!([finding.issue.name(), fields, anchor]);
+    format!("{:x}", Sha256::digest(key.to_string().as_bytes()))
+}
+
+// The lines of `content` that `fixed` no longer has, trimmed. Loads are left
+// out, as fixes rewrite them whenever a rule they add needs one.
+fn changed_lines<'a>(content: &'a str, fixed: &str) -> Vec<&'a str> {
+    let fixed: BTreeSet<&str> = fixed.lines().collect();
+    content
+        .lines()
+        .filter(|line| !fixed.contains(line))
+        .map(str::trim)
+        .filter(|line| !line.starts_with("load("))
+        .collect()
+}
+
+/// `path` relative to `root_dir`, with `/` separators.
+pub fn relative_path(root_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(root_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
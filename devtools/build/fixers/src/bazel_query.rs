@@ -0,0 +1,180 @@
+//! Validation of BUILD files by running `bazel query` over them.
+//!
+//! Static analysis can't catch everything Bazel rejects, so after fixing,
+//! `--bazel-validate` loads the packages with Bazel and reports its errors.
+
+use std::fmt;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::LazyLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+
+/// Query run when none is configured: every target in the workspace.
+pub const DEFAULT_QUERY: &str = "//...";
+
+/// How long `bazel query` may run when no timeout is configured.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+// How often to check whether bazel has exited
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// `ERROR: /path/to/BUILD.bazel:12:14: message`
+static LOCATED_ERROR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^ERROR: (?P<file>[^:\s][^:]*):(?P<line>\d+):(?:(?P<column>\d+):)? (?P<message>.*)$",
+    )
+    .expect("invalid regex")
+});
+static REFERENCED_BY_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"referenced by '(//[^']*)'").expect("invalid regex"));
+static LABEL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"'(//[^']*)'").expect("invalid regex"));
+
+/// One error reported by Bazel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BazelError {
+    /// The BUILD file the error points at, if it has a location.
+    pub file: Option<PathBuf>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// The rule the error is about (`referenced by '//pkg:target'`), if named.
+    pub rule: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for BazelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+            if let Some(line) = self.line {
+                write!(f, "{}:", line)?;
+            }
+            f.write_str(" ")?;
+        }
+        f.write_str(&self.message)?;
+        if let Some(rule) = &self.rule {
+            write!(f, " (rule {})", rule)?;
+        }
+        Ok(())
+    }
+}
+
+/// Extract the `ERROR:` lines from Bazel's stderr.
+pub fn parse_bazel_errors(stderr: &str) -> Vec<BazelError> {
+    stderr
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| line.starts_with("ERROR: "))
+        .map(|line| {
+            let (file, line_number, column, message) = match LOCATED_ERROR_RE.captures(line) {
+                Some(caps) => (
+                    Some(PathBuf::from(&caps["file"])),
+                    caps["line"].parse().ok(),
+                    caps.name("column").and_then(|c| c.as_str().parse().ok()),
+                    caps["message"].to_string(),
+                ),
+                None => (None, None, None, line["ERROR: ".len()..].to_string()),
+            };
+            let rule = REFERENCED_BY_RE
+                .captures(&message)
+                .or_else(|| LABEL_RE.captures(&message))
+                .map(|caps| caps[1].to_string());
+            BazelError {
+                file,
+                line: line_number,
+                column,
+                rule,
+                message,
+            }
+        })
+        .collect()
+}
+
+/// Run `bazel query <expression>` in `root` and return the errors it
+/// reports. Fails if bazel can't be started or runs longer than `timeout`.
+pub fn run_bazel_query(
+    bazel: &Path,
+    root: &Path,
+    expression: &str,
+    timeout: Duration,
+) -> io::Result<Vec<BazelError>> {
+    let (status, stderr) = run_bazel(
+        bazel,
+        root,
+        &["query", "--color=no", "--keep_going", expression],
+        timeout,
+    )?;
+
+    let errors = parse_bazel_errors(&stderr);
+    if errors.is_empty() && !status.success() {
+        // Bazel failed without an ERROR: line we recognise
+        let last_line = stderr.lines().rev().find(|line| !line.trim().is_empty());
+        return Ok(vec![BazelError {
+            file: None,
+            line: None,
+            column: None,
+            rule: None,
+            message: format!(
+                "bazel query exited with {}{}",
+                status,
+                last_line
+                    .map(|line| format!(": {}", line.trim()))
+                    .unwrap_or_default()
+            ),
+        }]);
+    }
+    Ok(errors)
+}
+
+/// Run `bazel <args>` in `root`, returning its exit status and stderr. Fails
+/// if bazel can't be started or runs longer than `timeout`.
+pub(crate) fn run_bazel(
+    bazel: &Path,
+    root: &Path,
+    args: &[&str],
+    timeout: Duration,
+) -> io::Result<(ExitStatus, String)> {
+    let mut child = Command::new(bazel)
+        .args(args)
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| {
+            io::Error::new(err.kind(), format!("running {}: {}", bazel.display(), err))
+        })?;
+
+    // Drain stderr on another thread so a chatty bazel can't fill the pipe
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stderr.read_to_string(&mut output);
+        output
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!(
+                    "bazel {} timed out after {}s",
+                    args.first().copied().unwrap_or_default(),
+                    timeout.as_secs()
+                ),
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    Ok((status, reader.join().unwrap_or_default()))
+}
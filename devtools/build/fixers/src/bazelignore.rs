@@ -0,0 +1,37 @@
+//! Support for `.bazelignore`, the list of directories Bazel doesn't look into.
+
+use std::fs;
+use std::path::Path;
+
+use crate::glob::glob_match;
+
+/// Name of the ignore file read from the root directory.
+pub const BAZELIGNORE_FILE_NAME: &str = ".bazelignore";
+
+// Read the directories listed in `root/.bazelignore`, relative to the root.
+// Blank lines and `#` comments are skipped, as are the `./` and trailing `/`
+// that Bazel tolerates. A missing or unreadable file ignores nothing.
+pub fn load_bazelignore(root: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(root.join(BAZELIGNORE_FILE_NAME)) else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            line.trim_start_matches("./")
+                .trim_end_matches('/')
+                .to_string()
+        })
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+/// Whether the root-relative directory `dir` is listed in (or matches a glob
+/// pattern of) `entries`. Subdirectories of an ignored directory are not
+/// checked, as discovery never walks into them.
+pub fn is_ignored(dir: &str, entries: &[String]) -> bool {
+    entries.iter().any(|entry| glob_match(entry, dir))
+}
@@ -0,0 +1,47 @@
+//! Removing old `.bak` files left behind by `--backup`, for `umbra-fix clean`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::undo::find_backups;
+
+/// What a clean removed, or would remove with `dry_run`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CleanReport {
+    pub removed: Vec<PathBuf>,
+    /// Total size of the removed backups.
+    pub bytes_freed: u64,
+}
+
+// Delete the backups under `root` written at least `older_than` ago. With
+// `dry_run`, only report what would be deleted.
+pub fn clean_backups(root: &Path, older_than: Duration, dry_run: bool) -> io::Result<CleanReport> {
+    let mut report = CleanReport::default();
+
+    for backup in find_backups(root, Some(older_than))? {
+        report.bytes_freed += fs::metadata(&backup.path)?.len();
+        if !dry_run {
+            fs::remove_file(&backup.path)?;
+        }
+        report.removed.push(backup.path);
+    }
+
+    Ok(report)
+}
+
+/// Format a byte count as e.g. `512 B`, `1.5 KiB` or `2.0 MiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
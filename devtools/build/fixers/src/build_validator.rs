@@ -0,0 +1,150 @@
+//! A minimal Starlark syntax check run before any fix is applied.
+//!
+//! The fixes edit the text around the tokens they expect, so on a file with
+//! an unclosed bracket or string they can make things worse. Only what the
+//! tokenizer would otherwise paper over is checked: brackets must balance,
+//! string literals must be closed, and escape sequences must be ones Starlark
+//! accepts.
+
+use std::fmt;
+
+use crate::starlark::tokenizer::{tokenize, Token, TokenKind};
+
+/// Why a file failed validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub message: String,
+    /// 1-based line.
+    pub line: usize,
+    /// 1-based column, in characters.
+    pub column: usize,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+/// Check that `content` has balanced brackets, closed string literals and
+/// valid escape sequences, returning the first problem found.
+pub fn validate_starlark_syntax(content: &str) -> Result<(), SyntaxError> {
+    let tokens = tokenize(content);
+    let error = |offset: usize, message: String| {
+        let line_start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+        SyntaxError {
+            message,
+            line: content[..offset].matches('\n').count() + 1,
+            column: content[line_start..offset].chars().count() + 1,
+        }
+    };
+
+    let mut open: Vec<&Token<'_>> = Vec::new();
+    for token in &tokens {
+        match token.kind {
+            TokenKind::String => {
+                check_string(token.text)
+                    .map_err(|(offset, message)| error(token.start + offset, message))?;
+            }
+            TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace => open.push(token),
+            TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace => match open.pop() {
+                Some(opening) if closing_bracket(opening.text) == token.text => {}
+                Some(opening) => {
+                    return Err(error(
+                        token.start,
+                        format!(
+                            "{:?} doesn't close {:?} from line {}",
+                            token.text, opening.text, opening.line
+                        ),
+                    ))
+                }
+                None => {
+                    return Err(error(
+                        token.start,
+                        format!("{:?} has no opening bracket", token.text),
+                    ))
+                }
+            },
+            _ => {}
+        }
+    }
+
+    match open.pop() {
+        Some(opening) => Err(error(
+            opening.start,
+            format!("{:?} is never closed", opening.text),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn closing_bracket(opening: &str) -> &'static str {
+    match opening {
+        "(" => ")",
+        "[" => "]",
+        _ => "}",
+    }
+}
+
+// Check a string literal token, including any r/b prefix. Errors carry the
+// byte offset within the token.
+fn check_string(text: &str) -> Result<(), (usize, String)> {
+    let bytes = text.as_bytes();
+    let prefix = text.find(['"', '\'']).unwrap_or(0);
+    let raw = text[..prefix].contains(['r', 'R']);
+    let quote = bytes[prefix];
+    let triple = bytes.get(prefix + 1) == Some(&quote) && bytes.get(prefix + 2) == Some(&quote);
+    let delimiter = if triple { 3 } else { 1 };
+
+    let unclosed = || Err((0, "string literal is never closed".to_string()));
+    let mut pos = prefix + delimiter;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' if raw => pos += 2,
+            b'\\' => {
+                pos += escape_length(&text[pos..]).ok_or_else(|| {
+                    let sequence: String = text[pos..].chars().take(2).collect();
+                    (pos, format!("invalid escape sequence {:?}", sequence))
+                })?
+            }
+            c if c == quote && (!triple || text[pos..].starts_with(&text[prefix..prefix + 3])) => {
+                return if pos + delimiter == bytes.len() {
+                    Ok(())
+                } else {
+                    unclosed()
+                };
+            }
+            _ => pos += 1,
+        }
+    }
+    unclosed()
+}
+
+// Length of the escape sequence at the start of `text` (from its backslash),
+// or `None` if Starlark rejects it
+fn escape_length(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let hex_digits = |count: usize| {
+        bytes
+            .get(2..2 + count)
+            .filter(|digits| digits.iter().all(u8::is_ascii_hexdigit))
+            .map(|_| 2 + count)
+    };
+    match *bytes.get(1)? {
+        b'\n' | b'\\' | b'\'' | b'"' | b'a' | b'b' | b'f' | b'n' | b'r' | b't' | b'v' => Some(2),
+        b'\r' if bytes.get(2) == Some(&b'\n') => Some(3),
+        b'0'..=b'7' => Some(
+            1 + bytes[1..]
+                .iter()
+                .take(3)
+                .take_while(|c| (b'0'..=b'7').contains(c))
+                .count(),
+        ),
+        b'x' => hex_digits(2),
+        b'u' => hex_digits(4),
+        b'U' => hex_digits(8),
+        _ => None,
+    }
+}
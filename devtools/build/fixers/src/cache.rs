@@ -0,0 +1,179 @@
+//! Cache of analysis results, so unchanged BUILD files aren't re-analyzed.
+//!
+//! Entries are keyed by the root-relative path of the file and hold a hash of
+//! its content together with a listing of its package directory (checks such
+//! as orphaned sources and unused deps look at the files next to it). The
+//! whole cache is discarded when the settings that affect analysis change.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use crate::atomic_write::atomic_write;
+use crate::config::Config;
+use crate::discovery::is_package;
+use crate::issue::Finding;
+
+/// Name of the cache file written to the root directory.
+pub const CACHE_FILE_NAME: &str = ".umbra-fix-cache.json";
+
+/// Analysis results of earlier runs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Cache {
+    /// Fingerprint of the settings the entries were computed with.
+    fingerprint: String,
+    entries: BTreeMap<String, CacheEntry>,
+    /// Lookups answered from the cache during this run.
+    #[serde(skip)]
+    hits: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    sha256: String,
+    findings: Vec<Finding>,
+}
+
+impl Cache {
+    /// An empty cache for runs with `config`.
+    pub fn new(config: &Config) -> Self {
+        Cache {
+            fingerprint: config_fingerprint(config),
+            ..Cache::default()
+        }
+    }
+
+    /// Read the cache at `path`. A missing or unreadable cache, or one written
+    /// with different settings, gives an empty cache.
+    pub fn load(path: &Path, config: &Config) -> Self {
+        let cache = fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<Cache>(&json).ok());
+        match cache {
+            Some(cache) if cache.fingerprint == config_fingerprint(config) => cache,
+            _ => Cache::new(config),
+        }
+    }
+
+    /// Write the cache to `path`.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string(self).map_err(io::Error::other)?;
+        atomic_write(path, json.as_bytes())
+    }
+
+    /// The findings cached for `file` if its hash is still `sha256`.
+    pub fn get(&mut self, file: &str, sha256: &str) -> Option<Vec<Finding>> {
+        let entry = self
+            .entries
+            .get(file)
+            .filter(|entry| entry.sha256 == sha256)?;
+        self.hits += 1;
+        Some(entry.findings.clone())
+    }
+
+    pub fn insert(&mut self, file: String, sha256: String, findings: Vec<Finding>) {
+        self.entries.insert(file, CacheEntry { sha256, findings });
+    }
+
+    /// How many files were answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+}
+
+/// Hash of a BUILD file's content and the listing (names, sizes and
+/// modification times) of its package directory.
+pub fn content_hash(content: &str, package_dir: Option<&Path>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    if let Some(package_dir) = package_dir {
+        hash_package_listing(&mut hasher, package_dir);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+// Everything in the config that changes what analysis finds. Per-run options
+// such as the mode, output and file selection don't, so they aren't part of it.
+fn config_fingerprint(config: &Config) -> String {
+    let settings = format!(
+        "{} {:?} {:?} {} {} {} {:?} {} {} {:?} {} {:?} {:?} {:?} {} {:?} {:?} {} {} {} {:?} {:?} {} {} {} {:?} {:?} {:?} {:?} {:?} {}",
+        env!("CARGO_PKG_VERSION"),
+        config.rule_filter,
+        config.sorted_list_attributes,
+        config.sort_attributes,
+        config.format,
+        config.format_only,
+        config.module_names,
+        config.prune_deps,
+        config.prune_system_deps,
+        config.import_map,
+        config.network,
+        config.rule_migrations,
+        config.rules_swift_upgrade,
+        config.minimum_os_versions,
+        config.bundle_id_prefix,
+        config.project_path_variable,
+        config.generated_file_patterns,
+        config.check_target_names,
+        config.warn_redundant_allow_empty,
+        config.require_license,
+        config.license_type,
+        config.max_line_length,
+        config.check_sha256,
+        config.fix_sha256,
+        config.require_dead_strip,
+        config.external_label_rewrites,
+        config.cross_file_findings,
+        config.naming_convention,
+        config.macro_registry,
+        config.version_catalog,
+        config.allow_unverifiable_sha256,
+    );
+    format!("{:x}", Sha256::digest(settings.as_bytes()))
+}
+
+// Hidden entries (.git, the cache itself) are skipped, as are the contents of
+// nested packages; the nested package directories themselves are listed.
+fn hash_package_listing(hasher: &mut Sha256, package_dir: &Path) {
+    let walker = WalkDir::new(package_dir)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            !entry.file_name().to_string_lossy().starts_with('.')
+                && !entry
+                    .path()
+                    .parent()
+                    .is_some_and(|parent| parent != package_dir && is_package(parent))
+        });
+
+    for entry in walker.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        let relative = entry
+            .path()
+            .strip_prefix(package_dir)
+            .unwrap_or(entry.path());
+        hasher.update(
+            format!(
+                "{}\0{}\0{}\n",
+                relative.to_string_lossy(),
+                metadata.len(),
+                modified.as_nanos()
+            )
+            .as_bytes(),
+        );
+    }
+}
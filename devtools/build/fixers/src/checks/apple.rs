@@ -0,0 +1,188 @@
+//! Checks on rules_apple bundling rules.
+
+use std::collections::BTreeMap;
+
+use crate::issue::BuildIssue;
+use crate::starlark::calls::{insert_after_name, top_level_calls, Call};
+use crate::starlark::tokenizer::{quote, tokenize, Token, TokenKind};
+
+/// Bundling rules that need a `minimum_os_version`, and their platform.
+pub const APPLE_BUNDLE_RULES: &[(&str, &str)] = &[
+    ("ios_application", "ios"),
+    ("macos_application", "macos"),
+    ("tvos_application", "tvos"),
+    ("watchos_application", "watchos"),
+    ("watchos_extension", "watchos"),
+];
+
+/// `minimum_os_version` inserted for platforms `umbra-fix.toml` doesn't set.
+pub const DEFAULT_MINIMUM_OS_VERSIONS: &[(&str, &str)] = &[
+    ("ios", "14.0"),
+    ("macos", "11.0"),
+    ("tvos", "14.0"),
+    ("watchos", "7.0"),
+];
+
+/// Prefix of the `bundle_id` inserted when `umbra-fix.toml` doesn't set one.
+pub const DEFAULT_BUNDLE_ID_PREFIX: &str = "com.example";
+
+// Flag bundling rules without a `minimum_os_version`. The version the fix
+// inserts comes from `versions` (platform -> version), falling back to the
+// built-in default for the platform.
+pub fn check_minimum_os_version(
+    content: &str,
+    versions: &BTreeMap<String, String>,
+) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues = Vec::new();
+
+    for call in top_level_calls(&tokens) {
+        let Some(platform) = bundle_platform(call.name) else {
+            continue;
+        };
+        if call.keyword(&tokens, "minimum_os_version").is_some() {
+            continue;
+        }
+        let Some(target) = call.target_name(&tokens) else {
+            continue;
+        };
+        let version = versions
+            .get(platform)
+            .map(String::as_str)
+            .or_else(|| default_minimum_os_version(platform))
+            .unwrap_or_default()
+            .to_string();
+
+        let message = format!(
+            "{} {:?} has no minimum_os_version; rules_apple needs one (using {})",
+            call.name, target, version
+        );
+        issues.push((
+            BuildIssue::MissingMinimumOsVersion { target, version },
+            message,
+        ));
+    }
+
+    issues
+}
+
+// Add `minimum_os_version = "..."` to the bundling rule named `target`
+pub fn fix_minimum_os_version(content: &str, target: &str, version: &str) -> String {
+    let tokens = tokenize(content);
+    let call = top_level_calls(&tokens).into_iter().find(|call| {
+        bundle_platform(call.name).is_some()
+            && call.keyword(&tokens, "minimum_os_version").is_none()
+            && call.target_name(&tokens).as_deref() == Some(target)
+    });
+
+    match call {
+        Some(call) => insert_after_name(
+            content,
+            &tokens,
+            &call,
+            &format!("minimum_os_version = {}", quote(version)),
+        ),
+        None => content.to_string(),
+    }
+}
+
+// Flag bundling rules whose `bundle_id` is missing or empty, without which
+// rules_apple fails or makes one up. The fix uses `prefix` followed by the
+// target name.
+pub fn check_bundle_id(content: &str, prefix: &str) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues = Vec::new();
+
+    for call in top_level_calls(&tokens) {
+        if bundle_platform(call.name).is_none() || has_bundle_id(&tokens, &call) {
+            continue;
+        }
+        let Some(target) = call.target_name(&tokens) else {
+            continue;
+        };
+        let bundle_id = default_bundle_id(prefix, &target);
+
+        let message = format!(
+            "{} {:?} has no bundle_id; rules_apple needs one (using {:?})",
+            call.name, target, bundle_id
+        );
+        issues.push((BuildIssue::MissingBundleId { target, bundle_id }, message));
+    }
+
+    issues
+}
+
+// Set the `bundle_id` of the bundling rule named `target`, replacing an
+// empty one
+pub fn fix_bundle_id(content: &str, target: &str, bundle_id: &str) -> String {
+    let tokens = tokenize(content);
+    let call = top_level_calls(&tokens).into_iter().find(|call| {
+        bundle_platform(call.name).is_some()
+            && !has_bundle_id(&tokens, call)
+            && call.target_name(&tokens).as_deref() == Some(target)
+    });
+    let Some(call) = call else {
+        return content.to_string();
+    };
+
+    match call.keyword(&tokens, "bundle_id") {
+        Some(argument) => {
+            let range = argument.byte_range(&tokens);
+            format!(
+                "{}{}{}",
+                &content[..range.start],
+                quote(bundle_id),
+                &content[range.end..]
+            )
+        }
+        None => insert_after_name(
+            content,
+            &tokens,
+            &call,
+            &format!("bundle_id = {}", quote(bundle_id)),
+        ),
+    }
+}
+
+// Whether `call` sets a `bundle_id` other than an empty string; one computed
+// by an expression is taken to be set
+fn has_bundle_id(tokens: &[Token<'_>], call: &Call<'_>) -> bool {
+    let Some(argument) = call.keyword(tokens, "bundle_id") else {
+        return false;
+    };
+    match &tokens[argument.value] {
+        [token] if token.kind == TokenKind::String => token
+            .string_value()
+            .is_some_and(|value| !value.trim().is_empty()),
+        _ => true,
+    }
+}
+
+// `prefix.target`, with the characters a bundle ID can't hold replaced by `-`
+fn default_bundle_id(prefix: &str, target: &str) -> String {
+    let name: String = target
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{}.{}", prefix.trim_end_matches('.'), name)
+}
+
+fn bundle_platform(rule: &str) -> Option<&'static str> {
+    APPLE_BUNDLE_RULES
+        .iter()
+        .find(|(name, _)| *name == rule)
+        .map(|(_, platform)| *platform)
+}
+
+fn default_minimum_os_version(platform: &str) -> Option<&'static str> {
+    DEFAULT_MINIMUM_OS_VERSIONS
+        .iter()
+        .find(|(name, _)| *name == platform)
+        .map(|(_, version)| *version)
+}
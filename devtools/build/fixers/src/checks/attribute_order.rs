@@ -0,0 +1,185 @@
+//! Check that rule attributes appear in the canonical (buildifier) order.
+
+use std::ops::Range;
+
+use crate::issue::BuildIssue;
+use crate::starlark::calls::{top_level_calls, Call};
+use crate::starlark::tokenizer::{tokenize, Token, TokenKind};
+
+/// Canonical attribute order. Attributes not listed here go between
+/// `linkopts` and `testonly`, keeping their relative order.
+pub const ATTRIBUTE_ORDER: &[&str] = &[
+    "name",
+    "module_name",
+    "srcs",
+    "hdrs",
+    "deps",
+    "data",
+    "copts",
+    "linkopts",
+    "testonly",
+    "visibility",
+];
+
+/// Position of `key` in the canonical attribute order.
+pub fn attribute_rank(key: &str) -> usize {
+    let unknown = ATTRIBUTE_ORDER
+        .iter()
+        .position(|known| *known == "testonly")
+        .unwrap_or(ATTRIBUTE_ORDER.len());
+    // Known attributes are spaced out so unknown ones fit in between
+    match ATTRIBUTE_ORDER.iter().position(|known| *known == key) {
+        Some(position) => position * 2,
+        None => unknown * 2 - 1,
+    }
+}
+
+// Report the rules whose attributes are out of canonical order
+pub fn check_attribute_order(content: &str) -> Option<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let targets: Vec<String> = top_level_calls(&tokens)
+        .iter()
+        .filter(|call| reordered_arguments(content, &tokens, call).is_some())
+        .map(|call| {
+            call.target_name(&tokens)
+                .map_or_else(|| format!("{}()", call.name), |name| format!("{:?}", name))
+        })
+        .collect();
+
+    (!targets.is_empty()).then(|| {
+        (
+            BuildIssue::UnorderedAttributes,
+            format!(
+                "attributes of {} are not in canonical order",
+                targets.join(", ")
+            ),
+        )
+    })
+}
+
+// Reorder the attributes of every rule into canonical order
+pub fn fix_sorted_attributes(content: &str) -> String {
+    let tokens = tokenize(content);
+    let mut fixed = content.to_string();
+
+    // Replace from the end so earlier byte offsets stay valid
+    for call in top_level_calls(&tokens).iter().rev() {
+        if let Some((range, replacement)) = reordered_arguments(content, &tokens, call) {
+            fixed.replace_range(range, &replacement);
+        }
+    }
+
+    fixed
+}
+
+// One keyword argument, as the byte range of its text
+struct Attribute<'a> {
+    key: &'a str,
+    /// From the key to the end of the value.
+    text: Range<usize>,
+    /// Index of the comma after the value, if any.
+    comma: Option<usize>,
+}
+
+// The byte range holding the call's arguments and its reordered text, or
+// `None` if the attributes are already in order or the layout isn't one we
+// can safely rearrange
+fn reordered_arguments(
+    content: &str,
+    tokens: &[Token<'_>],
+    call: &Call<'_>,
+) -> Option<(Range<usize>, String)> {
+    if call.name == "load" {
+        return None;
+    }
+    let attributes = keyword_attributes(tokens, call)?;
+    let in_order = attributes
+        .windows(2)
+        .all(|pair| attribute_rank(pair[0].key) <= attribute_rank(pair[1].key));
+    if in_order {
+        return None;
+    }
+
+    let mut sorted: Vec<usize> = (0..attributes.len()).collect();
+    sorted.sort_by_key(|&i| attribute_rank(attributes[i].key));
+
+    let open = &tokens[call.open];
+    let close = &tokens[call.close];
+    if open.line == close.line {
+        let has_comments = tokens[call.open..call.close]
+            .iter()
+            .any(|t| t.kind == TokenKind::Comment);
+        if has_comments {
+            return None;
+        }
+        let texts: Vec<&str> = sorted
+            .iter()
+            .map(|&i| &content[attributes[i].text.clone()])
+            .collect();
+        return Some((open.end()..close.start, texts.join(", ")));
+    }
+
+    // Multi-line calls: every attribute starts its own line, and the lines
+    // after the previous attribute (comments included) move with it
+    let line_start = |offset: usize| content[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = |offset: usize| {
+        content[offset..]
+            .find('\n')
+            .map_or(content.len(), |i| offset + i + 1)
+    };
+    let region_start = line_end(open.end());
+    let region_end = line_start(close.start);
+    if region_start > region_end || !content[region_end..close.start].trim().is_empty() {
+        return None;
+    }
+
+    let mut blocks = Vec::with_capacity(attributes.len());
+    let mut block_start = region_start;
+    for attribute in &attributes {
+        let first_on_line = content[line_start(attribute.text.start)..attribute.text.start]
+            .trim()
+            .is_empty();
+        let value_end = attribute
+            .comma
+            .map_or(attribute.text.end, |c| tokens[c].end());
+        if !first_on_line || attribute.text.start < block_start {
+            return None;
+        }
+        let block_end = line_end(value_end);
+        let mut block = content[block_start..block_end].to_string();
+        if attribute.comma.is_none() {
+            block.insert(attribute.text.end - block_start, ',');
+        }
+        blocks.push(block);
+        block_start = block_end;
+    }
+    if block_start > region_end {
+        return None;
+    }
+
+    let mut reordered: String = sorted.iter().map(|&i| blocks[i].as_str()).collect();
+    reordered.push_str(&content[block_start..region_end]);
+    Some((region_start..region_end, reordered))
+}
+
+// The call's arguments, or `None` if any is positional
+fn keyword_attributes<'a>(tokens: &[Token<'a>], call: &Call<'a>) -> Option<Vec<Attribute<'a>>> {
+    call.arguments(tokens)
+        .into_iter()
+        .map(|argument| {
+            let key = argument.key?;
+            let key_index = (call.open + 1..argument.value.start)
+                .rev()
+                .find(|&i| tokens[i].kind == TokenKind::Ident && tokens[i].text == key)?;
+            let value_end = argument.value.end.checked_sub(1)?;
+            let comma = (argument.value.end..call.close)
+                .find(|&i| tokens[i].kind != TokenKind::Comment)
+                .filter(|&i| tokens[i].kind == TokenKind::Comma);
+            Some(Attribute {
+                key,
+                text: tokens[key_index].start..tokens[value_end].end(),
+                comma,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,448 @@
+//! Checks on the values of individual rule attributes.
+
+use std::ops::Range;
+
+use crate::deprecated_attrs::{deprecation, expand_replacement};
+use crate::issue::BuildIssue;
+use crate::starlark::ast::{AttrValue, BuildFile};
+use crate::starlark::calls::{
+    append_to_list, element_removal_range, insert_after_name, is_list, line_indent,
+    span_removal_range, top_level_calls, Call,
+};
+use crate::starlark::tokenizer::{tokenize, Token, TokenKind};
+
+// swift_test targets that don't set testonly = True
+fn tests_without_testonly<'a>(tokens: &[Token<'a>]) -> Vec<Call<'a>> {
+    top_level_calls(tokens)
+        .into_iter()
+        .filter(|call| call.name == "swift_test")
+        .filter(|call| match call.keyword(tokens, "testonly") {
+            Some(argument) => {
+                !matches!(&tokens[argument.value.clone()], [token] if token.is_ident("True"))
+            }
+            None => true,
+        })
+        .collect()
+}
+
+pub fn check_testonly(content: &str) -> Option<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let names: Vec<String> = tests_without_testonly(&tokens)
+        .iter()
+        .map(|call| call.target_name(&tokens).unwrap_or_default())
+        .collect();
+    if names.is_empty() {
+        return None;
+    }
+
+    Some((
+        BuildIssue::MissingTestonly,
+        format!("swift_test without testonly = True: {}", names.join(", ")),
+    ))
+}
+
+// Set testonly = True on every swift_test, replacing any other value. Edits
+// are applied last-to-first so earlier offsets stay valid.
+pub fn fix_testonly(content: &str) -> String {
+    let tokens = tokenize(content);
+    let mut new_content = content.to_string();
+
+    for call in tests_without_testonly(&tokens).iter().rev() {
+        new_content = match call.keyword(&tokens, "testonly") {
+            Some(argument) => {
+                let range = argument.byte_range(&tokens);
+                format!(
+                    "{}True{}",
+                    &new_content[..range.start],
+                    &new_content[range.end..]
+                )
+            }
+            None => {
+                let (head, tail) = new_content.split_at(tokens[call.close].end());
+                let head = insert_after_name(head, &tokens, call, "testonly = True");
+                format!("{}{}", head, tail)
+            }
+        };
+    }
+
+    new_content
+}
+
+// swift_library targets with srcs and generated_header_name, and the token
+// range of their generates_header value if they set one
+fn header_generating_libraries<'a>(tokens: &[Token<'a>]) -> Vec<(Call<'a>, Option<Range<usize>>)> {
+    top_level_calls(tokens)
+        .into_iter()
+        .filter(|call| call.name == "swift_library")
+        .filter(|call| {
+            call.keyword(tokens, "srcs").is_some()
+                && call.keyword(tokens, "generated_header_name").is_some()
+        })
+        .map(|call| {
+            let generates_header = call
+                .keyword(tokens, "generates_header")
+                .map(|argument| argument.value);
+            (call, generates_header)
+        })
+        .collect()
+}
+
+pub fn check_generates_header_consistency(content: &str) -> Option<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let names: Vec<String> = header_generating_libraries(&tokens)
+        .iter()
+        .filter(|(_, generates_header)| generates_header.is_none())
+        .map(|(call, _)| call.target_name(&tokens).unwrap_or_default())
+        .collect();
+    if names.is_empty() {
+        return None;
+    }
+
+    Some((
+        BuildIssue::MissingGeneratesHeader,
+        format!(
+            "generated_header_name is set without generates_header = True: {}",
+            names.join(", ")
+        ),
+    ))
+}
+
+pub fn check_generates_header_conflict(content: &str) -> Option<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let names: Vec<String> = header_generating_libraries(&tokens)
+        .iter()
+        .filter(|(_, generates_header)| {
+            matches!(generates_header, Some(range) if matches!(&tokens[range.clone()], [token] if token.is_ident("False")))
+        })
+        .map(|(call, _)| call.target_name(&tokens).unwrap_or_default())
+        .collect();
+    if names.is_empty() {
+        return None;
+    }
+
+    Some((
+        BuildIssue::GeneratesHeaderConflict,
+        format!(
+            "generated_header_name is set but generates_header = False; remove one of them: {}",
+            names.join(", ")
+        ),
+    ))
+}
+
+// Add generates_header = True wherever generated_header_name is set without it
+pub fn fix_generates_header_consistency(content: &str) -> String {
+    let tokens = tokenize(content);
+    let mut new_content = content.to_string();
+
+    for (call, _) in header_generating_libraries(&tokens)
+        .iter()
+        .rev()
+        .filter(|(_, generates_header)| generates_header.is_none())
+    {
+        let (head, tail) = new_content.split_at(tokens[call.close].end());
+        let head = insert_after_name(head, &tokens, call, "generates_header = True");
+        new_content = format!("{}{}", head, tail);
+    }
+
+    new_content
+}
+
+// visibility (or package default_visibility) values that are a single bare
+// string literal, as token indices
+fn bare_string_visibilities(tokens: &[Token<'_>]) -> Vec<usize> {
+    top_level_calls(tokens)
+        .iter()
+        .flat_map(|call| call.arguments(tokens))
+        .filter(|argument| matches!(argument.key, Some("visibility" | "default_visibility")))
+        .filter(|argument| {
+            argument.value.len() == 1 && tokens[argument.value.start].kind == TokenKind::String
+        })
+        .map(|argument| argument.value.start)
+        .collect()
+}
+
+pub fn check_visibility_format(content: &str) -> Option<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let lines: Vec<String> = bare_string_visibilities(&tokens)
+        .iter()
+        .map(|&index| tokens[index].line.to_string())
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+
+    Some((
+        BuildIssue::IncorrectVisibilityFormat,
+        format!(
+            "visibility must be a list, not a string (line {})",
+            lines.join(", ")
+        ),
+    ))
+}
+
+// Wrap bare string visibility values in a list
+pub fn fix_visibility_format(content: &str) -> String {
+    let tokens = tokenize(content);
+    let mut new_content = content.to_string();
+
+    for &index in bare_string_visibilities(&tokens).iter().rev() {
+        let token = &tokens[index];
+        new_content.replace_range(token.start..token.end(), &format!("[{}]", token.text));
+    }
+
+    new_content
+}
+
+// `-I` copts of swift_library targets that point at an absolute path, as
+// (target, flag token index). `-I$(GENDIR)/...` and relative paths are fine.
+fn absolute_include_copts(tokens: &[Token<'_>]) -> Vec<(String, usize)> {
+    top_level_calls(tokens)
+        .iter()
+        .filter(|call| call.name == "swift_library")
+        .flat_map(|call| {
+            let target = call.target_name(tokens).unwrap_or_default();
+            let copts = call
+                .keyword(tokens, "copts")
+                .map_or(0..0, |argument| argument.value);
+            copts
+                .filter(|&i| {
+                    tokens[i]
+                        .string_value()
+                        .is_some_and(|copt| copt.starts_with("-I/"))
+                })
+                .map(move |i| (target.clone(), i))
+        })
+        .collect()
+}
+
+pub fn check_absolute_include_paths(content: &str) -> Option<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let flags: Vec<String> = absolute_include_copts(&tokens)
+        .iter()
+        .map(|(target, index)| format!("{} ({})", tokens[*index].text, target))
+        .collect();
+    if flags.is_empty() {
+        return None;
+    }
+
+    Some((
+        BuildIssue::AbsoluteIncludePath,
+        format!(
+            "copts include absolute paths outside the sandbox: {}",
+            flags.join(", ")
+        ),
+    ))
+}
+
+// Remove each absolute `-I` copt (with the `-Xcc` that passes it to clang)
+// and leave a comment above `copts` pointing to a cc_library dep instead
+pub fn fix_absolute_include_paths(content: &str) -> String {
+    let mut new_content = content.to_string();
+
+    loop {
+        let tokens = tokenize(&new_content);
+        let Some((_, index)) = absolute_include_copts(&tokens).into_iter().next() else {
+            return new_content;
+        };
+        let flag = tokens[index].string_value().unwrap_or_default();
+        let xcc = (index > 2
+            && tokens[index - 1].kind == TokenKind::Comma
+            && tokens[index - 2].string_value().as_deref() == Some("-Xcc"))
+        .then(|| tokens[index - 2].start);
+        let copts_line = (0..index)
+            .rev()
+            .find(|&i| tokens[i].is_ident("copts"))
+            .map_or(0, |i| tokens[i].start);
+
+        let (start, end) = element_removal_range(&new_content, &tokens, index);
+        new_content.replace_range(start..end, "");
+        if let Some(xcc_start) = xcc {
+            let tokens = tokenize(&new_content);
+            if let Some(xcc) = tokens.iter().position(|t| t.start == xcc_start) {
+                let (start, end) = element_removal_range(&new_content, &tokens, xcc);
+                new_content.replace_range(start..end, "");
+            }
+        }
+
+        let line_start = new_content[..copts_line].rfind('\n').map_or(0, |i| i + 1);
+        let indent = line_indent(&new_content, copts_line).to_string();
+        new_content.insert_str(
+            line_start,
+            &format!(
+                "{}# umbra-fix: removed {} (absolute include paths break hermetic builds); depend on a cc_library that provides the headers instead\n",
+                indent, flag
+            ),
+        );
+    }
+}
+
+// Flag each rule attribute that DEPRECATED_ATTRS lists, with the replacement
+// expanded for the attribute's values
+pub fn check_deprecated_attributes(file: &BuildFile) -> Vec<(BuildIssue, String)> {
+    let mut issues = Vec::new();
+    for rule in &file.rules {
+        let Some(target) = rule.name() else {
+            continue;
+        };
+        for attr in &rule.attrs {
+            let values: Vec<String> = match &attr.value {
+                AttrValue::List(elements) => elements
+                    .iter()
+                    .filter_map(AttrValue::as_str)
+                    .map(String::from)
+                    .collect(),
+                _ => Vec::new(),
+            };
+            issues.extend(deprecated_attribute(
+                &rule.rule_name,
+                target,
+                &attr.key,
+                &values,
+            ));
+        }
+    }
+    issues
+}
+
+// Like check_deprecated_attributes, from the tokens of `content`, for files
+// the AST can't parse
+pub fn check_deprecated_attributes_in_content(content: &str) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues = Vec::new();
+    for call in top_level_calls(&tokens) {
+        let Some(target) = call.target_name(&tokens) else {
+            continue;
+        };
+        for argument in call.arguments(&tokens) {
+            let Some(key) = argument.key else {
+                continue;
+            };
+            // Only the strings of a plain list are expanded, as from the AST
+            let value = &tokens[argument.value];
+            let values: Vec<String> = match value {
+                [open, .., close]
+                    if open.kind == TokenKind::LBracket && close.kind == TokenKind::RBracket =>
+                {
+                    value.iter().filter_map(Token::string_value).collect()
+                }
+                _ => Vec::new(),
+            };
+            issues.extend(deprecated_attribute(call.name, &target, key, &values));
+        }
+    }
+    issues
+}
+
+// The issue for `attribute` of the `rule` named `target`, if DEPRECATED_ATTRS
+// lists it, with the replacement expanded for its string `values`
+fn deprecated_attribute(
+    rule: &str,
+    target: &str,
+    attribute: &str,
+    values: &[String],
+) -> Option<(BuildIssue, String)> {
+    let (since, replacement) = deprecation(rule, attribute)?;
+    let replacement = expand_replacement(replacement, values);
+    let message = format!(
+        "{} {:?} sets {}, which rules_swift {} removed; use {}",
+        rule, target, attribute, since, replacement
+    );
+    Some((
+        BuildIssue::DeprecatedAttribute {
+            target: target.to_string(),
+            attribute: attribute.to_string(),
+            since: since.to_string(),
+            replacement,
+        },
+        message,
+    ))
+}
+
+// Remove `attribute` from the rule named `target`, leaving `comment` above
+// the rule so the migration isn't lost
+pub fn fix_deprecated_attribute(
+    content: &str,
+    target: &str,
+    attribute: &str,
+    comment: &str,
+) -> String {
+    let tokens = tokenize(content);
+    let found = top_level_calls(&tokens).into_iter().find_map(|call| {
+        let argument = call.keyword(&tokens, attribute)?;
+        (call.target_name(&tokens).as_deref() == Some(target)).then_some((call, argument))
+    });
+    let Some((call, argument)) = found else {
+        return content.to_string();
+    };
+
+    // The key and `=` precede the value
+    let (start, end) = span_removal_range(
+        content,
+        &tokens,
+        argument.value.start - 2,
+        argument.value.end - 1,
+    );
+    let rule_start = tokens[call.open - 1].start;
+    let line_start = content[..rule_start].rfind('\n').map_or(0, |i| i + 1);
+    format!(
+        "{}{}# {}\n{}{}",
+        &content[..line_start],
+        line_indent(content, rule_start),
+        comment,
+        &content[line_start..start],
+        &content[end..]
+    )
+}
+
+/// The linker flag that removes unreachable code and data from binaries.
+pub const DEAD_STRIP_LINKOPT: &str = "-dead_strip";
+
+// Flag each swift_binary that neither sets `strip_swift_symbols` nor passes
+// a dead-stripping flag in `linkopts`. Binaries whose linkopts aren't a list
+// literal are skipped, as the flag can't be looked for or appended.
+pub fn check_dead_strip(content: &str) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    top_level_calls(&tokens)
+        .into_iter()
+        .filter(|call| call.name == "swift_binary")
+        .filter(|call| call.keyword(&tokens, "strip_swift_symbols").is_none())
+        .filter(|call| match call.keyword(&tokens, "linkopts") {
+            Some(linkopts) => {
+                is_list(&tokens, &linkopts)
+                    && !tokens[linkopts.value]
+                        .iter()
+                        .filter_map(Token::string_value)
+                        .any(|opt| opt.contains("dead_strip"))
+            }
+            None => true,
+        })
+        .filter_map(|call| {
+            let target = call.target_name(&tokens)?;
+            let message = format!(
+                "swift_binary {:?} isn't dead-stripped; add {:?} to its linkopts",
+                target, DEAD_STRIP_LINKOPT
+            );
+            Some((BuildIssue::MissingStrip { target }, message))
+        })
+        .collect()
+}
+
+// Add the dead-strip flag to the linkopts of the swift_binary named `target`,
+// creating the attribute if it has none
+pub fn fix_dead_strip(content: &str, target: &str) -> String {
+    let tokens = tokenize(content);
+    let call = top_level_calls(&tokens)
+        .into_iter()
+        .filter(|call| call.name == "swift_binary")
+        .find(|call| call.target_name(&tokens).as_deref() == Some(target));
+    let Some(call) = call else {
+        return content.to_string();
+    };
+
+    let flag = format!("\"{}\"", DEAD_STRIP_LINKOPT);
+    match call.keyword(&tokens, "linkopts") {
+        Some(linkopts) => append_to_list(content, &tokens, &linkopts, &flag)
+            .unwrap_or_else(|| content.to_string()),
+        None => insert_after_name(content, &tokens, &call, &format!("linkopts = [{}]", flag)),
+    }
+}
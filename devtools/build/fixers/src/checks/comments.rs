@@ -0,0 +1,114 @@
+//! Check for blocks of commented-out rules, which are usually dead code.
+//! Whether one is worth keeping takes human judgment, so they aren't fixed;
+//! `umbra-fix --remove-comment-block LINE FILE` deletes one.
+
+use crate::issue::BuildIssue;
+use crate::starlark::tokenizer::{tokenize, TokenKind};
+
+/// Comment blocks longer than this many lines are checked for rules.
+pub const MAX_COMMENT_BLOCK_LINES: usize = 5;
+
+// Attributes whose assignment in a comment gives away a commented-out rule
+const RULE_ATTRIBUTES: &[&str] = &[
+    "name",
+    "srcs",
+    "deps",
+    "hdrs",
+    "data",
+    "module_name",
+    "visibility",
+];
+
+/// A run of consecutive lines holding nothing but a comment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommentBlock {
+    /// 1-based line of the first comment.
+    pub first_line: usize,
+    /// 1-based line of the last comment.
+    pub last_line: usize,
+}
+
+// Flag each comment block of more than MAX_COMMENT_BLOCK_LINES lines that
+// assigns rule attributes, such as `#     name = "Core",`
+pub fn check_commented_out_rules(content: &str) -> Vec<(BuildIssue, String)> {
+    comment_blocks(content)
+        .into_iter()
+        .filter(|block| block.last_line - block.first_line + 1 > MAX_COMMENT_BLOCK_LINES)
+        .filter(|block| {
+            block_lines(content, block).any(|line| {
+                let code = line.trim_start().trim_start_matches('#').trim_start();
+                RULE_ATTRIBUTES.iter().any(|attribute| {
+                    code.strip_prefix(attribute)
+                        .is_some_and(|rest| rest.trim_start().starts_with('='))
+                })
+            })
+        })
+        .map(|block| {
+            let message = format!(
+                "lines {}-{} are a commented-out rule; delete them if they are dead code \
+                 (umbra-fix --remove-comment-block {} <file>)",
+                block.first_line, block.last_line, block.first_line
+            );
+            (
+                BuildIssue::CommentedOutRule {
+                    line: block.first_line,
+                },
+                message,
+            )
+        })
+        .collect()
+}
+
+/// Remove the comment block starting at `line`, or `None` if no block
+/// starts there. When blank lines separate it from the code around it, one
+/// of them goes too.
+pub fn remove_comment_block(content: &str, line: usize) -> Option<String> {
+    let block = comment_blocks(content)
+        .into_iter()
+        .find(|block| block.first_line == line)?;
+
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let is_blank = |index: usize| lines.get(index).is_some_and(|line| line.trim().is_empty());
+    let mut start = block.first_line - 1;
+    let mut end = block.last_line;
+    let blank_before = start == 0 || is_blank(start - 1);
+    if blank_before && is_blank(end) {
+        end += 1;
+    } else if blank_before && start > 0 && end == lines.len() {
+        start -= 1;
+    }
+    Some(
+        lines[..start]
+            .iter()
+            .chain(&lines[end..])
+            .copied()
+            .collect(),
+    )
+}
+
+// The runs of consecutive lines that hold only a comment
+fn comment_blocks(content: &str) -> Vec<CommentBlock> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut blocks: Vec<CommentBlock> = Vec::new();
+    for token in tokenize(content) {
+        if token.kind != TokenKind::Comment || !lines[token.line - 1].trim_start().starts_with('#')
+        {
+            continue;
+        }
+        match blocks.last_mut() {
+            Some(block) if block.last_line + 1 == token.line => block.last_line = token.line,
+            _ => blocks.push(CommentBlock {
+                first_line: token.line,
+                last_line: token.line,
+            }),
+        }
+    }
+    blocks
+}
+
+fn block_lines<'a>(content: &'a str, block: &CommentBlock) -> impl Iterator<Item = &'a str> {
+    content
+        .lines()
+        .skip(block.first_line - 1)
+        .take(block.last_line - block.first_line + 1)
+}
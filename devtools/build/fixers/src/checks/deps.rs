@@ -0,0 +1,407 @@
+//! Checks on `deps`: labels none of a target's Swift sources import,
+//! labels listed twice, explicitly empty lists, and old `//third_party`
+//! labels of external repositories.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::issue::BuildIssue;
+use crate::label_resolver::{resolve_label, AbsoluteLabel};
+use crate::sources::matching_swift_files;
+use crate::starlark::calls::{element_removal_range, span_removal_range, top_level_calls, Call};
+use crate::starlark::tokenizer::{quote, tokenize, Token, TokenKind};
+use crate::swift_imports::parse_swift_imports;
+
+/// Labels under this prefix are Apple system frameworks, which are only
+/// pruned with `--prune-system-deps`.
+pub const SYSTEM_LABEL_PREFIX: &str = "//third_party/apple";
+
+// Identifiers that may appear in a `srcs` value we know how to evaluate
+const SRCS_IDENTS: &[&str] = &[
+    "glob",
+    "exclude",
+    "allow_empty",
+    "exclude_directories",
+    "True",
+    "False",
+];
+
+// Flag deps whose module (per import_map.toml) no source of the target
+// imports. Targets whose sources can't be determined are skipped, as are
+// labels missing from the import map. Labels are compared once resolved,
+// so `:Core` in Sources/Core matches an import map entry for `//Sources/Core`.
+pub fn check_unused_dependencies(
+    content: &str,
+    package_dir: &Path,
+    config: &Config,
+) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues = Vec::new();
+    let import_map: BTreeMap<AbsoluteLabel, &String> = config
+        .import_map
+        .iter()
+        .filter_map(|(label, module)| {
+            let label = resolve_label(label, "", &config.root_dir).ok()?;
+            Some((label, module))
+        })
+        .collect();
+    let package = package_dir.to_string_lossy();
+
+    for call in top_level_calls(&tokens) {
+        if !call.name.starts_with("swift_") {
+            continue;
+        }
+        let Some(target) = call.target_name(&tokens) else {
+            continue;
+        };
+        let Some(imported) = imported_modules(&tokens, &call, package_dir) else {
+            continue;
+        };
+
+        for (label, _) in deps_labels(&tokens, &call) {
+            if label.starts_with(SYSTEM_LABEL_PREFIX) && !config.prune_system_deps {
+                continue;
+            }
+            let module = match resolve_label(&label, &package, &config.root_dir) {
+                Ok(resolved) => import_map.get(&resolved).copied(),
+                Err(_) => config.import_map.get(&label),
+            };
+            let Some(module) = module else {
+                continue;
+            };
+            if imported.contains(module) {
+                continue;
+            }
+
+            issues.push((
+                BuildIssue::UnusedDependency {
+                    target: target.clone(),
+                    label: label.clone(),
+                },
+                format!(
+                    "{:?} depends on {} but none of its sources import {}",
+                    target, label, module
+                ),
+            ));
+        }
+    }
+
+    issues
+}
+
+// Remove `label` from the deps of the rule named `target`
+pub fn fix_unused_dependency(content: &str, target: &str, label: &str) -> String {
+    let tokens = tokenize(content);
+    let call = top_level_calls(&tokens)
+        .into_iter()
+        .find(|call| call.target_name(&tokens).as_deref() == Some(target));
+    let Some(call) = call else {
+        return content.to_string();
+    };
+    let Some((_, index)) = deps_labels(&tokens, &call)
+        .into_iter()
+        .find(|(value, _)| value == label)
+    else {
+        return content.to_string();
+    };
+
+    let (start, end) = element_removal_range(content, &tokens, index);
+    format!("{}{}", &content[..start], &content[end..])
+}
+
+// Flag each `deps` label that resolves to the same target as an earlier one
+// of the list, such as `:Core` after `//Sources/Core` in Sources/Core.
+// Labels that can't be resolved are compared as written.
+pub fn check_repeated_deps(
+    content: &str,
+    package: &str,
+    workspace_root: &Path,
+) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues = Vec::new();
+    for call in top_level_calls(&tokens) {
+        let Some(target) = call.target_name(&tokens) else {
+            continue;
+        };
+        let mut seen: BTreeMap<String, String> = BTreeMap::new();
+        for (label, _) in deps_labels(&tokens, &call) {
+            let resolved = resolve_label(&label, package, workspace_root)
+                .map(|resolved| resolved.to_string())
+                .unwrap_or_else(|_| label.clone());
+            let Some(first) = seen.get(&resolved) else {
+                seen.insert(resolved, label);
+                continue;
+            };
+            let message = if *first == label {
+                format!("{:?} lists {} in deps more than once", target, label)
+            } else {
+                format!(
+                    "{:?} lists {} in deps, which is the same target as {}",
+                    target, label, first
+                )
+            };
+            issues.push((
+                BuildIssue::RepeatedDep {
+                    target: target.clone(),
+                    label,
+                    first: first.clone(),
+                },
+                message,
+            ));
+        }
+    }
+    issues
+}
+
+// Remove the later of `first` and `label` from the deps of the rule named
+// `target`. The list may have been sorted since the check, so `label` may
+// now come first.
+pub fn fix_repeated_dep(content: &str, target: &str, label: &str, first: &str) -> String {
+    let tokens = tokenize(content);
+    let call = top_level_calls(&tokens)
+        .into_iter()
+        .find(|call| call.target_name(&tokens).as_deref() == Some(target));
+    let Some(call) = call else {
+        return content.to_string();
+    };
+    let labels = deps_labels(&tokens, &call);
+    let Some(kept) = labels.iter().position(|(value, _)| value == first) else {
+        return content.to_string();
+    };
+    let Some(repeated) = labels
+        .iter()
+        .enumerate()
+        .position(|(i, (value, _))| i != kept && value == label)
+    else {
+        return content.to_string();
+    };
+
+    let (_, index) = labels[kept.max(repeated)];
+    let (start, end) = element_removal_range(content, &tokens, index);
+    format!("{}{}", &content[..start], &content[end..])
+}
+
+// Flag each rule that sets `deps = []`, which is the default. A list holding
+// only comments is left alone, so the comments aren't lost.
+pub fn check_empty_deps(content: &str) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    top_level_calls(&tokens)
+        .into_iter()
+        .filter(|call| {
+            call.keyword(&tokens, "deps")
+                .is_some_and(|deps| is_empty_list(&tokens, deps.value))
+        })
+        .filter_map(|call| {
+            let target = call.target_name(&tokens)?;
+            let message = format!(
+                "{} {:?} sets deps = [], which is the default",
+                call.name, target
+            );
+            Some((BuildIssue::EmptyDepsAttribute { target }, message))
+        })
+        .collect()
+}
+
+// Remove `deps = []` from the rule named `target`, with its line if it has
+// one to itself, or else with the comma that separates it
+pub fn fix_empty_deps(content: &str, target: &str) -> String {
+    let tokens = tokenize(content);
+    let deps = top_level_calls(&tokens)
+        .into_iter()
+        .filter(|call| call.target_name(&tokens).as_deref() == Some(target))
+        .find_map(|call| call.keyword(&tokens, "deps"))
+        .filter(|deps| is_empty_list(&tokens, deps.value.clone()));
+    let Some(deps) = deps else {
+        return content.to_string();
+    };
+
+    // The key and `=` precede the value
+    let (start, end) =
+        span_removal_range(content, &tokens, deps.value.start - 2, deps.value.end - 1);
+    format!("{}{}", &content[..start], &content[end..])
+}
+
+// Flag each `deps` label, in a list or a select() branch, that lies under a
+// package `rewrites` maps to an external repository
+pub fn check_external_labels(
+    content: &str,
+    rewrites: &BTreeMap<String, String>,
+) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues = Vec::new();
+    for call in top_level_calls(&tokens) {
+        let Some(target) = call.target_name(&tokens) else {
+            continue;
+        };
+        let Some(deps) = call.keyword(&tokens, "deps") else {
+            continue;
+        };
+        let labels: BTreeSet<String> = label_tokens(&tokens, deps.value)
+            .into_iter()
+            .filter_map(Token::string_value)
+            .collect();
+        for label in labels {
+            let Some(replacement) = rewrite_external_label(&label, rewrites) else {
+                continue;
+            };
+            let message = format!(
+                "{:?} depends on {}, which is now the external label {}",
+                target, label, replacement
+            );
+            issues.push((
+                BuildIssue::AbsoluteExternalLabel {
+                    target: target.clone(),
+                    label,
+                    replacement,
+                },
+                message,
+            ));
+        }
+    }
+    issues
+}
+
+// Replace every `label` in the deps of the rule named `target` with
+// `replacement`
+pub fn fix_external_label(content: &str, target: &str, label: &str, replacement: &str) -> String {
+    let tokens = tokenize(content);
+    let Some(deps) = top_level_calls(&tokens)
+        .into_iter()
+        .filter(|call| call.target_name(&tokens).as_deref() == Some(target))
+        .find_map(|call| call.keyword(&tokens, "deps"))
+    else {
+        return content.to_string();
+    };
+
+    let mut fixed = content.to_string();
+    for token in label_tokens(&tokens, deps.value).into_iter().rev() {
+        if token.string_value().as_deref() == Some(label) {
+            fixed.replace_range(token.start..token.end(), &quote(replacement));
+        }
+    }
+    fixed
+}
+
+// The string tokens in `value` that are labels: list elements and the
+// values of select() branches, but not the branches' condition keys or
+// keyword arguments such as `no_match_error`
+fn label_tokens<'t, 'a>(tokens: &'t [Token<'a>], value: Range<usize>) -> Vec<&'t Token<'a>> {
+    let code: Vec<&Token<'a>> = tokens[value]
+        .iter()
+        .filter(|token| token.kind != TokenKind::Comment)
+        .collect();
+    code.iter()
+        .enumerate()
+        .filter(|(index, token)| {
+            token.kind == TokenKind::String
+                && code
+                    .get(index + 1)
+                    .is_none_or(|next| next.kind != TokenKind::Colon)
+                && index
+                    .checked_sub(1)
+                    .is_none_or(|previous| code[previous].kind != TokenKind::Equals)
+        })
+        .map(|(_, token)| *token)
+        .collect()
+}
+
+/// The external label `label` becomes under `rewrites`, which map packages
+/// such as `//third_party/foo` to repositories such as `@foo` (or a package
+/// in one, `@foo//lib`). Subpackages move along: `//third_party/foo/bar:baz`
+/// becomes `@foo//bar:baz`. The longest matching package wins.
+pub fn rewrite_external_label(label: &str, rewrites: &BTreeMap<String, String>) -> Option<String> {
+    let path = label.strip_prefix("//")?;
+    let (package, target) = match path.split_once(':') {
+        Some((package, target)) => (package, target),
+        None => (path, path.rsplit('/').next()?),
+    };
+
+    let (from, to) = rewrites
+        .iter()
+        .filter_map(|(from, to)| {
+            let from = from.strip_prefix("//")?.trim_end_matches('/');
+            let rest = package.strip_prefix(from)?;
+            (rest.is_empty() || rest.starts_with('/')).then_some((from, to))
+        })
+        .max_by_key(|(from, _)| from.len())?;
+
+    let (repository, base) = to.split_once("//").unwrap_or((to, ""));
+    let subpackage = package[from.len()..].trim_start_matches('/');
+    let package: Vec<&str> = [base.trim_matches('/'), subpackage]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect();
+    Some(format!("{}//{}:{}", repository, package.join("/"), target))
+}
+
+fn is_empty_list(tokens: &[Token<'_>], value: Range<usize>) -> bool {
+    matches!(
+        &tokens[value],
+        [open, close] if open.kind == TokenKind::LBracket && close.kind == TokenKind::RBracket
+    )
+}
+
+// The string elements of the rule's `deps` list with their token indices.
+// Lists with anything other than plain strings are ignored.
+fn deps_labels(tokens: &[Token<'_>], call: &Call<'_>) -> Vec<(String, usize)> {
+    let Some(deps) = call.keyword(tokens, "deps") else {
+        return Vec::new();
+    };
+    let value = &tokens[deps.value.clone()];
+    let is_list = value.first().map(|t| t.kind) == Some(TokenKind::LBracket)
+        && value.last().map(|t| t.kind) == Some(TokenKind::RBracket);
+    let inner = deps.value.start + 1..deps.value.end - 1;
+    if !is_list
+        || tokens[inner.clone()].iter().any(|t| {
+            !matches!(
+                t.kind,
+                TokenKind::String | TokenKind::Comma | TokenKind::Comment
+            )
+        })
+    {
+        return Vec::new();
+    }
+
+    inner
+        .filter_map(|i| tokens[i].string_value().map(|value| (value, i)))
+        .collect()
+}
+
+// The string elements of the rule's `srcs` (plain file names, glob includes
+// and glob excludes alike), or `None` if it has no `srcs` or the value uses
+// anything we can't evaluate
+pub fn srcs_patterns(tokens: &[Token<'_>], call: &Call<'_>) -> Option<Vec<String>> {
+    let srcs = call.keyword(tokens, "srcs")?;
+    let value = &tokens[srcs.value.clone()];
+    if value
+        .iter()
+        .any(|t| t.kind == TokenKind::Ident && !SRCS_IDENTS.contains(&t.text))
+    {
+        return None;
+    }
+    Some(value.iter().filter_map(Token::string_value).collect())
+}
+
+// Every module imported by the Swift files the rule's `srcs` matches, or
+// `None` if the sources can't be determined
+fn imported_modules(
+    tokens: &[Token<'_>],
+    call: &Call<'_>,
+    package_dir: &Path,
+) -> Option<BTreeSet<String>> {
+    // Exclude patterns are treated as includes, which can only keep deps
+    let patterns = srcs_patterns(tokens, call)?;
+    let files = matching_swift_files(package_dir, &patterns).ok()?;
+    if files.is_empty() {
+        return None;
+    }
+
+    let mut modules = BTreeSet::new();
+    for file in files {
+        let imports = parse_swift_imports(&package_dir.join(file)).ok()?;
+        modules.extend(imports.into_iter().map(|import| import.module));
+    }
+    Some(modules)
+}
@@ -0,0 +1,54 @@
+//! Removal of the `exports` attribute, which UmbraCore's legacy rules
+//! accepted but rules_swift's `swift_library` doesn't.
+//!
+//! The attribute is found with the tokenizer rather than a pattern, so values
+//! with nested brackets (`exports = [select({...})]`) are removed whole.
+
+use crate::checks::spm::SWIFT_RULES;
+use crate::checks::swift_library::UMBRA_SWIFT_RULES;
+use crate::issue::BuildIssue;
+use crate::starlark::calls::{span_removal_range, top_level_calls};
+use crate::starlark::tokenizer::tokenize;
+
+pub fn check_exports_attribute(content: &str) -> Option<(BuildIssue, String)> {
+    (remove_exports_attribute(content) != content).then(|| {
+        (
+            BuildIssue::ExportsAttribute,
+            "exports attribute is not supported by swift_library".to_string(),
+        )
+    })
+}
+
+// Remove the `exports` argument of every Swift rule, or legacy macro that is
+// migrated to one, with its line if it has one to itself, or else with the
+// comma that separates it. Other rules, such as java_library, support the
+// attribute and keep it.
+pub fn remove_exports_attribute(content: &str) -> String {
+    let tokens = tokenize(content);
+    let ranges: Vec<(usize, usize)> = top_level_calls(&tokens)
+        .into_iter()
+        .filter(|call| {
+            SWIFT_RULES.contains(&call.name)
+                || UMBRA_SWIFT_RULES
+                    .iter()
+                    .any(|(umbra, _)| *umbra == call.name)
+        })
+        .filter_map(|call| call.keyword(&tokens, "exports"))
+        .filter(|exports| !exports.value.is_empty())
+        // The key and `=` precede the value
+        .map(|exports| {
+            span_removal_range(
+                content,
+                &tokens,
+                exports.value.start - 2,
+                exports.value.end - 1,
+            )
+        })
+        .collect();
+
+    let mut fixed = content.to_string();
+    for (start, end) in ranges.into_iter().rev() {
+        fixed.replace_range(start..end, "");
+    }
+    fixed
+}
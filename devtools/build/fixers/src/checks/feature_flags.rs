@@ -0,0 +1,230 @@
+//! Checks on `select()`: its conditions must name `config_setting` (or
+//! `constraint_value`) targets, and it should say what happens on the
+//! configurations it doesn't list.
+
+use std::collections::BTreeSet;
+
+use crate::issue::BuildIssue;
+use crate::label_resolver::{resolve_label, AbsoluteLabel};
+use crate::starlark::ast::AttrValue;
+use crate::starlark::calls::{top_level_calls, Call};
+use crate::starlark::tokenizer::{find_matching, tokenize, Token, TokenKind};
+use crate::target_graph::TargetGraph;
+
+/// The condition every configuration matches when no other does.
+pub const DEFAULT_CONDITION: &str = "//conditions:default";
+
+/// Rules whose targets can be `select()` conditions. bazel_skylib's
+/// `selects.config_setting_group` expands to a `config_setting`.
+pub const CONFIG_SETTING_RULES: &[&str] = &[
+    "config_setting",
+    "constraint_value",
+    "selects.config_setting_group",
+];
+
+// Rule whose targets stand for the target their `actual` names
+const ALIAS_RULE: &str = "alias";
+
+// Flag each select() without a //conditions:default branch, so that the
+// configurations it doesn't list fail to build. Setting no_match_error
+// documents that this is intended, as does a suppression comment. A select()
+// whose conditions aren't all string literals is skipped.
+pub fn check_select_defaults(content: &str) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues = Vec::new();
+    for rule in top_level_calls(&tokens) {
+        let target = rule.target_name(&tokens);
+        for select in select_calls(&tokens, &rule) {
+            let arguments = select.arguments(&tokens);
+            if arguments
+                .iter()
+                .any(|argument| argument.key == Some("no_match_error"))
+            {
+                continue;
+            }
+            let Some(dict) = arguments.first().filter(|argument| argument.key.is_none()) else {
+                continue;
+            };
+            let Some(conditions) = dict_keys(&tokens, dict.value.start, dict.value.end - 1) else {
+                continue;
+            };
+            if conditions.iter().any(|condition| is_default(condition)) {
+                continue;
+            }
+
+            let line = select.line;
+            let location = match &target {
+                Some(target) => format!("line {} (in {:?})", line, target),
+                None => format!("line {}", line),
+            };
+            let message = format!(
+                "select() on {} has no {:?} branch, so configurations it doesn't list fail \
+                 to build; add one, or set no_match_error to say why there is none",
+                location, DEFAULT_CONDITION
+            );
+            issues.push((
+                BuildIssue::SelectWithoutDefault {
+                    line,
+                    target: target.clone(),
+                },
+                message,
+            ));
+        }
+    }
+    issues
+}
+
+// Flag each select() condition of the workspace's targets that isn't a
+// config_setting or constraint_value of the workspace, following aliases.
+// Conditions in external repositories (or aliases of them) can't be checked
+// and are skipped.
+pub fn check_config_settings(graph: &TargetGraph) -> Vec<(AbsoluteLabel, BuildIssue, String)> {
+    let mut issues = Vec::new();
+    for (label, info) in graph.targets() {
+        let mut conditions = BTreeSet::new();
+        for attr in &info.attrs {
+            collect_conditions(&attr.value, &mut conditions);
+        }
+
+        'conditions: for condition in conditions {
+            if is_default(condition) {
+                continue;
+            }
+            let Ok(setting) = resolve_label(condition, &label.package, graph.workspace_root())
+            else {
+                continue;
+            };
+            if setting.repository.is_some() {
+                continue;
+            }
+
+            let mut setting = setting;
+            let mut followed = BTreeSet::new();
+            let target = loop {
+                let Some(target) = graph.lookup(&setting) else {
+                    break None;
+                };
+                if target.rule_name != ALIAS_RULE || !followed.insert(setting.clone()) {
+                    break Some(target);
+                }
+                let actual = target
+                    .attrs
+                    .iter()
+                    .find(|attr| attr.key == "actual")
+                    .and_then(|attr| match &attr.value {
+                        AttrValue::String(actual) => Some(actual),
+                        _ => None,
+                    })
+                    .and_then(|actual| {
+                        resolve_label(actual, &setting.package, graph.workspace_root()).ok()
+                    });
+                match actual {
+                    Some(actual) if actual.repository.is_none() => setting = actual,
+                    _ => continue 'conditions,
+                }
+            };
+            let problem = match target {
+                None => "isn't defined in the workspace".to_string(),
+                Some(setting) if !CONFIG_SETTING_RULES.contains(&setting.rule_name.as_str()) => {
+                    format!("is a {}, not a config_setting", setting.rule_name)
+                }
+                Some(_) => continue,
+            };
+            let message = format!(
+                "{:?} selects on {}, which {}",
+                label.target, condition, problem
+            );
+            issues.push((
+                label.clone(),
+                BuildIssue::UnknownConfigSetting {
+                    target: label.target.clone(),
+                    label: condition.to_string(),
+                },
+                message,
+            ));
+        }
+    }
+    issues
+}
+
+fn is_default(condition: &str) -> bool {
+    condition == DEFAULT_CONDITION || condition == "@bazel_tools//conditions:default"
+}
+
+// Every select() call within `rule`, as calls of its own
+fn select_calls<'a>(tokens: &[Token<'a>], rule: &Call<'a>) -> Vec<Call<'a>> {
+    (rule.open + 1..rule.close)
+        .filter(|&i| {
+            tokens[i].is_ident("select")
+                && tokens[i + 1].kind == TokenKind::LParen
+                && tokens[i - 1].kind != TokenKind::Dot
+        })
+        .filter_map(|i| {
+            Some(Call {
+                name: tokens[i].text,
+                open: i + 1,
+                close: find_matching(tokens, i + 1)?,
+                line: tokens[i].line,
+            })
+        })
+        .collect()
+}
+
+// The keys of the dict literal from `open` to its matching `close`, or
+// `None` if it isn't one or a key isn't a string literal
+fn dict_keys(tokens: &[Token<'_>], open: usize, close: usize) -> Option<Vec<String>> {
+    if tokens[open].kind != TokenKind::LBrace || tokens[close].kind != TokenKind::RBrace {
+        return None;
+    }
+    let mut keys = Vec::new();
+    let mut index = open + 1;
+    let mut expects_key = true;
+    while index < close {
+        let token = &tokens[index];
+        match token.kind {
+            TokenKind::Comment => {}
+            TokenKind::Comma => expects_key = true,
+            TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace => {
+                if expects_key {
+                    return None;
+                }
+                index = find_matching(tokens, index)?;
+            }
+            _ if expects_key => {
+                let key = token.string_value()?;
+                if tokens.get(index + 1).map(|t| t.kind) != Some(TokenKind::Colon) {
+                    return None;
+                }
+                keys.push(key);
+                expects_key = false;
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    Some(keys)
+}
+
+// The conditions of every select() within `value`
+fn collect_conditions<'a>(value: &'a AttrValue, conditions: &mut BTreeSet<&'a str>) {
+    match value {
+        AttrValue::Select(branches) => {
+            for (condition, value) in branches {
+                conditions.insert(condition);
+                collect_conditions(value, conditions);
+            }
+        }
+        AttrValue::List(elements) | AttrValue::Concat(elements) => elements
+            .iter()
+            .for_each(|element| collect_conditions(element, conditions)),
+        AttrValue::Dict(entries) => entries
+            .iter()
+            .for_each(|(_, value)| collect_conditions(value, conditions)),
+        AttrValue::Call { args, attrs, .. } => {
+            for value in args.iter().chain(attrs.iter().map(|attr| &attr.value)) {
+                collect_conditions(value, conditions);
+            }
+        }
+        AttrValue::String(_) | AttrValue::Number(_) | AttrValue::Ident(_) => {}
+    }
+}
@@ -0,0 +1,322 @@
+//! Whitespace and layout checks that don't depend on the rules in the file.
+
+use std::ops::Range;
+
+use crate::issue::BuildIssue;
+use crate::starlark::calls::{line_indent, top_level_calls, Call};
+use crate::starlark::formatter::format_build_file;
+use crate::starlark::tokenizer::{find_matching, tokenize, Token, TokenKind};
+
+/// Attributes whose lists the `LineTooLong` fix puts one element per line.
+pub const SPLITTABLE_LIST_ATTRIBUTES: &[&str] = &["deps", "srcs"];
+
+pub fn check_line_endings(content: &str) -> Option<(BuildIssue, String)> {
+    let crlf_lines = content.matches("\r\n").count();
+    let lone_crs = content.matches('\r').count() - crlf_lines;
+    if crlf_lines == 0 && lone_crs == 0 {
+        return None;
+    }
+
+    Some((
+        BuildIssue::CrlfLineEnding,
+        format!(
+            "file uses Windows line endings ({} CRLF, {} bare CR)",
+            crlf_lines, lone_crs
+        ),
+    ))
+}
+
+// Convert CRLF and bare CR line endings to LF
+pub fn fix_line_endings(content: &str) -> String {
+    content.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+pub fn check_trailing_whitespace(content: &str) -> Option<(BuildIssue, String)> {
+    let lines = trailing_whitespace(content);
+    let (first, _) = *lines.first()?;
+    Some((
+        BuildIssue::TrailingWhitespace,
+        format!(
+            "{} line(s) have trailing whitespace, starting at line {}",
+            lines.len(),
+            first
+        ),
+    ))
+}
+
+// Strip trailing spaces and tabs from every line outside string literals
+pub fn fix_trailing_whitespace(content: &str) -> String {
+    let mut fixed = content.to_string();
+    for (_, range) in trailing_whitespace(content).into_iter().rev() {
+        fixed.replace_range(range, "");
+    }
+    fixed
+}
+
+// The 1-based line and byte range of the trailing spaces and tabs of each
+// line. Lines that end inside a (triple-quoted) string literal are left out,
+// as their whitespace is part of its value.
+fn trailing_whitespace(content: &str) -> Vec<(usize, Range<usize>)> {
+    let tokens = tokenize(content);
+    let strings: Vec<Range<usize>> = tokens
+        .iter()
+        .filter(|token| token.kind == TokenKind::String)
+        .map(|token| token.start..token.end())
+        .collect();
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (index, line) in content.split('\n').enumerate() {
+        let end = start + line.len();
+        let trimmed = start + line.trim_end_matches([' ', '\t']).len();
+        let in_string = strings
+            .iter()
+            .any(|string| string.start < end && end < string.end);
+        if trimmed < end && !in_string {
+            lines.push((index + 1, trimmed..end));
+        }
+        start = end + 1;
+    }
+    lines
+}
+
+pub fn check_trailing_newline(content: &str) -> Option<(BuildIssue, String)> {
+    (!content.is_empty() && !content.ends_with('\n')).then(|| {
+        (
+            BuildIssue::MissingTrailingNewline,
+            "file does not end with a newline".to_string(),
+        )
+    })
+}
+
+// Append the missing final newline
+pub fn fix_trailing_newline(content: &str) -> String {
+    if content.is_empty() || content.ends_with('\n') {
+        return content.to_string();
+    }
+    format!("{}\n", content)
+}
+
+// Split a string literal into its prefix (`r`, `b`, ...), delimiter and body,
+// returning `None` for literals that are already double-quoted.
+fn single_quoted_parts<'a>(token: &Token<'a>) -> Option<(&'a str, &'a str, &'a str)> {
+    if token.kind != TokenKind::String {
+        return None;
+    }
+    let quote_start = token.text.find(['"', '\''])?;
+    let (prefix, literal) = token.text.split_at(quote_start);
+    let quote = if literal.starts_with("'''") {
+        "'''"
+    } else {
+        "'"
+    };
+    if !literal.starts_with('\'') {
+        return None;
+    }
+    let body = literal.strip_prefix(quote)?.strip_suffix(quote)?;
+    Some((prefix, quote, body))
+}
+
+// Re-escape the body of a single-quoted literal for double quotes, or `None`
+// if it can't be expressed (a raw string can't escape a double quote).
+fn requote_body(body: &str, raw: bool) -> Option<String> {
+    if raw {
+        return (!body.contains('"')).then(|| body.to_string());
+    }
+
+    let mut requoted = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\'') => requoted.push('\''),
+                Some(escaped) => {
+                    requoted.push('\\');
+                    requoted.push(escaped);
+                }
+                None => requoted.push('\\'),
+            },
+            '"' => requoted.push_str("\\\""),
+            _ => requoted.push(c),
+        }
+    }
+    Some(requoted)
+}
+
+// The double-quoted replacement for a single-quoted literal, if it has one
+fn double_quoted(token: &Token<'_>) -> Option<String> {
+    let (prefix, quote, body) = single_quoted_parts(token)?;
+    let body = requote_body(body, prefix.contains(['r', 'R']))?;
+    let delimiter = if quote.len() == 3 { "\"\"\"" } else { "\"" };
+    Some(format!("{}{}{}{}", prefix, delimiter, body, delimiter))
+}
+
+pub fn check_quote_style(content: &str) -> Option<(BuildIssue, String)> {
+    let lines: Vec<usize> = tokenize(content)
+        .iter()
+        .filter(|token| double_quoted(token).is_some())
+        .map(|token| token.line)
+        .collect();
+
+    let first = *lines.first()?;
+    Some((
+        BuildIssue::InconsistentQuoteStyle,
+        format!(
+            "{} single-quoted string(s), starting at line {}",
+            lines.len(),
+            first
+        ),
+    ))
+}
+
+// Rewrite single-quoted string literals to use double quotes
+pub fn fix_quote_style(content: &str) -> String {
+    let mut new_content = String::with_capacity(content.len());
+    let mut last = 0;
+
+    for token in tokenize(content) {
+        let Some(replacement) = double_quoted(&token) else {
+            continue;
+        };
+
+        new_content.push_str(&content[last..token.start]);
+        new_content.push_str(&replacement);
+        last = token.end();
+    }
+    new_content.push_str(&content[last..]);
+
+    new_content
+}
+
+pub fn check_canonical_format(content: &str) -> Option<(BuildIssue, String)> {
+    (format_build_file(content) != content).then(|| {
+        (
+            BuildIssue::NonCanonicalFormat,
+            "layout differs from the canonical format".to_string(),
+        )
+    })
+}
+
+// Flag every line longer than `max` characters, with the rule attribute whose
+// value is on it. Single-line `deps` and `srcs` lists can be split.
+pub fn check_line_length(content: &str, max: usize) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let calls = top_level_calls(&tokens);
+    let mut issues = Vec::new();
+
+    for (index, text) in content.lines().enumerate() {
+        let length = text.chars().count();
+        if length <= max {
+            continue;
+        }
+        let line = index + 1;
+        let attribute = calls.iter().find_map(|call| {
+            let argument = call.arguments(&tokens).into_iter().find(|argument| {
+                !argument.value.is_empty()
+                    && tokens[argument.value.start].line <= line
+                    && tokens[argument.value.end - 1].line >= line
+            })?;
+            Some((call, argument.key?))
+        });
+
+        let (target, attribute, splittable) = match attribute {
+            Some((call, key)) => (
+                call.target_name(&tokens),
+                Some(key.to_string()),
+                single_line_list(&tokens, call, key).is_some(),
+            ),
+            None => (None, None, false),
+        };
+        let location = match (&target, &attribute) {
+            (Some(target), Some(attribute)) => format!(" ({} of {:?})", attribute, target),
+            (None, Some(attribute)) => format!(" ({})", attribute),
+            _ => String::new(),
+        };
+        issues.push((
+            BuildIssue::LineTooLong {
+                line,
+                target,
+                attribute,
+                splittable,
+            },
+            format!(
+                "line {} is {} characters long, over the limit of {}{}",
+                line, length, max, location
+            ),
+        ));
+    }
+
+    issues
+}
+
+// Put the elements of the `attribute` list of the rule named `target` on one
+// line each
+pub fn fix_line_length(content: &str, target: &str, attribute: &str) -> String {
+    let tokens = tokenize(content);
+    let list = top_level_calls(&tokens)
+        .iter()
+        .filter(|call| call.target_name(&tokens).as_deref() == Some(target))
+        .find_map(|call| single_line_list(&tokens, call, attribute));
+    let Some((open, close)) = list else {
+        return content.to_string();
+    };
+
+    let indent = line_indent(content, tokens[open].start);
+    let mut elements = Vec::new();
+    let mut start = open + 1;
+    let mut index = open + 1;
+    while index <= close {
+        let kind = tokens[index].kind;
+        if index == close || kind == TokenKind::Comma {
+            if start < index {
+                elements.push(&content[tokens[start].start..tokens[index - 1].end()]);
+            }
+            start = index + 1;
+            index += 1;
+        } else if matches!(
+            kind,
+            TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace
+        ) {
+            index = find_matching(&tokens, index).map_or(close, |end| end + 1);
+        } else {
+            index += 1;
+        }
+    }
+
+    let mut list = String::from("[\n");
+    for element in elements {
+        list.push_str(&format!("{}    {},\n", indent, element));
+    }
+    list.push_str(indent);
+    list.push(']');
+    format!(
+        "{}{}{}",
+        &content[..tokens[open].start],
+        list,
+        &content[tokens[close].end()..]
+    )
+}
+
+// The `[` and `]` token indices of the first list in the `attribute` value,
+// e.g. the include list of a glob(), if it has elements and fits on one line
+fn single_line_list(
+    tokens: &[Token<'_>],
+    call: &Call<'_>,
+    attribute: &str,
+) -> Option<(usize, usize)> {
+    if !SPLITTABLE_LIST_ATTRIBUTES.contains(&attribute) {
+        return None;
+    }
+    let argument = call.keyword(tokens, attribute)?;
+    let open = argument
+        .value
+        .clone()
+        .find(|&i| tokens[i].kind == TokenKind::LBracket)?;
+    let close = find_matching(tokens, open)?;
+    let has_elements = close > open + 1;
+    let has_comments = tokens[open..close]
+        .iter()
+        .any(|token| token.kind == TokenKind::Comment);
+    (has_elements && !has_comments && tokens[open].line == tokens[close].line)
+        .then_some((open, close))
+}
@@ -0,0 +1,808 @@
+//! Checks on `glob()` patterns: recursive globs that reach into nested
+//! packages, globs that pick up generated sources, Swift files that no
+//! `srcs` pattern matches, `allow_empty` on globs that can't be empty,
+//! patterns that would match build outputs, Swift `srcs` globs without an
+//! extension that also match other languages' sources, and `select()` inside
+//! `glob()`.
+
+use std::path::Path;
+
+use walkdir::WalkDir;
+
+use crate::checks::deps::srcs_patterns;
+use crate::checks::spm::SWIFT_RULES;
+use crate::discovery::is_package;
+use crate::glob::glob_match;
+use crate::glob_evaluator::{bazel_glob_match, evaluate_glob};
+use crate::issue::BuildIssue;
+use crate::sources::{collect_package_files, collect_swift_files};
+use crate::starlark::calls::{
+    append_to_list, element_removal_range, insert_after_name, is_list, line_indent,
+    span_removal_range, top_level_calls, Argument, Call,
+};
+use crate::starlark::tokenizer::{find_matching, quote, tokenize, Token, TokenKind};
+
+/// File names of generated Swift sources, unless `generated_file_patterns`
+/// in umbra-fix.toml says otherwise.
+pub const DEFAULT_GENERATED_FILE_PATTERNS: &[&str] =
+    &["*.generated.swift", "*.pb.swift", "*.grpc.swift"];
+
+/// Extensions of build outputs (archives, objects and shared libraries) that
+/// glob patterns must not match.
+pub const BUILD_ARTIFACT_EXTENSIONS: &[&str] = &["a", "o", "dylib", "so"];
+
+// Build artifact extensions of the prebuilt binaries packages vendor, which
+// the fix never removes from a glob
+const PREBUILT_BINARY_EXTENSIONS: &[&str] = &["a", "dylib"];
+
+// Extension of the sources the Swift rules compile
+const SWIFT_EXTENSION: &str = "swift";
+
+// Patterns such as `**/*.swift` or `Sources/**/*.swift`
+const RECURSIVE_SWIFT_PATTERN: &str = "**/*.swift";
+
+// Flag each nested package (a subdirectory with its own BUILD.bazel) that a
+// recursive `*.swift` glob reaches into without excluding it
+pub fn check_wildcard_globs(content: &str, package_dir: &Path) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues: Vec<(BuildIssue, String)> = Vec::new();
+
+    for glob in glob_calls(&tokens) {
+        let Some(include) = include_list(&tokens, &glob) else {
+            continue;
+        };
+        let excludes = glob
+            .keyword(&tokens, "exclude")
+            .map(|exclude| string_elements(&tokens, &exclude))
+            .unwrap_or_default();
+
+        for pattern in string_elements(&tokens, &include) {
+            let Some(base) = pattern.strip_suffix(RECURSIVE_SWIFT_PATTERN) else {
+                continue;
+            };
+            for subpackage in nested_packages(package_dir, base.trim_end_matches('/')) {
+                if excludes.contains(&exclude_pattern(&subpackage)) {
+                    continue;
+                }
+                let issue = BuildIssue::WildcardGlob {
+                    pattern: pattern.clone(),
+                    subpackage: subpackage.clone(),
+                };
+                if issues.iter().any(|(existing, _)| *existing == issue) {
+                    continue;
+                }
+                let message = format!(
+                    "glob {:?} also matches sources of the nested package {}",
+                    pattern, subpackage
+                );
+                issues.push((issue, message));
+            }
+        }
+    }
+
+    issues
+}
+
+// Add `subpackage/**` to the `exclude` of every glob that includes `pattern`
+pub fn fix_wildcard_glob(content: &str, pattern: &str, subpackage: &str) -> String {
+    let exclude = exclude_pattern(subpackage);
+    let quoted = quote(&exclude);
+    let mut content = content.to_string();
+
+    // Each pass edits one glob, so token indices stay valid
+    loop {
+        let tokens = tokenize(&content);
+        let edited = glob_calls(&tokens).into_iter().find_map(|glob| {
+            let include = include_list(&tokens, &glob)?;
+            if !string_elements(&tokens, &include)
+                .iter()
+                .any(|p| p == pattern)
+            {
+                return None;
+            }
+            match glob.keyword(&tokens, "exclude") {
+                Some(existing) if string_elements(&tokens, &existing).contains(&exclude) => None,
+                Some(existing) => append_to_list(&content, &tokens, &existing, &quoted),
+                None => Some(insert_after_name(
+                    &content,
+                    &tokens,
+                    &glob,
+                    &format!("exclude = [{}]", quoted),
+                )),
+            }
+        });
+        match edited {
+            Some(edited) => content = edited,
+            None => return content,
+        }
+    }
+}
+
+// Flag each glob without `exclude` that matches files whose names match one
+// of the `generated` patterns. The exclude patterns of the fix reach into
+// subdirectories when a generated file sits in one.
+pub fn check_generated_sources(
+    content: &str,
+    package_dir: &Path,
+    generated: &[String],
+) -> Vec<(BuildIssue, String)> {
+    if generated.is_empty() {
+        return Vec::new();
+    }
+    let Ok(files) = package_files(package_dir) else {
+        return Vec::new();
+    };
+    let tokens = tokenize(content);
+    let mut issues: Vec<(BuildIssue, String)> = Vec::new();
+
+    for glob in glob_calls(&tokens) {
+        if glob.keyword(&tokens, "exclude").is_some() {
+            continue;
+        }
+        let Some(include) = literal_include(&tokens, &glob) else {
+            continue;
+        };
+
+        let mut exclude = Vec::new();
+        let mut matched = Vec::new();
+        for file in &files {
+            if !include.iter().any(|pattern| glob_match(pattern, file)) {
+                continue;
+            }
+            let (directory, name) = file.rsplit_once('/').unwrap_or(("", file));
+            let Some(pattern) = generated.iter().find(|pattern| glob_match(pattern, name)) else {
+                continue;
+            };
+            let pattern = if directory.is_empty() {
+                pattern.clone()
+            } else {
+                format!("**/{}", pattern)
+            };
+            if !exclude.contains(&pattern) {
+                exclude.push(pattern);
+            }
+            matched.push(file.as_str());
+        }
+        if matched.is_empty() {
+            continue;
+        }
+
+        let issue = BuildIssue::GeneratedSourcesInGlob {
+            include: include.clone(),
+            exclude,
+        };
+        if issues.iter().any(|(existing, _)| *existing == issue) {
+            continue;
+        }
+        let message = format!(
+            "glob({:?}) also matches generated sources: {}",
+            include,
+            matched.join(", ")
+        );
+        issues.push((issue, message));
+    }
+
+    issues
+}
+
+// Add `exclude = [...]` to the globs with the `include` patterns that have no
+// exclude yet
+pub fn fix_glob_exclude(content: &str, include: &[String], exclude: &[String]) -> String {
+    let quoted: Vec<String> = exclude.iter().map(|pattern| quote(pattern)).collect();
+    let attribute = format!("exclude = [{}]", quoted.join(", "));
+    let mut content = content.to_string();
+
+    // Each pass edits one glob, so token indices stay valid
+    loop {
+        let tokens = tokenize(&content);
+        let glob = glob_calls(&tokens).into_iter().find(|glob| {
+            glob.keyword(&tokens, "exclude").is_none()
+                && literal_include(&tokens, glob).is_some_and(|patterns| patterns == include)
+        });
+        match glob {
+            Some(glob) => content = insert_after_name(&content, &tokens, &glob, &attribute),
+            None => return content,
+        }
+    }
+}
+
+// Flag each glob that sets `allow_empty = True` although it matches files of
+// the package. Globs whose patterns aren't all string literals are skipped.
+pub fn check_redundant_allow_empty(content: &str, package_dir: &Path) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues: Vec<(BuildIssue, String)> = Vec::new();
+
+    for glob in glob_calls(&tokens) {
+        if !allows_empty(&tokens, &glob) || glob_matches(&tokens, &glob, package_dir) != Some(true)
+        {
+            continue;
+        }
+        let Some(include) = literal_include(&tokens, &glob) else {
+            continue;
+        };
+        let issue = BuildIssue::RedundantAllowEmpty {
+            include: include.clone(),
+        };
+        if issues.iter().any(|(existing, _)| *existing == issue) {
+            continue;
+        }
+        let message = format!(
+            "glob({:?}) matches files of the package, so allow_empty = True is redundant",
+            include
+        );
+        issues.push((issue, message));
+    }
+
+    issues
+}
+
+/// Whether a glob in `content` could match no file of the package at
+/// `package_dir` without setting `allow_empty = True`.
+pub fn globs_need_allow_empty(content: &str, package_dir: &Path) -> bool {
+    let tokens = tokenize(content);
+    glob_calls(&tokens).iter().any(|glob| {
+        !allows_empty(&tokens, glob) && glob_matches(&tokens, glob, package_dir) != Some(true)
+    })
+}
+
+// Remove `allow_empty = True` from the globs with the `include` patterns
+pub fn fix_redundant_allow_empty(content: &str, include: &[String]) -> String {
+    let mut content = content.to_string();
+
+    // Each pass edits one glob, so token indices stay valid
+    loop {
+        let tokens = tokenize(&content);
+        let glob = glob_calls(&tokens).into_iter().find(|glob| {
+            allows_empty(&tokens, glob)
+                && literal_include(&tokens, glob).is_some_and(|patterns| patterns == include)
+        });
+        let Some(glob) = glob else {
+            return content;
+        };
+
+        let arguments = glob.arguments(&tokens);
+        let (start, end, replacement) = match arguments.as_slice() {
+            // Only the patterns are left, so the call fits on one line
+            [include, allow_empty] | [allow_empty, include]
+                if include.key.is_none() && allow_empty.key == Some("allow_empty") =>
+            {
+                let patterns = &content
+                    [tokens[include.value.start].start..tokens[include.value.end - 1].end()];
+                (
+                    tokens[glob.open].start,
+                    tokens[glob.close].end(),
+                    format!("({})", patterns),
+                )
+            }
+            _ => {
+                let Some(allow_empty) = arguments
+                    .iter()
+                    .find(|argument| argument.key == Some("allow_empty"))
+                else {
+                    return content;
+                };
+                // The key and `=` precede the value
+                let (start, end) = span_removal_range(
+                    &content,
+                    &tokens,
+                    allow_empty.value.start - 2,
+                    allow_empty.value.end - 1,
+                );
+                (start, end, String::new())
+            }
+        };
+        content.replace_range(start..end, &replacement);
+    }
+}
+
+// Flag the glob include patterns that match build artifacts, which would be
+// picked up if a build ever left them in the source tree. Only the patterns
+// the fix can remove are reported here; see
+// [`check_unremovable_artifact_patterns`] for the others.
+pub fn check_nonhermetic_glob_patterns(content: &str) -> Option<(BuildIssue, String)> {
+    let (patterns, _) = artifact_patterns(content);
+    if patterns.is_empty() {
+        return None;
+    }
+
+    let message = format!(
+        "glob patterns {:?} match build outputs, which makes the build non-hermetic",
+        patterns
+    );
+    Some((
+        BuildIssue::NonHermeticGlob {
+            patterns,
+            removable: true,
+        },
+        message,
+    ))
+}
+
+// Flag the glob include patterns that match build artifacts but are left for
+// a manual edit: references to vendored prebuilt binaries (`.a`, `.dylib`),
+// and patterns whose removal would leave a glob without any include
+pub fn check_unremovable_artifact_patterns(content: &str) -> Option<(BuildIssue, String)> {
+    let (_, patterns) = artifact_patterns(content);
+    if patterns.is_empty() {
+        return None;
+    }
+
+    let message = format!(
+        "glob patterns {:?} match build outputs or vendored prebuilt binaries; list \
+         the files the target needs explicitly, or remove the patterns if it needs none",
+        patterns
+    );
+    Some((
+        BuildIssue::NonHermeticGlob {
+            patterns,
+            removable: false,
+        },
+        message,
+    ))
+}
+
+// The glob include patterns that match build artifacts, split into those the
+// fix can remove and those it must leave alone
+fn artifact_patterns(content: &str) -> (Vec<String>, Vec<String>) {
+    let tokens = tokenize(content);
+    let mut removable: Vec<String> = Vec::new();
+    let mut kept: Vec<String> = Vec::new();
+    for glob in glob_calls(&tokens) {
+        let Some(include) = include_list(&tokens, &glob) else {
+            continue;
+        };
+        let elements = string_elements(&tokens, &include);
+        let candidates: Vec<&String> = elements
+            .iter()
+            .filter(|pattern| is_build_artifact_pattern(pattern))
+            .filter(|pattern| !is_prebuilt_binary_pattern(pattern))
+            .collect();
+        // Removing every include would leave an empty glob in place of
+        // whatever the attribute was meant to hold
+        let emptied = candidates.len() == elements.len();
+        for pattern in elements.iter().filter(|p| is_build_artifact_pattern(p)) {
+            if emptied || is_prebuilt_binary_pattern(pattern) {
+                if !kept.contains(pattern) {
+                    kept.push(pattern.clone());
+                }
+            } else if !removable.contains(pattern) {
+                removable.push(pattern.clone());
+            }
+        }
+    }
+    removable.retain(|pattern| !kept.contains(pattern));
+    (removable, kept)
+}
+// Flag each `select()` inside a glob(). Bazel expands globs while loading the
+// package, before any configuration exists to pick a select() branch, so it
+// rejects them; which rewrite is right depends on what the select() was for.
+pub fn check_select_in_glob(content: &str) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let rules = top_level_calls(&tokens);
+    let mut issues = Vec::new();
+    for glob in glob_calls(&tokens) {
+        let Some(select) = (glob.open + 1..glob.close).find(|&i| {
+            tokens[i].is_ident("select")
+                && tokens[i + 1].kind == TokenKind::LParen
+                && tokens[i - 1].kind != TokenKind::Dot
+        }) else {
+            continue;
+        };
+        let line = tokens[select].line;
+        let target = rules
+            .iter()
+            .find(|rule| rule.open < glob.open && glob.close < rule.close)
+            .and_then(|rule| rule.target_name(&tokens));
+        let location = match &target {
+            Some(target) => format!("line {} (in {:?})", line, target),
+            None => format!("line {}", line),
+        };
+        let message = format!(
+            "select() inside glob() on {}: glob() must be a static expression, because \
+             Bazel expands it while loading the package, before select() is resolved. \
+             Move the select() out of the glob, e.g. `glob([...]) + select({{...}})`, \
+             or give each select() branch its own glob()",
+            location
+        );
+        issues.push((BuildIssue::SelectInGlob { line, target }, message));
+    }
+    issues
+}
+
+// Remove the `patterns` from every glob include list, leaving a comment
+// above the glob for each one removed
+pub fn fix_nonhermetic_glob_patterns(content: &str, patterns: &[String]) -> String {
+    let mut content = content.to_string();
+
+    // Each pass removes one pattern, so token indices stay valid
+    loop {
+        let tokens = tokenize(&content);
+        let found = glob_calls(&tokens).into_iter().find_map(|glob| {
+            let include = include_list(&tokens, &glob)?;
+            // The last include is never removed
+            string_elements(&tokens, &include)
+                .iter()
+                .any(|pattern| !patterns.contains(pattern))
+                .then_some(())?;
+            let index = include.value.clone().find(|&i| {
+                tokens[i]
+                    .string_value()
+                    .is_some_and(|value| patterns.contains(&value))
+            })?;
+            Some((glob, index))
+        });
+        let Some((glob, index)) = found else {
+            return content;
+        };
+
+        let pattern = tokens[index].string_value().unwrap_or_default();
+        let (start, end) = element_removal_range(&content, &tokens, index);
+        let glob_start = tokens[glob.open - 1].start;
+        let line_start = content[..glob_start].rfind('\n').map_or(0, |i| i + 1);
+        content = format!(
+            "{}{}# {} was removed from this glob; it would match build outputs\n{}{}",
+            &content[..line_start],
+            line_indent(&content, glob_start),
+            quote(&pattern),
+            &content[line_start..start],
+            &content[end..]
+        );
+    }
+}
+
+// Flag each Swift file of the package that no rule's `srcs` matches
+pub fn check_orphaned_sources(content: &str, package_dir: &Path) -> Vec<(BuildIssue, String)> {
+    find_orphaned_sources(&package_dir.join("BUILD.bazel"), content)
+        .into_iter()
+        .map(|file| {
+            let message = format!("{} is not matched by the srcs of any target", file);
+            (BuildIssue::OrphanedSourceFile { file }, message)
+        })
+        .collect()
+}
+
+/// Swift files in the package of `build_file` that match none of the `srcs`
+/// patterns in `content`, relative to the package directory. Nothing is
+/// reported if no rule has `srcs` or one of them can't be evaluated.
+pub fn find_orphaned_sources(build_file: &Path, content: &str) -> Vec<String> {
+    let tokens = tokenize(content);
+    let Some(patterns) = all_srcs_patterns(&tokens) else {
+        return Vec::new();
+    };
+    let Some(package_dir) = build_file.parent() else {
+        return Vec::new();
+    };
+    let (Ok(files), Ok(matched)) = (
+        collect_swift_files(package_dir),
+        evaluate_glob(package_dir, &patterns, &[], true),
+    ) else {
+        return Vec::new();
+    };
+
+    files
+        .into_iter()
+        .filter(|file| !matched.contains(file))
+        .map(|file| file.to_string_lossy().replace('\\', "/"))
+        .collect()
+}
+
+// Cover `file` from the rule whose `srcs` patterns are closest to it: add a
+// `dir/*.swift` pattern to its glob, or else list the file explicitly
+pub fn fix_orphaned_source(content: &str, file: &str) -> String {
+    let tokens = tokenize(content);
+    let covered = all_srcs_patterns(&tokens).is_none_or(|patterns| {
+        patterns
+            .iter()
+            .any(|pattern| bazel_glob_match(pattern, file))
+    });
+    if covered {
+        return content.to_string();
+    }
+
+    let directory = file.rsplit_once('/').map_or("", |(directory, _)| directory);
+    let calls = top_level_calls(&tokens);
+    // Iterate in reverse so ties go to the first rule
+    let closest = calls
+        .iter()
+        .rev()
+        .filter_map(|call| {
+            Some((
+                call.keyword(&tokens, "srcs")?,
+                srcs_patterns(&tokens, call)?,
+            ))
+        })
+        .max_by_key(|(_, patterns)| {
+            patterns
+                .iter()
+                .map(|pattern| shared_directories(pattern, directory))
+                .max()
+                .unwrap_or(0)
+        });
+    let Some((srcs, _)) = closest else {
+        return content.to_string();
+    };
+
+    let include = glob_calls(&tokens)
+        .into_iter()
+        .filter(|glob| srcs.value.contains(&glob.open))
+        .find_map(|glob| include_list(&tokens, &glob));
+    let edited = match include {
+        Some(include) => {
+            let pattern = if directory.is_empty() {
+                "*.swift".to_string()
+            } else {
+                format!("{}/*.swift", directory)
+            };
+            append_to_list(content, &tokens, &include, &quote(&pattern))
+        }
+        None => append_to_list(content, &tokens, &srcs, &quote(file)),
+    };
+    edited.unwrap_or_else(|| content.to_string())
+}
+
+// Flag each include pattern without an extension filter (such as
+// `Sources/**/*`) in the `srcs` globs of a Swift rule that matches files other
+// than `.swift` sources, e.g. Objective-C files next to them
+pub fn check_mixed_source_languages(
+    content: &str,
+    package_dir: &Path,
+) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues = Vec::new();
+
+    for call in top_level_calls(&tokens) {
+        if !SWIFT_RULES.contains(&call.name) {
+            continue;
+        }
+        let (Some(target), Some(srcs)) = (call.target_name(&tokens), call.keyword(&tokens, "srcs"))
+        else {
+            continue;
+        };
+        for glob in glob_calls(&tokens)
+            .into_iter()
+            .filter(|glob| srcs.value.contains(&glob.open))
+        {
+            let Some(include) = literal_include(&tokens, &glob) else {
+                continue;
+            };
+            let excludes = glob
+                .keyword(&tokens, "exclude")
+                .map(|exclude| string_elements(&tokens, &exclude))
+                .unwrap_or_default();
+            for pattern in include {
+                if narrowed_pattern(&pattern).is_none() {
+                    continue;
+                }
+                let Ok(matched) =
+                    evaluate_glob(package_dir, std::slice::from_ref(&pattern), &excludes, true)
+                else {
+                    continue;
+                };
+                let files: Vec<String> = matched
+                    .iter()
+                    .filter(|file| file.extension().is_none_or(|ext| ext != SWIFT_EXTENSION))
+                    .map(|file| file.to_string_lossy().replace('\\', "/"))
+                    .collect();
+                if files.is_empty() {
+                    continue;
+                }
+                let message = format!(
+                    "{} {:?} globs {:?}, which also matches non-Swift files {}",
+                    call.name,
+                    target,
+                    pattern,
+                    files.join(", ")
+                );
+                issues.push((
+                    BuildIssue::MixedSourceLanguages {
+                        target: target.clone(),
+                        pattern,
+                        files,
+                    },
+                    message,
+                ));
+            }
+        }
+    }
+
+    issues
+}
+
+// Narrow `pattern` to Swift files in the `srcs` globs of the Swift rule
+// named `target`
+pub fn fix_mixed_source_languages(content: &str, target: &str, pattern: &str) -> String {
+    let Some(narrowed) = narrowed_pattern(pattern) else {
+        return content.to_string();
+    };
+    let tokens = tokenize(content);
+    let Some(srcs) = top_level_calls(&tokens)
+        .into_iter()
+        .filter(|call| SWIFT_RULES.contains(&call.name))
+        .find(|call| call.target_name(&tokens).as_deref() == Some(target))
+        .and_then(|call| call.keyword(&tokens, "srcs"))
+    else {
+        return content.to_string();
+    };
+
+    let mut fixed = content.to_string();
+    let strings: Vec<&Token<'_>> = glob_calls(&tokens)
+        .into_iter()
+        .filter(|glob| srcs.value.contains(&glob.open))
+        .filter_map(|glob| include_list(&tokens, &glob))
+        .flat_map(|include| &tokens[include.value])
+        .filter(|token| token.string_value().as_deref() == Some(pattern))
+        .collect();
+    for token in strings.into_iter().rev() {
+        fixed.replace_range(token.start..token.end(), &quote(&narrowed));
+    }
+    fixed
+}
+
+// `pattern` restricted to Swift files, or `None` if its last segment isn't a
+// wildcard without an extension: `**` becomes `**/*.swift` and `Sources/*`
+// becomes `Sources/*.swift`
+fn narrowed_pattern(pattern: &str) -> Option<String> {
+    let name = pattern.rsplit('/').next().unwrap_or(pattern);
+    if name == "**" {
+        Some(format!("{}/*.{}", pattern, SWIFT_EXTENSION))
+    } else if name.ends_with('*') && !name.contains('.') {
+        Some(format!("{}.{}", pattern, SWIFT_EXTENSION))
+    } else {
+        None
+    }
+}
+
+// The `srcs` patterns of every rule, or `None` if no rule has `srcs` or one
+// of them can't be evaluated
+fn all_srcs_patterns(tokens: &[Token<'_>]) -> Option<Vec<String>> {
+    let mut patterns = Vec::new();
+    let mut any_srcs = false;
+    for call in top_level_calls(tokens) {
+        if call.keyword(tokens, "srcs").is_some() {
+            patterns.extend(srcs_patterns(tokens, &call)?);
+            any_srcs = true;
+        }
+    }
+    any_srcs.then_some(patterns)
+}
+
+// How many leading directories the literal part of `pattern` shares with
+// `directory`
+fn shared_directories(pattern: &str, directory: &str) -> usize {
+    let base = pattern.rsplit_once('/').map_or("", |(base, _)| base);
+    base.split('/')
+        .take_while(|segment| !segment.contains(['*', '?', '[']))
+        .zip(directory.split('/'))
+        .take_while(|(a, b)| !a.is_empty() && a == b)
+        .count()
+}
+
+// Whether the last component of `pattern` names files with a build artifact
+// extension, e.g. `**/*.a` or `lib/libcore.dylib`
+fn is_build_artifact_pattern(pattern: &str) -> bool {
+    let name = pattern.rsplit('/').next().unwrap_or(pattern);
+    name.rsplit_once('.')
+        .is_some_and(|(_, extension)| BUILD_ARTIFACT_EXTENSIONS.contains(&extension))
+}
+
+// Whether `pattern` names vendored prebuilt binaries, e.g. `Frameworks/*.dylib`
+fn is_prebuilt_binary_pattern(pattern: &str) -> bool {
+    let name = pattern.rsplit('/').next().unwrap_or(pattern);
+    name.rsplit_once('.')
+        .is_some_and(|(_, extension)| PREBUILT_BINARY_EXTENSIONS.contains(&extension))
+}
+
+fn exclude_pattern(subpackage: &str) -> String {
+    format!("{}/**", subpackage)
+}
+
+// Every `glob(...)` call, at any nesting level
+fn glob_calls<'a>(tokens: &[Token<'a>]) -> Vec<Call<'a>> {
+    (0..tokens.len())
+        .filter(|&i| {
+            tokens[i].is_ident("glob")
+                && tokens.get(i + 1).map(|t| t.kind) == Some(TokenKind::LParen)
+                && (i == 0 || tokens[i - 1].kind != TokenKind::Dot)
+        })
+        .filter_map(|i| {
+            Some(Call {
+                name: tokens[i].text,
+                open: i + 1,
+                close: find_matching(tokens, i + 1)?,
+                line: tokens[i].line,
+            })
+        })
+        .collect()
+}
+
+// The include list: the first positional argument or `include = [...]`
+fn include_list<'a>(tokens: &[Token<'a>], glob: &Call<'a>) -> Option<Argument<'a>> {
+    glob.arguments(tokens)
+        .into_iter()
+        .find(|argument| matches!(argument.key, None | Some("include")))
+        .filter(|argument| is_list(tokens, argument))
+}
+
+// The include patterns of a glob, if they are all string literals
+fn literal_include(tokens: &[Token<'_>], glob: &Call<'_>) -> Option<Vec<String>> {
+    let include = include_list(tokens, glob)?;
+    let elements = &tokens[include.value.start + 1..include.value.end - 1];
+    elements
+        .iter()
+        .all(|t| {
+            matches!(
+                t.kind,
+                TokenKind::String | TokenKind::Comma | TokenKind::Comment
+            )
+        })
+        .then(|| string_elements(tokens, &include))
+}
+
+fn allows_empty(tokens: &[Token<'_>], glob: &Call<'_>) -> bool {
+    glob.keyword(tokens, "allow_empty")
+        .is_some_and(|argument| match &tokens[argument.value] {
+            [value] => value.is_ident("True"),
+            _ => false,
+        })
+}
+
+// Whether the glob matches anything in `package_dir`, or `None` if its
+// arguments aren't all literals
+fn glob_matches(tokens: &[Token<'_>], glob: &Call<'_>, package_dir: &Path) -> Option<bool> {
+    let include = literal_include(tokens, glob)?;
+    let excludes = match glob.keyword(tokens, "exclude") {
+        Some(exclude) if is_list(tokens, &exclude) => string_elements(tokens, &exclude),
+        Some(_) => return None,
+        None => Vec::new(),
+    };
+    let exclude_directories = match glob.keyword(tokens, "exclude_directories") {
+        Some(argument) => match &tokens[argument.value] {
+            [value] if value.text == "0" || value.is_ident("False") => false,
+            [value] if value.text == "1" || value.is_ident("True") => true,
+            _ => return None,
+        },
+        None => true,
+    };
+    let matched = evaluate_glob(package_dir, &include, &excludes, exclude_directories).ok()?;
+    Some(!matched.is_empty())
+}
+
+// The package's files, relative to it with `/` separators
+fn package_files(package_dir: &Path) -> std::io::Result<Vec<String>> {
+    Ok(collect_package_files(package_dir)?
+        .into_iter()
+        .map(|file| file.to_string_lossy().replace('\\', "/"))
+        .collect())
+}
+
+fn string_elements(tokens: &[Token<'_>], argument: &Argument<'_>) -> Vec<String> {
+    tokens[argument.value.clone()]
+        .iter()
+        .filter_map(Token::string_value)
+        .collect()
+}
+
+// Root-relative paths of the packages below `package_dir/base`. Packages
+// inside a nested package are covered by its exclude and not listed.
+fn nested_packages(package_dir: &Path, base: &str) -> Vec<String> {
+    let mut packages = Vec::new();
+    let mut walker = WalkDir::new(package_dir.join(base))
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter();
+
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_dir() || !is_package(entry.path()) {
+            continue;
+        }
+        walker.skip_current_dir();
+        if let Ok(relative) = entry.path().strip_prefix(package_dir) {
+            packages.push(relative.to_string_lossy().replace('\\', "/"));
+        }
+    }
+
+    packages
+}
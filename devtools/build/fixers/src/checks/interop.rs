@@ -0,0 +1,117 @@
+//! Check that Swift libraries depending on Objective-C targets enable
+//! rules_swift's Objective-C interop.
+
+use crate::checks::swift_library::swift_libraries;
+use crate::issue::BuildIssue;
+use crate::starlark::ast::{AttrValue, BuildFile};
+use crate::starlark::calls::{append_to_list, insert_after_name, is_list, top_level_calls};
+use crate::starlark::tokenizer::tokenize;
+
+/// The rules_swift feature Swift targets need to import Objective-C modules.
+pub const OBJC_INTEROP_FEATURE: &str = "swift.objc_interop";
+
+// Flag each swift_library whose deps include an objc_library of the same
+// file while its features lack `swift.objc_interop`. `package` is the
+// root-relative package path, so `//package:name` labels resolve too.
+// Features that can't be evaluated (variables, select()) aren't flagged.
+pub fn check_objc_interop(file: &BuildFile, package: Option<&str>) -> Vec<(BuildIssue, String)> {
+    let objc_libraries: Vec<&str> = file
+        .rules
+        .iter()
+        .filter(|rule| rule.rule_name == "objc_library")
+        .filter_map(|rule| rule.name())
+        .collect();
+    if objc_libraries.is_empty() {
+        return Vec::new();
+    }
+
+    let mut issues = Vec::new();
+    for library in swift_libraries(file) {
+        let has_feature = match &library.features {
+            None => false,
+            Some(AttrValue::List(features)) => features
+                .iter()
+                .any(|feature| feature.as_str() == Some(OBJC_INTEROP_FEATURE)),
+            Some(_) => continue,
+        };
+        if has_feature {
+            continue;
+        }
+
+        let mut labels = Vec::new();
+        if let Some(deps) = &library.deps {
+            collect_labels(deps, &mut labels);
+        }
+        let dependency = labels.into_iter().find(|label| {
+            local_target(label, package).is_some_and(|target| objc_libraries.contains(&target))
+        });
+        if let Some(dependency) = dependency {
+            let message = format!(
+                "swift_library {:?} depends on objc_library {:?} but doesn't set features = [{:?}]",
+                library.name, dependency, OBJC_INTEROP_FEATURE
+            );
+            issues.push((
+                BuildIssue::IncompatibleDependency {
+                    target: library.name,
+                    dependency: dependency.to_string(),
+                },
+                message,
+            ));
+        }
+    }
+
+    issues
+}
+
+// Add `swift.objc_interop` to the features of the swift_library `target`,
+// creating the attribute if it is missing
+pub fn fix_objc_interop(content: &str, target: &str) -> String {
+    let tokens = tokenize(content);
+    let Some(call) = top_level_calls(&tokens).into_iter().find(|call| {
+        call.name == "swift_library" && call.target_name(&tokens).as_deref() == Some(target)
+    }) else {
+        return content.to_string();
+    };
+
+    let feature = format!("\"{}\"", OBJC_INTEROP_FEATURE);
+    match call.keyword(&tokens, "features") {
+        Some(features) if is_list(&tokens, &features) => {
+            append_to_list(content, &tokens, &features, &feature)
+                .unwrap_or_else(|| content.to_string())
+        }
+        Some(_) => content.to_string(),
+        None => insert_after_name(
+            content,
+            &tokens,
+            &call,
+            &format!("features = [{}]", feature),
+        ),
+    }
+}
+
+// The string labels in `value`, including those of every select() branch
+pub(crate) fn collect_labels<'a>(value: &'a AttrValue, labels: &mut Vec<&'a str>) {
+    match value {
+        AttrValue::String(label) => labels.push(label),
+        AttrValue::List(elements) | AttrValue::Concat(elements) => elements
+            .iter()
+            .for_each(|element| collect_labels(element, labels)),
+        AttrValue::Select(branches) => branches
+            .iter()
+            .for_each(|(_, value)| collect_labels(value, labels)),
+        _ => {}
+    }
+}
+
+// The target name `label` refers to if it is in this package: `:name`,
+// a bare `name`, or `//package:name`
+fn local_target<'a>(label: &'a str, package: Option<&str>) -> Option<&'a str> {
+    if let Some(name) = label.strip_prefix(':') {
+        return Some(name);
+    }
+    if !label.contains([':', '/', '@']) {
+        return Some(label);
+    }
+    let (label_package, name) = label.strip_prefix("//")?.split_once(':')?;
+    (Some(label_package) == package).then_some(name)
+}
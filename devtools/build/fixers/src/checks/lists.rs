@@ -0,0 +1,117 @@
+//! Checks on list-valued rule attributes such as `deps`, `srcs` and `data`.
+
+use crate::issue::BuildIssue;
+use crate::starlark::tokenizer::{find_matching, tokenize, Token, TokenKind};
+
+/// The string elements of one list-valued attribute, as byte ranges into the file.
+struct StringList {
+    elements: Vec<(usize, usize, String)>,
+}
+
+// Find every `attr_name = [...]` keyword argument whose elements are all plain
+// string literals. Lists with comments or computed elements are skipped since
+// reordering them could change their meaning.
+fn find_string_lists(attr_name: &str, tokens: &[Token<'_>]) -> Vec<StringList> {
+    let mut lists = Vec::new();
+
+    for index in 0..tokens.len() {
+        let is_keyword_arg = tokens[index].is_ident(attr_name)
+            && index > 0
+            && matches!(tokens[index - 1].kind, TokenKind::LParen | TokenKind::Comma)
+            && tokens.get(index + 1).map(|t| t.kind) == Some(TokenKind::Equals)
+            && tokens.get(index + 2).map(|t| t.kind) == Some(TokenKind::LBracket);
+        if !is_keyword_arg {
+            continue;
+        }
+
+        let open = index + 2;
+        let Some(close) = find_matching(tokens, open) else {
+            continue;
+        };
+
+        let mut elements = Vec::new();
+        let mut expect_element = true;
+        let mut plain = true;
+        for token in &tokens[open + 1..close] {
+            match token.kind {
+                TokenKind::String if expect_element => {
+                    let value = token.string_value().unwrap_or_default();
+                    elements.push((token.start, token.end(), value));
+                    expect_element = false;
+                }
+                TokenKind::Comma if !expect_element => expect_element = true,
+                _ => {
+                    plain = false;
+                    break;
+                }
+            }
+        }
+
+        if plain {
+            lists.push(StringList { elements });
+        }
+    }
+
+    lists
+}
+
+fn sort_key(value: &str) -> (String, &str) {
+    (value.to_lowercase(), value)
+}
+
+fn is_sorted(list: &StringList) -> bool {
+    list.elements
+        .windows(2)
+        .all(|pair| sort_key(&pair[0].2) <= sort_key(&pair[1].2))
+}
+
+pub fn check_sorted_list_attribute(attr_name: &str, content: &str) -> Option<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let unsorted = find_string_lists(attr_name, &tokens)
+        .iter()
+        .filter(|list| !is_sorted(list))
+        .count();
+
+    (unsorted > 0).then(|| {
+        (
+            BuildIssue::UnsortedDeps {
+                attribute: attr_name.to_string(),
+            },
+            format!("{} {} list(s) are not sorted", unsorted, attr_name),
+        )
+    })
+}
+
+// Sort every `attr_name` string list in place. Elements move between the
+// existing slots, so indentation, separators and trailing commas are kept.
+pub fn fix_sorted_list_attribute(attr_name: &str, content: &str) -> String {
+    let tokens = tokenize(content);
+    let mut edits = Vec::new();
+
+    for list in find_string_lists(attr_name, &tokens) {
+        if is_sorted(&list) {
+            continue;
+        }
+        let mut sorted: Vec<_> = list
+            .elements
+            .iter()
+            .map(|(start, end, value)| (&content[*start..*end], value.as_str()))
+            .collect();
+        sorted.sort_by(|a, b| sort_key(a.1).cmp(&sort_key(b.1)));
+
+        for ((start, end, _), (text, _)) in list.elements.iter().zip(sorted) {
+            edits.push((*start, *end, text));
+        }
+    }
+
+    let mut new_content = String::with_capacity(content.len());
+    let mut last = 0;
+    for (start, end, text) in edits {
+        new_content.push_str(&content[last..start]);
+        new_content.push_str(text);
+        last = end;
+    }
+    new_content.push_str(&content[last..]);
+
+    new_content
+}
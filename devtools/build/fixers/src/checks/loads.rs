@@ -0,0 +1,371 @@
+//! Checks on `load()` statements.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::checks::workspace::{fix_missing_load, loaded_symbols};
+use crate::issue::BuildIssue;
+use crate::starlark::ast::{AttrValue, BuildFile};
+use crate::starlark::calls::top_level_calls;
+use crate::starlark::tokenizer::{quote, tokenize, Token};
+
+/// Built-in migrations for rules_swift and rules_apple bzl files that moved
+/// to their Bazel Central Registry repository names.
+pub const DEFAULT_RULE_MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "@build_bazel_rules_swift//swift:swift.bzl",
+        "@rules_swift//swift:swift.bzl",
+    ),
+    (
+        "@build_bazel_rules_apple//apple:apple.bzl",
+        "@rules_apple//apple:apple.bzl",
+    ),
+    (
+        "@build_bazel_rules_apple//apple:ios.bzl",
+        "@rules_apple//apple:ios.bzl",
+    ),
+    (
+        "@build_bazel_rules_apple//apple:macos.bzl",
+        "@rules_apple//apple:macos.bzl",
+    ),
+    (
+        "@build_bazel_rules_apple//apple:tvos.bzl",
+        "@rules_apple//apple:tvos.bzl",
+    ),
+    (
+        "@build_bazel_rules_apple//apple:watchos.bzl",
+        "@rules_apple//apple:watchos.bzl",
+    ),
+    (
+        "@build_bazel_rules_apple//apple:resources.bzl",
+        "@rules_apple//apple:resources.bzl",
+    ),
+    (
+        "@build_bazel_apple_support//lib:apple_support.bzl",
+        "@apple_support//lib:apple_support.bzl",
+    ),
+];
+
+/// Functions a BUILD file can call without loading them: Bazel's BUILD
+/// built-ins, its native rules and the Starlark globals.
+pub const BUILTIN_SYMBOLS: &[&str] = &[
+    "alias",
+    "config_setting",
+    "constraint_setting",
+    "constraint_value",
+    "environment",
+    "environment_group",
+    "exports_files",
+    "filegroup",
+    "genquery",
+    "genrule",
+    "glob",
+    "label_flag",
+    "label_setting",
+    "licenses",
+    "package",
+    "package_group",
+    "package_name",
+    "platform",
+    "repository_name",
+    "select",
+    "subpackages",
+    "test_suite",
+    "toolchain",
+    "toolchain_type",
+    // Native rules
+    "aar_import",
+    "android_binary",
+    "android_library",
+    "android_local_test",
+    "android_sdk",
+    "available_xcodes",
+    "cc_binary",
+    "cc_import",
+    "cc_library",
+    "cc_proto_library",
+    "cc_shared_library",
+    "cc_test",
+    "cc_toolchain",
+    "cc_toolchain_suite",
+    "fdo_prefetch_hints",
+    "fdo_profile",
+    "java_binary",
+    "java_import",
+    "java_library",
+    "java_lite_proto_library",
+    "java_package_configuration",
+    "java_plugin",
+    "java_proto_library",
+    "java_runtime",
+    "java_test",
+    "java_toolchain",
+    "objc_import",
+    "objc_library",
+    "proto_lang_toolchain",
+    "proto_library",
+    "py_binary",
+    "py_library",
+    "py_runtime",
+    "py_test",
+    "sh_binary",
+    "sh_library",
+    "sh_test",
+    "xcode_config",
+    "xcode_version",
+    // Starlark
+    "all",
+    "any",
+    "bool",
+    "dict",
+    "enumerate",
+    "fail",
+    "getattr",
+    "hasattr",
+    "int",
+    "len",
+    "list",
+    "max",
+    "min",
+    "print",
+    "range",
+    "reversed",
+    "sorted",
+    "str",
+    "tuple",
+    "type",
+    "zip",
+    "Label",
+];
+
+/// The project's own macros and the bzl files that define them, as read from
+/// `macro_registry.toml` (`umbra_swift_library = "//bazel:umbra.bzl"`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct MacroRegistry {
+    macros: BTreeMap<String, String>,
+}
+
+impl MacroRegistry {
+    pub fn new(macros: BTreeMap<String, String>) -> Self {
+        MacroRegistry { macros }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.macros.is_empty()
+    }
+
+    /// The bzl file that defines `name`, if it is a registered macro.
+    pub fn bzl(&self, name: &str) -> Option<&str> {
+        self.macros.get(name).map(String::as_str)
+    }
+
+    /// Every macro name and its bzl file, sorted by name.
+    pub fn macros(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.macros
+            .iter()
+            .map(|(name, bzl)| (name.as_str(), bzl.as_str()))
+    }
+}
+
+pub fn default_rule_migrations() -> BTreeMap<String, String> {
+    DEFAULT_RULE_MIGRATIONS
+        .iter()
+        .map(|(from, to)| (from.to_string(), to.to_string()))
+        .collect()
+}
+
+// The bzl label loaded by each top-level load(), with its token index
+fn loaded_labels(tokens: &[Token<'_>]) -> Vec<(String, usize)> {
+    top_level_calls(tokens)
+        .iter()
+        .filter(|call| call.name == "load")
+        .filter_map(|call| {
+            let label = call.open + 1;
+            tokens
+                .get(label)?
+                .string_value()
+                .map(|value| (value, label))
+        })
+        .collect()
+}
+
+// Report each load of a bzl file that `migrations` says has moved
+pub fn check_legacy_rule_loads(
+    content: &str,
+    migrations: &BTreeMap<String, String>,
+) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues: Vec<(BuildIssue, String)> = Vec::new();
+
+    for (label, index) in loaded_labels(&tokens) {
+        let Some(new_label) = migrations.get(&label) else {
+            continue;
+        };
+        let issue = BuildIssue::LegacyRuleLoad {
+            from: label.clone(),
+            to: new_label.clone(),
+        };
+        if issues.iter().any(|(existing, _)| *existing == issue) {
+            continue;
+        }
+        issues.push((
+            issue,
+            format!(
+                "line {} loads {}, which has moved to {}",
+                tokens[index].line, label, new_label
+            ),
+        ));
+    }
+
+    issues
+}
+
+// Point every load() of `from` at `to`. Only the label argument of load()
+// calls is rewritten, so other loads (even on the same line) are untouched.
+pub fn fix_legacy_rule_load(content: &str, from: &str, to: &str) -> String {
+    let tokens = tokenize(content);
+    let mut new_content = content.to_string();
+
+    for (_, index) in loaded_labels(&tokens)
+        .into_iter()
+        .rev()
+        .filter(|(label, _)| label == from)
+    {
+        let token = &tokens[index];
+        new_content.replace_range(token.start..token.end(), &quote(to));
+    }
+
+    new_content
+}
+
+// Report each function called in the file that is neither loaded nor a
+// built-in, once per name. swift_library is left to
+// MissingSwiftLibraryLoad, whose fix adds its load.
+pub fn check_undeclared_loads(file: &BuildFile) -> Vec<(BuildIssue, String)> {
+    let loaded: Vec<&str> = file
+        .loads
+        .iter()
+        .flat_map(|load| &load.symbols)
+        .map(|symbol| symbol.local.as_str())
+        .collect();
+
+    let mut calls = Vec::new();
+    for rule in &file.rules {
+        calls.push((rule.rule_name.as_str(), rule.line));
+        for value in rule
+            .args
+            .iter()
+            .chain(rule.attrs.iter().map(|attr| &attr.value))
+        {
+            called_functions(value, rule.line, &mut calls);
+        }
+    }
+
+    let mut issues: Vec<(BuildIssue, String)> = Vec::new();
+    for (symbol, line) in calls {
+        if symbol == "swift_library"
+            || symbol.starts_with("native.")
+            || BUILTIN_SYMBOLS.contains(&symbol)
+            || loaded.contains(&symbol)
+        {
+            continue;
+        }
+        let issue = BuildIssue::UndeclaredLoad {
+            symbol: symbol.to_string(),
+        };
+        if issues.iter().any(|(existing, _)| *existing == issue) {
+            continue;
+        }
+        issues.push((
+            issue,
+            format!(
+                "line {} calls {}, which is neither loaded nor a Bazel built-in",
+                line, symbol
+            ),
+        ));
+    }
+
+    issues
+}
+
+// Report each registered macro the file calls without loading it, once per
+// macro
+pub fn check_macro_loads(content: &str, registry: &MacroRegistry) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let calls = top_level_calls(&tokens);
+    let loaded = loaded_symbols(&tokens, &calls);
+
+    let mut issues: Vec<(BuildIssue, String)> = Vec::new();
+    for call in &calls {
+        let Some(bzl) = registry.bzl(call.name) else {
+            continue;
+        };
+        let issue = BuildIssue::MacroCallWithoutLoad {
+            symbol: call.name.to_string(),
+            bzl: bzl.to_string(),
+        };
+        if loaded.iter().any(|symbol| symbol == call.name)
+            || issues.iter().any(|(existing, _)| *existing == issue)
+        {
+            continue;
+        }
+        issues.push((
+            issue,
+            format!(
+                "line {} calls the macro {} without loading it from {}",
+                tokens[call.open].line, call.name, bzl
+            ),
+        ));
+    }
+
+    issues
+}
+
+// Load the macro `symbol` from `bzl`, alongside the file's other symbols
+// from it if it loads any
+pub fn fix_macro_load(content: &str, symbol: &str, bzl: &str) -> String {
+    let tokens = tokenize(content);
+    if loaded_symbols(&tokens, &top_level_calls(&tokens))
+        .iter()
+        .any(|loaded| loaded == symbol)
+    {
+        return content.to_string();
+    }
+    fix_missing_load(content, symbol, bzl)
+}
+
+// The functions called within `value`, such as glob(), with the line of the
+// rule they appear in
+fn called_functions<'a>(value: &'a AttrValue, line: usize, calls: &mut Vec<(&'a str, usize)>) {
+    match value {
+        AttrValue::Call {
+            function,
+            args,
+            attrs,
+        } => {
+            calls.push((function, line));
+            for value in args.iter().chain(attrs.iter().map(|attr| &attr.value)) {
+                called_functions(value, line, calls);
+            }
+        }
+        AttrValue::List(elements) | AttrValue::Concat(elements) => {
+            for element in elements {
+                called_functions(element, line, calls);
+            }
+        }
+        AttrValue::Dict(entries) => {
+            for (key, value) in entries {
+                called_functions(key, line, calls);
+                called_functions(value, line, calls);
+            }
+        }
+        AttrValue::Select(branches) => {
+            for (_, value) in branches {
+                called_functions(value, line, calls);
+            }
+        }
+        AttrValue::String(_) | AttrValue::Number(_) | AttrValue::Ident(_) => {}
+    }
+}
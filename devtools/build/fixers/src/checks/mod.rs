@@ -0,0 +1,716 @@
+//! Individual BUILD file checks and the fixes that resolve them.
+
+pub mod apple;
+pub mod attribute_order;
+pub mod attributes;
+pub mod comments;
+pub mod deps;
+pub mod exports;
+pub mod feature_flags;
+pub mod formatting;
+pub mod globs;
+pub mod interop;
+pub mod lists;
+pub mod loads;
+pub mod module_names;
+pub mod naming_convention;
+pub mod package;
+pub mod paths;
+pub mod resource_bundler;
+pub mod resources;
+pub mod spm;
+pub mod swift_library;
+pub mod target_names;
+pub mod version_catalog;
+pub mod visibility;
+pub mod workspace;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::discovery::is_workspace_file;
+use crate::issue::{BuildIssue, Finding};
+use crate::migrations::rules_swift;
+use crate::report::suppress::apply_suppressions;
+use crate::starlark::ast;
+use crate::starlark::calls::top_level_calls;
+use crate::starlark::formatter::format_build_file;
+use crate::starlark::tokenizer::tokenize;
+use crate::target_graph::TargetGraph;
+
+/// A content check: returns the issue and an explanation if the content has it.
+pub type Check = fn(&str) -> Option<(BuildIssue, String)>;
+
+/// Checks whose fixes normalize the raw text. Each runs (and its fix is
+/// applied) before any other check, so later checks see normalized content.
+const NORMALIZATIONS: &[Check] = &[
+    formatting::check_line_endings,
+    formatting::check_trailing_whitespace,
+    formatting::check_quote_style,
+];
+
+/// Every other check, in the order their fixes should be applied, with the
+/// rule type it looks at (`None` for checks of the whole file, or of any rule:
+/// see [`fix_issue_in_scope`]).
+const CHECKS: &[(Check, Option<&str>)] = &[
+    (
+        swift_library::check_swift_library_load,
+        Some("swift_library"),
+    ),
+    (swift_library::check_custom_library, Some("swift_library")),
+    (exports::check_exports_attribute, Some("swift_library")),
+    (swift_library::check_glob_patterns, None),
+    (globs::check_nonhermetic_glob_patterns, None),
+    (globs::check_unremovable_artifact_patterns, None),
+    (package::check_package_declaration, None),
+    (attributes::check_testonly, Some("swift_test")),
+    (
+        attributes::check_generates_header_consistency,
+        Some("swift_library"),
+    ),
+    (
+        attributes::check_generates_header_conflict,
+        Some("swift_library"),
+    ),
+    (attributes::check_visibility_format, None),
+    (
+        attributes::check_absolute_include_paths,
+        Some("swift_library"),
+    ),
+];
+
+/// Run every check over `content`.
+pub fn analyze_build_file(content: &str, config: &Config) -> Vec<Finding> {
+    analyze(content, None, config)
+}
+
+/// Run every check over `content`, including those that read the package's
+/// sources next to `build_file`. WORKSPACE files get the WORKSPACE checks.
+pub fn analyze_build_file_at(content: &str, build_file: &Path, config: &Config) -> Vec<Finding> {
+    if build_file.file_name().is_some_and(is_workspace_file) {
+        return analyze_workspace_file(content, config);
+    }
+    analyze(content, build_file.parent(), config)
+}
+
+/// Run the normalizations and the repository rule checks over a WORKSPACE file.
+pub fn analyze_workspace_file(content: &str, config: &Config) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let content = normalize(content, &mut findings);
+
+    findings.extend(
+        workspace::check_workspace_file(&content, config)
+            .into_iter()
+            .map(Finding::from),
+    );
+    findings.extend(
+        version_catalog::check_version_catalog(&content, &config.version_catalog)
+            .into_iter()
+            .map(Finding::from),
+    );
+    findings.extend(formatting::check_trailing_newline(&content).map(Finding::from));
+    apply_suppressions(&content, findings)
+}
+
+// Run the normalizations, returning the normalized content
+fn normalize(content: &str, findings: &mut Vec<Finding>) -> String {
+    let mut content = content.to_string();
+    for check in NORMALIZATIONS {
+        if let Some((issue, message)) = check(&content) {
+            content = fix_issue(&issue, &content);
+            findings.push(Finding { issue, message });
+        }
+    }
+    content
+}
+
+fn analyze(content: &str, package_dir: Option<&Path>, config: &Config) -> Vec<Finding> {
+    if config.format_only {
+        return formatting::check_canonical_format(content)
+            .map(Finding::from)
+            .into_iter()
+            .collect();
+    }
+
+    // An empty file has nothing else worth reporting
+    if let Some(finding) = package::check_empty_build_file(content) {
+        return vec![Finding::from(finding)];
+    }
+
+    let mut findings = Vec::new();
+    let content = normalize(content, &mut findings);
+
+    findings.extend(
+        CHECKS
+            .iter()
+            .filter(|(_, rule)| rule.is_none_or(|rule| config.analyzes_rule(rule)))
+            .filter_map(|(check, _)| check(&content))
+            .map(Finding::from),
+    );
+    if config.require_dead_strip {
+        findings.extend(
+            attributes::check_dead_strip(&content)
+                .into_iter()
+                .map(Finding::from),
+        );
+    }
+    if config.require_license {
+        findings.extend(package::check_license(&content, &config.license_type).map(Finding::from));
+    }
+
+    findings.extend(
+        module_names::check_module_names(&content, &config.module_names)
+            .into_iter()
+            .map(Finding::from),
+    );
+    findings.extend(
+        globs::check_select_in_glob(&content)
+            .into_iter()
+            .map(Finding::from),
+    );
+    findings.extend(
+        feature_flags::check_select_defaults(&content)
+            .into_iter()
+            .map(Finding::from),
+    );
+    findings.extend(
+        comments::check_commented_out_rules(&content)
+            .into_iter()
+            .map(Finding::from),
+    );
+
+    let parsed = ast::parse(&content);
+    if parsed.is_err() {
+        findings.extend(
+            attributes::check_deprecated_attributes_in_content(&content)
+                .into_iter()
+                .map(Finding::from),
+        );
+    }
+    if let Ok(file) = parsed {
+        let package = package_dir
+            .and_then(|dir| dir.strip_prefix(&config.root_dir).ok())
+            .map(|package| package.to_string_lossy().replace('\\', "/"));
+        findings.extend(
+            interop::check_objc_interop(&file, package.as_deref())
+                .into_iter()
+                .map(Finding::from),
+        );
+        findings.extend(
+            swift_library::check_swift_library_schema(&file)
+                .into_iter()
+                .map(Finding::from),
+        );
+        findings.extend(
+            attributes::check_deprecated_attributes(&file)
+                .into_iter()
+                .map(Finding::from),
+        );
+        findings.extend(
+            swift_library::check_umbra_wrapper_attributes(&file)
+                .into_iter()
+                .map(Finding::from),
+        );
+        findings.extend(
+            loads::check_undeclared_loads(&file)
+                .into_iter()
+                // Registered macros are reported (and fixed) below instead
+                .filter(|(issue, _)| {
+                    !matches!(issue, BuildIssue::UndeclaredLoad { symbol }
+                        if config.macro_registry.bzl(symbol).is_some())
+                })
+                .map(Finding::from),
+        );
+    }
+    findings.extend(
+        loads::check_macro_loads(&content, &config.macro_registry)
+            .into_iter()
+            .map(Finding::from),
+    );
+
+    let root_name = config.root_dir.canonicalize().ok().and_then(|root| {
+        root.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    });
+    findings.extend(
+        paths::check_hardcoded_paths(
+            &content,
+            root_name.as_deref(),
+            &config.project_path_variable,
+        )
+        .into_iter()
+        .map(Finding::from),
+    );
+
+    findings.extend(
+        apple::check_minimum_os_version(&content, &config.minimum_os_versions)
+            .into_iter()
+            .map(Finding::from),
+    );
+    findings.extend(
+        apple::check_bundle_id(&content, &config.bundle_id_prefix)
+            .into_iter()
+            .map(Finding::from),
+    );
+
+    let parsed = ast::parse(&content).ok();
+    match (package_dir, &parsed) {
+        (Some(package_dir), Some(file)) => findings.extend(
+            swift_library::check_empty_srcs(file, package_dir)
+                .into_iter()
+                .map(Finding::from),
+        ),
+        // Without the package's files or the AST only a missing srcs is found
+        _ => findings.extend(
+            swift_library::check_missing_srcs(&content)
+                .into_iter()
+                .map(Finding::from),
+        ),
+    }
+
+    if let Some(package_dir) = package_dir {
+        if let Some(file) = &parsed {
+            findings.extend(
+                swift_library::check_swift_settings(file, package_dir)
+                    .into_iter()
+                    .map(Finding::from),
+            );
+        }
+        findings.extend(
+            swift_library::check_test_files_in_library(&package_dir.join("BUILD.bazel"), &content)
+                .map(Finding::from),
+        );
+        findings.extend(
+            globs::check_wildcard_globs(&content, package_dir)
+                .into_iter()
+                .map(Finding::from),
+        );
+        findings.extend(
+            globs::check_generated_sources(&content, package_dir, &config.generated_file_patterns)
+                .into_iter()
+                .map(Finding::from),
+        );
+        findings.extend(
+            globs::check_mixed_source_languages(&content, package_dir)
+                .into_iter()
+                .map(Finding::from),
+        );
+        findings.extend(
+            globs::check_orphaned_sources(&content, package_dir)
+                .into_iter()
+                .map(Finding::from),
+        );
+        findings.extend(
+            resources::check_missing_data(&content, package_dir)
+                .into_iter()
+                .map(Finding::from),
+        );
+        findings.extend(
+            resource_bundler::check_resource_bundling(&content, package_dir)
+                .into_iter()
+                .map(Finding::from),
+        );
+        if spm::SWIFT_RULES
+            .iter()
+            .any(|rule| config.analyzes_rule(rule))
+        {
+            findings.extend(
+                spm::check_swift_package_manifest(&content, package_dir).map(Finding::from),
+            );
+        }
+    }
+
+    for attr_name in &config.sorted_list_attributes {
+        findings.extend(lists::check_sorted_list_attribute(attr_name, &content).map(Finding::from));
+    }
+
+    if let Some(package_dir) = package_dir.filter(|_| config.prune_deps) {
+        findings.extend(
+            deps::check_unused_dependencies(&content, package_dir, config)
+                .into_iter()
+                .map(Finding::from),
+        );
+    }
+
+    // Pruning can empty a deps list, so check the pruned content
+    let pruned = if config.prune_deps {
+        apply_fixes_in_scope(&content, &findings, config)
+    } else {
+        content.clone()
+    };
+    let package = package_dir
+        .map(|package_dir| package_dir.to_string_lossy())
+        .unwrap_or_default();
+    findings.extend(
+        deps::check_repeated_deps(&pruned, &package, &config.root_dir)
+            .into_iter()
+            .map(Finding::from),
+    );
+    findings.extend(
+        deps::check_empty_deps(&pruned)
+            .into_iter()
+            .map(Finding::from),
+    );
+    if !config.external_label_rewrites.is_empty() {
+        findings.extend(
+            deps::check_external_labels(&pruned, &config.external_label_rewrites)
+                .into_iter()
+                .map(Finding::from),
+        );
+    }
+
+    // Checked on the fixed content, as the allow_empty fix and the default srcs
+    // of an empty swift_library add `allow_empty = True` to every glob. Globs
+    // that match files then don't need it either.
+    if let Some(package_dir) = package_dir.filter(|_| config.warn_redundant_allow_empty) {
+        if !globs::globs_need_allow_empty(&content, package_dir) {
+            findings.retain(|finding| finding.issue != BuildIssue::GlobWithoutAllowEmpty);
+        }
+        let fixed = apply_fixes_in_scope(&content, &findings, config);
+        findings.extend(
+            globs::check_redundant_allow_empty(&fixed, package_dir)
+                .into_iter()
+                .map(Finding::from),
+        );
+    }
+
+    // Renaming runs after the other fixes that look targets up by name
+    if let Some(package_dir) = package_dir.filter(|_| config.check_target_names) {
+        if let Ok(file) = ast::parse(&content) {
+            findings.extend(
+                target_names::check_target_names(&file, package_dir, &config.root_dir)
+                    .map(Finding::from),
+            );
+        }
+    }
+    if !config.naming_convention.is_empty() {
+        findings.extend(
+            naming_convention::check_naming_conventions(&content, &config.naming_convention)
+                .into_iter()
+                .map(Finding::from),
+        );
+    }
+
+    // Cross-file findings were computed up front from the whole workspace
+    let package = package_dir
+        .and_then(|dir| dir.strip_prefix(&config.root_dir).ok())
+        .map(|package| package.to_string_lossy().replace('\\', "/"));
+    if let Some(cross_file) = package.and_then(|package| config.cross_file_findings.get(&package)) {
+        findings.extend(cross_file.iter().cloned());
+    }
+
+    // Drop the findings about targets of rules outside the rule filter, and
+    // those without a target whose fixes only change such targets
+    if !config.rule_filter.is_empty() {
+        let rules = target_rules(&content);
+        findings.retain(|finding| match finding.issue.target() {
+            Some(target) => rules
+                .iter()
+                .any(|(name, rule)| name == target && config.analyzes_rule(rule)),
+            None => {
+                !concerns_any_rule(&finding.issue)
+                    || fix_issue_in_scope(&finding.issue, &content, config) != content
+            }
+        });
+    }
+
+    // Fixes can add loads of their own, so migrate the fixed content
+    if !config.rule_migrations.is_empty() {
+        let fixed = apply_fixes_in_scope(&content, &findings, config);
+        findings.extend(
+            loads::check_legacy_rule_loads(&fixed, &config.rule_migrations)
+                .into_iter()
+                .map(Finding::from),
+        );
+    }
+
+    // Runs after the load() migrations so split loads keep the new repository name
+    if let Some(upgrade) = config.rules_swift_upgrade {
+        let fixed = apply_fixes_in_scope(&content, &findings, config);
+        findings.extend(
+            rules_swift::check_rules_swift_upgrade(&fixed, upgrade)
+                .into_iter()
+                .map(Finding::from),
+        );
+    }
+
+    // Other fixes insert attributes, so check the order of the fixed content
+    if config.sort_attributes {
+        let fixed = apply_fixes_in_scope(&content, &findings, config);
+        findings.extend(attribute_order::check_attribute_order(&fixed).map(Finding::from));
+    }
+
+    // Fixes change the lines, so measure the fixed content
+    if let Some(max) = config.max_line_length {
+        let fixed = apply_fixes_in_scope(&content, &findings, config);
+        findings.extend(
+            formatting::check_line_length(&fixed, max)
+                .into_iter()
+                .map(Finding::from),
+        );
+    }
+
+    findings.extend(formatting::check_trailing_newline(&content).map(Finding::from));
+
+    // The formatter runs after every other fix, so check the fixed content
+    if config.format {
+        let fixed = apply_fixes_in_scope(&content, &findings, config);
+        findings.extend(formatting::check_canonical_format(&fixed).map(Finding::from));
+    }
+
+    apply_suppressions(&content, findings)
+}
+
+/// Run the checks that look across BUILD files over the whole workspace's
+/// `graph`, returning their findings by the package they are about.
+pub fn analyze_target_graph(graph: &TargetGraph) -> BTreeMap<String, Vec<Finding>> {
+    let mut findings: BTreeMap<String, Vec<Finding>> = BTreeMap::new();
+    let issues = module_names::check_conflicting_module_names(graph)
+        .into_iter()
+        .chain(feature_flags::check_config_settings(graph))
+        .chain(visibility::check_visibility_leaks(graph));
+    for (label, issue, message) in issues {
+        findings
+            .entry(label.package)
+            .or_default()
+            .push(Finding { issue, message });
+    }
+    findings
+}
+
+// The name and rule type of each target in the file
+fn target_rules(content: &str) -> Vec<(String, String)> {
+    let tokens = tokenize(content);
+    top_level_calls(&tokens)
+        .iter()
+        .filter_map(|call| Some((call.target_name(&tokens)?, call.name.to_string())))
+        .collect()
+}
+
+/// Apply the fix for a single issue.
+pub fn fix_issue(issue: &BuildIssue, content: &str) -> String {
+    match issue {
+        BuildIssue::MissingSwiftLibraryLoad => swift_library::ensure_swift_library_load(content),
+        BuildIssue::CustomLibraryRule => swift_library::convert_umbra_swift_library(content),
+        BuildIssue::ExportsAttribute => exports::remove_exports_attribute(content),
+        BuildIssue::GlobWithoutAllowEmpty => swift_library::fix_glob_patterns(content),
+        BuildIssue::EmptySrcs { target, .. } => swift_library::fix_empty_srcs(content, target),
+        BuildIssue::UnsortedDeps { attribute } => {
+            lists::fix_sorted_list_attribute(attribute, content)
+        }
+        BuildIssue::MissingModuleName {
+            target,
+            module_name,
+        } => module_names::fix_missing_module_name(content, target, module_name),
+        BuildIssue::MissingMinimumOsVersion { target, version } => {
+            apple::fix_minimum_os_version(content, target, version)
+        }
+        BuildIssue::MissingBundleId { target, bundle_id } => {
+            apple::fix_bundle_id(content, target, bundle_id)
+        }
+        BuildIssue::EmptyBuildFile => package::fix_empty_build_file(content),
+        BuildIssue::MissingPackageDeclaration => package::fix_package_declaration(content),
+        BuildIssue::MissingLicense { license } => package::fix_license(content, license),
+        BuildIssue::CrlfLineEnding => formatting::fix_line_endings(content),
+        BuildIssue::TrailingWhitespace => formatting::fix_trailing_whitespace(content),
+        BuildIssue::InconsistentQuoteStyle => formatting::fix_quote_style(content),
+        BuildIssue::MissingTrailingNewline => formatting::fix_trailing_newline(content),
+        BuildIssue::MissingTestonly => attributes::fix_testonly(content),
+        BuildIssue::MissingGeneratesHeader => attributes::fix_generates_header_consistency(content),
+        BuildIssue::GeneratesHeaderConflict => content.to_string(),
+        BuildIssue::IncorrectVisibilityFormat => attributes::fix_visibility_format(content),
+        BuildIssue::AbsoluteIncludePath => attributes::fix_absolute_include_paths(content),
+        BuildIssue::HardcodedProjectPath { path, replacement } => {
+            paths::fix_hardcoded_path(content, path, replacement)
+        }
+        BuildIssue::LegacyRuleLoad { from, to } => loads::fix_legacy_rule_load(content, from, to),
+        BuildIssue::MacroCallWithoutLoad { symbol, bzl } => {
+            loads::fix_macro_load(content, symbol, bzl)
+        }
+        BuildIssue::InconsistentTargetName {
+            target,
+            expected,
+            package,
+        } => target_names::fix_target_name(content, target, expected, package),
+        BuildIssue::NamingConventionViolation {
+            target,
+            renamed: Some(renamed),
+            ..
+        } => naming_convention::fix_naming_convention(content, target, renamed),
+        BuildIssue::IncompatibleDependency { target, .. } => {
+            interop::fix_objc_interop(content, target)
+        }
+        BuildIssue::RepeatedDep {
+            target,
+            label,
+            first,
+        } => deps::fix_repeated_dep(content, target, label, first),
+        BuildIssue::EmptyDepsAttribute { target } => deps::fix_empty_deps(content, target),
+        BuildIssue::AbsoluteExternalLabel {
+            target,
+            label,
+            replacement,
+        } => deps::fix_external_label(content, target, label, replacement),
+        BuildIssue::UnusedDependency { target, label } => {
+            deps::fix_unused_dependency(content, target, label)
+        }
+        BuildIssue::WildcardGlob {
+            pattern,
+            subpackage,
+        } => globs::fix_wildcard_glob(content, pattern, subpackage),
+        BuildIssue::RedundantAllowEmpty { include } => {
+            globs::fix_redundant_allow_empty(content, include)
+        }
+        BuildIssue::GeneratedSourcesInGlob { include, exclude } => {
+            globs::fix_glob_exclude(content, include, exclude)
+        }
+        BuildIssue::NonHermeticGlob {
+            patterns,
+            removable: true,
+        } => globs::fix_nonhermetic_glob_patterns(content, patterns),
+        BuildIssue::MixedSourceLanguages {
+            target, pattern, ..
+        } => globs::fix_mixed_source_languages(content, target, pattern),
+        BuildIssue::OrphanedSourceFile { file } => globs::fix_orphaned_source(content, file),
+        BuildIssue::MissingStrip { target } => attributes::fix_dead_strip(content, target),
+        BuildIssue::DeprecatedAttribute {
+            target,
+            attribute,
+            since,
+            replacement,
+        } => attributes::fix_deprecated_attribute(
+            content,
+            target,
+            attribute,
+            &format!(
+                "{} was removed in rules_swift {}; use {}",
+                attribute, since, replacement
+            ),
+        ),
+        BuildIssue::UnsupportedAttribute {
+            target,
+            attribute,
+            rule,
+        } => attributes::fix_deprecated_attribute(
+            content,
+            target,
+            attribute,
+            &format!("{} was removed; {} doesn't support it", attribute, rule),
+        ),
+        BuildIssue::LineTooLong {
+            target: Some(target),
+            attribute: Some(attribute),
+            splittable: true,
+            ..
+        } => formatting::fix_line_length(content, target, attribute),
+        BuildIssue::DualBuildSystem
+        | BuildIssue::TestFilesInLibrary { .. }
+        | BuildIssue::UndeclaredLoad { .. }
+        | BuildIssue::UnusedSuppression { .. }
+        | BuildIssue::SelectInGlob { .. }
+        | BuildIssue::SelectWithoutDefault { .. }
+        | BuildIssue::CommentedOutRule { .. }
+        | BuildIssue::InvalidSyntax { .. }
+        | BuildIssue::InvalidSwiftLibrary { .. }
+        | BuildIssue::PrivateVisibilityLeak { .. }
+        | BuildIssue::UnknownConfigSetting { .. }
+        | BuildIssue::ConflictingModuleNames { .. }
+        | BuildIssue::NamingConventionViolation { renamed: None, .. }
+        | BuildIssue::UnbundledResources { bundle: None, .. }
+        | BuildIssue::NonHermeticGlob {
+            removable: false, ..
+        }
+        | BuildIssue::MissingSwiftSetting { .. }
+        | BuildIssue::LineTooLong { .. } => content.to_string(),
+        BuildIssue::MissingDataAttribute { target } => resources::fix_missing_data(content, target),
+        BuildIssue::UnbundledResources {
+            target,
+            resources,
+            bundle: Some(bundle),
+        } => resource_bundler::fix_resource_bundling(content, target, resources, bundle),
+        BuildIssue::RulesSwiftMigration { step } => rules_swift::fix_migration_step(content, step),
+        BuildIssue::UnorderedAttributes => attribute_order::fix_sorted_attributes(content),
+        BuildIssue::NonCanonicalFormat => format_build_file(content),
+        BuildIssue::Workspace(issue) => workspace::fix_workspace_issue(issue, content),
+    }
+}
+
+/// Apply the fix for `issue`. Fixes of issues that can concern any rule leave
+/// the targets of rules outside `config.rule_filter` as they were; the
+/// others only touch analyzed targets or the file as a whole.
+pub fn fix_issue_in_scope(issue: &BuildIssue, content: &str, config: &Config) -> String {
+    let fixed = fix_issue(issue, content);
+    if config.rule_filter.is_empty() || fixed == content || !concerns_any_rule(issue) {
+        return fixed;
+    }
+    restore_filtered_targets(content, fixed, config)
+}
+
+// Issues without a target from the checks that look into targets of any rule
+fn concerns_any_rule(issue: &BuildIssue) -> bool {
+    matches!(
+        issue,
+        BuildIssue::GlobWithoutAllowEmpty
+            | BuildIssue::NonHermeticGlob {
+                removable: true,
+                ..
+            }
+            | BuildIssue::IncorrectVisibilityFormat
+            | BuildIssue::UnsortedDeps { .. }
+            | BuildIssue::UnorderedAttributes
+    )
+}
+
+// Put back the argument lists of the targets in `fixed` whose rules are
+// outside the rule filter as they are in `original`. A target is found by
+// its rule and name, so a fix that renames either isn't undone.
+fn restore_filtered_targets(original: &str, fixed: String, config: &Config) -> String {
+    let tokens = tokenize(original);
+    let filtered: Vec<(&str, String, &str)> = top_level_calls(&tokens)
+        .iter()
+        .filter(|call| !config.analyzes_rule(call.name))
+        .filter_map(|call| {
+            let arguments = &original[tokens[call.open].start..tokens[call.close].end()];
+            Some((call.name, call.target_name(&tokens)?, arguments))
+        })
+        .collect();
+    if filtered.is_empty() {
+        return fixed;
+    }
+
+    let fixed_tokens = tokenize(&fixed);
+    let mut restored = fixed.clone();
+    for call in top_level_calls(&fixed_tokens).iter().rev() {
+        let Some(target) = call.target_name(&fixed_tokens) else {
+            continue;
+        };
+        if let Some((_, _, arguments)) = filtered
+            .iter()
+            .find(|(rule, name, _)| *rule == call.name && *name == target)
+        {
+            let range = fixed_tokens[call.open].start..fixed_tokens[call.close].end();
+            restored.replace_range(range, arguments);
+        }
+    }
+    restored
+}
+
+/// Apply the fixes for `findings` in order, each one seeing the output of the last.
+pub fn apply_fixes(content: &str, findings: &[Finding]) -> String {
+    findings
+        .iter()
+        .fold(content.to_string(), |content, finding| {
+            fix_issue(&finding.issue, &content)
+        })
+}
+
+/// Like [`apply_fixes`], leaving the targets of rules outside
+/// `config.rule_filter` as they were.
+pub fn apply_fixes_in_scope(content: &str, findings: &[Finding], config: &Config) -> String {
+    findings
+        .iter()
+        .fold(content.to_string(), |content, finding| {
+            fix_issue_in_scope(&finding.issue, &content, config)
+        })
+}
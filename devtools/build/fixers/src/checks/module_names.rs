@@ -0,0 +1,112 @@
+//! Checks that Swift module names match what the rest of the code imports,
+//! and that no two libraries of the workspace build the same module.
+
+use std::collections::BTreeMap;
+
+use crate::issue::BuildIssue;
+use crate::label_resolver::AbsoluteLabel;
+use crate::starlark::calls::{insert_after_name, top_level_calls};
+use crate::starlark::tokenizer::{quote, tokenize};
+use crate::target_graph::TargetGraph;
+
+// Flag swift_library targets whose default module name (the target name)
+// differs from the module name recorded in module_name_map.toml
+pub fn check_module_names(
+    content: &str,
+    module_names: &BTreeMap<String, String>,
+) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues = Vec::new();
+
+    for call in top_level_calls(&tokens) {
+        if call.name != "swift_library" || call.keyword(&tokens, "module_name").is_some() {
+            continue;
+        }
+        let Some(target) = call.target_name(&tokens) else {
+            continue;
+        };
+        let Some(module_name) = module_names.get(&target) else {
+            continue;
+        };
+        if *module_name == target {
+            continue;
+        }
+
+        issues.push((
+            BuildIssue::MissingModuleName {
+                target: target.clone(),
+                module_name: module_name.clone(),
+            },
+            format!(
+                "swift_library {:?} would build module {:?}, but it should be {:?}",
+                target, target, module_name
+            ),
+        ));
+    }
+
+    issues
+}
+
+// Add `module_name = "..."` to the swift_library named `target`
+pub fn fix_missing_module_name(content: &str, target: &str, module_name: &str) -> String {
+    let tokens = tokenize(content);
+    let call = top_level_calls(&tokens).into_iter().find(|call| {
+        call.name == "swift_library"
+            && call.keyword(&tokens, "module_name").is_none()
+            && call.target_name(&tokens).as_deref() == Some(target)
+    });
+
+    match call {
+        Some(call) => insert_after_name(
+            content,
+            &tokens,
+            &call,
+            &format!("module_name = {}", quote(module_name)),
+        ),
+        None => content.to_string(),
+    }
+}
+
+// Flag every swift_library whose Swift module name (`module_name`, or else
+// the target name) another swift_library of the workspace also builds
+pub fn check_conflicting_module_names(
+    graph: &TargetGraph,
+) -> Vec<(AbsoluteLabel, BuildIssue, String)> {
+    let mut modules: BTreeMap<&str, Vec<&AbsoluteLabel>> = BTreeMap::new();
+    for (label, info) in graph.targets() {
+        if info.rule_name != "swift_library" {
+            continue;
+        }
+        // A computed module name can't be compared
+        if let Some(module_name) = info.module_name() {
+            modules.entry(module_name).or_default().push(label);
+        }
+    }
+
+    let mut issues = Vec::new();
+    for (module_name, labels) in modules.into_iter().filter(|(_, labels)| labels.len() > 1) {
+        for label in &labels {
+            let conflicts: Vec<String> = labels
+                .iter()
+                .filter(|other| *other != label)
+                .map(|other| other.to_string())
+                .collect();
+            let message = format!(
+                "swift_library {} builds Swift module {:?}, as does {}; only one of them can be in a build",
+                label,
+                module_name,
+                conflicts.join(", ")
+            );
+            issues.push((
+                (*label).clone(),
+                BuildIssue::ConflictingModuleNames {
+                    target: label.target.clone(),
+                    module_name: module_name.to_string(),
+                    conflicts,
+                },
+                message,
+            ));
+        }
+    }
+    issues
+}
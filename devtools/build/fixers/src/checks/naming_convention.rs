@@ -0,0 +1,116 @@
+//! Check that target names follow the patterns `naming_convention.toml` sets
+//! per rule type, such as PascalCase for `swift_library` and a `Tests`
+//! suffix for `swift_test`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use regex::Regex;
+
+use crate::checks::target_names::rename_target;
+use crate::issue::BuildIssue;
+use crate::starlark::calls::top_level_calls;
+use crate::starlark::tokenizer::tokenize;
+
+/// Rule type -> pattern its target names must match, as a whole.
+#[derive(Debug, Clone, Default)]
+pub struct NamingConvention {
+    // The pattern as written, for messages, and compiled
+    patterns: BTreeMap<String, (String, Regex)>,
+}
+
+impl NamingConvention {
+    /// Compile `patterns` (rule type -> regex). Patterns are anchored, so
+    /// `[A-Z][A-Za-z0-9]*` must match the whole name.
+    pub fn new(patterns: &BTreeMap<String, String>) -> Result<Self, regex::Error> {
+        let patterns = patterns
+            .iter()
+            .map(|(rule, pattern)| {
+                let regex = Regex::new(&format!("^(?:{})$", pattern))?;
+                Ok((rule.clone(), (pattern.clone(), regex)))
+            })
+            .collect::<Result<_, regex::Error>>()?;
+        Ok(NamingConvention { patterns })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+/// Flag `target_name` if it doesn't match the pattern for `rule_name`. The
+/// fix renames it to its PascalCase form when that matches.
+pub fn check_naming_convention(
+    rule_name: &str,
+    target_name: &str,
+    convention: &NamingConvention,
+) -> Option<(BuildIssue, String)> {
+    violation(rule_name, target_name, convention, &BTreeSet::new())
+}
+
+// Check every target of the file. A rename onto a name another target of the
+// file already has is left to a manual fix.
+pub fn check_naming_conventions(
+    content: &str,
+    convention: &NamingConvention,
+) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let targets: Vec<(&str, String)> = top_level_calls(&tokens)
+        .into_iter()
+        .filter_map(|call| Some((call.name, call.target_name(&tokens)?)))
+        .collect();
+    let taken: BTreeSet<&str> = targets.iter().map(|(_, name)| name.as_str()).collect();
+
+    targets
+        .iter()
+        .filter_map(|(rule_name, target)| violation(rule_name, target, convention, &taken))
+        .collect()
+}
+
+// Rename the target to `renamed` along with the `:target` labels of the file
+pub fn fix_naming_convention(content: &str, target: &str, renamed: &str) -> String {
+    rename_target(content, target, renamed, None)
+}
+
+fn violation(
+    rule_name: &str,
+    target_name: &str,
+    convention: &NamingConvention,
+    taken: &BTreeSet<&str>,
+) -> Option<(BuildIssue, String)> {
+    let (pattern, regex) = convention.patterns.get(rule_name)?;
+    if regex.is_match(target_name) {
+        return None;
+    }
+
+    let renamed = Some(pascal_case(target_name))
+        .filter(|renamed| regex.is_match(renamed) && !taken.contains(renamed.as_str()));
+    let suggestion = renamed
+        .as_ref()
+        .map(|renamed| format!("; rename it to {:?}", renamed))
+        .unwrap_or_default();
+    Some((
+        BuildIssue::NamingConventionViolation {
+            target: target_name.to_string(),
+            rule_name: rule_name.to_string(),
+            renamed,
+        },
+        format!(
+            "{} {:?} doesn't match the naming convention {}{}",
+            rule_name, target_name, pattern, suggestion
+        ),
+    ))
+}
+
+// `my_module`, `my-module` and `myModule` all become `MyModule`
+fn pascal_case(name: &str) -> String {
+    name.split(['_', '-'])
+        .flat_map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+        })
+        .collect()
+}
@@ -0,0 +1,153 @@
+//! Checks on the file-level `package()` declaration.
+
+use crate::issue::BuildIssue;
+use crate::starlark::calls::top_level_calls;
+use crate::starlark::tokenizer::{tokenize, Token, TokenKind};
+
+/// The declaration inserted into files that don't have one.
+pub const DEFAULT_PACKAGE_DECLARATION: &str =
+    r#"package(default_visibility = ["//visibility:public"])"#;
+
+/// The license type `licenses()` must declare unless configured otherwise.
+pub const DEFAULT_LICENSE_TYPE: &str = "notice";
+
+pub fn check_empty_build_file(content: &str) -> Option<(BuildIssue, String)> {
+    content.trim().is_empty().then(|| {
+        (
+            BuildIssue::EmptyBuildFile,
+            "file is empty; Bazel needs at least a package() declaration".to_string(),
+        )
+    })
+}
+
+// Replace an empty (or whitespace-only) file with a package declaration
+pub fn fix_empty_build_file(content: &str) -> String {
+    if !content.trim().is_empty() {
+        return content.to_string();
+    }
+    format!("{}\n", DEFAULT_PACKAGE_DECLARATION)
+}
+
+pub fn check_package_declaration(content: &str) -> Option<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let calls = top_level_calls(&tokens);
+    if calls.is_empty() || calls.iter().any(|call| call.name == "package") {
+        return None;
+    }
+
+    Some((
+        BuildIssue::MissingPackageDeclaration,
+        "no package() declaration; the workspace default visibility applies".to_string(),
+    ))
+}
+
+// Insert the default package declaration as the first statement after the
+// load() calls (or at the top, below any file header comment)
+pub fn fix_package_declaration(content: &str) -> String {
+    if check_package_declaration(content).is_none() {
+        return content.to_string();
+    }
+
+    insert_declaration(content, DEFAULT_PACKAGE_DECLARATION)
+}
+
+// Flag files without a `licenses([license])` call, or whose licenses() call
+// declares anything else
+pub fn check_license(content: &str, license: &str) -> Option<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let calls = top_level_calls(&tokens);
+    let issue = BuildIssue::MissingLicense {
+        license: license.to_string(),
+    };
+    let Some(call) = calls.iter().find(|call| call.name == "licenses") else {
+        return Some((
+            issue,
+            format!(
+                "no licenses() declaration; expected licenses([{:?}])",
+                license
+            ),
+        ));
+    };
+
+    let declared: Vec<String> = tokens[call.open + 1..call.close]
+        .iter()
+        .filter_map(Token::string_value)
+        .collect();
+    if declared == [license] {
+        return None;
+    }
+    Some((
+        issue,
+        format!(
+            "licenses() declares {:?}, expected [{:?}]",
+            declared, license
+        ),
+    ))
+}
+
+// Replace the licenses() call with one declaring `license`, or insert it on
+// the line below the package() call
+pub fn fix_license(content: &str, license: &str) -> String {
+    if check_license(content, license).is_none() {
+        return content.to_string();
+    }
+
+    let declaration = format!("licenses([\"{}\"])", license);
+    let tokens = tokenize(content);
+    let calls = top_level_calls(&tokens);
+    if let Some(call) = calls.iter().find(|call| call.name == "licenses") {
+        let start = tokens[call.open - 1].start;
+        let end = tokens[call.close].end();
+        return format!("{}{}{}", &content[..start], declaration, &content[end..]);
+    }
+    let Some(package) = calls.iter().find(|call| call.name == "package") else {
+        return insert_declaration(content, &declaration);
+    };
+
+    let close = tokens[package.close].end();
+    let insert_at = content[close..]
+        .find('\n')
+        .map_or(content.len(), |i| close + i);
+    let mut new_content = content.to_string();
+    new_content.insert_str(insert_at, &format!("\n{}", declaration));
+    new_content
+}
+
+// Insert `declaration` as the first statement after the load() calls (or at
+// the top, below any file header comment)
+fn insert_declaration(content: &str, declaration: &str) -> String {
+    let tokens = tokenize(content);
+    let calls = top_level_calls(&tokens);
+    let mut new_content = content.to_string();
+
+    match calls.iter().rev().find(|call| call.name == "load") {
+        Some(last_load) => {
+            let close = tokens[last_load.close].end();
+            let insert_at = content[close..]
+                .find('\n')
+                .map_or(content.len(), |i| close + i);
+            let mut declaration = format!("\n\n{}", declaration);
+            if !content[insert_at..].starts_with("\n\n") {
+                declaration.push('\n');
+            }
+            new_content.insert_str(insert_at, &declaration);
+        }
+        None => {
+            let first_code = tokens
+                .iter()
+                .position(|token| token.kind != TokenKind::Comment)
+                .unwrap_or(0);
+            // Comments directly above the first statement belong to it
+            let mut first = first_code;
+            while first > 0 && tokens[first - 1].line + 1 == tokens[first].line {
+                first -= 1;
+            }
+            let insert_at = content[..tokens[first].start]
+                .rfind('\n')
+                .map_or(0, |i| i + 1);
+            new_content.insert_str(insert_at, &format!("{}\n\n", declaration));
+        }
+    }
+
+    new_content
+}
@@ -0,0 +1,96 @@
+//! Check for absolute paths into a developer's machine.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::issue::BuildIssue;
+use crate::starlark::tokenizer::{tokenize, Token, TokenKind};
+
+/// What hard-coded project paths are replaced with unless the config says otherwise.
+pub const DEFAULT_PROJECT_PATH_VARIABLE: &str = "$(WORKSPACE_ROOT)";
+
+// A path under a home directory (or C:\), starting at the beginning of the
+// string or after a separator such as `=` or a space. Paths glued to a flag
+// (`-I/Users/...`) are left to the absolute include path check.
+static PROJECT_PATH_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?:^|[^A-Za-z0-9_./\\-])((?:/Users/|/home/|/root/|[A-Za-z]:\\)[^\s"',;]*)"#)
+        .expect("invalid regex")
+});
+
+// Flag each distinct hard-coded path in a string literal. The replacement
+// keeps the part below the directory named `root_name` (the checkout being
+// fixed), or is just `variable` if the path lies outside it.
+pub fn check_hardcoded_paths(
+    content: &str,
+    root_name: Option<&str>,
+    variable: &str,
+) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues: Vec<(BuildIssue, String)> = Vec::new();
+
+    for token in tokens.iter().filter(|t| t.kind == TokenKind::String) {
+        for path in project_paths(token) {
+            if issues.iter().any(|(issue, _)| {
+                matches!(issue, BuildIssue::HardcodedProjectPath { path: p, .. } if p == path)
+            }) {
+                continue;
+            }
+            let replacement = replacement(path, root_name, variable);
+            let message = format!(
+                "line {}: {:?} points into a developer's machine; use {:?} instead",
+                token.line, path, replacement
+            );
+            issues.push((
+                BuildIssue::HardcodedProjectPath {
+                    path: path.to_string(),
+                    replacement,
+                },
+                message,
+            ));
+        }
+    }
+
+    issues
+}
+
+// Replace every occurrence of `path` in a string literal with `replacement`
+pub fn fix_hardcoded_path(content: &str, path: &str, replacement: &str) -> String {
+    let tokens = tokenize(content);
+    let mut new_content = content.to_string();
+
+    // Back to front, so earlier offsets stay valid
+    for token in tokens.iter().rev().filter(|t| t.kind == TokenKind::String) {
+        let ranges: Vec<_> = PROJECT_PATH_RE
+            .captures_iter(token.text)
+            .filter_map(|captures| captures.get(1))
+            .filter(|found| found.as_str() == path)
+            .map(|found| token.start + found.start()..token.start + found.end())
+            .collect();
+        for range in ranges.into_iter().rev() {
+            new_content.replace_range(range, replacement);
+        }
+    }
+
+    new_content
+}
+
+fn project_paths<'a>(token: &Token<'a>) -> impl Iterator<Item = &'a str> {
+    PROJECT_PATH_RE
+        .captures_iter(token.text)
+        .filter_map(|captures| captures.get(1))
+        .map(|found| found.as_str())
+}
+
+fn replacement(path: &str, root_name: Option<&str>, variable: &str) -> String {
+    let Some(root_name) = root_name else {
+        return variable.to_string();
+    };
+    let segments: Vec<&str> = path.split(['/', '\\']).collect();
+    let Some(root) = segments.iter().position(|segment| *segment == root_name) else {
+        return variable.to_string();
+    };
+    // Offset just past the root segment; the rest keeps its separators as written
+    let below: usize = segments[..=root].iter().map(|s| s.len() + 1).sum::<usize>() - 1;
+    format!("{}{}", variable, &path[below.min(path.len())..])
+}
@@ -0,0 +1,260 @@
+//! Check that asset catalogs and localizations are bundled with
+//! `apple_resource_bundle` rather than listed in a target's `data`, where
+//! they aren't compiled (`actool`, `ibtool`) and `Bundle.module` can't find
+//! them.
+
+use std::ops::Range;
+use std::path::Path;
+
+use crate::checks::workspace::{fix_missing_load, loaded_symbols};
+use crate::issue::BuildIssue;
+use crate::starlark::calls::{
+    append_to_list, element_removal_range, is_list, top_level_calls, Argument, Call,
+};
+use crate::starlark::tokenizer::{find_matching, quote, tokenize, Token, TokenKind};
+
+/// Directory extensions of resources that need an `apple_resource_bundle`.
+pub const BUNDLED_RESOURCE_EXTENSIONS: &[&str] = &["xcassets", "lproj"];
+
+/// The bzl `apple_resource_bundle` is loaded from, unless the file already
+/// loads rules_apple's resources.bzl under its older repository name.
+pub const RESOURCES_BZL: &str = "@rules_apple//apple:resources.bzl";
+
+// Flag each Swift target whose `data` lists asset catalogs or `.lproj`
+// directories, directly or as glob() patterns under them. Directories named
+// without wildcards must exist in `package_dir`. The fix moves
+// them into an apple_resource_bundle named `<target>Resources`, unless a
+// target of that name exists.
+pub fn check_resource_bundling(content: &str, package_dir: &Path) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let calls = top_level_calls(&tokens);
+    let mut issues = Vec::new();
+
+    for call in calls.iter().filter(|call| call.name.starts_with("swift_")) {
+        let Some(target) = call.target_name(&tokens) else {
+            continue;
+        };
+        let resources: Vec<String> = data_entries(&tokens, call)
+            .into_iter()
+            .filter_map(|(index, is_pattern)| {
+                let entry = tokens[index].string_value()?;
+                let directory = bundled_directory(&entry, is_pattern)?;
+                (directory.contains('*') || package_dir.join(directory).is_dir()).then_some(entry)
+            })
+            .collect();
+        if resources.is_empty() {
+            continue;
+        }
+
+        let bundle = format!("{}Resources", target);
+        let taken = calls
+            .iter()
+            .any(|other| other.target_name(&tokens).as_deref() == Some(bundle.as_str()));
+        let message = format!(
+            "{:?} lists {} in data; asset catalogs and localizations belong in an \
+             apple_resource_bundle{}",
+            target,
+            resources.join(", "),
+            if taken {
+                String::new()
+            } else {
+                format!(" (the fix adds {:?})", bundle)
+            }
+        );
+        issues.push((
+            BuildIssue::UnbundledResources {
+                target,
+                resources,
+                bundle: (!taken).then_some(bundle),
+            },
+            message,
+        ));
+    }
+    issues
+}
+
+// Move the `resources` entries of the data of the rule named `target` into a
+// new apple_resource_bundle named `bundle`, added after the rule, and depend
+// on it through `data` instead
+pub fn fix_resource_bundling(
+    content: &str,
+    target: &str,
+    resources: &[String],
+    bundle: &str,
+) -> String {
+    let mut content = content.to_string();
+
+    // Each pass removes one entry, so token indices stay valid
+    loop {
+        let tokens = tokenize(&content);
+        let Some(call) = find_target(&tokens, target) else {
+            return content;
+        };
+        let Some(index) = data_entries(&tokens, &call)
+            .into_iter()
+            .map(|(index, _)| index)
+            .find(|&index| {
+                tokens[index]
+                    .string_value()
+                    .is_some_and(|entry| resources.contains(&entry))
+            })
+        else {
+            break;
+        };
+        let (start, end) = element_removal_range(&content, &tokens, index);
+        content.replace_range(start..end, "");
+    }
+
+    let tokens = tokenize(&content);
+    let Some(call) = find_target(&tokens, target) else {
+        return content;
+    };
+    let Some(data) = call.keyword(&tokens, "data") else {
+        return content;
+    };
+    let label = format!("\":{}\"", bundle);
+    let value = data.byte_range(&tokens);
+    let mut content = if is_list(&tokens, &data) {
+        append_to_list(&content, &tokens, &data, &label).unwrap_or(content)
+    } else if is_empty_glob(&tokens, &data) {
+        format!(
+            "{}[{}]{}",
+            &content[..value.start],
+            label,
+            &content[value.end..]
+        )
+    } else {
+        format!(
+            "{} + [{}]{}",
+            &content[..value.end],
+            label,
+            &content[value.end..]
+        )
+    };
+
+    // The bundle goes right after the rule
+    let tokens = tokenize(&content);
+    let Some(call) = find_target(&tokens, target) else {
+        return content;
+    };
+    let patterns: Vec<String> = resources
+        .iter()
+        .map(|entry| match bundled_directory(entry, false) {
+            // A directory label bundles everything in it
+            Some(directory) if directory == entry => quote(&format!("{}/**", entry)),
+            _ => quote(entry),
+        })
+        .collect();
+    let rule = format!(
+        "\n\napple_resource_bundle(\n    name = {},\n    resources = glob([{}], allow_empty = True),\n)",
+        quote(bundle),
+        patterns.join(", ")
+    );
+    content.insert_str(tokens[call.close].end(), &rule);
+
+    let tokens = tokenize(&content);
+    let calls = top_level_calls(&tokens);
+    if loaded_symbols(&tokens, &calls)
+        .iter()
+        .any(|symbol| symbol == "apple_resource_bundle")
+    {
+        return content;
+    }
+    let bzl = calls
+        .iter()
+        .filter(|call| call.name == "load")
+        .filter_map(|call| tokens.get(call.open + 1)?.string_value())
+        .find(|label| label.ends_with("//apple:resources.bzl"))
+        .unwrap_or_else(|| RESOURCES_BZL.to_string());
+    fix_missing_load(&content, "apple_resource_bundle", &bzl)
+}
+
+fn find_target<'a>(tokens: &[Token<'a>], target: &str) -> Option<Call<'a>> {
+    top_level_calls(tokens)
+        .into_iter()
+        .find(|call| call.target_name(tokens).as_deref() == Some(target))
+}
+
+// The path up to and including the first `.xcassets` or `.lproj` directory
+// of a data entry. The last component of a glob pattern only matches files,
+// so `**/*.xcassets` names no directory.
+fn bundled_directory(entry: &str, is_pattern: bool) -> Option<&str> {
+    let components: Vec<&str> = entry.split('/').collect();
+    let directories = if is_pattern {
+        components.len().saturating_sub(1)
+    } else {
+        components.len()
+    };
+    let last = components[..directories].iter().position(|component| {
+        BUNDLED_RESOURCE_EXTENSIONS
+            .iter()
+            .any(|extension| component.ends_with(&format!(".{}", extension)))
+    })?;
+    let length = components[..=last]
+        .iter()
+        .map(|c| c.len() + 1)
+        .sum::<usize>()
+        - 1;
+    Some(&entry[..length])
+}
+
+// The string entries of the rule's `data`, with whether each is a glob()
+// include pattern rather than a plain label. Strings inside other calls,
+// such as select() or a glob's exclude list, aren't entries.
+fn data_entries(tokens: &[Token<'_>], call: &Call<'_>) -> Vec<(usize, bool)> {
+    let Some(data) = call.keyword(tokens, "data") else {
+        return Vec::new();
+    };
+    let mut entries = Vec::new();
+    let mut index = data.value.start;
+    while index < data.value.end {
+        let token = &tokens[index];
+        if token.kind == TokenKind::Ident && tokens[index + 1].kind == TokenKind::LParen {
+            let close = find_matching(tokens, index + 1).unwrap_or(data.value.end);
+            if token.text == "glob" {
+                if let Some(include) = glob_include(tokens, index + 1) {
+                    entries.extend(
+                        include
+                            .filter(|&i| tokens[i].kind == TokenKind::String)
+                            .map(|i| (i, true)),
+                    );
+                }
+            }
+            index = close + 1;
+            continue;
+        }
+        if token.kind == TokenKind::String {
+            entries.push((index, false));
+        }
+        index += 1;
+    }
+    entries
+}
+
+// The tokens inside the include list of the glob() opening at `open`
+fn glob_include(tokens: &[Token<'_>], open: usize) -> Option<Range<usize>> {
+    let close = find_matching(tokens, open)?;
+    let glob = Call {
+        name: "glob",
+        open,
+        close,
+        line: tokens[open].line,
+    };
+    let include = glob
+        .arguments(tokens)
+        .into_iter()
+        .find(|argument| argument.key.is_none() || argument.key == Some("include"))?;
+    is_list(tokens, &include).then(|| include.value.start + 1..include.value.end - 1)
+}
+
+// Whether `data` is a glob() whose include list is empty
+fn is_empty_glob(tokens: &[Token<'_>], data: &Argument<'_>) -> bool {
+    let value = data.value.clone();
+    tokens[value.start].is_ident("glob")
+        && find_matching(tokens, value.start + 1) == Some(value.end - 1)
+        && glob_include(tokens, value.start + 1).is_some_and(|include| {
+            tokens[include]
+                .iter()
+                .all(|token| token.kind == TokenKind::Comment)
+        })
+}
@@ -0,0 +1,61 @@
+//! Checks that targets bundle the resource files in their package.
+
+use std::path::Path;
+
+use crate::issue::BuildIssue;
+use crate::sources::collect_resources;
+use crate::starlark::calls::{insert_after_name, top_level_calls};
+use crate::starlark::tokenizer::tokenize;
+
+/// The `data` attribute added to targets whose package has resources.
+pub const DEFAULT_DATA_ATTRIBUTE: &str = "data = glob([\"**/*.xcassets\", \"**/*.strings\", \"**/*.json\", \"**/*.plist\"], allow_empty = True)";
+
+// Flag swift_library targets without a `data` attribute when the package has
+// resource files that `Bundle.module` would need at runtime
+pub fn check_missing_data(content: &str, package_dir: &Path) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let targets: Vec<String> = top_level_calls(&tokens)
+        .iter()
+        .filter(|call| call.name == "swift_library" && call.keyword(&tokens, "data").is_none())
+        .filter_map(|call| call.target_name(&tokens))
+        .collect();
+    if targets.is_empty() {
+        return Vec::new();
+    }
+
+    let resources = match collect_resources(package_dir) {
+        Ok(resources) if !resources.is_empty() => resources,
+        _ => return Vec::new(),
+    };
+    let example = resources[0].to_string_lossy().replace('\\', "/");
+
+    targets
+        .into_iter()
+        .map(|target| {
+            let message = format!(
+                "{:?} has no data attribute but its package has {} resource(s), e.g. {}",
+                target,
+                resources.len(),
+                example
+            );
+            (BuildIssue::MissingDataAttribute { target }, message)
+        })
+        .collect()
+}
+
+// Add the default `data` glob to the rule named `target`
+pub fn fix_missing_data(content: &str, target: &str) -> String {
+    let tokens = tokenize(content);
+    let call = top_level_calls(&tokens).into_iter().find(|call| {
+        call.target_name(&tokens).as_deref() == Some(target)
+            && call.keyword(&tokens, "data").is_none()
+    });
+    match call {
+        Some(call) => {
+            let (head, tail) = content.split_at(tokens[call.close].end());
+            let head = insert_after_name(head, &tokens, &call, DEFAULT_DATA_ATTRIBUTE);
+            format!("{}{}", head, tail)
+        }
+        None => content.to_string(),
+    }
+}
@@ -0,0 +1,103 @@
+//! Check for directories that Swift Package Manager and Bazel both build.
+
+use std::fs;
+use std::path::Path;
+
+use crate::checks::deps::srcs_patterns;
+use crate::glob::glob_match;
+use crate::issue::BuildIssue;
+use crate::sources::collect_swift_files;
+use crate::starlark::calls::top_level_calls;
+use crate::starlark::tokenizer::tokenize;
+use crate::swift_package_converter::parse_manifest;
+
+/// Manifest of a Swift package.
+pub const PACKAGE_MANIFEST: &str = "Package.swift";
+
+/// Rules whose targets correspond to SPM targets.
+pub const SWIFT_RULES: &[&str] = &["swift_library", "swift_test", "swift_binary"];
+
+// A swift rule in the BUILD file
+struct BazelTarget {
+    name: String,
+    module_name: Option<String>,
+    /// `None` if the `srcs` can't be evaluated.
+    patterns: Option<Vec<String>>,
+}
+
+// Flag a package directory that also has a Package.swift. The diagnostic
+// lists SPM targets without a Bazel rule (and the reverse), and targets whose
+// sources differ between the two builds. There is no fix: which build is
+// right needs a human decision.
+pub fn check_swift_package_manifest(
+    content: &str,
+    package_dir: &Path,
+) -> Option<(BuildIssue, String)> {
+    let manifest = fs::read_to_string(package_dir.join(PACKAGE_MANIFEST)).ok()?;
+    let spm_targets = parse_manifest(&manifest).targets;
+
+    let tokens = tokenize(content);
+    let bazel_targets: Vec<BazelTarget> = top_level_calls(&tokens)
+        .iter()
+        .filter(|call| SWIFT_RULES.contains(&call.name))
+        .filter_map(|call| {
+            Some(BazelTarget {
+                name: call.target_name(&tokens)?,
+                module_name: call.string_attr(&tokens, "module_name"),
+                patterns: srcs_patterns(&tokens, call),
+            })
+        })
+        .collect();
+
+    let files: Vec<String> = collect_swift_files(package_dir)
+        .unwrap_or_default()
+        .iter()
+        .map(|file| file.to_string_lossy().replace('\\', "/"))
+        .collect();
+
+    let mut mismatches = Vec::new();
+    for spm in &spm_targets {
+        let Some(bazel) = bazel_targets.iter().find(|bazel| bazel.provides(&spm.name)) else {
+            mismatches.push(format!("SPM target {:?} has no Bazel rule", spm.name));
+            continue;
+        };
+        let Some(patterns) = &bazel.patterns else {
+            continue;
+        };
+        let prefix = format!("{}/", spm.path.trim_end_matches('/'));
+        let spm_files = files.iter().filter(|file| file.starts_with(&prefix));
+        let bazel_files = files
+            .iter()
+            .filter(|file| patterns.iter().any(|pattern| glob_match(pattern, file)));
+        if !spm_files.eq(bazel_files) {
+            mismatches.push(format!(
+                "SPM target {:?} builds the Swift files in {} but the srcs of Bazel rule {:?} match different files",
+                spm.name, prefix, bazel.name
+            ));
+        }
+    }
+    for bazel in &bazel_targets {
+        if !spm_targets.iter().any(|spm| bazel.provides(&spm.name)) {
+            mismatches.push(format!("Bazel rule {:?} has no SPM target", bazel.name));
+        }
+    }
+
+    let details = if mismatches.is_empty() {
+        "the targets currently match".to_string()
+    } else {
+        mismatches.join("; ")
+    };
+    Some((
+        BuildIssue::DualBuildSystem,
+        format!(
+            "{} and BUILD.bazel both build this directory ({}); update them together, or remove {} if the package is only built with Bazel",
+            PACKAGE_MANIFEST, details, PACKAGE_MANIFEST
+        ),
+    ))
+}
+
+impl BazelTarget {
+    fn provides(&self, module: &str) -> bool {
+        self.name == module || self.module_name.as_deref() == Some(module)
+    }
+}
@@ -0,0 +1,533 @@
+//! Migration of legacy UmbraCore rules to plain rules_swift `swift_library`,
+//! `swift_test` and `swift_binary`.
+//!
+//! Each check reports an issue when the matching fix would change the file.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+
+use crate::checks::workspace::{fix_missing_load, fix_outdated_rule, loaded_symbols};
+use crate::glob::glob_match;
+use crate::glob_evaluator::evaluate_glob;
+use crate::issue::BuildIssue;
+use crate::schema::swift_library::{parse_swift_library, SwiftLibraryRule};
+use crate::sources::{collect_swift_files, matching_swift_files};
+use crate::starlark::ast::{self, AttrValue, BuildFile};
+use crate::starlark::calls::{element_removal_range, insert_after_name, top_level_calls};
+use crate::starlark::tokenizer::{quote, tokenize};
+use crate::swift_version::{
+    enables_strict_concurrency, required_swift_version, STRICT_CONCURRENCY_SETTING,
+};
+
+// The srcs given to a swift_library that has none
+const DEFAULT_SRCS: &str = r#"srcs = glob(["*.swift"], allow_empty = True)"#;
+
+const SWIFT_LIBRARY_LOAD: &str =
+    r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")"#;
+
+// Detects swift_library in any format
+static SWIFT_LIBRARY_CALL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bswift_library\s*\(").expect("invalid regex"));
+
+// A load of swift_library from rules_swift under either repository name, from
+// swift.bzl (possibly alongside other symbols) or rules_swift 2's swift_library.bzl
+static SWIFT_LIBRARY_LOADED_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"load\(\s*"@(?:build_bazel_rules_swift|rules_swift)//swift:swift(?:_library)?\.bzl"[^)]*"swift_library""#)
+        .expect("invalid regex")
+});
+
+/// The legacy UmbraCore macros and the rules_swift rules that replace them.
+pub const UMBRA_SWIFT_RULES: &[(&str, &str)] = &[
+    ("umbra_swift_library", "swift_library"),
+    ("umbra_swift_test", "swift_test"),
+    ("umbra_swift_binary", "swift_binary"),
+];
+
+/// Attributes only the `umbra_*` macros accept; the rules_swift rules reject them.
+pub const UMBRA_WRAPPER_ATTRIBUTES: &[&str] = &[
+    "module_visibility",
+    "swiftc_opts",
+    "additional_copts",
+    "swift_mode",
+    "enable_library_evolution",
+];
+
+// The bzl files that define the legacy macros
+const UMBRA_MACRO_BZLS: &[&str] = &[
+    "//:swift_rules.bzl",
+    "//bazel:swift_rules.bzl",
+    "//bazel/macros:swift.bzl",
+];
+
+// The bzl the converted rules are loaded from, unless the file already loads
+// rules_swift's swift.bzl under another name
+const RULES_SWIFT_BZL: &str = "@build_bazel_rules_swift//swift:swift.bzl";
+
+static ALLOW_EMPTY_FALSE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"allow_empty\s*=\s*False").expect("invalid regex"));
+
+static GLOB_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"glob\s*\(\s*\[(.*?)\]\s*\)").expect("invalid regex"));
+
+pub fn check_swift_library_load(content: &str) -> Option<(BuildIssue, String)> {
+    (ensure_swift_library_load(content) != content).then(|| {
+        (
+            BuildIssue::MissingSwiftLibraryLoad,
+            "swift_library is used but not loaded from rules_swift".to_string(),
+        )
+    })
+}
+
+pub fn check_custom_library(content: &str) -> Option<(BuildIssue, String)> {
+    (convert_umbra_swift_library(content) != content).then(|| {
+        let tokens = tokenize(content);
+        let calls = top_level_calls(&tokens);
+        let replacements: Vec<String> = UMBRA_SWIFT_RULES
+            .iter()
+            .filter(|(umbra, _)| calls.iter().any(|call| call.name == *umbra))
+            .map(|(umbra, rule)| format!("{} should be replaced with {}", umbra, rule))
+            .collect();
+        let message = if replacements.is_empty() {
+            "umbra_* macros should be loaded as rules_swift rules".to_string()
+        } else {
+            replacements.join(", ")
+        };
+        (BuildIssue::CustomLibraryRule, message)
+    })
+}
+
+// Flag the attributes of umbra_* macro calls that only the macros accept.
+// The CustomLibraryRule fix renames the calls, so these would break the
+// converted rules.
+pub fn check_umbra_wrapper_attributes(file: &BuildFile) -> Vec<(BuildIssue, String)> {
+    let mut issues = Vec::new();
+    for rule in &file.rules {
+        let Some((umbra, replacement)) = UMBRA_SWIFT_RULES
+            .iter()
+            .find(|(umbra, _)| *umbra == rule.rule_name)
+        else {
+            continue;
+        };
+        let Some(target) = rule.name() else {
+            continue;
+        };
+        for attr in &rule.attrs {
+            if !UMBRA_WRAPPER_ATTRIBUTES.contains(&attr.key.as_str()) {
+                continue;
+            }
+            issues.push((
+                BuildIssue::UnsupportedAttribute {
+                    target: target.to_string(),
+                    attribute: attr.key.clone(),
+                    rule: replacement.to_string(),
+                },
+                format!(
+                    "{} {:?} sets {}, which only {} accepts; {} doesn't support it",
+                    umbra, target, attr.key, umbra, replacement
+                ),
+            ));
+        }
+    }
+    issues
+}
+
+pub fn check_glob_patterns(content: &str) -> Option<(BuildIssue, String)> {
+    (fix_glob_patterns(content) != content).then(|| {
+        (
+            BuildIssue::GlobWithoutAllowEmpty,
+            "glob() should set allow_empty = True".to_string(),
+        )
+    })
+}
+
+// Flag each swift_library whose attributes don't fit the schema. The checks
+// that read libraries through swift_libraries() (srcs, test sources, Swift
+// settings, target names and Objective-C interop) leave it out; the checks
+// that work on the tokens still look at it.
+pub fn check_swift_library_schema(file: &BuildFile) -> Vec<(BuildIssue, String)> {
+    file.rules
+        .iter()
+        .filter(|rule| rule.rule_name == "swift_library")
+        .filter_map(|rule| {
+            let errors = parse_swift_library(&rule.attrs).err()?;
+            let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            let target = rule.name().map(String::from);
+            let rule_desc = match &target {
+                Some(target) => format!("{:?}", target),
+                None => format!("on line {}", rule.line),
+            };
+            let message = format!(
+                "swift_library {} has invalid attributes, so its srcs, test sources, Swift \
+                 settings, name and Objective-C interop aren't checked: {}",
+                rule_desc,
+                errors.join("; ")
+            );
+            Some((BuildIssue::InvalidSwiftLibrary { target, errors }, message))
+        })
+        .collect()
+}
+
+// Flag each swift_library that has no `srcs` from the tokens of `content`,
+// for when the AST or the package's files aren't available to
+// check_empty_srcs
+pub fn check_missing_srcs(content: &str) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    top_level_calls(&tokens)
+        .into_iter()
+        .filter(|call| call.name == "swift_library" && call.keyword(&tokens, "srcs").is_none())
+        .filter_map(|call| call.target_name(&tokens))
+        .map(|target| missing_srcs(&target))
+        .collect()
+}
+
+fn missing_srcs(target: &str) -> (BuildIssue, String) {
+    (
+        BuildIssue::EmptySrcs {
+            target: target.to_string(),
+            has_srcs: false,
+        },
+        format!("swift_library {:?} has no srcs attribute", target),
+    )
+}
+
+// Flag each swift_library that has no `srcs`, or whose `srcs` match none of
+// the Swift files in the package directory. A package without Swift files
+// (generated sources, placeholders) isn't flagged for an empty match, nor is
+// a rule whose `srcs` uses labels or variables that can't be evaluated.
+pub fn check_empty_srcs(file: &BuildFile, build_dir: &Path) -> Vec<(BuildIssue, String)> {
+    let swift_files = collect_swift_files(build_dir).unwrap_or_default();
+    let mut issues = Vec::new();
+
+    for library in swift_libraries(file) {
+        let target = &library.name;
+        let Some(srcs) = &library.srcs else {
+            issues.push(missing_srcs(target));
+            continue;
+        };
+
+        let mut patterns = Vec::new();
+        if swift_files.is_empty() || !collect_srcs_patterns(srcs, &mut patterns) {
+            continue;
+        }
+        let mut excludes = Vec::new();
+        collect_srcs_excludes(srcs, &mut excludes);
+        let matches_any = evaluate_glob(build_dir, &patterns, &excludes, true)
+            .unwrap_or_default()
+            .iter()
+            .any(|file| file.extension().is_some_and(|ext| ext == "swift"));
+        if !matches_any {
+            issues.push((
+                BuildIssue::EmptySrcs {
+                    target: target.to_string(),
+                    has_srcs: true,
+                },
+                format!(
+                    "srcs of swift_library {:?} match none of the {} Swift files in the package",
+                    target,
+                    swift_files.len()
+                ),
+            ));
+        }
+    }
+
+    issues
+}
+
+// Flag the first swift_library that isn't testonly and whose srcs match test
+// sources (`*Tests.swift` or `*Spec.swift`) in the package of `build_file`.
+// Moving them into a swift_test means restructuring the package, so the
+// issue is only reported.
+pub fn check_test_files_in_library(
+    build_file: &Path,
+    content: &str,
+) -> Option<(BuildIssue, String)> {
+    let package_dir = build_file.parent()?;
+    let file = ast::parse(content).ok()?;
+
+    let issue = swift_libraries(&file)
+        .filter(|library| !library.is_testonly())
+        .find_map(|library| {
+            let target = library.name;
+            let files: Vec<String> = library_sources(&library.srcs?, package_dir)?
+                .into_iter()
+                .filter(|file| file.ends_with("Tests.swift") || file.ends_with("Spec.swift"))
+                .collect();
+            if files.is_empty() {
+                return None;
+            }
+
+            let message = format!(
+                "swift_library {:?} includes test sources {}; move them into a separate swift_test",
+                target,
+                files.join(", ")
+            );
+            Some((BuildIssue::TestFilesInLibrary { target, files }, message))
+        });
+    issue
+}
+
+// Flag each swift_library whose sources use language features of a newer
+// Swift version (so far, concurrency) without the swift_settings, copts or
+// features that enable their checking. Adding a setting can surface new
+// compiler errors, so the issue is only reported.
+pub fn check_swift_settings(file: &BuildFile, package_dir: &Path) -> Vec<(BuildIssue, String)> {
+    swift_libraries(file)
+        .filter_map(|library| {
+            let configured = [&library.swift_settings, &library.copts, &library.features]
+                .into_iter()
+                .flatten()
+                .any(enables_strict_concurrency);
+            if configured {
+                return None;
+            }
+            let files = library_sources(library.srcs.as_ref()?, package_dir)?;
+            let version = required_swift_version(package_dir, &files).ok()??;
+
+            let message = format!(
+                "swift_library {:?} uses concurrency, which needs Swift {}; add swift_settings = [swift_feature_requirement({:?})]",
+                library.name, version, STRICT_CONCURRENCY_SETTING
+            );
+            Some((
+                BuildIssue::MissingSwiftSetting {
+                    target: library.name,
+                    setting: STRICT_CONCURRENCY_SETTING.to_string(),
+                },
+                message,
+            ))
+        })
+        .collect()
+}
+
+// Ensure swift_library is properly loaded at the top of the file
+pub fn ensure_swift_library_load(content: &str) -> String {
+    // Add the load statement at the top of the file if it's missing
+    if SWIFT_LIBRARY_CALL_RE.is_match(content) && !SWIFT_LIBRARY_LOADED_RE.is_match(content) {
+        return format!("{}\n\n{}", SWIFT_LIBRARY_LOAD, content);
+    }
+
+    content.to_string()
+}
+
+// Convert the umbra_swift_library, umbra_swift_test and umbra_swift_binary
+// macros to their rules_swift rules: first the calls, then each load of the
+// macros in turn. Content without the macros is returned unchanged, so a
+// second run changes nothing.
+pub fn convert_umbra_swift_library(content: &str) -> String {
+    let mut content = content.to_string();
+    for (umbra, rule) in UMBRA_SWIFT_RULES {
+        content = fix_outdated_rule(&content, umbra, rule);
+    }
+    while let Some(migrated) = migrate_umbra_load(&content) {
+        content = migrated;
+    }
+    content
+}
+
+// Replace the macros loaded by the first load of a macro bzl with their
+// rules, or `None` if no load has any left. A load of only macros becomes a
+// rules_swift load in place; otherwise the macros are removed from it and
+// their rules added to the file's rules_swift load.
+fn migrate_umbra_load(content: &str) -> Option<String> {
+    let tokens = tokenize(content);
+    let calls = top_level_calls(&tokens);
+    let (load, symbols, macros) =
+        calls
+            .iter()
+            .filter(|call| call.name == "load")
+            .find_map(|call| {
+                let label = tokens.get(call.open + 1)?.string_value()?;
+                if !UMBRA_MACRO_BZLS.contains(&label.as_str()) {
+                    return None;
+                }
+                let symbols = call.arguments(&tokens).split_off(1);
+                let macros: Vec<(usize, &str)> = symbols
+                    .iter()
+                    .filter(|symbol| symbol.key.is_none() && symbol.value.len() == 1)
+                    .filter_map(|symbol| {
+                        let name = tokens[symbol.value.start].string_value()?;
+                        let (_, rule) =
+                            UMBRA_SWIFT_RULES.iter().find(|(umbra, _)| *umbra == name)?;
+                        Some((symbol.value.start, *rule))
+                    })
+                    .collect();
+                (!macros.is_empty()).then_some((call, symbols, macros))
+            })?;
+
+    let rules_swift_bzl = calls
+        .iter()
+        .filter(|call| call.name == "load")
+        .filter_map(|call| tokens.get(call.open + 1)?.string_value())
+        .find(|label| label.ends_with("//swift:swift.bzl"));
+    let start = tokens[load.open - 1].start;
+    let end = tokens[load.close].end();
+
+    if macros.len() == symbols.len() && rules_swift_bzl.is_none() {
+        let rules: Vec<String> = macros.iter().map(|(_, rule)| quote(rule)).collect();
+        return Some(format!(
+            "{}load({}, {}){}",
+            &content[..start],
+            quote(RULES_SWIFT_BZL),
+            rules.join(", "),
+            &content[end..]
+        ));
+    }
+
+    let mut fixed = content.to_string();
+    if macros.len() == symbols.len() {
+        // The whole statement goes, with the blank line after it if it
+        // starts a group of statements
+        let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+        let mut line_end = content[end..]
+            .find('\n')
+            .map_or(content.len(), |i| end + i + 1);
+        let starts_group = line_start == 0 || content[..line_start].ends_with("\n\n");
+        if starts_group && content[line_end..].starts_with('\n') {
+            line_end += 1;
+        }
+        fixed.replace_range(line_start..line_end, "");
+    } else {
+        for (index, _) in macros.iter().rev() {
+            let (start, end) = element_removal_range(content, &tokens, *index);
+            fixed.replace_range(start..end, "");
+        }
+    }
+
+    let bzl = rules_swift_bzl.as_deref().unwrap_or(RULES_SWIFT_BZL);
+    for (_, rule) in &macros {
+        let tokens = tokenize(&fixed);
+        let calls = top_level_calls(&tokens);
+        if !loaded_symbols(&tokens, &calls)
+            .iter()
+            .any(|symbol| symbol == rule)
+        {
+            fixed = fix_missing_load(&fixed, rule, bzl);
+        }
+    }
+    Some(fixed)
+}
+
+// Fix glob patterns to set allow_empty=True
+pub fn fix_glob_patterns(content: &str) -> String {
+    // First fix patterns with allow_empty=False
+    let new_content = ALLOW_EMPTY_FALSE_RE.replace_all(content, "allow_empty = True");
+
+    // Then add allow_empty=True to patterns that don't have it
+    let new_content = GLOB_RE.replace_all(&new_content, |caps: &Captures| {
+        // Only replace if it doesn't already have allow_empty
+        if !caps[0].contains("allow_empty") {
+            format!(
+                "glob(\n        [{}],\n        allow_empty = True\n    )",
+                &caps[1]
+            )
+        } else {
+            // Return the original match
+            caps[0].to_string()
+        }
+    });
+
+    new_content.to_string()
+}
+
+// Give the swift_library named `target` a glob of the package's Swift files
+// if it has no `srcs`. A `srcs` that matches nothing needs a manual edit.
+pub fn fix_empty_srcs(content: &str, target: &str) -> String {
+    let tokens = tokenize(content);
+    let call = top_level_calls(&tokens).into_iter().find(|call| {
+        call.name == "swift_library"
+            && call.keyword(&tokens, "srcs").is_none()
+            && call.target_name(&tokens).as_deref() == Some(target)
+    });
+
+    match call {
+        Some(call) => insert_after_name(content, &tokens, &call, DEFAULT_SRCS),
+        None => content.to_string(),
+    }
+}
+
+/// The `swift_library` rules of `file` whose attributes are valid. The others
+/// are reported by [`check_swift_library_schema`].
+pub fn swift_libraries(file: &BuildFile) -> impl Iterator<Item = SwiftLibraryRule> + '_ {
+    file.rules
+        .iter()
+        .filter(|rule| rule.rule_name == "swift_library")
+        .filter_map(|rule| parse_swift_library(&rule.attrs).ok())
+}
+
+// The Swift files of the package at `package_dir` that `srcs` matches,
+// relative to it, or `None` if `srcs` can't be evaluated
+fn library_sources(srcs: &AttrValue, package_dir: &Path) -> Option<Vec<String>> {
+    let mut patterns = Vec::new();
+    let mut excludes = Vec::new();
+    if !collect_srcs_patterns(srcs, &mut patterns) {
+        return None;
+    }
+    collect_srcs_excludes(srcs, &mut excludes);
+
+    let files = matching_swift_files(package_dir, &patterns)
+        .ok()?
+        .into_iter()
+        .map(|file| file.to_string_lossy().replace('\\', "/"))
+        .filter(|file| !excludes.iter().any(|pattern| glob_match(pattern, file)))
+        .collect();
+    Some(files)
+}
+
+// Add the file names and glob include patterns in `value` to `patterns`,
+// returning false if it uses anything that can't be evaluated
+fn collect_srcs_patterns(value: &AttrValue, patterns: &mut Vec<String>) -> bool {
+    match value {
+        AttrValue::String(src) if src.starts_with(':') || src.contains("//") => false,
+        AttrValue::String(src) => {
+            patterns.push(src.clone());
+            true
+        }
+        AttrValue::List(elements) | AttrValue::Concat(elements) => elements
+            .iter()
+            .all(|element| collect_srcs_patterns(element, patterns)),
+        AttrValue::Select(branches) => branches
+            .iter()
+            .all(|(_, value)| collect_srcs_patterns(value, patterns)),
+        AttrValue::Call {
+            function,
+            args,
+            attrs,
+        } if function == "glob" => {
+            let include = args.first().or_else(|| {
+                attrs
+                    .iter()
+                    .find(|attr| attr.key == "include")
+                    .map(|attr| &attr.value)
+            });
+            include.is_some_and(|include| collect_srcs_patterns(include, patterns))
+        }
+        _ => false,
+    }
+}
+
+// Add the `exclude` patterns of the globs in `value` to `excludes`
+fn collect_srcs_excludes(value: &AttrValue, excludes: &mut Vec<String>) {
+    match value {
+        AttrValue::List(elements) | AttrValue::Concat(elements) => elements
+            .iter()
+            .for_each(|element| collect_srcs_excludes(element, excludes)),
+        AttrValue::Select(branches) => branches
+            .iter()
+            .for_each(|(_, value)| collect_srcs_excludes(value, excludes)),
+        AttrValue::Call {
+            function, attrs, ..
+        } if function == "glob" => {
+            let exclude = attrs.iter().find(|attr| attr.key == "exclude");
+            if let Some(AttrValue::List(elements)) = exclude.map(|attr| &attr.value) {
+                excludes.extend(
+                    elements
+                        .iter()
+                        .filter_map(AttrValue::as_str)
+                        .map(String::from),
+                );
+            }
+        }
+        _ => {}
+    }
+}
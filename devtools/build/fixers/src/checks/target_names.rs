@@ -0,0 +1,111 @@
+//! Check that a package's swift_library is named after its directory.
+
+use std::path::Path;
+
+use crate::checks::swift_library::swift_libraries;
+use crate::issue::BuildIssue;
+use crate::label_resolver::resolve_label;
+use crate::starlark::ast::BuildFile;
+use crate::starlark::calls::top_level_calls;
+use crate::starlark::tokenizer::{quote, tokenize, TokenKind};
+
+// Flag the swift_library of a package when its name differs from the package
+// directory. Packages with several libraries can't name them all after the
+// directory, so they are skipped, as is a rename onto a name already taken
+// and a package outside `workspace_root`.
+pub fn check_target_names(
+    file: &BuildFile,
+    package_dir: &Path,
+    workspace_root: &Path,
+) -> Option<(BuildIssue, String)> {
+    let expected = package_dir.file_name()?.to_str()?;
+    let package = package_dir.strip_prefix(workspace_root).ok()?;
+    let mut libraries = swift_libraries(file);
+    let library = libraries.next()?;
+    if libraries.next().is_some() {
+        return None;
+    }
+
+    let target = library.name.as_str();
+    if target == expected || file.rules.iter().any(|rule| rule.name() == Some(expected)) {
+        return None;
+    }
+
+    let module_note = if library.module_name.is_some() {
+        ""
+    } else {
+        " (its default module name changes with it)"
+    };
+    Some((
+        BuildIssue::InconsistentTargetName {
+            target: target.to_string(),
+            expected: expected.to_string(),
+            package: package.to_string_lossy().replace('\\', "/"),
+        },
+        format!(
+            "swift_library {:?} should be named after its package directory {:?}{}",
+            target, expected, module_note
+        ),
+    ))
+}
+
+// Rename the swift_library `target` of `package` to `expected` and update
+// the references to it within the file: `:target` and `//package:target`
+// labels. Other packages that depend on the old name need a manual update.
+pub fn fix_target_name(content: &str, target: &str, expected: &str, package: &str) -> String {
+    rename_target(content, target, expected, Some(package))
+}
+
+// Rename `target` to `renamed` and update the `:target` labels of the file,
+// and with `package` (relative to the workspace root) the other labels that
+// resolve to the target, such as `//package:target`
+pub(crate) fn rename_target(
+    content: &str,
+    target: &str,
+    renamed: &str,
+    package: Option<&str>,
+) -> String {
+    let tokens = tokenize(content);
+    let Some(call) = top_level_calls(&tokens)
+        .into_iter()
+        .find(|call| call.target_name(&tokens).as_deref() == Some(target))
+    else {
+        return content.to_string();
+    };
+    let Some(name) = call.keyword(&tokens, "name") else {
+        return content.to_string();
+    };
+
+    let short_label = format!(":{}", target);
+    // Bare names are left alone: in `srcs` they are files, not targets
+    let refers_to_target = |value: &str| match package {
+        Some(package) if value.starts_with("//") || value.starts_with("@//") => {
+            resolve_label(value, package, Path::new("")).is_ok_and(|label| {
+                label.repository.is_none() && label.package == package && label.target == target
+            })
+        }
+        _ => value == short_label,
+    };
+    let mut edits = vec![(name.byte_range(&tokens), renamed.to_string())];
+    for token in tokens.iter().filter(|t| t.kind == TokenKind::String) {
+        let Some(value) = token.string_value() else {
+            continue;
+        };
+        // `//package` names the target after the directory, never `target`,
+        // so each label that refers to it ends in `:target`
+        let Some(prefix) = value.strip_suffix(short_label.as_str()) else {
+            continue;
+        };
+        if !refers_to_target(&value) {
+            continue;
+        }
+        edits.push((token.start..token.end(), format!("{}:{}", prefix, renamed)));
+    }
+
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut new_content = content.to_string();
+    for (range, value) in edits.into_iter().rev() {
+        new_content.replace_range(range, &quote(&value));
+    }
+    new_content
+}
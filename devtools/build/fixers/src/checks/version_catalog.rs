@@ -0,0 +1,188 @@
+//! Check that the `http_archive`s of WORKSPACE files download the versions
+//! of the central version catalog, `third_party/versions.bzl` unless
+//! `--versions-file` names another. The catalog maps repository names to
+//! versions in a `VERSIONS` dict:
+//!
+//! ```text
+//! VERSIONS = {
+//!     "swift_log": "1.5.3",
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::issue::{BuildIssue, WorkspaceIssue};
+use crate::starlark::calls::{top_level_calls, Call};
+use crate::starlark::tokenizer::{find_matching, tokenize, Token, TokenKind};
+
+/// The dict of the catalog that holds the versions.
+pub const VERSIONS_DICT: &str = "VERSIONS";
+
+// Attributes of an http_archive that spell out its version
+const VERSIONED_ATTRIBUTES: &[&str] = &["url", "urls", "strip_prefix"];
+
+// A dotted version number, as in `swift-log-1.5.3.tar.gz` or `v2.0`
+static VERSION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\d+(?:\.\d+)+").expect("invalid regex"));
+
+/// Repository name -> version, read from the catalog's `VERSIONS` dict.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersionCatalog {
+    versions: BTreeMap<String, String>,
+}
+
+impl VersionCatalog {
+    /// Read the catalog at `path`, failing if it has no `VERSIONS` dict.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))?;
+        VersionCatalog::parse(&content).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}: no {} dict of repository names to versions",
+                    path.display(),
+                    VERSIONS_DICT
+                ),
+            )
+        })
+    }
+
+    /// The string entries of the `VERSIONS` dict of `content`, or `None` if
+    /// it has none.
+    pub fn parse(content: &str) -> Option<Self> {
+        let tokens = tokenize(content);
+        // A top-level assignment starts its line
+        let open = (0..tokens.len().saturating_sub(2)).find(|&i| {
+            tokens[i].is_ident(VERSIONS_DICT)
+                && (tokens[i].start == 0 || content[..tokens[i].start].ends_with('\n'))
+                && tokens[i + 1].kind == TokenKind::Equals
+                && tokens[i + 2].kind == TokenKind::LBrace
+        })? + 2;
+        let close = find_matching(&tokens, open)?;
+
+        let entries: Vec<_> = tokens[open + 1..close]
+            .iter()
+            .filter(|token| token.kind != TokenKind::Comment)
+            .collect();
+        let versions = entries
+            .windows(3)
+            .filter(|entry| entry[1].kind == TokenKind::Colon)
+            .filter_map(|entry| Some((entry[0].string_value()?, entry[2].string_value()?)))
+            .collect();
+        Some(VersionCatalog { versions })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.versions.is_empty()
+    }
+
+    /// The version the catalog pins `repository` to.
+    pub fn version(&self, repository: &str) -> Option<&str> {
+        self.versions.get(repository).map(String::as_str)
+    }
+}
+
+// Flag each http_archive in the catalog whose url (or strip_prefix) names
+// another version. Archives whose url has no dotted version are skipped.
+pub fn check_version_catalog(content: &str, catalog: &VersionCatalog) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let mut issues = Vec::new();
+    for call in top_level_calls(&tokens) {
+        if call.name != "http_archive" {
+            continue;
+        }
+        let Some(repository) = call.target_name(&tokens) else {
+            continue;
+        };
+        let Some(expected) = catalog.version(&repository) else {
+            continue;
+        };
+        let Some(version) = archive_version(&versioned_strings(&tokens, &call)) else {
+            continue;
+        };
+        if version == expected {
+            continue;
+        }
+
+        let message = format!(
+            "http_archive {:?} downloads version {} but versions.bzl pins {}; update its \
+             sha256 along with it (--check-sha256 --fix-sha256)",
+            repository, version, expected
+        );
+        issues.push((
+            BuildIssue::Workspace(WorkspaceIssue::VersionMismatch {
+                repository,
+                version,
+                expected: expected.to_string(),
+            }),
+            message,
+        ));
+    }
+    issues
+}
+
+// Replace `version` with `expected` in the url, urls and strip_prefix of the
+// http_archive named `repository`
+pub fn fix_version_mismatch(
+    content: &str,
+    repository: &str,
+    version: &str,
+    expected: &str,
+) -> String {
+    let tokens = tokenize(content);
+    let call = top_level_calls(&tokens)
+        .into_iter()
+        .find(|call| call.target_name(&tokens).as_deref() == Some(repository));
+    let Some(call) = call else {
+        return content.to_string();
+    };
+
+    let mut fixed = content.to_string();
+    let mut strings: Vec<usize> = VERSIONED_ATTRIBUTES
+        .iter()
+        .filter_map(|key| call.keyword(&tokens, key))
+        .flat_map(|argument| argument.value)
+        .filter(|&i| tokens[i].kind == TokenKind::String)
+        .collect();
+    strings.sort_unstable();
+    for &i in strings.iter().rev() {
+        let token = &tokens[i];
+        fixed.replace_range(
+            token.start..token.end(),
+            &token.text.replace(version, expected),
+        );
+    }
+    fixed
+}
+
+// The version of an archive: the last one in its strip_prefix, or else in
+// its first url, where it is usually part of the file name
+fn archive_version(strings: &BTreeMap<&str, Vec<String>>) -> Option<String> {
+    ["strip_prefix", "url", "urls"]
+        .iter()
+        .filter_map(|key| strings.get(key)?.first())
+        .find_map(|value| VERSION_RE.find_iter(value).last())
+        .map(|version| version.as_str().to_string())
+}
+
+// The string values of the attributes of `call` that spell out its version
+fn versioned_strings(tokens: &[Token<'_>], call: &Call<'_>) -> BTreeMap<&'static str, Vec<String>> {
+    VERSIONED_ATTRIBUTES
+        .iter()
+        .filter_map(|&key| {
+            let argument = call.keyword(tokens, key)?;
+            let values = tokens[argument.value]
+                .iter()
+                .filter_map(Token::string_value)
+                .collect();
+            Some((key, values))
+        })
+        .collect()
+}
@@ -0,0 +1,72 @@
+//! Check that `deps` only reference targets visible to the depending
+//! package, which Bazel otherwise rejects when it analyzes the target.
+
+use crate::issue::BuildIssue;
+use crate::label_resolver::AbsoluteLabel;
+use crate::target_graph::TargetGraph;
+
+/// Whether a target whose `visibility` is `vis` (absolute labels, as
+/// [`TargetGraph::visibility`] returns them) may be depended on from
+/// `requesting_package`. A target is always visible within its own
+/// package, which callers check first. Package groups can't be expanded
+/// here, so labels other than `//visibility:...`, `__pkg__` and
+/// `__subpackages__` are taken to allow it.
+pub fn visibility_allows(vis: &[String], requesting_package: &str) -> bool {
+    let requesting_package = requesting_package.trim_start_matches("//");
+    vis.iter().any(|visible_to| {
+        let Some((package, target)) = visible_to
+            .strip_prefix("//")
+            .and_then(|label| label.split_once(':'))
+        else {
+            return true;
+        };
+        match (package, target) {
+            ("visibility", "public") => true,
+            ("visibility", "private") => false,
+            (package, "__pkg__") => package == requesting_package,
+            (package, "__subpackages__") => {
+                package.is_empty()
+                    || requesting_package == package
+                    || requesting_package
+                        .strip_prefix(package)
+                        .is_some_and(|rest| rest.starts_with('/'))
+            }
+            _ => true,
+        }
+    })
+}
+
+// Flag each `deps` label of another package whose target isn't visible to
+// the depending target's package. Targets outside the workspace, and those
+// whose visibility isn't a list of labels, are skipped.
+pub fn check_visibility_leaks(graph: &TargetGraph) -> Vec<(AbsoluteLabel, BuildIssue, String)> {
+    let mut issues = Vec::new();
+    for (label, _) in graph.targets() {
+        for dep in graph.deps(label) {
+            if dep.package == label.package {
+                continue;
+            }
+            let Some(vis) = graph.visibility(&dep) else {
+                continue;
+            };
+            if visibility_allows(&vis, &label.package) {
+                continue;
+            }
+            let message = format!(
+                "{:?} depends on {}, which is only visible to {}; Bazel rejects the dependency",
+                label.target,
+                dep,
+                vis.join(", ")
+            );
+            issues.push((
+                label.clone(),
+                BuildIssue::PrivateVisibilityLeak {
+                    target: label.target.clone(),
+                    dependency: dep.to_string(),
+                },
+                message,
+            ));
+        }
+    }
+    issues
+}
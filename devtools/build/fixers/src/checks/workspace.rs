@@ -0,0 +1,352 @@
+//! Checks on the repository rules in WORKSPACE files.
+
+use std::time::Duration;
+
+use crate::checks::version_catalog::fix_version_mismatch;
+use crate::config::{Config, RunMode};
+use crate::issue::{BuildIssue, WorkspaceIssue};
+use crate::starlark::calls::{insert_after_name, top_level_calls, Call};
+use crate::starlark::tokenizer::{tokenize, Token, TokenKind};
+
+const HTTP_BZL: &str = "@bazel_tools//tools/build_defs/repo:http.bzl";
+const GIT_BZL: &str = "@bazel_tools//tools/build_defs/repo:git.bzl";
+
+/// Repository rules and the bzl file that defines them.
+pub const REPOSITORY_RULES: &[(&str, &str)] = &[
+    ("http_archive", HTTP_BZL),
+    ("http_file", HTTP_BZL),
+    ("http_jar", HTTP_BZL),
+    ("git_repository", GIT_BZL),
+    ("new_git_repository", GIT_BZL),
+];
+
+/// Native repository rules Bazel has removed, and their replacements.
+pub const OUTDATED_RULES: &[(&str, &str)] = &[("new_http_archive", "http_archive")];
+
+// Rules whose downloads should be pinned by checksum
+const CHECKSUMMED_RULES: &[&str] = &["http_archive", "http_file", "http_jar"];
+
+// Check every repository rule call. With `config.network`, archives missing
+// a sha256 are downloaded so the fix can insert it, in runs that fix files
+// only; with `config.check_sha256`, those that have one are downloaded to
+// verify it.
+pub fn check_workspace_file(content: &str, config: &Config) -> Vec<(BuildIssue, String)> {
+    let tokens = tokenize(content);
+    let calls = top_level_calls(&tokens);
+    let loaded = loaded_symbols(&tokens, &calls);
+    let mut issues = Vec::new();
+
+    for call in &calls {
+        let rule = match OUTDATED_RULES.iter().find(|(old, _)| *old == call.name) {
+            Some((old, new)) => {
+                issues.push((
+                    WorkspaceIssue::OutdatedRepositoryRule {
+                        rule: old.to_string(),
+                        replacement: new.to_string(),
+                    },
+                    format!("{} was removed from Bazel; use {}", old, new),
+                ));
+                *new
+            }
+            None => call.name,
+        };
+
+        let missing_load = REPOSITORY_RULES
+            .iter()
+            .find(|(name, _)| *name == rule)
+            .filter(|(name, _)| !loaded.iter().any(|symbol| symbol == name));
+        if let Some((rule, bzl)) = missing_load {
+            let issue = WorkspaceIssue::MissingLoad {
+                rule: rule.to_string(),
+                bzl: bzl.to_string(),
+            };
+            if !issues.iter().any(|(existing, _)| *existing == issue) {
+                issues.push((
+                    issue,
+                    format!("{} is used without loading it from {}", rule, bzl),
+                ));
+            }
+        }
+
+        let repository = call
+            .target_name(&tokens)
+            .unwrap_or_else(|| format!("<unnamed {}>", rule));
+        if CHECKSUMMED_RULES.contains(&rule) {
+            issues.extend(check_http_rule(&tokens, call, rule, &repository, config));
+        }
+        if rule.ends_with("git_repository") && call.keyword(&tokens, "commit").is_none() {
+            issues.push((
+                WorkspaceIssue::MissingCommit {
+                    repository: repository.clone(),
+                },
+                format!("{} {:?} is not pinned to a commit", rule, repository),
+            ));
+        }
+    }
+
+    issues
+        .into_iter()
+        .map(|(issue, message)| (BuildIssue::Workspace(issue), message))
+        .collect()
+}
+
+fn check_http_rule(
+    tokens: &[Token<'_>],
+    call: &Call<'_>,
+    rule: &str,
+    repository: &str,
+    config: &Config,
+) -> Option<(WorkspaceIssue, String)> {
+    let urls: Option<Vec<String>> = call.keyword(tokens, "urls").map(|urls| {
+        tokens[urls.value]
+            .iter()
+            .filter_map(Token::string_value)
+            .collect()
+    });
+    let url = call.string_attr(tokens, "url");
+    let url = match (url, urls) {
+        (Some(url), None) => url,
+        (None, Some(urls)) => urls.into_iter().next()?,
+        (url, urls) => {
+            let problem = if url.is_some() && urls.is_some() {
+                "sets both url and urls"
+            } else {
+                "sets neither url nor urls"
+            };
+            return Some((
+                WorkspaceIssue::IncorrectHttpArchive {
+                    repository: repository.to_string(),
+                },
+                format!("{} {:?} {}", rule, repository, problem),
+            ));
+        }
+    };
+
+    let timeout = Duration::from_secs(config.network_timeout_secs);
+    if let Some(sha256) = call.string_attr(tokens, "sha256") {
+        if !config.check_sha256 {
+            return None;
+        }
+        // An archive that can't be downloaded can't be verified either, which
+        // is reported unless --allow-unverifiable-sha256 accepts it
+        let computed = match config.downloads.sha256_of_url(&url, timeout) {
+            Ok(computed) => computed,
+            Err(_) if config.allow_unverifiable_sha256 => return None,
+            Err(err) => {
+                let message = format!(
+                    "{} {:?} has sha256 {} but {} can't be downloaded to verify it: {}",
+                    rule, repository, sha256, url, err
+                );
+                return Some((
+                    WorkspaceIssue::UnverifiableSha256 {
+                        repository: repository.to_string(),
+                        url,
+                        error: err.to_string(),
+                    },
+                    message,
+                ));
+            }
+        };
+        if computed.eq_ignore_ascii_case(&sha256) {
+            return None;
+        }
+        let hint = if config.fix_sha256 {
+            ""
+        } else {
+            " (rerun with --fix-sha256 to update it)"
+        };
+        let message = format!(
+            "{} {:?} has sha256 {} but {} hashes to {}{}",
+            rule, repository, sha256, url, computed, hint
+        );
+        return Some((
+            WorkspaceIssue::IncorrectSha256 {
+                repository: repository.to_string(),
+                url,
+                sha256,
+                computed,
+                update: config.fix_sha256,
+            },
+            message,
+        ));
+    }
+
+    let pinned = ["sha256", "integrity"]
+        .iter()
+        .any(|key| call.keyword(tokens, key).is_some());
+    if pinned {
+        return None;
+    }
+
+    let (sha256, hint) = if config.network && config.mode == RunMode::Fix {
+        match config.downloads.sha256_of_url(&url, timeout) {
+            Ok(sha256) => (Some(sha256), String::new()),
+            Err(err) => (None, format!(" ({})", err)),
+        }
+    } else if config.network {
+        (
+            None,
+            " (--network only downloads it when fixing)".to_string(),
+        )
+    } else {
+        (None, " (rerun with --network to compute it)".to_string())
+    };
+    Some((
+        WorkspaceIssue::MissingSha256 {
+            repository: repository.to_string(),
+            url,
+            sha256,
+        },
+        format!("{} {:?} has no sha256{}", rule, repository, hint),
+    ))
+}
+
+/// Apply the fix for a WORKSPACE issue; issues that need a manual fix leave
+/// the content unchanged.
+pub fn fix_workspace_issue(issue: &WorkspaceIssue, content: &str) -> String {
+    match issue {
+        WorkspaceIssue::MissingLoad { rule, bzl } => fix_missing_load(content, rule, bzl),
+        WorkspaceIssue::OutdatedRepositoryRule { rule, replacement } => {
+            fix_outdated_rule(content, rule, replacement)
+        }
+        WorkspaceIssue::MissingSha256 {
+            repository,
+            sha256: Some(sha256),
+            ..
+        } => fix_missing_sha256(content, repository, sha256),
+        WorkspaceIssue::IncorrectSha256 {
+            repository,
+            computed,
+            update: true,
+            ..
+        } => fix_incorrect_sha256(content, repository, computed),
+        WorkspaceIssue::VersionMismatch {
+            repository,
+            version,
+            expected,
+        } => fix_version_mismatch(content, repository, version, expected),
+        WorkspaceIssue::MissingSha256 { .. }
+        | WorkspaceIssue::IncorrectSha256 { .. }
+        | WorkspaceIssue::IncorrectHttpArchive { .. }
+        | WorkspaceIssue::UnverifiableSha256 { .. }
+        | WorkspaceIssue::MissingCommit { .. } => content.to_string(),
+    }
+}
+
+// Load `rule` from `bzl`: added to an existing load of that file, or as a
+// new load after the last one (or after workspace() when there are none)
+pub fn fix_missing_load(content: &str, rule: &str, bzl: &str) -> String {
+    let tokens = tokenize(content);
+    let calls = top_level_calls(&tokens);
+    let loads: Vec<&Call<'_>> = calls.iter().filter(|call| call.name == "load").collect();
+
+    let existing = loads.iter().find(|call| {
+        tokens[call.open + 1].kind == TokenKind::String
+            && tokens[call.open + 1].string_value().as_deref() == Some(bzl)
+    });
+    if let Some(load) = existing {
+        let last = (load.open..load.close)
+            .rev()
+            .find(|&i| tokens[i].kind != TokenKind::Comment)
+            .unwrap_or(load.open);
+        let at = tokens[last].end();
+        let separator = if tokens[last].kind == TokenKind::Comma {
+            " "
+        } else {
+            ", "
+        };
+        return format!(
+            "{}{}\"{}\"{}",
+            &content[..at],
+            separator,
+            rule,
+            &content[at..]
+        );
+    }
+
+    let statement = format!("load(\"{}\", \"{}\")\n", bzl, rule);
+    let anchor = loads
+        .last()
+        .copied()
+        .or_else(|| calls.iter().find(|call| call.name == "workspace"));
+    match anchor {
+        Some(anchor) => {
+            let end = tokens[anchor.close].end();
+            let at = content[end..]
+                .find('\n')
+                .map_or(content.len(), |i| end + i + 1);
+            let mut fixed = content[..at].to_string();
+            if !fixed.ends_with('\n') {
+                fixed.push('\n');
+            }
+            if anchor.name != "load" {
+                fixed.push('\n');
+            }
+            fixed.push_str(&statement);
+            fixed.push_str(&content[at..]);
+            fixed
+        }
+        None => format!("{}\n{}", statement, content),
+    }
+}
+
+// Rename every call of `rule` to `replacement`
+pub fn fix_outdated_rule(content: &str, rule: &str, replacement: &str) -> String {
+    let tokens = tokenize(content);
+    let mut fixed = content.to_string();
+    for call in top_level_calls(&tokens).iter().rev() {
+        if call.name == rule {
+            let name = &tokens[call.open - 1];
+            fixed.replace_range(name.start..name.end(), replacement);
+        }
+    }
+    fixed
+}
+
+// Pin the repository named `repository` to `sha256`
+pub fn fix_missing_sha256(content: &str, repository: &str, sha256: &str) -> String {
+    let tokens = tokenize(content);
+    let call = top_level_calls(&tokens)
+        .into_iter()
+        .find(|call| call.target_name(&tokens).as_deref() == Some(repository));
+    match call {
+        Some(call) => {
+            insert_after_name(content, &tokens, &call, &format!("sha256 = \"{}\"", sha256))
+        }
+        None => content.to_string(),
+    }
+}
+
+// Replace the sha256 of the repository named `repository` with `sha256`
+pub fn fix_incorrect_sha256(content: &str, repository: &str, sha256: &str) -> String {
+    let tokens = tokenize(content);
+    let value = top_level_calls(&tokens)
+        .into_iter()
+        .filter(|call| call.target_name(&tokens).as_deref() == Some(repository))
+        .find_map(|call| call.keyword(&tokens, "sha256"));
+    match value {
+        Some(value) if value.value.len() == 1 => {
+            let range = value.byte_range(&tokens);
+            format!(
+                "{}\"{}\"{}",
+                &content[..range.start],
+                sha256,
+                &content[range.end..]
+            )
+        }
+        _ => content.to_string(),
+    }
+}
+
+// Local names bound by the top-level load() statements
+pub(crate) fn loaded_symbols(tokens: &[Token<'_>], calls: &[Call<'_>]) -> Vec<String> {
+    calls
+        .iter()
+        .filter(|call| call.name == "load")
+        .flat_map(|call| call.arguments(tokens).into_iter().skip(1))
+        .filter_map(|argument| match argument.key {
+            Some(alias) => Some(alias.to_string()),
+            None => tokens[argument.value.start].string_value(),
+        })
+        .collect()
+}
@@ -0,0 +1,569 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::baseline::Baseline;
+use crate::bazel_query::{DEFAULT_QUERY, DEFAULT_TIMEOUT_SECS};
+use crate::checks::apple::DEFAULT_BUNDLE_ID_PREFIX;
+use crate::checks::globs::DEFAULT_GENERATED_FILE_PATTERNS;
+use crate::checks::loads::{default_rule_migrations, MacroRegistry};
+use crate::checks::naming_convention::NamingConvention;
+use crate::checks::package::DEFAULT_LICENSE_TYPE;
+use crate::checks::paths::DEFAULT_PROJECT_PATH_VARIABLE;
+use crate::checks::swift_library::UMBRA_SWIFT_RULES;
+use crate::checks::version_catalog::VersionCatalog;
+use crate::download::{DownloadCache, DEFAULT_NETWORK_TIMEOUT_SECS};
+use crate::issue::Finding;
+use crate::label::Label;
+use crate::migrations::rules_swift::VersionUpgrade;
+use crate::parallel_io::DEFAULT_MAX_OPEN_FILES;
+use crate::patch::DEFAULT_CONTEXT;
+
+pub mod schema;
+
+/// Name of the optional config file read from the root directory.
+pub const CONFIG_FILE_NAME: &str = "umbra-fix.toml";
+
+/// Name of the optional map from dependency labels to the Swift modules they provide.
+pub const IMPORT_MAP_FILE_NAME: &str = "import_map.toml";
+
+/// Name of the optional file of load() label migrations. Its presence turns
+/// the migrations on.
+pub const RULE_MIGRATIONS_FILE_NAME: &str = "rule_migrations.toml";
+
+/// Rule types analyzed unless `rule_filter` (or `--rules`) says otherwise.
+pub const DEFAULT_RULE_FILTER: &[&str] = &["swift_*"];
+
+/// Name of the optional map from target names to intended Swift module names.
+pub const MODULE_NAME_MAP_FILE_NAME: &str = "module_name_map.toml";
+
+/// Name of the optional map from SPM product names to the Bazel labels
+/// `convert-spm` depends on for them.
+pub const SPM_LABEL_MAP_FILE_NAME: &str = "spm_label_map.toml";
+
+/// Name of the optional map from old `//third_party/...` packages to the
+/// external repositories (`@repo//...`) that replace them.
+pub const EXTERNAL_LABEL_REWRITES_FILE_NAME: &str = "external_label_rewrites.toml";
+
+/// Per-rule patterns for target names; see [`NamingConvention`].
+pub const NAMING_CONVENTION_FILE_NAME: &str = "naming_convention.toml";
+
+/// Project macros and their bzl files; see [`MacroRegistry`].
+pub const MACRO_REGISTRY_FILE_NAME: &str = "macro_registry.toml";
+
+/// Version catalog read from the root directory unless `--versions-file`
+/// names another.
+pub const VERSIONS_FILE_NAME: &str = "third_party/versions.bzl";
+
+/// How a run treats the issues it finds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RunMode {
+    /// Apply fixes and write the modified files back.
+    #[default]
+    Fix,
+    /// Report what would be fixed without writing anything.
+    DryRun,
+    /// Like `DryRun`, but the run fails if anything would be fixed.
+    Check,
+    /// Print a unified diff of the fixes instead of applying them; the run
+    /// fails if there is anything to fix.
+    Diff,
+}
+
+/// What a run produces besides its console summary.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Fix files in place (or just report, depending on the run mode).
+    #[default]
+    Text,
+    /// Leave files untouched and write the fixes as a unified diff.
+    Patch,
+    /// Leave files untouched and write an HTML report with the issues and diffs.
+    Html,
+    /// Leave files untouched and write a JUnit XML report with a failed test
+    /// case per issue.
+    Junit,
+}
+
+/// When console output is colored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ColorMode {
+    /// Color when stdout is a terminal.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// When a progress bar shows the files processed so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ProgressMode {
+    /// Show it when stdout is a terminal.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Settings for a single run of the fixer.
+///
+/// Fields skipped by serde are per-run options set from the command line;
+/// everything else can also be set in `umbra-fix.toml`.
+#[derive(Debug, Clone, Deserialize, schemars::JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Directory searched for BUILD.bazel files.
+    #[serde(skip)]
+    pub root_dir: PathBuf,
+    #[serde(skip)]
+    pub mode: RunMode,
+    /// Fix files in place (text), or write the fixes as a unified diff (patch),
+    /// an HTML report (html) or the issues as JUnit XML (junit).
+    pub output: OutputFormat,
+    /// Where `--output patch` writes its diff.
+    #[serde(skip)]
+    pub patch_file: Option<PathBuf>,
+    /// Where `--output html` writes its report.
+    #[serde(skip)]
+    pub html_file: Option<PathBuf>,
+    /// Where `--output junit` writes its report.
+    #[serde(skip)]
+    pub junit_file: Option<PathBuf>,
+    /// Unchanged lines shown around each change in diffs.
+    #[serde(skip)]
+    pub diff_context: usize,
+    /// Print the diff of each fix on its own as it is applied.
+    #[serde(skip)]
+    pub verbose_diffs: bool,
+    /// Whether console diffs are colored.
+    #[serde(skip)]
+    pub color: ColorMode,
+    /// Whether a progress bar is drawn on stderr while files are processed.
+    #[serde(skip)]
+    pub progress: ProgressMode,
+    /// Copy each file to `<file>.bak` before writing its fixes, for `umbra-fix undo`.
+    pub backup: bool,
+    /// Where analysis results are cached between runs (no caching if unset).
+    #[serde(skip)]
+    pub cache_file: Option<PathBuf>,
+    /// Discard the cached results before the run.
+    #[serde(skip)]
+    pub invalidate_cache: bool,
+    /// Where a fixing run records its progress, so it can be resumed if interrupted.
+    #[serde(skip)]
+    pub progress_file: Option<PathBuf>,
+    /// Fail unless there is an interrupted run to resume.
+    #[serde(skip)]
+    pub resume: bool,
+    /// Forget the progress of an interrupted run and start over.
+    #[serde(skip)]
+    pub reset_progress: bool,
+    /// Known issues to leave unreported and unfixed, read from `--baseline`.
+    #[serde(skip)]
+    pub baseline: Option<Baseline>,
+    /// Where to write the issues found as a new baseline.
+    #[serde(skip)]
+    pub save_baseline: Option<PathBuf>,
+    /// Re-analyze every fixed file and fail if the fixes introduced new issues.
+    #[serde(skip)]
+    pub verify_idempotent: bool,
+    /// Read every BUILD file concurrently before processing them.
+    #[serde(skip)]
+    pub parallel_io: bool,
+    /// How many files `parallel_io` keeps open at once.
+    #[serde(skip)]
+    pub max_open_files: usize,
+    /// Also print each finding as a GitHub Actions annotation (set when
+    /// running in a GitHub Actions job).
+    #[serde(skip)]
+    pub github_annotations: bool,
+    /// Where to write a JSON report of the whole run.
+    #[serde(skip)]
+    pub report_file: Option<PathBuf>,
+    /// Only process BUILD files whose root-relative path matches one of these globs.
+    pub include_patterns: Vec<String>,
+    /// Skip BUILD files whose root-relative path matches any of these globs.
+    pub exclude_patterns: Vec<String>,
+    /// Only process the BUILD file that defines this label.
+    #[serde(skip)]
+    pub target: Option<Label>,
+    /// Only process BUILD files that differ from this git ref.
+    #[serde(skip)]
+    pub git_changed_only: Option<String>,
+    /// Only process BUILD files changed in the commits since this git tag.
+    #[serde(skip)]
+    pub since_tag: Option<String>,
+    /// How many directory levels below the root to search (unlimited if unset).
+    pub max_depth: Option<usize>,
+    /// Rule types to analyze (e.g. `swift_library`, or `swift_*` for every
+    /// rule with the prefix); checks of other rules are skipped and their
+    /// targets left untouched by fixes. Empty analyzes every rule.
+    pub rule_filter: Vec<String>,
+    /// List attributes whose string elements must be sorted.
+    pub sorted_list_attributes: Vec<String>,
+    /// Platform (ios, macos, tvos or watchos) -> `minimum_os_version` added to
+    /// bundling rules that lack one. Unset platforms use the built-in default.
+    pub minimum_os_versions: BTreeMap<String, String>,
+    /// Prefix of the `bundle_id` added to bundling rules that lack one; the
+    /// target name follows it.
+    pub bundle_id_prefix: String,
+    /// Migrate BUILD files between rules_swift major versions.
+    #[serde(skip)]
+    pub rules_swift_upgrade: Option<VersionUpgrade>,
+    /// What hard-coded paths into a developer's checkout are replaced with;
+    /// the part of the path below the checkout is kept.
+    pub project_path_variable: String,
+    /// File name patterns of generated Swift sources, which `srcs` globs
+    /// should exclude.
+    pub generated_file_patterns: Vec<String>,
+    /// Rename a package's swift_library after its directory, updating the
+    /// references to it within the package.
+    pub check_target_names: bool,
+    /// Report `allow_empty = True` on globs that match files of the package,
+    /// and remove it.
+    pub warn_redundant_allow_empty: bool,
+    /// Require every BUILD file to declare `licenses([license_type])`.
+    pub require_license: bool,
+    /// The license type `require_license` expects, e.g. `notice`.
+    pub license_type: String,
+    /// Require swift_binary targets to be dead-stripped, adding `-dead_strip`
+    /// to their linkopts. Off by default, as it changes how they link.
+    pub require_dead_strip: bool,
+    /// Longest line BUILD files may have, in characters (unlimited if unset).
+    pub max_line_length: Option<usize>,
+    /// Reorder rule attributes into canonical order (name, srcs, deps, ...).
+    pub sort_attributes: bool,
+    /// Reformat files into canonical layout after all other fixes.
+    pub format: bool,
+    /// Only reformat; skip every other check.
+    #[serde(skip)]
+    pub format_only: bool,
+    /// Target name -> Swift module name, read from `module_name_map.toml`.
+    #[serde(skip)]
+    pub module_names: BTreeMap<String, String>,
+    /// Remove deps whose module no source of the target imports.
+    #[serde(skip)]
+    pub prune_deps: bool,
+    /// Let `prune_deps` remove system framework labels too.
+    #[serde(skip)]
+    pub prune_system_deps: bool,
+    /// Dependency label -> Swift module name, read from `import_map.toml`.
+    #[serde(skip)]
+    pub import_map: BTreeMap<String, String>,
+    /// Download archives to compute missing sha256 values.
+    #[serde(skip)]
+    pub network: bool,
+    /// Download http_archive URLs in WORKSPACE files to verify their sha256.
+    #[serde(skip)]
+    pub check_sha256: bool,
+    /// Let the `check_sha256` fix replace sha256 values that don't match.
+    #[serde(skip)]
+    pub fix_sha256: bool,
+    /// Accept sha256 values `check_sha256` can't verify because the download
+    /// fails, instead of reporting them.
+    #[serde(skip)]
+    pub allow_unverifiable_sha256: bool,
+    /// Seconds a download may take before it is abandoned.
+    pub network_timeout_secs: u64,
+    /// Archives downloaded by the checks of this run.
+    #[serde(skip)]
+    pub downloads: DownloadCache,
+    /// Run `bazel query` after fixing and report the errors Bazel finds.
+    #[serde(skip)]
+    pub bazel_validate: bool,
+    /// Bazel binary used by `bazel_validate`.
+    #[serde(skip)]
+    pub bazel: PathBuf,
+    /// Query expression `bazel_validate` runs.
+    pub bazel_query: String,
+    /// Seconds `bazel query` may run before it is killed.
+    pub bazel_timeout_secs: u64,
+    /// Old load() label -> new label. Empty unless `rule_migrations.toml`
+    /// exists or `--migrate-rule-loads` is given.
+    #[serde(skip)]
+    pub rule_migrations: BTreeMap<String, String>,
+    /// SPM product name -> Bazel label, read from `spm_label_map.toml`.
+    #[serde(skip)]
+    pub spm_labels: BTreeMap<String, String>,
+    /// Findings of the checks that look across BUILD files, by package.
+    /// Filled in from the whole workspace at the start of a run.
+    #[serde(skip)]
+    pub cross_file_findings: BTreeMap<String, Vec<Finding>>,
+    /// Old package prefix -> external label prefix, read from
+    /// `external_label_rewrites.toml`.
+    #[serde(skip)]
+    pub external_label_rewrites: BTreeMap<String, String>,
+    /// Rule type -> pattern for target names, read from
+    /// `naming_convention.toml`. Empty (nothing checked) without the file.
+    #[serde(skip)]
+    pub naming_convention: NamingConvention,
+    /// Macro name -> defining bzl file, read from `macro_registry.toml`.
+    #[serde(skip)]
+    pub macro_registry: MacroRegistry,
+    /// Repository name -> version, read from `third_party/versions.bzl`.
+    /// Empty (nothing checked) without the file.
+    #[serde(skip)]
+    pub version_catalog: VersionCatalog,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            root_dir: PathBuf::new(),
+            mode: RunMode::default(),
+            output: OutputFormat::default(),
+            patch_file: None,
+            html_file: None,
+            junit_file: None,
+            diff_context: DEFAULT_CONTEXT,
+            verbose_diffs: false,
+            color: ColorMode::Auto,
+            progress: ProgressMode::Auto,
+            backup: false,
+            cache_file: None,
+            invalidate_cache: false,
+            progress_file: None,
+            resume: false,
+            reset_progress: false,
+            baseline: None,
+            save_baseline: None,
+            verify_idempotent: false,
+            parallel_io: false,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            github_annotations: false,
+            report_file: None,
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            target: None,
+            git_changed_only: None,
+            since_tag: None,
+            max_depth: None,
+            rule_filter: DEFAULT_RULE_FILTER
+                .iter()
+                .map(|rule| rule.to_string())
+                .collect(),
+            sorted_list_attributes: vec!["deps".to_string()],
+            minimum_os_versions: BTreeMap::new(),
+            bundle_id_prefix: DEFAULT_BUNDLE_ID_PREFIX.to_string(),
+            rules_swift_upgrade: None,
+            project_path_variable: DEFAULT_PROJECT_PATH_VARIABLE.to_string(),
+            generated_file_patterns: DEFAULT_GENERATED_FILE_PATTERNS
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect(),
+            check_target_names: false,
+            warn_redundant_allow_empty: false,
+            require_license: false,
+            license_type: DEFAULT_LICENSE_TYPE.to_string(),
+            max_line_length: None,
+            require_dead_strip: false,
+            sort_attributes: false,
+            format: false,
+            format_only: false,
+            module_names: BTreeMap::new(),
+            prune_deps: false,
+            prune_system_deps: false,
+            import_map: BTreeMap::new(),
+            network: false,
+            check_sha256: false,
+            fix_sha256: false,
+            allow_unverifiable_sha256: false,
+            network_timeout_secs: DEFAULT_NETWORK_TIMEOUT_SECS,
+            downloads: DownloadCache::default(),
+            bazel_validate: false,
+            bazel: PathBuf::from("bazel"),
+            bazel_query: DEFAULT_QUERY.to_string(),
+            bazel_timeout_secs: DEFAULT_TIMEOUT_SECS,
+            rule_migrations: BTreeMap::new(),
+            spm_labels: BTreeMap::new(),
+            cross_file_findings: BTreeMap::new(),
+            external_label_rewrites: BTreeMap::new(),
+            naming_convention: NamingConvention::default(),
+            macro_registry: MacroRegistry::default(),
+            version_catalog: VersionCatalog::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn new(root_dir: impl Into<PathBuf>) -> Self {
+        Config {
+            root_dir: root_dir.into(),
+            ..Config::default()
+        }
+    }
+
+    /// Build a config for `root_dir`, reading `umbra-fix.toml` from it if present.
+    pub fn load(root_dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let root_dir = root_dir.into();
+        let config_path = root_dir.join(CONFIG_FILE_NAME);
+
+        let mut config: Config = read_toml(&config_path)?.unwrap_or_default();
+        config.module_names =
+            read_toml(&root_dir.join(MODULE_NAME_MAP_FILE_NAME))?.unwrap_or_default();
+        config.import_map = read_toml(&root_dir.join(IMPORT_MAP_FILE_NAME))?.unwrap_or_default();
+        config.spm_labels = read_toml(&root_dir.join(SPM_LABEL_MAP_FILE_NAME))?.unwrap_or_default();
+        config.external_label_rewrites =
+            read_external_label_rewrites(&root_dir.join(EXTERNAL_LABEL_REWRITES_FILE_NAME))?;
+        config.naming_convention =
+            read_naming_convention(&root_dir.join(NAMING_CONVENTION_FILE_NAME))?;
+        config.macro_registry = read_macro_registry(&root_dir.join(MACRO_REGISTRY_FILE_NAME))?;
+        let versions_file = root_dir.join(VERSIONS_FILE_NAME);
+        if versions_file.is_file() {
+            config.version_catalog = VersionCatalog::load(&versions_file)?;
+        }
+        if let Some(file) =
+            read_toml::<RuleMigrationsFile>(&root_dir.join(RULE_MIGRATIONS_FILE_NAME))?
+        {
+            config.rule_migrations = file.into_migrations();
+        }
+        config.root_dir = root_dir;
+
+        Ok(config)
+    }
+
+    /// Whether fixed content should be written back to disk.
+    pub fn writes_files(&self) -> bool {
+        self.mode == RunMode::Fix && self.output == OutputFormat::Text
+    }
+
+    /// Whether checks of `rule` targets should run under `rule_filter`. The
+    /// legacy `umbra_*` macros count as the rules_swift rules replacing them.
+    pub fn analyzes_rule(&self, rule: &str) -> bool {
+        let rule = UMBRA_SWIFT_RULES
+            .iter()
+            .find(|(legacy, _)| *legacy == rule)
+            .map_or(rule, |(_, replacement)| replacement);
+        self.rule_filter.is_empty()
+            || self
+                .rule_filter
+                .iter()
+                .any(|pattern| match pattern.strip_suffix('*') {
+                    Some(prefix) => rule.starts_with(prefix),
+                    None => pattern == rule,
+                })
+    }
+
+    /// Whether reports should carry a diff of the proposed changes.
+    pub fn wants_diffs(&self) -> bool {
+        matches!(self.output, OutputFormat::Patch | OutputFormat::Html)
+            || self.mode == RunMode::Diff
+    }
+}
+
+/// Contents of `rule_migrations.toml`.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RuleMigrationsFile {
+    /// Include the built-in rules_swift and rules_apple migrations.
+    defaults: bool,
+    migrations: BTreeMap<String, String>,
+}
+
+impl Default for RuleMigrationsFile {
+    fn default() -> Self {
+        RuleMigrationsFile {
+            defaults: true,
+            migrations: BTreeMap::new(),
+        }
+    }
+}
+
+impl RuleMigrationsFile {
+    // Entries in the file override the built-in ones
+    fn into_migrations(self) -> BTreeMap<String, String> {
+        let mut migrations = if self.defaults {
+            default_rule_migrations()
+        } else {
+            BTreeMap::new()
+        };
+        migrations.extend(self.migrations);
+        migrations
+    }
+}
+
+// Read `external_label_rewrites.toml`, failing on entries that don't map a
+// `//` package to an `@repo` label
+fn read_external_label_rewrites(path: &Path) -> io::Result<BTreeMap<String, String>> {
+    let rewrites: BTreeMap<String, String> = read_toml(path)?.unwrap_or_default();
+    for (from, to) in &rewrites {
+        if !from.starts_with("//") || from.contains(':') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {:?} isn't a package (//path)", path.display(), from),
+            ));
+        }
+        if !to.starts_with('@') || to.contains(':') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}: {:?} isn't an external repository (@repo or @repo//path)",
+                    path.display(),
+                    to
+                ),
+            ));
+        }
+    }
+    Ok(rewrites)
+}
+
+// Read `naming_convention.toml`, failing on patterns that aren't valid regexes
+fn read_naming_convention(path: &Path) -> io::Result<NamingConvention> {
+    let patterns: BTreeMap<String, String> = read_toml(path)?.unwrap_or_default();
+    NamingConvention::new(&patterns).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), err),
+        )
+    })
+}
+
+// Read `macro_registry.toml`, failing on entries whose file isn't a bzl label
+fn read_macro_registry(path: &Path) -> io::Result<MacroRegistry> {
+    let registry: MacroRegistry = read_toml(path)?.unwrap_or_default();
+    for (name, bzl) in registry.macros() {
+        let is_label = bzl.starts_with("//") || bzl.starts_with('@') || bzl.starts_with(':');
+        if !is_label || !bzl.ends_with(".bzl") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}: {} is defined in {:?}, which isn't a bzl label (//pkg:file.bzl)",
+                    path.display(),
+                    name,
+                    bzl
+                ),
+            ));
+        }
+    }
+    Ok(registry)
+}
+
+// Parse a TOML file, or return `None` if it doesn't exist
+fn read_toml<T: serde::de::DeserializeOwned>(path: &Path) -> io::Result<Option<T>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)?;
+    toml::from_str(&content).map(Some).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), err),
+        )
+    })
+}
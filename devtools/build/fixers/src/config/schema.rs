@@ -0,0 +1,20 @@
+//! JSON Schema for `umbra-fix.toml`, so editors can validate the config.
+
+use schemars::schema_for;
+use serde_json::Value;
+
+use super::Config;
+
+/// Where the published schema lives. Override at build time with the
+/// `UMBRA_FIX_SCHEMA_URL` environment variable.
+pub const SCHEMA_URL: &str = match option_env!("UMBRA_FIX_SCHEMA_URL") {
+    Some(url) => url,
+    None => "https://raw.githubusercontent.com/UmbraDevelopment/UmbraCore/main/devtools/build/fixers/umbra-fix.schema.json",
+};
+
+/// The schema of the config file, with field descriptions and defaults.
+pub fn config_schema() -> Value {
+    let mut schema = schema_for!(Config);
+    schema.insert("$id".to_string(), Value::from(SCHEMA_URL));
+    schema.to_value()
+}
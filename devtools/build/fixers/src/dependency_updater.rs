@@ -0,0 +1,179 @@
+//! Batch renames of labels across BUILD files, for `umbra-fix update-deps`.
+//!
+//! Labels are compared after resolving them against their package, so a
+//! rename of `//Sources/Old:Old` also replaces `//Sources/Old` and, inside
+//! `Sources/Old`, `:Old`, but never `//Sources/Old:OldTests`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::atomic_write::atomic_write;
+use crate::baseline::relative_path;
+use crate::config::Config;
+use crate::discovery::{find_build_files, is_workspace_file};
+use crate::label_resolver::{resolve_label, AbsoluteLabel};
+use crate::starlark::calls::top_level_calls;
+use crate::starlark::tokenizer::{quote, tokenize};
+
+/// Name of the manifest `update-deps` reads from the root by default.
+pub const DEPS_UPDATE_FILE_NAME: &str = "deps_update.toml";
+
+/// Attributes whose labels are renamed, besides those of `load()` statements.
+pub const RENAMED_ATTRIBUTES: &[&str] = &["deps", "data"];
+
+/// One entry of `deps_update.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LabelRename {
+    pub from: String,
+    pub to: String,
+}
+
+/// Contents of `deps_update.toml`: `renames = [{ from = "...", to = "..." }]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DepsManifest {
+    pub renames: Vec<LabelRename>,
+}
+
+/// A label replaced in a BUILD file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replacement {
+    pub path: PathBuf,
+    /// 1-based line of the label.
+    pub line: usize,
+    /// The label as it was written.
+    pub from: String,
+    pub to: String,
+}
+
+/// Read a manifest, failing if any label in it isn't absolute.
+pub fn read_manifest(path: &Path) -> io::Result<DepsManifest> {
+    let content = fs::read_to_string(path)?;
+    let manifest: DepsManifest = toml::from_str(&content).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), err),
+        )
+    })?;
+
+    for rename in &manifest.renames {
+        for label in [&rename.from, &rename.to] {
+            if !label.starts_with("//") && !label.starts_with('@') {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{}: {:?} isn't an absolute label (//package:target)",
+                        path.display(),
+                        label
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(manifest)
+}
+
+/// Replace the labels of `renames` in the `load()` statements and the `deps`
+/// and `data` attributes of `content`, a BUILD file of `package`. Returns the
+/// new content and the line, old and new label of each replacement. Renames
+/// whose `from` can't be resolved match nothing; [`update_deps`] rejects them.
+pub fn rename_labels(
+    content: &str,
+    package: &str,
+    workspace_root: &Path,
+    renames: &[LabelRename],
+) -> (String, Vec<(usize, String, String)>) {
+    let resolved: Vec<(AbsoluteLabel, &str)> = renames
+        .iter()
+        .filter_map(|rename| {
+            let from = resolve_label(&rename.from, "", workspace_root).ok()?;
+            Some((from, rename.to.as_str()))
+        })
+        .collect();
+
+    let tokens = tokenize(content);
+    let mut strings = Vec::new();
+    for call in top_level_calls(&tokens) {
+        if call.name == "load" {
+            strings.push(call.open + 1);
+            continue;
+        }
+        for argument in call.arguments(&tokens) {
+            if argument
+                .key
+                .is_some_and(|key| RENAMED_ATTRIBUTES.contains(&key))
+            {
+                strings.extend(argument.value);
+            }
+        }
+    }
+
+    let mut fixed = content.to_string();
+    let mut replaced = Vec::new();
+    for index in strings.into_iter().rev() {
+        let token = &tokens[index];
+        let Some(label) = token.string_value() else {
+            continue;
+        };
+        let Ok(absolute) = resolve_label(&label, package, workspace_root) else {
+            continue;
+        };
+        if let Some((_, to)) = resolved.iter().find(|(from, _)| *from == absolute) {
+            fixed.replace_range(token.start..token.end(), &quote(to));
+            replaced.push((token.line, label, to.to_string()));
+        }
+    }
+    replaced.reverse();
+
+    (fixed, replaced)
+}
+
+/// Apply `renames` to every BUILD file the config discovers, writing the
+/// files back unless `dry_run` is set. Returns every replacement made, in
+/// file order. Fails before touching any file if a label can't be resolved.
+pub fn update_deps(
+    config: &Config,
+    renames: &[LabelRename],
+    dry_run: bool,
+) -> io::Result<Vec<Replacement>> {
+    let root = config.root_dir.as_path();
+    let errors: Vec<String> = renames
+        .iter()
+        .flat_map(|rename| [&rename.from, &rename.to])
+        .filter_map(|label| resolve_label(label, "", root).err())
+        .map(|err| err.to_string())
+        .collect();
+    if !errors.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("can't rename unresolvable labels: {}", errors.join("; ")),
+        ));
+    }
+
+    let mut replacements = Vec::new();
+    for path in find_build_files(config)? {
+        if path.file_name().is_some_and(is_workspace_file) {
+            continue;
+        }
+        let content = fs::read_to_string(&path)?;
+        let package = relative_path(root, path.parent().unwrap_or(root));
+        let (fixed, replaced) = rename_labels(&content, &package, root, renames);
+        if replaced.is_empty() {
+            continue;
+        }
+        if !dry_run {
+            atomic_write(&path, fixed.as_bytes())?;
+        }
+        replacements.extend(replaced.into_iter().map(|(line, from, to)| Replacement {
+            path: path.clone(),
+            line,
+            from,
+            to,
+        }));
+    }
+    Ok(replacements)
+}
@@ -0,0 +1,56 @@
+//! Rule attributes that newer rules_swift versions have removed or renamed.
+
+/// `(rule, attribute, rules_swift version that removed it, replacement)`.
+/// `<value>` in the replacement stands for each string in the attribute's
+/// list value.
+pub const DEPRECATED_ATTRS: &[(&str, &str, &str, &str)] = &[
+    (
+        "swift_library",
+        "defines",
+        "1.0",
+        r#"copts = ["-D<value>"]"#,
+    ),
+    ("swift_binary", "defines", "1.0", r#"copts = ["-D<value>"]"#),
+    ("swift_test", "defines", "1.0", r#"copts = ["-D<value>"]"#),
+];
+
+/// The entry for `attribute` of `rule`, as `(since, replacement)`.
+pub fn deprecation(rule: &str, attribute: &str) -> Option<(&'static str, &'static str)> {
+    DEPRECATED_ATTRS
+        .iter()
+        .find(|(deprecated_rule, deprecated_attr, _, _)| {
+            *deprecated_rule == rule && *deprecated_attr == attribute
+        })
+        .map(|&(_, _, since, replacement)| (since, replacement))
+}
+
+/// `replacement` with its quoted `<value>` element repeated for each of
+/// `values`, e.g. `copts = ["-DDEBUG", "-DTRACE"]`. Without values, or a
+/// placeholder, the replacement is returned as is.
+pub fn expand_replacement(replacement: &str, values: &[String]) -> String {
+    let Some(placeholder) = replacement.find("<value>") else {
+        return replacement.to_string();
+    };
+    let start = replacement[..placeholder].rfind('"');
+    let end = replacement[placeholder..]
+        .find('"')
+        .map(|end| placeholder + end + 1);
+    let (Some(start), Some(end)) = (start, end) else {
+        return replacement.to_string();
+    };
+    if values.is_empty() {
+        return replacement.to_string();
+    }
+
+    let element = &replacement[start..end];
+    let elements: Vec<String> = values
+        .iter()
+        .map(|value| element.replace("<value>", value))
+        .collect();
+    format!(
+        "{}{}{}",
+        &replacement[..start],
+        elements.join(", "),
+        &replacement[end..]
+    )
+}
@@ -0,0 +1,121 @@
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::bazelignore::{is_ignored, load_bazelignore};
+use crate::config::Config;
+use crate::git::{changed_files, changed_since_tag};
+use crate::glob::glob_match;
+
+/// File names Bazel reads as a WORKSPACE file.
+pub const WORKSPACE_FILE_NAMES: &[&str] = &["WORKSPACE", "WORKSPACE.bazel"];
+
+/// Whether `file_name` is a WORKSPACE file.
+pub fn is_workspace_file(file_name: &OsStr) -> bool {
+    WORKSPACE_FILE_NAMES.iter().any(|name| file_name == *name)
+}
+
+/// BUILD file names that make a directory a package of its own.
+pub const BUILD_FILE_NAMES: &[&str] = &["BUILD.bazel", "BUILD"];
+
+/// Whether `dir` is a package: globs and source listings of the packages
+/// above it stop there.
+pub fn is_package(dir: &Path) -> bool {
+    BUILD_FILE_NAMES.iter().any(|name| dir.join(name).is_file())
+}
+
+// Find all BUILD.bazel and WORKSPACE files under the root directory that pass
+// the configured include/exclude patterns (and, if set, differ from the git
+// ref or changed since the git tag). Directories listed in .bazelignore are
+// skipped. A max depth of 1 only looks at the root directory itself. With a
+// target label, only the BUILD file defining it is returned.
+pub fn find_build_files(config: &Config) -> io::Result<Vec<PathBuf>> {
+    if let Some(target) = &config.target {
+        return Ok(vec![target.build_file(&config.root_dir)?]);
+    }
+
+    let ignored = load_bazelignore(&config.root_dir);
+    let mut files = walk(&config.root_dir, config.max_depth, &ignored, |name| {
+        name == "BUILD.bazel" || is_workspace_file(name)
+    })?;
+
+    files.retain(|path| is_selected(config, path));
+
+    if let Some(git_ref) = &config.git_changed_only {
+        let changed = changed_files(&config.root_dir, git_ref)?;
+        files.retain(|path| changed.contains(path));
+    }
+    if let Some(tag) = &config.since_tag {
+        let changed = changed_since_tag(&config.root_dir, tag)?;
+        files.retain(|path| changed.contains(path));
+    }
+
+    Ok(files)
+}
+
+// Find the BUILD.bazel and WORKSPACE files of the whole workspace, for the
+// checks that look across packages: the run's exclude patterns and
+// .bazelignore still apply, but not the target, include patterns, depth or
+// git filters that narrow down which files the run processes.
+pub fn find_workspace_build_files(config: &Config) -> io::Result<Vec<PathBuf>> {
+    let mut workspace = Config::new(&config.root_dir);
+    workspace.exclude_patterns = config.exclude_patterns.clone();
+    find_build_files(&workspace)
+}
+
+// Find all WORKSPACE and WORKSPACE.bazel files under `root`, outside the
+// directories listed in .bazelignore
+pub fn find_workspace_files(root: &Path) -> io::Result<Vec<PathBuf>> {
+    walk(root, None, &load_bazelignore(root), is_workspace_file)
+}
+
+// Files under `root` whose name passes `wanted`, in file name order. The
+// directories matching an `ignored` entry (relative to `root`) are skipped.
+pub(crate) fn walk(
+    root: &Path,
+    max_depth: Option<usize>,
+    ignored: &[String],
+    wanted: impl Fn(&OsStr) -> bool,
+) -> io::Result<Vec<PathBuf>> {
+    let mut walker = WalkDir::new(root).sort_by_file_name();
+    if let Some(max_depth) = max_depth {
+        walker = walker.max_depth(max_depth);
+    }
+    let walker = walker.into_iter().filter_entry(|entry| {
+        if ignored.is_empty() || entry.depth() == 0 || !entry.file_type().is_dir() {
+            return true;
+        }
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        !is_ignored(&relative.to_string_lossy().replace('\\', "/"), ignored)
+    });
+
+    let mut files = Vec::new();
+    for entry in walker {
+        let entry = entry.map_err(io::Error::other)?;
+        if entry.file_type().is_file() && wanted(entry.file_name()) {
+            files.push(entry.into_path());
+        }
+    }
+    Ok(files)
+}
+
+// Whether a path is matched by an include pattern (if any are set) and by no
+// exclude pattern. Patterns are matched against the path relative to the root.
+fn is_selected(config: &Config, path: &Path) -> bool {
+    let relative = path.strip_prefix(&config.root_dir).unwrap_or(path);
+    let relative = relative.to_string_lossy().replace('\\', "/");
+
+    let included = config.include_patterns.is_empty()
+        || config
+            .include_patterns
+            .iter()
+            .any(|pattern| glob_match(pattern, &relative));
+    let excluded = config
+        .exclude_patterns
+        .iter()
+        .any(|pattern| glob_match(pattern, &relative));
+
+    included && !excluded
+}
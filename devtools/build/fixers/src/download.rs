@@ -0,0 +1,55 @@
+//! Downloads for checks that need the network (only with `--network` or
+//! `--check-sha256`).
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+
+/// Seconds a download may take unless `--network-timeout` says otherwise.
+pub const DEFAULT_NETWORK_TIMEOUT_SECS: u64 = 60;
+
+/// Download `url`, giving up after `timeout`, and return the hex SHA-256 of
+/// its contents.
+pub fn sha256_of_url(url: &str, timeout: Duration) -> io::Result<String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(io::Error::other)?;
+    let body = client
+        .get(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.bytes())
+        .map_err(|err| io::Error::other(format!("downloading {}: {}", url, err)))?;
+    Ok(format!("{:x}", Sha256::digest(&body)))
+}
+
+/// The SHA-256 of each URL downloaded so far in a run, or why it failed, so
+/// an archive is only downloaded once however often its WORKSPACE file is
+/// analyzed. Clones share the same results.
+#[derive(Debug, Clone, Default)]
+pub struct DownloadCache {
+    results: Arc<Mutex<HashMap<String, Result<String, String>>>>,
+}
+
+impl DownloadCache {
+    /// [`sha256_of_url`], downloading `url` only the first time it is asked for.
+    pub fn sha256_of_url(&self, url: &str, timeout: Duration) -> io::Result<String> {
+        let cached = self.results.lock().unwrap().get(url).cloned();
+        let result = match cached {
+            Some(result) => result,
+            None => {
+                let result = sha256_of_url(url, timeout).map_err(|err| err.to_string());
+                self.results
+                    .lock()
+                    .unwrap()
+                    .insert(url.to_string(), result.clone());
+                result
+            }
+        };
+        result.map_err(io::Error::other)
+    }
+}
@@ -0,0 +1,202 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::atomic_write::atomic_write;
+use crate::baseline::relative_path;
+use crate::build_validator::validate_starlark_syntax;
+use crate::cache::{content_hash, Cache};
+use crate::checks::{analyze_build_file_at, apply_fixes_in_scope, fix_issue_in_scope};
+use crate::config::Config;
+use crate::issue::{BuildIssue, Finding, IssueReport};
+use crate::metrics::{self, PhaseTimer};
+use crate::patch::{unified_diff, VERBOSE_DIFF_CONTEXT};
+use crate::undo::create_backup;
+
+// Fix a single BUILD.bazel file, writing it back only if the run mode allows it
+pub fn fix_build_file(file_path: &Path, config: &Config) -> io::Result<IssueReport> {
+    fix_file(file_path, config, None)
+}
+
+// Like `fix_build_file`, but take the findings from `cache` if the file and
+// its package are unchanged since they were cached, and cache them otherwise
+pub fn fix_build_file_with_cache(
+    file_path: &Path,
+    config: &Config,
+    cache: &mut Cache,
+) -> io::Result<IssueReport> {
+    fix_file(file_path, config, Some(cache))
+}
+
+// Like `fix_build_file_with_cache`, for a file whose content the caller has
+// already read (as `--parallel-io` does)
+pub fn fix_build_file_content(
+    file_path: &Path,
+    content: String,
+    config: &Config,
+    cache: Option<&mut Cache>,
+) -> io::Result<IssueReport> {
+    fix_content(file_path, content, config, cache)
+}
+
+fn fix_file(
+    file_path: &Path,
+    config: &Config,
+    cache: Option<&mut Cache>,
+) -> io::Result<IssueReport> {
+    let content = fs::read_to_string(file_path)?;
+    fix_content(file_path, content, config, cache)
+}
+
+fn fix_content(
+    file_path: &Path,
+    content: String,
+    config: &Config,
+    cache: Option<&mut Cache>,
+) -> io::Result<IssueReport> {
+    // Fixing a malformed file could only make it worse, so it is only
+    // reported, leaving the other files of the run to be fixed
+    if let Err(err) = validate_starlark_syntax(&content) {
+        let issue = BuildIssue::InvalidSyntax {
+            line: err.line,
+            column: err.column,
+        };
+        return Ok(IssueReport {
+            path: file_path.to_path_buf(),
+            findings: vec![Finding::from((issue, err.to_string()))],
+            modified: false,
+            diff: None,
+            fix_diffs: Vec::new(),
+        });
+    }
+
+    let relative = relative_path(&config.root_dir, file_path);
+    let mut cache = cache.map(|cache| (cache, content_hash(&content, file_path.parent())));
+    let cached = match &mut cache {
+        Some((cache, sha256)) => cache.get(&relative, sha256),
+        None => None,
+    };
+    let findings = match cached {
+        Some(findings) => findings,
+        None => {
+            let findings = {
+                let _timer = PhaseTimer::start(metrics::ANALYZE);
+                analyze_build_file_at(&content, file_path, config)
+            };
+            if let Some((cache, sha256)) = cache {
+                cache.insert(relative.clone(), sha256, findings.clone());
+            }
+            findings
+        }
+    };
+
+    // Baselined issues are left as they are; verification still sees them all
+    let all_findings = findings;
+    let findings: Vec<Finding> = match &config.baseline {
+        Some(baseline) => all_findings
+            .iter()
+            .filter(|finding| !baseline.contains(&relative, &content, finding))
+            .cloned()
+            .collect(),
+        None => all_findings.clone(),
+    };
+
+    let fix_timer = PhaseTimer::start(metrics::FIX);
+    let relative_to_root = file_path
+        .strip_prefix(&config.root_dir)
+        .unwrap_or(file_path);
+    let (new_content, fix_diffs) = if config.verbose_diffs {
+        apply_fixes_with_diffs(relative_to_root, &content, &findings, config)
+    } else {
+        (
+            apply_fixes_in_scope(&content, &findings, config),
+            Vec::new(),
+        )
+    };
+    let modified = new_content != content;
+
+    if modified && config.writes_files() {
+        if config.backup {
+            create_backup(file_path)?;
+        }
+        atomic_write(file_path, new_content.as_bytes())?;
+    }
+
+    let diff = (modified && config.wants_diffs()).then(|| {
+        unified_diff(
+            relative_to_root,
+            &content,
+            &new_content,
+            config.diff_context,
+        )
+    });
+
+    drop(fix_timer);
+
+    if modified && config.verify_idempotent {
+        let _timer = PhaseTimer::start(metrics::VERIFY);
+        let fixed = if config.writes_files() {
+            fs::read_to_string(file_path)?
+        } else {
+            new_content
+        };
+        verify_idempotent(file_path, &all_findings, &fixed, config)?;
+    }
+
+    Ok(IssueReport {
+        path: file_path.to_path_buf(),
+        findings,
+        modified,
+        diff,
+        fix_diffs,
+    })
+}
+
+// Like `apply_fixes_in_scope`, also returning the diff of each fix that
+// changed the content, scoped to the lines it touched
+fn apply_fixes_with_diffs(
+    relative_path: &Path,
+    content: &str,
+    findings: &[Finding],
+    config: &Config,
+) -> (String, Vec<(Finding, String)>) {
+    let mut content = content.to_string();
+    let mut diffs = Vec::new();
+    for finding in findings {
+        let fixed = fix_issue_in_scope(&finding.issue, &content, config);
+        if fixed != content {
+            let diff = unified_diff(relative_path, &content, &fixed, VERBOSE_DIFF_CONTEXT);
+            diffs.push((finding.clone(), diff));
+            content = fixed;
+        }
+    }
+    (content, diffs)
+}
+
+// Re-analyze fixed content and fail if it has issues the original didn't,
+// which means one of the fixes is broken
+pub fn verify_idempotent(
+    file_path: &Path,
+    original: &[Finding],
+    fixed: &str,
+    config: &Config,
+) -> io::Result<()> {
+    let introduced: Vec<String> = analyze_build_file_at(fixed, file_path, config)
+        .into_iter()
+        .filter(|finding| !original.iter().any(|before| before.issue == finding.issue))
+        .map(|finding| finding.to_string())
+        .collect();
+
+    if introduced.is_empty() {
+        return Ok(());
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "fixing {} introduced new issues: {}",
+            file_path.display(),
+            introduced.join("; ")
+        ),
+    ))
+}
@@ -0,0 +1,51 @@
+//! Generation of skeleton BUILD.bazel files for new Swift modules.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::sources::{collect_swift_files, determine_best_glob_pattern};
+
+const DEFAULT_TEMPLATE: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "{{name}}",
+    srcs = glob(
+        ["{{glob}}"],
+        allow_empty = True,
+    ),
+    # deps = [
+    #     "//Sources/Example",
+    # ],
+    visibility = ["//visibility:public"],
+)
+"#;
+
+// Produce BUILD.bazel content for `dir`, optionally from a template file
+// using `{{name}}` and `{{glob}}` placeholders
+pub fn generate_build_file(dir: &Path, template: Option<&Path>) -> io::Result<String> {
+    let name = dir
+        .canonicalize()?
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("cannot infer a target name from {}", dir.display()),
+            )
+        })?;
+
+    let swift_files = collect_swift_files(dir)?;
+    let glob = determine_best_glob_pattern(&swift_files);
+
+    let template = match template {
+        Some(path) => fs::read_to_string(path)?,
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+
+    Ok(template
+        .replace("{{name}}", &name)
+        .replace("{{glob}}", &glob))
+}
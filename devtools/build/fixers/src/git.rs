@@ -0,0 +1,62 @@
+//! Thin wrappers around the `git` command line.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Run git in `dir` and return its trimmed stdout, or an error with its stderr
+pub fn git(dir: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Files under `dir` that differ from `git_ref` in the working tree (staged or
+// not), as paths joined onto `dir`
+pub fn changed_files(dir: &Path, git_ref: &str) -> io::Result<Vec<PathBuf>> {
+    let output = git(dir, &["diff", "--name-only", "--relative", git_ref, "--"])?;
+    Ok(output.lines().map(|line| dir.join(line)).collect())
+}
+
+// How many of the latest tags the error for an unknown tag lists
+const SUGGESTED_TAG_COUNT: usize = 5;
+
+// Files under `dir` changed in the commits since `tag` (`git diff
+// TAG...HEAD`), as paths joined onto `dir`. An unknown tag is an error that
+// lists the latest tags.
+pub fn changed_since_tag(dir: &Path, tag: &str) -> io::Result<Vec<PathBuf>> {
+    let tag_ref = format!("refs/tags/{}", tag);
+    if git(dir, &["rev-parse", "--verify", "--quiet", &tag_ref]).is_err() {
+        let tags = git(dir, &["tag", "--list", "--sort=-creatordate"])?;
+        let latest: Vec<&str> = tags.lines().take(SUGGESTED_TAG_COUNT).collect();
+        let hint = if latest.is_empty() {
+            "the repository has no tags".to_string()
+        } else {
+            format!("the latest tags are {}", latest.join(", "))
+        };
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no git tag {:?} ({})", tag, hint),
+        ));
+    }
+
+    let range = format!("{}...HEAD", tag_ref);
+    let output = git(dir, &["diff", "--name-only", "--relative", &range, "--"])?;
+    Ok(output.lines().map(|line| dir.join(line)).collect())
+}
+
+// The directory git runs hooks from, honouring core.hooksPath and worktrees
+pub fn hooks_dir(repo: &Path) -> io::Result<PathBuf> {
+    let path = PathBuf::from(git(repo, &["rev-parse", "--git-path", "hooks"])?);
+    Ok(if path.is_absolute() {
+        path
+    } else {
+        repo.join(path)
+    })
+}
@@ -0,0 +1,90 @@
+//! Evaluation of `glob()` against a package directory, following Bazel's
+//! semantics rather than the simplified matching of [`crate::glob`]:
+//!
+//! - `*` and `?` match within a single path segment; a `**` segment matches
+//!   any number of segments, but at least one when it ends the pattern, so
+//!   `dir/**` doesn't match `dir` itself.
+//! - Subdirectories with their own BUILD file are separate packages and are
+//!   never matched into.
+//! - Directories are only matched with `exclude_directories = False`.
+//! - Results are relative to the package, sorted and without duplicates.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::discovery::is_package;
+
+/// Whether the package-relative, `/`-separated `path` matches `pattern`.
+pub fn bazel_glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    !path.is_empty() && match_segments(&pattern, &path)
+}
+
+/// The files (and, unless `exclude_directories`, directories) under `base`
+/// matching any of `patterns` and none of `excludes`.
+pub fn evaluate_glob(
+    base: &Path,
+    patterns: &[String],
+    excludes: &[String],
+    exclude_directories: bool,
+) -> io::Result<Vec<PathBuf>> {
+    let walker = WalkDir::new(base)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| !(entry.file_type().is_dir() && is_package(entry.path())));
+
+    let mut matches = Vec::new();
+    for entry in walker {
+        let entry = entry.map_err(io::Error::other)?;
+        if exclude_directories && entry.file_type().is_dir() {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(base)
+            .map_err(io::Error::other)?
+            .to_string_lossy()
+            .replace('\\', "/");
+        if patterns
+            .iter()
+            .any(|pattern| bazel_glob_match(pattern, &relative))
+            && !excludes
+                .iter()
+                .any(|pattern| bazel_glob_match(pattern, &relative))
+        {
+            matches.push(relative);
+        }
+    }
+
+    matches.sort();
+    matches.dedup();
+    Ok(matches.into_iter().map(PathBuf::from).collect())
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        // A trailing `**` needs a segment to match
+        Some((&"**", [])) => !path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                match_segment(segment.as_bytes(), name.as_bytes())
+                    && match_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn match_segment(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| match_segment(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && match_segment(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && match_segment(rest, &name[1..]),
+    }
+}
@@ -0,0 +1,112 @@
+//! Installation of a git pre-commit hook that runs `umbra-fix --check`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::atomic_write::atomic_write;
+use crate::git::hooks_dir;
+
+/// First line of the block the installer adds to the hook script.
+pub const HOOK_BEGIN: &str = "# >>> umbra-fix pre-commit >>>";
+/// Last line of the block the installer adds to the hook script.
+pub const HOOK_END: &str = "# <<< umbra-fix pre-commit <<<";
+
+const SHEBANG: &str = "#!/bin/sh\n";
+
+/// The lines added to the pre-commit hook, running the binary at `exe`.
+pub fn hook_block(exe: &Path) -> String {
+    format!(
+        "{}\n# Added by `umbra-fix install-hook`; remove with `umbra-fix uninstall-hook`.\n{} --check --git-changed-only HEAD || exit 1\n{}\n",
+        HOOK_BEGIN,
+        shell_quote(&exe.display().to_string()),
+        HOOK_END
+    )
+}
+
+// Quote a word for /bin/sh. Nothing is special inside single quotes, so
+// only a single quote itself has to be closed, escaped and reopened.
+fn shell_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+// Write the hook block into the repository's pre-commit hook, appending to
+// an existing hook instead of replacing it. Returns the hook path, or an
+// AlreadyExists error if the block is already there.
+pub fn install_hook(repo: &Path, exe: &Path) -> io::Result<PathBuf> {
+    let hooks = hooks_dir(repo)?;
+    fs::create_dir_all(&hooks)?;
+    let hook = hooks.join("pre-commit");
+
+    let existing = match fs::read_to_string(&hook) {
+        Ok(content) => Some(content),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err),
+    };
+
+    let content = match existing {
+        Some(content) if content.contains(HOOK_BEGIN) => {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already runs umbra-fix", hook.display()),
+            ));
+        }
+        Some(mut content) => {
+            if !content.ends_with('\n') {
+                content.push('\n');
+            }
+            format!("{}\n{}", content, hook_block(exe))
+        }
+        None => format!("{}\n{}", SHEBANG, hook_block(exe)),
+    };
+
+    atomic_write(&hook, content.as_bytes())?;
+    make_executable(&hook)?;
+    Ok(hook)
+}
+
+// Remove the hook block. The hook file is deleted if nothing else is left
+// in it. Returns whether a block was found.
+pub fn uninstall_hook(repo: &Path) -> io::Result<bool> {
+    let hook = hooks_dir(repo)?.join("pre-commit");
+    let content = match fs::read_to_string(&hook) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err),
+    };
+
+    let (Some(begin), Some(end)) = (content.find(HOOK_BEGIN), content.find(HOOK_END)) else {
+        return Ok(false);
+    };
+    let end = content[end..]
+        .find('\n')
+        .map_or(content.len(), |i| end + i + 1);
+    // Also drop the blank line the installer put before the block
+    let begin = if content[..begin].ends_with("\n\n") {
+        begin - 1
+    } else {
+        begin
+    };
+
+    let remaining = format!("{}{}", &content[..begin], &content[end..]);
+    if remaining.trim().is_empty() || remaining == SHEBANG {
+        fs::remove_file(&hook)?;
+    } else {
+        atomic_write(&hook, remaining.as_bytes())?;
+    }
+    Ok(true)
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(path, permissions)
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,521 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// A problem detected in a BUILD.bazel (or WORKSPACE) file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "name")]
+pub enum BuildIssue {
+    /// `swift_library` is called without loading it from rules_swift.
+    MissingSwiftLibraryLoad,
+    /// The legacy `umbra_swift_library` macro is used instead of `swift_library`.
+    CustomLibraryRule,
+    /// A rule sets the `exports` attribute, which rules_swift does not support.
+    ExportsAttribute,
+    /// A `glob()` call does not set `allow_empty = True`.
+    GlobWithoutAllowEmpty,
+    /// A `swift_library` has no `srcs` attribute, or its `srcs` match no
+    /// Swift files in the package.
+    EmptySrcs { target: String, has_srcs: bool },
+    /// The file does not end with a newline character.
+    MissingTrailingNewline,
+    /// The file uses CRLF (or bare CR) line endings instead of LF.
+    CrlfLineEnding,
+    /// One or more lines end with spaces or tabs.
+    TrailingWhitespace,
+    /// String literals use single quotes instead of the project's double quotes.
+    InconsistentQuoteStyle,
+    /// The string elements of a list attribute (`deps` by default) are not sorted.
+    UnsortedDeps { attribute: String },
+    /// A swift_library relies on the default module name, but module_name_map.toml
+    /// says it should expose a different one.
+    MissingModuleName { target: String, module_name: String },
+    /// An Apple bundling rule (`ios_application`, `watchos_extension`, ...) has
+    /// no `minimum_os_version`.
+    MissingMinimumOsVersion { target: String, version: String },
+    /// An Apple bundling rule has no `bundle_id`, or an empty one.
+    MissingBundleId { target: String, bundle_id: String },
+    /// The file is empty or contains only whitespace.
+    EmptyBuildFile,
+    /// The file has rules but no `package()` call.
+    MissingPackageDeclaration,
+    /// The file has no `licenses([license])` call, or declares another
+    /// license type (only checked with `require_license` in umbra-fix.toml).
+    MissingLicense { license: String },
+    /// A function is called that no `load()` imports and that isn't a Bazel
+    /// built-in, so Bazel fails to load the package.
+    UndeclaredLoad { symbol: String },
+    /// A macro of `macro_registry.toml` is called without loading it from
+    /// `bzl`, the file the registry says defines it.
+    MacroCallWithoutLoad { symbol: String, bzl: String },
+    /// A `swift_test` doesn't set `testonly = True`, so production targets can depend on it.
+    MissingTestonly,
+    /// A rule sets an attribute that rules_swift removed in version `since`.
+    /// The fix removes it and leaves a comment suggesting `replacement`.
+    DeprecatedAttribute {
+        target: String,
+        attribute: String,
+        since: String,
+        replacement: String,
+    },
+    /// An `umbra_*` macro call sets an attribute that only the macro accepts,
+    /// which the rules_swift `rule` replacing it would reject. The fix removes
+    /// it and leaves a comment.
+    UnsupportedAttribute {
+        target: String,
+        attribute: String,
+        rule: String,
+    },
+    /// The sources of a `swift_library` use language features (such as
+    /// concurrency) whose checking `setting` would enable, but its
+    /// `swift_settings` don't set it.
+    MissingSwiftSetting { target: String, setting: String },
+    /// A `swift_binary` isn't dead-stripped: it has no `strip_swift_symbols`
+    /// and no `-dead_strip` in `linkopts` (only checked with
+    /// `require_dead_strip` in umbra-fix.toml).
+    MissingStrip { target: String },
+    /// A `swift_library` that isn't `testonly` has test sources (`*Tests.swift`
+    /// or `*Spec.swift`) in its `srcs`. They belong in a separate `swift_test`,
+    /// which is left to a manual edit.
+    TestFilesInLibrary { target: String, files: Vec<String> },
+    /// A `swift_library` sets `generated_header_name` without `generates_header = True`.
+    MissingGeneratesHeader,
+    /// A `swift_library` sets `generated_header_name` but also `generates_header = False`.
+    /// Which one is intended can't be decided automatically.
+    GeneratesHeaderConflict,
+    /// A `visibility` value is a bare string rather than a list, which Bazel rejects.
+    IncorrectVisibilityFormat,
+    /// A `swift_library` passes an absolute `-I/...` include path in `copts`,
+    /// which breaks hermetic builds.
+    AbsoluteIncludePath,
+    /// A string literal holds an absolute path into a developer's machine
+    /// (`/Users/...`, `/home/...`, `/root/...` or `C:\...`). The fix replaces
+    /// it with `replacement`, based on the configured project path variable.
+    HardcodedProjectPath { path: String, replacement: String },
+    /// A `load()` of a bzl file that has moved, per the rule migrations.
+    LegacyRuleLoad { from: String, to: String },
+    /// The package's only `swift_library` isn't named after the package
+    /// directory (only checked with `check_target_names` in umbra-fix.toml).
+    /// `package` is the package path, which the rename resolves labels against.
+    InconsistentTargetName {
+        target: String,
+        expected: String,
+        package: String,
+    },
+    /// A target's name doesn't match the pattern `naming_convention.toml`
+    /// sets for its rule. Fixable when the PascalCase form of the name,
+    /// `renamed`, matches and no other target of the file has it.
+    NamingConventionViolation {
+        target: String,
+        rule_name: String,
+        renamed: Option<String>,
+    },
+    /// A `swift_library` builds Swift module `module_name`, as do the
+    /// `conflicts` (labels of other `swift_library` targets), so only one of
+    /// them can be in a build. Renaming needs a manual decision.
+    ConflictingModuleNames {
+        target: String,
+        module_name: String,
+        conflicts: Vec<String>,
+    },
+    /// A `swift_library` depends on an `objc_library` of the same file
+    /// (`dependency`) without enabling `swift.objc_interop` in `features`.
+    IncompatibleDependency { target: String, dependency: String },
+    /// A `deps` label whose module none of the target's sources import
+    /// (only checked with `--prune-deps`).
+    UnusedDependency { target: String, label: String },
+    /// A `deps` label (as written) that resolves to the same target as an
+    /// earlier entry, `first`, of the list. The fix removes it.
+    RepeatedDep {
+        target: String,
+        label: String,
+        first: String,
+    },
+    /// A rule sets `deps = []`, which is the default. The fix removes it.
+    EmptyDepsAttribute { target: String },
+    /// A `deps` label under an old `//third_party/...` package that
+    /// `external_label_rewrites.toml` maps to an external repository. The
+    /// fix replaces it with `replacement`.
+    AbsoluteExternalLabel {
+        target: String,
+        label: String,
+        replacement: String,
+    },
+    /// A recursive `**/*.swift` glob also matches the sources of a nested
+    /// package (a subdirectory with its own BUILD.bazel) that it doesn't exclude.
+    WildcardGlob { pattern: String, subpackage: String },
+    /// A `glob()` with the `include` patterns sets `allow_empty = True` but
+    /// matches files of the package, so it can't be empty (only checked with
+    /// `warn_redundant_allow_empty` in umbra-fix.toml).
+    RedundantAllowEmpty { include: Vec<String> },
+    /// A `select()` on `line` inside a `glob()`, which Bazel rejects: globs
+    /// are expanded while the package loads, before select() is resolved.
+    /// Needs a manual rewrite; `target` is the enclosing rule, if named.
+    SelectInGlob { line: usize, target: Option<String> },
+    /// A `select()` on `line` has no `//conditions:default` branch nor a
+    /// `no_match_error`, so the configurations it doesn't list fail to
+    /// build. `target` is the enclosing rule, if named.
+    SelectWithoutDefault { line: usize, target: Option<String> },
+    /// `target` lists `dependency`, a target of another package, in its
+    /// `deps`, but the dependency's visibility doesn't include the package.
+    PrivateVisibilityLeak { target: String, dependency: String },
+    /// A swift_library whose attributes don't fit its schema (a missing
+    /// `name`, or a value of the wrong type), so the checks that read
+    /// libraries through the schema skip it. `target` is its name, if it has
+    /// a string one.
+    InvalidSwiftLibrary {
+        target: Option<String>,
+        errors: Vec<String>,
+    },
+    /// A block of comments starting on `line` that holds a commented-out
+    /// rule. Needs a manual decision; `umbra-fix --remove-comment-block`
+    /// deletes it.
+    CommentedOutRule { line: usize },
+    /// The file isn't valid Starlark (an unclosed bracket or string, or an
+    /// invalid escape), so it is reported but left unfixed.
+    InvalidSyntax { line: usize, column: usize },
+    /// A `select()` of `target` has a condition, `label`, that isn't a
+    /// `config_setting` or `constraint_value` of the workspace.
+    UnknownConfigSetting { target: String, label: String },
+    /// Glob include `patterns` that match build outputs such as `*.o` or
+    /// `*.so`. If `removable`, the fix removes them, leaving a comment above
+    /// the glob. References to vendored prebuilt binaries (`*.a`, `*.dylib`)
+    /// and patterns that are all a glob includes need a manual edit.
+    NonHermeticGlob {
+        patterns: Vec<String>,
+        removable: bool,
+    },
+    /// A `glob()` with the `include` patterns has no `exclude` although it
+    /// matches generated sources (per `generated_file_patterns`). The fix
+    /// excludes them with the `exclude` patterns.
+    GeneratedSourcesInGlob {
+        include: Vec<String>,
+        exclude: Vec<String>,
+    },
+    /// A `srcs` glob `pattern` of a Swift rule has no extension filter and
+    /// matches `files` that aren't Swift sources, such as Objective-C files.
+    /// The fix narrows the pattern to `*.swift` files.
+    MixedSourceLanguages {
+        target: String,
+        pattern: String,
+        files: Vec<String>,
+    },
+    /// A Swift file in the package is not matched by the `srcs` of any target.
+    OrphanedSourceFile { file: String },
+    /// The directory also has a `Package.swift`, so SPM and Bazel may build it
+    /// differently. Reported but not fixed.
+    DualBuildSystem,
+    /// A `swift_library` has no `data` attribute although its package contains
+    /// resources (`.xcassets`, `.strings`, `.json`, `.plist` or `.lproj`).
+    MissingDataAttribute { target: String },
+    /// A Swift target lists asset catalogs or `.lproj` directories
+    /// (`resources`, as written) in `data` instead of bundling them. The fix
+    /// moves them into an `apple_resource_bundle` named `bundle`; `None` when
+    /// a target already has that name.
+    UnbundledResources {
+        target: String,
+        resources: Vec<String>,
+        bundle: Option<String>,
+    },
+    /// A step of the rules_swift major version upgrade requested with
+    /// `--upgrade-rules-swift-version` applies to the file.
+    RulesSwiftMigration { step: String },
+    /// Rule attributes are not in canonical order (only checked with `--sort-attributes`).
+    UnorderedAttributes,
+    /// The layout differs from what the formatter produces (only checked with `--format`).
+    NonCanonicalFormat,
+    /// Line `line` is longer than `max_line_length`. It holds the value of
+    /// `attribute` of `target`, which the fix can only split when it is a
+    /// single-line `deps` or `srcs` list (`splittable`).
+    LineTooLong {
+        line: usize,
+        target: Option<String>,
+        attribute: Option<String>,
+        splittable: bool,
+    },
+    /// A `# umbra-fix: disable=` or `disable-file=` comment on `line`
+    /// suppresses the `issue`, but it isn't reported where the comment applies.
+    UnusedSuppression { issue: String, line: usize },
+    /// A problem in a WORKSPACE file.
+    #[serde(untagged)]
+    Workspace(WorkspaceIssue),
+}
+
+/// A problem detected in a `WORKSPACE` or `WORKSPACE.bazel` file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "name")]
+pub enum WorkspaceIssue {
+    /// A repository rule is called without loading it from `@bazel_tools`.
+    MissingLoad { rule: String, bzl: String },
+    /// A repository rule that Bazel has removed in favour of another one.
+    OutdatedRepositoryRule { rule: String, replacement: String },
+    /// An `http_archive` sets neither or both of `url` and `urls`.
+    IncorrectHttpArchive { repository: String },
+    /// An `http_archive` has no `sha256`. `sha256` holds the computed hash
+    /// when the archive was downloaded (`--network`).
+    MissingSha256 {
+        repository: String,
+        url: String,
+        sha256: Option<String>,
+    },
+    /// An `http_archive`'s `sha256` differs from the `computed` hash of the
+    /// archive at `url` (only checked with `--check-sha256`). The fix, which
+    /// replaces it, only applies when `update` is set by `--fix-sha256`.
+    IncorrectSha256 {
+        repository: String,
+        url: String,
+        sha256: String,
+        computed: String,
+        update: bool,
+    },
+    /// An `http_archive`'s `sha256` couldn't be checked (`--check-sha256`)
+    /// because downloading `url` failed with `error`.
+    UnverifiableSha256 {
+        repository: String,
+        url: String,
+        error: String,
+    },
+    /// A `git_repository` isn't pinned to a `commit`.
+    MissingCommit { repository: String },
+    /// An `http_archive` downloads `version` while the version catalog
+    /// (`versions.bzl`) pins `expected`. The fix updates its url, urls and
+    /// strip_prefix.
+    VersionMismatch {
+        repository: String,
+        version: String,
+        expected: String,
+    },
+}
+
+impl BuildIssue {
+    /// Stable identifier used in reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildIssue::MissingSwiftLibraryLoad => "MissingSwiftLibraryLoad",
+            BuildIssue::CustomLibraryRule => "CustomLibraryRule",
+            BuildIssue::ExportsAttribute => "ExportsAttribute",
+            BuildIssue::GlobWithoutAllowEmpty => "GlobWithoutAllowEmpty",
+            BuildIssue::EmptySrcs { .. } => "EmptySrcs",
+            BuildIssue::MissingTrailingNewline => "MissingTrailingNewline",
+            BuildIssue::CrlfLineEnding => "CrlfLineEnding",
+            BuildIssue::TrailingWhitespace => "TrailingWhitespace",
+            BuildIssue::InconsistentQuoteStyle => "InconsistentQuoteStyle",
+            BuildIssue::UnsortedDeps { .. } => "UnsortedDeps",
+            BuildIssue::MissingModuleName { .. } => "MissingModuleName",
+            BuildIssue::MissingMinimumOsVersion { .. } => "MissingMinimumOsVersion",
+            BuildIssue::MissingBundleId { .. } => "MissingBundleId",
+            BuildIssue::EmptyBuildFile => "EmptyBuildFile",
+            BuildIssue::MissingPackageDeclaration => "MissingPackageDeclaration",
+            BuildIssue::MissingLicense { .. } => "MissingLicense",
+            BuildIssue::UndeclaredLoad { .. } => "UndeclaredLoad",
+            BuildIssue::MacroCallWithoutLoad { .. } => "MacroCallWithoutLoad",
+            BuildIssue::MissingTestonly => "MissingTestonly",
+            BuildIssue::DeprecatedAttribute { .. } => "DeprecatedAttribute",
+            BuildIssue::UnsupportedAttribute { .. } => "UnsupportedAttribute",
+            BuildIssue::TestFilesInLibrary { .. } => "TestFilesInLibrary",
+            BuildIssue::MissingSwiftSetting { .. } => "MissingSwiftSetting",
+            BuildIssue::MissingStrip { .. } => "MissingStrip",
+            BuildIssue::MissingGeneratesHeader => "MissingGeneratesHeader",
+            BuildIssue::GeneratesHeaderConflict => "GeneratesHeaderConflict",
+            BuildIssue::IncorrectVisibilityFormat => "IncorrectVisibilityFormat",
+            BuildIssue::AbsoluteIncludePath => "AbsoluteIncludePath",
+            BuildIssue::HardcodedProjectPath { .. } => "HardcodedProjectPath",
+            BuildIssue::LegacyRuleLoad { .. } => "LegacyRuleLoad",
+            BuildIssue::InconsistentTargetName { .. } => "InconsistentTargetName",
+            BuildIssue::NamingConventionViolation { .. } => "NamingConventionViolation",
+            BuildIssue::IncompatibleDependency { .. } => "IncompatibleDependency",
+            BuildIssue::ConflictingModuleNames { .. } => "ConflictingModuleNames",
+            BuildIssue::UnusedDependency { .. } => "UnusedDependency",
+            BuildIssue::RepeatedDep { .. } => "RepeatedDep",
+            BuildIssue::EmptyDepsAttribute { .. } => "EmptyDepsAttribute",
+            BuildIssue::AbsoluteExternalLabel { .. } => "AbsoluteExternalLabel",
+            BuildIssue::WildcardGlob { .. } => "WildcardGlob",
+            BuildIssue::RedundantAllowEmpty { .. } => "RedundantAllowEmpty",
+            BuildIssue::NonHermeticGlob { .. } => "NonHermeticGlob",
+            BuildIssue::SelectInGlob { .. } => "SelectInGlob",
+            BuildIssue::SelectWithoutDefault { .. } => "SelectWithoutDefault",
+            BuildIssue::CommentedOutRule { .. } => "CommentedOutRule",
+            BuildIssue::InvalidSyntax { .. } => "InvalidSyntax",
+            BuildIssue::InvalidSwiftLibrary { .. } => "InvalidSwiftLibrary",
+            BuildIssue::PrivateVisibilityLeak { .. } => "PrivateVisibilityLeak",
+            BuildIssue::UnknownConfigSetting { .. } => "UnknownConfigSetting",
+            BuildIssue::GeneratedSourcesInGlob { .. } => "GeneratedSourcesInGlob",
+            BuildIssue::MixedSourceLanguages { .. } => "MixedSourceLanguages",
+            BuildIssue::OrphanedSourceFile { .. } => "OrphanedSourceFile",
+            BuildIssue::DualBuildSystem => "DualBuildSystem",
+            BuildIssue::MissingDataAttribute { .. } => "MissingDataAttribute",
+            BuildIssue::UnbundledResources { .. } => "UnbundledResources",
+            BuildIssue::RulesSwiftMigration { .. } => "RulesSwiftMigration",
+            BuildIssue::UnorderedAttributes => "UnorderedAttributes",
+            BuildIssue::NonCanonicalFormat => "NonCanonicalFormat",
+            BuildIssue::LineTooLong { .. } => "LineTooLong",
+            BuildIssue::UnusedSuppression { .. } => "UnusedSuppression",
+            BuildIssue::Workspace(issue) => issue.name(),
+        }
+    }
+
+    /// The target the issue is about, for issues that concern a single rule.
+    pub fn target(&self) -> Option<&str> {
+        match self {
+            BuildIssue::EmptySrcs { target, .. }
+            | BuildIssue::MissingModuleName { target, .. }
+            | BuildIssue::MissingMinimumOsVersion { target, .. }
+            | BuildIssue::MissingBundleId { target, .. }
+            | BuildIssue::UnusedDependency { target, .. }
+            | BuildIssue::RepeatedDep { target, .. }
+            | BuildIssue::EmptyDepsAttribute { target }
+            | BuildIssue::AbsoluteExternalLabel { target, .. }
+            | BuildIssue::InconsistentTargetName { target, .. }
+            | BuildIssue::NamingConventionViolation { target, .. }
+            | BuildIssue::TestFilesInLibrary { target, .. }
+            | BuildIssue::MissingSwiftSetting { target, .. }
+            | BuildIssue::MissingStrip { target }
+            | BuildIssue::DeprecatedAttribute { target, .. }
+            | BuildIssue::UnsupportedAttribute { target, .. }
+            | BuildIssue::IncompatibleDependency { target, .. }
+            | BuildIssue::MixedSourceLanguages { target, .. }
+            | BuildIssue::MissingDataAttribute { target }
+            | BuildIssue::UnbundledResources { target, .. }
+            | BuildIssue::ConflictingModuleNames { target, .. }
+            | BuildIssue::PrivateVisibilityLeak { target, .. }
+            | BuildIssue::UnknownConfigSetting { target, .. }
+            | BuildIssue::SelectInGlob {
+                target: Some(target),
+                ..
+            }
+            | BuildIssue::SelectWithoutDefault {
+                target: Some(target),
+                ..
+            }
+            | BuildIssue::InvalidSwiftLibrary {
+                target: Some(target),
+                ..
+            } => Some(target),
+            _ => None,
+        }
+    }
+
+    /// The 1-based line and column the issue names, for issues found at a
+    /// specific place rather than on a whole rule or file.
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            BuildIssue::InvalidSyntax { line, column } => Some((*line, *column)),
+            BuildIssue::SelectInGlob { line, .. }
+            | BuildIssue::SelectWithoutDefault { line, .. }
+            | BuildIssue::CommentedOutRule { line }
+            | BuildIssue::LineTooLong { line, .. }
+            | BuildIssue::UnusedSuppression { line, .. } => Some((*line, 1)),
+            _ => None,
+        }
+    }
+
+    /// Whether the fixer can resolve the issue; others need a manual edit.
+    pub fn is_fixable(&self) -> bool {
+        match self {
+            BuildIssue::GeneratesHeaderConflict
+            | BuildIssue::DualBuildSystem
+            | BuildIssue::TestFilesInLibrary { .. }
+            | BuildIssue::UndeclaredLoad { .. }
+            | BuildIssue::UnusedSuppression { .. }
+            | BuildIssue::SelectInGlob { .. }
+            | BuildIssue::SelectWithoutDefault { .. }
+            | BuildIssue::CommentedOutRule { .. }
+            | BuildIssue::InvalidSyntax { .. }
+            | BuildIssue::InvalidSwiftLibrary { .. }
+            | BuildIssue::PrivateVisibilityLeak { .. }
+            | BuildIssue::UnknownConfigSetting { .. }
+            | BuildIssue::ConflictingModuleNames { .. }
+            | BuildIssue::MissingSwiftSetting { .. } => false,
+            BuildIssue::EmptySrcs { has_srcs, .. } => !has_srcs,
+            BuildIssue::NonHermeticGlob { removable, .. } => *removable,
+            BuildIssue::NamingConventionViolation { renamed, .. } => renamed.is_some(),
+            BuildIssue::UnbundledResources { bundle, .. } => bundle.is_some(),
+            BuildIssue::LineTooLong {
+                target, splittable, ..
+            } => target.is_some() && *splittable,
+            BuildIssue::Workspace(issue) => issue.is_fixable(),
+            _ => true,
+        }
+    }
+
+    /// Whether the issue is only a warning: it is reported and fixed like any
+    /// other, but doesn't make `--check` or `--diff-only` fail.
+    pub fn is_warning(&self) -> bool {
+        matches!(
+            self,
+            BuildIssue::RedundantAllowEmpty { .. } | BuildIssue::MissingStrip { .. }
+        )
+    }
+}
+
+impl WorkspaceIssue {
+    /// Stable identifier used in reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            WorkspaceIssue::MissingLoad { .. } => "MissingLoad",
+            WorkspaceIssue::OutdatedRepositoryRule { .. } => "OutdatedRepositoryRule",
+            WorkspaceIssue::IncorrectHttpArchive { .. } => "IncorrectHttpArchive",
+            WorkspaceIssue::MissingSha256 { .. } => "MissingSha256",
+            WorkspaceIssue::IncorrectSha256 { .. } => "IncorrectSha256",
+            WorkspaceIssue::UnverifiableSha256 { .. } => "UnverifiableSha256",
+            WorkspaceIssue::MissingCommit { .. } => "MissingCommit",
+            WorkspaceIssue::VersionMismatch { .. } => "VersionMismatch",
+        }
+    }
+
+    /// Whether the fixer can resolve the issue; others need a manual edit.
+    pub fn is_fixable(&self) -> bool {
+        match self {
+            WorkspaceIssue::MissingLoad { .. }
+            | WorkspaceIssue::OutdatedRepositoryRule { .. }
+            | WorkspaceIssue::VersionMismatch { .. } => true,
+            WorkspaceIssue::MissingSha256 { sha256, .. } => sha256.is_some(),
+            WorkspaceIssue::IncorrectSha256 { update, .. } => *update,
+            WorkspaceIssue::IncorrectHttpArchive { .. }
+            | WorkspaceIssue::UnverifiableSha256 { .. }
+            | WorkspaceIssue::MissingCommit { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for BuildIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An issue together with a human-readable explanation of where it was found.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    pub issue: BuildIssue,
+    pub message: String,
+}
+
+impl From<(BuildIssue, String)> for Finding {
+    fn from((issue, message): (BuildIssue, String)) -> Self {
+        Finding { issue, message }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.issue, self.message)
+    }
+}
+
+/// Everything the fixer found in one BUILD file.
+#[derive(Debug, Clone, Serialize)]
+pub struct IssueReport {
+    pub path: PathBuf,
+    pub findings: Vec<Finding>,
+    /// Whether the fixes changed the file content (written or not).
+    pub modified: bool,
+    /// Unified diff of the fixes, when the run asked for one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    /// Each fix that changed the file with its own diff, in the order they
+    /// were applied (only with `--verbose-diffs`).
+    #[serde(skip)]
+    pub fix_diffs: Vec<(Finding, String)>,
+}
@@ -0,0 +1,70 @@
+//! Bazel labels of targets in the main repository.
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::label_resolver::{resolve_label, LabelError};
+
+/// A target label such as `//Sources/Core:Core`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    /// Package path relative to the workspace root (empty for the root package).
+    pub package: String,
+    pub target: String,
+}
+
+impl Label {
+    /// Parse `//pkg:target`, `//pkg` (whose target is the package's last
+    /// component) or `:target`, which names a target in `current_package`.
+    /// The grammar is [`resolve_label`]'s, without the bare `target` form and
+    /// external repositories.
+    pub fn parse(label: &str, current_package: &str) -> io::Result<Label> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+
+        // A bare target name is only a label inside a BUILD file
+        if !(label.starts_with("//") || label.starts_with('@') || label.starts_with(':')) {
+            let err = LabelError::Malformed {
+                label: label.to_string(),
+            };
+            return Err(invalid(err.to_string()));
+        }
+        let resolved = resolve_label(label, current_package, Path::new(""))
+            .map_err(|err| invalid(err.to_string()))?;
+
+        // Only the main repository (`@//pkg`) has BUILD files here
+        if resolved.repository.is_some() {
+            return Err(invalid(format!(
+                "invalid label {:?}: labels in external repositories are not supported",
+                label
+            )));
+        }
+
+        Ok(Label {
+            package: resolved.package,
+            target: resolved.target,
+        })
+    }
+
+    /// The BUILD.bazel file that defines the label, under `root`.
+    pub fn build_file(&self, root: &Path) -> io::Result<PathBuf> {
+        let path = root.join(&self.package).join("BUILD.bazel");
+        if !path.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "{} resolves to {}, which doesn't exist",
+                    self,
+                    path.display()
+                ),
+            ));
+        }
+        Ok(path)
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "//{}:{}", self.package, self.target)
+    }
+}
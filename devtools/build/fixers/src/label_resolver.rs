@@ -0,0 +1,140 @@
+//! Resolution of relative Bazel labels to absolute ones, so that `:Core`,
+//! `//Sources/Core` and `//Sources/Core:Core` compare equal.
+
+use std::fmt;
+use std::path::Path;
+
+/// A fully qualified label such as `//Sources/Core:Core` or
+/// `@rules_swift//swift:swift`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AbsoluteLabel {
+    /// External repository name, `None` for the main repository.
+    pub repository: Option<String>,
+    /// Package path relative to the repository root (empty for the root package).
+    pub package: String,
+    pub target: String,
+}
+
+/// Why a label couldn't be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelError {
+    /// The label isn't of any form Bazel accepts.
+    Malformed { label: String },
+    /// The label has an empty target name, e.g. `//pkg:`.
+    MissingTarget { label: String },
+    /// The package path has `.` or `..` components.
+    InvalidPackage { label: String },
+    /// The current package is a directory outside the workspace root.
+    OutsideWorkspace { package: String },
+}
+
+impl fmt::Display for LabelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LabelError::Malformed { label } => write!(
+                f,
+                "invalid label {:?}: expected //package:target, //package or :target",
+                label
+            ),
+            LabelError::MissingTarget { label } => {
+                write!(f, "invalid label {:?}: missing target name", label)
+            }
+            LabelError::InvalidPackage { label } => {
+                write!(
+                    f,
+                    "invalid label {:?}: package paths can't contain . or ..",
+                    label
+                )
+            }
+            LabelError::OutsideWorkspace { package } => {
+                write!(f, "{} is outside the workspace", package)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LabelError {}
+
+impl fmt::Display for AbsoluteLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(repository) = &self.repository {
+            write!(f, "@{}", repository)?;
+        }
+        write!(f, "//{}:{}", self.package, self.target)
+    }
+}
+
+/// Resolve `label`, as written in a BUILD file of `current_package`, to an
+/// absolute label. `current_package` is a package path relative to
+/// `workspace_root`, or a directory under it. `@//pkg` is the main
+/// repository; `//pkg` names the target matching its last path component.
+pub fn resolve_label(
+    label: &str,
+    current_package: &str,
+    workspace_root: &Path,
+) -> Result<AbsoluteLabel, LabelError> {
+    let malformed = || LabelError::Malformed {
+        label: label.to_string(),
+    };
+
+    let (repository, body) = match label.strip_prefix('@') {
+        Some(rest) => {
+            let (repository, body) = rest.split_at(rest.find("//").ok_or_else(malformed)?);
+            let repository = repository.trim_start_matches('@');
+            (
+                (!repository.is_empty()).then(|| repository.to_string()),
+                body,
+            )
+        }
+        None => (None, label),
+    };
+
+    let (package, target) = if let Some(path) = body.strip_prefix("//") {
+        match path.split_once(':') {
+            Some((package, target)) => (package.to_string(), target),
+            None => (
+                path.to_string(),
+                path.rsplit('/').next().unwrap_or_default(),
+            ),
+        }
+    } else if repository.is_none() && !body.contains("//") {
+        // `:target`, or a bare `target` in the same package
+        let target = body.strip_prefix(':').unwrap_or(body);
+        if target.contains(':') {
+            return Err(malformed());
+        }
+        (package_path(current_package, workspace_root)?, target)
+    } else {
+        return Err(malformed());
+    };
+
+    let package = package.trim_end_matches('/');
+    if target.is_empty() {
+        return Err(LabelError::MissingTarget {
+            label: label.to_string(),
+        });
+    }
+    if package.split('/').any(|part| part == ".." || part == ".") {
+        return Err(LabelError::InvalidPackage {
+            label: label.to_string(),
+        });
+    }
+
+    Ok(AbsoluteLabel {
+        repository,
+        package: package.to_string(),
+        target: target.to_string(),
+    })
+}
+
+// `current_package` as a root-relative package path
+fn package_path(current_package: &str, workspace_root: &Path) -> Result<String, LabelError> {
+    let path = Path::new(current_package);
+    match path.strip_prefix(workspace_root) {
+        Ok(relative) => Ok(relative.to_string_lossy().replace('\\', "/")),
+        Err(_) if path.is_absolute() => Err(LabelError::OutsideWorkspace {
+            package: current_package.to_string(),
+        }),
+        Err(_) => Ok(current_package.trim_matches('/').to_string()),
+    }
+}
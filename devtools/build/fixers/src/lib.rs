@@ -0,0 +1,53 @@
+//! Analysis and fix-up of UmbraCore `BUILD.bazel` and `WORKSPACE` files.
+//!
+//! The `umbra-fix` binary is a thin CLI over this library.
+
+pub mod atomic_write;
+pub mod baseline;
+pub mod bazel_query;
+pub mod bazelignore;
+pub mod build_cleaner;
+pub mod build_validator;
+pub mod cache;
+pub mod checks;
+pub mod config;
+pub mod dependency_updater;
+pub mod deprecated_attrs;
+pub mod discovery;
+pub mod download;
+pub mod fixer;
+pub mod generate;
+pub mod git;
+pub mod glob;
+pub mod glob_evaluator;
+pub mod hook;
+pub mod issue;
+pub mod label;
+pub mod label_resolver;
+pub mod lockfile_checker;
+pub mod lsp;
+pub mod metrics;
+pub mod migrations;
+pub mod module_mapper;
+pub mod parallel_io;
+pub mod patch;
+#[cfg(feature = "profile")]
+pub mod profiler;
+pub mod progress_state;
+pub mod report;
+pub mod schema;
+pub mod sources;
+pub mod starlark;
+pub mod swift_imports;
+pub mod swift_package_converter;
+pub mod swift_version;
+pub mod target_graph;
+pub mod undo;
+pub mod workspace;
+
+pub use checks::{analyze_build_file, analyze_build_file_at};
+pub use config::{ColorMode, Config, OutputFormat, ProgressMode, RunMode};
+pub use discovery::{find_build_files, find_workspace_build_files, find_workspace_files};
+pub use fixer::{fix_build_file, fix_build_file_content, fix_build_file_with_cache};
+pub use issue::{BuildIssue, Finding, IssueReport, WorkspaceIssue};
+pub use report::{RemainingIssues, RunReport};
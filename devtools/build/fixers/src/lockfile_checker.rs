@@ -0,0 +1,93 @@
+//! Checks that `MODULE.bazel.lock` still matches `MODULE.bazel`, for
+//! `umbra-fix check-lock` and `--bazel-validate`.
+//!
+//! Bazel does the comparison: `bazel mod deps --lockfile_mode=error` fails
+//! when resolving the module graph would change the lock file, and
+//! `--lockfile_mode=update` rewrites it.
+
+use std::io;
+use std::path::Path;
+use std::process::ExitStatus;
+use std::time::Duration;
+
+use crate::bazel_query::{parse_bazel_errors, run_bazel, BazelError};
+
+/// The lock file Bazel keeps next to `MODULE.bazel`.
+pub const LOCK_FILE_NAME: &str = "MODULE.bazel.lock";
+
+/// The file that makes a workspace use Bzlmod, and so have a lock file.
+pub const MODULE_FILE_NAME: &str = "MODULE.bazel";
+
+/// The outcome of [`check_lockfile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockStatus {
+    /// The workspace has no `MODULE.bazel`, so there is nothing to check.
+    NoModule,
+    UpToDate,
+    /// Bazel would change the lock file; `errors` are what it reported.
+    Stale {
+        errors: Vec<BazelError>,
+    },
+}
+
+/// Run `bazel mod deps --lockfile_mode=error` in `root`. Fails if bazel can't
+/// be started, runs longer than `timeout`, or fails for a reason other than
+/// the lock file.
+pub fn check_lockfile(bazel: &Path, root: &Path, timeout: Duration) -> io::Result<LockStatus> {
+    if !root.join(MODULE_FILE_NAME).is_file() {
+        return Ok(LockStatus::NoModule);
+    }
+
+    let (status, stderr) = run_bazel(
+        bazel,
+        root,
+        &["mod", "deps", "--color=no", "--lockfile_mode=error"],
+        timeout,
+    )?;
+    if status.success() {
+        return Ok(LockStatus::UpToDate);
+    }
+    if reports_stale_lock(&stderr) {
+        return Ok(LockStatus::Stale {
+            errors: parse_bazel_errors(&stderr),
+        });
+    }
+    Err(mod_deps_failed(status, &stderr))
+}
+
+/// Run `bazel mod deps --lockfile_mode=update` in `root`, rewriting the lock
+/// file. Fails if bazel does.
+pub fn update_lockfile(bazel: &Path, root: &Path, timeout: Duration) -> io::Result<()> {
+    let (status, stderr) = run_bazel(
+        bazel,
+        root,
+        &["mod", "deps", "--color=no", "--lockfile_mode=update"],
+        timeout,
+    )?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(mod_deps_failed(status, &stderr))
+    }
+}
+
+// Whether bazel failed because `--lockfile_mode=error` found the lock file
+// out of date: "MODULE.bazel.lock is no longer up-to-date because: ...".
+// Other failures can mention locks too (the output base lock of a busy
+// server), and updating the lock file wouldn't help with those.
+fn reports_stale_lock(stderr: &str) -> bool {
+    stderr
+        .lines()
+        .any(|line| line.contains(&format!("{} is no longer up-to-date", LOCK_FILE_NAME)))
+}
+
+fn mod_deps_failed(status: ExitStatus, stderr: &str) -> io::Error {
+    let last_line = stderr.lines().rev().find(|line| !line.trim().is_empty());
+    io::Error::other(format!(
+        "bazel mod deps exited with {}{}",
+        status,
+        last_line
+            .map(|line| format!(": {}", line.trim()))
+            .unwrap_or_default()
+    ))
+}
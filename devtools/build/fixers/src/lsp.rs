@@ -0,0 +1,371 @@
+//! A Language Server Protocol server (`umbra-fix --lsp`), so editors show the
+//! findings as diagnostics and offer the fixes as quick fixes.
+//!
+//! Messages are JSON-RPC over stdin/stdout with `Content-Length` framing. The
+//! server keeps the text of open documents (full sync) and publishes
+//! diagnostics when a document is opened or saved.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument, Exit,
+    Notification, PublishDiagnostics,
+};
+use lsp_types::request::{CodeActionRequest, Initialize, Request, Shutdown};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
+    InitializeResult, NumberOrString, Position, PublishDiagnosticsParams, Range,
+    ServerCapabilities, ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextDocumentSyncOptions, TextDocumentSyncSaveOptions, TextEdit, Uri, WorkspaceEdit,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::checks::{analyze_build_file, analyze_build_file_at, fix_issue_in_scope};
+use crate::config::Config;
+use crate::issue::{BuildIssue, WorkspaceIssue};
+use crate::starlark::calls::top_level_calls;
+use crate::starlark::tokenizer::tokenize;
+
+/// `source` of the diagnostics the server publishes.
+pub const DIAGNOSTIC_SOURCE: &str = "umbra-fix";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Serve LSP requests read from `input` until the client sends `exit`.
+pub fn serve(config: &Config, mut input: impl BufRead, output: impl Write) -> io::Result<()> {
+    let mut server = Server {
+        config,
+        output,
+        documents: HashMap::new(),
+    };
+
+    while let Some(message) = read_message(&mut input)? {
+        let message = match message {
+            Ok(message) => message,
+            Err(err) => {
+                // The framing is intact, so the next message can still be read
+                server.respond(Value::Null, Err((PARSE_ERROR, err.to_string())))?;
+                continue;
+            }
+        };
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // A response to a request we never send
+            continue;
+        };
+        if method == Exit::METHOD {
+            break;
+        }
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        match message.get("id") {
+            Some(id) => server.handle_request(id.clone(), method, params)?,
+            None => server.handle_notification(method, params)?,
+        }
+    }
+
+    Ok(())
+}
+
+struct Server<'a, W> {
+    config: &'a Config,
+    output: W,
+    /// Text of the open documents, by URI.
+    documents: HashMap<String, String>,
+}
+
+impl<W: Write> Server<'_, W> {
+    fn handle_request(&mut self, id: Value, method: &str, params: Value) -> io::Result<()> {
+        let result = match method {
+            Initialize::METHOD => Ok(json!(initialize_result())),
+            Shutdown::METHOD => Ok(Value::Null),
+            CodeActionRequest::METHOD => {
+                parse_params(params).map(|params| json!(self.code_actions(params)))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("unsupported method {}", method))),
+        };
+        self.respond(id, result)
+    }
+
+    fn respond(&mut self, id: Value, result: Result<Value, (i64, String)>) -> io::Result<()> {
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        write_message(&mut self.output, &response)
+    }
+
+    // Malformed and unknown notifications are ignored, as the protocol requires
+    fn handle_notification(&mut self, method: &str, params: Value) -> io::Result<()> {
+        match method {
+            DidOpenTextDocument::METHOD => {
+                if let Ok(params) = parse_params::<DidOpenTextDocumentParams>(params) {
+                    let uri = params.text_document.uri;
+                    self.documents
+                        .insert(uri.as_str().to_string(), params.text_document.text);
+                    self.publish_diagnostics(&uri)?;
+                }
+            }
+            DidChangeTextDocument::METHOD => {
+                if let Ok(params) = parse_params::<DidChangeTextDocumentParams>(params) {
+                    // Full sync: the last change holds the whole text
+                    if let Some(change) = params.content_changes.into_iter().last() {
+                        self.documents
+                            .insert(params.text_document.uri.as_str().to_string(), change.text);
+                    }
+                }
+            }
+            DidSaveTextDocument::METHOD => {
+                if let Ok(params) = parse_params::<DidSaveTextDocumentParams>(params) {
+                    let uri = params.text_document.uri;
+                    if let Some(text) = params.text {
+                        self.documents.insert(uri.as_str().to_string(), text);
+                    }
+                    self.publish_diagnostics(&uri)?;
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                if let Ok(params) = parse_params::<DidCloseTextDocumentParams>(params) {
+                    let uri = params.text_document.uri;
+                    self.documents.remove(uri.as_str());
+                    self.send_diagnostics(uri, Vec::new())?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn publish_diagnostics(&mut self, uri: &Uri) -> io::Result<()> {
+        let Some(text) = self.documents.get(uri.as_str()) else {
+            return Ok(());
+        };
+        let findings = match file_path(uri) {
+            Some(path) => analyze_build_file_at(text, &path, self.config),
+            None => analyze_build_file(text, self.config),
+        };
+        let diagnostics = findings
+            .into_iter()
+            .map(|finding| Diagnostic {
+                range: issue_range(text, &finding.issue),
+                severity: Some(severity(&finding.issue)),
+                code: Some(NumberOrString::String(finding.issue.name().to_string())),
+                source: Some(DIAGNOSTIC_SOURCE.to_string()),
+                message: finding.message,
+                data: serde_json::to_value(&finding.issue).ok(),
+                ..Diagnostic::default()
+            })
+            .collect();
+        self.send_diagnostics(uri.clone(), diagnostics)
+    }
+
+    fn send_diagnostics(&mut self, uri: Uri, diagnostics: Vec<Diagnostic>) -> io::Result<()> {
+        let params = PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version: None,
+        };
+        write_message(
+            &mut self.output,
+            &json!({
+                "jsonrpc": "2.0",
+                "method": PublishDiagnostics::METHOD,
+                "params": params,
+            }),
+        )
+    }
+
+    // A quick fix for each of our fixable diagnostics in the request, which
+    // replaces the document with the fixed text
+    fn code_actions(&self, params: CodeActionParams) -> Vec<CodeActionOrCommand> {
+        let uri = params.text_document.uri;
+        let Some(text) = self.documents.get(uri.as_str()) else {
+            return Vec::new();
+        };
+
+        params
+            .context
+            .diagnostics
+            .into_iter()
+            .filter(|diagnostic| diagnostic.source.as_deref() == Some(DIAGNOSTIC_SOURCE))
+            .filter_map(|diagnostic| {
+                let issue: BuildIssue = serde_json::from_value(diagnostic.data.clone()?).ok()?;
+                if !issue.is_fixable() {
+                    return None;
+                }
+                let fixed = fix_issue_in_scope(&issue, text, self.config);
+                if fixed == *text {
+                    return None;
+                }
+                let edit = TextEdit {
+                    range: document_range(text),
+                    new_text: fixed,
+                };
+                Some(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: format!("Fix {}", issue.name()),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diagnostic]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                        ..WorkspaceEdit::default()
+                    }),
+                    is_preferred: Some(true),
+                    ..CodeAction::default()
+                }))
+            })
+            .collect()
+    }
+}
+
+fn initialize_result() -> InitializeResult {
+    InitializeResult {
+        capabilities: ServerCapabilities {
+            text_document_sync: Some(TextDocumentSyncCapability::Options(
+                TextDocumentSyncOptions {
+                    open_close: Some(true),
+                    change: Some(TextDocumentSyncKind::FULL),
+                    save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                    ..TextDocumentSyncOptions::default()
+                },
+            )),
+            code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+            ..ServerCapabilities::default()
+        },
+        server_info: Some(ServerInfo {
+            name: DIAGNOSTIC_SOURCE.to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        }),
+    }
+}
+
+/// How prominently an editor should show `issue`.
+pub fn severity(issue: &BuildIssue) -> DiagnosticSeverity {
+    match issue {
+        // Bazel refuses to load or build the package
+        BuildIssue::MissingSwiftLibraryLoad
+        | BuildIssue::UndeclaredLoad { .. }
+        | BuildIssue::EmptySrcs { .. }
+        | BuildIssue::IncorrectVisibilityFormat
+        | BuildIssue::GeneratesHeaderConflict
+        | BuildIssue::Workspace(WorkspaceIssue::MissingLoad { .. })
+        | BuildIssue::Workspace(WorkspaceIssue::IncorrectHttpArchive { .. }) => {
+            DiagnosticSeverity::ERROR
+        }
+        // Layout only
+        BuildIssue::MissingTrailingNewline
+        | BuildIssue::CrlfLineEnding
+        | BuildIssue::TrailingWhitespace
+        | BuildIssue::InconsistentQuoteStyle
+        | BuildIssue::UnsortedDeps { .. }
+        | BuildIssue::UnorderedAttributes
+        | BuildIssue::NonCanonicalFormat => DiagnosticSeverity::HINT,
+        _ => DiagnosticSeverity::WARNING,
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, (i64, String)> {
+    serde_json::from_value(params).map_err(|err| (INVALID_PARAMS, err.to_string()))
+}
+
+// The line of the rule an issue is about, or the first line for issues that
+// concern the whole file
+fn issue_range(text: &str, issue: &BuildIssue) -> Range {
+    let tokens = tokenize(text);
+    let line = match issue.position() {
+        Some((line, _)) => line.saturating_sub(1),
+        None => issue
+            .target()
+            .and_then(|target| {
+                top_level_calls(&tokens)
+                    .into_iter()
+                    .find(|call| call.target_name(&tokens).as_deref() == Some(target))
+            })
+            .map_or(0, |call| call.line.saturating_sub(1)),
+    };
+    let width = text.lines().nth(line).map_or(0, utf16_len);
+    Range::new(
+        Position::new(line as u32, 0),
+        Position::new(line as u32, width),
+    )
+}
+
+fn document_range(text: &str) -> Range {
+    let last_line = text.rsplit('\n').next().unwrap_or("");
+    Range::new(
+        Position::new(0, 0),
+        Position::new(text.matches('\n').count() as u32, utf16_len(last_line)),
+    )
+}
+
+// LSP positions count UTF-16 code units
+fn utf16_len(line: &str) -> u32 {
+    line.encode_utf16().count() as u32
+}
+
+// The local path of a `file://` URI
+fn file_path(uri: &Uri) -> Option<PathBuf> {
+    let path = uri.as_str().strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = (byte == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+// Read one message, or `None` at the end of the input. A body that isn't
+// JSON is an `Err` inside, since the messages after it can still be read.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<serde_json::Result<Value>>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "message without Content-Length")
+    })?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
@@ -0,0 +1,114 @@
+//! Wall-clock timing of the phases of a run.
+//!
+//! Timings are accumulated per thread without locking and merged into a
+//! process-wide total by [`flush_thread`] (worker threads call it before they
+//! are joined; [`snapshot`] flushes the calling thread).
+
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const DISCOVER: &str = "discover";
+pub const ANALYZE: &str = "analyze";
+pub const FIX: &str = "fix";
+pub const VERIFY: &str = "verify";
+pub const VALIDATE: &str = "validate";
+
+/// Accumulated time per phase, in the order phases were first recorded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metrics {
+    pub phases: Vec<(&'static str, Duration)>,
+}
+
+impl Metrics {
+    fn record(&mut self, phase: &'static str, elapsed: Duration) {
+        match self.phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => self.phases.push((phase, elapsed)),
+        }
+    }
+
+    fn merge(&mut self, other: Metrics) {
+        for (phase, elapsed) in other.phases {
+            self.record(phase, elapsed);
+        }
+    }
+
+    /// A table of each phase's duration and share of `wall_time`.
+    pub fn summary(&self, wall_time: Duration) -> String {
+        let mut table = format!("{:<10} {:>12} {:>8}\n", "phase", "duration", "% wall");
+        for (phase, elapsed) in &self.phases {
+            let share = if wall_time.is_zero() {
+                0.0
+            } else {
+                elapsed.as_secs_f64() / wall_time.as_secs_f64() * 100.0
+            };
+            let _ = writeln!(
+                table,
+                "{:<10} {:>10.3}ms {:>7.1}%",
+                phase,
+                elapsed.as_secs_f64() * 1000.0,
+                share
+            );
+        }
+        let _ = writeln!(
+            table,
+            "{:<10} {:>10.3}ms {:>7.1}%",
+            "total",
+            wall_time.as_secs_f64() * 1000.0,
+            100.0
+        );
+        table
+    }
+}
+
+thread_local! {
+    static THREAD_METRICS: RefCell<Metrics> = RefCell::new(Metrics::default());
+}
+
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics { phases: Vec::new() });
+
+/// Entry point for timing a phase.
+pub struct PhaseTimer;
+
+impl PhaseTimer {
+    /// Start timing `phase`; the time is recorded when the guard is dropped.
+    pub fn start(phase: &'static str) -> PhaseGuard {
+        PhaseGuard {
+            phase,
+            started: Instant::now(),
+        }
+    }
+}
+
+/// Records the time since [`PhaseTimer::start`] when dropped.
+pub struct PhaseGuard {
+    phase: &'static str,
+    started: Instant,
+}
+
+impl Drop for PhaseGuard {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        THREAD_METRICS.with(|metrics| metrics.borrow_mut().record(self.phase, elapsed));
+    }
+}
+
+/// Merge the calling thread's timings into the process-wide totals.
+pub fn flush_thread() {
+    let local = THREAD_METRICS.with(|metrics| metrics.take());
+    METRICS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .merge(local);
+}
+
+/// The process-wide totals, including the calling thread's timings.
+pub fn snapshot() -> Metrics {
+    flush_thread();
+    METRICS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
@@ -0,0 +1,3 @@
+//! Version-to-version migrations of BUILD files to newer rule set APIs.
+
+pub mod rules_swift;
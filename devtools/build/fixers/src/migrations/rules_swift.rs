@@ -0,0 +1,325 @@
+//! Migration of BUILD files between rules_swift major versions.
+//!
+//! Each major version bump is a chain of steps. A step is a plain `fix_*`
+//! function that leaves files it doesn't apply to unchanged, so steps can
+//! be checked and tested independently.
+
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::issue::BuildIssue;
+use crate::starlark::calls::{line_indent, top_level_calls};
+use crate::starlark::tokenizer::{quote, tokenize, Token, TokenKind};
+
+/// One step of a migration to the next rules_swift major version.
+#[derive(Debug)]
+pub struct MigrationStep {
+    /// Stable identifier used in reports.
+    pub name: &'static str,
+    pub description: &'static str,
+    pub fix: fn(&str) -> String,
+}
+
+/// Migration steps keyed by the major version they migrate from.
+pub const MIGRATIONS: &[(u32, &[MigrationStep])] = &[(
+    1,
+    &[
+        MigrationStep {
+            name: "SplitSwiftBzlLoads",
+            description: "load rules from their own bzl files instead of swift.bzl",
+            fix: fix_split_swift_bzl_loads,
+        },
+        MigrationStep {
+            name: "SwiftProtoLibraryProtos",
+            description: "swift_proto_library takes proto_library targets in protos, not deps",
+            fix: fix_swift_proto_library_protos,
+        },
+    ],
+)];
+
+// Rules that rules_swift 2 loads from `//swift:<rule>.bzl`
+const SPLIT_RULES: &[&str] = &[
+    "swift_binary",
+    "swift_compiler_plugin",
+    "swift_import",
+    "swift_interop_hint",
+    "swift_library",
+    "swift_library_group",
+    "swift_module_alias",
+    "swift_test",
+];
+
+const SWIFT_BZL_LABELS: &[&str] = &[
+    "@build_bazel_rules_swift//swift:swift.bzl",
+    "@rules_swift//swift:swift.bzl",
+];
+
+/// An upgrade between two rules_swift major versions, written `1->2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionUpgrade {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl VersionUpgrade {
+    /// The steps of every major version bump from `from` to `to`, in order.
+    pub fn steps(&self) -> impl Iterator<Item = &'static MigrationStep> {
+        let (from, to) = (self.from, self.to);
+        MIGRATIONS
+            .iter()
+            .filter(move |(version, _)| (from..to).contains(version))
+            .flat_map(|(_, steps)| steps.iter())
+    }
+}
+
+impl FromStr for VersionUpgrade {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (from, to) = text
+            .split_once("->")
+            .ok_or_else(|| format!("invalid upgrade {:?}: expected FROM->TO, e.g. 1->2", text))?;
+        let parse = |version: &str| {
+            version
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| format!("invalid rules_swift major version {:?}", version))
+        };
+        let upgrade = VersionUpgrade {
+            from: parse(from)?,
+            to: parse(to)?,
+        };
+
+        if upgrade.from >= upgrade.to {
+            return Err(format!("cannot upgrade rules_swift from {}", upgrade));
+        }
+        if let Some(version) = (upgrade.from..upgrade.to)
+            .find(|version| !MIGRATIONS.iter().any(|(known, _)| known == version))
+        {
+            return Err(format!(
+                "no rules_swift migration from {}.x to {}.x",
+                version,
+                version + 1
+            ));
+        }
+        Ok(upgrade)
+    }
+}
+
+impl fmt::Display for VersionUpgrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}->{}", self.from, self.to)
+    }
+}
+
+// Report each step of the upgrade that would change the file. Every step is
+// checked against the output of the ones before it.
+pub fn check_rules_swift_upgrade(
+    content: &str,
+    upgrade: VersionUpgrade,
+) -> Vec<(BuildIssue, String)> {
+    let mut content = content.to_string();
+    let mut issues = Vec::new();
+
+    for step in upgrade.steps() {
+        let fixed = (step.fix)(&content);
+        if fixed != content {
+            issues.push((
+                BuildIssue::RulesSwiftMigration {
+                    step: step.name.to_string(),
+                },
+                format!("rules_swift {}: {}", upgrade, step.description),
+            ));
+            content = fixed;
+        }
+    }
+
+    issues
+}
+
+// Apply the migration step called `name`
+pub fn fix_migration_step(content: &str, name: &str) -> String {
+    MIGRATIONS
+        .iter()
+        .flat_map(|(_, steps)| steps.iter())
+        .find(|step| step.name == name)
+        .map_or_else(|| content.to_string(), |step| (step.fix)(content))
+}
+
+// Split each load() of swift.bzl into one load per rule from its own bzl
+// file. Other symbols and aliased loads stay in swift.bzl; loads with
+// comments are left alone.
+pub fn fix_split_swift_bzl_loads(content: &str) -> String {
+    let tokens = tokenize(content);
+    let mut new_content = content.to_string();
+
+    for call in top_level_calls(&tokens).iter().rev() {
+        if call.name != "load" {
+            continue;
+        }
+        let arguments = call.arguments(&tokens);
+        let Some(label) = arguments
+            .first()
+            .and_then(|argument| tokens[argument.value.start].string_value())
+            .filter(|label| SWIFT_BZL_LABELS.contains(&label.as_str()))
+        else {
+            continue;
+        };
+        let has_comments = tokens[call.open..call.close]
+            .iter()
+            .any(|t| t.kind == TokenKind::Comment);
+        if has_comments {
+            continue;
+        }
+
+        let mut split = Vec::new();
+        let mut kept = Vec::new();
+        for argument in &arguments[1..] {
+            let value = &content[argument.byte_range(&tokens)];
+            let symbol = tokens[argument.value.clone()]
+                .first()
+                .and_then(|token| token.string_value());
+            match (argument.key, symbol) {
+                (None, Some(symbol)) if SPLIT_RULES.contains(&symbol.as_str()) => {
+                    split.push(symbol)
+                }
+                (Some(key), _) => kept.push(format!("{} = {}", key, value)),
+                (None, _) => kept.push(value.to_string()),
+            }
+        }
+        if split.is_empty() {
+            continue;
+        }
+
+        let repository = label.trim_end_matches("swift.bzl");
+        let mut loads: Vec<String> = split
+            .iter()
+            .map(|rule| format!("load(\"{}{}.bzl\", \"{}\")", repository, rule, rule))
+            .collect();
+        if !kept.is_empty() {
+            loads.insert(0, format!("load(\"{}\", {})", label, kept.join(", ")));
+        }
+
+        let start = tokens[call.open - 1].start;
+        new_content.replace_range(start..tokens[call.close].end(), &loads.join("\n"));
+    }
+
+    new_content
+}
+
+// Move the proto_library labels in the `deps` of swift_proto_library to
+// `protos`: labels of a proto_library declared in the same file, or whose
+// target name ends in `_proto` as Bazel's naming convention has it. deps is
+// renamed when it holds nothing else; other labels stay in deps. Only deps
+// that are a list of strings are touched.
+pub fn fix_swift_proto_library_protos(content: &str) -> String {
+    let mut content = content.to_string();
+
+    // Each pass moves the protos of one rule, so token indices stay valid
+    loop {
+        let tokens = tokenize(&content);
+        let calls = top_level_calls(&tokens);
+        let local_protos: Vec<String> = calls
+            .iter()
+            .filter(|call| call.name == "proto_library")
+            .filter_map(|call| call.target_name(&tokens))
+            .collect();
+        let is_proto = |label: &str| {
+            let name = match label.rsplit_once(':') {
+                Some((_, name)) => name,
+                None if label.starts_with("//") || label.starts_with('@') => {
+                    label.rsplit('/').next().unwrap_or_default()
+                }
+                None => label,
+            };
+            let local = !label.starts_with("//") && !label.starts_with('@');
+            name.ends_with("_proto") || (local && local_protos.iter().any(|proto| proto == name))
+        };
+
+        let found = calls.iter().find_map(|call| {
+            if call.name != "swift_proto_library" || call.keyword(&tokens, "protos").is_some() {
+                return None;
+            }
+            let deps = call.keyword(&tokens, "deps")?;
+            let elements = list_strings(&tokens, deps.value.clone())?;
+            let protos: Vec<usize> = elements
+                .iter()
+                .copied()
+                .filter(|&index| is_proto(&tokens[index].string_value().unwrap_or_default()))
+                .collect();
+            if protos.is_empty() {
+                return None;
+            }
+            let all_protos = protos.len() == elements.len();
+            // Comments can't follow their labels into a rebuilt list
+            let has_comments = tokens[deps.value.clone()]
+                .iter()
+                .any(|token| token.kind == TokenKind::Comment);
+            if !all_protos && has_comments {
+                return None;
+            }
+            let key = (call.open + 1..deps.value.start)
+                .rev()
+                .find(|&i| tokens[i].is_ident("deps"))?;
+            Some((key, deps.value, elements, protos))
+        });
+        let Some((key, value, elements, protos)) = found else {
+            return content;
+        };
+
+        if protos.len() == elements.len() {
+            content.replace_range(tokens[key].start..tokens[key].end(), "protos");
+            continue;
+        }
+
+        let label = |index: usize| quote(&tokens[index].string_value().unwrap_or_default());
+        let labels: Vec<String> = protos.iter().map(|&index| label(index)).collect();
+        let kept: Vec<String> = elements
+            .iter()
+            .filter(|index| !protos.contains(index))
+            .map(|&index| label(index))
+            .collect();
+        let key_start = tokens[key].start;
+        let indent = line_indent(&content, key_start);
+        let list_range = tokens[value.start].start..tokens[value.end - 1].end();
+        let list = if content[list_range.clone()].contains('\n') {
+            let elements: String = kept
+                .iter()
+                .map(|label| format!("{}    {},\n", indent, label))
+                .collect();
+            format!("[\n{}{}]", elements, indent)
+        } else {
+            format!("[{}]", kept.join(", "))
+        };
+        let own_line = content[..key_start]
+            .rsplit('\n')
+            .next()
+            .is_some_and(|before| before.trim().is_empty());
+        let attribute = if own_line {
+            format!("protos = [{}],\n{}", labels.join(", "), indent)
+        } else {
+            format!("protos = [{}], ", labels.join(", "))
+        };
+        // The list comes after the key, so replacing it first keeps the key in place
+        content.replace_range(list_range, &list);
+        content.insert_str(key_start, &attribute);
+    }
+}
+
+// The string tokens of `value` if it is a list of strings
+fn list_strings(tokens: &[Token<'_>], value: Range<usize>) -> Option<Vec<usize>> {
+    let (first, last) = (value.start, value.end.checked_sub(1)?);
+    if tokens[first].kind != TokenKind::LBracket || tokens[last].kind != TokenKind::RBracket {
+        return None;
+    }
+    let mut strings = Vec::new();
+    for (index, token) in tokens.iter().enumerate().take(last).skip(first + 1) {
+        match token.kind {
+            TokenKind::String => strings.push(index),
+            TokenKind::Comma | TokenKind::Comment => {}
+            _ => return None,
+        }
+    }
+    Some(strings)
+}
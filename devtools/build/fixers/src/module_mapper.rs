@@ -0,0 +1,74 @@
+//! Generation of `import_map.toml` from the workspace's `swift_library`
+//! targets, for `umbra-fix gen-import-map`.
+//!
+//! Each target maps from its absolute label to its Swift module name
+//! (`module_name`, or else the target name). Modules built outside the
+//! workspace, such as third-party packages, come from a seed map.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::label_resolver::resolve_label;
+use crate::target_graph::TargetGraph;
+
+/// Dependency label -> Swift module name, in import_map.toml's format.
+pub type ImportMap = BTreeMap<String, String>;
+
+/// The import map of the `swift_library` targets of `graph`, merged into
+/// `seed`. Seed entries for labels of those targets are replaced; targets
+/// whose module name is computed are left out.
+pub fn generate_import_map(graph: &TargetGraph, seed: &ImportMap) -> ImportMap {
+    let mut generated = ImportMap::new();
+    for (label, info) in graph.targets() {
+        if info.rule_name != "swift_library" {
+            continue;
+        }
+        if let Some(module_name) = info.module_name() {
+            generated.insert(label.to_string(), module_name.to_string());
+        }
+    }
+
+    let generated_labels: BTreeSet<String> = generated.keys().cloned().collect();
+    let mut import_map: ImportMap = seed
+        .iter()
+        .filter(|(label, _)| {
+            resolve_label(label, "", graph.workspace_root())
+                .map_or(true, |label| !generated_labels.contains(&label.to_string()))
+        })
+        .map(|(label, module)| (label.clone(), module.clone()))
+        .collect();
+    import_map.extend(generated);
+    import_map
+}
+
+/// Render `import_map` as TOML, one entry per line sorted by module name.
+pub fn format_import_map(import_map: &ImportMap) -> String {
+    let mut entries: Vec<(&String, &String)> = import_map.iter().collect();
+    entries.sort_by(|(a_label, a_module), (b_label, b_module)| {
+        (a_module, a_label).cmp(&(b_module, b_label))
+    });
+
+    let mut content = String::from(
+        "# Dependency label -> Swift module name, generated by umbra-fix gen-import-map\n",
+    );
+    for (label, module) in entries {
+        // A table per entry, so the order isn't the map's
+        let entry = BTreeMap::from([(label, module)]);
+        content.push_str(&toml::to_string(&entry).expect("strings serialize to TOML"));
+    }
+    content
+}
+
+/// Read the import map at `path` to seed the generated one with.
+pub fn read_import_map(path: &Path) -> io::Result<ImportMap> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))?;
+    toml::from_str(&content).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), err),
+        )
+    })
+}
@@ -0,0 +1,44 @@
+//! Concurrent reads of BUILD files, for `--parallel-io`.
+//!
+//! On network filesystems most of the time spent on a large workspace is
+//! waiting for reads, one file at a time. Reading them all up front on a
+//! tokio runtime overlaps that latency; a semaphore keeps the number of open
+//! descriptors under `max_open_files` so large trees don't hit `ulimit -n`.
+
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tokio::runtime::Builder;
+use tokio::sync::Semaphore;
+
+/// Default limit on the files `read_files` keeps open at once.
+pub const DEFAULT_MAX_OPEN_FILES: usize = 64;
+
+/// Read every file of `paths` concurrently, with at most `max_open_files` open
+/// at a time. The results are in the order of `paths`; a file that can't be
+/// read doesn't stop the others from being read.
+pub fn read_files(paths: &[PathBuf], max_open_files: usize) -> io::Result<Vec<io::Result<String>>> {
+    let runtime = Builder::new_multi_thread().enable_all().build()?;
+    let permits = Arc::new(Semaphore::new(max_open_files.max(1)));
+
+    runtime.block_on(async {
+        let tasks: Vec<_> = paths
+            .iter()
+            .map(|path| {
+                let path = path.clone();
+                let permits = Arc::clone(&permits);
+                tokio::spawn(async move {
+                    let _permit = permits.acquire_owned().await.map_err(io::Error::other)?;
+                    tokio::fs::read_to_string(&path).await
+                })
+            })
+            .collect();
+
+        let mut contents = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            contents.push(task.await.map_err(io::Error::other)?);
+        }
+        Ok(contents)
+    })
+}
@@ -0,0 +1,199 @@
+//! Unified diffs of proposed fixes, and applying them back.
+
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use similar::TextDiff;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+use crate::atomic_write::atomic_write;
+
+/// Number of unchanged lines shown around each change.
+pub const DEFAULT_CONTEXT: usize = 3;
+
+/// Number of unchanged lines shown around each fix with `--verbose-diffs`.
+pub const VERBOSE_DIFF_CONTEXT: usize = 2;
+
+// Unified diff of one file, with `a/` and `b/` headers for `relative_path`
+pub fn unified_diff(relative_path: &Path, old: &str, new: &str, context: usize) -> String {
+    let path = relative_path.to_string_lossy().replace('\\', "/");
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(context)
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string()
+}
+
+/// Write `diff` with file headers in bold, hunk headers in cyan, removed
+/// lines in red and added lines in green.
+pub fn write_colored_diff(out: &mut impl WriteColor, diff: &str) -> io::Result<()> {
+    for line in diff.split_inclusive('\n') {
+        let mut spec = ColorSpec::new();
+        if line.starts_with("---") || line.starts_with("+++") {
+            spec.set_bold(true);
+        } else if line.starts_with("@@") {
+            spec.set_fg(Some(Color::Cyan));
+        } else if line.starts_with('-') {
+            spec.set_fg(Some(Color::Red));
+        } else if line.starts_with('+') {
+            spec.set_fg(Some(Color::Green));
+        }
+        out.set_color(&spec)?;
+        write!(out, "{}", line.trim_end_matches('\n'))?;
+        out.reset()?;
+        if line.ends_with('\n') {
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// One file's changes within a patch.
+struct FilePatch<'a> {
+    path: &'a str,
+    hunks: Vec<Hunk<'a>>,
+}
+
+struct Hunk<'a> {
+    old_start: usize,
+    /// Lines prefixed with ' ', '-' or '+', plus whether each lacks a newline.
+    lines: Vec<(char, &'a str, bool)>,
+}
+
+fn parse_hunk_start(header: &str) -> Option<usize> {
+    let old_range = header.strip_prefix("@@ -")?.split(' ').next()?;
+    old_range.split(',').next()?.parse().ok()
+}
+
+fn parse_patch(patch: &str) -> io::Result<Vec<FilePatch<'_>>> {
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut lines = patch.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            let path = path.split('\t').next().unwrap_or(path);
+            let path = path.strip_prefix("b/").unwrap_or(path);
+            files.push(FilePatch {
+                path,
+                hunks: Vec::new(),
+            });
+        } else if line.starts_with("@@ ") {
+            let file = files
+                .last_mut()
+                .ok_or_else(|| invalid("hunk before file header".to_string()))?;
+            let old_start = parse_hunk_start(line)
+                .ok_or_else(|| invalid(format!("malformed hunk header: {}", line)))?;
+
+            let mut hunk = Hunk {
+                old_start,
+                lines: Vec::new(),
+            };
+            while let Some(&next) = lines.peek() {
+                let Some(kind) = next.chars().next().filter(|c| " -+\\".contains(*c)) else {
+                    break;
+                };
+                if next.starts_with("--- ") || next.starts_with("+++ ") {
+                    break;
+                }
+                lines.next();
+                if kind == '\\' {
+                    if let Some(last) = hunk.lines.last_mut() {
+                        last.2 = true;
+                    }
+                } else {
+                    hunk.lines.push((kind, &next[1..], false));
+                }
+            }
+            file.hunks.push(hunk);
+        }
+    }
+
+    Ok(files)
+}
+
+fn apply_file_patch(content: &str, file: &FilePatch<'_>) -> io::Result<String> {
+    let old_lines: Vec<&str> = content.lines().collect();
+    let mut output = String::with_capacity(content.len());
+    let mut next_old = 0;
+    // Whether the last line written came from a hunk and had no newline
+    let mut last_missing_newline = false;
+
+    for hunk in &file.hunks {
+        let start = hunk.old_start.saturating_sub(1);
+        if start < next_old || start > old_lines.len() {
+            return Err(invalid(format!("{}: hunks out of order", file.path)));
+        }
+        for line in &old_lines[next_old..start] {
+            output.push_str(line);
+            output.push('\n');
+        }
+        next_old = start;
+
+        for &(kind, text, no_newline) in &hunk.lines {
+            if kind != '+' {
+                if old_lines.get(next_old) != Some(&text) {
+                    return Err(invalid(format!(
+                        "{}: patch does not apply at line {}",
+                        file.path,
+                        next_old + 1
+                    )));
+                }
+                next_old += 1;
+            }
+            if kind != '-' {
+                output.push_str(text);
+                output.push('\n');
+                last_missing_newline = no_newline;
+            }
+        }
+    }
+
+    let tail = &old_lines[next_old..];
+    for line in tail {
+        output.push_str(line);
+        output.push('\n');
+    }
+
+    let ends_without_newline = if tail.is_empty() {
+        last_missing_newline
+    } else {
+        !content.ends_with('\n')
+    };
+    if ends_without_newline {
+        output.pop();
+    }
+
+    Ok(output)
+}
+
+// Apply a patch produced by `--output patch` to the files under `root`,
+// returning the files that were changed
+pub fn apply_patch(root: &Path, patch: &str) -> io::Result<Vec<PathBuf>> {
+    let files = parse_patch(patch)?;
+
+    // Compute every result first so a bad hunk leaves all files untouched
+    let mut results = Vec::with_capacity(files.len());
+    for file in &files {
+        let relative = Path::new(file.path);
+        let inside_root = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if !inside_root {
+            return Err(invalid(format!("{} is outside the root", file.path)));
+        }
+        let path = root.join(relative);
+        let content = fs::read_to_string(&path)?;
+        results.push((path, apply_file_patch(&content, file)?));
+    }
+
+    for (path, content) in &results {
+        atomic_write(path, content.as_bytes())?;
+    }
+
+    Ok(results.into_iter().map(|(path, _)| path).collect())
+}
@@ -0,0 +1,49 @@
+//! CPU profiles of a run for `--profile`, which needs the `profile` feature
+//! (`cargo build --release --features profile`).
+//!
+//! The report is a pprof protobuf. `go tool pprof -top umbra-fix profile.pb`
+//! lists the functions that took the most CPU time, and
+//! `go tool pprof -http=:8080 umbra-fix profile.pb` opens a flame graph in
+//! the browser.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use pprof::protos::Message;
+use pprof::{ProfilerGuard, ProfilerGuardBuilder};
+
+/// How many times a second the profiler samples the call stack.
+pub const SAMPLE_FREQUENCY_HZ: i32 = 1000;
+
+/// A running CPU profiler; samples until [`Profiler::write_report`].
+pub struct Profiler {
+    guard: ProfilerGuard<'static>,
+}
+
+impl Profiler {
+    /// Start sampling every thread of the process.
+    pub fn start() -> io::Result<Self> {
+        let guard = ProfilerGuardBuilder::default()
+            .frequency(SAMPLE_FREQUENCY_HZ)
+            .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+            .build()
+            .map_err(|err| io::Error::other(format!("can't start the CPU profiler: {}", err)))?;
+        Ok(Self { guard })
+    }
+
+    /// Stop sampling and write the samples to `path` as a pprof protobuf.
+    pub fn write_report(self, path: &Path) -> io::Result<()> {
+        let profile = self
+            .guard
+            .report()
+            .build()
+            .and_then(|report| report.pprof())
+            .map_err(|err| io::Error::other(format!("can't build the CPU profile: {}", err)))?;
+        let mut bytes = Vec::new();
+        profile
+            .write_to_vec(&mut bytes)
+            .map_err(|err| io::Error::other(format!("can't encode the CPU profile: {}", err)))?;
+        fs::write(path, bytes)
+    }
+}
@@ -0,0 +1,136 @@
+//! Progress of a fix run, saved after every file so an interrupted run can
+//! pick up where it stopped instead of starting over.
+//!
+//! The state file is a log of JSON lines, one per file a run has finished
+//! with, so saving the progress of a file appends one line instead of
+//! rewriting the whole state. Later lines override earlier ones.
+
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::atomic_write::atomic_write;
+
+/// Name of the state file written to the root directory.
+pub const PROGRESS_FILE_NAME: &str = ".umbra-fix-progress.json";
+
+/// Files a run has finished with, relative to the root.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgressState {
+    /// Files that were fixed (or needed no fix).
+    pub completed: HashSet<PathBuf>,
+    /// Files whose processing failed; a resumed run retries them.
+    pub failed: HashSet<PathBuf>,
+}
+
+// One line of the state file
+#[derive(Debug, Serialize, Deserialize)]
+struct Record {
+    file: PathBuf,
+    completed: bool,
+}
+
+impl ProgressState {
+    /// Read the state file at `path`, or `None` if there isn't one. A last
+    /// line cut short by an interrupted write is ignored.
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        let log = match fs::read_to_string(path) {
+            Ok(log) => log,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let mut state = ProgressState::default();
+        let mut lines = log.split_inclusive('\n').enumerate().peekable();
+        while let Some((index, line)) = lines.next() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: Record = match serde_json::from_str(line) {
+                Ok(record) => record,
+                Err(_) if lines.peek().is_none() && !line.ends_with('\n') => break,
+                Err(err) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{}:{}: {}", path.display(), index + 1, err),
+                    ))
+                }
+            };
+            if record.completed {
+                state.mark_completed(&record.file);
+            } else {
+                state.mark_failed(&record.file);
+            }
+        }
+        Ok(Some(state))
+    }
+
+    /// Write the whole state to `path`, replacing its log.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut log = Vec::new();
+        for (files, completed) in [(&self.completed, true), (&self.failed, false)] {
+            for file in files {
+                log.extend(record_line(file, completed)?);
+            }
+        }
+        atomic_write(path, &log)
+    }
+
+    pub fn is_completed(&self, file: &Path) -> bool {
+        self.completed.contains(file)
+    }
+
+    pub fn mark_completed(&mut self, file: &Path) {
+        self.failed.remove(file);
+        self.completed.insert(file.to_path_buf());
+    }
+
+    pub fn mark_failed(&mut self, file: &Path) {
+        self.failed.insert(file.to_path_buf());
+    }
+}
+
+/// The state file of a run in progress, which each finished file is appended
+/// to.
+#[derive(Debug)]
+pub struct ProgressLog {
+    file: File,
+}
+
+impl ProgressLog {
+    /// Open the state file at `path` to add to it, creating it if needed.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(ProgressLog { file })
+    }
+
+    pub fn mark_completed(&mut self, file: &Path) -> io::Result<()> {
+        self.file.write_all(&record_line(file, true)?)
+    }
+
+    pub fn mark_failed(&mut self, file: &Path) -> io::Result<()> {
+        self.file.write_all(&record_line(file, false)?)
+    }
+}
+
+// The line of the state file that records `file`, written in one go so an
+// interrupted run leaves at most the last line incomplete
+fn record_line(file: &Path, completed: bool) -> io::Result<Vec<u8>> {
+    let record = Record {
+        file: file.to_path_buf(),
+        completed,
+    };
+    let mut line = serde_json::to_vec(&record).map_err(io::Error::other)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+/// Delete the state file at `path`, if there is one.
+pub fn remove_state(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
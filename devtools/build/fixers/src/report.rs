@@ -0,0 +1,104 @@
+//! Summaries of a whole run: JSON for `--report-file`, HTML and JUnit XML
+//! for `--output html` and `--output junit`, and annotations for GitHub
+//! Actions. Findings silenced by suppression comments never reach them.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::config::{Config, RunMode};
+use crate::issue::IssueReport;
+
+pub mod github_actions;
+pub mod html;
+pub mod junit;
+pub mod suppress;
+
+/// The per-file reports of one run plus a few totals.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub root: PathBuf,
+    pub mode: RunMode,
+    /// Number of BUILD files analyzed.
+    pub files_scanned: usize,
+    /// Number of files whose content the fixes changed.
+    pub files_modified: usize,
+    /// Total number of findings across all files.
+    pub issue_count: usize,
+    pub files: Vec<IssueReport>,
+}
+
+impl RunReport {
+    pub fn new(config: &Config, reports: &[IssueReport]) -> Self {
+        RunReport {
+            root: config.root_dir.clone(),
+            mode: config.mode,
+            files_scanned: reports.len(),
+            files_modified: reports.iter().filter(|report| report.modified).count(),
+            issue_count: reports.iter().map(|report| report.findings.len()).sum(),
+            files: reports.to_vec(),
+        }
+    }
+
+    /// Write the report as pretty-printed JSON, creating parent directories as needed.
+    pub fn write_to(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.write_all(b"\n")?;
+        writer.flush()
+    }
+}
+
+/// The issues a run leaves behind, which `--assert-no-issues` writes to
+/// stderr as JSON: the findings of `reports` that analyze the files as the
+/// run left them.
+#[derive(Debug, Clone, Serialize)]
+pub struct RemainingIssues {
+    pub remaining: usize,
+    pub issues: Vec<RemainingIssue>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemainingIssue {
+    /// The BUILD file, relative to the root.
+    pub file: PathBuf,
+    pub issue: &'static str,
+    pub fixable: bool,
+    pub message: String,
+}
+
+impl RemainingIssues {
+    pub fn new(root: &Path, reports: &[IssueReport]) -> Self {
+        let issues: Vec<RemainingIssue> = reports
+            .iter()
+            .flat_map(|report| report.findings.iter().map(move |finding| (report, finding)))
+            .map(|(report, finding)| RemainingIssue {
+                file: report
+                    .path
+                    .strip_prefix(root)
+                    .unwrap_or(&report.path)
+                    .to_path_buf(),
+                issue: finding.issue.name(),
+                fixable: finding.issue.is_fixable(),
+                message: finding.message.clone(),
+            })
+            .collect();
+        RemainingIssues {
+            remaining: issues.len(),
+            issues,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
@@ -0,0 +1,110 @@
+//! GitHub Actions workflow commands, which the runner turns into inline
+//! annotations on the pull request.
+
+use std::io::{self, Write};
+
+use lsp_types::DiagnosticSeverity;
+
+use crate::issue::{BuildIssue, Finding};
+use crate::lsp::severity;
+use crate::starlark::calls::top_level_calls;
+use crate::starlark::tokenizer::tokenize;
+
+/// The workflow command an annotation is written with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationLevel {
+    Error,
+    Warning,
+    Notice,
+}
+
+impl AnnotationLevel {
+    /// The name of the workflow command.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AnnotationLevel::Error => "error",
+            AnnotationLevel::Warning => "warning",
+            AnnotationLevel::Notice => "notice",
+        }
+    }
+}
+
+/// Whether the process runs in a GitHub Actions job (`CI` and
+/// `GITHUB_ACTIONS` are both `true`).
+pub fn is_github_actions() -> bool {
+    let set = |name| std::env::var(name).is_ok_and(|value| value == "true");
+    set("CI") && set("GITHUB_ACTIONS")
+}
+
+/// The level of an issue's annotation, following its editor severity.
+pub fn annotation_level(issue: &BuildIssue) -> AnnotationLevel {
+    match severity(issue) {
+        DiagnosticSeverity::ERROR => AnnotationLevel::Error,
+        DiagnosticSeverity::WARNING => AnnotationLevel::Warning,
+        _ => AnnotationLevel::Notice,
+    }
+}
+
+/// The 1-based line and column an issue points at in `content`: the place
+/// it names, the rule name of its target, or the start of the file.
+pub fn issue_position(content: &str, issue: &BuildIssue) -> (usize, usize) {
+    if let Some(position) = issue.position() {
+        return position;
+    }
+    let Some(target) = issue.target() else {
+        return (1, 1);
+    };
+
+    let tokens = tokenize(content);
+    let call = top_level_calls(&tokens)
+        .into_iter()
+        .find(|call| call.target_name(&tokens).as_deref() == Some(target));
+    match call {
+        Some(call) => {
+            let start = tokens[call.open - 1].start;
+            let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+            (call.line, content[line_start..start].chars().count() + 1)
+        }
+        None => (1, 1),
+    }
+}
+
+/// The workflow command annotating `finding` in the file at `path`, whose
+/// content is `content`.
+pub fn annotation(path: &str, content: &str, finding: &Finding) -> String {
+    let (line, col) = issue_position(content, &finding.issue);
+    format!(
+        "::{} file={},line={},col={}::{}",
+        annotation_level(&finding.issue).as_str(),
+        escape_property(path),
+        line,
+        col,
+        escape_data(&finding.to_string())
+    )
+}
+
+/// Write one annotation per finding, one per line.
+pub fn write_annotations(
+    out: &mut impl Write,
+    path: &str,
+    content: &str,
+    findings: &[Finding],
+) -> io::Result<()> {
+    for finding in findings {
+        writeln!(out, "{}", annotation(path, content, finding))?;
+    }
+    Ok(())
+}
+
+// Workflow command messages can't contain raw line breaks or `%`
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+// Property values also can't contain the `:` and `,` that delimit them
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
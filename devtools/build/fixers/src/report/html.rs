@@ -0,0 +1,159 @@
+//! Self-contained HTML report of a run, for `--output html`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+
+use crate::atomic_write::atomic_write;
+use crate::config::Config;
+use crate::issue::IssueReport;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", sans-serif; margin: 2em; color: #1f2328; }
+h1 { font-size: 1.5em; }
+table { border-collapse: collapse; margin-bottom: 1.5em; }
+th, td { border: 1px solid #d0d7de; padding: 4px 10px; text-align: left; }
+th { background: #f6f8fa; }
+td.count { text-align: right; }
+details { border: 1px solid #d0d7de; border-radius: 6px; margin: 0.5em 0; padding: 0.5em 1em; }
+summary { cursor: pointer; font-family: ui-monospace, monospace; }
+.manual { color: #9a6700; }
+ul { padding-left: 1.5em; }
+pre.diff { background: #f6f8fa; padding: 0.5em; overflow-x: auto; }
+pre.diff span { display: block; }
+.diff .add { background: #dafbe1; color: #116329; }
+.diff .del { background: #ffebe9; color: #82071e; }
+.diff .hunk { color: #0550ae; }
+.diff .file { font-weight: bold; }
+"#;
+
+const SCRIPT: &str = r#"
+function toggleAll(open) {
+  document.querySelectorAll("details").forEach(function (d) { d.open = open; });
+}
+"#;
+
+/// Render the reports of a run as a single HTML page with inline CSS and JS.
+pub fn render_html(config: &Config, reports: &[IssueReport]) -> String {
+    let with_issues: Vec<&IssueReport> = reports
+        .iter()
+        .filter(|report| !report.findings.is_empty())
+        .collect();
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for finding in with_issues.iter().flat_map(|report| &report.findings) {
+        *counts.entry(finding.issue.name()).or_default() += 1;
+    }
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>umbra-fix report</title>\n");
+    let _ = writeln!(
+        html,
+        "<style>{}</style>\n<script>{}</script>",
+        STYLE, SCRIPT
+    );
+    html.push_str("</head>\n<body>\n<h1>umbra-fix report</h1>\n");
+    let _ = writeln!(
+        html,
+        "<p>{} BUILD files scanned under <code>{}</code>, {} with issues.</p>",
+        reports.len(),
+        escape(&config.root_dir.display().to_string()),
+        with_issues.len()
+    );
+
+    html.push_str("<table>\n<tr><th>Issue</th><th>Count</th></tr>\n");
+    for (name, count) in &counts {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"count\">{}</td></tr>",
+            name, count
+        );
+    }
+    let total: usize = counts.values().sum();
+    let _ = writeln!(
+        html,
+        "<tr><th>Total</th><th class=\"count\">{}</th></tr>\n</table>",
+        total
+    );
+
+    html.push_str("<p><button onclick=\"toggleAll(true)\">Expand all</button> ");
+    html.push_str("<button onclick=\"toggleAll(false)\">Collapse all</button></p>\n");
+
+    for report in with_issues {
+        let path = report
+            .path
+            .strip_prefix(&config.root_dir)
+            .unwrap_or(&report.path);
+        let _ = writeln!(
+            html,
+            "<details>\n<summary>{} ({} issues)</summary>\n<ul>",
+            escape(&path.display().to_string()),
+            report.findings.len()
+        );
+        for finding in &report.findings {
+            let class = if finding.issue.is_fixable() {
+                ""
+            } else {
+                " class=\"manual\""
+            };
+            let _ = writeln!(
+                html,
+                "<li{}><strong>{}</strong>: {}</li>",
+                class,
+                finding.issue.name(),
+                escape(&finding.message)
+            );
+        }
+        html.push_str("</ul>\n");
+        if let Some(diff) = &report.diff {
+            html.push_str(&render_diff(diff));
+        }
+        html.push_str("</details>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Render the report and write it to `path`.
+pub fn write_html(path: &Path, config: &Config, reports: &[IssueReport]) -> io::Result<()> {
+    atomic_write(path, render_html(config, reports).as_bytes())
+}
+
+// A unified diff with each line classed for highlighting
+fn render_diff(diff: &str) -> String {
+    let mut html = String::from("<pre class=\"diff\">");
+    for line in diff.lines() {
+        let class = if line.starts_with("+++") || line.starts_with("---") {
+            "file"
+        } else if line.starts_with("@@") {
+            "hunk"
+        } else if line.starts_with('+') {
+            "add"
+        } else if line.starts_with('-') {
+            "del"
+        } else {
+            "context"
+        };
+        let _ = write!(html, "<span class=\"{}\">{}</span>", class, escape(line));
+    }
+    html.push_str("</pre>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,88 @@
+//! JUnit XML report of a run, for `--output junit`. Each BUILD file is a
+//! test suite and each finding a failed test case, so CI systems that
+//! aggregate test results can show them.
+
+use std::io;
+use std::path::Path;
+
+use quick_xml::events::{BytesDecl, BytesText, Event};
+use quick_xml::Writer;
+
+use crate::atomic_write::atomic_write;
+use crate::issue::IssueReport;
+
+/// Name of the test case that stands for a file without findings.
+pub const CLEAN_TEST_CASE: &str = "clean";
+
+/// Render the reports as a `<testsuites>` document.
+pub fn render_junit(reports: &[IssueReport]) -> io::Result<String> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+
+    let failures: usize = reports.iter().map(|report| report.findings.len()).sum();
+    let tests: usize = reports
+        .iter()
+        .map(|report| report.findings.len().max(1))
+        .sum();
+    writer
+        .create_element("testsuites")
+        .with_attribute(("name", "umbra-fix"))
+        .with_attribute(("tests", tests.to_string().as_str()))
+        .with_attribute(("failures", failures.to_string().as_str()))
+        .write_inner_content(|writer| {
+            for report in reports {
+                write_suite(writer, report)?;
+            }
+            Ok(())
+        })?;
+
+    String::from_utf8(writer.into_inner()).map_err(io::Error::other)
+}
+
+/// Write the JUnit report of `reports` to `path`.
+pub fn write_junit_report(reports: &[IssueReport], path: &Path) -> io::Result<()> {
+    let mut xml = render_junit(reports)?;
+    xml.push('\n');
+    atomic_write(path, xml.as_bytes())
+}
+
+fn write_suite(writer: &mut Writer<Vec<u8>>, report: &IssueReport) -> io::Result<()> {
+    let path = report.path.display().to_string();
+    let failures = report.findings.len();
+    writer
+        .create_element("testsuite")
+        .with_attribute(("name", path.as_str()))
+        .with_attribute(("tests", failures.max(1).to_string().as_str()))
+        .with_attribute(("failures", failures.to_string().as_str()))
+        .write_inner_content(|writer| {
+            if report.findings.is_empty() {
+                writer
+                    .create_element("testcase")
+                    .with_attribute(("name", CLEAN_TEST_CASE))
+                    .with_attribute(("classname", path.as_str()))
+                    .write_empty()?;
+                return Ok(());
+            }
+            for finding in &report.findings {
+                let kind = if finding.issue.is_fixable() {
+                    "fixable"
+                } else {
+                    "manual"
+                };
+                writer
+                    .create_element("testcase")
+                    .with_attribute(("name", finding.issue.name()))
+                    .with_attribute(("classname", path.as_str()))
+                    .write_inner_content(|writer| {
+                        writer
+                            .create_element("failure")
+                            .with_attribute(("message", finding.message.as_str()))
+                            .with_attribute(("type", kind))
+                            .write_text_content(BytesText::new(&finding.to_string()))?;
+                        Ok(())
+                    })?;
+            }
+            Ok(())
+        })?;
+    Ok(())
+}
@@ -0,0 +1,186 @@
+//! Suppression comments in BUILD files.
+//!
+//! `# umbra-fix: disable=EmptySrcs` silences the listed issues (by
+//! [`BuildIssue::name`], comma-separated) for the rule that follows the
+//! comment. Issues of a target match the rule by its name; those found on a
+//! line match when it is one of the rule's lines, and for a rule without a
+//! `name`, such as `package()`, any issue without a target matches. Issues
+//! about the file as a whole can only be silenced with
+//! `# umbra-fix: disable-file=EmptySrcs`, which silences them anywhere in
+//! the file.
+
+use std::ops::RangeInclusive;
+
+use crate::issue::{BuildIssue, Finding};
+use crate::starlark::calls::top_level_calls;
+use crate::starlark::tokenizer::{tokenize, TokenKind};
+
+/// Marker that starts a suppression comment, after the `#`.
+pub const SUPPRESSION_PREFIX: &str = "umbra-fix:";
+
+/// Where a suppression applies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SuppressionScope {
+    /// The whole file (`disable-file=`).
+    File,
+    /// The rule following the comment (`disable=`): its target name and the
+    /// lines from its name to its closing `)`.
+    Rule {
+        target: Option<String>,
+        lines: Option<RangeInclusive<usize>>,
+    },
+}
+
+/// One issue name of a suppression comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    pub issue: String,
+    pub scope: SuppressionScope,
+    /// 1-based line of the comment.
+    pub line: usize,
+}
+
+impl Suppression {
+    /// Whether this suppresses `issue`.
+    pub fn suppresses(&self, issue: &BuildIssue) -> bool {
+        if self.issue != issue.name() {
+            return false;
+        }
+        match &self.scope {
+            SuppressionScope::File => true,
+            SuppressionScope::Rule { target, lines } => match issue.target() {
+                Some(issue_target) => target.as_deref() == Some(issue_target),
+                None if target.is_none() => true,
+                None => match (issue.position(), lines) {
+                    (Some((line, _)), Some(lines)) => lines.contains(&line),
+                    _ => false,
+                },
+            },
+        }
+    }
+}
+
+/// The suppressions in the comments of `content`, in file order.
+pub fn parse_suppressions(content: &str) -> Vec<Suppression> {
+    let tokens = tokenize(content);
+    let calls: Vec<_> = top_level_calls(&tokens)
+        .into_iter()
+        .filter(|call| call.name != "load")
+        .collect();
+
+    let mut suppressions = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Comment {
+            continue;
+        }
+        let Some(directive) = token
+            .text
+            .trim_start_matches('#')
+            .trim()
+            .strip_prefix(SUPPRESSION_PREFIX)
+        else {
+            continue;
+        };
+        let directive = directive.trim();
+        let (names, scope) = if let Some(names) = directive.strip_prefix("disable-file=") {
+            (names, SuppressionScope::File)
+        } else if let Some(names) = directive.strip_prefix("disable=") {
+            // The rule that comes after the comment
+            let rule = calls.iter().find(|call| call.open > index);
+            let scope = SuppressionScope::Rule {
+                target: rule.and_then(|call| call.target_name(&tokens)),
+                lines: rule.map(|call| call.line..=tokens[call.close].line),
+            };
+            (names, scope)
+        } else {
+            continue;
+        };
+
+        suppressions.extend(
+            names
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(|name| Suppression {
+                    issue: name.to_string(),
+                    scope: scope.clone(),
+                    line: token.line,
+                }),
+        );
+    }
+
+    suppressions
+}
+
+// Drop the findings a suppression comment in `content` silences, and report
+// each suppression that silences none of them
+pub fn apply_suppressions(content: &str, findings: Vec<Finding>) -> Vec<Finding> {
+    let suppressions = parse_suppressions(content);
+    if suppressions.is_empty() {
+        return findings;
+    }
+
+    let mut used = vec![false; suppressions.len()];
+    let mut kept: Vec<Finding> = findings
+        .into_iter()
+        .filter(|finding| {
+            let mut suppressed = false;
+            for (suppression, used) in suppressions.iter().zip(used.iter_mut()) {
+                if suppression.suppresses(&finding.issue) {
+                    *used = true;
+                    suppressed = true;
+                }
+            }
+            !suppressed
+        })
+        .collect();
+
+    // Issues about the whole file, which a rule's suppression can't silence
+    let file_wide: Vec<&str> = kept
+        .iter()
+        .filter(|finding| finding.issue.target().is_none() && finding.issue.position().is_none())
+        .map(|finding| finding.issue.name())
+        .collect();
+
+    let unused: Vec<Suppression> = suppressions
+        .into_iter()
+        .zip(used)
+        .filter(|(_, used)| !used)
+        .map(|(suppression, _)| suppression)
+        .collect();
+    kept.extend(unused.into_iter().map(|suppression| {
+        let message = if matches!(suppression.scope, SuppressionScope::Rule { .. })
+            && file_wide.contains(&suppression.issue.as_str())
+        {
+            format!(
+                "line {} disables {} for one rule, but it is reported for the whole file; \
+                 use disable-file={}",
+                suppression.line, suppression.issue, suppression.issue
+            )
+        } else {
+            format!(
+                "line {} disables {}, which isn't reported {}",
+                suppression.line,
+                suppression.issue,
+                match &suppression.scope {
+                    SuppressionScope::File => "in this file".to_string(),
+                    SuppressionScope::Rule {
+                        target: Some(target),
+                        ..
+                    } => format!("for {:?}", target),
+                    SuppressionScope::Rule { target: None, .. } => {
+                        "for the rule below it".to_string()
+                    }
+                }
+            )
+        };
+        Finding {
+            message,
+            issue: BuildIssue::UnusedSuppression {
+                issue: suppression.issue,
+                line: suppression.line,
+            },
+        }
+    }));
+    kept
+}
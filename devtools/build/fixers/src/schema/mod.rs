@@ -0,0 +1,3 @@
+//! Typed representations of rule attributes, validated from the syntax tree.
+
+pub mod swift_library;
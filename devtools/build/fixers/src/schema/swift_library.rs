@@ -0,0 +1,156 @@
+//! The attributes of a `swift_library` rule, with their types checked.
+//!
+//! Checks of `swift_library` targets read the rule through
+//! [`parse_swift_library`] instead of picking attributes out one by one, so
+//! a malformed rule is skipped consistently rather than half-analyzed; it is
+//! reported as `InvalidSwiftLibrary` instead.
+
+use std::fmt;
+
+use crate::starlark::ast::{AttrValue, Attribute};
+
+/// A `swift_library` rule. List attributes keep their expression, since they
+/// may be a `glob()`, a `select()`, a variable or a concatenation of those.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwiftLibraryRule {
+    pub name: String,
+    pub module_name: Option<String>,
+    pub srcs: Option<AttrValue>,
+    pub hdrs: Option<AttrValue>,
+    pub deps: Option<AttrValue>,
+    pub data: Option<AttrValue>,
+    pub copts: Option<AttrValue>,
+    pub linkopts: Option<AttrValue>,
+    pub features: Option<AttrValue>,
+    pub swift_settings: Option<AttrValue>,
+    pub generates_header: Option<bool>,
+    pub generated_header_name: Option<String>,
+    pub testonly: Option<bool>,
+    pub visibility: Option<AttrValue>,
+}
+
+/// Why an attribute of a `swift_library` is invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttributeError {
+    /// A required attribute is not set.
+    Missing { attribute: &'static str },
+    /// The value isn't of the attribute's type (`expected` describes it).
+    WrongType {
+        attribute: String,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for AttributeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeError::Missing { attribute } => write!(f, "missing attribute {}", attribute),
+            AttributeError::WrongType {
+                attribute,
+                expected,
+            } => write!(f, "{} should be {}", attribute, expected),
+        }
+    }
+}
+
+impl std::error::Error for AttributeError {}
+
+impl SwiftLibraryRule {
+    /// Whether the rule sets `testonly = True`.
+    pub fn is_testonly(&self) -> bool {
+        self.testonly == Some(true)
+    }
+}
+
+/// Read the attributes of a `swift_library` call, reporting every attribute
+/// that is missing or has the wrong type. Attributes not in
+/// [`SwiftLibraryRule`] are not checked.
+pub fn parse_swift_library(attrs: &[Attribute]) -> Result<SwiftLibraryRule, Vec<AttributeError>> {
+    let mut errors = Vec::new();
+    let attr = |key: &str| attrs.iter().find(|attr| attr.key == key);
+
+    let name = match attr("name") {
+        Some(name) => string_attr(Some(name), &mut errors),
+        None => {
+            errors.push(AttributeError::Missing { attribute: "name" });
+            None
+        }
+    };
+    let rule = SwiftLibraryRule {
+        name: name.unwrap_or_default(),
+        module_name: string_attr(attr("module_name"), &mut errors),
+        srcs: list_attr(attr("srcs"), &mut errors),
+        hdrs: list_attr(attr("hdrs"), &mut errors),
+        deps: list_attr(attr("deps"), &mut errors),
+        data: list_attr(attr("data"), &mut errors),
+        copts: list_attr(attr("copts"), &mut errors),
+        linkopts: list_attr(attr("linkopts"), &mut errors),
+        features: list_attr(attr("features"), &mut errors),
+        swift_settings: list_attr(attr("swift_settings"), &mut errors),
+        generates_header: bool_attr(attr("generates_header"), &mut errors),
+        generated_header_name: string_attr(attr("generated_header_name"), &mut errors),
+        testonly: bool_attr(attr("testonly"), &mut errors),
+        visibility: list_attr(attr("visibility"), &mut errors),
+    };
+
+    if errors.is_empty() {
+        Ok(rule)
+    } else {
+        Err(errors)
+    }
+}
+
+fn string_attr(attr: Option<&Attribute>, errors: &mut Vec<AttributeError>) -> Option<String> {
+    let attr = attr?;
+    match &attr.value {
+        AttrValue::String(value) => Some(value.clone()),
+        _ => {
+            errors.push(wrong_type(attr, "a string"));
+            None
+        }
+    }
+}
+
+// Bazel also takes 0 and 1 for booleans, as older BUILD files use
+fn bool_attr(attr: Option<&Attribute>, errors: &mut Vec<AttributeError>) -> Option<bool> {
+    let attr = attr?;
+    match &attr.value {
+        AttrValue::Ident(value) if value == "True" => Some(true),
+        AttrValue::Ident(value) if value == "False" => Some(false),
+        AttrValue::Number(value) if value == "1" => Some(true),
+        AttrValue::Number(value) if value == "0" => Some(false),
+        _ => {
+            errors.push(wrong_type(attr, "True or False"));
+            None
+        }
+    }
+}
+
+fn list_attr(attr: Option<&Attribute>, errors: &mut Vec<AttributeError>) -> Option<AttrValue> {
+    let attr = attr?;
+    if is_list(&attr.value) {
+        Some(attr.value.clone())
+    } else {
+        errors.push(wrong_type(attr, "a list"));
+        None
+    }
+}
+
+// Whether `value` can evaluate to a list. Calls and variables can't be
+// evaluated, so they are given the benefit of the doubt.
+fn is_list(value: &AttrValue) -> bool {
+    match value {
+        AttrValue::List(_) | AttrValue::Call { .. } => true,
+        AttrValue::Ident(name) => !matches!(name.as_str(), "True" | "False" | "None"),
+        AttrValue::Concat(operands) => operands.iter().all(is_list),
+        AttrValue::Select(branches) => branches.iter().all(|(_, value)| is_list(value)),
+        AttrValue::String(_) | AttrValue::Number(_) | AttrValue::Dict(_) => false,
+    }
+}
+
+fn wrong_type(attr: &Attribute, expected: &'static str) -> AttributeError {
+    AttributeError::WrongType {
+        attribute: attr.key.clone(),
+        expected,
+    }
+}
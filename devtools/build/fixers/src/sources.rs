@@ -0,0 +1,137 @@
+//! Discovery of the Swift sources that belong to a Bazel package.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::discovery::is_package;
+use crate::glob::glob_match;
+
+// Collect the Swift files in a package directory, relative to it. Directories
+// with their own BUILD file are separate packages and are not descended into.
+pub fn collect_swift_files(package_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let walker = WalkDir::new(package_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0 || !entry.file_type().is_dir() || !is_package(entry.path())
+        });
+
+    for entry in walker {
+        let entry = entry.map_err(io::Error::other)?;
+        let is_swift = entry.path().extension().is_some_and(|ext| ext == "swift");
+        if entry.file_type().is_file() && is_swift {
+            let relative = entry
+                .path()
+                .strip_prefix(package_dir)
+                .map_err(io::Error::other)?;
+            files.push(relative.to_path_buf());
+        }
+    }
+
+    Ok(files)
+}
+
+// Collect every file in a package directory, relative to it, skipping nested
+// packages and hidden entries
+pub fn collect_package_files(package_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let walker = WalkDir::new(package_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !(entry.file_name().to_string_lossy().starts_with('.')
+                    || entry.file_type().is_dir() && is_package(entry.path()))
+        });
+
+    for entry in walker {
+        let entry = entry.map_err(io::Error::other)?;
+        if entry.file_type().is_file() {
+            let relative = entry
+                .path()
+                .strip_prefix(package_dir)
+                .map_err(io::Error::other)?;
+            files.push(relative.to_path_buf());
+        }
+    }
+
+    Ok(files)
+}
+
+// The package's Swift files that match any of `patterns` (glob patterns or
+// plain file names, relative to the package directory)
+pub fn matching_swift_files(package_dir: &Path, patterns: &[String]) -> io::Result<Vec<PathBuf>> {
+    Ok(collect_swift_files(package_dir)?
+        .into_iter()
+        .filter(|file| {
+            let file = file.to_string_lossy().replace('\\', "/");
+            patterns.iter().any(|pattern| glob_match(pattern, &file))
+        })
+        .collect())
+}
+
+/// Extensions of resource files and bundles a Swift module may load at runtime.
+pub const RESOURCE_EXTENSIONS: &[&str] = &["xcassets", "strings", "json", "plist", "lproj"];
+
+// Collect the resource files and bundle directories (such as `.xcassets`) in a
+// package directory, relative to it. Nested packages are skipped, as are the
+// contents of a matched bundle directory.
+pub fn collect_resources(package_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut resources = Vec::new();
+    let mut walker = WalkDir::new(package_dir)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter();
+
+    while let Some(entry) = walker.next() {
+        let entry = entry.map_err(io::Error::other)?;
+        let is_dir = entry.file_type().is_dir();
+        if is_dir && is_package(entry.path()) {
+            walker.skip_current_dir();
+            continue;
+        }
+        let is_resource = entry
+            .path()
+            .extension()
+            .is_some_and(|ext| RESOURCE_EXTENSIONS.iter().any(|known| ext == *known));
+        if is_resource {
+            if is_dir {
+                walker.skip_current_dir();
+            }
+            let relative = entry
+                .path()
+                .strip_prefix(package_dir)
+                .map_err(io::Error::other)?;
+            resources.push(relative.to_path_buf());
+        }
+    }
+
+    Ok(resources)
+}
+
+// Pick the narrowest glob pattern that covers all of the given sources
+pub fn determine_best_glob_pattern(files: &[PathBuf]) -> String {
+    if files
+        .iter()
+        .all(|file| file.parent() == Some(Path::new("")))
+    {
+        return "*.swift".to_string();
+    }
+
+    let top_dirs: Vec<_> = files
+        .iter()
+        .map(|file| file.components().next().map(|c| c.as_os_str()))
+        .collect();
+    let nested_in_one_dir = files.iter().all(|file| file.components().count() > 1)
+        && top_dirs.windows(2).all(|pair| pair[0] == pair[1]);
+
+    match top_dirs.first() {
+        Some(Some(dir)) if nested_in_one_dir => {
+            format!("{}/**/*.swift", dir.to_string_lossy())
+        }
+        _ => "**/*.swift".to_string(),
+    }
+}
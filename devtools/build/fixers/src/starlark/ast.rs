@@ -0,0 +1,447 @@
+//! A minimal syntax tree for BUILD files: loads, the package declaration and
+//! rule calls with their attribute values.
+//!
+//! New checks should read the file through [`parse`] rather than matching
+//! its text. The tree keeps the line and byte range of each rule so fixes can
+//! still splice edits into the original content. Top-level assignments are
+//! parsed but not kept; constructs BUILD files rarely use (comprehensions,
+//! conditional expressions, operators other than `+`) are parse errors.
+
+use std::fmt;
+use std::ops::Range;
+
+use super::tokenizer::{tokenize, Token, TokenKind};
+
+/// A parsed BUILD file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildFile {
+    pub loads: Vec<LoadStatement>,
+    pub package: Option<PackageDeclaration>,
+    pub rules: Vec<RuleCall>,
+}
+
+/// `load("//label.bzl", "symbol", alias = "symbol")`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadStatement {
+    pub label: String,
+    pub symbols: Vec<LoadedSymbol>,
+    /// 1-based line of `load`.
+    pub line: usize,
+}
+
+/// A symbol brought in by `load()`, under its own name or an alias.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadedSymbol {
+    /// Name bound in the BUILD file.
+    pub local: String,
+    /// Name exported by the bzl file.
+    pub exported: String,
+}
+
+/// The `package(...)` call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageDeclaration {
+    pub attrs: Vec<Attribute>,
+    /// 1-based line of `package`.
+    pub line: usize,
+}
+
+/// A top-level call other than `load()` and `package()`, usually a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleCall {
+    pub rule_name: String,
+    /// Positional arguments, as in `exports_files(["Info.plist"])`.
+    pub args: Vec<AttrValue>,
+    pub attrs: Vec<Attribute>,
+    /// 1-based line of the rule name.
+    pub line: usize,
+    /// Byte range from the rule name to the closing `)`.
+    pub span: Range<usize>,
+}
+
+/// A keyword argument: `key = value`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribute {
+    pub key: String,
+    pub value: AttrValue,
+}
+
+/// The value of an attribute (or of an element nested in one).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttrValue {
+    String(String),
+    /// A number literal, as written.
+    Number(String),
+    /// A name such as `True`, `None` or a variable (`COPTS`, `native.x`).
+    Ident(String),
+    List(Vec<AttrValue>),
+    Dict(Vec<(AttrValue, AttrValue)>),
+    /// A function call other than `select()`, such as `glob([...])`.
+    Call {
+        function: String,
+        args: Vec<AttrValue>,
+        attrs: Vec<Attribute>,
+    },
+    /// `select({"condition": value, ...})`.
+    Select(Vec<(String, AttrValue)>),
+    /// Operands joined with `+`.
+    Concat(Vec<AttrValue>),
+}
+
+impl RuleCall {
+    /// The value of the attribute named `key`.
+    pub fn attr(&self, key: &str) -> Option<&AttrValue> {
+        self.attrs
+            .iter()
+            .find(|attr| attr.key == key)
+            .map(|attr| &attr.value)
+    }
+
+    /// The `name` attribute, if it is a string literal.
+    pub fn name(&self) -> Option<&str> {
+        self.attr("name").and_then(AttrValue::as_str)
+    }
+}
+
+impl AttrValue {
+    /// The string, if this is a string literal.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AttrValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Why a file couldn't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    /// 1-based line of the offending token (the last line at end of file).
+    pub line: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse a BUILD file.
+pub fn parse(content: &str) -> Result<BuildFile, ParseError> {
+    let tokens: Vec<Token<'_>> = tokenize(content)
+        .into_iter()
+        .filter(|token| token.kind != TokenKind::Comment)
+        .collect();
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+    };
+    let mut file = BuildFile::default();
+
+    while parser.peek().is_some() {
+        parser.statement(&mut file)?;
+    }
+
+    Ok(file)
+}
+
+struct Parser<'t, 'a> {
+    tokens: &'t [Token<'a>],
+    pos: usize,
+}
+
+// A parsed argument list
+struct Arguments {
+    args: Vec<AttrValue>,
+    attrs: Vec<Attribute>,
+    close: usize,
+}
+
+impl<'a> Parser<'_, 'a> {
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_kind(&self, offset: usize) -> Option<TokenKind> {
+        self.tokens.get(self.pos + offset).map(|token| token.kind)
+    }
+
+    fn next(&mut self) -> Result<Token<'a>, ParseError> {
+        let token = *self
+            .peek()
+            .ok_or_else(|| self.error("unexpected end of file"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, kind: TokenKind, what: &str) -> Result<Token<'a>, ParseError> {
+        match self.peek() {
+            Some(token) if token.kind == kind => self.next(),
+            Some(token) => Err(self.error(&format!("expected {}, found {:?}", what, token.text))),
+            None => Err(self.error(&format!("expected {}, found end of file", what))),
+        }
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        let line = self
+            .peek()
+            .or(self.tokens.last())
+            .map_or(1, |token| token.line);
+        ParseError {
+            message: message.to_string(),
+            line,
+        }
+    }
+
+    fn statement(&mut self, file: &mut BuildFile) -> Result<(), ParseError> {
+        let token = *self
+            .peek()
+            .ok_or_else(|| self.error("unexpected end of file"))?;
+        if token.kind == TokenKind::Ident && self.peek_kind(1) == Some(TokenKind::Equals) {
+            self.pos += 2;
+            self.expression()?;
+            return Ok(());
+        }
+        // A dotted name such as `selects.config_setting_group` can be a rule too
+        let mut rule_name = token.text.to_string();
+        let mut offset = 1;
+        while token.kind == TokenKind::Ident
+            && self.peek_kind(offset) == Some(TokenKind::Dot)
+            && self.peek_kind(offset + 1) == Some(TokenKind::Ident)
+        {
+            rule_name.push('.');
+            rule_name.push_str(self.tokens[self.pos + offset + 1].text);
+            offset += 2;
+        }
+        if token.kind != TokenKind::Ident || self.peek_kind(offset) != Some(TokenKind::LParen) {
+            if matches!(token.text, "def" | "if" | "for" | "return") {
+                return Err(self.error(&format!("{:?} statements are not supported", token.text)));
+            }
+            self.expression()?;
+            return Ok(());
+        }
+
+        self.pos += offset + 1;
+        let arguments = self.arguments()?;
+        match rule_name.as_str() {
+            "load" => file.loads.push(self.load(token, arguments)?),
+            "package" if file.package.is_some() => {
+                return Err(ParseError {
+                    message: "package() is declared twice".to_string(),
+                    line: token.line,
+                });
+            }
+            "package" => {
+                file.package = Some(PackageDeclaration {
+                    attrs: arguments.attrs,
+                    line: token.line,
+                })
+            }
+            _ => file.rules.push(RuleCall {
+                rule_name,
+                args: arguments.args,
+                attrs: arguments.attrs,
+                line: token.line,
+                span: token.start..self.tokens[arguments.close].end(),
+            }),
+        }
+        Ok(())
+    }
+
+    fn load(&self, token: Token<'a>, arguments: Arguments) -> Result<LoadStatement, ParseError> {
+        let error = |message: &str| ParseError {
+            message: message.to_string(),
+            line: token.line,
+        };
+        let mut args = arguments.args.into_iter();
+        let label = match args.next() {
+            Some(AttrValue::String(label)) => label,
+            _ => return Err(error("load() needs a label string first")),
+        };
+
+        let mut symbols = Vec::new();
+        for arg in args {
+            let AttrValue::String(symbol) = arg else {
+                return Err(error("load() symbols must be strings"));
+            };
+            symbols.push(LoadedSymbol {
+                local: symbol.clone(),
+                exported: symbol,
+            });
+        }
+        for attr in arguments.attrs {
+            let AttrValue::String(exported) = attr.value else {
+                return Err(error("load() aliases must be strings"));
+            };
+            symbols.push(LoadedSymbol {
+                local: attr.key,
+                exported,
+            });
+        }
+
+        Ok(LoadStatement {
+            label,
+            symbols,
+            line: token.line,
+        })
+    }
+
+    // Parse the arguments after an opening `(`, through the closing `)`
+    fn arguments(&mut self) -> Result<Arguments, ParseError> {
+        let mut args = Vec::new();
+        let mut attrs = Vec::new();
+
+        loop {
+            if self.peek_kind(0) == Some(TokenKind::RParen) {
+                let close = self.pos;
+                self.pos += 1;
+                return Ok(Arguments { args, attrs, close });
+            }
+            let token = *self.peek().ok_or_else(|| self.error("unclosed ("))?;
+            if token.kind == TokenKind::Ident && self.peek_kind(1) == Some(TokenKind::Equals) {
+                self.pos += 2;
+                attrs.push(Attribute {
+                    key: token.text.to_string(),
+                    value: self.expression()?,
+                });
+            } else if token.kind == TokenKind::Operator && token.text.starts_with('*') {
+                return Err(self.error("*args and **kwargs are not supported"));
+            } else if !attrs.is_empty() {
+                return Err(self.error("positional argument follows keyword argument"));
+            } else {
+                args.push(self.expression()?);
+            }
+            if self.peek_kind(0) != Some(TokenKind::RParen) {
+                self.expect(TokenKind::Comma, "',' or ')'")?;
+            }
+        }
+    }
+
+    fn expression(&mut self) -> Result<AttrValue, ParseError> {
+        let mut operands = vec![self.operand()?];
+        while let Some(token) = self.peek() {
+            match token.kind {
+                TokenKind::Operator if token.text == "+" => {
+                    self.pos += 1;
+                    operands.push(self.operand()?);
+                }
+                TokenKind::Operator => {
+                    return Err(self.error(&format!("operator {:?} is not supported", token.text)))
+                }
+                TokenKind::Ident if matches!(token.text, "if" | "for") => {
+                    return Err(
+                        self.error(&format!("{:?} expressions are not supported", token.text))
+                    )
+                }
+                _ => break,
+            }
+        }
+
+        Ok(if operands.len() == 1 {
+            operands.remove(0)
+        } else {
+            AttrValue::Concat(operands)
+        })
+    }
+
+    fn operand(&mut self) -> Result<AttrValue, ParseError> {
+        let token = self.next()?;
+        match token.kind {
+            TokenKind::String => Ok(AttrValue::String(token.string_value().unwrap_or_default())),
+            TokenKind::Number => Ok(AttrValue::Number(token.text.to_string())),
+            TokenKind::Operator
+                if token.text == "-" && self.peek_kind(0) == Some(TokenKind::Number) =>
+            {
+                let number = self.next()?;
+                Ok(AttrValue::Number(format!("-{}", number.text)))
+            }
+            TokenKind::LBracket => self.list(),
+            TokenKind::LBrace => self.dict(),
+            TokenKind::LParen => {
+                let value = self.expression()?;
+                if self.peek_kind(0) == Some(TokenKind::Comma) {
+                    return Err(self.error("tuples are not supported"));
+                }
+                self.expect(TokenKind::RParen, "')'")?;
+                Ok(value)
+            }
+            TokenKind::Ident => self.name_or_call(token),
+            _ => {
+                self.pos -= 1;
+                Err(self.error(&format!("unexpected {:?}", token.text)))
+            }
+        }
+    }
+
+    // A possibly dotted name, called if followed by `(`
+    fn name_or_call(&mut self, first: Token<'a>) -> Result<AttrValue, ParseError> {
+        let mut name = first.text.to_string();
+        while self.peek_kind(0) == Some(TokenKind::Dot) {
+            self.pos += 1;
+            name.push('.');
+            name.push_str(self.expect(TokenKind::Ident, "a name after '.'")?.text);
+        }
+        if self.peek_kind(0) != Some(TokenKind::LParen) {
+            return Ok(AttrValue::Ident(name));
+        }
+
+        let line = first.line;
+        self.pos += 1;
+        let Arguments { args, attrs, .. } = self.arguments()?;
+        if name != "select" {
+            return Ok(AttrValue::Call {
+                function: name,
+                args,
+                attrs,
+            });
+        }
+
+        let error = |message: &str| ParseError {
+            message: message.to_string(),
+            line,
+        };
+        let Some(AttrValue::Dict(entries)) = args.into_iter().next() else {
+            return Err(error("select() needs a dict of conditions"));
+        };
+        entries
+            .into_iter()
+            .map(|(condition, value)| match condition {
+                AttrValue::String(condition) => Ok((condition, value)),
+                _ => Err(error("select() conditions must be strings")),
+            })
+            .collect::<Result<_, _>>()
+            .map(AttrValue::Select)
+    }
+
+    fn list(&mut self) -> Result<AttrValue, ParseError> {
+        let mut elements = Vec::new();
+        while self.peek_kind(0) != Some(TokenKind::RBracket) {
+            elements.push(self.expression()?);
+            if self.peek().is_some_and(|token| token.is_ident("for")) {
+                return Err(self.error("list comprehensions are not supported"));
+            }
+            if self.peek_kind(0) != Some(TokenKind::RBracket) {
+                self.expect(TokenKind::Comma, "',' or ']'")?;
+            }
+        }
+        self.pos += 1;
+        Ok(AttrValue::List(elements))
+    }
+
+    fn dict(&mut self) -> Result<AttrValue, ParseError> {
+        let mut entries = Vec::new();
+        while self.peek_kind(0) != Some(TokenKind::RBrace) {
+            let key = self.expression()?;
+            self.expect(TokenKind::Colon, "':'")?;
+            entries.push((key, self.expression()?));
+            if self.peek_kind(0) != Some(TokenKind::RBrace) {
+                self.expect(TokenKind::Comma, "',' or '}'")?;
+            }
+        }
+        self.pos += 1;
+        Ok(AttrValue::Dict(entries))
+    }
+}
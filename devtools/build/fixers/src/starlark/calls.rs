@@ -0,0 +1,299 @@
+//! Token-level access to top-level rule calls such as `swift_library(...)`.
+
+use std::ops::Range;
+
+use super::tokenizer::{find_matching, Token, TokenKind};
+
+/// A top-level call expression, identified by token indices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call<'a> {
+    pub name: &'a str,
+    /// Index of the `(` token.
+    pub open: usize,
+    /// Index of the matching `)` token.
+    pub close: usize,
+    /// 1-based line of the rule name.
+    pub line: usize,
+}
+
+/// One argument of a call. `value` is the token range of the expression,
+/// excluding surrounding comments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Argument<'a> {
+    pub key: Option<&'a str>,
+    pub value: Range<usize>,
+}
+
+// Find every call made at the top level of the file (rules, macros, load()...)
+pub fn top_level_calls<'a>(tokens: &[Token<'a>]) -> Vec<Call<'a>> {
+    let mut calls = Vec::new();
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let token = tokens[index];
+        let is_call = token.kind == TokenKind::Ident
+            && tokens.get(index + 1).map(|t| t.kind) == Some(TokenKind::LParen);
+
+        match token.kind {
+            _ if is_call => {
+                let Some(close) = find_matching(tokens, index + 1) else {
+                    break;
+                };
+                calls.push(Call {
+                    name: token.text,
+                    open: index + 1,
+                    close,
+                    line: token.line,
+                });
+                index = close + 1;
+            }
+            TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace => {
+                index = find_matching(tokens, index).map_or(tokens.len(), |close| close + 1);
+            }
+            _ => index += 1,
+        }
+    }
+
+    calls
+}
+
+impl<'a> Call<'a> {
+    pub fn arguments(&self, tokens: &[Token<'a>]) -> Vec<Argument<'a>> {
+        let mut arguments = Vec::new();
+        let mut index = self.open + 1;
+
+        while index < self.close {
+            // Find the comma that ends this argument, skipping nested brackets
+            let mut end = index;
+            while end < self.close && tokens[end].kind != TokenKind::Comma {
+                end = match tokens[end].kind {
+                    TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace => {
+                        find_matching(tokens, end).map_or(self.close, |close| close + 1)
+                    }
+                    _ => end + 1,
+                };
+            }
+            let end = end.min(self.close);
+
+            let significant: Vec<usize> = (index..end)
+                .filter(|&i| tokens[i].kind != TokenKind::Comment)
+                .collect();
+            if let (Some(&first), Some(&last)) = (significant.first(), significant.last()) {
+                let is_keyword = tokens[first].kind == TokenKind::Ident
+                    && first < last
+                    && tokens[first + 1].kind == TokenKind::Equals;
+                arguments.push(if is_keyword {
+                    let value_start = (first + 2..=last)
+                        .find(|&i| tokens[i].kind != TokenKind::Comment)
+                        .unwrap_or(last + 1);
+                    Argument {
+                        key: Some(tokens[first].text),
+                        value: value_start..last + 1,
+                    }
+                } else {
+                    Argument {
+                        key: None,
+                        value: first..last + 1,
+                    }
+                });
+            }
+
+            index = end + 1;
+        }
+
+        arguments
+    }
+
+    /// The keyword argument named `key`, if present.
+    pub fn keyword(&self, tokens: &[Token<'a>], key: &str) -> Option<Argument<'a>> {
+        self.arguments(tokens)
+            .into_iter()
+            .find(|argument| argument.key == Some(key))
+    }
+
+    /// The value of `key` when it is a single string literal.
+    pub fn string_attr(&self, tokens: &[Token<'a>], key: &str) -> Option<String> {
+        let argument = self.keyword(tokens, key)?;
+        match &tokens[argument.value.clone()] {
+            [token] => token.string_value(),
+            _ => None,
+        }
+    }
+
+    /// The `name` attribute of the rule.
+    pub fn target_name(&self, tokens: &[Token<'a>]) -> Option<String> {
+        self.string_attr(tokens, "name")
+    }
+}
+
+impl Argument<'_> {
+    /// Byte range of the value expression in the source.
+    pub fn byte_range(&self, tokens: &[Token<'_>]) -> Range<usize> {
+        if self.value.is_empty() {
+            let at = tokens[self.value.start].start;
+            return at..at;
+        }
+        tokens[self.value.start].start..tokens[self.value.end - 1].end()
+    }
+}
+
+// Leading whitespace of the line containing `offset`
+pub fn line_indent(content: &str, offset: usize) -> &str {
+    let line_start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = &content[line_start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+// Insert `attribute` (e.g. `testonly = True`) as the argument after `name`,
+// on its own line when the call spans several lines.
+pub fn insert_after_name(
+    content: &str,
+    tokens: &[Token<'_>],
+    call: &Call<'_>,
+    attribute: &str,
+) -> String {
+    let arguments = call.arguments(tokens);
+    let anchor = arguments
+        .iter()
+        .find(|argument| argument.key == Some("name"))
+        .or(arguments.first());
+
+    let Some(anchor) = anchor else {
+        let at = tokens[call.open].end();
+        return format!("{}{}{}", &content[..at], attribute, &content[at..]);
+    };
+
+    let value_end = anchor.value.end.max(1) - 1;
+    let after_value = value_end + 1;
+    let multiline = tokens[call.close].line > tokens[call.open].line;
+    let value_range = anchor.byte_range(tokens);
+
+    let (at, insertion) = if tokens[after_value].kind == TokenKind::Comma {
+        let at = tokens[after_value].end();
+        if multiline {
+            // Keep any trailing comment on the name line where it is
+            let line_end = content[at..].find('\n').map_or(content.len(), |i| at + i);
+            let indent = line_indent(content, value_range.start);
+            (line_end, format!("\n{}{},", indent, attribute))
+        } else {
+            (at, format!(" {},", attribute))
+        }
+    } else if multiline {
+        let indent = line_indent(content, value_range.start);
+        (value_range.end, format!(",\n{}{},", indent, attribute))
+    } else {
+        (value_range.end, format!(", {}", attribute))
+    };
+
+    format!("{}{}{}", &content[..at], insertion, &content[at..])
+}
+
+// Byte range to delete so the list element at `index` disappears cleanly:
+// its whole line when it sits on a line of its own, otherwise the element
+// and one neighbouring comma
+pub fn element_removal_range(content: &str, tokens: &[Token<'_>], index: usize) -> (usize, usize) {
+    span_removal_range(content, tokens, index, index)
+}
+
+// Like `element_removal_range`, for an element spanning the tokens `first`
+// to `last` (such as a `key = value` argument)
+pub fn span_removal_range(
+    content: &str,
+    tokens: &[Token<'_>],
+    first: usize,
+    last: usize,
+) -> (usize, usize) {
+    let (start, end) = (tokens[first].start, tokens[last].end());
+    let comma = tokens
+        .get(last + 1)
+        .filter(|t| t.kind == TokenKind::Comma)
+        .map(|_| last + 1);
+    let final_token = comma.unwrap_or(last);
+    let next = &tokens[final_token + 1];
+    let previous = &tokens[first - 1];
+
+    // A trailing comment on the element's line is removed with it
+    let own_line = previous.line < tokens[first].line
+        && (next.line > tokens[final_token].line || next.kind == TokenKind::Comment);
+    if own_line {
+        let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = content[end..]
+            .find('\n')
+            .map_or(content.len(), |i| end + i + 1);
+        return (line_start, line_end);
+    }
+
+    match comma {
+        Some(comma) if !matches!(next.kind, TokenKind::RBracket | TokenKind::RParen) => {
+            (start, tokens[comma + 1].start)
+        }
+        Some(comma) => (start, tokens[comma].end()),
+        None if previous.kind == TokenKind::Comma => (previous.start, end),
+        None => (start, end),
+    }
+}
+
+// Append `element` to a list literal, on its own line before the `]` if the
+// list spans several lines
+pub fn append_to_list(
+    content: &str,
+    tokens: &[Token<'_>],
+    list: &Argument<'_>,
+    element: &str,
+) -> Option<String> {
+    if !is_list(tokens, list) {
+        return None;
+    }
+    let open = list.value.start;
+    let close = list.value.end - 1;
+    let last = (open..close)
+        .rev()
+        .find(|&i| tokens[i].kind != TokenKind::Comment)
+        .unwrap_or(open);
+    let needs_comma = !matches!(tokens[last].kind, TokenKind::LBracket | TokenKind::Comma);
+
+    let close_start = tokens[close].start;
+    let close_line_start = content[..close_start].rfind('\n').map_or(0, |i| i + 1);
+    let own_line = tokens[close].line > tokens[open].line
+        && content[close_line_start..close_start].trim().is_empty();
+
+    if !own_line {
+        let separator = match tokens[last].kind {
+            TokenKind::LBracket => "",
+            TokenKind::Comma => " ",
+            _ => ", ",
+        };
+        let at = tokens[last].end();
+        return Some(format!(
+            "{}{}{}{}",
+            &content[..at],
+            separator,
+            element,
+            &content[at..]
+        ));
+    }
+
+    let indent = if last == open {
+        format!("{}    ", line_indent(content, close_start))
+    } else {
+        line_indent(content, tokens[last].start).to_string()
+    };
+    let mut edited = format!(
+        "{}{}{},\n{}",
+        &content[..close_line_start],
+        indent,
+        element,
+        &content[close_line_start..]
+    );
+    if needs_comma {
+        edited.insert(tokens[last].end(), ',');
+    }
+    Some(edited)
+}
+
+// Whether the argument's value is a list literal
+pub fn is_list(tokens: &[Token<'_>], argument: &Argument<'_>) -> bool {
+    let value = &tokens[argument.value.clone()];
+    value.first().map(|t| t.kind) == Some(TokenKind::LBracket)
+        && value.last().map(|t| t.kind) == Some(TokenKind::RBracket)
+}
@@ -0,0 +1,417 @@
+//! Canonical layout for BUILD files.
+//!
+//! Each top-level call is parsed into a small expression tree and printed
+//! back with 4-space indentation, one argument per line, and keyword
+//! arguments in canonical order (see
+//! [`ATTRIBUTE_ORDER`](crate::checks::attribute_order::ATTRIBUTE_ORDER)).
+//! Statements the parser doesn't understand, or that contain comments, are
+//! kept verbatim. Line length is not taken into account.
+
+use super::tokenizer::{find_matching, tokenize, Token, TokenKind};
+use crate::checks::attribute_order::attribute_rank;
+
+const INDENT: &str = "    ";
+
+/// Re-emit `content` in canonical layout. Formatting is idempotent.
+pub fn format_build_file(content: &str) -> String {
+    let tokens = tokenize(content);
+    let statements = split_statements(content, &tokens);
+    if statements.is_empty() {
+        return content.to_string();
+    }
+
+    let mut formatted = String::new();
+    let mut previous: Option<&Statement> = None;
+    for statement in &statements {
+        if let Some(previous) = previous {
+            let is_rule =
+                |s: &Statement| matches!(&s.body, Body::Call(call) if call.callee != "load");
+            let adjacent = statement.first_line == previous.last_line + 1;
+            formatted.push('\n');
+            if is_rule(previous) || is_rule(statement) || !adjacent {
+                formatted.push('\n');
+            }
+        }
+        formatted.push_str(&statement.render());
+        previous = Some(statement);
+    }
+    formatted.push('\n');
+    formatted
+}
+
+// A top-level statement with the comment lines directly above it
+struct Statement {
+    leading_comments: Vec<String>,
+    body: Body,
+    trailing_comment: Option<String>,
+    /// Source lines spanned, including leading comments.
+    first_line: usize,
+    last_line: usize,
+}
+
+enum Body {
+    Call(Call),
+    /// Source text kept as-is (with trailing whitespace stripped).
+    Verbatim(String),
+    /// A comment block separated from the code around it by blank lines.
+    Comments,
+}
+
+struct Call {
+    callee: String,
+    args: Vec<Argument>,
+}
+
+struct Argument {
+    key: Option<String>,
+    value: Expr,
+}
+
+enum Expr {
+    Atom(String),
+    List(Vec<Expr>),
+    Dict(Vec<(Expr, Expr)>),
+    Call(Call),
+    Binary(Box<Expr>, String, Box<Expr>),
+}
+
+impl Statement {
+    fn render(&self) -> String {
+        let mut lines = self.leading_comments.clone();
+        match &self.body {
+            Body::Call(call) => lines.push(call.render_top_level()),
+            Body::Verbatim(text) => lines.push(text.clone()),
+            Body::Comments => {}
+        }
+        let mut rendered = lines.join("\n");
+        if let Some(comment) = &self.trailing_comment {
+            rendered.push_str("  ");
+            rendered.push_str(comment);
+        }
+        rendered
+    }
+}
+
+impl Call {
+    fn render_top_level(&self) -> String {
+        // load() statements always stay on one line
+        if self.callee == "load" {
+            let args: Vec<String> = self.args.iter().map(|arg| arg.render(0)).collect();
+            return format!("load({})", args.join(", "));
+        }
+
+        let mut args: Vec<&Argument> = self.args.iter().collect();
+        if args.iter().all(|arg| arg.key.is_some()) {
+            args.sort_by_key(|arg| {
+                let key = arg.key.as_deref().unwrap_or_default();
+                (attribute_rank(key), key)
+            });
+        }
+        self.render_with(&args, 0)
+    }
+
+    fn render(&self, level: usize) -> String {
+        self.render_with(&self.args.iter().collect::<Vec<_>>(), level)
+    }
+
+    fn render_with(&self, args: &[&Argument], level: usize) -> String {
+        // A lone positional argument hugs the parentheses: glob([...])
+        if let [Argument { key: None, value }] = args {
+            return format!("{}({})", self.callee, value.render(level));
+        }
+        let items = args.iter().map(|arg| arg.render(level + 1)).collect();
+        render_sequence(&format!("{}(", self.callee), ")", items, level)
+    }
+}
+
+impl Argument {
+    fn render(&self, level: usize) -> String {
+        match &self.key {
+            Some(key) => format!("{} = {}", key, self.value.render(level)),
+            None => self.value.render(level),
+        }
+    }
+}
+
+impl Expr {
+    fn render(&self, level: usize) -> String {
+        match self {
+            Expr::Atom(text) => text.clone(),
+            Expr::List(items) => {
+                let items = items.iter().map(|item| item.render(level + 1)).collect();
+                render_sequence("[", "]", items, level)
+            }
+            Expr::Dict(entries) => {
+                let items = entries
+                    .iter()
+                    .map(|(key, value)| {
+                        format!("{}: {}", key.render(level + 1), value.render(level + 1))
+                    })
+                    .collect();
+                render_sequence("{", "}", items, level)
+            }
+            Expr::Call(call) => call.render(level),
+            Expr::Binary(left, op, right) => {
+                format!("{} {} {}", left.render(level), op, right.render(level))
+            }
+        }
+    }
+}
+
+// Print a bracketed sequence inline if it is a single one-line item,
+// otherwise one item per line with a trailing comma
+fn render_sequence(open: &str, close: &str, items: Vec<String>, level: usize) -> String {
+    match items.as_slice() {
+        [] => format!("{}{}", open, close),
+        [item] if !item.contains('\n') => format!("{}{}{}", open, item, close),
+        _ => {
+            let mut rendered = format!("{}\n", open);
+            for item in &items {
+                rendered.push_str(&INDENT.repeat(level + 1));
+                rendered.push_str(item);
+                rendered.push_str(",\n");
+            }
+            rendered.push_str(&INDENT.repeat(level));
+            rendered.push_str(close);
+            rendered
+        }
+    }
+}
+
+// Group the tokens into top-level statements
+fn split_statements(content: &str, tokens: &[Token<'_>]) -> Vec<Statement> {
+    let mut statements = Vec::new();
+    let mut comments: Vec<&Token<'_>> = Vec::new();
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let token = &tokens[index];
+        if token.kind == TokenKind::Comment {
+            // A blank line ends the current comment block
+            if comments
+                .last()
+                .is_some_and(|last| last.line + 1 < token.line)
+            {
+                statements.push(comment_block(&comments));
+                comments.clear();
+            }
+            comments.push(token);
+            index += 1;
+            continue;
+        }
+
+        let last = statement_end(tokens, index);
+        let mut statement = Statement {
+            leading_comments: Vec::new(),
+            body: parse_body(content, tokens, index, last),
+            trailing_comment: None,
+            first_line: token.line,
+            last_line: end_line(&tokens[last]),
+        };
+
+        if comments
+            .last()
+            .is_some_and(|comment| comment.line + 1 == token.line)
+        {
+            statement.first_line = comments[0].line;
+            statement.leading_comments = comments
+                .iter()
+                .map(|c| c.text.trim_end().to_string())
+                .collect();
+            comments.clear();
+        } else if !comments.is_empty() {
+            statements.push(comment_block(&comments));
+            comments.clear();
+        }
+
+        index = last + 1;
+        if let Some(comment) = tokens
+            .get(index)
+            .filter(|next| next.kind == TokenKind::Comment && next.line == statement.last_line)
+        {
+            statement.trailing_comment = Some(comment.text.trim_end().to_string());
+            index += 1;
+        }
+        statements.push(statement);
+    }
+
+    if !comments.is_empty() {
+        statements.push(comment_block(&comments));
+    }
+    statements
+}
+
+fn comment_block(comments: &[&Token<'_>]) -> Statement {
+    Statement {
+        leading_comments: comments
+            .iter()
+            .map(|c| c.text.trim_end().to_string())
+            .collect(),
+        body: Body::Comments,
+        trailing_comment: None,
+        first_line: comments[0].line,
+        last_line: comments[comments.len() - 1].line,
+    }
+}
+
+// Index of the last token of the statement starting at `start`. A statement
+// ends at a line break outside brackets, unless the line ends with an
+// operator that continues it.
+fn statement_end(tokens: &[Token<'_>], start: usize) -> usize {
+    let mut index = start;
+    loop {
+        if matches!(
+            tokens[index].kind,
+            TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace
+        ) {
+            match find_matching(tokens, index) {
+                Some(close) => index = close,
+                None => return tokens.len() - 1,
+            }
+        }
+
+        let Some(next) = tokens.get(index + 1) else {
+            return index;
+        };
+        let continues = matches!(
+            tokens[index].kind,
+            TokenKind::Operator | TokenKind::Equals | TokenKind::Dot | TokenKind::Comma
+        );
+        if next.kind == TokenKind::Comment || (next.line > end_line(&tokens[index]) && !continues) {
+            return index;
+        }
+        index += 1;
+    }
+}
+
+fn end_line(token: &Token<'_>) -> usize {
+    token.line + token.text.matches('\n').count()
+}
+
+fn parse_body(content: &str, tokens: &[Token<'_>], first: usize, last: usize) -> Body {
+    let statement = &tokens[first..=last];
+    let has_comments = statement.iter().any(|t| t.kind == TokenKind::Comment);
+
+    if !has_comments {
+        let mut parser = Parser {
+            tokens: statement,
+            pos: 0,
+        };
+        if let Some(Expr::Call(call)) = parser.expr() {
+            if parser.pos == statement.len() {
+                return Body::Call(call);
+            }
+        }
+    }
+
+    let line_start = content[..tokens[first].start]
+        .rfind('\n')
+        .map_or(0, |i| i + 1);
+    let text = content[line_start..tokens[last].end()]
+        .split('\n')
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n");
+    Body::Verbatim(text)
+}
+
+// Recursive-descent parser for the expressions BUILD files use. Anything
+// else (comprehensions, conditionals, unary operators, tuples) fails the
+// parse, so the statement is kept verbatim.
+struct Parser<'t, 'a> {
+    tokens: &'t [Token<'a>],
+    pos: usize,
+}
+
+impl<'t, 'a> Parser<'t, 'a> {
+    fn peek(&self, offset: usize) -> Option<&'t Token<'a>> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn eat(&mut self, kind: TokenKind) -> bool {
+        let matched = self.peek(0).is_some_and(|token| token.kind == kind);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn expr(&mut self) -> Option<Expr> {
+        let mut expr = self.term()?;
+        while let Some(op) = self.peek(0).filter(|t| t.kind == TokenKind::Operator) {
+            let op = op.text.to_string();
+            self.pos += 1;
+            expr = Expr::Binary(Box::new(expr), op, Box::new(self.term()?));
+        }
+        Some(expr)
+    }
+
+    fn term(&mut self) -> Option<Expr> {
+        let token = *self.peek(0)?;
+        self.pos += 1;
+        match token.kind {
+            TokenKind::String | TokenKind::Number => Some(Expr::Atom(token.text.to_string())),
+            TokenKind::Ident => {
+                let mut name = token.text.to_string();
+                while self.peek(0).is_some_and(|t| t.kind == TokenKind::Dot)
+                    && self.peek(1).is_some_and(|t| t.kind == TokenKind::Ident)
+                {
+                    name.push('.');
+                    name.push_str(self.peek(1)?.text);
+                    self.pos += 2;
+                }
+                if self.eat(TokenKind::LParen) {
+                    let args = self.sequence(TokenKind::RParen, Parser::argument)?;
+                    return Some(Expr::Call(Call { callee: name, args }));
+                }
+                Some(Expr::Atom(name))
+            }
+            TokenKind::LBracket => Some(Expr::List(
+                self.sequence(TokenKind::RBracket, Parser::expr)?,
+            )),
+            TokenKind::LBrace => Some(Expr::Dict(
+                self.sequence(TokenKind::RBrace, Parser::dict_entry)?,
+            )),
+            _ => None,
+        }
+    }
+
+    fn argument(&mut self) -> Option<Argument> {
+        let is_keyword = self.peek(0).is_some_and(|t| t.kind == TokenKind::Ident)
+            && self.peek(1).is_some_and(|t| t.kind == TokenKind::Equals);
+        let key = is_keyword.then(|| self.tokens[self.pos].text.to_string());
+        if is_keyword {
+            self.pos += 2;
+        }
+        Some(Argument {
+            key,
+            value: self.expr()?,
+        })
+    }
+
+    fn dict_entry(&mut self) -> Option<(Expr, Expr)> {
+        let key = self.expr()?;
+        if !self.eat(TokenKind::Colon) {
+            return None;
+        }
+        Some((key, self.expr()?))
+    }
+
+    // Comma-separated items up to and including `close`
+    fn sequence<T>(
+        &mut self,
+        close: TokenKind,
+        mut item: impl FnMut(&mut Self) -> Option<T>,
+    ) -> Option<Vec<T>> {
+        let mut items = Vec::new();
+        loop {
+            if self.eat(close) {
+                return Some(items);
+            }
+            items.push(item(self)?);
+            if !self.eat(TokenKind::Comma) {
+                return self.eat(close).then_some(items);
+            }
+        }
+    }
+}
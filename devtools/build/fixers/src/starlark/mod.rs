@@ -0,0 +1,6 @@
+//! Minimal Starlark support for reading and rewriting BUILD files.
+
+pub mod ast;
+pub mod calls;
+pub mod formatter;
+pub mod tokenizer;
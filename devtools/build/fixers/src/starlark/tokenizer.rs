@@ -0,0 +1,273 @@
+//! A lossless tokenizer for the subset of Starlark used in BUILD files.
+//!
+//! Whitespace and newlines are not emitted as tokens, but every token keeps
+//! its byte offset so callers can splice edits back into the original text.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Ident,
+    String,
+    Number,
+    Comment,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    Comma,
+    Colon,
+    Equals,
+    Dot,
+    Operator,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// Byte offset of the first character.
+    pub start: usize,
+    /// 1-based line of the first character.
+    pub line: usize,
+}
+
+impl<'a> Token<'a> {
+    /// Byte offset just past the last character.
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    pub fn is_ident(&self, name: &str) -> bool {
+        self.kind == TokenKind::Ident && self.text == name
+    }
+
+    /// The decoded value of a string literal, or `None` for other tokens.
+    pub fn string_value(&self) -> Option<String> {
+        if self.kind != TokenKind::String {
+            return None;
+        }
+        let body_start = self.text.find(['"', '\'']).unwrap_or(0);
+        let prefix = &self.text[..body_start];
+        let raw = prefix.contains(['r', 'R']);
+        let literal = &self.text[body_start..];
+        let quote_len = if literal.starts_with("\"\"\"") || literal.starts_with("'''") {
+            3
+        } else {
+            1
+        };
+        let quote = &literal[..quote_len];
+        let body = literal[quote_len..]
+            .strip_suffix(quote)
+            .unwrap_or(&literal[quote_len..]);
+
+        if raw {
+            return Some(body.to_string());
+        }
+        Some(unescape(body))
+    }
+}
+
+fn unescape(body: &str) -> String {
+    let mut value = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('r') => value.push('\r'),
+            Some('0') => value.push('\0'),
+            Some('\n') => {}
+            Some(escaped @ ('\\' | '"' | '\'')) => value.push(escaped),
+            Some(other) => {
+                value.push('\\');
+                value.push(other);
+            }
+            None => value.push('\\'),
+        }
+    }
+    value
+}
+
+/// `value` as a double-quoted Starlark string literal. Only quotes,
+/// backslashes and the control characters Starlark has escapes for are
+/// escaped; other characters are written as they are.
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            '\0' => quoted.push_str("\\0"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+pub fn tokenize(content: &str) -> Vec<Token<'_>> {
+    let bytes = content.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    let mut line = 1;
+
+    while pos < bytes.len() {
+        let c = bytes[pos];
+        let start = pos;
+        let start_line = line;
+
+        let kind = match c {
+            b'\n' => {
+                line += 1;
+                pos += 1;
+                continue;
+            }
+            b' ' | b'\t' | b'\r' | b'\x0c' => {
+                pos += 1;
+                continue;
+            }
+            b'\\' if bytes.get(pos + 1) == Some(&b'\n') => {
+                line += 1;
+                pos += 2;
+                continue;
+            }
+            b'#' => {
+                pos = content[pos..].find('\n').map_or(bytes.len(), |i| pos + i);
+                TokenKind::Comment
+            }
+            b'"' | b'\'' => {
+                pos = scan_string(content, pos, &mut line);
+                TokenKind::String
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while pos < bytes.len()
+                    && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_')
+                {
+                    pos += 1;
+                }
+                let is_prefix = matches!(
+                    content[start..pos].to_ascii_lowercase().as_str(),
+                    "r" | "b" | "rb" | "br"
+                );
+                if is_prefix && matches!(bytes.get(pos), Some(b'"' | b'\'')) {
+                    pos = scan_string(content, pos, &mut line);
+                    TokenKind::String
+                } else {
+                    TokenKind::Ident
+                }
+            }
+            c if c.is_ascii_digit() => {
+                while pos < bytes.len()
+                    && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'.')
+                {
+                    pos += 1;
+                }
+                TokenKind::Number
+            }
+            b'(' | b')' | b'[' | b']' | b'{' | b'}' | b',' | b':' | b'.' => {
+                pos += 1;
+                match c {
+                    b'(' => TokenKind::LParen,
+                    b')' => TokenKind::RParen,
+                    b'[' => TokenKind::LBracket,
+                    b']' => TokenKind::RBracket,
+                    b'{' => TokenKind::LBrace,
+                    b'}' => TokenKind::RBrace,
+                    b',' => TokenKind::Comma,
+                    b':' => TokenKind::Colon,
+                    _ => TokenKind::Dot,
+                }
+            }
+            b'=' if bytes.get(pos + 1) != Some(&b'=') => {
+                pos += 1;
+                TokenKind::Equals
+            }
+            b'=' | b'+' | b'-' | b'*' | b'/' | b'%' | b'<' | b'>' | b'!' | b'&' | b'|' | b'^'
+            | b'~' => {
+                pos += 1;
+                while pos < bytes.len() && b"=*/<>".contains(&bytes[pos]) && pos - start < 3 {
+                    pos += 1;
+                }
+                TokenKind::Operator
+            }
+            _ => {
+                pos += content[pos..].chars().next().map_or(1, char::len_utf8);
+                TokenKind::Unknown
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            text: &content[start..pos],
+            start,
+            line: start_line,
+        });
+    }
+
+    tokens
+}
+
+// Scan a string literal starting at its opening quote and return the offset
+// just past it. Unterminated single-line strings stop at the end of the line.
+fn scan_string(content: &str, start: usize, line: &mut usize) -> usize {
+    let bytes = content.as_bytes();
+    let quote = bytes[start];
+    let triple = bytes.get(start + 1) == Some(&quote) && bytes.get(start + 2) == Some(&quote);
+    let mut pos = start + if triple { 3 } else { 1 };
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => {
+                if bytes.get(pos + 1) == Some(&b'\n') {
+                    *line += 1;
+                }
+                pos += 2;
+            }
+            b'\n' if !triple => return pos,
+            b'\n' => {
+                *line += 1;
+                pos += 1;
+            }
+            c if c == quote => {
+                if !triple {
+                    return pos + 1;
+                }
+                if bytes.get(pos + 1) == Some(&quote) && bytes.get(pos + 2) == Some(&quote) {
+                    return pos + 3;
+                }
+                pos += 1;
+            }
+            _ => pos += 1,
+        }
+    }
+
+    bytes.len()
+}
+
+/// Index of the token closing the bracket opened at `open`, tracking nesting.
+pub fn find_matching(tokens: &[Token<'_>], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, token) in tokens.iter().enumerate().skip(open) {
+        match token.kind {
+            TokenKind::LParen | TokenKind::LBracket | TokenKind::LBrace => depth += 1,
+            TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(index);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
@@ -0,0 +1,176 @@
+//! Extraction of `import` declarations from Swift source files.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A module imported by a Swift source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwiftImport {
+    /// Top-level module name (`Foundation` for `import Foundation.NSString`).
+    pub module: String,
+    /// Imported with `@testable`.
+    pub testable: bool,
+    /// Every import of the module is inside an `#if` that checks the
+    /// platform (`canImport`, `os`, `arch` or `targetEnvironment`).
+    pub platform_conditional: bool,
+}
+
+// Keywords for importing a single declaration: `import struct Foundation.Date`
+const IMPORT_KINDS: &[&str] = &[
+    "typealias",
+    "struct",
+    "class",
+    "enum",
+    "protocol",
+    "let",
+    "var",
+    "func",
+    "actor",
+];
+
+const PLATFORM_CONDITIONS: &[&str] = &["canImport(", "os(", "arch(", "targetEnvironment("];
+
+/// Read `path` and return the modules it imports, each listed once.
+pub fn parse_swift_imports(path: &Path) -> io::Result<Vec<SwiftImport>> {
+    let source = fs::read_to_string(path)?;
+    Ok(extract_swift_imports(&source))
+}
+
+/// Return the modules imported by `source`, each listed once, in order of
+/// first import.
+pub fn extract_swift_imports(source: &str) -> Vec<SwiftImport> {
+    let mut imports: Vec<SwiftImport> = Vec::new();
+    // One entry per open #if: whether any branch so far tests the platform
+    let mut conditions: Vec<bool> = Vec::new();
+    let mut in_block_comment = 0usize;
+    let mut in_multiline_string = false;
+
+    for line in source.lines() {
+        let code = strip_comments(line, &mut in_block_comment, &mut in_multiline_string);
+        let code = code.trim();
+
+        if let Some(condition) = directive(code, "#if") {
+            conditions.push(is_platform_condition(condition));
+            continue;
+        }
+        if let Some(condition) = directive(code, "#elseif") {
+            if let Some(platform) = conditions.last_mut() {
+                *platform |= is_platform_condition(condition);
+            }
+            continue;
+        }
+        if directive(code, "#endif").is_some() {
+            conditions.pop();
+            continue;
+        }
+
+        let Some((module, testable)) = parse_import(code) else {
+            continue;
+        };
+        let platform_conditional = conditions.iter().any(|&platform| platform);
+
+        match imports.iter_mut().find(|import| import.module == module) {
+            Some(existing) => {
+                existing.testable |= testable;
+                existing.platform_conditional &= platform_conditional;
+            }
+            None => imports.push(SwiftImport {
+                module,
+                testable,
+                platform_conditional,
+            }),
+        }
+    }
+
+    imports
+}
+
+// The rest of the line after a compiler directive such as `#if`
+fn directive<'a>(code: &'a str, name: &str) -> Option<&'a str> {
+    let rest = code.strip_prefix(name)?;
+    (rest.is_empty() || rest.starts_with(char::is_whitespace)).then_some(rest)
+}
+
+fn is_platform_condition(condition: &str) -> bool {
+    PLATFORM_CONDITIONS
+        .iter()
+        .any(|check| condition.contains(check))
+}
+
+// Parse `[@attr...] [modifier] import [kind] Module[.Sub...]`
+fn parse_import(code: &str) -> Option<(String, bool)> {
+    let mut words = code.split_whitespace().peekable();
+    let mut testable = false;
+
+    while let Some(word) = words.next_if(|word| word.starts_with('@')) {
+        testable |= word == "@testable";
+    }
+    words.next_if(|word| {
+        matches!(
+            *word,
+            "public" | "package" | "internal" | "fileprivate" | "private"
+        )
+    });
+    if words.next()? != "import" {
+        return None;
+    }
+    words.next_if(|word| IMPORT_KINDS.contains(word));
+
+    let path = words.next()?;
+    let module = path.split('.').next()?;
+    let is_identifier =
+        module.chars().all(|c| c.is_alphanumeric() || c == '_') && !module.is_empty();
+    is_identifier.then(|| (module.to_string(), testable))
+}
+
+// Remove `//` and `/* */` comments (which nest in Swift) and the contents of
+// multi-line string literals, tracking state across lines
+pub(crate) fn strip_comments(line: &str, block_depth: &mut usize, in_string: &mut bool) -> String {
+    let mut code = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while !rest.is_empty() {
+        if *in_string {
+            match rest.find("\"\"\"") {
+                Some(end) => {
+                    *in_string = false;
+                    rest = &rest[end + 3..];
+                }
+                None => break,
+            }
+        } else if *block_depth > 0 {
+            let open = rest.find("/*");
+            let close = rest.find("*/");
+            match (open, close) {
+                (Some(open), Some(close)) if open < close => {
+                    *block_depth += 1;
+                    rest = &rest[open + 2..];
+                }
+                (_, Some(close)) => {
+                    *block_depth -= 1;
+                    rest = &rest[close + 2..];
+                }
+                (Some(open), None) => {
+                    *block_depth += 1;
+                    rest = &rest[open + 2..];
+                }
+                (None, None) => break,
+            }
+        } else if rest.starts_with("//") {
+            break;
+        } else if rest.starts_with("/*") {
+            *block_depth += 1;
+            rest = &rest[2..];
+        } else if rest.starts_with("\"\"\"") {
+            *in_string = true;
+            rest = &rest[3..];
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            code.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    code
+}
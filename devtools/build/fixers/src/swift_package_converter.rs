@@ -0,0 +1,429 @@
+//! Conversion of `Package.swift` manifests to BUILD.bazel files, for
+//! `umbra-fix convert-spm`.
+//!
+//! The manifest isn't evaluated: its `products`, package `dependencies` and
+//! `targets` are extracted with regular expressions, which covers manifests
+//! that declare them literally. Each target becomes a `swift_library`,
+//! `swift_test` or `swift_binary` whose paths are relative to the manifest's
+//! directory, so the BUILD.bazel belongs next to it.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::starlark::tokenizer::quote;
+
+const RULES_SWIFT_BZL: &str = "@build_bazel_rules_swift//swift:swift.bzl";
+
+static TARGET_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\.(target|testTarget|executableTarget)\s*\(\s*name\s*:\s*"([^"]+)""#)
+        .expect("invalid regex")
+});
+static PRODUCT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\.(library|executable)\s*\(\s*name\s*:\s*"([^"]+)""#).expect("invalid regex")
+});
+static PACKAGE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\.package\s*\(").expect("invalid regex"));
+static PATH_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\bpath\s*:\s*"([^"]*)""#).expect("invalid regex"));
+static URL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\burl\s*:\s*"([^"]*)""#).expect("invalid regex"));
+static NAME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\bname\s*:\s*"([^"]*)""#).expect("invalid regex"));
+// One element of a target's `dependencies`
+static DEPENDENCY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"\.product\s*\(\s*name\s*:\s*"([^"]+)"\s*,\s*package\s*:\s*"([^"]+)"|\.(?:target|byName)\s*\(\s*name\s*:\s*"([^"]+)"|"([^"]+)""#,
+    )
+    .expect("invalid regex")
+});
+static RESOURCE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\.(?:process|copy|embedInCode)\s*\(\s*"([^"]+)""#).expect("invalid regex")
+});
+// A `key: [` list argument
+static LIST_ARGUMENT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(\w+)\s*:\s*\[").expect("invalid regex"));
+static STRING_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""([^"]*)""#).expect("invalid regex"));
+
+/// The kinds of SPM targets and the rules they convert to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetKind {
+    Regular,
+    Test,
+    Executable,
+}
+
+impl TargetKind {
+    pub fn rule(self) -> &'static str {
+        match self {
+            TargetKind::Regular => "swift_library",
+            TargetKind::Test => "swift_test",
+            TargetKind::Executable => "swift_binary",
+        }
+    }
+}
+
+/// A dependency of an SPM target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetDependency {
+    /// Another target of the manifest.
+    Target(String),
+    /// A product of an external package, by its identity or name.
+    Product { name: String, package: String },
+}
+
+/// A target declared in `Package.swift`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpmTarget {
+    pub kind: TargetKind,
+    pub name: String,
+    /// Source directory, relative to the package.
+    pub path: String,
+    pub dependencies: Vec<TargetDependency>,
+    /// Resource paths, relative to `path`.
+    pub resources: Vec<String>,
+}
+
+/// A product declared in `Package.swift`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpmProduct {
+    pub name: String,
+    pub targets: Vec<String>,
+}
+
+/// An external package the manifest depends on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpmPackage {
+    /// Lowercased last component of the URL or path, as SPM identifies it.
+    pub identity: String,
+    /// The `name:` given to the dependency, if any.
+    pub name: Option<String>,
+}
+
+/// What convert-spm reads from a `Package.swift`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpmManifest {
+    pub products: Vec<SpmProduct>,
+    pub packages: Vec<SpmPackage>,
+    pub targets: Vec<SpmTarget>,
+}
+
+/// Extract the products, package dependencies and targets of a manifest.
+/// Without a `path:`, SPM looks for sources in `Sources/<name>`, or
+/// `Tests/<name>` for test targets.
+pub fn parse_manifest(manifest: &str) -> SpmManifest {
+    let products = PRODUCT_RE
+        .captures_iter(manifest)
+        .map(|captures| {
+            let arguments =
+                &manifest[call_arguments(manifest, captures.get(0).map_or(0, |m| m.start()))];
+            SpmProduct {
+                name: captures[2].to_string(),
+                targets: list_argument(arguments, "targets")
+                    .map(|list| strings(list).collect())
+                    .unwrap_or_default(),
+            }
+        })
+        .collect();
+
+    let packages = PACKAGE_RE
+        .find_iter(manifest)
+        .filter_map(|found| {
+            let arguments = &manifest[call_arguments(manifest, found.start())];
+            let location = URL_RE
+                .captures(arguments)
+                .or_else(|| PATH_RE.captures(arguments))?[1]
+                .to_string();
+            Some(SpmPackage {
+                identity: package_identity(&location),
+                name: NAME_RE
+                    .captures(arguments)
+                    .map(|captures| captures[1].to_string()),
+            })
+        })
+        .collect();
+
+    // `.target(name:)` also names a dependency inside another target
+    let mut targets: Vec<(TargetKind, String, &str)> = Vec::new();
+    let mut previous_end = 0;
+    for captures in TARGET_RE.captures_iter(manifest) {
+        let start = captures.get(0).map_or(0, |m| m.start());
+        if start < previous_end {
+            continue;
+        }
+        let kind = match &captures[1] {
+            "testTarget" => TargetKind::Test,
+            "executableTarget" => TargetKind::Executable,
+            _ => TargetKind::Regular,
+        };
+        let arguments = call_arguments(manifest, start);
+        previous_end = arguments.end;
+        targets.push((kind, captures[2].to_string(), &manifest[arguments]));
+    }
+    let target_names: Vec<&str> = targets.iter().map(|(_, name, _)| name.as_str()).collect();
+
+    let targets = targets
+        .iter()
+        .map(|(kind, name, arguments)| {
+            let path = match PATH_RE.captures(arguments) {
+                Some(path) => path[1].trim_end_matches('/').to_string(),
+                None if *kind == TargetKind::Test => format!("Tests/{}", name),
+                None => format!("Sources/{}", name),
+            };
+            let dependencies = list_argument(arguments, "dependencies")
+                .map(|list| {
+                    DEPENDENCY_RE
+                        .captures_iter(list)
+                        .map(|captures| match (captures.get(1), captures.get(2)) {
+                            (Some(name), Some(package)) => TargetDependency::Product {
+                                name: name.as_str().to_string(),
+                                package: package.as_str().to_string(),
+                            },
+                            _ => {
+                                let name = captures
+                                    .get(3)
+                                    .or_else(|| captures.get(4))
+                                    .map_or("", |m| m.as_str());
+                                if target_names.contains(&name) {
+                                    TargetDependency::Target(name.to_string())
+                                } else {
+                                    // By name, a product of the package with that name
+                                    TargetDependency::Product {
+                                        name: name.to_string(),
+                                        package: name.to_string(),
+                                    }
+                                }
+                            }
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let resources = list_argument(arguments, "resources")
+                .map(|list| {
+                    RESOURCE_RE
+                        .captures_iter(list)
+                        .map(|captures| captures[1].trim_end_matches('/').to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+            SpmTarget {
+                kind: *kind,
+                name: name.clone(),
+                path,
+                dependencies,
+                resources,
+            }
+        })
+        .collect();
+
+    SpmManifest {
+        products,
+        packages,
+        targets,
+    }
+}
+
+/// The label for `product` of `package`: the one in `labels` (keyed by
+/// product name), or else the repository rules_swift_package_manager
+/// generates for the package, e.g. `@swiftpkg_swift_log//:Logging`.
+pub fn product_label(
+    manifest: &SpmManifest,
+    product: &str,
+    package: &str,
+    labels: &BTreeMap<String, String>,
+) -> String {
+    if let Some(label) = labels.get(product) {
+        return label.clone();
+    }
+    let identity = manifest
+        .packages
+        .iter()
+        .find(|candidate| {
+            candidate.identity == package.to_lowercase()
+                || candidate.name.as_deref() == Some(package)
+        })
+        .map_or_else(|| package.to_lowercase(), |found| found.identity.clone());
+    let repository: String = identity
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("@swiftpkg_{}//:{}", repository, product)
+}
+
+/// BUILD.bazel content for the manifest at `manifest_path`. Resources that
+/// are directories next to the manifest are globbed; others are listed as
+/// files.
+pub fn convert_manifest(
+    manifest_path: &Path,
+    labels: &BTreeMap<String, String>,
+) -> io::Result<String> {
+    let manifest = parse_manifest(&fs::read_to_string(manifest_path)?);
+    if manifest.targets.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: no targets found", manifest_path.display()),
+        ));
+    }
+    let package_dir = manifest_path.parent().unwrap_or(Path::new(""));
+
+    let mut rules: Vec<&str> = manifest
+        .targets
+        .iter()
+        .map(|target| target.kind.rule())
+        .collect();
+    rules.sort();
+    rules.dedup();
+    let symbols: Vec<String> = rules.iter().map(|rule| quote(rule)).collect();
+
+    let mut content = format!(
+        "load({}, {})\n\npackage(default_visibility = [\"//visibility:public\"])\n",
+        quote(RULES_SWIFT_BZL),
+        symbols.join(", ")
+    );
+
+    for target in &manifest.targets {
+        content.push_str(&format!(
+            "\n{}(\n    name = {},\n",
+            target.kind.rule(),
+            quote(&target.name)
+        ));
+        content.push_str(&format!(
+            "    srcs = glob(\n        [{}],\n        allow_empty = True,\n    ),\n",
+            quote(&format!("{}/**/*.swift", target.path))
+        ));
+
+        let mut deps: Vec<String> = target
+            .dependencies
+            .iter()
+            .map(|dependency| match dependency {
+                TargetDependency::Target(name) => format!(":{}", name),
+                TargetDependency::Product { name, package } => {
+                    product_label(&manifest, name, package, labels)
+                }
+            })
+            .collect();
+        deps.sort();
+        deps.dedup();
+        if !deps.is_empty() {
+            content.push_str("    deps = [\n");
+            for dep in &deps {
+                content.push_str(&format!("        {},\n", quote(dep)));
+            }
+            content.push_str("    ],\n");
+        }
+
+        if !target.resources.is_empty() {
+            let data: Vec<String> = target
+                .resources
+                .iter()
+                .map(|resource| {
+                    let path = format!("{}/{}", target.path, resource);
+                    if package_dir.join(&path).is_dir() {
+                        format!("{}/**", path)
+                    } else {
+                        path
+                    }
+                })
+                .collect();
+            content.push_str("    data = glob(\n        [\n");
+            for pattern in &data {
+                content.push_str(&format!("            {},\n", quote(pattern)));
+            }
+            content.push_str("        ],\n        allow_empty = True,\n    ),\n");
+        }
+
+        if target.kind == TargetKind::Test {
+            content.push_str("    testonly = True,\n");
+        }
+
+        // With products declared, only the targets they export are public
+        let exported = manifest.products.is_empty()
+            || manifest
+                .products
+                .iter()
+                .any(|product| product.targets.contains(&target.name));
+        let visibility = if exported {
+            "//visibility:public"
+        } else {
+            "//visibility:private"
+        };
+        content.push_str(&format!("    visibility = [{}],\n)\n", quote(visibility)));
+    }
+
+    Ok(content)
+}
+
+// SPM's identity for a package URL or path: its last component without
+// `.git`, lowercased
+fn package_identity(location: &str) -> String {
+    let name = location
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(location);
+    name.trim_end_matches(".git").to_lowercase()
+}
+
+// Byte range of the text between the parentheses of the call starting at
+// `start`, skipping over nested brackets and string literals
+fn call_arguments(text: &str, start: usize) -> Range<usize> {
+    let Some(open) = text[start..].find('(').map(|i| start + i) else {
+        return start..start;
+    };
+    let close = matching_close(text, open).unwrap_or(text.len());
+    open + 1..close
+}
+
+// The contents of the `key: [...]` list argument in `arguments`
+fn list_argument<'a>(arguments: &'a str, key: &str) -> Option<&'a str> {
+    let open = LIST_ARGUMENT_RE
+        .captures_iter(arguments)
+        .find(|captures| &captures[1] == key)?
+        .get(0)?
+        .end()
+        - 1;
+    let close = matching_close(arguments, open)?;
+    Some(&arguments[open + 1..close])
+}
+
+// Byte offset of the bracket closing the one at `open`
+fn matching_close(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (offset, c) in text[open..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + offset);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn strings(text: &str) -> impl Iterator<Item = String> + '_ {
+    STRING_RE
+        .captures_iter(text)
+        .map(|captures| captures[1].to_string())
+}
@@ -0,0 +1,92 @@
+//! Detection of the Swift language version that source files need, and of
+//! the `swift_settings` that opt a target into it.
+//!
+//! Detection is heuristic: it looks for the keywords of a language feature,
+//! outside comments, rather than type-checking the sources.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::starlark::ast::AttrValue;
+use crate::swift_imports::strip_comments;
+
+/// The Swift version that introduced `async`/`await`, actors and `@MainActor`.
+pub const CONCURRENCY_SWIFT_VERSION: &str = "5.5";
+
+/// The setting a target whose sources use concurrency should enable.
+pub const STRICT_CONCURRENCY_SETTING: &str = "swift.strict-concurrency=complete";
+
+// Words only concurrency code uses; `actor` also needs a name after it, as
+// it is a common variable name too
+const CONCURRENCY_KEYWORDS: &[&str] = &["async", "await", "@MainActor"];
+
+/// Whether `source` uses concurrency features: `async` functions, `await`,
+/// actor declarations or `@MainActor`.
+pub fn uses_concurrency(source: &str) -> bool {
+    let mut in_block_comment = 0usize;
+    let mut in_multiline_string = false;
+
+    source.lines().any(|line| {
+        let code = strip_comments(line, &mut in_block_comment, &mut in_multiline_string);
+        let words: Vec<&str> = code
+            .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '@'))
+            .filter(|word| !word.is_empty())
+            .collect();
+        words.iter().enumerate().any(|(index, word)| {
+            CONCURRENCY_KEYWORDS.contains(word)
+                || (*word == "actor" && words.get(index + 1).is_some_and(|name| is_type_name(name)))
+        })
+    })
+}
+
+/// The Swift version the files under `package_dir` need, or `None` if any
+/// Swift 5 compiler accepts them.
+pub fn required_swift_version(
+    package_dir: &Path,
+    files: &[String],
+) -> io::Result<Option<&'static str>> {
+    for file in files {
+        if uses_concurrency(&fs::read_to_string(package_dir.join(file))?) {
+            return Ok(Some(CONCURRENCY_SWIFT_VERSION));
+        }
+    }
+    Ok(None)
+}
+
+/// Whether any string in `value` (a `swift_settings`, `copts` or `features`
+/// expression) enables complete strict concurrency checking.
+pub fn enables_strict_concurrency(value: &AttrValue) -> bool {
+    match value {
+        AttrValue::String(value) => is_complete_strict_concurrency(value),
+        AttrValue::List(elements) | AttrValue::Concat(elements) => {
+            elements.iter().any(enables_strict_concurrency)
+        }
+        AttrValue::Call { args, attrs, .. } => {
+            args.iter().any(enables_strict_concurrency)
+                || attrs
+                    .iter()
+                    .any(|attr| enables_strict_concurrency(&attr.value))
+        }
+        AttrValue::Select(branches) => branches
+            .iter()
+            .any(|(_, value)| enables_strict_concurrency(value)),
+        AttrValue::Dict(_) | AttrValue::Ident(_) | AttrValue::Number(_) => false,
+    }
+}
+
+// `-strict-concurrency=complete` (or the rules_swift feature of that name),
+// or the StrictConcurrency upcoming feature. The `minimal` and `targeted`
+// levels only check part of the code.
+fn is_complete_strict_concurrency(setting: &str) -> bool {
+    if let Some((_, level)) = setting.split_once("strict-concurrency=") {
+        return level == "complete";
+    }
+    setting
+        .split_once("StrictConcurrency")
+        .is_some_and(|(_, level)| level.is_empty() || level == "=complete")
+}
+
+fn is_type_name(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_uppercase() || c == '_')
+}
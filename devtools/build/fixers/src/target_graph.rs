@@ -0,0 +1,254 @@
+//! An in-memory graph of every target of the workspace, for checks that need
+//! to see past the BUILD file they are analyzing (dependency cycles, deps on
+//! testonly targets, targets nothing depends on).
+//!
+//! Cross-file checks should query a [`TargetGraph`] rather than reading other
+//! BUILD files themselves, so every one of them sees the same targets and
+//! resolves labels the same way.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::baseline::relative_path;
+use crate::checks::interop::collect_labels;
+use crate::discovery::is_workspace_file;
+use crate::label_resolver::{resolve_label, AbsoluteLabel};
+use crate::starlark::ast::{self, AttrValue, Attribute, ParseError};
+
+/// Attributes whose labels are edges of the graph.
+pub const DEPENDENCY_ATTRIBUTES: &[&str] = &["deps"];
+
+/// A target as declared in its BUILD file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetInfo {
+    pub rule_name: String,
+    pub attrs: Vec<Attribute>,
+    pub build_file_path: PathBuf,
+}
+
+impl TargetInfo {
+    /// The Swift module the target builds: its `module_name`, or else its
+    /// name. `None` when the module name is computed rather than a string.
+    pub fn module_name(&self) -> Option<&str> {
+        let attr = |key: &str| self.attrs.iter().find(|attr| attr.key == key);
+        match attr("module_name") {
+            Some(module_name) => module_name.value.as_str(),
+            None => attr("name")?.value.as_str(),
+        }
+    }
+}
+
+/// A target as printed by `--emit-targets`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TargetEntry {
+    pub label: String,
+    pub rule_name: String,
+    /// BUILD file, relative to the workspace root.
+    pub file: String,
+    pub name: String,
+    /// The string labels of `visibility`; `None` if the target doesn't set it.
+    pub visibility: Option<Vec<String>>,
+}
+
+/// Every named target of the workspace, keyed by its absolute label, and the
+/// edges of their `deps`.
+#[derive(Debug, Clone, Default)]
+pub struct TargetGraph {
+    workspace_root: PathBuf,
+    targets: BTreeMap<AbsoluteLabel, TargetInfo>,
+    // Package -> the `default_visibility` of its package(), as written
+    default_visibility: BTreeMap<String, AttrValue>,
+    deps: BTreeMap<AbsoluteLabel, BTreeSet<AbsoluteLabel>>,
+    reverse_deps: BTreeMap<AbsoluteLabel, BTreeSet<AbsoluteLabel>>,
+}
+
+impl TargetGraph {
+    /// The directory packages are relative to.
+    pub fn workspace_root(&self) -> &Path {
+        &self.workspace_root
+    }
+
+    /// The target `label` names, if any BUILD file declares it.
+    pub fn lookup(&self, label: &AbsoluteLabel) -> Option<&TargetInfo> {
+        self.targets.get(label)
+    }
+
+    /// Every target, in label order.
+    pub fn targets(&self) -> impl Iterator<Item = (&AbsoluteLabel, &TargetInfo)> {
+        self.targets.iter()
+    }
+
+    /// An entry for each target whose rule is one of `rule_types` (every
+    /// target if it is empty), in label order.
+    pub fn entries(&self, workspace_root: &Path, rule_types: &[String]) -> Vec<TargetEntry> {
+        self.targets
+            .iter()
+            .filter(|(_, info)| rule_types.is_empty() || rule_types.contains(&info.rule_name))
+            .map(|(label, info)| {
+                let attr = |key: &str| {
+                    info.attrs
+                        .iter()
+                        .find(|attr| attr.key == key)
+                        .map(|attr| &attr.value)
+                };
+                let visibility = attr("visibility").map(|value| {
+                    let mut labels = Vec::new();
+                    collect_labels(value, &mut labels);
+                    labels.into_iter().map(String::from).collect()
+                });
+                TargetEntry {
+                    label: label.to_string(),
+                    rule_name: info.rule_name.clone(),
+                    file: relative_path(workspace_root, &info.build_file_path),
+                    name: attr("name")
+                        .and_then(AttrValue::as_str)
+                        .unwrap_or(&label.target)
+                        .to_string(),
+                    visibility,
+                }
+            })
+            .collect()
+    }
+
+    /// Who may depend on `label`: its `visibility`, or else the
+    /// `default_visibility` of its package, or else `//visibility:private`,
+    /// as absolute labels. `None` if the target isn't declared or the value
+    /// isn't a list of labels.
+    pub fn visibility(&self, label: &AbsoluteLabel) -> Option<Vec<String>> {
+        let info = self.targets.get(label)?;
+        let value = info
+            .attrs
+            .iter()
+            .find(|attr| attr.key == "visibility")
+            .map(|attr| &attr.value)
+            .or_else(|| self.default_visibility.get(&label.package));
+        let Some(value) = value else {
+            return Some(vec!["//visibility:private".to_string()]);
+        };
+        let elements = match value {
+            AttrValue::List(elements) => elements.as_slice(),
+            value => std::slice::from_ref(value),
+        };
+        elements
+            .iter()
+            .map(|element| {
+                let visible_to = element.as_str()?;
+                Some(
+                    resolve_label(visible_to, &label.package, &self.workspace_root)
+                        .map_or_else(|_| visible_to.to_string(), |resolved| resolved.to_string()),
+                )
+            })
+            .collect()
+    }
+
+    /// The labels `label` depends on directly, whether or not they are
+    /// declared in the workspace, sorted.
+    pub fn deps(&self, label: &AbsoluteLabel) -> Vec<AbsoluteLabel> {
+        self.deps
+            .get(label)
+            .map_or_else(Vec::new, |deps| deps.iter().cloned().collect())
+    }
+
+    /// The targets that depend on `label` directly, sorted.
+    pub fn reverse_deps(&self, label: &AbsoluteLabel) -> Vec<AbsoluteLabel> {
+        self.reverse_deps
+            .get(label)
+            .map_or_else(Vec::new, |dependents| dependents.iter().cloned().collect())
+    }
+
+    // Add the rules of `file`, the parsed BUILD file of `package`.
+    fn add_file(
+        &mut self,
+        workspace_root: &Path,
+        package: &str,
+        path: &Path,
+        file: ast::BuildFile,
+    ) {
+        let default_visibility = file.package.and_then(|package| {
+            package
+                .attrs
+                .into_iter()
+                .find(|attr| attr.key == "default_visibility")
+        });
+        if let Some(attr) = default_visibility {
+            self.default_visibility
+                .insert(package.to_string(), attr.value);
+        }
+        for rule in file.rules {
+            let Some(name) = rule.name() else {
+                continue;
+            };
+            let label = AbsoluteLabel {
+                repository: None,
+                package: package.to_string(),
+                target: name.to_string(),
+            };
+
+            let mut labels = Vec::new();
+            for attr in &rule.attrs {
+                if DEPENDENCY_ATTRIBUTES.contains(&attr.key.as_str()) {
+                    collect_labels(&attr.value, &mut labels);
+                }
+            }
+            for dep in labels {
+                let Ok(dep) = resolve_label(dep, package, workspace_root) else {
+                    continue;
+                };
+                self.reverse_deps
+                    .entry(dep.clone())
+                    .or_default()
+                    .insert(label.clone());
+                self.deps.entry(label.clone()).or_default().insert(dep);
+            }
+
+            self.targets.insert(
+                label,
+                TargetInfo {
+                    rule_name: rule.rule_name,
+                    attrs: rule.attrs,
+                    build_file_path: path.to_path_buf(),
+                },
+            );
+        }
+    }
+}
+
+/// Parse every BUILD file of `build_files` (WORKSPACE files are skipped) into
+/// a graph of the targets they declare. Packages are the files' directories
+/// relative to `workspace_root`. Files that can't be read or parsed are left
+/// out of the graph and returned with their error alongside it.
+pub fn build_target_graph(
+    workspace_root: &Path,
+    build_files: &[PathBuf],
+) -> (TargetGraph, Vec<(PathBuf, ParseError)>) {
+    let mut graph = TargetGraph {
+        workspace_root: workspace_root.to_path_buf(),
+        ..TargetGraph::default()
+    };
+    let mut errors = Vec::new();
+
+    for path in build_files {
+        if path.file_name().is_some_and(is_workspace_file) {
+            continue;
+        }
+        let parsed = fs::read_to_string(path)
+            .map_err(|err| ParseError {
+                message: format!("can't read the file: {}", err),
+                line: 1,
+            })
+            .and_then(|content| ast::parse(&content));
+        match parsed {
+            Ok(file) => {
+                let package =
+                    relative_path(workspace_root, path.parent().unwrap_or(workspace_root));
+                graph.add_file(workspace_root, &package, path, file);
+            }
+            Err(err) => errors.push((path.clone(), err)),
+        }
+    }
+
+    (graph, errors)
+}
@@ -0,0 +1,108 @@
+//! Restoring BUILD and WORKSPACE files from the `.bak` copies kept by `--backup`.
+
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::atomic_write::atomic_write;
+use crate::discovery::{is_workspace_file, walk};
+
+/// Suffix of backup files; `BUILD.bazel` is backed up as `BUILD.bazel.bak`.
+pub const BACKUP_SUFFIX: &str = ".bak";
+
+/// A backup found under the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    /// The `.bak` file.
+    pub path: PathBuf,
+    /// The file it restores.
+    pub original: PathBuf,
+    /// When the backup was written.
+    pub modified: SystemTime,
+}
+
+/// Backup file for `path`.
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(BACKUP_SUFFIX);
+    path.with_file_name(name)
+}
+
+// Copy `path` to its backup file, replacing any backup from an earlier run
+pub fn create_backup(path: &Path) -> io::Result<PathBuf> {
+    let backup = backup_path(path);
+    atomic_write(&backup, &fs::read(path)?)?;
+    Ok(backup)
+}
+
+// Find the backups of BUILD.bazel and WORKSPACE files under `root`, skipping
+// those written less than `older_than` ago
+pub fn find_backups(root: &Path, older_than: Option<Duration>) -> io::Result<Vec<Backup>> {
+    let now = SystemTime::now();
+    let mut backups = Vec::new();
+
+    for path in walk(root, None, &[], |name| original_name(name).is_some())? {
+        let modified = fs::metadata(&path)?.modified()?;
+        let age = now.duration_since(modified).unwrap_or_default();
+        if older_than.is_some_and(|older_than| age < older_than) {
+            continue;
+        }
+        let Some(name) = path.file_name().and_then(original_name) else {
+            continue;
+        };
+        let original = path.with_file_name(name);
+        backups.push(Backup {
+            path,
+            original,
+            modified,
+        });
+    }
+
+    Ok(backups)
+}
+
+// Put the backup's content back in place of the original, then remove the
+// backup. The original is replaced atomically, so an interrupted undo leaves
+// either the fixed or the restored file, and the backup is only removed once
+// the restore succeeded.
+pub fn restore_backup(backup: &Backup) -> io::Result<()> {
+    let content = fs::read(&backup.path)?;
+    atomic_write(&backup.original, &content)?;
+    fs::remove_file(&backup.path)
+}
+
+/// Parse a duration such as `90s`, `15m`, `2h` or `7d`.
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (amount, unit) = text.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration {:?}: expected e.g. 30m, 2h or 7d", text))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "invalid duration unit {:?}: expected s, m, h or d",
+                unit
+            ))
+        }
+    };
+    amount
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("invalid duration {:?}: too long", text))
+}
+
+// The name of the file a backup named `name` restores, if it is one
+fn original_name(name: &OsStr) -> Option<OsString> {
+    let original = name.to_str()?.strip_suffix(BACKUP_SUFFIX)?;
+    let original = OsString::from(original);
+    (original == "BUILD.bazel" || is_workspace_file(&original)).then_some(original)
+}
@@ -0,0 +1,19 @@
+//! Location of the Bazel workspace a directory belongs to.
+
+use std::path::{Path, PathBuf};
+
+/// Files that mark the root directory of a Bazel workspace.
+pub const WORKSPACE_MARKERS: &[&str] = &["WORKSPACE", "WORKSPACE.bazel", "MODULE.bazel"];
+
+/// The nearest directory at or above `start` that contains one of the
+/// [`WORKSPACE_MARKERS`].
+pub fn find_workspace_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| {
+            WORKSPACE_MARKERS
+                .iter()
+                .any(|marker| dir.join(marker).is_file())
+        })
+        .map(Path::to_path_buf)
+}
@@ -0,0 +1,95 @@
+use std::fs;
+
+use serde_json::Value;
+
+use crate::common::{umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+const COMMENTED_OUT_RULE: &str = include_str!("fixtures/commented_out_rule.BUILD");
+
+fn exit_summary(stderr: &[u8]) -> Value {
+    let stderr = String::from_utf8_lossy(stderr);
+    let line = stderr.lines().last().unwrap_or_default();
+    serde_json::from_str(line).unwrap_or_else(|err| panic!("{}: {}", err, stderr))
+}
+
+#[test]
+fn fixable_issues_are_fixed_and_pass() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--assert-no-issues", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(exit_summary(&output.stderr)["remaining"], 0);
+    let content = fs::read_to_string(dir.path().join("Sources/Core/BUILD.bazel")).unwrap();
+    assert_ne!(content, DIRTY);
+}
+
+#[test]
+fn manual_fix_issues_fail_with_a_json_summary() {
+    let dir = workspace(&[("Sources/Core", COMMENTED_OUT_RULE)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--assert-no-issues", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1 issues remain:"), "{}", stdout);
+    assert!(
+        stdout.contains("Sources/Core/BUILD.bazel: [CommentedOutRule]"),
+        "{}",
+        stdout
+    );
+    let summary = exit_summary(&output.stderr);
+    assert_eq!(summary["remaining"], 1);
+    assert_eq!(summary["issues"][0]["file"], "Sources/Core/BUILD.bazel");
+    assert_eq!(summary["issues"][0]["issue"], "CommentedOutRule");
+    assert_eq!(summary["issues"][0]["fixable"], false);
+}
+
+#[test]
+fn dry_run_counts_fixable_issues_as_remaining() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--assert-no-issues", "--dry-run", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let summary = exit_summary(&output.stderr);
+    assert!(summary["issues"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|issue| issue["fixable"] == true));
+    let content = fs::read_to_string(dir.path().join("Sources/Core/BUILD.bazel")).unwrap();
+    assert_eq!(content, DIRTY);
+}
+
+#[test]
+fn fixed_files_are_analyzed_again() {
+    // Splitting the deps list still leaves the label on a line over the limit
+    let content = "load(\"@build_bazel_rules_swift//swift:swift.bzl\", \"swift_library\")\n\n\
+                   package(default_visibility = [\"//visibility:public\"])\n\n\
+                   swift_library(\n    name = \"Core\",\n    \
+                   srcs = glob([\"*.swift\"], allow_empty = True),\n    \
+                   deps = [\"//some/very/long/package/path/that/is/long:Target\"],\n)\n";
+    let dir = workspace(&[("Sources/Core", content)]);
+    fs::write(dir.path().join("umbra-fix.toml"), "max_line_length = 50\n").unwrap();
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--assert-no-issues", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let summary = exit_summary(&output.stderr);
+    let issues = summary["issues"].as_array().unwrap();
+    assert!(
+        issues.iter().any(|issue| issue["issue"] == "LineTooLong"
+            && issue["message"]
+                .as_str()
+                .unwrap()
+                .contains("deps of \"Core\"")),
+        "{:?}",
+        issues
+    );
+}
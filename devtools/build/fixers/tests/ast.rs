@@ -0,0 +1,233 @@
+use umbra_build_fixer::starlark::ast::{parse, AttrValue, Attribute, LoadedSymbol};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+const FORMAT_INPUT: &str = include_str!("fixtures/format/input.BUILD");
+
+fn string(value: &str) -> AttrValue {
+    AttrValue::String(value.to_string())
+}
+
+fn strings(values: &[&str]) -> AttrValue {
+    AttrValue::List(values.iter().map(|value| string(value)).collect())
+}
+
+#[test]
+fn parses_loads_with_aliases() {
+    let file = parse(
+        r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library", test = "swift_test")
+load("//tools:defs.bzl", "umbra_library")
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(file.loads.len(), 2);
+    assert_eq!(
+        file.loads[0].label,
+        "@build_bazel_rules_swift//swift:swift.bzl"
+    );
+    assert_eq!(
+        file.loads[0].symbols,
+        vec![
+            LoadedSymbol {
+                local: "swift_library".to_string(),
+                exported: "swift_library".to_string(),
+            },
+            LoadedSymbol {
+                local: "test".to_string(),
+                exported: "swift_test".to_string(),
+            },
+        ]
+    );
+    assert_eq!(file.loads[1].line, 2);
+}
+
+#[test]
+fn parses_package_declaration() {
+    let file = parse(r#"package(default_visibility = ["//visibility:public"])"#).unwrap();
+
+    let package = file.package.unwrap();
+    assert_eq!(
+        package.attrs,
+        vec![Attribute {
+            key: "default_visibility".to_string(),
+            value: strings(&["//visibility:public"]),
+        }]
+    );
+    assert!(file.rules.is_empty());
+}
+
+#[test]
+fn parses_rule_attributes() {
+    let content = r#"
+swift_library(
+    name = "Core",
+    srcs = glob(["**/*.swift"], allow_empty = False),
+    copts = {"opt": ["-O"]},
+    deps = [
+        "//Sources/Base",
+        ":Helpers",  # trailing comment
+    ],
+    testonly = True,
+)
+"#;
+    let file = parse(content).unwrap();
+
+    assert_eq!(file.rules.len(), 1);
+    let rule = &file.rules[0];
+    assert_eq!(rule.rule_name, "swift_library");
+    assert_eq!(rule.name(), Some("Core"));
+    assert_eq!(rule.line, 2);
+    assert_eq!(&content[rule.span.clone()], content.trim());
+    assert_eq!(
+        rule.attr("srcs"),
+        Some(&AttrValue::Call {
+            function: "glob".to_string(),
+            args: vec![strings(&["**/*.swift"])],
+            attrs: vec![Attribute {
+                key: "allow_empty".to_string(),
+                value: AttrValue::Ident("False".to_string()),
+            }],
+        })
+    );
+    assert_eq!(
+        rule.attr("copts"),
+        Some(&AttrValue::Dict(vec![(string("opt"), strings(&["-O"]))]))
+    );
+    assert_eq!(
+        rule.attr("deps"),
+        Some(&strings(&["//Sources/Base", ":Helpers"]))
+    );
+    assert_eq!(
+        rule.attr("testonly"),
+        Some(&AttrValue::Ident("True".to_string()))
+    );
+}
+
+#[test]
+fn parses_select_and_concatenation() {
+    let file = parse(
+        r#"
+swift_library(
+    name = "Core",
+    deps = [":Base"] + select({
+        "//conditions:ios": [":UIKitSupport"],
+        "//conditions:default": [],
+    }),
+)
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        file.rules[0].attr("deps"),
+        Some(&AttrValue::Concat(vec![
+            strings(&[":Base"]),
+            AttrValue::Select(vec![
+                ("//conditions:ios".to_string(), strings(&[":UIKitSupport"])),
+                ("//conditions:default".to_string(), AttrValue::List(vec![])),
+            ]),
+        ]))
+    );
+}
+
+#[test]
+fn parses_positional_arguments_and_dotted_calls() {
+    let file = parse(
+        r#"
+exports_files(["Info.plist"])
+filegroup(name = "all", srcs = native.glob(["*"]), tags = -1)
+selects.config_setting_group(name = "any", match_any = [":a", ":b"])
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(file.rules[0].args, vec![strings(&["Info.plist"])]);
+    assert!(file.rules[0].attrs.is_empty());
+    assert!(matches!(
+        file.rules[1].attr("srcs"),
+        Some(AttrValue::Call { function, .. }) if function == "native.glob"
+    ));
+    assert_eq!(
+        file.rules[1].attr("tags"),
+        Some(&AttrValue::Number("-1".to_string()))
+    );
+    assert_eq!(file.rules[2].rule_name, "selects.config_setting_group");
+    assert_eq!(file.rules[2].name(), Some("any"));
+}
+
+#[test]
+fn skips_top_level_assignments() {
+    let file = parse(
+        r#"
+COPTS = ["-Osize"] + select({"//conditions:default": []})
+
+swift_library(name = "Core", copts = COPTS)
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(file.rules.len(), 1);
+    assert_eq!(
+        file.rules[0].attr("copts"),
+        Some(&AttrValue::Ident("COPTS".to_string()))
+    );
+}
+
+#[test]
+fn parses_fixture_build_files() {
+    for content in [CLEAN, DIRTY, FORMAT_INPUT] {
+        let file = parse(content).unwrap();
+        assert!(!file.rules.is_empty());
+    }
+}
+
+#[test]
+fn parses_empty_file() {
+    let file = parse("# Nothing here\n").unwrap();
+
+    assert!(file.loads.is_empty());
+    assert!(file.package.is_none());
+    assert!(file.rules.is_empty());
+}
+
+#[test]
+fn reports_unclosed_call() {
+    let error = parse("swift_library(\n    name = \"Core\",\n").unwrap_err();
+
+    assert_eq!(error.line, 2);
+    assert!(error.message.contains("unclosed"), "{}", error);
+}
+
+#[test]
+fn reports_missing_comma() {
+    let error = parse("swift_library(\n    name = \"Core\"\n    srcs = [],\n)\n").unwrap_err();
+
+    assert_eq!(error.line, 3);
+    assert_eq!(
+        error.to_string(),
+        "line 3: expected ',' or ')', found \"srcs\""
+    );
+}
+
+#[test]
+fn reports_unsupported_constructs() {
+    for content in [
+        "x = [f for f in glob([\"*\"])]",
+        "swift_library(name = \"a\" if True else \"b\")",
+        "swift_library(**kwargs)",
+        "swift_library(name = \"Core\", \"positional\")",
+        "def helper():\n    pass\n",
+        "x = \"%s\" % name",
+    ] {
+        assert!(parse(content).is_err(), "{}", content);
+    }
+}
+
+#[test]
+fn reports_malformed_load_and_select() {
+    assert!(parse("load(\"swift_library\" + \"x\")").is_err());
+    assert!(parse("load(name = \"//a.bzl\")").is_err());
+    assert!(parse("x = select([\":a\"])").is_err());
+    assert!(parse("package()\npackage()").is_err());
+}
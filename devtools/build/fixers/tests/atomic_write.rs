@@ -0,0 +1,67 @@
+use std::fs;
+
+use umbra_build_fixer::atomic_write::{atomic_write, temp_path_for, write_temp_file};
+
+#[test]
+fn atomic_write_replaces_content_and_leaves_no_temp_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("BUILD.bazel");
+    fs::write(&path, "old\n").unwrap();
+
+    atomic_write(&path, b"new\n").unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+    assert!(!temp_path_for(&path).exists());
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn crash_before_rename_leaves_original_intact() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("BUILD.bazel");
+    fs::write(&path, "original\n").unwrap();
+
+    // Simulate the process dying after the temp file was written but before
+    // it was renamed into place.
+    let temp_path = write_temp_file(&path, b"half-writ").unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "original\n");
+    assert!(temp_path
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .starts_with("BUILD.bazel.umbra-fix.tmp."));
+
+    // The next run reuses the stale temp file and completes normally.
+    atomic_write(&path, b"fixed\n").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "fixed\n");
+    assert!(!temp_path.exists());
+}
+
+#[test]
+fn failed_write_leaves_original_intact() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("BUILD.bazel");
+    fs::write(&path, "original\n").unwrap();
+    // A directory in the temp file's place makes the write fail.
+    fs::create_dir(temp_path_for(&path)).unwrap();
+
+    assert!(atomic_write(&path, b"new\n").is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "original\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn permissions_are_preserved() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("BUILD.bazel");
+    fs::write(&path, "old\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+    atomic_write(&path, b"new\n").unwrap();
+
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o640);
+}
@@ -0,0 +1,168 @@
+use umbra_build_fixer::checks::attributes::{
+    check_absolute_include_paths, check_generates_header_conflict,
+    check_generates_header_consistency, check_testonly, check_visibility_format,
+    fix_absolute_include_paths, fix_generates_header_consistency, fix_testonly,
+    fix_visibility_format,
+};
+use umbra_build_fixer::BuildIssue;
+
+use crate::common::{umbra_fix, workspace};
+
+#[test]
+fn swift_test_with_testonly_is_left_alone() {
+    let content = "swift_test(\n    name = \"CoreTests\",\n    testonly = True,\n)\n";
+
+    assert!(check_testonly(content).is_none());
+    assert_eq!(fix_testonly(content), content);
+}
+
+#[test]
+fn missing_testonly_is_inserted_after_name() {
+    let content = "swift_test(\n    name = \"CoreTests\",\n    srcs = [\"CoreTests.swift\"],\n)\n\nswift_test(name = \"UtilsTests\")\n";
+
+    let (issue, message) = check_testonly(content).unwrap();
+    assert_eq!(issue, BuildIssue::MissingTestonly);
+    assert!(message.contains("CoreTests, UtilsTests"), "{}", message);
+    assert_eq!(
+        fix_testonly(content),
+        "swift_test(\n    name = \"CoreTests\",\n    testonly = True,\n    srcs = [\"CoreTests.swift\"],\n)\n\nswift_test(name = \"UtilsTests\", testonly = True)\n"
+    );
+}
+
+#[test]
+fn testonly_false_is_changed_to_true() {
+    let content = "swift_test(\n    name = \"CoreTests\",\n    testonly = False,\n)\n";
+
+    assert!(check_testonly(content).is_some());
+    let fixed = fix_testonly(content);
+    assert_eq!(
+        fixed,
+        "swift_test(\n    name = \"CoreTests\",\n    testonly = True,\n)\n"
+    );
+    assert!(check_testonly(&fixed).is_none());
+}
+
+const HEADER_LIBRARY: &str = r#"swift_library(
+    name = "Bridge",
+    srcs = ["Bridge.swift"],
+    generated_header_name = "Bridge-Swift.h",
+)
+"#;
+
+#[test]
+fn generates_header_is_added_next_to_generated_header_name() {
+    let (issue, _) = check_generates_header_consistency(HEADER_LIBRARY).unwrap();
+    assert_eq!(issue, BuildIssue::MissingGeneratesHeader);
+
+    let fixed = fix_generates_header_consistency(HEADER_LIBRARY);
+    assert_eq!(
+        fixed,
+        HEADER_LIBRARY.replace(
+            "    name = \"Bridge\",\n",
+            "    name = \"Bridge\",\n    generates_header = True,\n"
+        )
+    );
+    assert!(check_generates_header_consistency(&fixed).is_none());
+    assert!(check_generates_header_conflict(&fixed).is_none());
+}
+
+#[test]
+fn generates_header_false_is_a_manual_conflict() {
+    let content = HEADER_LIBRARY.replace("    srcs", "    generates_header = False,\n    srcs");
+    assert!(check_generates_header_consistency(&content).is_none());
+    let (issue, _) = check_generates_header_conflict(&content).unwrap();
+    assert_eq!(issue, BuildIssue::GeneratesHeaderConflict);
+    assert!(!issue.is_fixable());
+
+    let full = format!(
+        "{}\npackage(default_visibility = [\"//visibility:public\"])\n\n{}",
+        "load(\"@build_bazel_rules_swift//swift:swift.bzl\", \"swift_library\")\n", content
+    );
+    let dir = workspace(&[("Sources/Bridge", full.as_str())]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--root", root]);
+
+    // --check only fails on issues it could fix
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("needs manual fix: [GeneratesHeaderConflict]"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("0 fixable issues"), "{}", stdout);
+
+    let output = umbra_fix(&["--assert-no-issues", "--dry-run", "--root", root]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn visibility_lists_are_left_alone() {
+    let content = "package(default_visibility = [\"//visibility:public\"])\n\nswift_library(\n    name = \"Core\",\n    visibility = [\"//Sources:__subpackages__\"],\n)\n";
+
+    assert!(check_visibility_format(content).is_none());
+    assert_eq!(fix_visibility_format(content), content);
+}
+
+#[test]
+fn bare_string_visibility_is_wrapped_in_a_list() {
+    let content = "package(default_visibility = \"//visibility:public\")\n\nswift_library(\n    name = \"Core\",\n    visibility = \"//visibility:private\",\n)\n";
+
+    let (issue, message) = check_visibility_format(content).unwrap();
+    assert_eq!(issue, BuildIssue::IncorrectVisibilityFormat);
+    assert!(message.contains("line 1, 5"), "{}", message);
+    assert_eq!(
+        fix_visibility_format(content),
+        "package(default_visibility = [\"//visibility:public\"])\n\nswift_library(\n    name = \"Core\",\n    visibility = [\"//visibility:private\"],\n)\n"
+    );
+}
+
+#[test]
+fn select_visibility_is_not_modified() {
+    let content = "swift_library(\n    name = \"Core\",\n    visibility = select({\n        \":internal\": [\"//visibility:private\"],\n        \"//conditions:default\": [\"//visibility:public\"],\n    }),\n)\n";
+
+    assert!(check_visibility_format(content).is_none());
+    assert_eq!(fix_visibility_format(content), content);
+}
+
+#[test]
+fn relative_and_gendir_include_paths_are_allowed() {
+    let content = "swift_library(\n    name = \"Core\",\n    copts = [\n        \"-Xcc\",\n        \"-Iinclude\",\n        \"-I$(GENDIR)/Sources/Core\",\n    ],\n)\n";
+
+    assert!(check_absolute_include_paths(content).is_none());
+    assert_eq!(fix_absolute_include_paths(content), content);
+}
+
+#[test]
+fn absolute_include_path_is_removed_with_a_comment() {
+    let content = "swift_library(\n    name = \"Core\",\n    copts = [\n        \"-warnings-as-errors\",\n        \"-Xcc\",\n        \"-I/usr/local/include\",\n    ],\n)\n";
+
+    let (issue, message) = check_absolute_include_paths(content).unwrap();
+    assert_eq!(issue, BuildIssue::AbsoluteIncludePath);
+    assert!(
+        message.contains("\"-I/usr/local/include\" (Core)"),
+        "{}",
+        message
+    );
+    assert_eq!(
+        fix_absolute_include_paths(content),
+        "swift_library(\n    name = \"Core\",\n    # umbra-fix: removed -I/usr/local/include (absolute include paths break hermetic builds); depend on a cc_library that provides the headers instead\n    copts = [\n        \"-warnings-as-errors\",\n    ],\n)\n"
+    );
+}
+
+#[test]
+fn every_absolute_include_path_is_removed() {
+    let content = "swift_library(\n    name = \"Core\",\n    copts = [\"-I/opt/a\", \"-Iinclude\", \"-I/Users/dev/b\"],\n)\n";
+
+    let fixed = fix_absolute_include_paths(content);
+
+    assert!(
+        fixed.contains("    copts = [\"-Iinclude\"],\n"),
+        "{}",
+        fixed
+    );
+    assert!(fixed.contains("removed -I/opt/a"));
+    assert!(fixed.contains("removed -I/Users/dev/b"));
+    assert!(check_absolute_include_paths(&fixed).is_none());
+}
@@ -0,0 +1,173 @@
+use std::fs;
+
+use umbra_build_fixer::baseline::Baseline;
+use umbra_build_fixer::{fix_build_file, BuildIssue, RunMode};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+const SWIFT_TEST: &str = r#"
+load("@build_bazel_rules_swift//swift:swift.bzl", "swift_test")
+
+swift_test(
+    name = "CoreTests",
+    srcs = ["CoreTests.swift"],
+)
+"#;
+
+#[test]
+fn only_issues_missing_from_the_baseline_are_reported() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+    let baseline = dir.path().join("baseline.json");
+    let baseline_arg = baseline.to_str().unwrap();
+
+    let output = umbra_fix(&[
+        "--baseline",
+        baseline_arg,
+        "--save-baseline",
+        "--root",
+        root,
+    ]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("issues to baseline"), "{}", stdout);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    assert_eq!(fs::read_to_string(&path).unwrap(), DIRTY);
+
+    // Everything found so far is baselined
+    let output = umbra_fix(&[
+        "--check",
+        "--no-cache",
+        "--baseline",
+        baseline_arg,
+        "--root",
+        root,
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+
+    fs::write(&path, format!("{}{}", DIRTY, SWIFT_TEST)).unwrap();
+    let output = umbra_fix(&[
+        "--check",
+        "--no-cache",
+        "--baseline",
+        baseline_arg,
+        "--root",
+        root,
+    ]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[MissingTestonly]"), "{}", stdout);
+    assert!(!stdout.contains("[CustomLibraryRule]"), "{}", stdout);
+    assert!(!stdout.contains("[ExportsAttribute]"), "{}", stdout);
+}
+
+#[test]
+fn baselined_issues_are_not_fixed() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+    config.mode = RunMode::DryRun;
+    let report = fix_build_file(&path, &config).unwrap();
+    config.mode = RunMode::Fix;
+    config.baseline = Some(Baseline::from_reports(dir.path(), &[report]));
+    fs::write(&path, format!("{}{}", DIRTY, SWIFT_TEST)).unwrap();
+
+    let report = fix_build_file(&path, &config).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert_eq!(issues, [&BuildIssue::MissingTestonly]);
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(content.contains("testonly = True"), "{}", content);
+    assert!(content.contains("umbra_swift_library("), "{}", content);
+}
+
+#[test]
+fn missing_baseline_file_is_an_error() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--baseline", "missing.json", "--root", root]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("missing.json"), "{}", stderr);
+}
+
+#[test]
+fn baselined_issues_stay_baselined_when_lines_move() {
+    let content = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "Core",
+    srcs = ["Core.swift"],
+    module_name = "UmbraCoreModuleWithAVeryLongNameIndeed",
+)
+"#;
+    let dir = workspace(&[("Sources/Core", content)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+    config.max_line_length = Some(50);
+    config.mode = RunMode::DryRun;
+    let report = fix_build_file(&path, &config).unwrap();
+    assert!(report
+        .findings
+        .iter()
+        .any(|finding| matches!(finding.issue, BuildIssue::LineTooLong { .. })));
+    config.baseline = Some(Baseline::from_reports(dir.path(), &[report]));
+    fs::write(
+        &path,
+        content.replace("package(", "# The package\n# defaults\npackage("),
+    )
+    .unwrap();
+
+    let report = fix_build_file(&path, &config).unwrap();
+
+    assert_eq!(report.findings, []);
+}
+
+#[test]
+fn new_issues_of_a_baselined_kind_are_reported() {
+    let content = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "Core",
+    srcs = glob(["*.swift"]),
+)
+"#;
+    let dir = workspace(&[("Sources/Core", content)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+    config.mode = RunMode::DryRun;
+    let report = fix_build_file(&path, &config).unwrap();
+    assert!(report
+        .findings
+        .iter()
+        .any(|finding| finding.issue == BuildIssue::GlobWithoutAllowEmpty));
+    config.baseline = Some(Baseline::from_reports(dir.path(), &[report]));
+
+    // Moving the glob doesn't make it new, another glob without allow_empty is
+    let moved = content.replace("package(", "# The package\npackage(");
+    fs::write(&path, &moved).unwrap();
+    assert_eq!(fix_build_file(&path, &config).unwrap().findings, []);
+
+    let extras =
+        "\nswift_library(\n    name = \"Extras\",\n    srcs = glob([\"Extras/*.swift\"]),\n)\n";
+    fs::write(&path, format!("{}{}", moved, extras)).unwrap();
+    let report = fix_build_file(&path, &config).unwrap();
+
+    assert!(
+        report
+            .findings
+            .iter()
+            .any(|finding| finding.issue == BuildIssue::GlobWithoutAllowEmpty),
+        "{:?}",
+        report.findings
+    );
+}
@@ -0,0 +1,112 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+
+use umbra_build_fixer::bazel_query::{parse_bazel_errors, run_bazel_query, BazelError};
+
+use crate::common::{fake_bazel, workspace};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+const STDERR: &str = "\
+Loading: 0 packages loaded
+ERROR: /work/Sources/Core/BUILD.bazel:5:14: no such target '//Sources/Errors:Errors': target 'Errors' not declared in package 'Sources/Errors' and referenced by '//Sources/Core:Core'
+ERROR: Evaluation of query \"//...\" failed: errors were encountered while computing transitive closure
+";
+
+#[test]
+fn parses_located_and_unlocated_errors() {
+    let errors = parse_bazel_errors(STDERR);
+
+    assert_eq!(
+        errors,
+        [
+            BazelError {
+                file: Some(PathBuf::from("/work/Sources/Core/BUILD.bazel")),
+                line: Some(5),
+                column: Some(14),
+                rule: Some("//Sources/Core:Core".to_string()),
+                message: "no such target '//Sources/Errors:Errors': target 'Errors' not declared in package 'Sources/Errors' and referenced by '//Sources/Core:Core'".to_string(),
+            },
+            BazelError {
+                file: None,
+                line: None,
+                column: None,
+                rule: None,
+                message: "Evaluation of query \"//...\" failed: errors were encountered while computing transitive closure".to_string(),
+            },
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn reports_errors_from_bazel() {
+    let dir = tempfile::tempdir().unwrap();
+    let bazel = fake_bazel(
+        dir.path(),
+        &format!(
+            "echo \"$@\" > args.txt\ncat >&2 <<'EOF'\n{}EOF\nexit 7\n",
+            STDERR
+        ),
+    );
+
+    let errors =
+        run_bazel_query(&bazel, dir.path(), "//Sources/...", Duration::from_secs(10)).unwrap();
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].rule.as_deref(), Some("//Sources/Core:Core"));
+    let args = fs::read_to_string(dir.path().join("args.txt")).unwrap();
+    assert_eq!(args.trim(), "query --color=no --keep_going //Sources/...");
+}
+
+#[cfg(unix)]
+#[test]
+fn successful_query_has_no_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let bazel = fake_bazel(dir.path(), "echo 'Loading: done' >&2\n");
+
+    let errors = run_bazel_query(&bazel, dir.path(), "//...", Duration::from_secs(10)).unwrap();
+
+    assert!(errors.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn slow_bazel_times_out() {
+    let dir = tempfile::tempdir().unwrap();
+    let bazel = fake_bazel(dir.path(), "sleep 10\n");
+
+    let err = run_bazel_query(&bazel, dir.path(), "//...", Duration::from_millis(200)).unwrap_err();
+
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+}
+
+#[cfg(unix)]
+#[test]
+fn bazel_validate_fails_the_run_on_errors() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let bin = tempfile::tempdir().unwrap();
+    let bazel = fake_bazel(
+        bin.path(),
+        &format!("cat >&2 <<'EOF'\n{}EOF\nexit 7\n", STDERR),
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_umbra-fix"))
+        .args(["--bazel-validate", "--root", dir.path().to_str().unwrap()])
+        .env("UMBRA_FIX_BAZEL", &bazel)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("bazel: /work/Sources/Core/BUILD.bazel:5: no such target"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("(rule //Sources/Core:Core)"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("reported 2 errors"));
+}
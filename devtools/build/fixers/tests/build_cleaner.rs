@@ -0,0 +1,100 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use umbra_build_fixer::build_cleaner::{clean_backups, format_bytes};
+use umbra_build_fixer::undo::backup_path;
+
+use crate::common::{umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+// Write a backup of `package`'s BUILD file, last modified `age` ago
+fn write_backup(root: &Path, package: &str, age: Duration) -> PathBuf {
+    let backup = backup_path(&root.join(package).join("BUILD.bazel"));
+    fs::write(&backup, DIRTY).unwrap();
+    File::options()
+        .write(true)
+        .open(&backup)
+        .unwrap()
+        .set_modified(SystemTime::now() - age)
+        .unwrap();
+    backup
+}
+
+#[test]
+fn clean_deletes_only_old_backups() {
+    let dir = workspace(&[("Sources/Core", DIRTY), ("Sources/Errors", DIRTY)]);
+    let old = write_backup(dir.path(), "Sources/Core", 10 * DAY);
+    let recent = write_backup(dir.path(), "Sources/Errors", DAY);
+
+    let report = clean_backups(dir.path(), 7 * DAY, false).unwrap();
+
+    assert_eq!(report.removed, vec![old.clone()]);
+    assert_eq!(report.bytes_freed, DIRTY.len() as u64);
+    assert!(!old.exists());
+    assert!(recent.exists());
+    assert!(dir.path().join("Sources/Core/BUILD.bazel").exists());
+}
+
+#[test]
+fn dry_run_keeps_old_backups() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let old = write_backup(dir.path(), "Sources/Core", 10 * DAY);
+
+    let report = clean_backups(dir.path(), 7 * DAY, true).unwrap();
+
+    assert_eq!(report.removed, vec![old.clone()]);
+    assert_eq!(report.bytes_freed, DIRTY.len() as u64);
+    assert!(old.exists());
+}
+
+#[test]
+fn clean_command_reports_space_freed() {
+    let dir = workspace(&[("Sources/Core", DIRTY), ("Sources/Errors", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+    let old = write_backup(dir.path(), "Sources/Core", Duration::from_secs(3 * 60 * 60));
+    let recent = write_backup(dir.path(), "Sources/Errors", Duration::from_secs(60));
+
+    let output = umbra_fix(&["clean", "--root", root, "--older-than", "2h", "--dry-run"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Would delete: "), "{}", stdout);
+    assert!(
+        stdout.contains(&format!(
+            "Would delete 1 backups, freeing {}",
+            format_bytes(DIRTY.len() as u64)
+        )),
+        "{}",
+        stdout
+    );
+    assert!(old.exists());
+
+    let output = umbra_fix(&["clean", "--root", root, "--older-than", "2h"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Deleted 1 backups"), "{}", stdout);
+    assert!(!old.exists());
+    assert!(recent.exists());
+}
+
+#[test]
+fn clean_requires_older_than() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+
+    let output = umbra_fix(&["clean", "--root", dir.path().to_str().unwrap()]);
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn bytes_are_formatted() {
+    assert_eq!(format_bytes(0), "0 B");
+    assert_eq!(format_bytes(1023), "1023 B");
+    assert_eq!(format_bytes(1536), "1.5 KiB");
+    assert_eq!(format_bytes(2 * 1024 * 1024), "2.0 MiB");
+}
@@ -0,0 +1,138 @@
+use std::fs;
+
+use umbra_build_fixer::build_validator::{validate_starlark_syntax, SyntaxError};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+const UNCLOSED_CALL: &str = include_str!("fixtures/malformed/unclosed_call.BUILD");
+const UNCLOSED_STRING: &str = include_str!("fixtures/malformed/unclosed_string.BUILD");
+const INVALID_ESCAPE: &str = include_str!("fixtures/malformed/invalid_escape.BUILD");
+const MISMATCHED_BRACKET: &str = include_str!("fixtures/malformed/mismatched_bracket.BUILD");
+
+fn syntax_error(line: usize, column: usize, message: &str) -> SyntaxError {
+    SyntaxError {
+        message: message.to_string(),
+        line,
+        column,
+    }
+}
+
+#[test]
+fn well_formed_files_pass() {
+    assert_eq!(validate_starlark_syntax(CLEAN), Ok(()));
+    assert_eq!(validate_starlark_syntax(DIRTY), Ok(()));
+    assert_eq!(
+        validate_starlark_syntax(
+            "x = [\"a\\tb\\\"\", 'c\\x41\\u00e9\\101', r\"\\d+\", \"\"\"doc \"quoted\" \\\n\"\"\"]\n"
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn unclosed_call_is_rejected() {
+    assert_eq!(
+        validate_starlark_syntax(UNCLOSED_CALL),
+        Err(syntax_error(3, 14, "\"(\" is never closed"))
+    );
+}
+
+#[test]
+fn unclosed_string_is_rejected() {
+    assert_eq!(
+        validate_starlark_syntax(UNCLOSED_STRING),
+        Err(syntax_error(4, 12, "string literal is never closed"))
+    );
+    assert!(validate_starlark_syntax("doc = \"\"\"never closed\n").is_err());
+}
+
+#[test]
+fn invalid_escape_is_rejected() {
+    assert_eq!(
+        validate_starlark_syntax(INVALID_ESCAPE),
+        Err(syntax_error(6, 24, "invalid escape sequence \"\\\\U\""))
+    );
+    assert!(validate_starlark_syntax("x = \"\\x4\"\n").is_err());
+}
+
+#[test]
+fn mismatched_bracket_is_rejected() {
+    assert_eq!(
+        validate_starlark_syntax(MISMATCHED_BRACKET),
+        Err(syntax_error(5, 27, "\")\" doesn't close \"[\" from line 5"))
+    );
+    assert!(validate_starlark_syntax("x = 1)\n").is_err());
+}
+
+#[test]
+fn malformed_file_is_reported_but_not_fixed() {
+    for malformed in [
+        UNCLOSED_CALL,
+        UNCLOSED_STRING,
+        INVALID_ESCAPE,
+        MISMATCHED_BRACKET,
+    ] {
+        let dir = workspace(&[("Sources/Core", malformed)]);
+        let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+        let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+        assert_eq!(report.findings.len(), 1);
+        assert!(matches!(
+            report.findings[0].issue,
+            BuildIssue::InvalidSyntax { .. }
+        ));
+        assert!(report.findings[0].message.starts_with("line "));
+        assert!(!report.modified);
+        assert_eq!(fs::read_to_string(&path).unwrap(), malformed);
+    }
+}
+
+#[test]
+fn malformed_file_does_not_stop_the_run() {
+    let dir = workspace(&[
+        ("Sources/Broken", UNCLOSED_CALL),
+        ("Sources/Core", DIRTY),
+        ("Sources/Utils", DIRTY),
+    ]);
+
+    let output = umbra_fix(&["--check", "--root", dir.path().to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("[InvalidSyntax] line 3:14: \"(\" is never closed"),
+        "{}",
+        stdout
+    );
+    assert_eq!(
+        stdout.matches("[CustomLibraryRule]").count(),
+        2,
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn files_around_a_malformed_one_are_fixed() {
+    let dir = workspace(&[("Sources/Broken", UNCLOSED_CALL), ("Sources/Core", DIRTY)]);
+
+    let output = umbra_fix(&["--root", dir.path().to_str().unwrap()]);
+
+    // The run still fails, as the malformed file was rejected
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("error: 1 BUILD files are malformed and were left unchanged"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("Sources/Broken/BUILD.bazel"), "{}", stderr);
+    let broken = fs::read_to_string(dir.path().join("Sources/Broken/BUILD.bazel")).unwrap();
+    assert_eq!(broken, UNCLOSED_CALL);
+    let fixed = fs::read_to_string(dir.path().join("Sources/Core/BUILD.bazel")).unwrap();
+    assert_ne!(fixed, DIRTY);
+}
@@ -0,0 +1,97 @@
+use std::fs;
+
+use umbra_build_fixer::checks::apple::{check_bundle_id, fix_bundle_id, DEFAULT_BUNDLE_ID_PREFIX};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+fn application(rule: &str) -> String {
+    format!(
+        "{}(\n    name = \"App\",\n    minimum_os_version = \"14.0\",\n)\n",
+        rule
+    )
+}
+
+#[test]
+fn applications_without_bundle_id_are_flagged_and_fixed() {
+    for rule in [
+        "ios_application",
+        "macos_application",
+        "watchos_application",
+    ] {
+        let content = application(rule);
+        let issues = check_bundle_id(&content, DEFAULT_BUNDLE_ID_PREFIX);
+
+        let issue = BuildIssue::MissingBundleId {
+            target: "App".to_string(),
+            bundle_id: "com.example.App".to_string(),
+        };
+        assert_eq!(issues.len(), 1, "{}", rule);
+        assert_eq!(issues[0].0, issue, "{}", rule);
+        assert_eq!(
+            fix_bundle_id(&content, "App", "com.example.App"),
+            format!(
+                "{}(\n    name = \"App\",\n    bundle_id = \"com.example.App\",\n    minimum_os_version = \"14.0\",\n)\n",
+                rule
+            )
+        );
+    }
+}
+
+#[test]
+fn empty_bundle_id_is_replaced() {
+    let content = "ios_application(\n    name = \"App\",\n    bundle_id = \"\",\n)\n";
+
+    let issues = check_bundle_id(content, "com.umbra");
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(
+        fix_bundle_id(content, "App", "com.umbra.App"),
+        "ios_application(\n    name = \"App\",\n    bundle_id = \"com.umbra.App\",\n)\n"
+    );
+}
+
+#[test]
+fn existing_and_computed_bundle_ids_are_kept() {
+    let set = "macos_application(\n    name = \"App\",\n    bundle_id = \"com.umbra.app\",\n)\n";
+    let computed =
+        "macos_application(\n    name = \"App\",\n    bundle_id = BUNDLE_PREFIX + \".app\",\n)\n";
+    let library = "swift_library(\n    name = \"App\",\n    srcs = [\"App.swift\"],\n)\n";
+
+    for content in [set, computed, library] {
+        assert!(check_bundle_id(content, DEFAULT_BUNDLE_ID_PREFIX).is_empty());
+    }
+}
+
+#[test]
+fn target_names_are_made_valid_bundle_ids() {
+    let content = application("ios_application").replace("\"App\"", "\"umbra_app\"");
+
+    let issues = check_bundle_id(&content, "com.umbra.");
+
+    assert!(matches!(
+        &issues[0].0,
+        BuildIssue::MissingBundleId { bundle_id, .. } if bundle_id == "com.umbra.umbra-app"
+    ));
+}
+
+#[test]
+fn prefix_is_read_from_config_file() {
+    let dir = workspace(&[("Apps/Umbra", &application("watchos_application"))]);
+    fs::write(
+        dir.path().join("umbra-fix.toml"),
+        // Bundling rules are outside the default swift_* rules
+        "bundle_id_prefix = \"dev.umbra\"\nrule_filter = []\n",
+    )
+    .unwrap();
+    let path = dir.path().join("Apps/Umbra/BUILD.bazel");
+
+    fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(
+        fixed.contains("name = \"App\",\n    bundle_id = \"dev.umbra.App\",\n"),
+        "{}",
+        fixed
+    );
+}
@@ -0,0 +1,111 @@
+use std::fs;
+
+use umbra_build_fixer::cache::{Cache, CACHE_FILE_NAME};
+use umbra_build_fixer::{fix_build_file_with_cache, BuildIssue, Config, RunMode};
+
+use crate::common::{umbra_fix, workspace};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+#[test]
+fn second_run_on_unchanged_repo_skips_analysis() {
+    let dir = workspace(&[("Sources/Core", CLEAN), ("Sources/Utils", CLEAN)]);
+    let root = dir.path().to_str().unwrap();
+    let core = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let first = umbra_fix(&["--root", root, "--metrics"]);
+    assert!(first.status.success(), "{:?}", first);
+    assert!(dir.path().join(CACHE_FILE_NAME).is_file());
+    let modified = fs::metadata(&core).unwrap().modified().unwrap();
+
+    let second = umbra_fix(&["--root", root, "--metrics"]);
+
+    assert!(second.status.success(), "{:?}", second);
+    let first = String::from_utf8_lossy(&first.stdout);
+    let second = String::from_utf8_lossy(&second.stdout);
+    assert!(
+        second.contains("Skipped analysis of 2 unchanged files (cached)"),
+        "{}",
+        second
+    );
+    // The analyze phase, where the time goes, doesn't run at all
+    assert!(first.contains("\nanalyze "), "{}", first);
+    assert!(!second.contains("\nanalyze "), "{}", second);
+    assert_eq!(fs::metadata(&core).unwrap().modified().unwrap(), modified);
+    assert_eq!(fs::read_to_string(&core).unwrap(), CLEAN);
+}
+
+#[test]
+fn cached_findings_are_reused_for_unchanged_content() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = Config::load(dir.path()).unwrap();
+    config.mode = RunMode::DryRun;
+    let mut cache = Cache::new(&config);
+
+    let first = fix_build_file_with_cache(&path, &config, &mut cache).unwrap();
+    let second = fix_build_file_with_cache(&path, &config, &mut cache).unwrap();
+
+    assert_eq!(cache.hits(), 1);
+    assert_eq!(second.findings, first.findings);
+    assert!(second
+        .findings
+        .iter()
+        .any(|finding| finding.issue == BuildIssue::CustomLibraryRule));
+}
+
+#[test]
+fn changes_to_the_package_invalidate_its_entry() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let root = dir.path().to_str().unwrap();
+    assert!(umbra_fix(&["--root", root]).status.success());
+    fs::create_dir_all(dir.path().join("Sources/Core/Internal")).unwrap();
+    fs::write(dir.path().join("Sources/Core/Internal/Helper.swift"), "").unwrap();
+
+    let output = umbra_fix(&["--root", root, "--dry-run"]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("Skipped analysis"), "{}", stdout);
+    assert!(stdout.contains("[OrphanedSourceFile]"), "{}", stdout);
+}
+
+#[test]
+fn no_cache_and_invalidate_cache_analyze_everything() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let root = dir.path().to_str().unwrap();
+    assert!(umbra_fix(&["--root", root]).status.success());
+
+    for flag in ["--no-cache", "--invalidate-cache"] {
+        let output = umbra_fix(&["--root", root, flag]);
+
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(!stdout.contains("Skipped analysis"), "{}: {}", flag, stdout);
+    }
+}
+
+#[test]
+fn cache_from_different_settings_is_discarded() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let root = dir.path().to_str().unwrap();
+    assert!(umbra_fix(&["--root", root]).status.success());
+
+    let output = umbra_fix(&["--root", root, "--sort-attributes"]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("Skipped analysis"), "{}", stdout);
+}
+
+#[test]
+fn runs_that_only_report_leave_no_cache() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let root = dir.path().to_str().unwrap();
+
+    for flag in ["--check", "--dry-run", "--diff-only"] {
+        let output = umbra_fix(&["--root", root, flag]);
+
+        assert!(output.status.success(), "{}: {:?}", flag, output);
+        assert!(!dir.path().join(CACHE_FILE_NAME).exists(), "{}", flag);
+    }
+}
@@ -0,0 +1,45 @@
+use std::fs;
+
+use crate::common::{umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+#[test]
+fn check_fails_on_dirty_workspace_without_modifying_files() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[CustomLibraryRule]"), "{}", stdout);
+    assert!(!stdout.contains("Modifying"), "{}", stdout);
+    let content = fs::read_to_string(dir.path().join("Sources/Core/BUILD.bazel")).unwrap();
+    assert_eq!(content, DIRTY);
+}
+
+#[test]
+fn check_passes_on_clean_workspace() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("0 fixable issues"), "{}", stdout);
+}
+
+#[test]
+fn dry_run_succeeds_on_dirty_workspace() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--dry-run", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(0));
+    let content = fs::read_to_string(dir.path().join("Sources/Core/BUILD.bazel")).unwrap();
+    assert_eq!(content, DIRTY);
+}
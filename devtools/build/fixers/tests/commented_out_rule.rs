@@ -0,0 +1,89 @@
+use std::fs;
+
+use umbra_build_fixer::checks::comments::{check_commented_out_rules, remove_comment_block};
+use umbra_build_fixer::BuildIssue;
+
+use crate::common::{umbra_fix, workspace};
+
+const COMMENTED_OUT: &str = include_str!("fixtures/commented_out_rule.BUILD");
+
+#[test]
+fn commented_out_rule_is_flagged_with_its_first_line() {
+    let issues = check_commented_out_rules(COMMENTED_OUT);
+
+    // The 4-line prose comment assigns no attributes and is left alone
+    let flagged: Vec<&BuildIssue> = issues.iter().map(|(issue, _)| issue).collect();
+    assert_eq!(flagged, [&BuildIssue::CommentedOutRule { line: 14 }]);
+    assert!(issues[0].1.contains("lines 14-23"), "{}", issues[0].1);
+    assert!(
+        issues[0].1.contains("umbra-fix --remove-comment-block 14"),
+        "{}",
+        issues[0].1
+    );
+}
+
+#[test]
+fn short_blocks_are_not_flagged() {
+    let content = "# swift_library(\n#     name = \"Old\",\n# )\nfilegroup(name = \"Files\")\n";
+
+    assert!(check_commented_out_rules(content).is_empty());
+}
+
+#[test]
+fn commented_out_rule_needs_a_manual_fix() {
+    let dir = workspace(&[("Sources/Core", COMMENTED_OUT)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--root", root]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("needs manual fix: [CommentedOutRule]"),
+        "{}",
+        stdout
+    );
+    let content = fs::read_to_string(dir.path().join("Sources/Core/BUILD.bazel")).unwrap();
+    assert_eq!(content, COMMENTED_OUT);
+}
+
+#[test]
+fn block_is_removed_with_a_blank_line_around_it() {
+    let fixed = remove_comment_block(COMMENTED_OUT, 14).unwrap();
+
+    assert_eq!(
+        fixed,
+        COMMENTED_OUT[..COMMENTED_OUT.find("\n\n# swift_library").unwrap() + 1]
+    );
+    assert!(remove_comment_block(COMMENTED_OUT, 15).is_none());
+}
+
+#[test]
+fn remove_comment_block_flag_edits_the_file() {
+    let dir = workspace(&[("Sources/Core", COMMENTED_OUT)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let output = umbra_fix(&["--remove-comment-block", "14", path.to_str().unwrap()]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(!content.contains("LegacyCore"), "{}", content);
+    assert!(check_commented_out_rules(&content).is_empty());
+
+    let output = umbra_fix(&["--remove-comment-block", "14", path.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("no block of comments starts on line 14"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn block_between_rules_leaves_one_blank_line() {
+    let content = format!("{}\nfilegroup(\n    name = \"Files\",\n)\n", COMMENTED_OUT);
+
+    let fixed = remove_comment_block(&content, 14).unwrap();
+
+    assert!(fixed.contains(")\n\nfilegroup(\n"), "{}", fixed);
+}
@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use tempfile::TempDir;
+use umbra_build_fixer::Config;
+
+/// Create a temporary workspace containing `BUILD.bazel` files at the given relative dirs.
+pub fn workspace(files: &[(&str, &str)]) -> TempDir {
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+    for (package, content) in files {
+        write_build_file(dir.path(), package, content);
+    }
+    dir
+}
+
+pub fn write_build_file(root: &Path, package: &str, content: &str) -> PathBuf {
+    let package_dir = root.join(package);
+    fs::create_dir_all(&package_dir).expect("failed to create package dir");
+    let path = package_dir.join("BUILD.bazel");
+    fs::write(&path, content).expect("failed to write BUILD.bazel");
+    path
+}
+
+/// Config for tests: fixes are always checked for idempotency.
+pub fn test_config(root: &Path) -> Config {
+    let mut config = Config::load(root).expect("failed to load config");
+    config.verify_idempotent = true;
+    config
+}
+
+/// Run the `umbra-fix` binary with the given arguments.
+pub fn umbra_fix(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_umbra-fix"))
+        .args(args)
+        .env_remove("GITHUB_ACTIONS")
+        .output()
+        .expect("failed to run umbra-fix")
+}
+
+/// Run git in `dir` as a test user, failing the test if it fails.
+pub fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .status()
+        .expect("failed to run git");
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+/// A workspace with `files` committed to a new git repository.
+pub fn git_repo(files: &[(&str, &str)]) -> tempfile::TempDir {
+    let dir = workspace(files);
+    git(dir.path(), &["init", "--quiet"]);
+    git(dir.path(), &["add", "."]);
+    git(
+        dir.path(),
+        &["commit", "--quiet", "--allow-empty", "-m", "initial"],
+    );
+    dir
+}
+
+/// Write an executable shell script standing in for bazel.
+#[cfg(unix)]
+pub fn fake_bazel(dir: &Path, script: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join("bazel");
+    fs::write(&path, format!("#!/bin/sh\n{}", script)).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
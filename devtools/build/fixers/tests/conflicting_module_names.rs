@@ -0,0 +1,211 @@
+use std::fs;
+
+use umbra_build_fixer::checks::analyze_target_graph;
+use umbra_build_fixer::checks::module_names::check_conflicting_module_names;
+use umbra_build_fixer::target_graph::build_target_graph;
+use umbra_build_fixer::{analyze_build_file_at, find_build_files, BuildIssue, Config};
+
+use crate::common::{umbra_fix, workspace, write_build_file};
+
+const CORE: &str = include_str!("fixtures/conflicting_module_names/core.BUILD");
+const LEGACY_CORE: &str = include_str!("fixtures/conflicting_module_names/legacy_core.BUILD");
+const UTILS: &str = include_str!("fixtures/conflicting_module_names/utils.BUILD");
+
+fn conflicting_workspace() -> tempfile::TempDir {
+    workspace(&[
+        ("Sources/Core", CORE),
+        ("Sources/LegacyCore", LEGACY_CORE),
+        ("Sources/Utils", UTILS),
+    ])
+}
+
+fn conflicts(target: &str, conflicts: &[&str]) -> BuildIssue {
+    BuildIssue::ConflictingModuleNames {
+        target: target.to_string(),
+        module_name: "Core".to_string(),
+        conflicts: conflicts.iter().map(|label| label.to_string()).collect(),
+    }
+}
+
+#[test]
+fn libraries_building_the_same_module_are_flagged() {
+    let dir = conflicting_workspace();
+    let build_files = find_build_files(&Config::new(dir.path())).unwrap();
+    let (graph, _) = build_target_graph(dir.path(), &build_files);
+
+    let issues = check_conflicting_module_names(&graph);
+
+    let flagged: Vec<(String, &BuildIssue)> = issues
+        .iter()
+        .map(|(label, issue, _)| (label.to_string(), issue))
+        .collect();
+    // module_name wins over the target name; swift_test targets don't count
+    assert_eq!(
+        flagged,
+        [
+            (
+                "//Sources/Core:Core".to_string(),
+                &conflicts(
+                    "Core",
+                    &["//Sources/LegacyCore:LegacyCore", "//Sources/Utils:Core"]
+                )
+            ),
+            (
+                "//Sources/LegacyCore:LegacyCore".to_string(),
+                &conflicts(
+                    "LegacyCore",
+                    &["//Sources/Core:Core", "//Sources/Utils:Core"]
+                )
+            ),
+            (
+                "//Sources/Utils:Core".to_string(),
+                &conflicts(
+                    "Core",
+                    &["//Sources/Core:Core", "//Sources/LegacyCore:LegacyCore"]
+                )
+            ),
+        ]
+    );
+    assert!(issues[0].2.contains("builds Swift module \"Core\""));
+}
+
+#[test]
+fn unique_module_names_are_not_flagged() {
+    let dir = workspace(&[("Sources/Core", CORE), ("Sources/LegacyCore", UTILS)]);
+    fs::write(
+        dir.path().join("Sources/LegacyCore/BUILD.bazel"),
+        UTILS.replace("\"Core\"", "\"LegacyCore\""),
+    )
+    .unwrap();
+    let build_files = find_build_files(&Config::new(dir.path())).unwrap();
+    let (graph, _) = build_target_graph(dir.path(), &build_files);
+
+    assert!(check_conflicting_module_names(&graph).is_empty());
+}
+
+#[test]
+fn findings_are_reported_with_the_file_of_each_target() {
+    let dir = conflicting_workspace();
+    let build_files = find_build_files(&Config::new(dir.path())).unwrap();
+    let (graph, _) = build_target_graph(dir.path(), &build_files);
+    let mut config = Config::new(dir.path());
+    config.cross_file_findings = analyze_target_graph(&graph);
+
+    let utils = dir.path().join("Sources/Utils/BUILD.bazel");
+    let findings = analyze_build_file_at(UTILS, &utils, &config);
+
+    let issues: Vec<&BuildIssue> = findings
+        .iter()
+        .map(|finding| &finding.issue)
+        .filter(|issue| issue.name() == "ConflictingModuleNames")
+        .collect();
+    assert_eq!(
+        issues,
+        [&conflicts(
+            "Core",
+            &["//Sources/Core:Core", "//Sources/LegacyCore:LegacyCore"]
+        )]
+    );
+}
+
+#[test]
+fn check_mode_reports_conflicts_without_fixing() {
+    let dir = conflicting_workspace();
+    let root = dir.path().to_str().unwrap();
+
+    let check = umbra_fix(&["--check", "--root", root]);
+    let fix = umbra_fix(&["--root", root]);
+
+    assert_eq!(check.status.code(), Some(1), "{:?}", check);
+    let stdout = String::from_utf8_lossy(&check.stdout);
+    assert_eq!(
+        stdout.matches("[ConflictingModuleNames]").count(),
+        3,
+        "{}",
+        stdout
+    );
+    assert!(fix.status.success(), "{:?}", fix);
+    let stdout = String::from_utf8_lossy(&fix.stdout);
+    assert_eq!(
+        stdout
+            .matches("needs manual fix: [ConflictingModuleNames]")
+            .count(),
+        3,
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn suppression_comment_silences_the_conflict() {
+    let suppressed = UTILS.replace(
+        "swift_library(",
+        "# umbra-fix: disable=ConflictingModuleNames\nswift_library(",
+    );
+    let dir = workspace(&[("Sources/Core", CORE), ("Sources/Utils", &suppressed)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--root", root]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.matches("[ConflictingModuleNames]").count(),
+        1,
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("Sources/Core/BUILD.bazel"), "{}", stdout);
+}
+
+#[test]
+fn unparsable_file_does_not_hide_the_conflicts() {
+    let dir = conflicting_workspace();
+    write_build_file(
+        dir.path(),
+        "Sources/Generated",
+        "SRCS = [name + \".swift\" for name in [\"A\", \"B\"]]\n",
+    );
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--root", root]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.matches("[ConflictingModuleNames]").count(),
+        3,
+        "{}",
+        stdout
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("warning: cross-file checks skip 1 BUILD files that can't be parsed:"),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("Sources/Generated/BUILD.bazel: "),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn excluded_files_are_left_out_of_cross_file_checks() {
+    let dir = conflicting_workspace();
+    write_build_file(
+        dir.path(),
+        "Sources/Generated",
+        "SRCS = [name + \".swift\" for name in [\"A\", \"B\"]]\n",
+    );
+    fs::write(
+        dir.path().join("umbra-fix.toml"),
+        "exclude_patterns = [\"Sources/Generated/**\"]\n",
+    )
+    .unwrap();
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--root", root]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("cross-file checks skip"), "{}", stderr);
+}
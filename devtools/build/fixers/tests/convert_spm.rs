@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use umbra_build_fixer::swift_package_converter::{
+    convert_manifest, parse_manifest, TargetDependency, TargetKind,
+};
+use umbra_build_fixer::{analyze_build_file, Config};
+
+use crate::common::umbra_fix;
+
+const MANIFEST: &str = include_str!("fixtures/convert_spm/Package.swift");
+const EXPECTED: &str = include_str!("fixtures/convert_spm/expected.BUILD");
+const DUAL_BUILD_MANIFEST: &str = include_str!("fixtures/dual_build_system/Package.swift");
+
+fn fixture_manifest() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("devtools/build/fixers/tests/fixtures/convert_spm/Package.swift")
+}
+
+#[test]
+fn manifest_targets_are_extracted() {
+    let manifest = parse_manifest(MANIFEST);
+
+    let targets: Vec<_> = manifest
+        .targets
+        .iter()
+        .map(|target| (target.kind, target.name.as_str(), target.path.as_str()))
+        .collect();
+    assert_eq!(
+        targets,
+        [
+            (TargetKind::Regular, "KeyStorage", "Sources/Storage"),
+            (TargetKind::Regular, "UmbraKeys", "Sources/UmbraKeys"),
+            (TargetKind::Executable, "UmbraCLI", "Sources/UmbraCLI"),
+            (TargetKind::Test, "UmbraKeysTests", "Tests/UmbraKeysTests"),
+        ]
+    );
+    assert_eq!(
+        manifest.targets[2].dependencies,
+        [
+            TargetDependency::Target("UmbraKeys".to_string()),
+            TargetDependency::Product {
+                name: "ArgumentParser".to_string(),
+                package: "swift-argument-parser".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        manifest.targets[1].resources,
+        ["Resources", "Defaults.plist"]
+    );
+}
+
+#[test]
+fn manifest_products_and_packages_are_extracted() {
+    let manifest = parse_manifest(MANIFEST);
+
+    let products: Vec<_> = manifest
+        .products
+        .iter()
+        .map(|product| (product.name.as_str(), product.targets.clone()))
+        .collect();
+    assert_eq!(
+        products,
+        [
+            ("UmbraKeys", vec!["UmbraKeys".to_string()]),
+            ("umbra-keys", vec!["UmbraCLI".to_string()]),
+        ]
+    );
+    let identities: Vec<_> = manifest
+        .packages
+        .iter()
+        .map(|package| package.identity.as_str())
+        .collect();
+    assert_eq!(
+        identities,
+        ["swift-log", "swift-argument-parser", "cryptoshims"]
+    );
+}
+
+#[test]
+fn manifest_converts_to_build_file() {
+    let content = convert_manifest(&fixture_manifest(), &BTreeMap::new()).unwrap();
+
+    assert_eq!(content, EXPECTED);
+    assert_eq!(analyze_build_file(&content, &Config::default()), vec![]);
+}
+
+#[test]
+fn label_map_overrides_product_labels() {
+    let labels = BTreeMap::from([(
+        "CryptoShims".to_string(),
+        "//Sources/CryptoShims".to_string(),
+    )]);
+
+    let content = convert_manifest(&fixture_manifest(), &labels).unwrap();
+
+    assert!(
+        content.contains("\"//Sources/CryptoShims\","),
+        "{}",
+        content
+    );
+    assert!(!content.contains("@swiftpkg_cryptoshims"), "{}", content);
+}
+
+#[test]
+fn targets_without_products_are_public() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = dir.path().join("Package.swift");
+    fs::write(&manifest, DUAL_BUILD_MANIFEST).unwrap();
+
+    let content = convert_manifest(&manifest, &BTreeMap::new()).unwrap();
+
+    assert!(!content.contains("//visibility:private"), "{}", content);
+    assert!(
+        content.contains("swift_library(\n    name = \"Networking\",\n    srcs = glob(\n        [\"Sources/Networking/**/*.swift\"],"),
+        "{}",
+        content
+    );
+    assert!(content.contains("    deps = [\n        \":Core\",\n    ],\n"));
+}
+
+#[test]
+fn manifest_without_targets_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = dir.path().join("Package.swift");
+    fs::write(&manifest, "let package = Package(name: \"Empty\")\n").unwrap();
+
+    assert!(convert_manifest(&manifest, &BTreeMap::new()).is_err());
+}
+
+#[test]
+fn convert_spm_writes_build_file_with_label_map() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("MODULE.bazel"), "").unwrap();
+    fs::write(
+        dir.path().join("spm_label_map.toml"),
+        "Logging = \"//ThirdParty/Logging\"\n",
+    )
+    .unwrap();
+    let package = dir.path().join("Packages/UmbraKeys");
+    fs::create_dir_all(&package).unwrap();
+    fs::write(package.join("Package.swift"), MANIFEST).unwrap();
+    let manifest = package.join("Package.swift");
+
+    let output = umbra_fix(&["convert-spm", "--manifest", manifest.to_str().unwrap()]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let content = fs::read_to_string(package.join("BUILD.bazel")).unwrap();
+    assert!(content.contains("\"//ThirdParty/Logging\","), "{}", content);
+
+    let output = umbra_fix(&["convert-spm", "--manifest", manifest.to_str().unwrap()]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+}
@@ -0,0 +1,124 @@
+use std::fs;
+
+use umbra_build_fixer::checks::attributes::{check_dead_strip, fix_dead_strip};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const BINARIES: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_binary")
+
+swift_binary(
+    name = "umbra",
+    srcs = ["main.swift"],
+)
+
+swift_binary(
+    name = "umbrad",
+    srcs = ["daemon.swift"],
+    linkopts = [
+        "-ObjC",
+    ],
+)
+
+swift_binary(
+    name = "umbra-xpc",
+    srcs = ["xpc.swift"],
+    linkopts = ["-ObjC", "-Wl,-dead_strip"],
+)
+
+swift_binary(
+    name = "umbra-keys",
+    srcs = ["keys.swift"],
+    strip_swift_symbols = True,
+)
+"#;
+
+fn missing_strip(target: &str) -> BuildIssue {
+    BuildIssue::MissingStrip {
+        target: target.to_string(),
+    }
+}
+
+#[test]
+fn binaries_without_stripping_are_flagged() {
+    let issues: Vec<_> = check_dead_strip(BINARIES)
+        .into_iter()
+        .map(|(issue, _)| issue)
+        .collect();
+
+    assert_eq!(issues, [missing_strip("umbra"), missing_strip("umbrad")]);
+}
+
+#[test]
+fn linkopts_is_added_when_missing() {
+    let fixed = fix_dead_strip(BINARIES, "umbra");
+
+    assert!(
+        fixed.contains(
+            "    name = \"umbra\",\n    linkopts = [\"-dead_strip\"],\n    srcs = [\"main.swift\"],\n"
+        ),
+        "{}",
+        fixed
+    );
+}
+
+#[test]
+fn flag_is_appended_to_existing_linkopts() {
+    let fixed = fix_dead_strip(BINARIES, "umbrad");
+
+    assert!(
+        fixed.contains("    linkopts = [\n        \"-ObjC\",\n        \"-dead_strip\",\n    ],\n"),
+        "{}",
+        fixed
+    );
+    let inline = "swift_binary(name = \"a\", linkopts = [\"-ObjC\"])\n";
+    assert_eq!(
+        fix_dead_strip(inline, "a"),
+        "swift_binary(name = \"a\", linkopts = [\"-ObjC\", \"-dead_strip\"])\n"
+    );
+}
+
+#[test]
+fn check_is_opt_in() {
+    let dir = workspace(&[("Sources/CLI", BINARIES)]);
+    let path = dir.path().join("Sources/CLI/BUILD.bazel");
+    let mut config = test_config(dir.path());
+
+    let report = fix_build_file(&path, &config).unwrap();
+    assert!(!report
+        .findings
+        .iter()
+        .any(|finding| matches!(finding.issue, BuildIssue::MissingStrip { .. })));
+
+    config.require_dead_strip = true;
+    fix_build_file(&path, &config).unwrap();
+
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert_eq!(fixed.matches("\"-dead_strip\"").count(), 2, "{}", fixed);
+    assert!(check_dead_strip(&fixed).is_empty());
+}
+
+#[test]
+fn missing_strip_is_a_warning() {
+    let content = BINARIES.replacen(
+        "\n\n",
+        "\n\npackage(default_visibility = [\"//visibility:public\"])\n\n",
+        1,
+    );
+    let dir = workspace(&[("Sources/CLI", content.as_str())]);
+    let root = dir.path().to_str().unwrap();
+    let without = umbra_fix(&["--check", "--root", root]);
+
+    fs::write(
+        dir.path().join("umbra-fix.toml"),
+        "require_dead_strip = true\n",
+    )
+    .unwrap();
+    let output = umbra_fix(&["--check", "--root", root]);
+
+    // Reported, but it doesn't change whether --check fails
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[MissingStrip]"), "{}", stdout);
+    assert_eq!(without.status.code(), Some(0));
+    assert_eq!(output.status.code(), Some(0), "{}", stdout);
+}
@@ -0,0 +1,119 @@
+use std::fs;
+
+use umbra_build_fixer::checks::attributes::{
+    check_deprecated_attributes, check_deprecated_attributes_in_content, fix_deprecated_attribute,
+};
+use umbra_build_fixer::deprecated_attrs::{deprecation, expand_replacement};
+use umbra_build_fixer::starlark::ast::parse;
+use umbra_build_fixer::{analyze_build_file, fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const DEFINES: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+swift_library(
+    name = "Core",
+    srcs = glob(["*.swift"], allow_empty = True),
+    defines = [
+        "DEBUG",
+        "TRACE",
+    ],
+    visibility = ["//visibility:public"],
+)
+"#;
+
+#[test]
+fn table_lists_defines() {
+    assert_eq!(
+        deprecation("swift_library", "defines"),
+        Some(("1.0", r#"copts = ["-D<value>"]"#))
+    );
+    assert_eq!(deprecation("objc_library", "defines"), None);
+    assert_eq!(
+        expand_replacement(r#"copts = ["-D<value>"]"#, &["FOO".to_string()]),
+        r#"copts = ["-DFOO"]"#
+    );
+}
+
+#[test]
+fn defines_is_flagged_with_copts_replacement() {
+    let issues = check_deprecated_attributes(&parse(DEFINES).unwrap());
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(
+        issues[0].0,
+        BuildIssue::DeprecatedAttribute {
+            target: "Core".to_string(),
+            attribute: "defines".to_string(),
+            since: "1.0".to_string(),
+            replacement: r#"copts = ["-DDEBUG", "-DTRACE"]"#.to_string(),
+        }
+    );
+    assert!(issues[0].1.contains("rules_swift 1.0"), "{}", issues[0].1);
+}
+
+#[test]
+fn fix_removes_attribute_and_leaves_comment() {
+    let fixed = fix_deprecated_attribute(
+        DEFINES,
+        "Core",
+        "defines",
+        r#"defines was removed in rules_swift 1.0; use copts = ["-DDEBUG", "-DTRACE"]"#,
+    );
+
+    assert_eq!(
+        fixed,
+        r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+# defines was removed in rules_swift 1.0; use copts = ["-DDEBUG", "-DTRACE"]
+swift_library(
+    name = "Core",
+    srcs = glob(["*.swift"], allow_empty = True),
+    visibility = ["//visibility:public"],
+)
+"#
+    );
+    assert!(check_deprecated_attributes(&parse(&fixed).unwrap()).is_empty());
+}
+
+#[test]
+fn build_file_is_fixed() {
+    let dir = workspace(&[("Sources/Core", DEFINES)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(report
+        .findings
+        .iter()
+        .any(|finding| matches!(finding.issue, BuildIssue::DeprecatedAttribute { .. })));
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(!fixed.contains("defines ="), "{}", fixed);
+    assert!(
+        fixed.contains(r#"use copts = ["-DDEBUG", "-DTRACE"]"#),
+        "{}",
+        fixed
+    );
+}
+
+#[test]
+fn tokens_give_the_same_issues_as_the_ast() {
+    assert_eq!(
+        check_deprecated_attributes_in_content(DEFINES),
+        check_deprecated_attributes(&parse(DEFINES).unwrap())
+    );
+}
+
+#[test]
+fn defines_is_flagged_when_the_ast_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    // Valid Starlark the AST doesn't support
+    let content = format!("{}\nNAMES = [name for name in [\"A\"]]\n", DEFINES);
+    assert!(parse(&content).is_err());
+
+    let findings = analyze_build_file(&content, &test_config(dir.path()));
+
+    assert!(findings
+        .iter()
+        .any(|finding| matches!(finding.issue, BuildIssue::DeprecatedAttribute { .. })));
+}
@@ -0,0 +1,113 @@
+use std::fs;
+
+use umbra_build_fixer::patch::apply_patch;
+
+use crate::common::{umbra_fix, workspace};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+/// A hunk of a unified diff: its header ranges and body lines.
+struct Hunk {
+    path: String,
+    old_start: usize,
+    new_start: usize,
+    lines: Vec<String>,
+}
+
+fn parse_hunks(diff: &str) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut path = String::new();
+    for line in diff.lines() {
+        if let Some(file) = line.strip_prefix("+++ b/") {
+            path = file.to_string();
+        } else if let Some(header) = line.strip_prefix("@@ -") {
+            let (old, rest) = header.split_once(" +").unwrap();
+            let new = rest.split_once(" @@").unwrap().0;
+            let start = |range: &str| range.split(',').next().unwrap().parse().unwrap();
+            hunks.push(Hunk {
+                path: path.clone(),
+                old_start: start(old),
+                new_start: start(new),
+                lines: Vec::new(),
+            });
+        } else if !line.starts_with("--- ") {
+            hunks.last_mut().unwrap().lines.push(line.to_string());
+        }
+    }
+    hunks
+}
+
+#[test]
+fn diffs_are_printed_without_modifying_files() {
+    let dir = workspace(&[("Sources/Core", DIRTY), ("Sources/Clean", CLEAN)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--diff-only", "--no-cache", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    assert_eq!(fs::read_to_string(&path).unwrap(), DIRTY);
+
+    let diff = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        diff.starts_with("--- a/Sources/Core/BUILD.bazel\n"),
+        "{}",
+        diff
+    );
+    let hunks = parse_hunks(&diff);
+    assert!(hunks
+        .iter()
+        .all(|hunk| hunk.path == "Sources/Core/BUILD.bazel"));
+    assert!(hunks
+        .iter()
+        .any(|hunk| hunk.old_start == 1 && hunk.new_start == 1));
+    let lines: Vec<&str> = hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .map(String::as_str)
+        .collect();
+    assert!(lines.contains(&"-umbra_swift_library("), "{}", diff);
+    assert!(lines.contains(&"+swift_library("), "{}", diff);
+    assert!(lines.contains(&"-    exports = ["), "{}", diff);
+
+    // The diff applies cleanly, after which there is nothing left to change
+    apply_patch(dir.path(), &diff).unwrap();
+    let output = umbra_fix(&["--diff-only", "--no-cache", "--root", root]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn context_lines_can_be_changed() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&[
+        "--diff-only",
+        "--diff-context",
+        "0",
+        "--no-cache",
+        "--root",
+        root,
+    ]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let diff = String::from_utf8(output.stdout).unwrap();
+    let hunks = parse_hunks(&diff);
+    assert!(!hunks.is_empty());
+    assert!(hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .all(|line| !line.starts_with(' ')));
+}
+
+#[test]
+fn diff_only_conflicts_with_check() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--diff-only", "--check", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(2));
+}
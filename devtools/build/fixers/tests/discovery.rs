@@ -0,0 +1,160 @@
+use std::fs;
+
+use umbra_build_fixer::bazelignore::load_bazelignore;
+use umbra_build_fixer::glob::glob_match;
+use umbra_build_fixer::{find_build_files, Config};
+
+use crate::common::{umbra_fix, workspace};
+
+const BUILD: &str = "package()\n";
+
+fn relative_build_files(config: &Config) -> Vec<String> {
+    find_build_files(config)
+        .unwrap()
+        .iter()
+        .map(|path| {
+            path.strip_prefix(&config.root_dir)
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect()
+}
+
+#[test]
+fn glob_match_handles_wildcards() {
+    assert!(glob_match("Sources/**", "Sources/Core/BUILD.bazel"));
+    assert!(glob_match("**/BUILD.bazel", "BUILD.bazel"));
+    assert!(glob_match(
+        "Sources/*/BUILD.bazel",
+        "Sources/Core/BUILD.bazel"
+    ));
+    assert!(!glob_match(
+        "Sources/*/BUILD.bazel",
+        "Sources/Core/Impl/BUILD.bazel"
+    ));
+    assert!(glob_match("Sources/Co?e/*", "Sources/Core/BUILD.bazel"));
+    assert!(!glob_match(".build/**", "Sources/.build/BUILD.bazel"));
+    assert!(glob_match("**/.build/**", "Sources/.build/x/BUILD.bazel"));
+}
+
+#[test]
+fn exclude_pattern_omits_generated_build_files() {
+    let dir = workspace(&[
+        ("Sources/Core", BUILD),
+        (".build/checkouts/Dep", BUILD),
+        ("Tests/CoreTests", BUILD),
+    ]);
+    let mut config = Config::new(dir.path());
+    config.exclude_patterns = vec![".build/**".to_string()];
+
+    assert_eq!(
+        relative_build_files(&config),
+        vec!["Sources/Core/BUILD.bazel", "Tests/CoreTests/BUILD.bazel"]
+    );
+}
+
+#[test]
+fn include_pattern_restricts_to_subtree() {
+    let dir = workspace(&[("Sources/Core", BUILD), ("Tests/CoreTests", BUILD)]);
+    let mut config = Config::new(dir.path());
+    config.include_patterns = vec!["Sources/**".to_string()];
+
+    assert_eq!(
+        relative_build_files(&config),
+        vec!["Sources/Core/BUILD.bazel"]
+    );
+}
+
+#[test]
+fn include_and_exclude_flags_are_repeatable() {
+    let dir = workspace(&[
+        ("Sources/Core", "glob([\"*.swift\"])\n"),
+        ("Sources/Generated", "glob([\"*.swift\"])\n"),
+        ("Tests/CoreTests", "glob([\"*.swift\"])\n"),
+    ]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&[
+        "--check",
+        "--root",
+        root,
+        "--include",
+        "Sources/**",
+        "--include",
+        "Tests/**",
+        "--exclude-pattern",
+        "Sources/Generated/**",
+    ]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("in 2 BUILD.bazel files"), "{}", stdout);
+    assert!(!stdout.contains("Generated"), "{}", stdout);
+}
+
+#[test]
+fn max_depth_limits_traversal() {
+    let dir = workspace(&[("", BUILD), ("Sources", BUILD), ("Sources/Core", BUILD)]);
+    let mut config = Config::new(dir.path());
+
+    assert_eq!(relative_build_files(&config).len(), 3);
+
+    config.max_depth = Some(1);
+    assert_eq!(relative_build_files(&config), vec!["BUILD.bazel"]);
+
+    config.max_depth = Some(2);
+    assert_eq!(
+        relative_build_files(&config),
+        vec!["BUILD.bazel", "Sources/BUILD.bazel"]
+    );
+}
+
+#[test]
+fn max_depth_flag_is_applied() {
+    let dir = workspace(&[
+        ("", BUILD),
+        ("Sources", "glob([\"*.swift\"])\n"),
+        ("Sources/Core", "glob([\"*.swift\"])\n"),
+    ]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--dry-run", "--max-depth", "1", "--root", root]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Found 1 BUILD.bazel files"), "{}", stdout);
+}
+
+#[test]
+fn bazelignore_directories_are_not_discovered() {
+    let dir = workspace(&[
+        ("Sources/Core", BUILD),
+        ("bazel-out/k8-fastbuild", BUILD),
+        ("generated/protos", BUILD),
+        ("generated/swift", BUILD),
+        ("third_party/cache/v1", BUILD),
+        ("third_party/zlib", BUILD),
+    ]);
+    fs::write(
+        dir.path().join(".bazelignore"),
+        "# Bazel's own output\nbazel-out\n\n./generated/\nthird_party/cach*\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        relative_build_files(&Config::new(dir.path())),
+        ["Sources/Core/BUILD.bazel", "third_party/zlib/BUILD.bazel"]
+    );
+}
+
+#[test]
+fn bazelignore_entries_are_normalized() {
+    let dir = workspace(&[]);
+    fs::write(
+        dir.path().join(".bazelignore"),
+        "  node_modules  \n# comment\n./out/\n\n",
+    )
+    .unwrap();
+
+    assert_eq!(load_bazelignore(dir.path()), ["node_modules", "out"]);
+    assert!(load_bazelignore(&dir.path().join("missing")).is_empty());
+}
@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+
+use umbra_build_fixer::checks::fix_issue;
+use umbra_build_fixer::checks::spm::check_swift_package_manifest;
+use umbra_build_fixer::{analyze_build_file_at, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+// Package.swift declares Core, Networking and CoreTests
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("devtools/build/fixers/tests/fixtures/dual_build_system")
+}
+
+const BUILD: &str = r#"swift_library(
+    name = "Core",
+    srcs = glob(["Sources/Core/*.swift"]),
+)
+
+swift_test(
+    name = "CoreTests",
+    srcs = glob(["Tests/CoreTests/**/*.swift"]),
+)
+
+swift_library(
+    name = "Extras",
+    srcs = glob(["Extras/*.swift"]),
+)
+"#;
+
+#[test]
+fn mismatched_targets_and_sources_are_reported() {
+    let (issue, message) = check_swift_package_manifest(BUILD, &fixture_dir()).unwrap();
+
+    assert_eq!(issue, BuildIssue::DualBuildSystem);
+    assert!(!issue.is_fixable());
+    assert!(
+        message.contains("SPM target \"Networking\" has no Bazel rule"),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains("SPM target \"Core\" builds the Swift files in Sources/Core/"),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains("Bazel rule \"Extras\" has no SPM target"),
+        "{}",
+        message
+    );
+    assert!(!message.contains("\"CoreTests\""), "{}", message);
+}
+
+#[test]
+fn matching_targets_are_still_reported() {
+    let content = BUILD
+        .replace("Sources/Core/*.swift", "Sources/Core/**/*.swift")
+        .replace("name = \"Extras\"", "name = \"Networking\"")
+        .replace("Extras/*.swift", "Sources/Networking/*.swift");
+
+    let (_, message) = check_swift_package_manifest(&content, &fixture_dir()).unwrap();
+
+    assert!(
+        message.contains("the targets currently match"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn dual_build_system_is_not_fixed() {
+    let path = fixture_dir().join("BUILD.bazel");
+
+    let findings = analyze_build_file_at(BUILD, &path, &test_config(&fixture_dir()));
+
+    assert!(findings
+        .iter()
+        .any(|finding| finding.issue == BuildIssue::DualBuildSystem));
+    let fixed = fix_issue(&BuildIssue::DualBuildSystem, BUILD);
+    assert_eq!(fixed, BUILD);
+}
+
+#[test]
+fn package_without_manifest_is_clean() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+
+    assert!(check_swift_package_manifest(CLEAN, &dir.path().join("Sources/Core")).is_none());
+}
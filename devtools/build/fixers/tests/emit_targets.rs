@@ -0,0 +1,129 @@
+use serde_json::{json, Value};
+
+use crate::common::{umbra_fix, workspace, write_build_file};
+
+const CORE: &str = include_str!("fixtures/target_graph/core.BUILD");
+const LOGGING: &str = include_str!("fixtures/target_graph/logging.BUILD");
+const APP: &str = include_str!("fixtures/target_graph/app.BUILD");
+
+fn three_packages() -> tempfile::TempDir {
+    workspace(&[
+        ("Sources/Core", CORE),
+        ("Sources/Logging", LOGGING),
+        ("Apps/App", APP),
+    ])
+}
+
+fn emit_targets(args: &[&str]) -> Value {
+    let output = umbra_fix(args);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).expect("stdout is not JSON")
+}
+
+#[test]
+fn prints_every_target_as_json() {
+    let dir = three_packages();
+    let root = dir.path().to_str().unwrap();
+
+    let targets = emit_targets(&["--emit-targets", "--root", root]);
+
+    let labels: Vec<&str> = targets
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|target| target["label"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        labels,
+        [
+            "//Apps/App:App",
+            "//Sources/Core:Core",
+            "//Sources/Core:CoreTests",
+            "//Sources/Logging:Logging",
+        ]
+    );
+    assert_eq!(
+        targets[1],
+        json!({
+            "label": "//Sources/Core:Core",
+            "rule_name": "swift_library",
+            "file": "Sources/Core/BUILD.bazel",
+            "name": "Core",
+            "visibility": ["//visibility:public"],
+        })
+    );
+    assert_eq!(targets[0]["visibility"], Value::Null);
+}
+
+#[test]
+fn filter_limits_rule_types() {
+    let dir = three_packages();
+    let root = dir.path().to_str().unwrap();
+
+    let targets = emit_targets(&[
+        "--emit-targets",
+        "--emit-targets-filter",
+        "swift_test,swift_binary",
+        "--root",
+        root,
+    ]);
+
+    let rules: Vec<(&str, &str)> = targets
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|target| {
+            (
+                target["name"].as_str().unwrap(),
+                target["rule_name"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        rules,
+        [("App", "swift_binary"), ("CoreTests", "swift_test")]
+    );
+}
+
+#[test]
+fn files_are_left_untouched() {
+    let dir = workspace(&[("Sources/Core", "swift_library(name='Core')\n")]);
+    let root = dir.path().to_str().unwrap();
+
+    let targets = emit_targets(&["--emit-targets", "--root", root]);
+
+    assert_eq!(targets.as_array().unwrap().len(), 1);
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("Sources/Core/BUILD.bazel")).unwrap(),
+        "swift_library(name='Core')\n"
+    );
+}
+
+#[test]
+fn unparsable_file_is_an_error() {
+    let dir = three_packages();
+    write_build_file(dir.path(), "Sources/Broken", "[x for x in y]\n");
+
+    let output = umbra_fix(&["--emit-targets", "--root", dir.path().to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Sources/Broken/BUILD.bazel"));
+}
+
+#[test]
+fn filter_requires_emit_targets() {
+    let dir = three_packages();
+
+    let output = umbra_fix(&[
+        "--emit-targets-filter",
+        "swift_test",
+        "--root",
+        dir.path().to_str().unwrap(),
+    ]);
+
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,150 @@
+use std::fs;
+
+use umbra_build_fixer::checks::deps::{check_empty_deps, fix_empty_deps};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const EMPTY_DEPS: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library", "swift_test")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "Core",
+    srcs = glob(
+        ["*.swift"],
+        allow_empty = True,
+    ),
+    deps = [],
+    visibility = ["//visibility:public"],
+)
+
+swift_test(
+    name = "CoreTests",
+    srcs = glob(
+        ["Tests/*.swift"],
+        allow_empty = True,
+    ),
+    deps = ["//Sources/Core"],
+    testonly = True,
+)
+"#;
+
+fn empty_deps(target: &str) -> BuildIssue {
+    BuildIssue::EmptyDepsAttribute {
+        target: target.to_string(),
+    }
+}
+
+#[test]
+fn empty_deps_line_is_removed() {
+    let dir = workspace(&[("Sources/Core", EMPTY_DEPS)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert_eq!(issues, [&empty_deps("Core")]);
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        EMPTY_DEPS.replace("    deps = [],\n", "")
+    );
+}
+
+#[test]
+fn non_empty_deps_are_unchanged() {
+    let content = EMPTY_DEPS.replace("deps = [],", "deps = [\"//foo\"],");
+
+    assert!(check_empty_deps(&content).is_empty());
+    assert_eq!(fix_empty_deps(&content, "CoreTests"), content);
+}
+
+#[test]
+fn deps_holding_only_a_comment_are_kept() {
+    let content = "swift_library(\n    name = \"Core\",\n    deps = [\n        # added by the release script\n    ],\n)\n";
+
+    assert!(check_empty_deps(content).is_empty());
+}
+
+#[test]
+fn inline_deps_take_the_preceding_comma() {
+    let last = "swift_library(name = \"Core\", srcs = [\"Core.swift\"], deps = [])\n";
+    let middle = "swift_library(name = \"Core\", deps = [], srcs = [\"Core.swift\"])\n";
+
+    assert_eq!(
+        check_empty_deps(last)
+            .into_iter()
+            .map(|(issue, _)| issue)
+            .collect::<Vec<_>>(),
+        [empty_deps("Core")]
+    );
+    assert_eq!(
+        fix_empty_deps(last, "Core"),
+        "swift_library(name = \"Core\", srcs = [\"Core.swift\"])\n"
+    );
+    assert_eq!(
+        fix_empty_deps(middle, "Core"),
+        "swift_library(name = \"Core\", srcs = [\"Core.swift\"])\n"
+    );
+}
+
+#[test]
+fn last_attribute_without_trailing_comma_keeps_indentation() {
+    let content =
+        "swift_library(\n    name = \"Core\",\n    srcs = [\"Core.swift\"],\n    deps = []\n)\n";
+
+    assert_eq!(
+        fix_empty_deps(content, "Core"),
+        "swift_library(\n    name = \"Core\",\n    srcs = [\"Core.swift\"],\n)\n"
+    );
+}
+
+#[test]
+fn only_the_named_rule_is_fixed() {
+    let content = "swift_library(\n    name = \"Core\",\n    deps = [],\n)\n\nswift_library(\n    name = \"Extras\",\n    deps = [],\n)\n";
+
+    assert_eq!(
+        fix_empty_deps(content, "Extras"),
+        "swift_library(\n    name = \"Core\",\n    deps = [],\n)\n\nswift_library(\n    name = \"Extras\",\n)\n"
+    );
+}
+
+#[test]
+fn deps_emptied_by_pruning_are_removed() {
+    let content = EMPTY_DEPS.replace(
+        "deps = [],",
+        "deps = [\n        \"//Sources/Logging\",\n    ],",
+    );
+    let dir = workspace(&[("Sources/Core", &content)]);
+    fs::write(
+        dir.path().join("import_map.toml"),
+        "\"//Sources/Logging\" = \"UmbraLogging\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("Sources/Core/Core.swift"),
+        "import Foundation\n",
+    )
+    .unwrap();
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+    config.prune_deps = true;
+
+    let report = fix_build_file(&path, &config).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert_eq!(
+        issues,
+        [
+            &BuildIssue::UnusedDependency {
+                target: "Core".to_string(),
+                label: "//Sources/Logging".to_string(),
+            },
+            &empty_deps("Core"),
+        ]
+    );
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        EMPTY_DEPS.replace("    deps = [],\n", "")
+    );
+}
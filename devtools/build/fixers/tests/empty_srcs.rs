@@ -0,0 +1,142 @@
+use std::fs;
+
+use umbra_build_fixer::checks::swift_library::check_empty_srcs;
+use umbra_build_fixer::starlark::ast::parse;
+use umbra_build_fixer::{analyze_build_file, fix_build_file, BuildIssue, Config};
+
+use crate::common::{test_config, workspace};
+
+const TWO_LIBRARIES: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "Core",
+    srcs = glob(["Core/*.swift"], allow_empty = True),
+)
+
+swift_library(
+    name = "Extras",
+    visibility = ["//visibility:public"],
+)
+"#;
+
+#[test]
+fn only_the_library_without_srcs_is_fixed() {
+    let dir = workspace(&[("Sources/Core", TWO_LIBRARIES)]);
+    let package = dir.path().join("Sources/Core");
+    fs::create_dir_all(package.join("Core")).unwrap();
+    fs::write(package.join("Core/Core.swift"), "struct Core {}\n").unwrap();
+    fs::write(package.join("Extras.swift"), "struct Extras {}\n").unwrap();
+    let path = package.join("BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert!(issues.contains(&&BuildIssue::EmptySrcs {
+        target: "Extras".to_string(),
+        has_srcs: false,
+    }));
+    assert!(!issues
+        .iter()
+        .any(|issue| matches!(issue, BuildIssue::EmptySrcs { target, .. } if target == "Core")));
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(
+        content.contains(
+            "    name = \"Extras\",\n    srcs = glob([\"*.swift\"], allow_empty = True),\n"
+        ),
+        "{}",
+        content
+    );
+    assert_eq!(content.matches("srcs = ").count(), 2);
+}
+
+#[test]
+fn srcs_matching_no_package_files_are_reported() {
+    let dir = workspace(&[("Sources/Core", TWO_LIBRARIES)]);
+    let package = dir.path().join("Sources/Core");
+    fs::write(package.join("Misplaced.swift"), "struct Misplaced {}\n").unwrap();
+
+    let issues = check_empty_srcs(&parse(TWO_LIBRARIES).unwrap(), &package);
+
+    let issues: Vec<_> = issues.into_iter().map(|(issue, _)| issue).collect();
+    assert_eq!(
+        issues,
+        [
+            BuildIssue::EmptySrcs {
+                target: "Core".to_string(),
+                has_srcs: true,
+            },
+            BuildIssue::EmptySrcs {
+                target: "Extras".to_string(),
+                has_srcs: false,
+            },
+        ]
+    );
+    assert!(!issues[0].is_fixable());
+    assert!(issues[1].is_fixable());
+}
+
+#[test]
+fn files_of_a_nested_package_with_a_plain_build_file_are_not_counted() {
+    let dir = workspace(&[("Sources/Core", TWO_LIBRARIES)]);
+    let package = dir.path().join("Sources/Core");
+    fs::create_dir_all(package.join("Core")).unwrap();
+    fs::write(package.join("Core/Core.swift"), "struct Core {}\n").unwrap();
+    fs::write(package.join("Core/BUILD"), "").unwrap();
+
+    let issues = check_empty_srcs(&parse(TWO_LIBRARIES).unwrap(), &package);
+
+    // Core.swift belongs to the nested package, which the glob can't reach
+    // either, so Core's srcs aren't held against it
+    assert!(!issues.iter().any(
+        |(issue, _)| matches!(issue, BuildIssue::EmptySrcs { target, .. } if target == "Core")
+    ));
+}
+
+#[test]
+fn package_without_swift_files_only_reports_missing_srcs() {
+    let dir = workspace(&[("Sources/Core", TWO_LIBRARIES)]);
+    let package = dir.path().join("Sources/Core");
+
+    let issues = check_empty_srcs(&parse(TWO_LIBRARIES).unwrap(), &package);
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(
+        issues[0].1,
+        "swift_library \"Extras\" has no srcs attribute"
+    );
+}
+
+fn missing_srcs_of_extras(content: &str, config: &Config) -> bool {
+    analyze_build_file(content, config).iter().any(|finding| {
+        finding.issue
+            == BuildIssue::EmptySrcs {
+                target: "Extras".to_string(),
+                has_srcs: false,
+            }
+    })
+}
+
+#[test]
+fn missing_srcs_are_found_without_a_package_dir() {
+    let dir = tempfile::tempdir().unwrap();
+
+    assert!(missing_srcs_of_extras(
+        TWO_LIBRARIES,
+        &test_config(dir.path())
+    ));
+}
+
+#[test]
+fn missing_srcs_are_found_when_the_ast_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    // Valid Starlark the AST doesn't support
+    let content = format!(
+        "{}\nNAMES = [name for name in [\"A\", \"B\"]]\n",
+        TWO_LIBRARIES
+    );
+    assert!(parse(&content).is_err());
+
+    assert!(missing_srcs_of_extras(&content, &test_config(dir.path())));
+}
@@ -0,0 +1,114 @@
+use umbra_build_fixer::checks::exports::{check_exports_attribute, remove_exports_attribute};
+use umbra_build_fixer::BuildIssue;
+
+#[test]
+fn nested_select_is_removed_whole() {
+    let content = r#"swift_library(
+    name = "Core",
+    srcs = glob(["*.swift"]),
+    exports = [select({"//conditions:default": [":Foo"]})],
+    deps = [":Base"],
+)
+"#;
+
+    assert_eq!(
+        remove_exports_attribute(content),
+        r#"swift_library(
+    name = "Core",
+    srcs = glob(["*.swift"]),
+    deps = [":Base"],
+)
+"#
+    );
+}
+
+#[test]
+fn multiline_nested_value_is_removed_whole() {
+    let content = r#"swift_library(
+    name = "Core",
+    exports = [
+        ":Base",
+    ] + select({
+        "//conditions:default": [],
+        "@platforms//os:macos": [":MacBase"],
+    }),
+    visibility = ["//visibility:public"],
+)
+"#;
+
+    assert_eq!(
+        remove_exports_attribute(content),
+        r#"swift_library(
+    name = "Core",
+    visibility = ["//visibility:public"],
+)
+"#
+    );
+}
+
+#[test]
+fn last_attribute_without_trailing_comma_is_removed() {
+    let content = "swift_library(\n    name = \"Core\",\n    exports = [\":Base\"]\n)\n";
+
+    assert_eq!(
+        remove_exports_attribute(content),
+        "swift_library(\n    name = \"Core\",\n)\n"
+    );
+}
+
+#[test]
+fn attribute_on_a_shared_line_keeps_its_neighbours() {
+    let content = "swift_library(name = \"Core\", exports = [\":Base\"], deps = [\":Base\"])\n";
+
+    assert_eq!(
+        remove_exports_attribute(content),
+        "swift_library(name = \"Core\", deps = [\":Base\"])\n"
+    );
+}
+
+#[test]
+fn similarly_named_attributes_are_left_alone() {
+    let content = "swift_library(\n    name = \"Core\",\n    reexports = [\":Base\"],\n)\n";
+
+    assert_eq!(remove_exports_attribute(content), content);
+    assert!(check_exports_attribute(content).is_none());
+}
+
+#[test]
+fn check_flags_nested_exports() {
+    let content = "swift_library(\n    name = \"Core\",\n    exports = [select({\"//conditions:default\": [\":Foo\"]})],\n)\n";
+
+    let (issue, _) = check_exports_attribute(content).unwrap();
+
+    assert_eq!(issue, BuildIssue::ExportsAttribute);
+}
+
+#[test]
+fn rules_that_support_exports_keep_it() {
+    let content = r#"java_library(
+    name = "Core",
+    exports = [":Base"],
+)
+
+swift_binary(
+    name = "Tool",
+    exports = [":Core"],
+)
+"#;
+
+    assert_eq!(
+        remove_exports_attribute(content),
+        r#"java_library(
+    name = "Core",
+    exports = [":Base"],
+)
+
+swift_binary(
+    name = "Tool",
+)
+"#
+    );
+    assert!(
+        check_exports_attribute("android_library(name = \"A\", exports = [\":B\"])\n").is_none()
+    );
+}
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use umbra_build_fixer::checks::apply_fixes;
+use umbra_build_fixer::checks::deps::{check_external_labels, rewrite_external_label};
+use umbra_build_fixer::config::EXTERNAL_LABEL_REWRITES_FILE_NAME;
+use umbra_build_fixer::{analyze_build_file, fix_build_file, BuildIssue, Config, Finding};
+
+use crate::common::{test_config, workspace};
+
+const INPUT: &str = include_str!("fixtures/external_labels/input.BUILD");
+const EXPECTED: &str = include_str!("fixtures/external_labels/expected.BUILD");
+const REWRITES: &str = include_str!("fixtures/external_labels/external_label_rewrites.toml");
+
+fn rewrites() -> BTreeMap<String, String> {
+    toml::from_str(REWRITES).unwrap()
+}
+
+fn rewrite(label: &str) -> Option<String> {
+    rewrite_external_label(label, &rewrites())
+}
+
+#[test]
+fn labels_under_rewritten_packages_move_to_the_repository() {
+    assert_eq!(
+        rewrite("//third_party/swift_log:Logging").as_deref(),
+        Some("@swift_log//:Logging")
+    );
+    assert_eq!(
+        rewrite("//third_party/swift_log").as_deref(),
+        Some("@swift_log//:swift_log")
+    );
+    assert_eq!(
+        rewrite("//third_party/grpc/GRPC:Core").as_deref(),
+        Some("@grpc_swift//Sources/GRPC:Core")
+    );
+    assert_eq!(
+        rewrite("//third_party/grpc").as_deref(),
+        Some("@grpc_swift//Sources:grpc")
+    );
+}
+
+#[test]
+fn other_labels_are_left_alone() {
+    assert_eq!(rewrite("//third_party/grpcio:grpcio"), None);
+    assert_eq!(rewrite("@swift_log//:Logging"), None);
+    assert_eq!(rewrite(":swift_log"), None);
+    assert_eq!(rewrite("//Sources/Core"), None);
+}
+
+#[test]
+fn longest_package_wins() {
+    let mut rewrites = rewrites();
+    rewrites.insert(
+        "//third_party/grpc/GRPC".to_string(),
+        "@grpc_core".to_string(),
+    );
+
+    assert_eq!(
+        rewrite_external_label("//third_party/grpc/GRPC:Core", &rewrites).as_deref(),
+        Some("@grpc_core//:Core")
+    );
+}
+
+#[test]
+fn only_old_labels_are_flagged() {
+    let issues: Vec<BuildIssue> = check_external_labels(INPUT, &rewrites())
+        .into_iter()
+        .map(|(issue, _)| issue)
+        .collect();
+
+    let flagged: Vec<(&str, &str)> = issues
+        .iter()
+        .map(|issue| match issue {
+            BuildIssue::AbsoluteExternalLabel {
+                label, replacement, ..
+            } => (label.as_str(), replacement.as_str()),
+            other => panic!("unexpected issue {:?}", other),
+        })
+        .collect();
+    assert_eq!(
+        flagged,
+        [
+            (
+                "//third_party/grpc/GRPC:Core",
+                "@grpc_swift//Sources/GRPC:Core"
+            ),
+            ("//third_party/swift_log", "@swift_log//:swift_log"),
+            ("//third_party/swift_log:Logging", "@swift_log//:Logging"),
+        ]
+    );
+}
+
+#[test]
+fn fix_replaces_only_old_labels() {
+    let findings: Vec<Finding> = check_external_labels(INPUT, &rewrites())
+        .into_iter()
+        .map(Finding::from)
+        .collect();
+
+    let fixed = apply_fixes(INPUT, &findings);
+
+    assert_eq!(fixed, EXPECTED);
+    assert!(check_external_labels(&fixed, &rewrites()).is_empty());
+}
+
+#[test]
+fn select_conditions_are_not_labels_to_rewrite() {
+    let content = "swift_library(\n    name = \"Core\",\n    deps = select(\n        {\n            \"//third_party/swift_log:linux\": [\"//third_party/swift_log:Logging\"],\n            \"//conditions:default\": [],\n        },\n        no_match_error = \"//third_party/swift_log\",\n    ),\n)\n";
+
+    let findings: Vec<Finding> = check_external_labels(content, &rewrites())
+        .into_iter()
+        .map(Finding::from)
+        .collect();
+
+    assert_eq!(findings.len(), 1, "{:?}", findings);
+    assert_eq!(
+        apply_fixes(content, &findings),
+        content.replace(
+            "[\"//third_party/swift_log:Logging\"]",
+            "[\"@swift_log//:Logging\"]"
+        )
+    );
+}
+
+#[test]
+fn rewrites_file_turns_the_check_on() {
+    let dir = workspace(&[("Sources/Networking", INPUT)]);
+    fs::write(dir.path().join(EXTERNAL_LABEL_REWRITES_FILE_NAME), REWRITES).unwrap();
+    let path = dir.path().join("Sources/Networking/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(report.modified);
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(!fixed.contains("\"//third_party/swift_log"), "{}", fixed);
+    assert!(!fixed.contains("\"//third_party/grpc/"), "{}", fixed);
+    assert!(
+        fixed.contains("\"//third_party/grpcio:grpcio\""),
+        "{}",
+        fixed
+    );
+    assert!(
+        fixed.contains("\"@grpc_swift//Sources/GRPC:Core\""),
+        "{}",
+        fixed
+    );
+}
+
+#[test]
+fn without_rewrites_nothing_is_flagged() {
+    let findings = analyze_build_file(INPUT, &Config::default());
+
+    assert!(!findings
+        .iter()
+        .any(|finding| matches!(finding.issue, BuildIssue::AbsoluteExternalLabel { .. })));
+}
+
+#[test]
+fn rewrite_to_a_non_external_label_is_rejected() {
+    let dir = workspace(&[]);
+    fs::write(
+        dir.path().join(EXTERNAL_LABEL_REWRITES_FILE_NAME),
+        "\"//third_party/foo\" = \"//vendor/foo\"\n",
+    )
+    .unwrap();
+
+    let err = Config::load(dir.path()).unwrap_err();
+
+    assert!(err.to_string().contains("//vendor/foo"), "{}", err);
+}
@@ -0,0 +1,195 @@
+use umbra_build_fixer::checks::analyze_target_graph;
+use umbra_build_fixer::checks::feature_flags::{check_config_settings, check_select_defaults};
+use umbra_build_fixer::target_graph::build_target_graph;
+use umbra_build_fixer::{find_build_files, BuildIssue, Config};
+
+use crate::common::{umbra_fix, workspace};
+
+const CONFIG: &str = include_str!("fixtures/feature_flags/config.BUILD");
+const APP: &str = include_str!("fixtures/feature_flags/app.BUILD");
+
+fn two_packages() -> tempfile::TempDir {
+    workspace(&[("Sources/Config", CONFIG), ("Apps/App", APP)])
+}
+
+fn unknown(label: &str) -> BuildIssue {
+    BuildIssue::UnknownConfigSetting {
+        target: "App".to_string(),
+        label: label.to_string(),
+    }
+}
+
+#[test]
+fn conditions_must_be_config_settings_of_the_workspace() {
+    let dir = two_packages();
+    let build_files = find_build_files(&Config::new(dir.path())).unwrap();
+    let (graph, _) = build_target_graph(dir.path(), &build_files);
+
+    let issues = check_config_settings(&graph);
+
+    // config_setting and constraint_value targets, local or not, are fine;
+    // external repositories and //conditions:default aren't checked
+    let flagged: Vec<(String, &BuildIssue)> = issues
+        .iter()
+        .map(|(label, issue, _)| (label.to_string(), issue))
+        .collect();
+    assert_eq!(
+        flagged,
+        [
+            (
+                "//Apps/App:App".to_string(),
+                &unknown("//Sources/Config:Settings")
+            ),
+            (
+                "//Apps/App:App".to_string(),
+                &unknown("//Sources/Config:profiling")
+            ),
+        ]
+    );
+    assert!(
+        issues[0]
+            .2
+            .contains("is a swift_library, not a config_setting"),
+        "{}",
+        issues[0].2
+    );
+    assert!(
+        issues[1].2.contains("isn't defined in the workspace"),
+        "{}",
+        issues[1].2
+    );
+}
+
+#[test]
+fn config_setting_groups_and_aliases_of_config_settings_are_conditions() {
+    let settings = r#"load("@bazel_skylib//lib:selects.bzl", "selects")
+
+config_setting(
+    name = "debug",
+    values = {"compilation_mode": "dbg"},
+)
+
+selects.config_setting_group(
+    name = "debug_or_opt",
+    match_any = [":debug", "//Sources/Config:debug"],
+)
+
+alias(
+    name = "dbg",
+    actual = ":debug",
+)
+
+alias(
+    name = "library",
+    actual = "//Sources/Config:Settings",
+)
+"#;
+    let app = r#"swift_library(
+    name = "App",
+    defines = select({
+        "//Settings:debug_or_opt": ["TRACE"],
+        "//Settings:dbg": ["DEBUG"],
+        "//Settings:library": [],
+        "//conditions:default": [],
+    }),
+)
+"#;
+    let dir = workspace(&[
+        ("Settings", settings),
+        ("Sources/Config", CONFIG),
+        ("Apps/App", app),
+    ]);
+    let build_files = find_build_files(&Config::new(dir.path())).unwrap();
+    let (graph, _) = build_target_graph(dir.path(), &build_files);
+
+    let issues = check_config_settings(&graph);
+
+    // Only the alias of a swift_library is flagged, as what it stands for
+    assert_eq!(issues.len(), 1, "{:?}", issues);
+    assert_eq!(issues[0].1, unknown("//Settings:library"));
+    assert!(
+        issues[0]
+            .2
+            .contains("is a swift_library, not a config_setting"),
+        "{}",
+        issues[0].2
+    );
+}
+
+#[test]
+fn findings_are_keyed_by_the_package_of_the_select() {
+    let dir = two_packages();
+    let build_files = find_build_files(&Config::new(dir.path())).unwrap();
+    let (graph, _) = build_target_graph(dir.path(), &build_files);
+
+    let findings = analyze_target_graph(&graph);
+
+    assert_eq!(findings.keys().collect::<Vec<_>>(), ["Apps/App"]);
+}
+
+#[test]
+fn select_without_default_is_flagged() {
+    let issues = check_select_defaults(APP);
+
+    // The copts select() explains itself with no_match_error
+    let flagged: Vec<&BuildIssue> = issues.iter().map(|(issue, _)| issue).collect();
+    assert_eq!(
+        flagged,
+        [&BuildIssue::SelectWithoutDefault {
+            line: 19,
+            target: Some("App".to_string()),
+        }]
+    );
+    assert!(issues[0].1.contains("no_match_error"), "{}", issues[0].1);
+}
+
+#[test]
+fn select_with_computed_conditions_is_skipped() {
+    let content = r#"swift_library(
+    name = "Core",
+    defines = select({
+        DEBUG_SETTING: ["DEBUG"],
+    }),
+)
+"#;
+
+    assert!(check_select_defaults(content).is_empty());
+}
+
+#[test]
+fn check_mode_reports_both_checks() {
+    let dir = two_packages();
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.matches("[UnknownConfigSetting]").count(),
+        2,
+        "{}",
+        stdout
+    );
+    assert_eq!(
+        stdout.matches("[SelectWithoutDefault]").count(),
+        1,
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn suppression_documents_an_intentionally_missing_default() {
+    let suppressed = APP.replace(
+        "swift_library(\n    name = \"App\"",
+        "# umbra-fix: disable=SelectWithoutDefault\nswift_library(\n    name = \"App\"",
+    );
+    let dir = workspace(&[("Sources/Config", CONFIG), ("Apps/App", &suppressed)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--root", root]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("[SelectWithoutDefault]"), "{}", stdout);
+}
@@ -0,0 +1,47 @@
+// swift-tools-version:5.9
+import PackageDescription
+
+let package = Package(
+    name: "UmbraKeys",
+    platforms: [.macOS(.v14)],
+    products: [
+        .library(name: "UmbraKeys", targets: ["UmbraKeys"]),
+        .executable(name: "umbra-keys", targets: ["UmbraCLI"]),
+    ],
+    dependencies: [
+        .package(url: "https://github.com/apple/swift-log.git", from: "1.5.0"),
+        .package(url: "https://github.com/apple/swift-argument-parser", from: "1.3.0"),
+        .package(path: "../CryptoShims"),
+    ],
+    targets: [
+        .target(
+            name: "KeyStorage",
+            dependencies: [
+                .product(name: "Logging", package: "swift-log"),
+            ],
+            path: "Sources/Storage/"
+        ),
+        .target(
+            name: "UmbraKeys",
+            dependencies: [
+                "KeyStorage",
+                .product(name: "CryptoShims", package: "CryptoShims"),
+            ],
+            resources: [
+                .process("Resources"),
+                .copy("Defaults.plist"),
+            ]
+        ),
+        .executableTarget(
+            name: "UmbraCLI",
+            dependencies: [
+                .target(name: "UmbraKeys"),
+                .product(name: "ArgumentParser", package: "swift-argument-parser"),
+            ]
+        ),
+        .testTarget(
+            name: "UmbraKeysTests",
+            dependencies: ["UmbraKeys"]
+        ),
+    ]
+)
@@ -0,0 +1,11 @@
+// swift-tools-version:5.9
+import PackageDescription
+
+let package = Package(
+    name: "Core",
+    targets: [
+        .target(name: "Core", path: "Sources/Core"),
+        .target(name: "Networking", dependencies: ["Core"]),
+        .testTarget(name: "CoreTests", dependencies: ["Core"]),
+    ]
+)
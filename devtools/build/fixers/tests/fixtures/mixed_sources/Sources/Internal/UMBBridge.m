@@ -0,0 +1,4 @@
+#import "UMBBridge.h"
+
+@implementation UMBBridge
+@end
@@ -0,0 +1,11 @@
+import Foundation
+
+// Formatting stays synchronous; callers await the key elsewhere.
+/* An actor isn't needed here: the formatter has no state. */
+public struct KeyFormatter {
+    public var actor = "system"
+
+    public func format(_ key: Data) -> String {
+        key.base64EncodedString()
+    }
+}
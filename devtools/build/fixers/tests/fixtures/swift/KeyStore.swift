@@ -0,0 +1,15 @@
+import Foundation
+
+/// Caches keys loaded from the keychain.
+public actor KeyStore {
+    private var keys: [String: Data] = [:]
+
+    public func key(named name: String) async throws -> Data {
+        if let key = keys[name] {
+            return key
+        }
+        let key = try await KeychainLoader.load(name)
+        keys[name] = key
+        return key
+    }
+}
@@ -0,0 +1,20 @@
+// MARK: - Imports
+
+import XCTest
+
+// MARK: - Module under test
+
+@testable import UmbraCore
+import UmbraErrors
+
+// MARK: - Tests
+
+final class CoreTests: XCTestCase {
+    let usage = """
+    import NotAModule
+    """
+
+    func testVersion() {
+        XCTAssertFalse(UmbraCore.version.isEmpty)
+    }
+}
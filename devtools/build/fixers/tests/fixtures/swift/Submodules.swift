@@ -0,0 +1,10 @@
+import Foundation.NSString
+import struct Foundation.Date
+@_implementationOnly import CoreServices
+import Foundation
+
+/* import CommentedOut */
+
+public struct Clock {
+    let now = Date()
+}
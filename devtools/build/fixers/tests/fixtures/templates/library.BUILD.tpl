@@ -0,0 +1,13 @@
+load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "{{name}}",
+    module_name = "Umbra{{name}}",
+    srcs = glob(
+        ["{{glob}}"],
+        allow_empty = True,
+    ),
+    visibility = ["//visibility:public"],
+)
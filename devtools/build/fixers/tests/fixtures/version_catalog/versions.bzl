@@ -0,0 +1,7 @@
+"""Versions of the external repositories of the workspace."""
+
+VERSIONS = {
+    "rules_swift": "2.1.1",
+    # Bumped for the structured logging API
+    "swift_log": "1.5.4",
+}
@@ -0,0 +1,48 @@
+use std::fs;
+
+use umbra_build_fixer::starlark::formatter::format_build_file;
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const INPUT: &str = include_str!("fixtures/format/input.BUILD");
+const EXPECTED: &str = include_str!("fixtures/format/expected.BUILD");
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+#[test]
+fn formats_to_golden_output() {
+    assert_eq!(format_build_file(INPUT), EXPECTED);
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    assert_eq!(format_build_file(EXPECTED), EXPECTED);
+}
+
+#[test]
+fn format_only_skips_other_fixes() {
+    let dir = workspace(&[("Sources/Core", INPUT)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--format-only", "--root", root]);
+
+    assert!(output.status.success());
+    let content = fs::read_to_string(dir.path().join("Sources/Core/BUILD.bazel")).unwrap();
+    // Unsorted deps and the glob without allow_empty are left as formatted
+    assert_eq!(content, EXPECTED);
+}
+
+#[test]
+fn format_runs_after_other_fixes() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+    config.format = true;
+
+    let report = fix_build_file(&path, &config).unwrap();
+
+    let last = report.findings.last().map(|finding| &finding.issue);
+    assert_eq!(last, Some(&BuildIssue::NonCanonicalFormat));
+    let content = fs::read_to_string(&path).unwrap();
+    assert_eq!(format_build_file(&content), content);
+}
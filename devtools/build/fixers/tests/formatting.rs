@@ -0,0 +1,96 @@
+use std::fs;
+
+use umbra_build_fixer::checks::formatting::{
+    check_line_endings, check_trailing_newline, check_trailing_whitespace, fix_trailing_whitespace,
+};
+use umbra_build_fixer::{analyze_build_file, fix_build_file, BuildIssue, Config};
+
+use crate::common::{test_config, workspace};
+
+const NO_TRAILING_NEWLINE: &str = include_str!("fixtures/no_trailing_newline.BUILD");
+
+#[test]
+fn missing_trailing_newline_is_detected() {
+    let (issue, _) = check_trailing_newline(NO_TRAILING_NEWLINE).unwrap();
+    assert_eq!(issue, BuildIssue::MissingTrailingNewline);
+    assert!(check_trailing_newline("package()\n").is_none());
+}
+
+#[test]
+fn fix_appends_exactly_one_newline() {
+    let dir = workspace(&[("Sources/Core", NO_TRAILING_NEWLINE)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(report.modified);
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert_eq!(fixed, format!("{}\n", NO_TRAILING_NEWLINE));
+    assert!(!fixed.ends_with("\n\n"));
+}
+
+const CRLF: &str = include_str!("fixtures/crlf.BUILD");
+
+#[test]
+fn crlf_line_endings_are_detected() {
+    let (issue, _) = check_line_endings(CRLF).unwrap();
+    assert_eq!(issue, BuildIssue::CrlfLineEnding);
+    assert!(check_line_endings("package()\n").is_none());
+    assert!(check_line_endings("package()\r").is_some());
+}
+
+#[test]
+fn fix_converts_line_endings_and_applies_other_fixes() {
+    let dir = workspace(&[("Sources/Core", CRLF)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| f.issue.clone()).collect();
+    assert_eq!(issues[0], BuildIssue::CrlfLineEnding);
+    assert!(issues.contains(&BuildIssue::CustomLibraryRule));
+    assert!(issues.contains(&BuildIssue::ExportsAttribute));
+
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(!fixed.contains('\r'));
+    assert!(fixed.contains("swift_library(\n    name = \"Core\""));
+    assert!(!fixed.contains("exports"));
+    assert!(fixed.contains("allow_empty = True"));
+}
+
+const TRAILING_WHITESPACE: &str =
+    "swift_library(  \n    name = \"Core\",\t\n    srcs = [\"a.swift\"], \t \n)\n";
+
+#[test]
+fn trailing_spaces_and_tabs_are_stripped() {
+    let (issue, message) = check_trailing_whitespace(TRAILING_WHITESPACE).unwrap();
+    assert_eq!(issue, BuildIssue::TrailingWhitespace);
+    assert!(message.contains("3 line(s)"), "{}", message);
+
+    let fixed = fix_trailing_whitespace(TRAILING_WHITESPACE);
+    assert_eq!(
+        fixed,
+        "swift_library(\n    name = \"Core\",\n    srcs = [\"a.swift\"],\n)\n"
+    );
+    assert!(check_trailing_whitespace(&fixed).is_none());
+    assert_eq!(fix_trailing_whitespace(&fixed), fixed);
+}
+
+#[test]
+fn whitespace_inside_multi_line_strings_is_kept() {
+    let content = "genrule(\n    name = \"Banner\",  \n    cmd = \"\"\"\necho 'a'  \necho 'b'\t\n\"\"\",\n)\n";
+
+    let fixed = fix_trailing_whitespace(content);
+
+    assert_eq!(fixed, content.replace("\"Banner\",  \n", "\"Banner\",\n"));
+    assert!(check_trailing_whitespace(&fixed).is_none());
+    let (_, message) = check_trailing_whitespace(content).unwrap();
+    assert!(message.contains("1 line(s)"), "{}", message);
+}
+
+#[test]
+fn trailing_whitespace_is_fixed_before_other_checks() {
+    let content = "glob([\"*.swift\"])  \n";
+    let findings = analyze_build_file(content, &Config::default());
+    assert_eq!(findings[0].issue, BuildIssue::TrailingWhitespace);
+}
@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use umbra_build_fixer::generate::generate_build_file;
+use umbra_build_fixer::sources::{collect_swift_files, determine_best_glob_pattern};
+use umbra_build_fixer::starlark::tokenizer::{find_matching, tokenize, TokenKind};
+use umbra_build_fixer::{analyze_build_file, Config};
+
+use crate::common::umbra_fix;
+
+fn touch(root: &Path, relative: &str) {
+    let path = root.join(relative);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, "").unwrap();
+}
+
+// Every bracket is closed, nothing is unrecognised, and the fixer has nothing to say.
+fn assert_valid_starlark(content: &str) {
+    let tokens = tokenize(content);
+    assert!(tokens.iter().all(|t| t.kind != TokenKind::Unknown));
+    let mut index = 0;
+    while index < tokens.len() {
+        if matches!(tokens[index].kind, TokenKind::LParen | TokenKind::LBracket) {
+            index = find_matching(&tokens, index).expect("unbalanced brackets");
+        }
+        assert!(!matches!(
+            tokens[index].kind,
+            TokenKind::LParen | TokenKind::LBracket
+        ));
+        index += 1;
+    }
+    assert_eq!(analyze_build_file(content, &Config::default()), vec![]);
+}
+
+#[test]
+fn glob_pattern_matches_source_layout() {
+    let flat = vec![PathBuf::from("A.swift"), PathBuf::from("B.swift")];
+    assert_eq!(determine_best_glob_pattern(&flat), "*.swift");
+
+    let nested = vec![
+        PathBuf::from("Sources/A.swift"),
+        PathBuf::from("Sources/Impl/B.swift"),
+    ];
+    assert_eq!(determine_best_glob_pattern(&nested), "Sources/**/*.swift");
+
+    let mixed = vec![PathBuf::from("A.swift"), PathBuf::from("Impl/B.swift")];
+    assert_eq!(determine_best_glob_pattern(&mixed), "**/*.swift");
+}
+
+#[test]
+fn nested_packages_are_not_collected() {
+    let dir = tempfile::tempdir().unwrap();
+    touch(dir.path(), "A.swift");
+    touch(dir.path(), "Impl/B.swift");
+    touch(dir.path(), "Sub/BUILD.bazel");
+    touch(dir.path(), "Sub/C.swift");
+
+    let files = collect_swift_files(dir.path()).unwrap();
+    assert_eq!(
+        files,
+        vec![PathBuf::from("A.swift"), PathBuf::from("Impl/B.swift")]
+    );
+}
+
+#[test]
+fn generated_build_file_is_valid_starlark() {
+    let dir = tempfile::tempdir().unwrap();
+    let module = dir.path().join("CoreTypes");
+    touch(&module, "Sources/Token.swift");
+
+    let content = generate_build_file(&module, None).unwrap();
+
+    assert!(content.contains("name = \"CoreTypes\""));
+    assert!(content.contains("[\"Sources/**/*.swift\"]"));
+    assert!(content.contains("# deps = ["));
+    assert_valid_starlark(&content);
+}
+
+#[test]
+fn template_placeholders_are_substituted() {
+    let dir = tempfile::tempdir().unwrap();
+    let module = dir.path().join("Core");
+    touch(&module, "Core.swift");
+    let template = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("devtools/build/fixers/tests/fixtures/templates/library.BUILD.tpl");
+
+    let content = generate_build_file(&module, Some(&template)).unwrap();
+
+    assert!(content.contains("module_name = \"UmbraCore\""));
+    assert!(content.contains("[\"*.swift\"]"));
+    assert_valid_starlark(&content);
+}
+
+#[test]
+fn generate_subcommand_writes_build_file_once() {
+    let dir = tempfile::tempdir().unwrap();
+    touch(dir.path(), "Core/Core.swift");
+    let module = dir.path().join("Core");
+    let module_arg = module.to_str().unwrap();
+
+    let output = umbra_fix(&["generate", "--dir", module_arg]);
+    assert!(output.status.success());
+    assert!(module.join("BUILD.bazel").is_file());
+
+    let output = umbra_fix(&["generate", "--dir", module_arg]);
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,95 @@
+use std::fs;
+
+use umbra_build_fixer::checks::globs::check_generated_sources;
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const GENERATED_SOURCES: &str = include_str!("fixtures/generated_sources.BUILD");
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+#[test]
+fn glob_matching_generated_sources_gets_an_exclude() {
+    let dir = workspace(&[("Sources/Models", GENERATED_SOURCES)]);
+    let package = dir.path().join("Sources/Models");
+    fs::write(package.join("Models.swift"), "").unwrap();
+    fs::write(package.join("Models.pb.swift"), "").unwrap();
+    let path = package.join("BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(report.findings.iter().any(|finding| finding.issue
+        == BuildIssue::GeneratedSourcesInGlob {
+            include: strings(&["*.swift"]),
+            exclude: strings(&["*.pb.swift"]),
+        }));
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(
+        fixed
+            .contains(r#"srcs = glob(["*.swift"], exclude = ["*.pb.swift"], allow_empty = True),"#),
+        "{}",
+        fixed
+    );
+}
+
+#[test]
+fn recursive_globs_exclude_generated_sources_in_subdirectories() {
+    let content = GENERATED_SOURCES.replace(r#"["*.swift"]"#, r#"["**/*.swift"]"#);
+    let dir = workspace(&[("Sources/Models", &content)]);
+    let package = dir.path().join("Sources/Models");
+    fs::create_dir_all(package.join("Proto")).unwrap();
+    fs::write(package.join("Proto/Models.pb.swift"), "").unwrap();
+    fs::write(package.join("Proto/Models.grpc.swift"), "").unwrap();
+
+    let issues = check_generated_sources(
+        &content,
+        &package,
+        &strings(&["*.generated.swift", "*.pb.swift", "*.grpc.swift"]),
+    );
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(
+        issues[0].0,
+        BuildIssue::GeneratedSourcesInGlob {
+            include: strings(&["**/*.swift"]),
+            exclude: strings(&["**/*.grpc.swift", "**/*.pb.swift"]),
+        }
+    );
+}
+
+#[test]
+fn globs_with_an_exclude_are_left_alone() {
+    let content = GENERATED_SOURCES.replace(
+        "allow_empty = True",
+        r#"exclude = ["Legacy.swift"], allow_empty = True"#,
+    );
+    let dir = workspace(&[("Sources/Models", &content)]);
+    let package = dir.path().join("Sources/Models");
+    fs::write(package.join("Models.pb.swift"), "").unwrap();
+
+    let issues = check_generated_sources(&content, &package, &strings(&["*.pb.swift"]));
+
+    assert!(issues.is_empty());
+}
+
+#[test]
+fn patterns_come_from_the_config() {
+    let dir = workspace(&[("Sources/Models", GENERATED_SOURCES)]);
+    fs::write(
+        dir.path().join("umbra-fix.toml"),
+        "generated_file_patterns = []\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("Sources/Models/Models.pb.swift"), "").unwrap();
+    let path = dir.path().join("Sources/Models/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(!report
+        .findings
+        .iter()
+        .any(|finding| matches!(finding.issue, BuildIssue::GeneratedSourcesInGlob { .. })));
+}
@@ -0,0 +1,140 @@
+use std::process::Command;
+
+use umbra_build_fixer::report::github_actions::{
+    annotation, annotation_level, issue_position, AnnotationLevel,
+};
+use umbra_build_fixer::{BuildIssue, Finding};
+
+use crate::common::workspace;
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+const UNLOADED: &str = r#"package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "Core",
+    srcs = glob(["*.swift"], allow_empty = True),
+)
+"#;
+
+const LIBRARIES: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+swift_library(
+    name = "Core",
+    srcs = [],
+)
+
+  swift_library(
+    name = "Crypto",
+    srcs = [],
+)
+"#;
+
+#[test]
+fn issues_point_at_their_target() {
+    let issue = BuildIssue::EmptySrcs {
+        target: "Crypto".to_string(),
+        has_srcs: true,
+    };
+    assert_eq!(issue_position(LIBRARIES, &issue), (8, 3));
+    assert_eq!(
+        issue_position(LIBRARIES, &BuildIssue::MissingTrailingNewline),
+        (1, 1)
+    );
+}
+
+#[test]
+fn issues_found_on_a_line_point_at_it() {
+    let issue = BuildIssue::SelectWithoutDefault {
+        line: 9,
+        target: Some("Crypto".to_string()),
+    };
+    assert_eq!(issue_position(LIBRARIES, &issue), (9, 1));
+    let issue = BuildIssue::InvalidSyntax {
+        line: 4,
+        column: 12,
+    };
+    assert_eq!(issue_position(LIBRARIES, &issue), (4, 12));
+    let issue = BuildIssue::CommentedOutRule { line: 6 };
+    assert_eq!(issue_position(LIBRARIES, &issue), (6, 1));
+}
+
+#[test]
+fn levels_follow_severity() {
+    assert_eq!(
+        annotation_level(&BuildIssue::MissingSwiftLibraryLoad),
+        AnnotationLevel::Error
+    );
+    assert_eq!(
+        annotation_level(&BuildIssue::CustomLibraryRule),
+        AnnotationLevel::Warning
+    );
+    assert_eq!(
+        annotation_level(&BuildIssue::TrailingWhitespace),
+        AnnotationLevel::Notice
+    );
+}
+
+#[test]
+fn annotation_escapes_properties_and_message() {
+    let finding = Finding {
+        issue: BuildIssue::EmptySrcs {
+            target: "Core".to_string(),
+            has_srcs: true,
+        },
+        message: "srcs match 0% of files\nsee docs".to_string(),
+    };
+
+    assert_eq!(
+        annotation("Sources/a,b/BUILD.bazel", LIBRARIES, &finding),
+        "::error file=Sources/a%2Cb/BUILD.bazel,line=3,col=1::[EmptySrcs] srcs match 0%25 of files%0Asee docs"
+    );
+}
+
+#[test]
+fn annotations_are_printed_in_github_actions() {
+    let dir = workspace(&[("Sources/Core", UNLOADED)]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_umbra-fix"))
+        .args([
+            "--check",
+            "--no-cache",
+            "--root",
+            dir.path().to_str().unwrap(),
+        ])
+        .env("CI", "true")
+        .env("GITHUB_ACTIONS", "true")
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.starts_with("::error file=Sources/Core/BUILD.bazel,line=1,col=1::")),
+        "{}",
+        stdout
+    );
+    // The normal output is kept
+    assert!(stdout.contains("Sources/Core/BUILD.bazel: ["), "{}", stdout);
+}
+
+#[test]
+fn annotations_need_github_actions() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_umbra-fix"))
+        .args([
+            "--check",
+            "--no-cache",
+            "--root",
+            dir.path().to_str().unwrap(),
+        ])
+        .env("CI", "true")
+        .env_remove("GITHUB_ACTIONS")
+        .output()
+        .unwrap();
+
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("::"));
+}
@@ -0,0 +1,206 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use proptest::prelude::*;
+use umbra_build_fixer::checks::globs::check_redundant_allow_empty;
+use umbra_build_fixer::glob_evaluator::{bazel_glob_match, evaluate_glob};
+
+use crate::common::workspace;
+
+fn touch(root: &Path, file: &str) {
+    let path = root.join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, "").unwrap();
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+fn paths(values: &[&str]) -> Vec<PathBuf> {
+    values.iter().map(PathBuf::from).collect()
+}
+
+// A package with Swift files at several depths, a resource bundle and a
+// nested package
+fn package() -> tempfile::TempDir {
+    let dir = workspace(&[]);
+    for file in [
+        "Core.swift",
+        "Internal/Helper.swift",
+        "Internal/Deep/Impl.swift",
+        "Internal/Deep/ImplTests.swift",
+        "Resources/Assets.xcassets/Contents.json",
+        "Plugin/BUILD.bazel",
+        "Plugin/Plugin.swift",
+        "Legacy/BUILD",
+        "Legacy/Old.swift",
+    ] {
+        touch(dir.path(), file);
+    }
+    dir
+}
+
+#[test]
+fn known_matches() {
+    let cases = [
+        ("*.swift", "Core.swift", true),
+        ("*.swift", "Internal/Helper.swift", false),
+        ("**/*.swift", "Core.swift", true),
+        ("**/*.swift", "Internal/Deep/Impl.swift", true),
+        ("Internal/**/*.swift", "Internal/Helper.swift", true),
+        ("Internal/*/Impl.swift", "Internal/Deep/Impl.swift", true),
+        ("Internal/**", "Internal/Deep/Impl.swift", true),
+        // A trailing ** needs at least one segment
+        ("Internal/**", "Internal", false),
+        ("**", "Core.swift", true),
+        ("Core.?wift", "Core.swift", true),
+        ("Core.swift", "Core.swift", true),
+        ("Core.swift", "Internal/Core.swift", false),
+    ];
+    for (pattern, path, expected) in cases {
+        assert_eq!(
+            bazel_glob_match(pattern, path),
+            expected,
+            "{} vs {}",
+            pattern,
+            path
+        );
+    }
+}
+
+#[test]
+fn files_are_found_sorted_and_without_duplicates() {
+    let dir = package();
+
+    let files = evaluate_glob(
+        dir.path(),
+        &strings(&["**/*.swift", "Internal/**/*.swift"]),
+        &[],
+        true,
+    )
+    .unwrap();
+
+    assert_eq!(
+        files,
+        paths(&[
+            "Core.swift",
+            "Internal/Deep/Impl.swift",
+            "Internal/Deep/ImplTests.swift",
+            "Internal/Helper.swift",
+        ])
+    );
+}
+
+#[test]
+fn excludes_remove_matches() {
+    let dir = package();
+
+    let files = evaluate_glob(
+        dir.path(),
+        &strings(&["**/*.swift"]),
+        &strings(&["**/*Tests.swift", "Core.swift"]),
+        true,
+    )
+    .unwrap();
+
+    assert_eq!(
+        files,
+        paths(&["Internal/Deep/Impl.swift", "Internal/Helper.swift"])
+    );
+}
+
+#[test]
+fn nested_packages_are_not_matched_into() {
+    let dir = package();
+
+    let files = evaluate_glob(dir.path(), &strings(&["**"]), &[], true).unwrap();
+
+    assert!(!files.iter().any(|file| file.starts_with("Plugin")));
+    assert!(!files.iter().any(|file| file.starts_with("Legacy")));
+}
+
+#[test]
+fn directories_are_matched_only_when_not_excluded() {
+    let dir = package();
+    let patterns = strings(&["Resources/*"]);
+
+    assert!(evaluate_glob(dir.path(), &patterns, &[], true)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        evaluate_glob(dir.path(), &patterns, &[], false).unwrap(),
+        paths(&["Resources/Assets.xcassets"])
+    );
+    // `dir/**` doesn't match `dir` itself, even with directories
+    let all = evaluate_glob(dir.path(), &strings(&["Internal/**"]), &[], false).unwrap();
+    assert!(!all.contains(&PathBuf::from("Internal")));
+    assert!(all.contains(&PathBuf::from("Internal/Deep")));
+}
+
+#[test]
+fn exclude_directories_is_honored_by_redundant_allow_empty_check() {
+    let dir = package();
+    let content = r#"filegroup(
+    name = "Bundles",
+    srcs = glob(["Resources/*"], allow_empty = True, exclude_directories = 0),
+)
+
+filegroup(
+    name = "Files",
+    srcs = glob(["Resources/*"], allow_empty = True),
+)
+"#;
+
+    let issues = check_redundant_allow_empty(content, dir.path());
+
+    // Only the glob that matches the bundle directory needs no allow_empty
+    assert_eq!(issues.len(), 1, "{:?}", issues);
+}
+
+fn segment() -> impl Strategy<Value = String> {
+    "[a-c]{1,2}(\\.swift)?"
+}
+
+proptest! {
+    #[test]
+    fn evaluation_agrees_with_matching(
+        files in prop::collection::btree_set(prop::collection::vec(segment(), 1..4), 1..8),
+        pattern in prop::sample::select(vec!["**", "*", "**/*.swift", "a/**", "*/*", "a*/**/b*", "**/a"]),
+        exclude in prop::sample::select(vec!["", "**/*.swift", "b/**", "a"]),
+    ) {
+        let dir = workspace(&[]);
+        let mut written = BTreeSet::new();
+        for segments in &files {
+            let file = segments.join("/");
+            // A path can't be both a file and a directory
+            let clashes = written.iter().any(|other: &String| {
+                other.starts_with(&format!("{}/", file)) || file.starts_with(&format!("{}/", other))
+            });
+            if clashes {
+                continue;
+            }
+            touch(dir.path(), &file);
+            written.insert(file);
+        }
+        let excludes = if exclude.is_empty() { Vec::new() } else { strings(&[exclude]) };
+
+        let found = evaluate_glob(dir.path(), &strings(&[pattern]), &excludes, true).unwrap();
+
+        let expected: Vec<PathBuf> = written
+            .iter()
+            .filter(|file| bazel_glob_match(pattern, file))
+            .filter(|file| !excludes.iter().any(|exclude| bazel_glob_match(exclude, file)))
+            .map(PathBuf::from)
+            .collect();
+        prop_assert_eq!(&found, &expected);
+
+        // Without excludes everything matches `**`, and nothing matches a
+        // pattern that is also excluded
+        let all = evaluate_glob(dir.path(), &strings(&["**"]), &[], true).unwrap();
+        prop_assert_eq!(all.len(), written.len());
+        let none = evaluate_glob(dir.path(), &strings(&[pattern]), &strings(&[pattern]), true).unwrap();
+        prop_assert!(none.is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+use std::fs;
+
+use umbra_build_fixer::checks::paths::{check_hardcoded_paths, fix_hardcoded_path};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const HARDCODED_PATH: &str = include_str!("fixtures/hardcoded_path.BUILD");
+
+fn issues(content: &str, root_name: Option<&str>) -> Vec<BuildIssue> {
+    check_hardcoded_paths(content, root_name, "$(WORKSPACE_ROOT)")
+        .into_iter()
+        .map(|(issue, _)| issue)
+        .collect()
+}
+
+#[test]
+fn paths_into_the_checkout_keep_their_relative_part() {
+    let issues = issues(HARDCODED_PATH, Some("UmbraCore"));
+
+    assert_eq!(
+        issues,
+        [
+            BuildIssue::HardcodedProjectPath {
+                path: "/Users/mpy/CascadeProjects/UmbraCore/Sources/Core".to_string(),
+                replacement: "$(WORKSPACE_ROOT)/Sources/Core".to_string(),
+            },
+            BuildIssue::HardcodedProjectPath {
+                path: "/Users/mpy/CascadeProjects/UmbraCore/Resources/config.json".to_string(),
+                replacement: "$(WORKSPACE_ROOT)/Resources/config.json".to_string(),
+            },
+            BuildIssue::HardcodedProjectPath {
+                path: "C:\\\\Users\\\\mpy\\\\UmbraCore\\\\Resources\\\\strings.json".to_string(),
+                replacement: "$(WORKSPACE_ROOT)\\\\Resources\\\\strings.json".to_string(),
+            },
+            BuildIssue::HardcodedProjectPath {
+                path: "/home/ci/.cache/umbra".to_string(),
+                replacement: "$(WORKSPACE_ROOT)".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn paths_are_replaced_in_place() {
+    let fixed = fix_hardcoded_path(
+        HARDCODED_PATH,
+        "/Users/mpy/CascadeProjects/UmbraCore/Sources/Core",
+        "$(WORKSPACE_ROOT)/Sources/Core",
+    );
+
+    assert!(fixed.contains(r#"copts = ["-DPROJECT_ROOT=$(WORKSPACE_ROOT)/Sources/Core"],"#));
+    // A longer path sharing the prefix is left for its own fix
+    assert!(fixed.contains("\"/Users/mpy/CascadeProjects/UmbraCore/Resources/config.json\""));
+}
+
+#[test]
+fn include_flags_and_relative_paths_are_not_flagged() {
+    let content = r#"swift_library(
+    name = "Core",
+    copts = ["-I/Users/dev/include"],
+    srcs = ["Sources/home/Users.swift"],
+)
+"#;
+
+    assert!(issues(content, Some("UmbraCore")).is_empty());
+}
+
+#[test]
+fn fixing_uses_the_configured_variable() {
+    let dir = workspace(&[("Sources/Core", HARDCODED_PATH)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+    config.project_path_variable = "$(SRCROOT)".to_string();
+
+    fix_build_file(&path, &config).unwrap();
+
+    let content = fs::read_to_string(&path).unwrap();
+    for hardcoded in ["/Users/", "/home/", "C:\\\\"] {
+        assert!(!content.contains(hardcoded), "{}", content);
+    }
+    assert!(content.contains("\"$(SRCROOT)\""), "{}", content);
+    assert!(content.contains("cache=$(SRCROOT)"), "{}", content);
+}
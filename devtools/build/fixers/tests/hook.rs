@@ -0,0 +1,113 @@
+use std::fs;
+
+use umbra_build_fixer::hook::{hook_block, HOOK_BEGIN, HOOK_END};
+
+use crate::common::{git_repo, umbra_fix, write_build_file};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+#[test]
+fn install_hook_writes_executable_pre_commit_script() {
+    let dir = git_repo(&[]);
+    let repo = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["install-hook", "--repo", repo]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let hook = dir.path().join(".git/hooks/pre-commit");
+    let script = fs::read_to_string(&hook).unwrap();
+    assert!(script.starts_with("#!/bin/sh\n"), "{}", script);
+    assert!(script.contains(HOOK_BEGIN) && script.contains(HOOK_END));
+    assert!(
+        script.contains("' --check --git-changed-only HEAD || exit 1\n"),
+        "{}",
+        script
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_ne!(fs::metadata(&hook).unwrap().permissions().mode() & 0o111, 0);
+    }
+
+    // Installing twice is refused rather than duplicating the block
+    let output = umbra_fix(&["install-hook", "--repo", repo]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[cfg(unix)]
+#[test]
+fn executable_path_reaches_the_shell_unchanged() {
+    use std::path::Path;
+    use std::process::Command;
+
+    let exe = r#"/opt/it's "my" $HOME/`tools`\umbra-fix"#;
+    let block = hook_block(Path::new(exe));
+    let line = block
+        .lines()
+        .find(|line| line.ends_with(" --check --git-changed-only HEAD || exit 1"))
+        .unwrap();
+    let word = line.trim_end_matches(" --check --git-changed-only HEAD || exit 1");
+
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(format!("printf %s {}", word))
+        .output()
+        .unwrap();
+
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), exe);
+}
+
+#[test]
+fn existing_hook_is_appended_to_and_restored_on_uninstall() {
+    let dir = git_repo(&[]);
+    let repo = dir.path().to_str().unwrap();
+    let hook = dir.path().join(".git/hooks/pre-commit");
+    let original = "#!/bin/sh\n./scripts/lint.sh\n";
+    fs::write(&hook, original).unwrap();
+
+    assert!(umbra_fix(&["install-hook", "--repo", repo])
+        .status
+        .success());
+    let script = fs::read_to_string(&hook).unwrap();
+    assert!(script.starts_with(original), "{}", script);
+    assert!(script.contains(HOOK_BEGIN), "{}", script);
+
+    assert!(umbra_fix(&["uninstall-hook", "--repo", repo])
+        .status
+        .success());
+    assert_eq!(fs::read_to_string(&hook).unwrap(), original);
+}
+
+#[test]
+fn uninstall_removes_hook_that_only_ran_umbra_fix() {
+    let dir = git_repo(&[]);
+    let repo = dir.path().to_str().unwrap();
+
+    assert!(umbra_fix(&["install-hook", "--repo", repo])
+        .status
+        .success());
+    assert!(umbra_fix(&["uninstall-hook", "--repo", repo])
+        .status
+        .success());
+
+    assert!(!dir.path().join(".git/hooks/pre-commit").exists());
+}
+
+#[test]
+fn git_changed_only_skips_unchanged_files() {
+    let dir = git_repo(&[("Sources/Core", DIRTY), ("Sources/Utils", CLEAN)]);
+    let root = dir.path().to_str().unwrap();
+
+    // Committed dirty files are not reported until they change
+    let output = umbra_fix(&["--check", "--git-changed-only", "HEAD", "--root", root]);
+    assert_eq!(output.status.code(), Some(0));
+
+    write_build_file(dir.path(), "Sources/Utils", DIRTY);
+    let output = umbra_fix(&["--check", "--git-changed-only", "HEAD", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Sources/Utils/BUILD.bazel"), "{}", stdout);
+    assert!(!stdout.contains("Sources/Core/BUILD.bazel"), "{}", stdout);
+}
@@ -0,0 +1,72 @@
+use std::fs;
+
+use umbra_build_fixer::report::html::render_html;
+use umbra_build_fixer::{fix_build_file, OutputFormat, RunMode};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+#[test]
+fn html_report_lists_issues_and_diffs() {
+    let dir = workspace(&[("Sources/Core", DIRTY), ("Sources/Utils", CLEAN)]);
+    let mut config = test_config(dir.path());
+    config.mode = RunMode::DryRun;
+    config.output = OutputFormat::Html;
+    let reports: Vec<_> = ["Sources/Core", "Sources/Utils"]
+        .iter()
+        .map(|package| {
+            let path = dir.path().join(package).join("BUILD.bazel");
+            fix_build_file(&path, &config).unwrap()
+        })
+        .collect();
+
+    let html = render_html(&config, &reports);
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("2 BUILD files scanned"), "{}", html);
+    assert!(html.contains("<tr><td>CustomLibraryRule</td><td class=\"count\">1</td></tr>"));
+    assert!(html.contains("<summary>Sources/Core/BUILD.bazel ("));
+    assert!(!html.contains("Sources/Utils/BUILD.bazel"));
+    assert!(html.contains("<span class=\"file\">--- a/Sources/Core/BUILD.bazel</span>"));
+    assert!(html.contains("<span class=\"add\">+"));
+    assert!(html.contains("<span class=\"del\">-"));
+    // Self-contained: no external stylesheets or scripts
+    assert!(!html.contains("<link"));
+    assert!(!html.contains("src="));
+}
+
+#[test]
+fn output_html_writes_report_without_touching_files() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+    let report_dir = tempfile::tempdir().unwrap();
+    let report = report_dir.path().join("report.html");
+
+    let output = umbra_fix(&[
+        "--root",
+        root,
+        "--output",
+        "html",
+        "--html-file",
+        report.to_str().unwrap(),
+    ]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let content = fs::read_to_string(dir.path().join("Sources/Core/BUILD.bazel")).unwrap();
+    assert_eq!(content, DIRTY);
+    let html = fs::read_to_string(&report).unwrap();
+    assert!(html.contains("umbra_swift_library"));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Adding to report:"));
+}
+
+#[test]
+fn output_html_requires_html_file() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--root", root, "--output", "html"]);
+
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,95 @@
+use std::fs;
+
+use umbra_build_fixer::checks::{analyze_build_file_at, apply_fixes};
+use umbra_build_fixer::fixer::verify_idempotent;
+use umbra_build_fixer::{fix_build_file, BuildIssue, RunMode};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+const MISSING_SRCS: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+swift_library(
+    name = "Core",
+    visibility = ["//visibility:public"],
+)
+"#;
+
+const LEGACY_LOAD: &str = r#"load("//tools:a.bzl", "swift_helper")
+
+swift_helper(name = "Core")
+"#;
+
+// Migrations that send each load back where the other came from, so fixing
+// one LegacyRuleLoad always introduces another
+const CYCLIC_MIGRATIONS: &str = r#"defaults = false
+
+[migrations]
+"//tools:a.bzl" = "//tools:b.bzl"
+"//tools:b.bzl" = "//tools:a.bzl"
+"#;
+
+#[test]
+fn correct_fixes_pass_verification() {
+    let dir = workspace(&[("Sources/Core", DIRTY), ("Sources/Lib", MISSING_SRCS)]);
+    let config = test_config(dir.path());
+
+    for package in ["Sources/Core", "Sources/Lib"] {
+        let path = dir.path().join(package).join("BUILD.bazel");
+        let report = fix_build_file(&path, &config).unwrap();
+        assert!(report.modified);
+    }
+}
+
+#[test]
+fn fixes_verify_against_their_findings() {
+    let dir = workspace(&[("Sources/Core", MISSING_SRCS)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let config = test_config(dir.path());
+
+    let findings = analyze_build_file_at(MISSING_SRCS, &path, &config);
+    assert!(findings.iter().any(|finding| finding.issue
+        == BuildIssue::EmptySrcs {
+            target: "Core".to_string(),
+            has_srcs: false,
+        }));
+    assert!(verify_idempotent(
+        &path,
+        &findings,
+        &apply_fixes(MISSING_SRCS, &findings),
+        &config
+    )
+    .is_ok());
+}
+
+#[test]
+fn non_idempotent_fix_is_caught() {
+    let dir = workspace(&[("Sources/Core", LEGACY_LOAD)]);
+    fs::write(dir.path().join("rule_migrations.toml"), CYCLIC_MIGRATIONS).unwrap();
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+    config.mode = RunMode::DryRun;
+
+    let err = fix_build_file(&path, &config).unwrap_err();
+
+    assert!(
+        err.to_string()
+            .contains("loads //tools:b.bzl, which has moved to //tools:a.bzl"),
+        "{}",
+        err
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), LEGACY_LOAD);
+}
+
+#[test]
+fn verify_idempotent_flag_is_accepted() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--verify-idempotent", "--root", root]);
+
+    assert!(output.status.success());
+    let fixed = fs::read_to_string(dir.path().join("Sources/Core/BUILD.bazel")).unwrap();
+    assert_ne!(fixed, DIRTY);
+}
@@ -0,0 +1,125 @@
+use std::fs;
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use umbra_build_fixer::fix_build_file;
+use umbra_build_fixer::report::junit::{render_junit, CLEAN_TEST_CASE};
+use umbra_build_fixer::RunMode;
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+// Names of the elements in `xml`, in document order; panics if it isn't
+// well-formed
+fn element_names(xml: &str) -> Vec<String> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().check_end_names = true;
+    let mut names = Vec::new();
+    loop {
+        match reader.read_event().unwrap() {
+            Event::Start(element) | Event::Empty(element) => {
+                names.push(String::from_utf8(element.name().as_ref().to_vec()).unwrap())
+            }
+            Event::Eof => return names,
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn junit_report_has_a_suite_per_file_and_a_failure_per_issue() {
+    let dir = workspace(&[("Sources/Core", DIRTY), ("Sources/Utils", CLEAN)]);
+    let mut config = test_config(dir.path());
+    config.mode = RunMode::DryRun;
+    let reports: Vec<_> = ["Sources/Core", "Sources/Utils"]
+        .iter()
+        .map(|package| {
+            let path = dir.path().join(package).join("BUILD.bazel");
+            fix_build_file(&path, &config).unwrap()
+        })
+        .collect();
+    let failures = reports[0].findings.len();
+    assert!(failures > 0);
+    assert!(reports[1].findings.is_empty());
+
+    let xml = render_junit(&reports).unwrap();
+
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+    let names = element_names(&xml);
+    assert_eq!(names.iter().filter(|name| *name == "testsuites").count(), 1);
+    assert_eq!(names.iter().filter(|name| *name == "testsuite").count(), 2);
+    assert_eq!(
+        names.iter().filter(|name| *name == "testcase").count(),
+        failures + 1
+    );
+    assert_eq!(
+        names.iter().filter(|name| *name == "failure").count(),
+        failures
+    );
+    assert!(xml.contains(&format!(
+        "<testsuites name=\"umbra-fix\" tests=\"{}\" failures=\"{}\">",
+        failures + 1,
+        failures
+    )));
+    assert!(
+        xml.contains("<testcase name=\"CustomLibraryRule\""),
+        "{}",
+        xml
+    );
+    assert!(xml.contains(&format!("<testcase name=\"{}\"", CLEAN_TEST_CASE)));
+    assert!(xml.contains("type=\"fixable\""));
+}
+
+#[test]
+fn junit_report_escapes_messages() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let mut config = test_config(dir.path());
+    config.mode = RunMode::DryRun;
+    let mut report = fix_build_file(&dir.path().join("Sources/Core/BUILD.bazel"), &config).unwrap();
+    report.findings[0].message = "deps < \"srcs\" & more".to_string();
+
+    let xml = render_junit(&[report]).unwrap();
+
+    element_names(&xml);
+    assert!(
+        xml.contains("message=\"deps &lt; &quot;srcs&quot; &amp; more\""),
+        "{}",
+        xml
+    );
+}
+
+#[test]
+fn output_junit_writes_report_without_touching_files() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+    let report_dir = tempfile::tempdir().unwrap();
+    let report = report_dir.path().join("results.xml");
+
+    let output = umbra_fix(&[
+        "--root",
+        root,
+        "--output",
+        "junit",
+        "--junit-file",
+        report.to_str().unwrap(),
+    ]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let content = fs::read_to_string(dir.path().join("Sources/Core/BUILD.bazel")).unwrap();
+    assert_eq!(content, DIRTY);
+    let xml = fs::read_to_string(&report).unwrap();
+    assert!(element_names(&xml).contains(&"failure".to_string()));
+    assert!(xml.contains("Sources/Core/BUILD.bazel"));
+}
+
+#[test]
+fn output_junit_requires_junit_file() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--root", root, "--output", "junit"]);
+
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,102 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use umbra_build_fixer::label::Label;
+use umbra_build_fixer::label_resolver::resolve_label;
+
+use crate::common::{umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+fn label(package: &str, target: &str) -> Label {
+    Label {
+        package: package.to_string(),
+        target: target.to_string(),
+    }
+}
+
+#[test]
+fn parses_all_label_forms() {
+    assert_eq!(
+        Label::parse("//Sources/Core:Core", "").unwrap(),
+        label("Sources/Core", "Core")
+    );
+    assert_eq!(
+        Label::parse("//Sources/Core", "").unwrap(),
+        label("Sources/Core", "Core")
+    );
+    assert_eq!(
+        Label::parse(":CoreTests", "Sources/Core").unwrap(),
+        label("Sources/Core", "CoreTests")
+    );
+    assert_eq!(Label::parse("@//:umbra", "").unwrap(), label("", "umbra"));
+}
+
+#[test]
+fn rejects_invalid_labels() {
+    for invalid in [
+        "Sources/Core",
+        "//Sources/Core:",
+        "@rules_swift//swift",
+        "//../x:y",
+    ] {
+        let err = Label::parse(invalid, "").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{}", invalid);
+    }
+}
+
+#[test]
+fn errors_match_the_label_resolver() {
+    for invalid in ["//Sources/Core:", "//Sources/../Core", "@rules_swift"] {
+        let err = Label::parse(invalid, "").unwrap_err();
+        let resolver_err = resolve_label(invalid, "", Path::new("")).unwrap_err();
+        assert_eq!(err.to_string(), resolver_err.to_string(), "{}", invalid);
+    }
+}
+
+#[test]
+fn resolves_label_to_build_file() {
+    let dir = workspace(&[("", DIRTY), ("Sources/Core", DIRTY)]);
+
+    assert_eq!(
+        label("Sources/Core", "Core")
+            .build_file(dir.path())
+            .unwrap(),
+        dir.path().join("Sources/Core/BUILD.bazel")
+    );
+    assert_eq!(
+        label("", "umbra").build_file(dir.path()).unwrap(),
+        dir.path().join("BUILD.bazel")
+    );
+
+    let err = label("Sources/Missing", "Missing")
+        .build_file(dir.path())
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    assert!(err.to_string().contains("//Sources/Missing:Missing"));
+}
+
+#[test]
+fn target_flag_fixes_only_that_file() {
+    let dir = workspace(&[("Sources/Core", DIRTY), ("Sources/Utils", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--root", root, "--target", "//Sources/Core:Core"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let read = |package: &str| fs::read_to_string(dir.path().join(package).join("BUILD.bazel"));
+    assert_ne!(read("Sources/Core").unwrap(), DIRTY);
+    assert_eq!(read("Sources/Utils").unwrap(), DIRTY);
+}
+
+#[test]
+fn target_flag_fails_for_missing_package() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--root", root, "--target", "//Sources/Missing"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("doesn't exist"));
+}
@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use umbra_build_fixer::label_resolver::{resolve_label, AbsoluteLabel, LabelError};
+
+const ROOT: &str = "/workspace";
+
+fn resolve(label: &str, package: &str) -> Result<AbsoluteLabel, LabelError> {
+    resolve_label(label, package, Path::new(ROOT))
+}
+
+fn absolute(repository: Option<&str>, package: &str, target: &str) -> AbsoluteLabel {
+    AbsoluteLabel {
+        repository: repository.map(String::from),
+        package: package.to_string(),
+        target: target.to_string(),
+    }
+}
+
+#[test]
+fn all_forms_resolve_to_the_same_label() {
+    let core = absolute(None, "Sources/Core", "Core");
+
+    assert_eq!(resolve(":Core", "Sources/Core").unwrap(), core);
+    assert_eq!(resolve("Core", "Sources/Core").unwrap(), core);
+    assert_eq!(resolve("//Sources/Core", "Sources/Logging").unwrap(), core);
+    assert_eq!(resolve("//Sources/Core:Core", "").unwrap(), core);
+    assert_eq!(resolve("@//Sources/Core", "").unwrap(), core);
+    assert_eq!(core.to_string(), "//Sources/Core:Core");
+}
+
+#[test]
+fn current_package_may_be_a_directory() {
+    assert_eq!(
+        resolve(":CoreTests", "/workspace/Sources/Core").unwrap(),
+        absolute(None, "Sources/Core", "CoreTests")
+    );
+    assert_eq!(
+        resolve(":CoreTests", "/elsewhere/Sources/Core"),
+        Err(LabelError::OutsideWorkspace {
+            package: "/elsewhere/Sources/Core".to_string(),
+        })
+    );
+}
+
+#[test]
+fn external_repositories_keep_their_name() {
+    let label = resolve("@rules_swift//swift:swift", "Sources/Core").unwrap();
+    assert_eq!(label, absolute(Some("rules_swift"), "swift", "swift"));
+    assert_eq!(label.to_string(), "@rules_swift//swift:swift");
+
+    // The implicit target is the last package component here too
+    assert_eq!(
+        resolve("@swift_argument_parser//Sources/ArgumentParser", "").unwrap(),
+        absolute(
+            Some("swift_argument_parser"),
+            "Sources/ArgumentParser",
+            "ArgumentParser"
+        )
+    );
+}
+
+#[test]
+fn invalid_labels_are_rejected() {
+    assert!(matches!(
+        resolve("//Sources/Core:", ""),
+        Err(LabelError::MissingTarget { .. })
+    ));
+    assert!(matches!(
+        resolve("//Sources/../Core", ""),
+        Err(LabelError::InvalidPackage { .. })
+    ));
+    assert!(matches!(
+        resolve("@rules_swift", ""),
+        Err(LabelError::Malformed { .. })
+    ));
+    assert!(matches!(
+        resolve("Sources/Core:Core", ""),
+        Err(LabelError::Malformed { .. })
+    ));
+}
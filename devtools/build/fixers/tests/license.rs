@@ -0,0 +1,90 @@
+use std::fs;
+
+use umbra_build_fixer::checks::package::{check_license, fix_license};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const UNLICENSED: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "Core",
+    srcs = glob(["*.swift"], allow_empty = True),
+)
+"#;
+
+fn notice() -> BuildIssue {
+    BuildIssue::MissingLicense {
+        license: "notice".to_string(),
+    }
+}
+
+#[test]
+fn missing_licenses_call_is_added_below_package() {
+    let (issue, _) = check_license(UNLICENSED, "notice").unwrap();
+    assert_eq!(issue, notice());
+
+    let fixed = fix_license(UNLICENSED, "notice");
+
+    assert!(
+        fixed.contains(
+            "package(default_visibility = [\"//visibility:public\"])\nlicenses([\"notice\"])\n\nswift_library("
+        ),
+        "{}",
+        fixed
+    );
+    assert_eq!(check_license(&fixed, "notice"), None);
+}
+
+#[test]
+fn correct_license_is_accepted() {
+    let content = UNLICENSED.replace(
+        ")\n\nswift_library",
+        ")\n\nlicenses([\"notice\"])\n\nswift_library",
+    );
+
+    assert_eq!(check_license(&content, "notice"), None);
+}
+
+#[test]
+fn wrong_license_type_is_replaced() {
+    let content = UNLICENSED.replace(
+        ")\n\nswift_library",
+        ")\n\nlicenses([\"restricted\"])  # legal review\n\nswift_library",
+    );
+
+    let (issue, message) = check_license(&content, "notice").unwrap();
+    assert_eq!(issue, notice());
+    assert!(message.contains("\"restricted\""), "{}", message);
+
+    let fixed = fix_license(&content, "notice");
+    assert!(
+        fixed.contains("\nlicenses([\"notice\"])  # legal review\n"),
+        "{}",
+        fixed
+    );
+    assert_eq!(check_license(&fixed, "notice"), None);
+}
+
+#[test]
+fn license_type_is_configurable() {
+    let dir = workspace(&[("Sources/Core", UNLICENSED)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+
+    let report = fix_build_file(&path, &config).unwrap();
+    assert!(report.findings.is_empty());
+
+    config.require_license = true;
+    config.license_type = "unencumbered".to_string();
+    let report = fix_build_file(&path, &config).unwrap();
+
+    assert!(report.findings.iter().any(|finding| finding.issue
+        == BuildIssue::MissingLicense {
+            license: "unencumbered".to_string(),
+        }));
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(fixed.contains("licenses([\"unencumbered\"])"), "{}", fixed);
+}
@@ -0,0 +1,110 @@
+use std::fs;
+
+use umbra_build_fixer::checks::formatting::{check_line_length, fix_line_length};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const LONG_DEPS: &str = include_str!("fixtures/long_deps.BUILD");
+
+#[test]
+fn long_lines_are_flagged() {
+    let issues = check_line_length(LONG_DEPS, 100);
+
+    let issues: Vec<_> = issues.into_iter().map(|(issue, _)| issue).collect();
+    assert_eq!(
+        issues,
+        [
+            BuildIssue::LineTooLong {
+                line: 8,
+                target: Some("Core".to_string()),
+                attribute: Some("deps".to_string()),
+                splittable: true,
+            },
+            BuildIssue::LineTooLong {
+                line: 9,
+                target: Some("Core".to_string()),
+                attribute: Some("copts".to_string()),
+                splittable: false,
+            },
+        ]
+    );
+    assert!(!issues[1].is_fixable());
+    assert!(check_line_length(LONG_DEPS, 250).is_empty());
+}
+
+#[test]
+fn deps_are_split_one_per_line() {
+    let fixed = fix_line_length(LONG_DEPS, "Core", "deps");
+
+    assert!(
+        fixed.contains(
+            "    deps = [\n        \"//Sources/UmbraCoreTypes\",\n        \"//Sources/UmbraCryptoService\",\n"
+        ),
+        "{}",
+        fixed
+    );
+    assert!(
+        fixed.contains("        \"//Sources/UmbraXPC\",\n    ],\n    copts"),
+        "{}",
+        fixed
+    );
+    assert_eq!(fixed.matches("\"//Sources/").count(), 7);
+    assert!(fixed
+        .lines()
+        .filter(|line| line.contains("//Sources/"))
+        .all(|line| line.len() < 100));
+}
+
+#[test]
+fn glob_include_list_is_split() {
+    let content = "swift_library(\n    name = \"Core\",\n    srcs = glob([\"Sources/**/*.swift\", \"Generated/**/*.swift\"], allow_empty = True),\n)\n";
+
+    let issues = check_line_length(content, 80);
+    assert!(matches!(
+        issues[0].0,
+        BuildIssue::LineTooLong {
+            splittable: true,
+            ..
+        }
+    ));
+    assert_eq!(
+        fix_line_length(content, "Core", "srcs"),
+        "swift_library(\n    name = \"Core\",\n    srcs = glob([\n        \"Sources/**/*.swift\",\n        \"Generated/**/*.swift\",\n    ], allow_empty = True),\n)\n"
+    );
+}
+
+#[test]
+fn max_line_length_flag_fixes_deps() {
+    let dir = workspace(&[("Sources/Core", LONG_DEPS)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--max-line-length", "100", "--no-cache", "--root", root]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let fixed = fs::read_to_string(dir.path().join("Sources/Core/BUILD.bazel")).unwrap();
+    assert!(
+        fixed.contains("        \"//Sources/UmbraXPC\",\n"),
+        "{}",
+        fixed
+    );
+    // The copts line still needs a manual edit
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("LineTooLong") || stderr.contains("LineTooLong"),
+        "{}{}",
+        stdout,
+        stderr
+    );
+}
+
+#[test]
+fn lines_are_unlimited_by_default() {
+    let dir = workspace(&[("Sources/Core", LONG_DEPS)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(report.findings.is_empty(), "{:?}", report.findings);
+}
@@ -0,0 +1,93 @@
+use std::fs;
+
+use proptest::prelude::*;
+use umbra_build_fixer::checks::lists::{check_sorted_list_attribute, fix_sorted_list_attribute};
+use umbra_build_fixer::{analyze_build_file, BuildIssue, Config};
+
+use crate::common::workspace;
+
+const UNSORTED: &str = r#"swift_library(
+    name = "Core",
+    srcs = ["b.swift", "a.swift"],
+    deps = [
+        "//Sources/Logging",
+        ":CoreTypes",
+        "//Sources/errors",  # keep
+    ],
+)
+
+swift_library(
+    name = "CoreTypes",
+    deps = [
+        "//Sources/Zeta",
+        "//Sources/alpha",
+    ],
+)
+"#;
+
+#[test]
+fn unsorted_deps_are_flagged() {
+    let (issue, message) = check_sorted_list_attribute("deps", UNSORTED).unwrap();
+    assert_eq!(
+        issue,
+        BuildIssue::UnsortedDeps {
+            attribute: "deps".to_string()
+        }
+    );
+    // The first deps list has a comment and is left alone.
+    assert!(message.starts_with("1 deps"), "{}", message);
+}
+
+#[test]
+fn fix_sorts_case_insensitively_and_keeps_layout() {
+    let fixed = fix_sorted_list_attribute("deps", UNSORTED);
+    assert!(fixed.contains(
+        "    deps = [\n        \"//Sources/alpha\",\n        \"//Sources/Zeta\",\n    ],"
+    ));
+    // Unrelated lists are untouched.
+    assert!(fixed.contains(r#"srcs = ["b.swift", "a.swift"],"#));
+    assert!(check_sorted_list_attribute("deps", &fixed).is_none());
+}
+
+#[test]
+fn srcs_sorting_is_enabled_through_config() {
+    let content = r#"swift_library(name = "Core", srcs = ["b.swift", "a.swift"])"#;
+    let dir = workspace(&[]);
+    fs::write(
+        dir.path().join("umbra-fix.toml"),
+        "sorted_list_attributes = [\"deps\", \"srcs\"]\n",
+    )
+    .unwrap();
+
+    let default_issues = analyze_build_file(content, &Config::default());
+    assert!(!default_issues
+        .iter()
+        .any(|f| matches!(f.issue, BuildIssue::UnsortedDeps { .. })));
+
+    let config = Config::load(dir.path()).unwrap();
+    let issues = analyze_build_file(content, &config);
+    assert!(issues.iter().any(|f| f.issue
+        == BuildIssue::UnsortedDeps {
+            attribute: "srcs".to_string()
+        }));
+}
+
+proptest! {
+    #[test]
+    fn sorting_deps_is_idempotent(
+        labels in prop::collection::vec("(//|:)[A-Za-z][A-Za-z0-9_/]{0,12}", 0..12),
+        trailing_comma in any::<bool>(),
+    ) {
+        let mut content = String::from("swift_library(\n    name = \"Core\",\n    deps = [\n");
+        for (i, label) in labels.iter().enumerate() {
+            let comma = if i + 1 < labels.len() || trailing_comma { "," } else { "" };
+            content.push_str(&format!("        \"{}\"{}\n", label, comma));
+        }
+        content.push_str("    ],\n)\n");
+
+        let fixed = fix_sorted_list_attribute("deps", &content);
+        prop_assert!(check_sorted_list_attribute("deps", &fixed).is_none());
+        prop_assert_eq!(fix_sorted_list_attribute("deps", &fixed), fixed.clone());
+        prop_assert_eq!(fixed.lines().count(), content.lines().count());
+    }
+}
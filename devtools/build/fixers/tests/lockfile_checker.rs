@@ -0,0 +1,218 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Output};
+use std::time::Duration;
+
+use umbra_build_fixer::lockfile_checker::{
+    check_lockfile, update_lockfile, LockStatus, MODULE_FILE_NAME,
+};
+
+use crate::common::{fake_bazel, umbra_fix, workspace};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+const STALE_STDERR: &str = "\
+ERROR: MODULE.bazel.lock is no longer up-to-date because: the root module file has changed. Please run `bazel mod deps --lockfile_mode=update` to update your lockfile.
+";
+
+// A bazel that fails `--lockfile_mode=error` until `--lockfile_mode=update`
+// has run, recording every invocation in calls.txt
+const STATEFUL_BAZEL: &str = r#"echo "$@" >> calls.txt
+case "$*" in
+  *lockfile_mode=update*) touch updated ;;
+  *lockfile_mode=error*)
+    if [ ! -f updated ]; then
+      echo "ERROR: MODULE.bazel.lock is no longer up-to-date" >&2
+      exit 48
+    fi ;;
+esac
+"#;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn module_workspace() -> tempfile::TempDir {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    fs::write(
+        dir.path().join(MODULE_FILE_NAME),
+        "module(name = \"umbra\")\n",
+    )
+    .unwrap();
+    dir
+}
+
+#[cfg(unix)]
+#[test]
+fn up_to_date_lock_file() {
+    let dir = module_workspace();
+    let bazel = fake_bazel(dir.path(), "echo \"$@\" > args.txt\n");
+
+    let status = check_lockfile(&bazel, dir.path(), TIMEOUT).unwrap();
+
+    assert_eq!(status, LockStatus::UpToDate);
+    let args = fs::read_to_string(dir.path().join("args.txt")).unwrap();
+    assert_eq!(args.trim(), "mod deps --color=no --lockfile_mode=error");
+}
+
+#[cfg(unix)]
+#[test]
+fn stale_lock_file_reports_bazel_errors() {
+    let dir = module_workspace();
+    let bazel = fake_bazel(
+        dir.path(),
+        &format!("cat >&2 <<'EOF'\n{}EOF\nexit 48\n", STALE_STDERR),
+    );
+
+    let status = check_lockfile(&bazel, dir.path(), TIMEOUT).unwrap();
+
+    let LockStatus::Stale { errors } = status else {
+        panic!("expected a stale lock file, got {:?}", status);
+    };
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0]
+        .message
+        .starts_with("MODULE.bazel.lock is no longer up-to-date"));
+}
+
+#[cfg(unix)]
+#[test]
+fn other_bazel_failures_are_errors() {
+    let dir = module_workspace();
+    let bazel = fake_bazel(
+        dir.path(),
+        "echo 'ERROR: error loading package: BUILD file not found' >&2\nexit 1\n",
+    );
+
+    let err = check_lockfile(&bazel, dir.path(), TIMEOUT).unwrap_err();
+
+    assert!(err.to_string().contains("BUILD file not found"), "{}", err);
+}
+
+#[cfg(unix)]
+#[test]
+fn failures_mentioning_locks_are_errors() {
+    let dir = module_workspace();
+    let bazel = fake_bazel(
+        dir.path(),
+        "echo 'Another command is running. Waiting for the output base lock' >&2\n\
+         echo 'ERROR: could not read block device' >&2\nexit 1\n",
+    );
+
+    let err = check_lockfile(&bazel, dir.path(), TIMEOUT).unwrap_err();
+
+    assert!(err.to_string().contains("block device"), "{}", err);
+}
+
+#[cfg(unix)]
+#[test]
+fn workspace_without_module_is_not_checked() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let bazel = fake_bazel(dir.path(), "touch ran\nexit 1\n");
+
+    let status = check_lockfile(&bazel, dir.path(), TIMEOUT).unwrap();
+
+    assert_eq!(status, LockStatus::NoModule);
+    assert!(!dir.path().join("ran").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn update_runs_bazel_in_update_mode() {
+    let dir = module_workspace();
+    let bazel = fake_bazel(dir.path(), STATEFUL_BAZEL);
+
+    update_lockfile(&bazel, dir.path(), TIMEOUT).unwrap();
+
+    let calls = fs::read_to_string(dir.path().join("calls.txt")).unwrap();
+    assert_eq!(calls.trim(), "mod deps --color=no --lockfile_mode=update");
+}
+
+#[cfg(unix)]
+#[test]
+fn slow_bazel_times_out() {
+    let dir = module_workspace();
+    let bazel = fake_bazel(dir.path(), "sleep 5\n");
+
+    let err = check_lockfile(&bazel, dir.path(), Duration::from_millis(200)).unwrap_err();
+
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert!(
+        err.to_string().starts_with("bazel mod timed out"),
+        "{}",
+        err
+    );
+}
+
+#[cfg(unix)]
+fn check_lock(dir: &Path, bazel: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_umbra-fix"))
+        .arg("check-lock")
+        .args(args)
+        .args(["--root", dir.to_str().unwrap()])
+        .env("UMBRA_FIX_BAZEL", bazel)
+        .output()
+        .unwrap()
+}
+
+#[cfg(unix)]
+#[test]
+fn check_lock_fails_on_stale_lock_file() {
+    let dir = module_workspace();
+    let bazel = fake_bazel(dir.path(), STATEFUL_BAZEL);
+
+    let output = check_lock(dir.path(), &bazel, &[]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--update-lock"));
+    let calls = fs::read_to_string(dir.path().join("calls.txt")).unwrap();
+    assert!(!calls.contains("lockfile_mode=update"));
+}
+
+#[cfg(unix)]
+#[test]
+fn check_lock_updates_stale_lock_file() {
+    let dir = module_workspace();
+    let bazel = fake_bazel(dir.path(), STATEFUL_BAZEL);
+
+    let output = check_lock(dir.path(), &bazel, &["--update-lock"]);
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Updated MODULE.bazel.lock"));
+    let output = check_lock(dir.path(), &bazel, &[]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("MODULE.bazel.lock is up to date"));
+}
+
+#[cfg(unix)]
+#[test]
+fn bazel_validate_fails_the_run_on_stale_lock_file() {
+    let dir = module_workspace();
+    let bin = tempfile::tempdir().unwrap();
+    let bazel = fake_bazel(bin.path(), STATEFUL_BAZEL);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_umbra-fix"))
+        .args(["--bazel-validate", "--root", dir.path().to_str().unwrap()])
+        .env("UMBRA_FIX_BAZEL", &bazel)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("bazel query //... succeeded"), "{}", stdout);
+    assert!(
+        stdout.contains("bazel: MODULE.bazel.lock is no longer up-to-date"),
+        "{}",
+        stdout
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("MODULE.bazel.lock is out of date"));
+}
+
+#[test]
+fn check_lock_without_module_succeeds() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+
+    let output = umbra_fix(&["check-lock", "--root", dir.path().to_str().unwrap()]);
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("nothing to check"));
+}
@@ -0,0 +1,153 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use serde_json::{json, Value};
+use umbra_build_fixer::lsp;
+
+use crate::common::{test_config, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+fn frame(messages: &[Value]) -> Vec<u8> {
+    let mut input = Vec::new();
+    for message in messages {
+        let body = message.to_string();
+        write!(input, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+    }
+    input
+}
+
+fn unframe(output: &[u8]) -> Vec<Value> {
+    let mut messages = Vec::new();
+    let mut rest = std::str::from_utf8(output).unwrap();
+    while let Some((header, tail)) = rest.split_once("\r\n\r\n") {
+        let length: usize = header
+            .strip_prefix("Content-Length: ")
+            .unwrap()
+            .parse()
+            .unwrap();
+        messages.push(serde_json::from_str(&tail[..length]).unwrap());
+        rest = &tail[length..];
+    }
+    messages
+}
+
+fn did_open(uri: &str, text: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {
+            "textDocument": { "uri": uri, "languageId": "starlark", "version": 1, "text": text },
+        },
+    })
+}
+
+fn diagnostics(messages: &[Value]) -> Vec<Value> {
+    messages
+        .iter()
+        .filter(|m| m["method"] == "textDocument/publishDiagnostics")
+        .flat_map(|m| m["params"]["diagnostics"].as_array().unwrap().clone())
+        .collect()
+}
+
+#[test]
+fn opening_a_document_publishes_diagnostics() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let uri = format!(
+        "file://{}/Sources/Core/BUILD.bazel",
+        dir.path().to_str().unwrap()
+    );
+    let mut child = Command::new(env!("CARGO_BIN_EXE_umbra-fix"))
+        .args(["--lsp", "--root", dir.path().to_str().unwrap()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let input = frame(&[
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "capabilities": {} } }),
+        json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        did_open(&uri, DIRTY),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ]);
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    let messages = unframe(&output.stdout);
+    assert_eq!(messages[0]["id"], 1);
+    assert_eq!(
+        messages[0]["result"]["capabilities"]["codeActionProvider"],
+        true
+    );
+    let diagnostics = diagnostics(&messages);
+    let custom_rule = diagnostics
+        .iter()
+        .find(|d| d["code"] == "CustomLibraryRule")
+        .expect("no CustomLibraryRule diagnostic");
+    assert_eq!(custom_rule["source"], "umbra-fix");
+    assert!(messages.iter().any(|m| m["id"] == 2));
+}
+
+#[test]
+fn code_action_replaces_the_document_with_the_fix() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let config = test_config(dir.path());
+    let uri = "untitled:BUILD.bazel";
+
+    let mut opened = Vec::new();
+    lsp::serve(&config, &frame(&[did_open(uri, DIRTY)])[..], &mut opened).unwrap();
+    let diagnostic = diagnostics(&unframe(&opened))
+        .into_iter()
+        .find(|d| d["code"] == "CustomLibraryRule")
+        .unwrap();
+
+    let mut output = Vec::new();
+    let input = frame(&[
+        did_open(uri, DIRTY),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "textDocument/codeAction",
+            "params": {
+                "textDocument": { "uri": uri },
+                "range": diagnostic["range"],
+                "context": { "diagnostics": [diagnostic] },
+            },
+        }),
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "unknown/method" }),
+    ]);
+    lsp::serve(&config, &input[..], &mut output).unwrap();
+
+    let messages = unframe(&output);
+    let actions = &messages.iter().find(|m| m["id"] == 1).unwrap()["result"];
+    assert_eq!(actions.as_array().unwrap().len(), 1);
+    assert_eq!(actions[0]["kind"], "quickfix");
+    let new_text = actions[0]["edit"]["changes"][uri][0]["newText"]
+        .as_str()
+        .unwrap();
+    assert!(new_text.contains("\nswift_library("), "{}", new_text);
+    let unknown = messages.iter().find(|m| m["id"] == 2).unwrap();
+    assert_eq!(unknown["error"]["code"], -32601);
+}
+
+#[test]
+fn malformed_message_gets_a_parse_error_and_serving_continues() {
+    let dir = workspace(&[]);
+    let config = test_config(dir.path());
+
+    let mut input = b"Content-Length: 9\r\n\r\n{not json".to_vec();
+    input.extend(frame(&[
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "shutdown" }),
+    ]));
+    let mut output = Vec::new();
+    lsp::serve(&config, &input[..], &mut output).unwrap();
+
+    let messages = unframe(&output);
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0]["id"], Value::Null);
+    assert_eq!(messages[0]["error"]["code"], -32700);
+    assert_eq!(messages[1]["id"], 1);
+    assert_eq!(messages[1]["result"], Value::Null);
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use umbra_build_fixer::checks::apply_fixes;
+use umbra_build_fixer::checks::loads::{check_macro_loads, MacroRegistry};
+use umbra_build_fixer::config::MACRO_REGISTRY_FILE_NAME;
+use umbra_build_fixer::{fix_build_file, BuildIssue, Config, Finding};
+
+use crate::common::{test_config, workspace};
+
+const UMBRA_BZL: &str = "//bazel:umbra.bzl";
+
+const UNLOADED_MACROS: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+umbra_swift_module(
+    name = "Core",
+    srcs = glob(["*.swift"], allow_empty = True),
+)
+
+umbra_swift_tests(
+    name = "CoreTests",
+    srcs = glob(["Tests/*.swift"], allow_empty = True),
+    deps = [":Core"],
+)
+
+umbra_swift_module(
+    name = "CoreExtras",
+    srcs = glob(["Extras/*.swift"], allow_empty = True),
+)
+
+umbra_docs(name = "Docs")
+"#;
+
+const REGISTRY: &str = r#"umbra_swift_module = "//bazel:umbra.bzl"
+umbra_swift_tests = "//bazel:umbra.bzl"
+umbra_docs = "//bazel:docs.bzl"
+"#;
+
+fn registry() -> MacroRegistry {
+    MacroRegistry::new(toml::from_str::<BTreeMap<String, String>>(REGISTRY).unwrap())
+}
+
+fn missing_load(symbol: &str, bzl: &str) -> BuildIssue {
+    BuildIssue::MacroCallWithoutLoad {
+        symbol: symbol.to_string(),
+        bzl: bzl.to_string(),
+    }
+}
+
+#[test]
+fn each_unloaded_macro_is_flagged_once() {
+    let issues = check_macro_loads(UNLOADED_MACROS, &registry());
+
+    let flagged: Vec<&BuildIssue> = issues.iter().map(|(issue, _)| issue).collect();
+    assert_eq!(
+        flagged,
+        [
+            &missing_load("umbra_swift_module", UMBRA_BZL),
+            &missing_load("umbra_swift_tests", UMBRA_BZL),
+            &missing_load("umbra_docs", "//bazel:docs.bzl"),
+        ]
+    );
+    assert!(issues[0].1.contains("line 3"), "{}", issues[0].1);
+}
+
+#[test]
+fn loaded_and_unregistered_macros_are_not_flagged() {
+    let content = format!(
+        "load({:?}, \"umbra_swift_module\", tests = \"umbra_swift_tests\")\n{}",
+        UMBRA_BZL,
+        UNLOADED_MACROS.replace("umbra_swift_tests(", "tests(")
+    );
+
+    let issues = check_macro_loads(&content, &registry());
+
+    let flagged: Vec<&BuildIssue> = issues.iter().map(|(issue, _)| issue).collect();
+    assert_eq!(flagged, [&missing_load("umbra_docs", "//bazel:docs.bzl")]);
+    assert!(check_macro_loads(UNLOADED_MACROS, &MacroRegistry::default()).is_empty());
+}
+
+#[test]
+fn macros_from_the_same_bzl_share_one_load() {
+    let findings: Vec<Finding> = check_macro_loads(UNLOADED_MACROS, &registry())
+        .into_iter()
+        .map(Finding::from)
+        .collect();
+
+    let fixed = apply_fixes(UNLOADED_MACROS, &findings);
+
+    assert!(fixed.starts_with(
+        "load(\"@build_bazel_rules_swift//swift:swift.bzl\", \"swift_library\")\n\
+         load(\"//bazel:umbra.bzl\", \"umbra_swift_module\", \"umbra_swift_tests\")\n\
+         load(\"//bazel:docs.bzl\", \"umbra_docs\")\n\n"
+    ));
+    assert!(check_macro_loads(&fixed, &registry()).is_empty());
+    assert_eq!(apply_fixes(&fixed, &findings), fixed);
+}
+
+#[test]
+fn macro_is_added_to_an_existing_load_of_its_bzl() {
+    let content = UNLOADED_MACROS.replace(
+        "\"swift_library\")\n",
+        "\"swift_library\")\nload(\"//bazel:umbra.bzl\", \"umbra_swift_module\")\n",
+    );
+    let findings: Vec<Finding> = check_macro_loads(&content, &registry())
+        .into_iter()
+        .map(Finding::from)
+        .collect();
+
+    let fixed = apply_fixes(&content, &findings);
+
+    assert!(fixed
+        .contains("load(\"//bazel:umbra.bzl\", \"umbra_swift_module\", \"umbra_swift_tests\")\n"));
+    assert_eq!(fixed.matches("//bazel:umbra.bzl").count(), 1, "{}", fixed);
+}
+
+#[test]
+fn registered_macros_are_not_also_undeclared_loads() {
+    let dir = workspace(&[("Sources/Core", UNLOADED_MACROS)]);
+    fs::write(dir.path().join(MACRO_REGISTRY_FILE_NAME), REGISTRY).unwrap();
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(report
+        .findings
+        .iter()
+        .any(|finding| finding.issue == missing_load("umbra_docs", "//bazel:docs.bzl")));
+    assert!(!report
+        .findings
+        .iter()
+        .any(|finding| matches!(finding.issue, BuildIssue::UndeclaredLoad { .. })));
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(
+        fixed.contains("load(\"//bazel:docs.bzl\", \"umbra_docs\")"),
+        "{}",
+        fixed
+    );
+}
+
+#[test]
+fn registry_entry_that_isnt_a_bzl_label_fails_to_load() {
+    let dir = workspace(&[]);
+    fs::write(
+        dir.path().join(MACRO_REGISTRY_FILE_NAME),
+        "umbra_docs = \"bazel/docs.bzl\"\n",
+    )
+    .unwrap();
+
+    let err = Config::load(dir.path()).unwrap_err();
+
+    assert!(err.to_string().contains("umbra_docs"), "{}", err);
+}
@@ -0,0 +1,95 @@
+//! Integration tests for the `umbra-fix` library and binary.
+
+mod common;
+
+mod assert_no_issues;
+mod ast;
+mod atomic_write;
+mod attributes;
+mod baseline;
+mod bazel_query;
+mod build_cleaner;
+mod build_validator;
+mod bundle_id;
+mod cache;
+mod check_mode;
+mod commented_out_rule;
+mod conflicting_module_names;
+mod convert_spm;
+mod dead_strip;
+mod deprecated_attributes;
+mod diff_only;
+mod discovery;
+mod dual_build_system;
+mod emit_targets;
+mod empty_deps;
+mod empty_srcs;
+mod exports_attribute;
+mod external_labels;
+mod feature_flags;
+mod format;
+mod formatting;
+mod generate;
+mod generated_sources;
+mod github_actions;
+mod glob_evaluator;
+mod hardcoded_path;
+mod hook;
+mod html_report;
+mod idempotency;
+mod junit;
+mod label;
+mod label_resolver;
+mod license;
+mod line_length;
+mod lists;
+mod lockfile_checker;
+mod lsp;
+mod macro_loads;
+mod metrics;
+mod minimum_os_version;
+mod mixed_sources;
+mod module_mapper;
+mod module_names;
+mod naming_convention;
+mod nonhermetic_glob;
+mod objc_interop;
+mod orphaned_sources;
+mod package;
+mod parallel_io;
+mod patch;
+#[cfg(feature = "profile")]
+mod profile;
+mod progress_bar;
+mod progress_state;
+mod prune_deps;
+mod quote_style;
+mod redundant_allow_empty;
+mod repeated_deps;
+mod report_file;
+mod resource_bundler;
+mod resources;
+mod rule_filter;
+mod rule_migrations;
+mod rules_swift_upgrade;
+mod schema;
+mod select_in_glob;
+mod since_tag;
+mod sort_attributes;
+mod suppress;
+mod swift_imports;
+mod swift_library_rule;
+mod swift_version;
+mod target_graph;
+mod target_names;
+mod test_files_in_library;
+mod umbra_macros;
+mod undeclared_load;
+mod undo;
+mod update_deps;
+mod verbose_diffs;
+mod version_catalog;
+mod visibility;
+mod wildcard_glob;
+mod workspace;
+mod workspace_root;
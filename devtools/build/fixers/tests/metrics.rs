@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use umbra_build_fixer::metrics::{self, PhaseTimer};
+
+use crate::common::{umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+fn phase_lines(stdout: &str) -> Vec<&str> {
+    stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|word| ["discover", "analyze", "fix", "verify", "total"].contains(word))
+        .collect()
+}
+
+#[test]
+fn metrics_flag_reports_every_phase() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--metrics", "--verify-idempotent", "--root", root]);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        phase_lines(&stdout),
+        ["discover", "analyze", "fix", "verify", "total"],
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn metrics_are_only_printed_when_asked() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--dry-run", "--root", root]);
+
+    assert!(phase_lines(&String::from_utf8_lossy(&output.stdout)).is_empty());
+}
+
+#[test]
+fn thread_timings_are_merged() {
+    std::thread::spawn(|| {
+        let _timer = PhaseTimer::start("test-worker-phase");
+        std::thread::sleep(Duration::from_millis(5));
+        drop(_timer);
+        metrics::flush_thread();
+    })
+    .join()
+    .unwrap();
+
+    let snapshot = metrics::snapshot();
+    let (_, elapsed) = snapshot
+        .phases
+        .iter()
+        .find(|(phase, _)| *phase == "test-worker-phase")
+        .expect("worker phase was not merged");
+    assert!(*elapsed >= Duration::from_millis(5));
+}
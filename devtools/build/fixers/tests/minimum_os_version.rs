@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use umbra_build_fixer::checks::apple::{check_minimum_os_version, fix_minimum_os_version};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+fn bundle(rule: &str) -> String {
+    format!(
+        "{}(\n    name = \"App\",\n    bundle_id = \"com.umbra.app\",\n)\n",
+        rule
+    )
+}
+
+#[test]
+fn each_platform_gets_its_default_version() {
+    let cases = [
+        ("ios_application", "14.0"),
+        ("macos_application", "11.0"),
+        ("tvos_application", "14.0"),
+        ("watchos_application", "7.0"),
+        ("watchos_extension", "7.0"),
+    ];
+
+    for (rule, version) in cases {
+        let content = bundle(rule);
+        let issues = check_minimum_os_version(&content, &BTreeMap::new());
+
+        let issue = BuildIssue::MissingMinimumOsVersion {
+            target: "App".to_string(),
+            version: version.to_string(),
+        };
+        assert_eq!(issues.len(), 1, "{}", rule);
+        assert_eq!(issues[0].0, issue, "{}", rule);
+        assert_eq!(
+            fix_minimum_os_version(&content, "App", version),
+            format!(
+                "{}(\n    name = \"App\",\n    minimum_os_version = \"{}\",\n    bundle_id = \"com.umbra.app\",\n)\n",
+                rule, version
+            )
+        );
+    }
+}
+
+#[test]
+fn existing_minimum_os_version_is_kept() {
+    let content = "ios_application(\n    name = \"App\",\n    minimum_os_version = \"16.0\",\n)\n";
+
+    assert!(check_minimum_os_version(content, &BTreeMap::new()).is_empty());
+}
+
+#[test]
+fn other_rules_are_ignored() {
+    let content = "swift_library(\n    name = \"App\",\n    srcs = [\"App.swift\"],\n)\n";
+
+    assert!(check_minimum_os_version(content, &BTreeMap::new()).is_empty());
+}
+
+#[test]
+fn version_is_read_from_config_file() {
+    let content = format!(
+        "{}\n{}",
+        bundle("ios_application"),
+        bundle("macos_application").replace("App", "MacApp")
+    );
+    let dir = workspace(&[("Apps/Umbra", &content)]);
+    fs::write(
+        dir.path().join("umbra-fix.toml"),
+        // Bundling rules are outside the default swift_* rules
+        "rule_filter = []\n\n[minimum_os_versions]\nios = \"16.0\"\n",
+    )
+    .unwrap();
+    let path = dir.path().join("Apps/Umbra/BUILD.bazel");
+
+    fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(
+        fixed.contains("name = \"App\",\n    minimum_os_version = \"16.0\",\n"),
+        "{}",
+        fixed
+    );
+    assert!(
+        fixed.contains("name = \"MacApp\",\n    minimum_os_version = \"11.0\",\n"),
+        "{}",
+        fixed
+    );
+}
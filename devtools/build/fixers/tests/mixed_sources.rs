@@ -0,0 +1,115 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use umbra_build_fixer::checks::globs::{check_mixed_source_languages, fix_mixed_source_languages};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const MIXED_SOURCES: &str = include_str!("fixtures/mixed_sources.BUILD");
+
+// Swift sources next to an Objective-C class under Sources/
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("devtools/build/fixers/tests/fixtures/mixed_sources")
+}
+
+fn mixed_issue(pattern: &str) -> BuildIssue {
+    BuildIssue::MixedSourceLanguages {
+        target: "Core".to_string(),
+        pattern: pattern.to_string(),
+        files: vec![
+            "Sources/Internal/UMBBridge.h".to_string(),
+            "Sources/Internal/UMBBridge.m".to_string(),
+        ],
+    }
+}
+
+#[test]
+fn glob_without_extension_matching_objc_files_is_flagged() {
+    let issues: Vec<_> = check_mixed_source_languages(MIXED_SOURCES, &fixture_dir())
+        .into_iter()
+        .map(|(issue, _)| issue)
+        .collect();
+
+    assert_eq!(issues, [mixed_issue("Sources/**/*")]);
+}
+
+#[test]
+fn glob_is_narrowed_to_swift_files() {
+    let dir = workspace(&[("Sources/Core", MIXED_SOURCES)]);
+    let package = dir.path().join("Sources/Core");
+    for file in [
+        "Sources/Core.swift",
+        "Sources/Internal/Helper.swift",
+        "Sources/Internal/UMBBridge.h",
+        "Sources/Internal/UMBBridge.m",
+    ] {
+        let target = package.join(file);
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        fs::copy(fixture_dir().join(file), target).unwrap();
+    }
+    let path = package.join("BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert_eq!(issues, [&mixed_issue("Sources/**/*")]);
+    let content = fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        content,
+        MIXED_SOURCES.replace("\"Sources/**/*\"", "\"Sources/**/*.swift\"")
+    );
+}
+
+#[test]
+fn trailing_recursive_wildcard_is_narrowed() {
+    let content = "swift_library(\n    name = \"Core\",\n    srcs = glob([\"Sources/**\"]),\n)\n";
+
+    let issues: Vec<_> = check_mixed_source_languages(content, &fixture_dir())
+        .into_iter()
+        .map(|(issue, _)| issue)
+        .collect();
+
+    assert_eq!(issues, [mixed_issue("Sources/**")]);
+    assert_eq!(
+        fix_mixed_source_languages(content, "Core", "Sources/**"),
+        content.replace("\"Sources/**\"", "\"Sources/**/*.swift\"")
+    );
+}
+
+#[test]
+fn excluded_objc_files_are_not_flagged() {
+    let content = "swift_library(\n    name = \"Core\",\n    srcs = glob([\"Sources/**/*\"], exclude = [\"**/*.m\", \"**/*.h\"]),\n)\n";
+
+    assert!(check_mixed_source_languages(content, &fixture_dir()).is_empty());
+}
+
+#[test]
+fn patterns_with_an_extension_are_not_flagged() {
+    let content = "swift_library(\n    name = \"Core\",\n    srcs = glob([\"Sources/**/*.swift\", \"Sources/**/*.m\"]),\n)\n";
+
+    assert!(check_mixed_source_languages(content, &fixture_dir()).is_empty());
+}
+
+#[test]
+fn globs_of_other_rules_are_not_flagged() {
+    let content = "objc_library(\n    name = \"Bridge\",\n    srcs = glob([\"Sources/**/*\"]),\n)\n\nfilegroup(\n    name = \"all\",\n    srcs = glob([\"Sources/**/*\"]),\n)\n";
+
+    assert!(check_mixed_source_languages(content, &fixture_dir()).is_empty());
+}
+
+#[test]
+fn swift_test_globs_are_checked() {
+    let content = "swift_test(\n    name = \"Core\",\n    srcs = glob([\"Sources/*\", \"Sources/Internal/*\"]),\n)\n";
+
+    let issues: Vec<_> = check_mixed_source_languages(content, &fixture_dir())
+        .into_iter()
+        .map(|(issue, _)| issue)
+        .collect();
+
+    assert_eq!(issues, [mixed_issue("Sources/Internal/*")]);
+    assert_eq!(
+        fix_mixed_source_languages(content, "Core", "Sources/Internal/*"),
+        content.replace("\"Sources/Internal/*\"", "\"Sources/Internal/*.swift\"")
+    );
+}
@@ -0,0 +1,173 @@
+use std::fs;
+
+use umbra_build_fixer::module_mapper::{format_import_map, generate_import_map, ImportMap};
+use umbra_build_fixer::target_graph::build_target_graph;
+use umbra_build_fixer::{find_build_files, Config};
+
+use crate::common::{umbra_fix, workspace};
+
+const CORE: &str = include_str!("fixtures/module_mapper/core.BUILD");
+const UTILS: &str = include_str!("fixtures/module_mapper/utils.BUILD");
+const SEED: &str = include_str!("fixtures/module_mapper/seed.toml");
+
+fn two_packages() -> tempfile::TempDir {
+    workspace(&[("Sources/Core", CORE), ("Sources/Utils", UTILS)])
+}
+
+fn entries(import_map: &ImportMap) -> Vec<(&str, &str)> {
+    import_map
+        .iter()
+        .map(|(label, module)| (label.as_str(), module.as_str()))
+        .collect()
+}
+
+#[test]
+fn swift_libraries_map_to_their_module_names() {
+    let dir = two_packages();
+    let build_files = find_build_files(&Config::new(dir.path())).unwrap();
+    let (graph, _) = build_target_graph(dir.path(), &build_files);
+
+    let import_map = generate_import_map(&graph, &ImportMap::new());
+
+    // Tests and filegroups build no module to import
+    assert_eq!(
+        entries(&import_map),
+        [
+            ("//Sources/Core:Core", "UmbraCore"),
+            ("//Sources/Utils:Utils", "Utils"),
+        ]
+    );
+}
+
+#[test]
+fn seed_entries_are_kept_unless_generated() {
+    let dir = two_packages();
+    let build_files = find_build_files(&Config::new(dir.path())).unwrap();
+    let (graph, _) = build_target_graph(dir.path(), &build_files);
+    let seed: ImportMap = toml::from_str(SEED).unwrap();
+
+    let import_map = generate_import_map(&graph, &seed);
+
+    assert_eq!(
+        entries(&import_map),
+        [
+            ("//Sources/Core:Core", "UmbraCore"),
+            ("//Sources/Utils:Utils", "Utils"),
+            ("@swift_log//:Logging", "Logging"),
+        ]
+    );
+}
+
+#[test]
+fn map_is_sorted_by_module_name() {
+    let import_map: ImportMap = [("//Sources/A:A", "Zebra"), ("//Sources/B:B", "Alpha")]
+        .into_iter()
+        .map(|(label, module)| (label.to_string(), module.to_string()))
+        .collect();
+
+    let content = format_import_map(&import_map);
+
+    let lines: Vec<&str> = content.lines().skip(1).collect();
+    assert_eq!(
+        lines,
+        [
+            "\"//Sources/B:B\" = \"Alpha\"",
+            "\"//Sources/A:A\" = \"Zebra\""
+        ]
+    );
+}
+
+#[test]
+fn gen_import_map_writes_a_map_the_fixer_reads() {
+    let dir = two_packages();
+    let root = dir.path().to_str().unwrap();
+    let seed = dir.path().join("seed.toml");
+    fs::write(&seed, SEED).unwrap();
+
+    let output = umbra_fix(&[
+        "gen-import-map",
+        "--seed-map",
+        seed.to_str().unwrap(),
+        "--root",
+        root,
+    ]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Wrote 3 modules to"), "{}", stdout);
+    let config = Config::load(dir.path()).unwrap();
+    assert_eq!(
+        config
+            .import_map
+            .get("@swift_log//:Logging")
+            .map(String::as_str),
+        Some("Logging")
+    );
+    assert_eq!(
+        config
+            .import_map
+            .get("//Sources/Core:Core")
+            .map(String::as_str),
+        Some("UmbraCore")
+    );
+}
+
+#[test]
+fn output_names_another_file() {
+    let dir = two_packages();
+    let root = dir.path().to_str().unwrap();
+    let path = dir.path().join("maps/imports.toml");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+    let output = umbra_fix(&[
+        "gen-import-map",
+        "--output",
+        path.to_str().unwrap(),
+        "--root",
+        root,
+    ]);
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(fs::read_to_string(&path)
+        .unwrap()
+        .contains("\"//Sources/Utils:Utils\" = \"Utils\""));
+    assert!(!dir.path().join("import_map.toml").exists());
+}
+
+#[test]
+fn formatted_map_reads_back_unchanged() {
+    let import_map: ImportMap = [
+        ("//Sources/Café:Café", "Café"),
+        ("@repo//quote:\"q\"", "Back\\slash"),
+    ]
+    .into_iter()
+    .map(|(label, module)| (label.to_string(), module.to_string()))
+    .collect();
+
+    let content = format_import_map(&import_map);
+
+    assert!(
+        content.contains("\"//Sources/Café:Café\" = \"Café\"\n"),
+        "{}",
+        content
+    );
+    assert_eq!(toml::from_str::<ImportMap>(&content).unwrap(), import_map);
+}
+
+#[test]
+fn gen_import_map_skips_excluded_packages() {
+    let dir = two_packages();
+    let root = dir.path().to_str().unwrap();
+    fs::write(
+        dir.path().join("umbra-fix.toml"),
+        "exclude_patterns = [\"Sources/Utils/**\"]\n",
+    )
+    .unwrap();
+
+    let output = umbra_fix(&["gen-import-map", "--root", root]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let import_map = fs::read_to_string(dir.path().join("import_map.toml")).unwrap();
+    assert!(import_map.contains("//Sources/Core:Core"), "{}", import_map);
+    assert!(!import_map.contains("//Sources/Utils"), "{}", import_map);
+}
@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use umbra_build_fixer::checks::module_names::check_module_names;
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const CORE_IMPL: &str = include_str!("fixtures/core_impl.BUILD");
+
+fn module_map() -> BTreeMap<String, String> {
+    BTreeMap::from([
+        ("CoreImpl".to_string(), "Core".to_string()),
+        ("CoreTypes".to_string(), "CoreTypes".to_string()),
+    ])
+}
+
+#[test]
+fn target_with_different_module_name_is_flagged() {
+    let issues = check_module_names(CORE_IMPL, &module_map());
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(
+        issues[0].0,
+        BuildIssue::MissingModuleName {
+            target: "CoreImpl".to_string(),
+            module_name: "Core".to_string(),
+        }
+    );
+}
+
+#[test]
+fn explicit_module_name_is_not_flagged() {
+    let content = CORE_IMPL.replace(
+        "name = \"CoreImpl\",",
+        "name = \"CoreImpl\",\n    module_name = \"Core\",",
+    );
+    assert!(check_module_names(&content, &module_map()).is_empty());
+}
+
+#[test]
+fn fix_inserts_module_name_after_name() {
+    let dir = workspace(&[("Sources/Core", CORE_IMPL)]);
+    fs::write(
+        dir.path().join("module_name_map.toml"),
+        "CoreImpl = \"Core\"\n",
+    )
+    .unwrap();
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let config = test_config(dir.path());
+    fix_build_file(&path, &config).unwrap();
+
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(fixed.contains(
+        "    name = \"CoreImpl\",  # implementation of the Core module\n    module_name = \"Core\",\n    srcs"
+    ));
+    assert!(check_module_names(&fixed, &config.module_names).is_empty());
+}
+
+#[test]
+fn single_line_rules_get_inline_module_name() {
+    let content = "swift_library(name = \"CoreImpl\", srcs = [])\n";
+    let fixed = umbra_build_fixer::checks::module_names::fix_missing_module_name(
+        content, "CoreImpl", "Core",
+    );
+    assert_eq!(
+        fixed,
+        "swift_library(name = \"CoreImpl\", module_name = \"Core\", srcs = [])\n"
+    );
+}
+
+#[test]
+fn module_names_are_written_as_starlark_strings() {
+    let content = "swift_library(name = \"CaféImpl\", srcs = [])\n";
+    let fixed = umbra_build_fixer::checks::module_names::fix_missing_module_name(
+        content,
+        "CaféImpl",
+        "Café\"s",
+    );
+    assert_eq!(
+        fixed,
+        "swift_library(name = \"CaféImpl\", module_name = \"Café\\\"s\", srcs = [])\n"
+    );
+}
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use umbra_build_fixer::checks::naming_convention::{
+    check_naming_convention, check_naming_conventions, NamingConvention,
+};
+use umbra_build_fixer::config::NAMING_CONVENTION_FILE_NAME;
+use umbra_build_fixer::{fix_build_file, BuildIssue, Config};
+
+use crate::common::{test_config, workspace};
+
+const CONVENTION: &str = r#"swift_library = "[A-Z][A-Za-z0-9]*"
+swift_test = "[A-Z][A-Za-z0-9]*Tests"
+"#;
+
+const MISNAMED: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library", "swift_test")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "myModule",
+    srcs = glob(["*.swift"], allow_empty = True),
+)
+
+swift_test(
+    name = "my_module_tests",
+    srcs = glob(["Tests/*.swift"], allow_empty = True),
+    testonly = True,
+    deps = [":myModule"],
+)
+"#;
+
+fn convention() -> NamingConvention {
+    let patterns: BTreeMap<String, String> = toml::from_str(CONVENTION).unwrap();
+    NamingConvention::new(&patterns).unwrap()
+}
+
+#[test]
+fn camel_case_library_is_flagged() {
+    let (issue, message) =
+        check_naming_convention("swift_library", "myModule", &convention()).unwrap();
+
+    assert_eq!(
+        issue,
+        BuildIssue::NamingConventionViolation {
+            target: "myModule".to_string(),
+            rule_name: "swift_library".to_string(),
+            renamed: Some("MyModule".to_string()),
+        }
+    );
+    assert!(message.contains("[A-Z][A-Za-z0-9]*"), "{}", message);
+}
+
+#[test]
+fn pascal_case_library_is_accepted() {
+    assert!(check_naming_convention("swift_library", "MyModule", &convention()).is_none());
+    assert!(check_naming_convention("swift_test", "MyModuleTests", &convention()).is_none());
+}
+
+#[test]
+fn rules_without_a_pattern_are_not_checked() {
+    assert!(check_naming_convention("objc_library", "my_module", &convention()).is_none());
+}
+
+#[test]
+fn patterns_match_the_whole_name() {
+    // "Module" alone matches the library pattern, but "myModule" doesn't
+    assert!(check_naming_convention("swift_library", "myModule", &convention()).is_some());
+    assert!(check_naming_convention("swift_test", "MyModuleTestsV2", &convention()).is_some());
+}
+
+#[test]
+fn rename_that_still_breaks_the_convention_needs_a_manual_fix() {
+    let (issue, _) = check_naming_convention("swift_test", "my_module", &convention()).unwrap();
+
+    assert!(!issue.is_fixable());
+}
+
+#[test]
+fn rename_onto_a_taken_name_needs_a_manual_fix() {
+    let content = MISNAMED.replace(
+        "swift_test(\n    name = \"my_module_tests\"",
+        "swift_test(\n    name = \"MyModule\"",
+    );
+
+    let issues = check_naming_conventions(&content, &convention());
+
+    assert!(issues.iter().any(|(issue, _)| *issue
+        == BuildIssue::NamingConventionViolation {
+            target: "myModule".to_string(),
+            rule_name: "swift_library".to_string(),
+            renamed: None,
+        }));
+}
+
+#[test]
+fn check_is_opt_in() {
+    let dir = workspace(&[("Sources/MyModule", MISNAMED)]);
+    let path = dir.path().join("Sources/MyModule/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(!report
+        .findings
+        .iter()
+        .any(|finding| matches!(finding.issue, BuildIssue::NamingConventionViolation { .. })));
+}
+
+#[test]
+fn targets_are_renamed_with_their_references() {
+    let dir = workspace(&[("Sources/MyModule", MISNAMED)]);
+    fs::write(dir.path().join(NAMING_CONVENTION_FILE_NAME), CONVENTION).unwrap();
+    let path = dir.path().join("Sources/MyModule/BUILD.bazel");
+
+    fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(fixed.contains("name = \"MyModule\""), "{}", fixed);
+    assert!(fixed.contains("name = \"MyModuleTests\""), "{}", fixed);
+    assert!(fixed.contains("deps = [\":MyModule\"]"), "{}", fixed);
+    assert!(!fixed.contains("myModule"), "{}", fixed);
+}
+
+#[test]
+fn invalid_pattern_fails_to_load() {
+    let dir = workspace(&[]);
+    fs::write(
+        dir.path().join(NAMING_CONVENTION_FILE_NAME),
+        "swift_library = \"[A-Z\"\n",
+    )
+    .unwrap();
+
+    let err = Config::load(dir.path()).unwrap_err();
+
+    assert!(
+        err.to_string().contains(NAMING_CONVENTION_FILE_NAME),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,118 @@
+use std::fs;
+
+use umbra_build_fixer::checks::globs::{
+    check_nonhermetic_glob_patterns, check_unremovable_artifact_patterns,
+    fix_nonhermetic_glob_patterns,
+};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const NONHERMETIC: &str = include_str!("fixtures/nonhermetic_glob.BUILD");
+
+fn patterns() -> Vec<String> {
+    vec!["**/*.o".to_string()]
+}
+
+#[test]
+fn build_artifact_patterns_are_flagged() {
+    let (issue, message) = check_nonhermetic_glob_patterns(NONHERMETIC).unwrap();
+
+    assert_eq!(
+        issue,
+        BuildIssue::NonHermeticGlob {
+            patterns: patterns(),
+            removable: true,
+        }
+    );
+    assert!(message.contains("\"**/*.o\""), "{}", message);
+}
+
+#[test]
+fn prebuilt_binaries_and_sole_patterns_need_a_manual_fix() {
+    let (issue, message) = check_unremovable_artifact_patterns(NONHERMETIC).unwrap();
+
+    // The dylib is vendored, and lib/*.so is all its glob includes
+    assert_eq!(
+        issue,
+        BuildIssue::NonHermeticGlob {
+            patterns: vec!["lib/*.so".to_string(), "Frameworks/*.dylib".to_string()],
+            removable: false,
+        }
+    );
+    assert!(!issue.is_fixable());
+    assert!(
+        message.contains("vendored prebuilt binaries"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn source_patterns_are_not_flagged() {
+    let content = "objc_library(\n    name = \"A\",\n    srcs = glob([\"**/*.m\", \"*.swift\", \"lib/*.asm\"]),\n    hdrs = glob([\"*.h\"], exclude = [\"*.o\"]),\n)\n";
+
+    assert_eq!(check_nonhermetic_glob_patterns(content), None);
+    assert_eq!(check_unremovable_artifact_patterns(content), None);
+}
+
+#[test]
+fn fix_removes_patterns_and_leaves_comments() {
+    let fixed = fix_nonhermetic_glob_patterns(NONHERMETIC, &patterns());
+
+    assert!(
+        fixed.contains(
+            "    # \"**/*.o\" was removed from this glob; it would match build outputs\n    srcs = glob(\n        [\n            \"**/*.m\",\n        ],\n"
+        ),
+        "{}",
+        fixed
+    );
+    assert!(
+        fixed.contains("    data = glob([\"Frameworks/*.dylib\"], allow_empty = True),\n"),
+        "{}",
+        fixed
+    );
+    assert_eq!(check_nonhermetic_glob_patterns(&fixed), None);
+}
+
+#[test]
+fn fix_never_empties_a_glob() {
+    let patterns = vec!["lib/*.so".to_string()];
+
+    assert_eq!(
+        fix_nonhermetic_glob_patterns(NONHERMETIC, &patterns),
+        NONHERMETIC
+    );
+}
+
+#[test]
+fn build_file_is_fixed() {
+    let dir = workspace(&[("Sources/Crypto", NONHERMETIC)]);
+    let path = dir.path().join("Sources/Crypto/BUILD.bazel");
+
+    let mut config = test_config(dir.path());
+    // objc_library is outside the default swift_* rules
+    config.rule_filter.clear();
+
+    let report = fix_build_file(&path, &config).unwrap();
+
+    assert!(report.findings.iter().any(|finding| matches!(
+        finding.issue,
+        BuildIssue::NonHermeticGlob {
+            removable: false,
+            ..
+        }
+    )));
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(!fixed.contains("\"**/*.o\","), "{}", fixed);
+    assert!(
+        fixed.contains("glob([\"lib/*.so\"], allow_empty = True)"),
+        "{}",
+        fixed
+    );
+    assert!(
+        fixed.contains("glob([\"Frameworks/*.dylib\"], allow_empty = True)"),
+        "{}",
+        fixed
+    );
+}
@@ -0,0 +1,115 @@
+use std::fs;
+
+use umbra_build_fixer::checks::interop::{check_objc_interop, fix_objc_interop};
+use umbra_build_fixer::starlark::ast::parse;
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const MIXED: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+package(default_visibility = ["//visibility:public"])
+
+objc_library(
+    name = "CryptoBridge",
+    srcs = ["CryptoBridge.m"],
+    hdrs = ["CryptoBridge.h"],
+)
+
+swift_library(
+    name = "Crypto",
+    srcs = glob(["*.swift"], allow_empty = True),
+    deps = [
+        ":CryptoBridge",
+        "//Sources/Foundation",
+    ],
+)
+
+swift_library(
+    name = "CryptoKit",
+    srcs = glob(["Kit/*.swift"], allow_empty = True),
+    deps = ["//Sources/Crypto:CryptoBridge"],
+    features = ["swift.objc_interop"],
+)
+"#;
+
+fn issues(content: &str, package: Option<&str>) -> Vec<BuildIssue> {
+    check_objc_interop(&parse(content).unwrap(), package)
+        .into_iter()
+        .map(|(issue, _)| issue)
+        .collect()
+}
+
+#[test]
+fn dependency_on_objc_library_needs_interop_feature() {
+    assert_eq!(
+        issues(MIXED, Some("Sources/Crypto")),
+        [BuildIssue::IncompatibleDependency {
+            target: "Crypto".to_string(),
+            dependency: ":CryptoBridge".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn labels_of_the_package_resolve_to_its_targets() {
+    let content = MIXED.replace(
+        "    features = [\"swift.objc_interop\"],\n",
+        "    features = [\"swift.emit_symbol_graph\"],\n",
+    );
+
+    let issues = issues(&content, Some("Sources/Crypto"));
+
+    assert_eq!(issues.len(), 2);
+    assert_eq!(
+        issues[1],
+        BuildIssue::IncompatibleDependency {
+            target: "CryptoKit".to_string(),
+            dependency: "//Sources/Crypto:CryptoBridge".to_string(),
+        }
+    );
+}
+
+#[test]
+fn fix_adds_the_feature() {
+    let content = MIXED.replace(
+        "    features = [\"swift.objc_interop\"],\n",
+        "    features = [\"swift.emit_symbol_graph\"],\n",
+    );
+
+    let fixed = fix_objc_interop(&content, "Crypto");
+    let fixed = fix_objc_interop(&fixed, "CryptoKit");
+
+    assert!(
+        fixed
+            .contains("    name = \"Crypto\",\n    features = [\"swift.objc_interop\"],\n    srcs"),
+        "{}",
+        fixed
+    );
+    assert!(
+        fixed.contains("    features = [\"swift.emit_symbol_graph\", \"swift.objc_interop\"],\n"),
+        "{}",
+        fixed
+    );
+    assert!(issues(&fixed, Some("Sources/Crypto")).is_empty());
+}
+
+#[test]
+fn build_file_is_fixed() {
+    let dir = workspace(&[("Sources/Crypto", MIXED)]);
+    let path = dir.path().join("Sources/Crypto/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(report
+        .findings
+        .iter()
+        .any(|finding| matches!(finding.issue, BuildIssue::IncompatibleDependency { .. })));
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        fixed.matches("\"swift.objc_interop\"").count(),
+        2,
+        "{}",
+        fixed
+    );
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use umbra_build_fixer::checks::globs::{find_orphaned_sources, fix_orphaned_source};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+// Core.swift is matched by clean.BUILD's `*.swift`, Internal/Helper.swift isn't
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("devtools/build/fixers/tests/fixtures/orphaned_sources")
+}
+
+#[test]
+fn files_outside_every_glob_are_found() {
+    let build_file = fixture_dir().join("BUILD.bazel");
+
+    assert_eq!(
+        find_orphaned_sources(&build_file, CLEAN),
+        ["Internal/Helper.swift"]
+    );
+}
+
+#[test]
+fn glob_is_extended_to_cover_orphaned_file() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let package = dir.path().join("Sources/Core");
+    for file in ["Core.swift", "Internal/Helper.swift"] {
+        let target = package.join(file);
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        fs::copy(fixture_dir().join(file), target).unwrap();
+    }
+    let path = package.join("BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert_eq!(
+        issues,
+        [&BuildIssue::OrphanedSourceFile {
+            file: "Internal/Helper.swift".to_string()
+        }]
+    );
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(
+        content.contains("[\"*.swift\", \"Internal/*.swift\"]"),
+        "{}",
+        content
+    );
+}
+
+#[test]
+fn orphaned_file_is_listed_in_plain_srcs() {
+    let content = "swift_library(\n    name = \"Core\",\n    srcs = [\"Core.swift\"],\n)\n";
+
+    assert_eq!(
+        fix_orphaned_source(content, "Internal/Helper.swift"),
+        "swift_library(\n    name = \"Core\",\n    srcs = [\"Core.swift\", \"Internal/Helper.swift\"],\n)\n"
+    );
+}
+
+#[test]
+fn closest_rule_gets_the_file() {
+    let content = "\
+swift_library(
+    name = \"Core\",
+    srcs = glob([\"Sources/*.swift\"]),
+)
+
+swift_test(
+    name = \"CoreTests\",
+    srcs = glob([\"Tests/*.swift\"]),
+)
+";
+
+    let fixed = fix_orphaned_source(content, "Tests/Unit/CoreTests.swift");
+
+    assert!(
+        fixed.contains("glob([\"Tests/*.swift\", \"Tests/Unit/*.swift\"])"),
+        "{}",
+        fixed
+    );
+}
+
+#[test]
+fn srcs_that_cannot_be_evaluated_are_skipped() {
+    let dir = workspace(&[]);
+    fs::write(dir.path().join("Core.swift"), "").unwrap();
+    let content = "swift_library(\n    name = \"Core\",\n    srcs = SRCS,\n)\n";
+
+    assert!(find_orphaned_sources(&dir.path().join("BUILD.bazel"), content).is_empty());
+}
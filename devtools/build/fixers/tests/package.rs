@@ -0,0 +1,67 @@
+use std::fs;
+
+use umbra_build_fixer::checks::package::{check_package_declaration, fix_package_declaration};
+use umbra_build_fixer::{analyze_build_file, fix_build_file, BuildIssue, Config};
+
+use crate::common::{test_config, workspace};
+
+const PACKAGE: &str = "package(default_visibility = [\"//visibility:public\"])\n";
+
+#[test]
+fn empty_and_whitespace_only_files_get_a_package_declaration() {
+    for content in ["", "  \n\t\n\n"] {
+        let dir = workspace(&[("Sources/Core", content)]);
+        let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+        // test_config re-analyzes the result, so this also checks that the
+        // fixed file has no new issues.
+        let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+        let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+        assert_eq!(issues, vec![&BuildIssue::EmptyBuildFile], "{:?}", content);
+        assert_eq!(fs::read_to_string(&path).unwrap(), PACKAGE);
+        assert!(analyze_build_file(PACKAGE, &Config::default()).is_empty());
+    }
+}
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+#[test]
+fn file_with_package_call_is_not_modified() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    assert!(check_package_declaration(CLEAN).is_none());
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(!report.modified);
+    assert_eq!(fs::read_to_string(&path).unwrap(), CLEAN);
+}
+
+#[test]
+fn package_declaration_goes_after_loads() {
+    let content = "load(\"//:defs.bzl\", \"umbra_module\")\n\numbra_module(name = \"Core\")\n";
+
+    let (issue, _) = check_package_declaration(content).unwrap();
+    assert_eq!(issue, BuildIssue::MissingPackageDeclaration);
+    assert_eq!(
+        fix_package_declaration(content),
+        format!(
+            "load(\"//:defs.bzl\", \"umbra_module\")\n\n{}\numbra_module(name = \"Core\")\n",
+            PACKAGE
+        )
+    );
+}
+
+#[test]
+fn package_declaration_goes_below_header_comment() {
+    let content = "# Copyright Umbra\n\n# The core module.\nexports_files([\"Info.plist\"])\n";
+
+    assert_eq!(
+        fix_package_declaration(content),
+        format!(
+            "# Copyright Umbra\n\n{}\n# The core module.\nexports_files([\"Info.plist\"])\n",
+            PACKAGE
+        )
+    );
+}
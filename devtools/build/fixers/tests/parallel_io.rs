@@ -0,0 +1,116 @@
+use std::fs;
+
+use umbra_build_fixer::parallel_io::read_files;
+use umbra_build_fixer::{find_build_files, RunMode};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+fn sample_workspace() -> tempfile::TempDir {
+    let packages: Vec<(String, &str)> = (0..20)
+        .map(|index| {
+            let content = if index % 2 == 0 { DIRTY } else { CLEAN };
+            (format!("Sources/Module{}", index), content)
+        })
+        .collect();
+    let files: Vec<(&str, &str)> = packages
+        .iter()
+        .map(|(package, content)| (package.as_str(), *content))
+        .collect();
+    workspace(&files)
+}
+
+#[test]
+fn reads_match_sequential_reads_in_order() {
+    let dir = sample_workspace();
+    let files = find_build_files(&test_config(dir.path())).unwrap();
+
+    let contents = read_files(&files, 4).unwrap();
+
+    assert_eq!(contents.len(), files.len());
+    for (file, content) in files.iter().zip(contents) {
+        assert_eq!(content.unwrap(), fs::read_to_string(file).unwrap());
+    }
+}
+
+#[test]
+fn unreadable_file_only_fails_its_own_entry() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let files = vec![
+        dir.path().join("Sources/Core/BUILD.bazel"),
+        dir.path().join("Sources/Missing/BUILD.bazel"),
+    ];
+
+    let contents = read_files(&files, 1).unwrap();
+
+    assert_eq!(contents[0].as_ref().unwrap(), CLEAN);
+    assert!(contents[1].is_err());
+}
+
+#[test]
+fn zero_max_open_files_still_reads() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let files = find_build_files(&test_config(dir.path())).unwrap();
+
+    let contents = read_files(&files, 0).unwrap();
+
+    assert_eq!(contents[0].as_ref().unwrap(), CLEAN);
+}
+
+#[test]
+fn check_output_matches_sequential_io() {
+    let dir = sample_workspace();
+    let root = dir.path().to_str().unwrap();
+
+    let sequential = umbra_fix(&["--check", "--root", root]);
+    let parallel = umbra_fix(&[
+        "--check",
+        "--parallel-io",
+        "--max-open-files",
+        "2",
+        "--root",
+        root,
+    ]);
+
+    assert_eq!(parallel.status.code(), sequential.status.code());
+    assert_eq!(parallel.stdout, sequential.stdout);
+}
+
+#[test]
+fn fixes_match_sequential_io() {
+    let sequential = sample_workspace();
+    let parallel = sample_workspace();
+
+    let output = umbra_fix(&["--root", sequential.path().to_str().unwrap()]);
+    assert!(output.status.success());
+    let output = umbra_fix(&["--parallel-io", "--root", parallel.path().to_str().unwrap()]);
+    assert!(output.status.success());
+
+    let mut config = test_config(sequential.path());
+    config.mode = RunMode::DryRun;
+    for file in find_build_files(&config).unwrap() {
+        let relative = file.strip_prefix(sequential.path()).unwrap();
+        assert_eq!(
+            fs::read_to_string(parallel.path().join(relative)).unwrap(),
+            fs::read_to_string(&file).unwrap(),
+            "{}",
+            relative.display()
+        );
+    }
+}
+
+#[test]
+fn max_open_files_requires_parallel_io() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+
+    let output = umbra_fix(&[
+        "--max-open-files",
+        "8",
+        "--root",
+        dir.path().to_str().unwrap(),
+    ]);
+
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::Path;
+
+use umbra_build_fixer::patch::{apply_patch, unified_diff};
+use umbra_build_fixer::{fix_build_file, Config};
+
+use crate::common::{umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+const NO_TRAILING_NEWLINE: &str = include_str!("fixtures/no_trailing_newline.BUILD");
+
+#[test]
+fn diff_has_workspace_relative_headers() {
+    let diff = unified_diff(Path::new("Sources/Core/BUILD.bazel"), "a\nb\n", "a\nc\n", 3);
+    assert!(diff.starts_with("--- a/Sources/Core/BUILD.bazel\n+++ b/Sources/Core/BUILD.bazel\n"));
+    assert!(diff.contains("-b\n+c\n"));
+}
+
+#[test]
+fn patch_output_round_trips_to_in_place_fix() {
+    let files = [
+        ("Sources/Core", DIRTY),
+        ("Sources/Lib", NO_TRAILING_NEWLINE),
+        ("Sources/Clean", "package()\n"),
+    ];
+    let patched = workspace(&files);
+    let fixed = workspace(&files);
+    let patch_dir = tempfile::tempdir().unwrap();
+    let patch_file = patch_dir.path().join("out.patch");
+
+    let output = umbra_fix(&[
+        "--root",
+        patched.path().to_str().unwrap(),
+        "--output",
+        "patch",
+        "--patch-file",
+        patch_file.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    // Nothing is modified in place, and only dirty files appear in the patch
+    let core = patched.path().join("Sources/Core/BUILD.bazel");
+    assert_eq!(fs::read_to_string(&core).unwrap(), DIRTY);
+    let patch = fs::read_to_string(&patch_file).unwrap();
+    assert!(patch.contains("+++ b/Sources/Core/BUILD.bazel"));
+    assert!(patch.contains("+++ b/Sources/Lib/BUILD.bazel"));
+    assert!(!patch.contains("Sources/Clean"));
+
+    let output = umbra_fix(&[
+        "--apply-patch",
+        patch_file.to_str().unwrap(),
+        "--root",
+        patched.path().to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let config = Config::new(fixed.path());
+    for (package, _) in files {
+        let fixed_path = fixed.path().join(package).join("BUILD.bazel");
+        fix_build_file(&fixed_path, &config).unwrap();
+        assert_eq!(
+            fs::read_to_string(patched.path().join(package).join("BUILD.bazel")).unwrap(),
+            fs::read_to_string(&fixed_path).unwrap(),
+            "{}",
+            package
+        );
+    }
+}
+
+#[test]
+fn mismatched_patch_is_rejected_without_changes() {
+    let dir = workspace(&[("Sources/Core", "a\nb\n")]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let patch = unified_diff(Path::new("Sources/Core/BUILD.bazel"), "a\nx\n", "a\ny\n", 3);
+
+    assert!(apply_patch(dir.path(), &patch).is_err());
+    assert_eq!(fs::read_to_string(path).unwrap(), "a\nb\n");
+}
+
+#[test]
+fn paths_outside_the_root_are_rejected_without_changes() {
+    let outer = tempfile::tempdir().unwrap();
+    let root = outer.path().join("root");
+    fs::create_dir_all(root.join("Sources/Core")).unwrap();
+    fs::write(root.join("Sources/Core/BUILD.bazel"), "a\nb\n").unwrap();
+    fs::write(outer.path().join("BUILD.bazel"), "a\nb\n").unwrap();
+
+    let patch = unified_diff(Path::new("Sources/Core/BUILD.bazel"), "a\nb\n", "a\nc\n", 3)
+        + &unified_diff(Path::new("../BUILD.bazel"), "a\nb\n", "a\nc\n", 3);
+
+    let error = apply_patch(&root, &patch).unwrap_err();
+    assert!(error.to_string().contains("outside the root"), "{}", error);
+    assert_eq!(
+        fs::read_to_string(root.join("Sources/Core/BUILD.bazel")).unwrap(),
+        "a\nb\n"
+    );
+    assert_eq!(
+        fs::read_to_string(outer.path().join("BUILD.bazel")).unwrap(),
+        "a\nb\n"
+    );
+}
+
+#[test]
+fn patch_output_is_appended() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let patch_dir = tempfile::tempdir().unwrap();
+    let patch_file = patch_dir.path().join("out.patch");
+    fs::write(&patch_file, "# earlier run\n").unwrap();
+
+    let output = umbra_fix(&[
+        "--root",
+        dir.path().to_str().unwrap(),
+        "--output",
+        "patch",
+        "--patch-file",
+        patch_file.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let patch = fs::read_to_string(&patch_file).unwrap();
+    assert!(
+        patch.starts_with("# earlier run\n--- a/Sources/Core/BUILD.bazel\n"),
+        "{}",
+        patch
+    );
+}
@@ -0,0 +1,23 @@
+use std::fs;
+
+use crate::common::{umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+#[test]
+fn profile_is_written_to_the_given_file() {
+    let dir = workspace(&[("Sources/Core", DIRTY), ("Sources/Utils", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+    let profile = dir.path().join("profile.pb");
+
+    let output = umbra_fix(&[
+        "--check",
+        "--root",
+        root,
+        "--profile",
+        profile.to_str().unwrap(),
+    ]);
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    assert!(fs::metadata(&profile).unwrap().len() > 0);
+}
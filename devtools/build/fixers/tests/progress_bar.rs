@@ -0,0 +1,64 @@
+use crate::common::{umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+fn two_packages() -> tempfile::TempDir {
+    workspace(&[("Sources/Core", DIRTY), ("Sources/Utils", DIRTY)])
+}
+
+#[test]
+fn never_draws_no_progress_bar() {
+    let dir = two_packages();
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--progress", "never", "--root", root]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for captured in [&stdout, &stderr] {
+        assert!(!captured.contains('\x1b'), "{:?}", captured);
+        assert!(!captured.contains(" files ("), "{:?}", captured);
+    }
+}
+
+#[test]
+fn auto_draws_no_progress_bar_into_a_pipe() {
+    let dir = two_packages();
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--root", root]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains(" files ("), "{:?}", stderr);
+}
+
+#[test]
+fn always_draws_the_bar_on_stderr() {
+    let dir = two_packages();
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--progress", "always", "--root", root]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("/2 files ("), "{:?}", stderr);
+    // Reports stay on stdout, free of the bar
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Found 2 BUILD.bazel files"), "{}", stdout);
+    assert!(!stdout.contains('\x1b'), "{:?}", stdout);
+}
+
+#[test]
+fn always_is_rejected_where_no_bar_is_drawn() {
+    let dir = two_packages();
+    let root = dir.path().to_str().unwrap();
+
+    for mode in ["--check", "--diff-only"] {
+        let output = umbra_fix(&[mode, "--progress", "always", "--root", root]);
+
+        assert_eq!(output.status.code(), Some(2), "{}", mode);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("--progress always"), "{:?}", stderr);
+        assert!(!stderr.contains(" files ("), "{:?}", stderr);
+    }
+}
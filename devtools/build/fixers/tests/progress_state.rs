@@ -0,0 +1,139 @@
+use std::fs;
+use std::path::Path;
+
+use umbra_build_fixer::progress_state::{ProgressLog, ProgressState, PROGRESS_FILE_NAME};
+
+use crate::common::{umbra_fix, workspace};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+#[test]
+fn interrupted_run_is_resumed_without_redoing_completed_files() {
+    let dir = workspace(&[
+        ("Sources/A", DIRTY),
+        ("Sources/B", DIRTY),
+        ("Sources/C", DIRTY),
+    ]);
+    let root = dir.path().to_str().unwrap();
+    let build_file = |package: &str| dir.path().join(package).join("BUILD.bazel");
+    // B can't be read, so the run stops there after fixing A
+    fs::write(build_file("Sources/B"), b"\xff\xfe").unwrap();
+
+    let interrupted = umbra_fix(&["--root", root]);
+
+    assert!(!interrupted.status.success());
+    let state = ProgressState::load(&dir.path().join(PROGRESS_FILE_NAME))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        state.completed,
+        [Path::new("Sources/A/BUILD.bazel").to_path_buf()].into()
+    );
+    assert_eq!(
+        state.failed,
+        [Path::new("Sources/B/BUILD.bazel").to_path_buf()].into()
+    );
+    assert_ne!(fs::read_to_string(build_file("Sources/A")).unwrap(), DIRTY);
+    assert_eq!(fs::read_to_string(build_file("Sources/C")).unwrap(), DIRTY);
+
+    // A is reverted to show the resumed run doesn't process it again
+    fs::write(build_file("Sources/A"), DIRTY).unwrap();
+    fs::write(build_file("Sources/B"), DIRTY).unwrap();
+    let resumed = umbra_fix(&["--root", root, "--resume"]);
+
+    assert!(resumed.status.success(), "{:?}", resumed);
+    let stdout = String::from_utf8_lossy(&resumed.stdout);
+    assert!(
+        stdout.contains("Resuming an interrupted run: skipping 1 completed files"),
+        "{}",
+        stdout
+    );
+    assert_eq!(fs::read_to_string(build_file("Sources/A")).unwrap(), DIRTY);
+    assert_ne!(fs::read_to_string(build_file("Sources/B")).unwrap(), DIRTY);
+    assert_ne!(fs::read_to_string(build_file("Sources/C")).unwrap(), DIRTY);
+    assert!(!dir.path().join(PROGRESS_FILE_NAME).exists());
+}
+
+#[test]
+fn reset_processes_every_file_again() {
+    let dir = workspace(&[("Sources/A", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+    let mut state = ProgressState::default();
+    state.mark_completed(Path::new("Sources/A/BUILD.bazel"));
+    state.save(&dir.path().join(PROGRESS_FILE_NAME)).unwrap();
+
+    let output = umbra_fix(&["--root", root, "--reset"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let fixed = fs::read_to_string(dir.path().join("Sources/A/BUILD.bazel")).unwrap();
+    assert_ne!(fixed, DIRTY);
+    assert!(!dir.path().join(PROGRESS_FILE_NAME).exists());
+}
+
+#[test]
+fn resume_without_progress_file_fails() {
+    let dir = workspace(&[("Sources/A", CLEAN)]);
+
+    let output = umbra_fix(&["--root", dir.path().to_str().unwrap(), "--resume"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("no progress file"), "{}", stderr);
+}
+
+#[test]
+fn dry_run_records_no_progress() {
+    let dir = workspace(&[("Sources/A", DIRTY), ("Sources/B", DIRTY)]);
+    fs::write(dir.path().join("Sources/B/BUILD.bazel"), b"\xff\xfe").unwrap();
+
+    let output = umbra_fix(&["--root", dir.path().to_str().unwrap(), "--dry-run"]);
+
+    assert!(!output.status.success());
+    assert!(!dir.path().join(PROGRESS_FILE_NAME).exists());
+}
+
+#[test]
+fn each_file_appends_a_record_to_the_log() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(PROGRESS_FILE_NAME);
+
+    let mut log = ProgressLog::open(&path).unwrap();
+    log.mark_failed(Path::new("Sources/A/BUILD.bazel")).unwrap();
+    log.mark_completed(Path::new("Sources/B/BUILD.bazel"))
+        .unwrap();
+    // A resumed run retries A
+    log.mark_completed(Path::new("Sources/A/BUILD.bazel"))
+        .unwrap();
+    drop(log);
+
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 3);
+    let state = ProgressState::load(&path).unwrap().unwrap();
+    assert_eq!(
+        state.completed,
+        [
+            Path::new("Sources/A/BUILD.bazel").to_path_buf(),
+            Path::new("Sources/B/BUILD.bazel").to_path_buf(),
+        ]
+        .into()
+    );
+    assert!(state.failed.is_empty());
+}
+
+#[test]
+fn record_cut_short_by_an_interruption_is_ignored() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(PROGRESS_FILE_NAME);
+    fs::write(
+        &path,
+        "{\"file\":\"Sources/A/BUILD.bazel\",\"completed\":true}\n{\"file\":\"Sour",
+    )
+    .unwrap();
+
+    let state = ProgressState::load(&path).unwrap().unwrap();
+
+    assert_eq!(
+        state.completed,
+        [Path::new("Sources/A/BUILD.bazel").to_path_buf()].into()
+    );
+}
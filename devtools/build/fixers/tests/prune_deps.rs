@@ -0,0 +1,113 @@
+use std::fs;
+use std::path::Path;
+
+use umbra_build_fixer::checks::deps::fix_unused_dependency;
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const PRUNE_DEPS: &str = include_str!("fixtures/prune_deps.BUILD");
+
+const IMPORT_MAP: &str = r#"
+"//Sources/Logging" = "UmbraLogging"
+"//Sources/UmbraErrors" = "UmbraErrors"
+"//third_party/apple:UIKit" = "UIKit"
+"#;
+
+fn prune_workspace() -> tempfile::TempDir {
+    let dir = workspace(&[("Sources/Core", PRUNE_DEPS)]);
+    fs::write(dir.path().join("import_map.toml"), IMPORT_MAP).unwrap();
+    let source_dir = dir.path().join("Sources/Core/Errors");
+    fs::create_dir_all(&source_dir).unwrap();
+    fs::write(
+        source_dir.join("CoreError.swift"),
+        "import Foundation\nimport UmbraErrors\n\npublic struct CoreError: Error {}\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn read_build_file(root: &Path) -> String {
+    fs::read_to_string(root.join("Sources/Core/BUILD.bazel")).unwrap()
+}
+
+#[test]
+fn unused_dep_is_removed_and_system_dep_kept() {
+    let dir = prune_workspace();
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+    config.prune_deps = true;
+
+    let report = fix_build_file(&path, &config).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert_eq!(
+        issues,
+        [&BuildIssue::UnusedDependency {
+            target: "Core".to_string(),
+            label: "//Sources/Logging".to_string(),
+        }]
+    );
+    let content = read_build_file(dir.path());
+    assert!(!content.contains("//Sources/Logging"), "{}", content);
+    assert!(!content.contains("no longer used"), "{}", content);
+    assert!(content.contains("    deps = [\n        \"//Sources/UmbraErrors\",\n"));
+    assert!(content.contains("//third_party/apple:UIKit"));
+}
+
+#[test]
+fn prune_system_deps_removes_system_frameworks() {
+    let dir = prune_workspace();
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--prune-deps", "--prune-system-deps", "--root", root]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let content = read_build_file(dir.path());
+    assert!(!content.contains("//Sources/Logging"));
+    assert!(!content.contains("//third_party/apple:UIKit"));
+    assert!(content.contains("//Sources/UmbraErrors"));
+}
+
+#[test]
+fn deps_are_kept_without_prune_deps() {
+    let dir = prune_workspace();
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn labels_are_matched_in_any_form() {
+    let dir = prune_workspace();
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let content = read_build_file(dir.path())
+        .replace("\"//Sources/Logging\",", "\"//Sources/Logging:Logging\",");
+    fs::write(&path, content).unwrap();
+    let mut config = test_config(dir.path());
+    config.prune_deps = true;
+
+    let report = fix_build_file(&path, &config).unwrap();
+
+    assert!(report.findings.iter().any(|f| f.issue
+        == BuildIssue::UnusedDependency {
+            target: "Core".to_string(),
+            label: "//Sources/Logging:Logging".to_string(),
+        }));
+    assert!(!read_build_file(dir.path()).contains("//Sources/Logging"));
+}
+
+#[test]
+fn inline_deps_lose_one_element() {
+    let content = "swift_library(name = \"A\", deps = [\":b\", \":c\"])\n";
+    assert_eq!(
+        fix_unused_dependency(content, "A", ":b"),
+        "swift_library(name = \"A\", deps = [\":c\"])\n"
+    );
+    assert_eq!(
+        fix_unused_dependency(content, "A", ":c"),
+        "swift_library(name = \"A\", deps = [\":b\"])\n"
+    );
+}
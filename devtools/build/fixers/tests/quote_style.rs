@@ -0,0 +1,77 @@
+use umbra_build_fixer::checks::formatting::{check_quote_style, fix_quote_style};
+use umbra_build_fixer::starlark::tokenizer::{quote, tokenize};
+use umbra_build_fixer::BuildIssue;
+
+#[test]
+fn single_quoted_strings_are_flagged() {
+    let content = "swift_library(\n    name = 'Core',\n    srcs = [\"a.swift\"],\n)\n";
+    let (issue, message) = check_quote_style(content).unwrap();
+    assert_eq!(issue, BuildIssue::InconsistentQuoteStyle);
+    assert!(message.contains("line 2"), "{}", message);
+}
+
+#[test]
+fn double_quoted_strings_and_comments_are_left_alone() {
+    let content = "# don't touch 'this'\nswift_library(name = \"Core\")\n";
+    assert!(check_quote_style(content).is_none());
+    assert_eq!(fix_quote_style(content), content);
+}
+
+#[test]
+fn empty_strings_are_converted() {
+    assert_eq!(fix_quote_style("x = ''"), r#"x = """#);
+}
+
+#[test]
+fn embedded_double_quotes_are_escaped() {
+    assert_eq!(
+        fix_quote_style(r#"copts = ['-DNAME="Core"']"#),
+        r#"copts = ["-DNAME=\"Core\""]"#
+    );
+}
+
+#[test]
+fn escaped_single_quotes_are_unescaped() {
+    assert_eq!(fix_quote_style(r"x = 'it\'s'"), r#"x = "it's""#);
+}
+
+#[test]
+fn other_escape_sequences_are_preserved() {
+    assert_eq!(
+        fix_quote_style(r"x = 'tab\there\\n'"),
+        r#"x = "tab\there\\n""#
+    );
+}
+
+#[test]
+fn raw_and_triple_quoted_strings_are_converted() {
+    assert_eq!(fix_quote_style(r"x = r'\d+'"), r#"x = r"\d+""#);
+    assert_eq!(fix_quote_style("x = '''a\nb'''"), "x = \"\"\"a\nb\"\"\"");
+    // A raw string can't escape a double quote, so it is left as is.
+    assert_eq!(fix_quote_style(r#"x = r'"'"#), r#"x = r'"'"#);
+    assert!(check_quote_style(r#"x = r'"'"#).is_none());
+}
+
+#[test]
+fn fixed_content_passes_the_check() {
+    let content = "load('//:defs.bzl', 'umbra_library')\numbra_library(name = 'a\"b')\n";
+    let fixed = fix_quote_style(content);
+    assert!(check_quote_style(&fixed).is_none());
+    assert_eq!(fix_quote_style(&fixed), fixed);
+}
+
+#[test]
+fn quoted_strings_read_back_unchanged() {
+    for value in ["Core", "Café", "a\"b", "back\\slash", "two\nlines\tand\r\0"] {
+        let quoted = quote(value);
+        let tokens = tokenize(&quoted);
+        assert_eq!(tokens.len(), 1, "{}", quoted);
+        assert_eq!(
+            tokens[0].string_value().as_deref(),
+            Some(value),
+            "{}",
+            quoted
+        );
+    }
+    assert_eq!(quote("Café"), "\"Café\"");
+}
@@ -0,0 +1,117 @@
+use std::fs;
+
+use umbra_build_fixer::checks::globs::fix_redundant_allow_empty;
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const LIBRARY: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "Core",
+    srcs = glob(["*.swift"], allow_empty = True),
+    data = glob(
+        ["Resources/*.json"],
+        allow_empty = True,
+    ),
+)
+"#;
+
+fn redundant_globs(findings: &[umbra_build_fixer::Finding]) -> Vec<&BuildIssue> {
+    findings
+        .iter()
+        .map(|finding| &finding.issue)
+        .filter(|issue| matches!(issue, BuildIssue::RedundantAllowEmpty { .. }))
+        .collect()
+}
+
+#[test]
+fn check_is_opt_in() {
+    let dir = workspace(&[("Sources/Core", LIBRARY)]);
+    fs::write(dir.path().join("Sources/Core/Core.swift"), "").unwrap();
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(redundant_globs(&report.findings).is_empty());
+}
+
+#[test]
+fn allow_empty_is_removed_from_globs_that_match_files() {
+    let dir = workspace(&[("Sources/Core", LIBRARY)]);
+    fs::write(dir.path().join("Sources/Core/Core.swift"), "").unwrap();
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+    config.warn_redundant_allow_empty = true;
+
+    let report = fix_build_file(&path, &config).unwrap();
+
+    assert_eq!(
+        redundant_globs(&report.findings),
+        [&BuildIssue::RedundantAllowEmpty {
+            include: vec!["*.swift".to_string()],
+        }]
+    );
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(
+        fixed.contains("    srcs = glob([\"*.swift\"]),\n"),
+        "{}",
+        fixed
+    );
+    // Nothing matches the resources glob, so it keeps allow_empty
+    assert!(fixed.contains("        allow_empty = True,\n"), "{}", fixed);
+}
+
+#[test]
+fn matching_glob_without_allow_empty_is_not_flagged() {
+    let content = LIBRARY.replace(
+        r#"glob(["*.swift"], allow_empty = True)"#,
+        r#"glob(["*.swift"])"#,
+    );
+    let dir = workspace(&[("Sources/Core", &content)]);
+    fs::write(dir.path().join("Sources/Core/Core.swift"), "").unwrap();
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+    config.warn_redundant_allow_empty = true;
+
+    let report = fix_build_file(&path, &config).unwrap();
+
+    assert!(report.findings.is_empty(), "{:?}", report.findings);
+}
+
+#[test]
+fn flag_enables_the_check() {
+    let dir = workspace(&[("Sources/Core", LIBRARY)]);
+    fs::write(dir.path().join("Sources/Core/Core.swift"), "").unwrap();
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--warn-redundant-allow-empty", "--root", root]);
+
+    // A warning, so it doesn't fail --check
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[RedundantAllowEmpty]"), "{}", stdout);
+}
+
+#[test]
+fn multiline_glob_is_collapsed() {
+    let content = "srcs = glob(\n        [\"*.swift\"],\n        allow_empty = True\n    ),\n";
+
+    let fixed = fix_redundant_allow_empty(content, &["*.swift".to_string()]);
+
+    assert_eq!(fixed, "srcs = glob([\"*.swift\"]),\n");
+}
+
+#[test]
+fn other_arguments_are_kept() {
+    let content = r#"srcs = glob(["*.swift"], allow_empty = True, exclude = ["Old.swift"]),"#;
+
+    let fixed = fix_redundant_allow_empty(content, &["*.swift".to_string()]);
+
+    assert_eq!(
+        fixed,
+        r#"srcs = glob(["*.swift"], exclude = ["Old.swift"]),"#
+    );
+}
@@ -0,0 +1,89 @@
+use std::fs;
+use std::path::Path;
+
+use umbra_build_fixer::checks::deps::{check_repeated_deps, fix_repeated_dep};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const REPEATED_DEPS: &str = include_str!("fixtures/repeated_deps.BUILD");
+
+fn repeated(label: &str) -> BuildIssue {
+    BuildIssue::RepeatedDep {
+        target: "CoreTests".to_string(),
+        label: label.to_string(),
+        first: "//Sources/Core".to_string(),
+    }
+}
+
+#[test]
+fn labels_of_the_same_target_are_flagged_after_the_first() {
+    let issues = check_repeated_deps(REPEATED_DEPS, "Sources/Core", Path::new("/workspace"));
+
+    let flagged: Vec<&BuildIssue> = issues.iter().map(|(issue, _)| issue).collect();
+    assert_eq!(flagged, [&repeated(":Core"), &repeated("//Sources/Core")]);
+    assert!(
+        issues[0].1.contains("same target as //Sources/Core"),
+        "{}",
+        issues[0].1
+    );
+    assert!(issues[1].1.contains("more than once"), "{}", issues[1].1);
+}
+
+#[test]
+fn relative_labels_of_other_packages_are_not_repeats() {
+    // In Sources/App, `:Core` is //Sources/App:Core
+    let issues = check_repeated_deps(REPEATED_DEPS, "Sources/App", Path::new("/workspace"));
+
+    let flagged: Vec<&BuildIssue> = issues.iter().map(|(issue, _)| issue).collect();
+    assert_eq!(flagged, [&repeated("//Sources/Core")]);
+}
+
+#[test]
+fn fix_removes_the_later_entry() {
+    let fixed = fix_repeated_dep(
+        REPEATED_DEPS,
+        "CoreTests",
+        "//Sources/Core",
+        "//Sources/Core",
+    );
+
+    assert_eq!(
+        fixed,
+        REPEATED_DEPS.replacen(
+            "        \":Core\",  # the same target, relative to this package\n        \"//Sources/Core\",\n",
+            "        \":Core\",  # the same target, relative to this package\n",
+            1
+        )
+    );
+}
+
+#[test]
+fn only_the_first_occurrence_is_kept() {
+    let dir = workspace(&[("Sources/Core", REPEATED_DEPS)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert_eq!(issues, [&repeated(":Core"), &repeated("//Sources/Core")]);
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(
+        fixed.contains("    deps = [\n        \"//Sources/Core\",\n    ],\n"),
+        "{}",
+        fixed
+    );
+}
+
+#[test]
+fn fix_keeps_the_earlier_entry_after_the_list_is_sorted() {
+    let content = "swift_test(\n    name = \"CoreTests\",\n    deps = [\"//Sources/Core:Core\", \":Core\"],\n)\n";
+
+    let fixed = fix_repeated_dep(content, "CoreTests", "//Sources/Core:Core", ":Core");
+
+    assert!(
+        fixed.contains("deps = [\"//Sources/Core:Core\"]"),
+        "{}",
+        fixed
+    );
+}
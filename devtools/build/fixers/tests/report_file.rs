@@ -0,0 +1,74 @@
+use std::fs;
+
+use serde_json::Value;
+
+use crate::common::{umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+#[test]
+fn report_file_contains_every_analyzed_file() {
+    let dir = workspace(&[("Sources/Core", DIRTY), ("Sources/Utils", CLEAN)]);
+    let root = dir.path().to_str().unwrap();
+    let reports_dir = tempfile::tempdir().unwrap();
+    // The parent directory doesn't exist yet and must be created.
+    let report_path = reports_dir.path().join("ci/report.json");
+
+    // Without the cache, so the second run's output matches the first
+    let plain = umbra_fix(&["--dry-run", "--no-cache", "--root", root]);
+    let output = umbra_fix(&[
+        "--dry-run",
+        "--no-cache",
+        "--root",
+        root,
+        "--report-file",
+        report_path.to_str().unwrap(),
+    ]);
+
+    assert!(output.status.success());
+    assert_eq!(output.stdout, plain.stdout);
+
+    let report: Value = serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["mode"], "dry-run");
+    assert_eq!(report["files_scanned"], 2);
+    assert_eq!(report["files_modified"], 1);
+
+    let files = report["files"].as_array().unwrap();
+    let core = files
+        .iter()
+        .find(|file| {
+            file["path"]
+                .as_str()
+                .unwrap()
+                .ends_with("Sources/Core/BUILD.bazel")
+        })
+        .unwrap();
+    assert_eq!(core["modified"], true);
+    let names: Vec<&str> = core["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|finding| finding["issue"]["name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"CustomLibraryRule"), "{:?}", names);
+}
+
+#[test]
+fn report_file_is_overwritten() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let root = dir.path().to_str().unwrap();
+    let report_path = dir.path().join("report.json");
+    fs::write(&report_path, "stale content that is not JSON").unwrap();
+
+    let output = umbra_fix(&[
+        "--root",
+        root,
+        "--report-file",
+        report_path.to_str().unwrap(),
+    ]);
+
+    assert!(output.status.success());
+    let report: Value = serde_json::from_str(&fs::read_to_string(&report_path).unwrap()).unwrap();
+    assert_eq!(report["issue_count"], 0);
+}
@@ -0,0 +1,178 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use umbra_build_fixer::checks::resource_bundler::{check_resource_bundling, fix_resource_bundling};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const DATA_WITH_CATALOG: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "Core",
+    srcs = glob(["*.swift"], allow_empty = True),
+    data = [
+        "Assets.xcassets",
+        "config.json",
+        "en.lproj/Localizable.strings",
+    ],
+)
+"#;
+
+// An asset catalog, a localization and a plain resource next to Core.swift
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("devtools/build/fixers/tests/fixtures/resource_bundler")
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    for entry in walkdir::WalkDir::new(from).min_depth(1) {
+        let entry = entry.unwrap();
+        let target = to.join(entry.path().strip_prefix(from).unwrap());
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).unwrap();
+        } else {
+            fs::copy(entry.path(), &target).unwrap();
+        }
+    }
+}
+
+#[test]
+fn catalogs_and_localizations_in_data_are_flagged() {
+    let issues = check_resource_bundling(DATA_WITH_CATALOG, &fixture_dir());
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(
+        issues[0].0,
+        BuildIssue::UnbundledResources {
+            target: "Core".to_string(),
+            resources: vec![
+                "Assets.xcassets".to_string(),
+                "en.lproj/Localizable.strings".to_string(),
+            ],
+            bundle: Some("CoreResources".to_string()),
+        }
+    );
+    assert!(
+        issues[0].1.contains("apple_resource_bundle"),
+        "{}",
+        issues[0].1
+    );
+}
+
+#[test]
+fn glob_patterns_under_a_catalog_are_flagged() {
+    let content = DATA_WITH_CATALOG.replace(
+        "data = [\n        \"Assets.xcassets\",\n        \"config.json\",\n        \"en.lproj/Localizable.strings\",\n    ]",
+        "data = glob([\"Assets.xcassets/**\", \"**/*.xcassets\", \"*.json\"])",
+    );
+
+    let issues = check_resource_bundling(&content, &fixture_dir());
+
+    // `**/*.xcassets` only matches files, so it names no catalog
+    assert!(matches!(
+        &issues[0].0,
+        BuildIssue::UnbundledResources { resources, .. } if resources == &["Assets.xcassets/**"]
+    ));
+}
+
+#[test]
+fn missing_directories_are_not_flagged() {
+    let content = DATA_WITH_CATALOG.replace("Assets.xcassets", "Missing.xcassets");
+
+    let issues = check_resource_bundling(&content, &fixture_dir());
+
+    assert!(matches!(
+        &issues[0].0,
+        BuildIssue::UnbundledResources { resources, .. }
+            if resources == &["en.lproj/Localizable.strings"]
+    ));
+}
+
+#[test]
+fn taken_bundle_name_needs_a_manual_fix() {
+    let content = format!(
+        "{}\nfilegroup(\n    name = \"CoreResources\",\n)\n",
+        DATA_WITH_CATALOG
+    );
+
+    let issues = check_resource_bundling(&content, &fixture_dir());
+
+    assert!(!issues[0].0.is_fixable());
+}
+
+#[test]
+fn fix_moves_resources_into_a_bundle() {
+    let fixed = fix_resource_bundling(
+        DATA_WITH_CATALOG,
+        "Core",
+        &[
+            "Assets.xcassets".to_string(),
+            "en.lproj/Localizable.strings".to_string(),
+        ],
+        "CoreResources",
+    );
+
+    assert_eq!(
+        fixed,
+        r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+load("@rules_apple//apple:resources.bzl", "apple_resource_bundle")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "Core",
+    srcs = glob(["*.swift"], allow_empty = True),
+    data = [
+        "config.json",
+        ":CoreResources",
+    ],
+)
+
+apple_resource_bundle(
+    name = "CoreResources",
+    resources = glob(["Assets.xcassets/**", "en.lproj/Localizable.strings"], allow_empty = True),
+)
+"#
+    );
+}
+
+#[test]
+fn glob_of_only_resources_is_replaced_by_the_bundle() {
+    let content =
+        "swift_library(\n    name = \"Core\",\n    data = glob([\"Assets.xcassets/**\"]),\n)\n";
+
+    let fixed = fix_resource_bundling(
+        content,
+        "Core",
+        &["Assets.xcassets/**".to_string()],
+        "CoreResources",
+    );
+
+    assert!(
+        fixed.contains("    data = [\":CoreResources\"],\n"),
+        "{}",
+        fixed
+    );
+    assert!(fixed.contains("glob([\"Assets.xcassets/**\"], allow_empty = True)"));
+}
+
+#[test]
+fn build_file_in_the_fixture_package_is_fixed() {
+    let dir = workspace(&[("Sources/Core", DATA_WITH_CATALOG)]);
+    let package = dir.path().join("Sources/Core");
+    copy_dir(&fixture_dir(), &package);
+    let path = package.join("BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(report
+        .findings
+        .iter()
+        .any(|finding| matches!(finding.issue, BuildIssue::UnbundledResources { .. })));
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(fixed.contains("apple_resource_bundle(\n    name = \"CoreResources\""));
+    assert!(check_resource_bundling(&fixed, &package).is_empty());
+}
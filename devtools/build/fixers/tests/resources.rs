@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::PathBuf;
+
+use umbra_build_fixer::checks::resources::check_missing_data;
+use umbra_build_fixer::sources::collect_resources;
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+#[test]
+fn data_attribute_is_added_when_package_has_resources() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let package = dir.path().join("Sources/Core");
+    fs::create_dir_all(package.join("Resources/en.lproj")).unwrap();
+    fs::write(package.join("Resources/en.lproj/Localizable.strings"), "").unwrap();
+    let path = package.join("BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert_eq!(
+        issues,
+        [&BuildIssue::MissingDataAttribute {
+            target: "Core".to_string()
+        }]
+    );
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(
+        content.contains(
+            "    name = \"Core\",\n    data = glob([\"**/*.xcassets\", \"**/*.strings\", \"**/*.json\", \"**/*.plist\"], allow_empty = True),\n"
+        ),
+        "{}",
+        content
+    );
+}
+
+#[test]
+fn package_without_resources_is_clean() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let package = dir.path().join("Sources/Core");
+    fs::write(package.join("Core.swift"), "import Foundation\n").unwrap();
+
+    assert!(check_missing_data(CLEAN, &package).is_empty());
+}
+
+#[test]
+fn existing_data_attribute_is_kept() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let package = dir.path().join("Sources/Core");
+    fs::write(package.join("config.json"), "{}").unwrap();
+    let content = CLEAN.replace(
+        "    visibility",
+        "    data = [\"config.json\"],\n    visibility",
+    );
+
+    assert!(check_missing_data(&content, &package).is_empty());
+}
+
+#[test]
+fn resources_of_nested_packages_are_ignored() {
+    let dir = workspace(&[("Sources/Core", CLEAN), ("Sources/Core/UI", CLEAN)]);
+    let package = dir.path().join("Sources/Core");
+    fs::create_dir_all(package.join("UI/Assets.xcassets/Icon.imageset")).unwrap();
+    fs::create_dir_all(package.join("Media.xcassets/Logo.imageset")).unwrap();
+    fs::write(package.join("Media.xcassets/Contents.json"), "{}").unwrap();
+    fs::write(package.join("Info.plist"), "").unwrap();
+
+    assert_eq!(
+        collect_resources(&package).unwrap(),
+        [PathBuf::from("Info.plist"), PathBuf::from("Media.xcassets")]
+    );
+}
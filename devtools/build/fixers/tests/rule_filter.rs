@@ -0,0 +1,137 @@
+use std::fs;
+
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const LIBRARY_AND_TEST: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_test")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "Core",
+    exports = [":Base"],
+)
+
+swift_test(
+    name = "CoreTests",
+    srcs = ["CoreTests.swift"],
+    deps = [":Core"],
+)
+"#;
+
+#[test]
+fn rules_flag_only_analyzes_the_given_rules() {
+    let dir = workspace(&[("Sources/Core", LIBRARY_AND_TEST)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&[
+        "--check",
+        "--no-cache",
+        "--rules",
+        "swift_test",
+        "--root",
+        root,
+    ]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[MissingTestonly]"), "{}", stdout);
+    for skipped in ["MissingSwiftLibraryLoad", "ExportsAttribute", "EmptySrcs"] {
+        assert!(!stdout.contains(skipped), "{}", stdout);
+    }
+}
+
+#[test]
+fn rules_all_analyzes_every_rule() {
+    let dir = workspace(&[("Sources/Core", LIBRARY_AND_TEST)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--no-cache", "--rules", "all", "--root", root]);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for reported in [
+        "MissingTestonly",
+        "MissingSwiftLibraryLoad",
+        "ExportsAttribute",
+    ] {
+        assert!(stdout.contains(reported), "{}", stdout);
+    }
+}
+
+#[test]
+fn findings_about_filtered_out_targets_are_dropped() {
+    let dir = workspace(&[("Sources/Core", LIBRARY_AND_TEST)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+    config.rule_filter = vec!["swift_test".to_string()];
+
+    let report = fix_build_file(&path, &config).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert_eq!(issues, [&BuildIssue::MissingTestonly]);
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(content.contains("exports = [\":Base\"]"), "{}", content);
+    assert!(content.contains("testonly = True"), "{}", content);
+}
+
+const SWIFT_AND_OBJC: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "Core",
+    srcs = glob(["*.swift"]),
+)
+
+objc_library(
+    name = "Bridge",
+    srcs = glob(["*.m"]),
+    visibility = "//visibility:public",
+)
+"#;
+
+#[test]
+fn only_swift_rules_are_analyzed_by_default() {
+    let dir = workspace(&[("Sources/Core", SWIFT_AND_OBJC)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert_eq!(issues, [&BuildIssue::GlobWithoutAllowEmpty]);
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(content.contains("allow_empty = True"), "{}", content);
+    // The fixes of checks of any rule leave the objc_library alone
+    assert!(
+        content.ends_with(
+            "objc_library(\n    name = \"Bridge\",\n    srcs = glob([\"*.m\"]),\n    visibility = \"//visibility:public\",\n)\n"
+        ),
+        "{}",
+        content
+    );
+}
+
+#[test]
+fn checks_of_any_rule_skip_filtered_out_rules() {
+    let content = SWIFT_AND_OBJC.replace("glob([\"*.swift\"])", "[\"Core.swift\"]");
+    let dir = workspace(&[("Sources/Core", &content)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert_eq!(report.findings, []);
+    assert_eq!(fs::read_to_string(&path).unwrap(), content);
+
+    let mut config = test_config(dir.path());
+    config.rule_filter.clear();
+    let report = fix_build_file(&path, &config).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert_eq!(
+        issues,
+        [
+            &BuildIssue::GlobWithoutAllowEmpty,
+            &BuildIssue::IncorrectVisibilityFormat
+        ]
+    );
+}
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use umbra_build_fixer::checks::loads::{check_legacy_rule_loads, fix_legacy_rule_load};
+use umbra_build_fixer::{analyze_build_file, fix_build_file, BuildIssue, Config};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+fn migrations(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+    entries
+        .iter()
+        .map(|(from, to)| (from.to_string(), to.to_string()))
+        .collect()
+}
+
+#[test]
+fn only_the_migrated_load_on_a_line_changes() {
+    let content = "load(\"@old//swift:swift.bzl\", \"swift_library\"); load(\"@other//:defs.bzl\", \"@old//swift:swift.bzl\")\n";
+    let migrations = migrations(&[("@old//swift:swift.bzl", "@new//swift:swift.bzl")]);
+
+    let issues = check_legacy_rule_loads(content, &migrations);
+    assert_eq!(issues.len(), 1);
+    let BuildIssue::LegacyRuleLoad { from, to } = &issues[0].0 else {
+        panic!("unexpected issue {:?}", issues[0].0);
+    };
+
+    assert_eq!(
+        fix_legacy_rule_load(content, from, to),
+        "load(\"@new//swift:swift.bzl\", \"swift_library\"); load(\"@other//:defs.bzl\", \"@old//swift:swift.bzl\")\n"
+    );
+}
+
+#[test]
+fn migrations_are_off_by_default() {
+    assert!(analyze_build_file(CLEAN, &Config::default()).is_empty());
+}
+
+#[test]
+fn rule_migrations_file_enables_defaults_and_overrides() {
+    let dir = workspace(&[("Sources/Core", CLEAN), ("Sources/Legacy", DIRTY)]);
+    fs::write(
+        dir.path().join("rule_migrations.toml"),
+        "[migrations]\n\"@build_bazel_rules_apple//apple:ios.bzl\" = \"@rules_apple//apple:ios.bzl\"\n",
+    )
+    .unwrap();
+    let config = test_config(dir.path());
+
+    for package in ["Sources/Core", "Sources/Legacy"] {
+        let path = dir.path().join(package).join("BUILD.bazel");
+        fix_build_file(&path, &config).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(
+            content.contains("load(\"@rules_swift//swift:swift.bzl\", \"swift_library\")"),
+            "{}",
+            content
+        );
+        assert!(!content.contains("build_bazel_rules_swift"), "{}", content);
+    }
+}
+
+#[test]
+fn migrate_rule_loads_flag_uses_built_in_defaults() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--migrate-rule-loads", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[LegacyRuleLoad]"), "{}", stdout);
+}
@@ -0,0 +1,154 @@
+use std::fs;
+
+use umbra_build_fixer::migrations::rules_swift::{
+    check_rules_swift_upgrade, fix_split_swift_bzl_loads, fix_swift_proto_library_protos,
+    VersionUpgrade,
+};
+use umbra_build_fixer::undo::backup_path;
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+const V1: VersionUpgrade = VersionUpgrade { from: 1, to: 2 };
+
+#[test]
+fn rules_are_loaded_from_their_own_bzl_files() {
+    let content = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library", "swift_test")
+"#;
+
+    assert_eq!(
+        fix_split_swift_bzl_loads(content),
+        r#"load("@build_bazel_rules_swift//swift:swift_library.bzl", "swift_library")
+load("@build_bazel_rules_swift//swift:swift_test.bzl", "swift_test")
+"#
+    );
+}
+
+#[test]
+fn other_swift_bzl_symbols_stay_loaded_from_swift_bzl() {
+    let content = r#"load("@rules_swift//swift:swift.bzl", "SwiftInfo", "swift_library", lib = "swift_library")
+"#;
+
+    assert_eq!(
+        fix_split_swift_bzl_loads(content),
+        r#"load("@rules_swift//swift:swift.bzl", "SwiftInfo", lib = "swift_library")
+load("@rules_swift//swift:swift_library.bzl", "swift_library")
+"#
+    );
+}
+
+#[test]
+fn loads_with_comments_are_not_split() {
+    let content =
+        "load(\n    \"@rules_swift//swift:swift.bzl\",\n    \"swift_library\",  # keep\n)\n";
+
+    assert_eq!(fix_split_swift_bzl_loads(content), content);
+}
+
+#[test]
+fn swift_proto_library_deps_become_protos() {
+    let content = r#"swift_proto_library(
+    name = "ApiSwift",
+    deps = [":api_proto"],
+)
+
+swift_library(
+    name = "Api",
+    deps = [":ApiSwift"],
+)
+"#;
+
+    assert_eq!(
+        fix_swift_proto_library_protos(content),
+        content.replacen("deps = [\":api_proto\"]", "protos = [\":api_proto\"]", 1)
+    );
+}
+
+#[test]
+fn only_proto_labels_move_to_protos() {
+    let content = r#"proto_library(
+    name = "api",
+    srcs = ["api.proto"],
+)
+
+swift_proto_library(
+    name = "ApiSwift",
+    deps = [
+        ":api",
+        "//Sources/Runtime",
+        "@com_google_protobuf//:any_proto",
+    ],
+)
+
+swift_proto_library(name = "Other", deps = [":grpc_plugin"])
+"#;
+
+    assert_eq!(
+        fix_swift_proto_library_protos(content),
+        content.replace(
+            "    deps = [\n        \":api\",\n        \"//Sources/Runtime\",\n        \"@com_google_protobuf//:any_proto\",\n    ],\n",
+            "    protos = [\":api\", \"@com_google_protobuf//:any_proto\"],\n    deps = [\n        \"//Sources/Runtime\",\n    ],\n",
+        )
+    );
+}
+
+#[test]
+fn upgrade_versions_are_parsed() {
+    assert_eq!("1->2".parse(), Ok(V1));
+    assert_eq!(V1.to_string(), "1->2");
+    assert_eq!(V1.steps().count(), 2);
+    assert!("2->3".parse::<VersionUpgrade>().is_err());
+    assert!("2->1".parse::<VersionUpgrade>().is_err());
+    assert!("2".parse::<VersionUpgrade>().is_err());
+}
+
+#[test]
+fn each_applicable_step_is_reported() {
+    let issues = check_rules_swift_upgrade(CLEAN, V1);
+
+    let issues: Vec<_> = issues.into_iter().map(|(issue, _)| issue).collect();
+    assert_eq!(
+        issues,
+        [BuildIssue::RulesSwiftMigration {
+            step: "SplitSwiftBzlLoads".to_string()
+        }]
+    );
+}
+
+#[test]
+fn migrations_only_run_when_requested() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+
+    assert!(!fix_build_file(&path, &config).unwrap().modified);
+
+    config.rules_swift_upgrade = Some(V1);
+    assert!(fix_build_file(&path, &config).unwrap().modified);
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(
+        content.starts_with(
+            "load(\"@build_bazel_rules_swift//swift:swift_library.bzl\", \"swift_library\")\n"
+        ),
+        "{}",
+        content
+    );
+}
+
+#[test]
+fn upgrade_can_be_rolled_back_with_undo() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let root = dir.path().to_str().unwrap();
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let output = umbra_fix(&["--root", root, "--upgrade-rules-swift-version", "1->2"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_ne!(fs::read_to_string(&path).unwrap(), CLEAN);
+    assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), CLEAN);
+
+    let output = umbra_fix(&["undo", "--root", root]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(fs::read_to_string(&path).unwrap(), CLEAN);
+}
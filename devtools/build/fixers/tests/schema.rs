@@ -0,0 +1,51 @@
+use serde_json::{json, Value};
+use umbra_build_fixer::config::schema::{config_schema, SCHEMA_URL};
+
+const COMMITTED_SCHEMA: &str = include_str!("../umbra-fix.schema.json");
+
+#[test]
+fn committed_schema_is_up_to_date() {
+    let committed: Value = serde_json::from_str(COMMITTED_SCHEMA).unwrap();
+    assert_eq!(
+        committed,
+        config_schema(),
+        "regenerate with `umbra-fix --print-schema > umbra-fix.schema.json`"
+    );
+}
+
+#[test]
+fn schema_has_id_and_compiles() {
+    let schema = config_schema();
+    assert_eq!(schema["$id"], SCHEMA_URL);
+    jsonschema::validator_for(&schema).unwrap();
+}
+
+#[test]
+fn schema_accepts_valid_config() {
+    let validator = jsonschema::validator_for(&config_schema()).unwrap();
+    let config = json!({
+        "output": "patch",
+        "max_depth": 2,
+        "include_patterns": ["Sources/**"],
+        "sorted_list_attributes": ["deps", "srcs"],
+        "format": true,
+    });
+    assert!(validator.is_valid(&config));
+    assert!(validator.is_valid(&json!({})));
+}
+
+#[test]
+fn schema_rejects_unknown_fields_and_bad_values() {
+    let validator = jsonschema::validator_for(&config_schema()).unwrap();
+    assert!(!validator.is_valid(&json!({ "outptu": "patch" })));
+    assert!(!validator.is_valid(&json!({ "output": "xml" })));
+    assert!(!validator.is_valid(&json!({ "max_depth": -1 })));
+}
+
+#[test]
+fn print_schema_matches_library() {
+    let output = crate::common::umbra_fix(&["--print-schema"]);
+    assert!(output.status.success());
+    let printed: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(printed, config_schema());
+}
@@ -0,0 +1,85 @@
+use umbra_build_fixer::checks::apply_fixes;
+use umbra_build_fixer::checks::globs::check_select_in_glob;
+use umbra_build_fixer::{analyze_build_file, BuildIssue, Config, Finding};
+
+const SELECT_IN_GLOB: &str = include_str!("fixtures/select_in_glob.BUILD");
+
+#[test]
+fn select_inside_glob_is_flagged() {
+    let issues = check_select_in_glob(SELECT_IN_GLOB);
+
+    let flagged: Vec<&BuildIssue> = issues.iter().map(|(issue, _)| issue).collect();
+    assert_eq!(
+        flagged,
+        [&BuildIssue::SelectInGlob {
+            line: 8,
+            target: Some("Core".to_string()),
+        }]
+    );
+}
+
+#[test]
+fn diagnostic_explains_why_and_how_to_fix() {
+    let issues = check_select_in_glob(SELECT_IN_GLOB);
+
+    let message = &issues[0].1;
+    assert!(message.contains("line 8 (in \"Core\")"), "{}", message);
+    assert!(
+        message.contains("glob() must be a static expression"),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains("before select() is resolved"),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains("glob([...]) + select({...})"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn glob_outside_a_rule_is_flagged_without_target() {
+    let content = "SRCS = glob(select({\"//conditions:default\": [\"*.swift\"]}))\n";
+
+    let issues = check_select_in_glob(content);
+
+    assert_eq!(
+        issues[0].0,
+        BuildIssue::SelectInGlob {
+            line: 1,
+            target: None,
+        }
+    );
+    assert!(issues[0].1.starts_with("select() inside glob() on line 1:"));
+}
+
+#[test]
+fn select_of_globs_is_fine() {
+    let content = r#"swift_library(
+    name = "Logging",
+    srcs = glob(["*.swift"]) + select({
+        "@platforms//os:macos": glob(["macOS/*.swift"]),
+        "//conditions:default": [],
+    }),
+)
+"#;
+
+    assert!(check_select_in_glob(content).is_empty());
+}
+
+#[test]
+fn issue_is_reported_but_not_fixed() {
+    let findings: Vec<Finding> = analyze_build_file(SELECT_IN_GLOB, &Config::default())
+        .into_iter()
+        .filter(|finding| matches!(finding.issue, BuildIssue::SelectInGlob { .. }))
+        .collect();
+
+    assert_eq!(findings.len(), 1);
+    assert!(!findings[0].issue.is_fixable());
+    assert_eq!(findings[0].issue.target(), Some("Core"));
+    assert_eq!(apply_fixes(SELECT_IN_GLOB, &findings), SELECT_IN_GLOB);
+}
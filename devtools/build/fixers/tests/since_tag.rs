@@ -0,0 +1,106 @@
+use std::fs;
+use std::io;
+
+use umbra_build_fixer::git::changed_since_tag;
+
+use crate::common::{git, git_repo, umbra_fix, write_build_file};
+
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+// A repo tagged v1.0 with Sources/Core and Sources/Logging, where only
+// Sources/Logging has been changed (and broken) in a commit since
+fn tagged_repo() -> tempfile::TempDir {
+    let dir = git_repo(&[("Sources/Core", DIRTY), ("Sources/Logging", CLEAN)]);
+    git(dir.path(), &["tag", "v1.0"]);
+    write_build_file(dir.path(), "Sources/Logging", DIRTY);
+    git(
+        dir.path(),
+        &["commit", "--quiet", "--all", "-m", "change logging"],
+    );
+    dir
+}
+
+#[test]
+fn changed_since_tag_lists_committed_changes() {
+    let dir = tagged_repo();
+
+    let changed = changed_since_tag(dir.path(), "v1.0").unwrap();
+
+    assert_eq!(changed, [dir.path().join("Sources/Logging/BUILD.bazel")]);
+}
+
+#[test]
+fn unknown_tag_lists_the_latest_tags() {
+    let dir = tagged_repo();
+    for tag in ["v1.1", "v1.2", "v1.3", "v1.4", "v1.5"] {
+        git(dir.path(), &["tag", tag]);
+    }
+
+    let err = changed_since_tag(dir.path(), "v9.9").unwrap_err();
+
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    let message = err.to_string();
+    assert!(message.starts_with("no git tag \"v9.9\""), "{}", message);
+    // Only five of the six tags are listed
+    let listed = ["v1.0", "v1.1", "v1.2", "v1.3", "v1.4", "v1.5"]
+        .iter()
+        .filter(|tag| message.contains(*tag))
+        .count();
+    assert_eq!(listed, 5, "{}", message);
+}
+
+#[test]
+fn unknown_tag_in_untagged_repo() {
+    let dir = git_repo(&[("Sources/Core", CLEAN)]);
+
+    let err = changed_since_tag(dir.path(), "v1.0").unwrap_err();
+
+    assert!(
+        err.to_string().contains("the repository has no tags"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn since_tag_only_processes_files_changed_since_the_tag() {
+    let dir = tagged_repo();
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--since-tag", "v1.0", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Sources/Logging/BUILD.bazel"), "{}", stdout);
+    assert!(!stdout.contains("Sources/Core/BUILD.bazel"), "{}", stdout);
+}
+
+#[test]
+fn since_tag_ignores_uncommitted_changes() {
+    let dir = tagged_repo();
+    git(dir.path(), &["tag", "v1.1"]);
+    fs::write(dir.path().join("Sources/Core/BUILD.bazel"), CLEAN).unwrap();
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--since-tag", "v1.1", "--root", root]);
+
+    assert!(output.status.success(), "{:?}", output);
+}
+
+#[test]
+fn since_tag_conflicts_with_git_changed_only() {
+    let dir = tagged_repo();
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&[
+        "--since-tag",
+        "v1.0",
+        "--git-changed-only",
+        "HEAD",
+        "--root",
+        root,
+    ]);
+
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,60 @@
+use std::fs;
+
+use umbra_build_fixer::checks::attribute_order::{check_attribute_order, fix_sorted_attributes};
+use umbra_build_fixer::starlark::formatter::format_build_file;
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const INPUT: &str = include_str!("fixtures/sort_attributes/input.BUILD");
+const EXPECTED: &str = include_str!("fixtures/sort_attributes/expected.BUILD");
+
+#[test]
+fn sorts_to_golden_output() {
+    assert_eq!(fix_sorted_attributes(INPUT), EXPECTED);
+}
+
+#[test]
+fn sorted_file_is_unchanged() {
+    assert_eq!(fix_sorted_attributes(EXPECTED), EXPECTED);
+    assert_eq!(check_attribute_order(EXPECTED), None);
+}
+
+#[test]
+fn check_names_unordered_targets() {
+    let (issue, message) = check_attribute_order(INPUT).unwrap();
+
+    assert_eq!(issue, BuildIssue::UnorderedAttributes);
+    assert_eq!(
+        message,
+        "attributes of \"Core\", \"CoreTests\" are not in canonical order"
+    );
+}
+
+#[test]
+fn attributes_are_left_alone_without_flag() {
+    let dir = workspace(&[("Sources/Core", INPUT)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--root", root]);
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+
+    let output = umbra_fix(&["--check", "--sort-attributes", "--root", root]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("[UnorderedAttributes]"));
+}
+
+#[test]
+fn sort_attributes_agrees_with_format() {
+    let dir = workspace(&[("Sources/Core", INPUT)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+    config.sort_attributes = true;
+    config.format = true;
+
+    fix_build_file(&path, &config).unwrap();
+
+    let content = fs::read_to_string(&path).unwrap();
+    assert_eq!(format_build_file(&content), content);
+    assert_eq!(check_attribute_order(&content), None);
+}
@@ -0,0 +1,223 @@
+use std::fs;
+
+use umbra_build_fixer::report::suppress::{parse_suppressions, Suppression, SuppressionScope};
+use umbra_build_fixer::{analyze_build_file_at, fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const TWO_LIBRARIES: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+package(default_visibility = ["//visibility:public"])
+
+# Sources are generated at build time
+# umbra-fix: disable=EmptySrcs
+swift_library(
+    name = "Generated",
+)
+
+swift_library(
+    name = "Extras",
+)
+"#;
+
+fn empty_srcs(target: &str) -> BuildIssue {
+    BuildIssue::EmptySrcs {
+        target: target.to_string(),
+        has_srcs: false,
+    }
+}
+
+fn issues(content: &str) -> Vec<BuildIssue> {
+    let dir = workspace(&[("Sources/Core", content)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    analyze_build_file_at(content, &path, &test_config(dir.path()))
+        .into_iter()
+        .map(|finding| finding.issue)
+        .collect()
+}
+
+#[test]
+fn rule_suppression_silences_only_the_next_rule() {
+    let dir = workspace(&[("Sources/Core", TWO_LIBRARIES)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert!(issues.contains(&&empty_srcs("Extras")), "{:?}", issues);
+    assert!(!issues.contains(&&empty_srcs("Generated")), "{:?}", issues);
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(
+        content.contains(
+            "# umbra-fix: disable=EmptySrcs\nswift_library(\n    name = \"Generated\",\n)\n"
+        ),
+        "{}",
+        content
+    );
+}
+
+#[test]
+fn file_suppression_silences_every_rule() {
+    let content = TWO_LIBRARIES.replace(
+        "# umbra-fix: disable=EmptySrcs",
+        "# umbra-fix: disable-file=EmptySrcs",
+    );
+
+    let issues = issues(&content);
+
+    assert!(!issues.contains(&empty_srcs("Extras")), "{:?}", issues);
+    assert!(!issues.contains(&empty_srcs("Generated")), "{:?}", issues);
+}
+
+#[test]
+fn suppressions_list_several_issues() {
+    let content = "# umbra-fix: disable-file=MissingPackageDeclaration, MissingSwiftLibraryLoad\nswift_library(\n    name = \"Core\",\n    srcs = glob([\"*.swift\"], allow_empty = True),\n)\n";
+
+    assert_eq!(issues(content), []);
+}
+
+#[test]
+fn suppression_of_an_unreported_issue_is_flagged() {
+    let content = TWO_LIBRARIES.replace(
+        "# umbra-fix: disable=EmptySrcs\n",
+        "# umbra-fix: disable=EmptySrcs\n# umbra-fix: disable=UnsortedDeps\n",
+    );
+
+    let issues = issues(&content);
+
+    assert!(
+        issues.contains(&BuildIssue::UnusedSuppression {
+            issue: "UnsortedDeps".to_string(),
+            line: 7,
+        }),
+        "{:?}",
+        issues
+    );
+    assert!(!issues.contains(&BuildIssue::UnusedSuppression {
+        issue: "EmptySrcs".to_string(),
+        line: 6,
+    }));
+}
+
+#[test]
+fn unused_file_suppression_is_flagged() {
+    let content = TWO_LIBRARIES.replace(
+        "# umbra-fix: disable=EmptySrcs",
+        "# umbra-fix: disable-file=ExportsAttribute",
+    );
+
+    let issues = issues(&content);
+
+    assert!(issues.contains(&BuildIssue::UnusedSuppression {
+        issue: "ExportsAttribute".to_string(),
+        line: 6,
+    }));
+    assert!(issues.contains(&empty_srcs("Generated")));
+}
+
+#[test]
+fn suppressions_are_parsed() {
+    assert_eq!(
+        parse_suppressions(TWO_LIBRARIES),
+        [Suppression {
+            issue: "EmptySrcs".to_string(),
+            scope: SuppressionScope::Rule {
+                target: Some("Generated".to_string()),
+                lines: Some(7..=9),
+            },
+            line: 6,
+        }]
+    );
+    assert!(parse_suppressions("# umbra-fix: removed -I/usr/include\n").is_empty());
+}
+
+#[test]
+fn rule_suppression_silences_issues_found_on_its_lines() {
+    let content = TWO_LIBRARIES
+        .replace(
+            "# umbra-fix: disable=EmptySrcs",
+            "# umbra-fix: disable=LineTooLong",
+        )
+        .replace(
+            "    name = \"Generated\",\n",
+            "    name = \"Generated\",\n    srcs = [\"AVeryLongGeneratedSourceFileName.swift\", \"AnotherGeneratedSource.swift\"],\n",
+        );
+    let dir = workspace(&[("Sources/Core", &content)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let mut config = test_config(dir.path());
+    config.max_line_length = Some(80);
+
+    let issues: Vec<BuildIssue> = analyze_build_file_at(&content, &path, &config)
+        .into_iter()
+        .map(|finding| finding.issue)
+        .collect();
+
+    assert!(
+        !issues
+            .iter()
+            .any(|issue| matches!(issue, BuildIssue::LineTooLong { .. })),
+        "{:?}",
+        issues
+    );
+    assert!(
+        !issues
+            .iter()
+            .any(|issue| matches!(issue, BuildIssue::UnusedSuppression { .. })),
+        "{:?}",
+        issues
+    );
+}
+
+#[test]
+fn rule_suppression_of_a_file_wide_issue_is_flagged() {
+    let content = TWO_LIBRARIES
+        .replace(
+            "# umbra-fix: disable=EmptySrcs",
+            "# umbra-fix: disable=GlobWithoutAllowEmpty",
+        )
+        .replace(
+            "    name = \"Generated\",\n",
+            "    name = \"Generated\",\n    srcs = glob([\"*.swift\"]),\n",
+        );
+    let dir = workspace(&[("Sources/Core", &content)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let findings = analyze_build_file_at(&content, &path, &test_config(dir.path()));
+
+    // One glob's suppression would silence the finding for every glob
+    assert!(findings
+        .iter()
+        .any(|finding| finding.issue == BuildIssue::GlobWithoutAllowEmpty));
+    let unused = findings
+        .iter()
+        .find(|finding| matches!(finding.issue, BuildIssue::UnusedSuppression { .. }))
+        .unwrap();
+    assert!(
+        unused
+            .message
+            .contains("use disable-file=GlobWithoutAllowEmpty"),
+        "{}",
+        unused.message
+    );
+}
+
+#[test]
+fn rule_suppression_leaves_issues_without_a_target_on_other_rules() {
+    let content = TWO_LIBRARIES
+        .replace(
+            "# umbra-fix: disable=EmptySrcs",
+            "# umbra-fix: disable=GlobWithoutAllowEmpty",
+        )
+        .replace(
+            "    name = \"Extras\",\n",
+            "    name = \"Extras\",\n    srcs = glob([\"*.swift\"]),\n",
+        );
+
+    let issues = issues(&content);
+
+    assert!(issues.contains(&BuildIssue::GlobWithoutAllowEmpty));
+    assert!(issues.contains(&BuildIssue::UnusedSuppression {
+        issue: "GlobWithoutAllowEmpty".to_string(),
+        line: 6,
+    }));
+}
@@ -0,0 +1,44 @@
+use std::path::Path;
+
+use umbra_build_fixer::swift_imports::{parse_swift_imports, SwiftImport};
+
+fn imports(fixture: &str) -> Vec<SwiftImport> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("devtools/build/fixers/tests/fixtures/swift")
+        .join(fixture);
+    parse_swift_imports(&path).unwrap()
+}
+
+fn modules(imports: &[SwiftImport]) -> Vec<&str> {
+    imports
+        .iter()
+        .map(|import| import.module.as_str())
+        .collect()
+}
+
+#[test]
+fn submodule_imports_resolve_to_unique_top_level_modules() {
+    let imports = imports("Submodules.swift");
+
+    assert_eq!(modules(&imports), ["Foundation", "CoreServices"]);
+    assert!(imports.iter().all(|import| !import.testable));
+}
+
+#[test]
+fn imports_under_can_import_are_platform_conditional() {
+    let imports = imports("Conditional.swift");
+
+    assert_eq!(modules(&imports), ["SwiftUI", "UIKit", "AppKit", "os"]);
+    let conditional: Vec<bool> = imports.iter().map(|i| i.platform_conditional).collect();
+    // `#if DEBUG` is a build condition, not a platform check
+    assert_eq!(conditional, [false, true, true, false]);
+}
+
+#[test]
+fn imports_between_mark_sections_are_found() {
+    let imports = imports("MarkedTests.swift");
+
+    assert_eq!(modules(&imports), ["XCTest", "UmbraCore", "UmbraErrors"]);
+    let testable: Vec<bool> = imports.iter().map(|i| i.testable).collect();
+    assert_eq!(testable, [false, true, false]);
+}
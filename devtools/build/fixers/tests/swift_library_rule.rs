@@ -0,0 +1,105 @@
+use umbra_build_fixer::schema::swift_library::{parse_swift_library, AttributeError};
+use umbra_build_fixer::starlark::ast::{parse, AttrValue, Attribute};
+use umbra_build_fixer::{analyze_build_file, BuildIssue};
+
+use crate::common::test_config;
+
+fn attrs(rule: &str) -> Vec<Attribute> {
+    let file = parse(rule).unwrap();
+    file.rules[0].attrs.clone()
+}
+
+#[test]
+fn attributes_are_typed() {
+    let library = parse_swift_library(&attrs(
+        r#"swift_library(
+    name = "Core",
+    module_name = "UmbraCore",
+    srcs = glob(["*.swift"]) + select({"//:debug": ["Debug.swift"], "//conditions:default": []}),
+    deps = COMMON_DEPS + ["//Sources/Foundation"],
+    generates_header = True,
+    testonly = 0,
+    visibility = ["//visibility:public"],
+)
+"#,
+    ))
+    .unwrap();
+
+    assert_eq!(library.name, "Core");
+    assert_eq!(library.module_name.as_deref(), Some("UmbraCore"));
+    assert!(matches!(library.srcs, Some(AttrValue::Concat(_))));
+    assert!(library.deps.is_some());
+    assert_eq!(library.hdrs, None);
+    assert_eq!(library.generates_header, Some(true));
+    assert_eq!(library.testonly, Some(false));
+    assert!(!library.is_testonly());
+}
+
+#[test]
+fn missing_name_is_an_error() {
+    let errors =
+        parse_swift_library(&attrs(r#"swift_library(srcs = ["Core.swift"])"#)).unwrap_err();
+
+    assert_eq!(errors, [AttributeError::Missing { attribute: "name" }]);
+}
+
+#[test]
+fn every_type_mismatch_is_reported() {
+    let errors = parse_swift_library(&attrs(
+        r#"swift_library(
+    name = "Core",
+    srcs = "Core.swift",
+    testonly = "yes",
+    visibility = "//visibility:public",
+    copts = None,
+)
+"#,
+    ))
+    .unwrap_err();
+
+    let wrong_type = |attribute: &str, expected| AttributeError::WrongType {
+        attribute: attribute.to_string(),
+        expected,
+    };
+    assert_eq!(
+        errors,
+        [
+            wrong_type("srcs", "a list"),
+            wrong_type("copts", "a list"),
+            wrong_type("testonly", "True or False"),
+            wrong_type("visibility", "a list"),
+        ]
+    );
+    assert_eq!(errors[0].to_string(), "srcs should be a list");
+}
+
+#[test]
+fn invalid_rules_are_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let content = r#"swift_library(
+    name = "Core",
+    srcs = "Core.swift",
+)
+"#;
+
+    let findings = analyze_build_file(content, &test_config(dir.path()));
+
+    let finding = findings
+        .iter()
+        .find(|finding| matches!(finding.issue, BuildIssue::InvalidSwiftLibrary { .. }))
+        .unwrap();
+    assert_eq!(
+        finding.issue,
+        BuildIssue::InvalidSwiftLibrary {
+            target: Some("Core".to_string()),
+            errors: vec!["srcs should be a list".to_string()],
+        }
+    );
+    assert!(
+        finding
+            .message
+            .contains("\"Core\" has invalid attributes, so its srcs"),
+        "{}",
+        finding.message
+    );
+}
@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::Path;
+
+use umbra_build_fixer::checks::swift_library::check_swift_settings;
+use umbra_build_fixer::starlark::ast::parse;
+use umbra_build_fixer::swift_version::{required_swift_version, uses_concurrency};
+use umbra_build_fixer::BuildIssue;
+
+use crate::common::workspace;
+
+const KEYS: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+swift_library(
+    name = "Keys",
+    srcs = glob(["*.swift"], allow_empty = True),
+)
+"#;
+
+fn fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("devtools/build/fixers/tests/fixtures/swift")
+        .join(name);
+    fs::read_to_string(path).unwrap()
+}
+
+// A package with the given fixture Swift files next to `content`
+fn keys_package(content: &str, sources: &[&str]) -> tempfile::TempDir {
+    let dir = workspace(&[("Sources/Keys", content)]);
+    for source in sources {
+        fs::write(
+            dir.path().join("Sources/Keys").join(source),
+            fixture(source),
+        )
+        .unwrap();
+    }
+    dir
+}
+
+fn issues(dir: &Path, content: &str) -> Vec<BuildIssue> {
+    check_swift_settings(&parse(content).unwrap(), &dir.join("Sources/Keys"))
+        .into_iter()
+        .map(|(issue, _)| issue)
+        .collect()
+}
+
+#[test]
+fn concurrency_keywords_are_detected() {
+    assert!(uses_concurrency(&fixture("KeyStore.swift")));
+    assert!(uses_concurrency("@MainActor\nfinal class Model {}\n"));
+    // Mentions in comments and properties named actor don't count
+    assert!(!uses_concurrency(&fixture("KeyFormatter.swift")));
+}
+
+#[test]
+fn concurrency_needs_swift_5_5() {
+    let dir = keys_package(KEYS, &["KeyStore.swift", "KeyFormatter.swift"]);
+    let package = dir.path().join("Sources/Keys");
+
+    let version = required_swift_version(&package, &["KeyStore.swift".to_string()]).unwrap();
+    assert_eq!(version, Some("5.5"));
+    let version = required_swift_version(&package, &["KeyFormatter.swift".to_string()]).unwrap();
+    assert_eq!(version, None);
+}
+
+#[test]
+fn library_using_concurrency_needs_the_setting() {
+    let dir = keys_package(KEYS, &["KeyStore.swift", "KeyFormatter.swift"]);
+
+    assert_eq!(
+        issues(dir.path(), KEYS),
+        [BuildIssue::MissingSwiftSetting {
+            target: "Keys".to_string(),
+            setting: "swift.strict-concurrency=complete".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn setting_or_synchronous_sources_are_not_flagged() {
+    let dir = keys_package(KEYS, &["KeyFormatter.swift"]);
+    assert!(issues(dir.path(), KEYS).is_empty());
+
+    let configured = KEYS.replace(
+        "    srcs",
+        "    swift_settings = [swift_feature_requirement(\"swift.strict-concurrency=complete\")],\n    srcs",
+    );
+    let dir = keys_package(&configured, &["KeyStore.swift"]);
+    assert!(issues(dir.path(), &configured).is_empty());
+
+    let copts = KEYS.replace(
+        "    srcs",
+        "    copts = [\"-strict-concurrency=complete\"],\n    srcs",
+    );
+    assert!(issues(dir.path(), &copts).is_empty());
+
+    let upcoming = KEYS.replace(
+        "    srcs",
+        "    copts = [\"-enable-upcoming-feature\", \"StrictConcurrency\"],\n    srcs",
+    );
+    assert!(issues(dir.path(), &upcoming).is_empty());
+}
+
+#[test]
+fn partial_strict_concurrency_is_flagged() {
+    let dir = keys_package(KEYS, &["KeyStore.swift"]);
+
+    for level in ["minimal", "targeted"] {
+        let copts = KEYS.replace(
+            "    srcs",
+            &format!("    copts = [\"-strict-concurrency={}\"],\n    srcs", level),
+        );
+        assert_eq!(issues(dir.path(), &copts).len(), 1, "{}", level);
+    }
+}
@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+
+use umbra_build_fixer::label_resolver::AbsoluteLabel;
+use umbra_build_fixer::target_graph::build_target_graph;
+use umbra_build_fixer::{find_build_files, Config};
+
+use crate::common::{workspace, write_build_file};
+
+const CORE: &str = include_str!("fixtures/target_graph/core.BUILD");
+const LOGGING: &str = include_str!("fixtures/target_graph/logging.BUILD");
+const APP: &str = include_str!("fixtures/target_graph/app.BUILD");
+
+fn label(package: &str, target: &str) -> AbsoluteLabel {
+    AbsoluteLabel {
+        repository: None,
+        package: package.to_string(),
+        target: target.to_string(),
+    }
+}
+
+fn three_packages() -> tempfile::TempDir {
+    workspace(&[
+        ("Sources/Core", CORE),
+        ("Sources/Logging", LOGGING),
+        ("Apps/App", APP),
+    ])
+}
+
+fn build_files(root: &Path) -> Vec<PathBuf> {
+    find_build_files(&Config::new(root)).unwrap()
+}
+
+#[test]
+fn every_named_target_is_in_the_graph() {
+    let dir = three_packages();
+
+    let (graph, _) = build_target_graph(dir.path(), &build_files(dir.path()));
+
+    let labels: Vec<String> = graph
+        .targets()
+        .map(|(label, _)| label.to_string())
+        .collect();
+    assert_eq!(
+        labels,
+        [
+            "//Apps/App:App",
+            "//Sources/Core:Core",
+            "//Sources/Core:CoreTests",
+            "//Sources/Logging:Logging",
+        ]
+    );
+}
+
+#[test]
+fn lookup_returns_rule_attributes_and_file() {
+    let dir = three_packages();
+
+    let (graph, _) = build_target_graph(dir.path(), &build_files(dir.path()));
+
+    let core = graph.lookup(&label("Sources/Core", "CoreTests")).unwrap();
+    assert_eq!(core.rule_name, "swift_test");
+    assert_eq!(
+        core.build_file_path,
+        dir.path().join("Sources/Core/BUILD.bazel")
+    );
+    assert!(core.attrs.iter().any(|attr| attr.key == "deps"));
+    assert!(graph.lookup(&label("Sources/Core", "Missing")).is_none());
+}
+
+#[test]
+fn reverse_deps_resolve_every_label_form() {
+    let dir = three_packages();
+
+    let (graph, _) = build_target_graph(dir.path(), &build_files(dir.path()));
+
+    assert_eq!(
+        graph.reverse_deps(&label("Sources/Core", "Core")),
+        [
+            label("Apps/App", "App"),
+            label("Sources/Core", "CoreTests"),
+            label("Sources/Logging", "Logging"),
+        ]
+    );
+    // Listed in both deps and a select() branch, reported once
+    assert_eq!(
+        graph.reverse_deps(&label("Sources/Logging", "Logging")),
+        [label("Apps/App", "App")]
+    );
+    assert!(graph.reverse_deps(&label("Apps/App", "App")).is_empty());
+}
+
+#[test]
+fn deps_include_external_labels() {
+    let dir = three_packages();
+
+    let (graph, _) = build_target_graph(dir.path(), &build_files(dir.path()));
+
+    let external = AbsoluteLabel {
+        repository: Some("swiftpkg_swift_log".to_string()),
+        package: String::new(),
+        target: "Logging".to_string(),
+    };
+    assert_eq!(
+        graph.deps(&label("Sources/Logging", "Logging")),
+        [label("Sources/Core", "Core"), external.clone()]
+    );
+    assert!(graph.lookup(&external).is_none());
+    assert_eq!(
+        graph.reverse_deps(&external),
+        [label("Sources/Logging", "Logging")]
+    );
+}
+
+#[test]
+fn unparsable_files_are_reported_and_left_out() {
+    let dir = three_packages();
+    let broken = write_build_file(
+        dir.path(),
+        "Sources/Broken",
+        "swift_library(\n    name = \n",
+    );
+    let also_broken = write_build_file(dir.path(), "Sources/Other", "[x for x in y]\n");
+
+    let (graph, errors) = build_target_graph(dir.path(), &build_files(dir.path()));
+
+    let paths: Vec<_> = errors.iter().map(|(path, _)| path.clone()).collect();
+    assert_eq!(paths, [broken, also_broken]);
+    // The files that parse still make up the graph
+    assert!(graph.lookup(&label("Sources/Core", "Core")).is_some());
+}
@@ -0,0 +1,121 @@
+use std::fs;
+
+use umbra_build_fixer::checks::target_names::{check_target_names, fix_target_name};
+use umbra_build_fixer::starlark::ast::parse;
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const MISNAMED: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library", "swift_test")
+
+package(default_visibility = ["//visibility:public"])
+
+swift_library(
+    name = "CoreLib",
+    srcs = glob(["*.swift"], allow_empty = True),
+)
+
+# Both spellings of the label are listed to check that each is renamed
+# umbra-fix: disable=RepeatedDep
+swift_test(
+    name = "CoreTests",
+    srcs = glob(["Tests/*.swift"], allow_empty = True),
+    testonly = True,
+    deps = [
+        ":CoreLib",
+        "//Sources/Core:CoreLib",
+        "//Sources/Other:CoreLib",
+        "//Vendor/Core:CoreLib",
+    ],
+)
+"#;
+
+#[test]
+fn check_is_opt_in() {
+    let dir = workspace(&[("Sources/Core", MISNAMED)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(!report
+        .findings
+        .iter()
+        .any(|finding| matches!(finding.issue, BuildIssue::InconsistentTargetName { .. })));
+}
+
+#[test]
+fn target_is_renamed_with_its_references() {
+    let dir = workspace(&[("Sources/Core", MISNAMED)]);
+    fs::write(
+        dir.path().join("umbra-fix.toml"),
+        "check_target_names = true\n",
+    )
+    .unwrap();
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(report.findings.iter().any(|finding| finding.issue
+        == BuildIssue::InconsistentTargetName {
+            target: "CoreLib".to_string(),
+            expected: "Core".to_string(),
+            package: "Sources/Core".to_string(),
+        }));
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(fixed.contains("    name = \"Core\",\n"), "{}", fixed);
+    assert!(fixed.contains("        \":Core\",\n"), "{}", fixed);
+    assert!(
+        fixed.contains("        \"//Sources/Core:Core\",\n"),
+        "{}",
+        fixed
+    );
+    // Targets of other packages that happen to share the old name
+    assert!(
+        fixed.contains("        \"//Sources/Other:CoreLib\",\n"),
+        "{}",
+        fixed
+    );
+    assert!(
+        fixed.contains("        \"//Vendor/Core:CoreLib\",\n"),
+        "{}",
+        fixed
+    );
+}
+
+#[test]
+fn fix_only_touches_references_to_the_target() {
+    let content = "swift_library(name = 'Old', deps = [':Older'])\nfilegroup(name = 'Files', srcs = [':Old'])\n";
+
+    let fixed = fix_target_name(content, "Old", "New", "Sources/Old");
+
+    assert_eq!(
+        fixed,
+        "swift_library(name = \"New\", deps = [':Older'])\nfilegroup(name = 'Files', srcs = [\":New\"])\n"
+    );
+}
+
+#[test]
+fn packages_with_several_libraries_are_skipped() {
+    let content = format!(
+        "{}\nswift_library(name = \"CoreExtras\", srcs = [])\n",
+        MISNAMED
+    );
+    let dir = workspace(&[("Sources/Core", &content)]);
+    let package = dir.path().join("Sources/Core");
+
+    assert!(check_target_names(&parse(&content).unwrap(), &package, dir.path()).is_none());
+    assert!(check_target_names(&parse(MISNAMED).unwrap(), &package, dir.path()).is_some());
+}
+
+#[test]
+fn rename_onto_an_existing_name_is_skipped() {
+    let content = MISNAMED.replace("\"CoreTests\"", "\"Core\"");
+    let dir = workspace(&[("Sources/Core", &content)]);
+
+    assert!(check_target_names(
+        &parse(&content).unwrap(),
+        &dir.path().join("Sources/Core"),
+        dir.path()
+    )
+    .is_none());
+}
@@ -0,0 +1,66 @@
+use std::fs;
+
+use umbra_build_fixer::checks::swift_library::check_test_files_in_library;
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const LIBRARY: &str = include_str!("fixtures/test_files_in_library.BUILD");
+
+#[test]
+fn test_file_in_srcs_glob_is_reported() {
+    let dir = workspace(&[("Sources/Core", LIBRARY)]);
+    let package = dir.path().join("Sources/Core");
+    fs::write(package.join("Core.swift"), "").unwrap();
+    fs::write(package.join("CoreTests.swift"), "import XCTest\n").unwrap();
+
+    let (issue, message) =
+        check_test_files_in_library(&package.join("BUILD.bazel"), LIBRARY).unwrap();
+
+    assert_eq!(
+        issue,
+        BuildIssue::TestFilesInLibrary {
+            target: "Core".to_string(),
+            files: vec!["CoreTests.swift".to_string()],
+        }
+    );
+    assert!(message.contains("separate swift_test"), "{}", message);
+}
+
+#[test]
+fn issue_is_reported_but_not_fixed() {
+    let dir = workspace(&[("Sources/Core", LIBRARY)]);
+    let package = dir.path().join("Sources/Core");
+    fs::write(package.join("Core.swift"), "").unwrap();
+    fs::write(package.join("CoreSpec.swift"), "").unwrap();
+    let path = package.join("BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(report
+        .findings
+        .iter()
+        .any(|finding| matches!(finding.issue, BuildIssue::TestFilesInLibrary { .. })));
+    assert_eq!(fs::read_to_string(&path).unwrap(), LIBRARY);
+}
+
+#[test]
+fn excluded_and_testonly_sources_are_not_reported() {
+    let dir = workspace(&[("Sources/Core", LIBRARY)]);
+    let package = dir.path().join("Sources/Core");
+    fs::write(package.join("Core.swift"), "").unwrap();
+    fs::write(package.join("CoreTests.swift"), "").unwrap();
+    let path = package.join("BUILD.bazel");
+
+    let excluded = LIBRARY.replace(
+        "allow_empty = True",
+        "allow_empty = True, exclude = [\"*Tests.swift\"]",
+    );
+    assert_eq!(check_test_files_in_library(&path, &excluded), None);
+
+    let testonly = LIBRARY.replace(
+        "    name = \"Core\",\n",
+        "    name = \"Core\",\n    testonly = True,\n",
+    );
+    assert_eq!(check_test_files_in_library(&path, &testonly), None);
+}
@@ -0,0 +1,156 @@
+use std::fs;
+
+use umbra_build_fixer::checks::swift_library::{
+    check_custom_library, check_umbra_wrapper_attributes, convert_umbra_swift_library,
+};
+use umbra_build_fixer::starlark::ast::parse;
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace};
+
+const UMBRA_MACROS: &str = include_str!("fixtures/umbra_macros.BUILD");
+
+#[test]
+fn all_three_macros_are_converted_with_their_load() {
+    let converted = convert_umbra_swift_library(UMBRA_MACROS);
+
+    assert!(converted.starts_with(
+        "load(\"@build_bazel_rules_swift//swift:swift.bzl\", \"swift_binary\", \"swift_library\", \"swift_test\")\n\npackage("
+    ), "{}", converted);
+    assert!(converted.contains("\nswift_library(\n"), "{}", converted);
+    assert!(converted.contains("\nswift_test(\n"), "{}", converted);
+    assert!(converted.contains("\nswift_binary(\n"), "{}", converted);
+    assert!(!converted.contains("umbra_"), "{}", converted);
+}
+
+#[test]
+fn conversion_is_idempotent() {
+    let converted = convert_umbra_swift_library(UMBRA_MACROS);
+
+    assert_eq!(convert_umbra_swift_library(&converted), converted);
+    assert_eq!(check_custom_library(&converted), None);
+}
+
+#[test]
+fn check_names_each_macro_used() {
+    let (issue, message) = check_custom_library(UMBRA_MACROS).unwrap();
+
+    assert_eq!(issue, BuildIssue::CustomLibraryRule);
+    assert_eq!(
+        message,
+        "umbra_swift_library should be replaced with swift_library, \
+         umbra_swift_test should be replaced with swift_test, \
+         umbra_swift_binary should be replaced with swift_binary"
+    );
+}
+
+#[test]
+fn other_macros_stay_in_their_load() {
+    let content = r#"load("//bazel/macros:swift.bzl", "umbra_swift_test", "umbra_test_library")
+load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library")
+
+umbra_test_library(
+    name = "TestSupport",
+    srcs = ["Support.swift"],
+)
+
+umbra_swift_test(
+    name = "CoreTests",
+    srcs = ["CoreTests.swift"],
+)
+"#;
+
+    let converted = convert_umbra_swift_library(content);
+
+    assert!(
+        converted.starts_with(
+            r#"load("//bazel/macros:swift.bzl", "umbra_test_library")
+load("@build_bazel_rules_swift//swift:swift.bzl", "swift_library", "swift_test")
+"#
+        ),
+        "{}",
+        converted
+    );
+    assert!(
+        converted.contains("\numbra_test_library(\n"),
+        "{}",
+        converted
+    );
+    assert!(converted.contains("\nswift_test(\n"), "{}", converted);
+    assert_eq!(convert_umbra_swift_library(&converted), converted);
+}
+
+#[test]
+fn macro_load_is_merged_into_existing_rules_swift_load() {
+    let content = r#"load("@rules_swift//swift:swift.bzl", "swift_library")
+load("//:swift_rules.bzl", "umbra_swift_binary")
+
+swift_library(
+    name = "Core",
+    srcs = ["Core.swift"],
+)
+
+umbra_swift_binary(
+    name = "Tool",
+    srcs = ["main.swift"],
+)
+"#;
+
+    let converted = convert_umbra_swift_library(content);
+
+    assert_eq!(
+        converted,
+        r#"load("@rules_swift//swift:swift.bzl", "swift_library", "swift_binary")
+
+swift_library(
+    name = "Core",
+    srcs = ["Core.swift"],
+)
+
+swift_binary(
+    name = "Tool",
+    srcs = ["main.swift"],
+)
+"#
+    );
+}
+
+#[test]
+fn wrapper_attributes_are_flagged() {
+    let issues = check_umbra_wrapper_attributes(&parse(UMBRA_MACROS).unwrap());
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(
+        issues[0].0,
+        BuildIssue::UnsupportedAttribute {
+            target: "Core".to_string(),
+            attribute: "module_visibility".to_string(),
+            rule: "swift_library".to_string(),
+        }
+    );
+    assert!(issues[0].0.is_fixable());
+}
+
+#[test]
+fn fixing_converts_rules_and_comments_removed_attributes() {
+    let dir = workspace(&[("Sources/Core", UMBRA_MACROS)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    let config = test_config(dir.path());
+
+    let report = fix_build_file(&path, &config).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| f.issue.name()).collect();
+    assert_eq!(issues, ["CustomLibraryRule", "UnsupportedAttribute"]);
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(
+        content.contains(
+            "# module_visibility was removed; swift_library doesn't support it\nswift_library(\n"
+        ),
+        "{}",
+        content
+    );
+    assert!(!content.contains("module_visibility ="), "{}", content);
+
+    let report = fix_build_file(&path, &config).unwrap();
+    assert!(report.findings.is_empty(), "{:?}", report.findings);
+}
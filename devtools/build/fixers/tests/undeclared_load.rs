@@ -0,0 +1,213 @@
+use umbra_build_fixer::checks::loads::{check_undeclared_loads, BUILTIN_SYMBOLS};
+use umbra_build_fixer::starlark::ast::parse;
+use umbra_build_fixer::{fix_build_file, BuildIssue, RunMode};
+
+use crate::common::{test_config, workspace};
+
+const BINARY_LOAD_ONLY: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_binary")
+
+swift_binary(
+    name = "Tool",
+    srcs = ["main.swift"],
+)
+
+swift_test(
+    name = "ToolTests",
+    srcs = ["ToolTests.swift"],
+)
+"#;
+
+const MULTI_SYMBOL_LOAD: &str = r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_binary", test = "swift_test")
+load("@rules_apple//apple:macos.bzl", "macos_application")
+
+swift_binary(
+    name = "Tool",
+    srcs = ["main.swift"],
+)
+
+test(
+    name = "ToolTests",
+    srcs = ["ToolTests.swift"],
+)
+
+macos_application(
+    name = "App",
+    deps = [":Tool"],
+)
+"#;
+
+const BUILTINS: &str = r#"package(default_visibility = ["//visibility:public"])
+
+licenses(["notice"])
+
+exports_files(["Info.plist"])
+
+filegroup(
+    name = "Resources",
+    srcs = glob(["Resources/**"], allow_empty = True),
+)
+
+config_setting(
+    name = "debug",
+    values = {"compilation_mode": "dbg"},
+)
+
+alias(
+    name = "Default",
+    actual = select({
+        ":debug": ":Resources",
+        "//conditions:default": ":Resources",
+    }),
+)
+
+genrule(
+    name = "Version",
+    outs = ["version.txt"],
+    cmd = "echo " + str(len(package_name())) + " > $@",
+)
+
+test_suite(name = "AllTests")
+"#;
+
+const NATIVE_RULES: &str = r#"package(default_visibility = ["//visibility:public"])
+
+objc_library(
+    name = "Bridge",
+    srcs = ["Bridge.m"],
+    hdrs = ["Bridge.h"],
+    deps = [":Crypto"],
+)
+
+cc_library(
+    name = "Crypto",
+    srcs = ["crypto.c"],
+)
+
+cc_test(
+    name = "CryptoTests",
+    srcs = ["crypto_test.c"],
+    deps = [":Crypto"],
+)
+
+sh_test(
+    name = "Smoke",
+    srcs = ["smoke.sh"],
+)
+
+proto_library(
+    name = "Messages",
+    srcs = ["messages.proto"],
+)
+
+py_binary(
+    name = "Generate",
+    srcs = ["generate.py"],
+)
+
+java_library(
+    name = "Bindings",
+    srcs = ["Bindings.java"],
+)
+"#;
+
+fn undeclared(content: &str) -> Vec<BuildIssue> {
+    check_undeclared_loads(&parse(content).unwrap())
+        .into_iter()
+        .map(|(issue, _)| issue)
+        .collect()
+}
+
+#[test]
+fn call_of_symbol_missing_from_load_is_flagged() {
+    let issues = check_undeclared_loads(&parse(BINARY_LOAD_ONLY).unwrap());
+
+    assert_eq!(issues.len(), 1);
+    assert_eq!(
+        issues[0].0,
+        BuildIssue::UndeclaredLoad {
+            symbol: "swift_test".to_string()
+        }
+    );
+    assert!(issues[0].1.contains("line 8"), "{}", issues[0].1);
+    assert!(!issues[0].0.is_fixable());
+}
+
+#[test]
+fn every_symbol_of_multi_symbol_loads_is_declared() {
+    assert!(undeclared(MULTI_SYMBOL_LOAD).is_empty());
+}
+
+#[test]
+fn aliased_symbol_is_declared_under_its_alias_only() {
+    let content = MULTI_SYMBOL_LOAD.replace("\ntest(", "\nswift_test(");
+
+    assert_eq!(
+        undeclared(&content),
+        [BuildIssue::UndeclaredLoad {
+            symbol: "swift_test".to_string()
+        }]
+    );
+}
+
+#[test]
+fn builtins_need_no_load() {
+    assert!(undeclared(BUILTINS).is_empty());
+    for builtin in ["package", "licenses", "filegroup", "glob"] {
+        assert!(BUILTIN_SYMBOLS.contains(&builtin), "{}", builtin);
+    }
+}
+
+#[test]
+fn native_rules_need_no_load() {
+    let dir = workspace(&[("Sources/Bridge", NATIVE_RULES)]);
+    let path = dir.path().join("Sources/Bridge/BUILD.bazel");
+
+    assert!(undeclared(NATIVE_RULES).is_empty());
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+    assert_eq!(report.findings, []);
+}
+
+#[test]
+fn nested_calls_are_checked() {
+    let content = r#"filegroup(
+    name = "Docs",
+    srcs = glob(["*.md"]) + docs_srcs(),
+)
+
+filegroup(
+    name = "MoreDocs",
+    srcs = docs_srcs(),
+)
+"#;
+
+    assert_eq!(
+        undeclared(content),
+        [BuildIssue::UndeclaredLoad {
+            symbol: "docs_srcs".to_string()
+        }]
+    );
+}
+
+#[test]
+fn swift_library_is_left_to_missing_load_check() {
+    let dir = workspace(&[(
+        "Sources/Core",
+        r#"load("@build_bazel_rules_swift//swift:swift.bzl", "swift_binary")
+
+swift_library(
+    name = "Core",
+    srcs = glob(["*.swift"], allow_empty = True),
+)
+"#,
+    )]);
+    let mut config = test_config(dir.path());
+    config.mode = RunMode::DryRun;
+
+    let report = fix_build_file(&dir.path().join("Sources/Core/BUILD.bazel"), &config).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert!(issues.contains(&&BuildIssue::MissingSwiftLibraryLoad));
+    assert!(!issues
+        .iter()
+        .any(|issue| matches!(issue, BuildIssue::UndeclaredLoad { .. })));
+}
@@ -0,0 +1,111 @@
+use std::fs::{self, File};
+use std::time::{Duration, SystemTime};
+
+use umbra_build_fixer::fix_build_file;
+use umbra_build_fixer::undo::{backup_path, find_backups, parse_duration, restore_backup};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+#[test]
+fn undo_restores_files_fixed_with_backup() {
+    let dir = workspace(&[("Sources/Core", DIRTY), ("Sources/Errors", DIRTY)]);
+    let mut config = test_config(dir.path());
+    config.backup = true;
+    for package in ["Sources/Core", "Sources/Errors"] {
+        let path = dir.path().join(package).join("BUILD.bazel");
+        assert!(fix_build_file(&path, &config).unwrap().modified);
+        assert_ne!(fs::read_to_string(&path).unwrap(), DIRTY);
+    }
+
+    let output = umbra_fix(&["undo", "--root", dir.path().to_str().unwrap()]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Restored 2 files from backups"),
+        "{}",
+        stdout
+    );
+    for package in ["Sources/Core", "Sources/Errors"] {
+        let path = dir.path().join(package).join("BUILD.bazel");
+        assert_eq!(fs::read_to_string(&path).unwrap(), DIRTY);
+        assert!(!backup_path(&path).exists());
+    }
+}
+
+#[test]
+fn dry_run_lists_backups_without_restoring() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+    assert!(umbra_fix(&["--root", root, "--backup"]).status.success());
+    let fixed = fs::read_to_string(&path).unwrap();
+
+    let output = umbra_fix(&["undo", "--root", root, "--dry-run"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Would restore: "), "{}", stdout);
+    assert!(
+        stdout.contains("Would restore 1 files from backups"),
+        "{}",
+        stdout
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), fixed);
+    assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), DIRTY);
+}
+
+#[test]
+fn older_than_skips_recent_backups() {
+    let dir = workspace(&[("Sources/Core", DIRTY), ("Sources/Errors", DIRTY)]);
+    let mut config = test_config(dir.path());
+    config.backup = true;
+    let old = dir.path().join("Sources/Core/BUILD.bazel");
+    let recent = dir.path().join("Sources/Errors/BUILD.bazel");
+    fix_build_file(&old, &config).unwrap();
+    fix_build_file(&recent, &config).unwrap();
+    let two_hours_ago = SystemTime::now() - Duration::from_secs(2 * 60 * 60);
+    File::options()
+        .write(true)
+        .open(backup_path(&old))
+        .unwrap()
+        .set_modified(two_hours_ago)
+        .unwrap();
+
+    let backups = find_backups(dir.path(), Some(Duration::from_secs(60 * 60))).unwrap();
+
+    assert_eq!(backups.len(), 1);
+    assert_eq!(backups[0].original, old);
+    restore_backup(&backups[0]).unwrap();
+    assert_eq!(fs::read_to_string(&old).unwrap(), DIRTY);
+    assert_ne!(fs::read_to_string(&recent).unwrap(), DIRTY);
+    assert!(backup_path(&recent).exists());
+}
+
+#[test]
+fn fixing_without_backup_writes_no_bak_files() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(!backup_path(&path).exists());
+    assert!(find_backups(dir.path(), None).unwrap().is_empty());
+}
+
+#[test]
+fn durations_are_parsed() {
+    assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+    assert_eq!(parse_duration("15m"), Ok(Duration::from_secs(15 * 60)));
+    assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(2 * 60 * 60)));
+    assert_eq!(
+        parse_duration("7d"),
+        Ok(Duration::from_secs(7 * 24 * 60 * 60))
+    );
+    assert!(parse_duration("2w").is_err());
+    assert!(parse_duration("h").is_err());
+    assert!(parse_duration("").is_err());
+    assert!(parse_duration("999999999999999999d").is_err());
+}
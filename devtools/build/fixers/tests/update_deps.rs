@@ -0,0 +1,208 @@
+use std::fs;
+use std::path::Path;
+
+use umbra_build_fixer::dependency_updater::{
+    read_manifest, rename_labels, update_deps, LabelRename, DEPS_UPDATE_FILE_NAME,
+};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const APP: &str = include_str!("fixtures/update_deps/app.BUILD");
+const OLD: &str = include_str!("fixtures/update_deps/old.BUILD");
+const MANIFEST: &str = include_str!("fixtures/update_deps/deps_update.toml");
+
+fn rename(from: &str, to: &str) -> LabelRename {
+    LabelRename {
+        from: from.to_string(),
+        to: to.to_string(),
+    }
+}
+
+#[test]
+fn manifest_lists_renames() {
+    let dir = workspace(&[]);
+    let path = dir.path().join(DEPS_UPDATE_FILE_NAME);
+    fs::write(&path, MANIFEST).unwrap();
+
+    let manifest = read_manifest(&path).unwrap();
+
+    assert_eq!(
+        manifest.renames,
+        [rename("//Sources/Old:Old", "//Sources/New:New")]
+    );
+}
+
+#[test]
+fn relative_labels_in_manifest_are_rejected() {
+    let dir = workspace(&[]);
+    let path = dir.path().join(DEPS_UPDATE_FILE_NAME);
+    fs::write(
+        &path,
+        "renames = [{ from = \":Old\", to = \"//Sources/New\" }]\n",
+    )
+    .unwrap();
+
+    let err = read_manifest(&path).unwrap_err();
+
+    assert!(err.to_string().contains("\":Old\""), "{}", err);
+}
+
+#[test]
+fn only_exact_matches_are_renamed() {
+    let renames = [rename("//Sources/Old:Old", "//Sources/New:New")];
+
+    let (fixed, replaced) = rename_labels(APP, "Sources/App", Path::new("/repo"), &renames);
+
+    let lines: Vec<_> = replaced
+        .iter()
+        .map(|(line, from, _)| (*line, from.as_str()))
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            (7, "//Sources/Old:Old"),
+            (9, "//Sources/Old"),
+            (18, "//Sources/Old:Old")
+        ]
+    );
+    assert!(
+        fixed.contains("data = [\"//Sources/New:New\"],"),
+        "{}",
+        fixed
+    );
+    assert!(fixed.contains("\"//Sources/Old:OldTests\","), "{}", fixed);
+    assert!(fixed.contains("\"//Sources/Oldish:Oldish\","), "{}", fixed);
+    assert!(
+        fixed.contains("load(\"//Sources/Old:defs.bzl\""),
+        "{}",
+        fixed
+    );
+}
+
+#[test]
+fn relative_labels_and_loads_are_renamed() {
+    let renames = [
+        rename("//Sources/Old:Old", "//Sources/New:New"),
+        rename("//Sources/Old:defs.bzl", "//Sources/New:defs.bzl"),
+    ];
+
+    let (fixed, _) = rename_labels(OLD, "Sources/Old", Path::new("/repo"), &renames);
+    assert!(
+        fixed.contains("deps = [\"//Sources/New:New\"],"),
+        "{}",
+        fixed
+    );
+    // The rule's own name isn't a label reference
+    assert!(fixed.contains("name = \"Old\","), "{}", fixed);
+
+    let (fixed, _) = rename_labels(APP, "Sources/App", Path::new("/repo"), &renames);
+    assert!(
+        fixed.contains("load(\"//Sources/New:defs.bzl\", \"old_settings\")"),
+        "{}",
+        fixed
+    );
+}
+
+#[test]
+fn update_deps_rewrites_every_build_file() {
+    let dir = workspace(&[("Sources/App", APP), ("Sources/Old", OLD)]);
+    let renames = [rename("//Sources/Old:Old", "//Sources/New:New")];
+
+    let replacements = update_deps(&test_config(dir.path()), &renames, false).unwrap();
+
+    assert_eq!(replacements.len(), 4);
+    assert!(replacements[0].path.ends_with("Sources/App/BUILD.bazel"));
+    assert_eq!(replacements[3].from, ":Old");
+    let app = fs::read_to_string(dir.path().join("Sources/App/BUILD.bazel")).unwrap();
+    assert!(!app.contains("\"//Sources/Old\""), "{}", app);
+    let old = fs::read_to_string(dir.path().join("Sources/Old/BUILD.bazel")).unwrap();
+    assert!(old.contains("deps = [\"//Sources/New:New\"]"), "{}", old);
+}
+
+#[test]
+fn update_deps_command_logs_replacements() {
+    let dir = workspace(&[("Sources/App", APP), ("Sources/Old", OLD)]);
+    let root = dir.path().to_str().unwrap();
+    fs::write(dir.path().join(DEPS_UPDATE_FILE_NAME), MANIFEST).unwrap();
+
+    let output = umbra_fix(&["update-deps", "--root", root]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Sources/App/BUILD.bazel:9: //Sources/Old -> //Sources/New:New"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("Replaced 4 labels"), "{}", stdout);
+}
+
+#[test]
+fn update_deps_dry_run_leaves_files_untouched() {
+    let dir = workspace(&[("Sources/App", APP), ("Sources/Old", OLD)]);
+    let root = dir.path().to_str().unwrap();
+    let manifest = dir.path().join("renames.toml");
+    fs::write(&manifest, MANIFEST).unwrap();
+
+    let output = umbra_fix(&[
+        "update-deps",
+        "--manifest",
+        manifest.to_str().unwrap(),
+        "--root",
+        root,
+        "--dry-run",
+    ]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Would replace 4 labels"), "{}", stdout);
+    let app = fs::read_to_string(dir.path().join("Sources/App/BUILD.bazel")).unwrap();
+    assert_eq!(app, APP);
+}
+
+#[test]
+fn unresolvable_labels_are_an_error() {
+    let dir = workspace(&[("Sources/App", APP), ("Sources/Old", OLD)]);
+    let renames = [
+        rename("//Sources/Old:Old", "//Sources/New:New"),
+        rename("//Sources/Old:", "//Sources/New:New"),
+    ];
+
+    let err = update_deps(&test_config(dir.path()), &renames, false).unwrap_err();
+
+    assert!(err.to_string().contains("\"//Sources/Old:\""), "{}", err);
+    let app = fs::read_to_string(dir.path().join("Sources/App/BUILD.bazel")).unwrap();
+    assert_eq!(app, APP);
+}
+
+#[test]
+fn unresolvable_replacements_are_an_error() {
+    let dir = workspace(&[("Sources/App", APP), ("Sources/Old", OLD)]);
+    let renames = [rename("//Sources/Old:Old", "//Sources/New:")];
+
+    let err = update_deps(&test_config(dir.path()), &renames, false).unwrap_err();
+
+    assert!(err.to_string().contains("\"//Sources/New:\""), "{}", err);
+    let app = fs::read_to_string(dir.path().join("Sources/App/BUILD.bazel")).unwrap();
+    assert_eq!(app, APP);
+}
+
+#[test]
+fn excluded_build_files_are_left_alone() {
+    let dir = workspace(&[("Sources/App", APP), ("Sources/Old", OLD)]);
+    let root = dir.path().to_str().unwrap();
+    fs::write(dir.path().join(DEPS_UPDATE_FILE_NAME), MANIFEST).unwrap();
+    fs::write(
+        dir.path().join("umbra-fix.toml"),
+        "exclude_patterns = [\"Sources/App/**\"]\n",
+    )
+    .unwrap();
+
+    let output = umbra_fix(&["update-deps", "--root", root]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let app = fs::read_to_string(dir.path().join("Sources/App/BUILD.bazel")).unwrap();
+    assert_eq!(app, APP);
+    let old = fs::read_to_string(dir.path().join("Sources/Old/BUILD.bazel")).unwrap();
+    assert!(old.contains("deps = [\"//Sources/New:New\"]"), "{}", old);
+}
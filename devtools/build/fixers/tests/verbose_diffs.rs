@@ -0,0 +1,91 @@
+use termcolor::Buffer;
+use umbra_build_fixer::patch::write_colored_diff;
+use umbra_build_fixer::{fix_build_file, BuildIssue, RunMode};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+const DIFF: &str = "--- a/BUILD.bazel
++++ b/BUILD.bazel
+@@ -1,2 +1,2 @@
+ load(\"@rules_swift//swift:swift.bzl\", \"swift_library\")
+-umbra_swift_library(
++swift_library(
+";
+
+#[test]
+fn each_applied_fix_gets_its_own_diff() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let mut config = test_config(dir.path());
+    config.mode = RunMode::DryRun;
+    config.verbose_diffs = true;
+
+    let report = fix_build_file(&dir.path().join("Sources/Core/BUILD.bazel"), &config).unwrap();
+
+    assert_eq!(report.fix_diffs.len(), report.findings.len());
+    let (finding, diff) = &report.fix_diffs[0];
+    assert_eq!(finding.issue, BuildIssue::CustomLibraryRule);
+    // Scoped to the changed lines with 2 lines of context
+    assert!(diff.contains("@@ -1,5 +1,5 @@"), "{}", diff);
+    assert!(diff.contains("+swift_library("), "{}", diff);
+    assert!(!diff.contains("exports"), "{}", diff);
+}
+
+#[test]
+fn fix_diffs_are_only_computed_with_verbose_diffs() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let mut config = test_config(dir.path());
+    config.mode = RunMode::DryRun;
+
+    let report = fix_build_file(&dir.path().join("Sources/Core/BUILD.bazel"), &config).unwrap();
+
+    assert!(report.modified);
+    assert!(report.fix_diffs.is_empty());
+}
+
+#[test]
+fn colored_diff_marks_hunks_and_changed_lines() {
+    let mut plain = Buffer::no_color();
+    write_colored_diff(&mut plain, DIFF).unwrap();
+    assert_eq!(String::from_utf8(plain.into_inner()).unwrap(), DIFF);
+
+    let mut colored = Buffer::ansi();
+    write_colored_diff(&mut colored, DIFF).unwrap();
+    let colored = String::from_utf8(colored.into_inner()).unwrap();
+    assert!(colored.contains("\x1b[36m@@ -1,2 +1,2 @@"), "{:?}", colored);
+    assert!(
+        colored.contains("\x1b[31m-umbra_swift_library("),
+        "{:?}",
+        colored
+    );
+    assert!(colored.contains("\x1b[32m+swift_library("), "{:?}", colored);
+}
+
+#[test]
+fn verbose_diffs_prints_hunks_without_color_in_never_mode() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&[
+        "--root",
+        root,
+        "--dry-run",
+        "--no-cache",
+        "--verbose-diffs",
+        "--color",
+        "never",
+    ]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("  fixed [CustomLibraryRule]"), "{}", stdout);
+    assert!(
+        stdout.contains("--- a/Sources/Core/BUILD.bazel"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("@@ -1,5 +1,5 @@"), "{}", stdout);
+    assert!(stdout.contains("@@ -4,6 +4,3 @@"), "{}", stdout);
+    assert!(!stdout.contains('\x1b'), "{}", stdout);
+}
@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::Path;
+
+use umbra_build_fixer::checks::version_catalog::{
+    check_version_catalog, fix_version_mismatch, VersionCatalog,
+};
+use umbra_build_fixer::{fix_build_file, BuildIssue, Config, WorkspaceIssue};
+
+use crate::common::{test_config, umbra_fix};
+
+const WORKSPACE: &str = include_str!("fixtures/version_catalog/WORKSPACE");
+const VERSIONS: &str = include_str!("fixtures/version_catalog/versions.bzl");
+
+fn swift_log_mismatch() -> BuildIssue {
+    BuildIssue::Workspace(WorkspaceIssue::VersionMismatch {
+        repository: "swift_log".to_string(),
+        version: "1.5.3".to_string(),
+        expected: "1.5.4".to_string(),
+    })
+}
+
+// A workspace with the fixture WORKSPACE, and its catalog at `versions_file`
+fn workspace_with_catalog(versions_file: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("WORKSPACE"), WORKSPACE).unwrap();
+    let versions_file = dir.path().join(versions_file);
+    fs::create_dir_all(versions_file.parent().unwrap()).unwrap();
+    fs::write(versions_file, VERSIONS).unwrap();
+    dir
+}
+
+#[test]
+fn catalog_is_read_from_the_versions_dict() {
+    let catalog = VersionCatalog::parse(VERSIONS).unwrap();
+
+    assert_eq!(catalog.version("rules_swift"), Some("2.1.1"));
+    assert_eq!(catalog.version("swift_log"), Some("1.5.4"));
+    assert_eq!(catalog.version("rules_apple"), None);
+    assert!(VersionCatalog::parse("SWIFT_LOG_VERSION = \"1.5.4\"\n").is_none());
+}
+
+#[test]
+fn only_the_mismatching_archive_is_flagged() {
+    let catalog = VersionCatalog::parse(VERSIONS).unwrap();
+
+    let issues = check_version_catalog(WORKSPACE, &catalog);
+
+    let flagged: Vec<&BuildIssue> = issues.iter().map(|(issue, _)| issue).collect();
+    assert_eq!(flagged, [&swift_log_mismatch()]);
+    assert!(issues[0].1.contains("sha256"), "{}", issues[0].1);
+}
+
+#[test]
+fn fix_updates_the_url_and_strip_prefix() {
+    let fixed = fix_version_mismatch(WORKSPACE, "swift_log", "1.5.3", "1.5.4");
+
+    assert_eq!(
+        fixed,
+        WORKSPACE
+            .replace("\"swift-log-1.5.3\"", "\"swift-log-1.5.4\"")
+            .replace("archive/1.5.3.tar.gz", "archive/1.5.4.tar.gz")
+    );
+}
+
+#[test]
+fn default_catalog_is_third_party_versions_bzl() {
+    let dir = workspace_with_catalog("third_party/versions.bzl");
+    let path = dir.path().join("WORKSPACE");
+
+    let config = Config::load(dir.path()).unwrap();
+    let report = fix_build_file(&path, &config).unwrap();
+
+    let issues: Vec<&BuildIssue> = report.findings.iter().map(|f| &f.issue).collect();
+    assert_eq!(issues, [&swift_log_mismatch()]);
+    let fixed = fs::read_to_string(&path).unwrap();
+    assert!(fixed.contains("archive/1.5.4.tar.gz"), "{}", fixed);
+}
+
+#[test]
+fn versions_file_names_another_catalog() {
+    let dir = workspace_with_catalog("deps/versions.bzl");
+    let root = dir.path().to_str().unwrap();
+    let versions_file = dir.path().join("deps/versions.bzl");
+
+    let output = umbra_fix(&[
+        "--check",
+        "--versions-file",
+        versions_file.to_str().unwrap(),
+        "--root",
+        root,
+    ]);
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[VersionMismatch]"), "{}", stdout);
+    // Not read from the default location
+    let config = test_config(dir.path());
+    assert!(config.version_catalog.is_empty());
+}
+
+#[test]
+fn catalog_without_versions_dict_is_an_error() {
+    let dir = workspace_with_catalog("third_party/versions.bzl");
+    fs::write(
+        dir.path().join("third_party/versions.bzl"),
+        "SWIFT_LOG_VERSION = \"1.5.4\"\n",
+    )
+    .unwrap();
+
+    let err = Config::load(dir.path()).unwrap_err();
+
+    assert!(err.to_string().contains("no VERSIONS dict"), "{}", err);
+    assert!(VersionCatalog::load(Path::new("missing/versions.bzl")).is_err());
+}
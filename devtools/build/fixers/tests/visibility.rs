@@ -0,0 +1,144 @@
+use umbra_build_fixer::checks::analyze_target_graph;
+use umbra_build_fixer::checks::visibility::{check_visibility_leaks, visibility_allows};
+use umbra_build_fixer::label_resolver::AbsoluteLabel;
+use umbra_build_fixer::target_graph::build_target_graph;
+use umbra_build_fixer::{find_build_files, BuildIssue, Config};
+
+use crate::common::{umbra_fix, workspace};
+
+const CORE: &str = include_str!("fixtures/visibility/core.BUILD");
+const APP: &str = include_str!("fixtures/visibility/app.BUILD");
+
+fn two_packages() -> tempfile::TempDir {
+    workspace(&[("Sources/Core", CORE), ("Apps/App", APP)])
+}
+
+fn leak(dependency: &str) -> BuildIssue {
+    BuildIssue::PrivateVisibilityLeak {
+        target: "App".to_string(),
+        dependency: dependency.to_string(),
+    }
+}
+
+fn vis(labels: &[&str]) -> Vec<String> {
+    labels.iter().map(|label| label.to_string()).collect()
+}
+
+#[test]
+fn visibility_allows_matching_packages() {
+    assert!(visibility_allows(
+        &vis(&["//visibility:public"]),
+        "Apps/App"
+    ));
+    assert!(!visibility_allows(
+        &vis(&["//visibility:private"]),
+        "Apps/App"
+    ));
+    assert!(visibility_allows(&vis(&["//Apps/App:__pkg__"]), "Apps/App"));
+    assert!(!visibility_allows(&vis(&["//Apps:__pkg__"]), "Apps/App"));
+    assert!(visibility_allows(
+        &vis(&["//Apps:__subpackages__"]),
+        "Apps/App"
+    ));
+    assert!(visibility_allows(
+        &vis(&["//Apps:__subpackages__"]),
+        "//Apps"
+    ));
+    assert!(!visibility_allows(
+        &vis(&["//Apps:__subpackages__"]),
+        "AppsExtra"
+    ));
+    assert!(visibility_allows(&vis(&["//:__subpackages__"]), "Apps/App"));
+    // Package groups can't be expanded, so they are given the benefit of the doubt
+    assert!(visibility_allows(
+        &vis(&["//Sources/Core:friends"]),
+        "Apps/App"
+    ));
+    assert!(!visibility_allows(&[], "Apps/App"));
+}
+
+#[test]
+fn deps_on_targets_hidden_from_the_package_are_flagged() {
+    let dir = two_packages();
+    let build_files = find_build_files(&Config::new(dir.path())).unwrap();
+    let (graph, _) = build_target_graph(dir.path(), &build_files);
+
+    let issues = check_visibility_leaks(&graph);
+
+    // Public and Shared (//Apps/...) are visible to Apps/App; CoreTests is in
+    // the same package as what it depends on
+    let flagged: Vec<(String, &BuildIssue)> = issues
+        .iter()
+        .map(|(label, issue, _)| (label.to_string(), issue))
+        .collect();
+    assert_eq!(
+        flagged,
+        [
+            ("//Apps/App:App".to_string(), &leak("//Sources/Core:Core")),
+            (
+                "//Apps/App:App".to_string(),
+                &leak("//Sources/Core:Internal")
+            ),
+        ]
+    );
+    assert!(
+        issues[0]
+            .2
+            .contains("only visible to //Sources/Core:__pkg__"),
+        "{}",
+        issues[0].2
+    );
+    // Without its own visibility, Internal gets the package default
+    assert!(
+        issues[1].2.contains("only visible to //visibility:private"),
+        "{}",
+        issues[1].2
+    );
+}
+
+#[test]
+fn visibility_defaults_to_private() {
+    let dir = workspace(&[("Sources/Core", "swift_library(\n    name = \"Core\",\n)\n")]);
+    let build_files = find_build_files(&Config::new(dir.path())).unwrap();
+    let (graph, _) = build_target_graph(dir.path(), &build_files);
+    let core = AbsoluteLabel {
+        repository: None,
+        package: "Sources/Core".to_string(),
+        target: "Core".to_string(),
+    };
+
+    assert_eq!(
+        graph.visibility(&core),
+        Some(vis(&["//visibility:private"]))
+    );
+}
+
+#[test]
+fn findings_are_keyed_by_the_depending_package() {
+    let dir = two_packages();
+    let build_files = find_build_files(&Config::new(dir.path())).unwrap();
+    let (graph, _) = build_target_graph(dir.path(), &build_files);
+
+    let findings = analyze_target_graph(&graph);
+
+    assert_eq!(findings.keys().collect::<Vec<_>>(), ["Apps/App"]);
+}
+
+#[test]
+fn check_mode_reports_the_leaks() {
+    let dir = two_packages();
+    let root = dir.path().to_str().unwrap();
+
+    let output = umbra_fix(&["--check", "--root", root]);
+
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout
+            .matches("needs manual fix: [PrivateVisibilityLeak]")
+            .count(),
+        2,
+        "{}",
+        stdout
+    );
+}
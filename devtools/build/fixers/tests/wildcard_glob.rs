@@ -0,0 +1,99 @@
+use std::fs;
+
+use umbra_build_fixer::checks::globs::{check_wildcard_globs, fix_wildcard_glob};
+use umbra_build_fixer::{fix_build_file, BuildIssue};
+
+use crate::common::{test_config, workspace, write_build_file};
+
+const WILDCARD_GLOB: &str = include_str!("fixtures/wildcard_glob.BUILD");
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+fn wildcard_issue(subpackage: &str) -> BuildIssue {
+    BuildIssue::WildcardGlob {
+        pattern: "**/*.swift".to_string(),
+        subpackage: subpackage.to_string(),
+    }
+}
+
+#[test]
+fn nested_package_is_excluded_from_recursive_glob() {
+    let dir = workspace(&[
+        ("Sources/Core", WILDCARD_GLOB),
+        ("Sources/Core/Internal", CLEAN),
+    ]);
+    let path = dir.path().join("Sources/Core/BUILD.bazel");
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    let issues: Vec<_> = report.findings.iter().map(|f| &f.issue).collect();
+    assert_eq!(issues, [&wildcard_issue("Internal")]);
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(
+        content.contains(
+            "    srcs = glob(\n        [\"**/*.swift\"],\n        exclude = [\"Internal/**\"],\n        allow_empty = True,\n    ),\n"
+        ),
+        "{}",
+        content
+    );
+}
+
+#[test]
+fn packages_inside_nested_packages_are_not_listed() {
+    let dir = workspace(&[
+        ("Sources/Core", WILDCARD_GLOB),
+        ("Sources/Core/Internal", CLEAN),
+        ("Sources/Core/Internal/Detail", CLEAN),
+        ("Sources/Core/Plugins/Metal", CLEAN),
+    ]);
+
+    let issues: Vec<_> = check_wildcard_globs(WILDCARD_GLOB, &dir.path().join("Sources/Core"))
+        .into_iter()
+        .map(|(issue, _)| issue)
+        .collect();
+
+    assert_eq!(
+        issues,
+        [wildcard_issue("Internal"), wildcard_issue("Plugins/Metal")]
+    );
+}
+
+#[test]
+fn glob_without_nested_packages_is_clean() {
+    let dir = workspace(&[("Sources/Core", WILDCARD_GLOB)]);
+    fs::create_dir_all(dir.path().join("Sources/Core/Internal")).unwrap();
+
+    assert!(check_wildcard_globs(WILDCARD_GLOB, &dir.path().join("Sources/Core")).is_empty());
+}
+
+#[test]
+fn already_excluded_package_is_not_flagged() {
+    let dir = tempfile::tempdir().unwrap();
+    write_build_file(dir.path(), "Internal", CLEAN);
+    let content = "srcs = glob([\"**/*.swift\"], exclude = [\"Internal/**\"])\n";
+
+    assert!(check_wildcard_globs(content, dir.path()).is_empty());
+}
+
+#[test]
+fn exclude_is_appended_to_existing_list() {
+    let inline = "srcs = glob([\"**/*.swift\"], exclude = [\"Tests/**\"])\n";
+    assert_eq!(
+        fix_wildcard_glob(inline, "**/*.swift", "Internal"),
+        "srcs = glob([\"**/*.swift\"], exclude = [\"Tests/**\", \"Internal/**\"])\n"
+    );
+
+    let multiline = "srcs = glob(\n    [\"**/*.swift\"],\n    exclude = [\n        \"Tests/**\"  # fixtures\n    ],\n)\n";
+    assert_eq!(
+        fix_wildcard_glob(multiline, "**/*.swift", "Internal"),
+        "srcs = glob(\n    [\"**/*.swift\"],\n    exclude = [\n        \"Tests/**\",  # fixtures\n        \"Internal/**\",\n    ],\n)\n"
+    );
+}
+
+#[test]
+fn only_globs_with_the_pattern_are_changed() {
+    let content = "srcs = glob([\"*.swift\"]) + glob([\"**/*.swift\"])\n";
+    assert_eq!(
+        fix_wildcard_glob(content, "**/*.swift", "Internal"),
+        "srcs = glob([\"*.swift\"]) + glob([\"**/*.swift\"], exclude = [\"Internal/**\"])\n"
+    );
+}
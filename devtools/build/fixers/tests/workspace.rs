@@ -0,0 +1,364 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+use umbra_build_fixer::checks::workspace::{check_workspace_file, fix_missing_load};
+use umbra_build_fixer::{
+    find_build_files, find_workspace_files, fix_build_file, BuildIssue, Config, RunMode,
+    WorkspaceIssue,
+};
+
+use crate::common::{test_config, umbra_fix, workspace};
+
+const INPUT: &str = include_str!("fixtures/workspace/input.WORKSPACE");
+const EXPECTED: &str = include_str!("fixtures/workspace/expected.WORKSPACE");
+const CLEAN: &str = include_str!("fixtures/clean.BUILD");
+
+fn workspace_issues(content: &str, config: &Config) -> Vec<WorkspaceIssue> {
+    check_workspace_file(content, config)
+        .into_iter()
+        .map(|(issue, _)| match issue {
+            BuildIssue::Workspace(issue) => issue,
+            other => panic!("unexpected issue {}", other),
+        })
+        .collect()
+}
+
+// Serve `body` over HTTP on a local port and return its URL. Every request
+// is answered, as verifying the fixes downloads the archive again.
+fn serve(body: &'static [u8]) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/archive.tar.gz", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for mut stream in listener.incoming().map_while(Result::ok) {
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request);
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(header.as_bytes()).unwrap();
+            stream.write_all(body).unwrap();
+        }
+    });
+    url
+}
+
+// Like `serve`, also counting the requests it answers
+fn serve_counted(body: &'static [u8]) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/archive.tar.gz", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&requests);
+    thread::spawn(move || {
+        for mut stream in listener.incoming().map_while(Result::ok) {
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request);
+            counter.fetch_add(1, Ordering::SeqCst);
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(header.as_bytes()).unwrap();
+            stream.write_all(body).unwrap();
+        }
+    });
+    (url, requests)
+}
+
+#[test]
+fn finds_workspace_files() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    fs::write(dir.path().join("WORKSPACE"), INPUT).unwrap();
+    fs::create_dir_all(dir.path().join("Examples/App")).unwrap();
+    fs::write(dir.path().join("Examples/App/WORKSPACE.bazel"), "").unwrap();
+
+    let found = find_workspace_files(dir.path()).unwrap();
+    assert_eq!(
+        found,
+        [
+            dir.path().join("Examples/App/WORKSPACE.bazel"),
+            dir.path().join("WORKSPACE"),
+        ]
+    );
+
+    let all = find_build_files(&test_config(dir.path())).unwrap();
+    assert_eq!(all.len(), 3);
+}
+
+#[test]
+fn detects_repository_rule_issues() {
+    let issues = workspace_issues(INPUT, &Config::default());
+
+    assert_eq!(
+        issues,
+        [
+            WorkspaceIssue::MissingLoad {
+                rule: "http_archive".to_string(),
+                bzl: "@bazel_tools//tools/build_defs/repo:http.bzl".to_string(),
+            },
+            WorkspaceIssue::OutdatedRepositoryRule {
+                rule: "new_http_archive".to_string(),
+                replacement: "http_archive".to_string(),
+            },
+            WorkspaceIssue::MissingSha256 {
+                repository: "swift_log".to_string(),
+                url: "https://github.com/apple/swift-log/archive/1.5.4.tar.gz".to_string(),
+                sha256: None,
+            },
+            WorkspaceIssue::IncorrectHttpArchive {
+                repository: "mirrored".to_string(),
+            },
+            WorkspaceIssue::MissingCommit {
+                repository: "crypto_swift".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn fixes_workspace_to_golden_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("WORKSPACE");
+    fs::write(&path, INPUT).unwrap();
+
+    let report = fix_build_file(&path, &test_config(dir.path())).unwrap();
+
+    assert!(report.modified);
+    assert_eq!(fs::read_to_string(&path).unwrap(), EXPECTED);
+    // Without --network the sha256 (and the other manual issues) remain
+    let manual: Vec<_> = report
+        .findings
+        .iter()
+        .filter(|f| !f.issue.is_fixable())
+        .map(|f| f.issue.name())
+        .collect();
+    assert_eq!(
+        manual,
+        ["MissingSha256", "IncorrectHttpArchive", "MissingCommit"]
+    );
+}
+
+#[test]
+fn network_fills_in_missing_sha256() {
+    let url = serve(b"archive contents");
+    let content = format!(
+        "load(\"@bazel_tools//tools/build_defs/repo:http.bzl\", \"http_archive\")\n\n\
+         http_archive(\n    name = \"remote\",\n    urls = [\"{}\"],\n)\n",
+        url
+    );
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("WORKSPACE.bazel");
+    fs::write(&path, &content).unwrap();
+    let mut config = test_config(dir.path());
+    config.network = true;
+
+    fix_build_file(&path, &config).unwrap();
+
+    let sha256 = format!("{:x}", Sha256::digest(b"archive contents"));
+    let expected = content.replace(
+        "    name = \"remote\",\n",
+        &format!("    name = \"remote\",\n    sha256 = \"{}\",\n", sha256),
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+}
+
+#[test]
+fn network_only_downloads_when_fixing() {
+    let (url, requests) = serve_counted(b"archive contents");
+    let content = format!(
+        "load(\"@bazel_tools//tools/build_defs/repo:http.bzl\", \"http_archive\")\n\n\
+         http_archive(\n    name = \"remote\",\n    urls = [\"{}\"],\n)\n",
+        url
+    );
+    let config = Config {
+        network: true,
+        mode: RunMode::DryRun,
+        ..Config::default()
+    };
+
+    let issues = workspace_issues(&content, &config);
+
+    assert_eq!(
+        issues,
+        [WorkspaceIssue::MissingSha256 {
+            repository: "remote".to_string(),
+            url,
+            sha256: None,
+        }]
+    );
+    assert_eq!(requests.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn network_is_rejected_in_modes_that_only_report() {
+    let dir = workspace(&[("Sources/Core", CLEAN)]);
+    let root = dir.path().to_str().unwrap();
+
+    for mode in ["--check", "--dry-run", "--diff-only"] {
+        let output = umbra_fix(&["--network", mode, "--root", root]);
+        assert_eq!(output.status.code(), Some(2), "{}: {:?}", mode, output);
+    }
+}
+
+// A WORKSPACE pinning an archive at `url` to `sha256`
+fn pinned_archive(url: &str, sha256: &str) -> String {
+    format!(
+        "load(\"@bazel_tools//tools/build_defs/repo:http.bzl\", \"http_archive\")\n\n\
+         http_archive(\n    name = \"remote\",\n    sha256 = \"{}\",\n    url = \"{}\",\n)\n",
+        sha256, url
+    )
+}
+
+#[test]
+fn check_sha256_reports_mismatch() {
+    let url = serve(b"archive contents");
+    let content = pinned_archive(&url, &"0".repeat(64));
+    let config = Config {
+        check_sha256: true,
+        ..Config::default()
+    };
+
+    let issues = workspace_issues(&content, &config);
+
+    let computed = format!("{:x}", Sha256::digest(b"archive contents"));
+    assert_eq!(
+        issues,
+        [WorkspaceIssue::IncorrectSha256 {
+            repository: "remote".to_string(),
+            url,
+            sha256: "0".repeat(64),
+            computed,
+            update: false,
+        }]
+    );
+    // Replacing a checksum is only done with --fix-sha256
+    assert!(!issues[0].is_fixable());
+}
+
+#[test]
+fn check_sha256_accepts_matching_hash() {
+    let url = serve(b"archive contents");
+    let sha256 = format!("{:X}", Sha256::digest(b"archive contents"));
+    let config = Config {
+        check_sha256: true,
+        ..Config::default()
+    };
+
+    assert!(workspace_issues(&pinned_archive(&url, &sha256), &config).is_empty());
+}
+
+#[test]
+fn each_archive_is_downloaded_once_per_run() {
+    let (url, requests) = serve_counted(b"archive contents");
+    let content = pinned_archive(&url, &"0".repeat(64));
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("WORKSPACE.bazel");
+    fs::write(&path, &content).unwrap();
+    let mut config = test_config(dir.path());
+    config.check_sha256 = true;
+    config.fix_sha256 = true;
+
+    // Analysis, the fix and its verification all need the hash
+    fix_build_file(&path, &config).unwrap();
+
+    let sha256 = format!("{:x}", Sha256::digest(b"archive contents"));
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        pinned_archive(&url, &sha256)
+    );
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn fix_sha256_replaces_wrong_hash() {
+    let url = serve(b"archive contents");
+    let content = pinned_archive(&url, &"0".repeat(64));
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("WORKSPACE.bazel");
+    fs::write(&path, &content).unwrap();
+    let mut config = test_config(dir.path());
+    config.check_sha256 = true;
+    config.fix_sha256 = true;
+
+    fix_build_file(&path, &config).unwrap();
+
+    let sha256 = format!("{:x}", Sha256::digest(b"archive contents"));
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        pinned_archive(&url, &sha256)
+    );
+}
+
+#[test]
+fn downloads_give_up_after_network_timeout() {
+    // Connections are queued but never answered
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/archive.tar.gz", listener.local_addr().unwrap());
+    let config = Config {
+        check_sha256: true,
+        network_timeout_secs: 1,
+        ..Config::default()
+    };
+
+    let started = Instant::now();
+    let issues = workspace_issues(&pinned_archive(&url, &"0".repeat(64)), &config);
+
+    assert!(started.elapsed() < Duration::from_secs(30));
+    assert_eq!(issues.len(), 1, "{:?}", issues);
+    assert!(matches!(
+        &issues[0],
+        WorkspaceIssue::UnverifiableSha256 { repository, url: failed, error }
+            if repository == "remote" && failed == &url && !error.is_empty()
+    ));
+    assert!(!issues[0].is_fixable());
+}
+
+#[test]
+fn unverifiable_sha256_can_be_allowed() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/archive.tar.gz", listener.local_addr().unwrap());
+    let config = Config {
+        check_sha256: true,
+        allow_unverifiable_sha256: true,
+        network_timeout_secs: 1,
+        ..Config::default()
+    };
+
+    let issues = workspace_issues(&pinned_archive(&url, &"0".repeat(64)), &config);
+
+    assert!(issues.is_empty(), "{:?}", issues);
+}
+
+#[test]
+fn missing_load_is_added_to_existing_load() {
+    let content = "load(\"@bazel_tools//tools/build_defs/repo:http.bzl\", \"http_file\")\n";
+
+    assert_eq!(
+        fix_missing_load(
+            content,
+            "http_archive",
+            "@bazel_tools//tools/build_defs/repo:http.bzl"
+        ),
+        "load(\"@bazel_tools//tools/build_defs/repo:http.bzl\", \"http_file\", \"http_archive\")\n"
+    );
+}
+
+#[test]
+fn workspace_issues_serialize_with_their_own_name() {
+    let issue = BuildIssue::Workspace(WorkspaceIssue::MissingCommit {
+        repository: "crypto_swift".to_string(),
+    });
+
+    let value = serde_json::to_value(&issue).unwrap();
+
+    assert_eq!(
+        value,
+        serde_json::json!({ "name": "MissingCommit", "repository": "crypto_swift" })
+    );
+}
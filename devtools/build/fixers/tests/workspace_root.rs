@@ -0,0 +1,66 @@
+use std::fs;
+use std::process::Command;
+
+use umbra_build_fixer::undo::backup_path;
+use umbra_build_fixer::workspace::find_workspace_root;
+
+use crate::common::workspace;
+
+const DIRTY: &str = include_str!("fixtures/dirty.BUILD");
+
+#[test]
+fn workspace_root_is_found_above_the_start() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("WORKSPACE"), "").unwrap();
+    let start = dir.path().join("Sources/Core/Internal");
+    fs::create_dir_all(&start).unwrap();
+
+    assert_eq!(find_workspace_root(&start), Some(dir.path().to_path_buf()));
+}
+
+#[test]
+fn nearest_marker_wins() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("WORKSPACE.bazel"), "").unwrap();
+    let nested = dir.path().join("third_party/module");
+    fs::create_dir_all(nested.join("Sources")).unwrap();
+    fs::write(nested.join("MODULE.bazel"), "").unwrap();
+
+    assert_eq!(
+        find_workspace_root(&nested.join("Sources")),
+        Some(nested.clone())
+    );
+}
+
+#[test]
+fn root_defaults_to_the_enclosing_workspace() {
+    let dir = workspace(&[("Sources/Core", DIRTY)]);
+    fs::write(dir.path().join("MODULE.bazel"), "").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_umbra-fix"))
+        .args(["--check", "--no-cache"])
+        .current_dir(dir.path().join("Sources/Core"))
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Sources/Core/BUILD.bazel"), "{}", stdout);
+}
+
+#[test]
+fn clean_defaults_to_the_enclosing_workspace() {
+    let dir = workspace(&[("Sources/Core", DIRTY), ("Sources/Errors", DIRTY)]);
+    fs::write(dir.path().join("MODULE.bazel"), "").unwrap();
+    let backup = backup_path(&dir.path().join("Sources/Errors/BUILD.bazel"));
+    fs::write(&backup, DIRTY).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_umbra-fix"))
+        .args(["clean", "--older-than", "0s"])
+        .current_dir(dir.path().join("Sources/Core"))
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(!backup.exists());
+}